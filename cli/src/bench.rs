@@ -0,0 +1,129 @@
+//! `wasiw --bench`: times loading, graph building, layout and recommendations on a dataset, or
+//! on synthetic ratings when none is given, and prints the timings as JSON so runs can be
+//! compared across commits.
+
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use serde::Serialize;
+use wasiw_core::synthetic::{self, SyntheticOptions};
+use wasiw_core::{
+    build_graph_with_progress, recommend, BuildProgress, BuildStage, MergeStrategy, NodeType,
+    Viewport,
+};
+
+/// Recommendations asked for per user.
+const RECOMMENDATIONS: usize = 20;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Report {
+    /// The dataset file, or `synthetic`.
+    source: String,
+    users: usize,
+    ratings: usize,
+    nodes: usize,
+    edges: usize,
+    recommended_users: usize,
+    phases: Vec<Phase>,
+    total_ms: f64,
+}
+
+#[derive(Serialize)]
+struct Phase {
+    name: &'static str,
+    ms: f64,
+}
+
+/// Milliseconds since `start`, to the microsecond.
+fn since(start: Instant) -> f64 {
+    (start.elapsed().as_secs_f64() * 1e6).round() / 1000.0
+}
+
+/// A stage's phase name, one word for scripts rather than the progress label.
+fn phase_name(stage: BuildStage) -> &'static str {
+    match stage {
+        BuildStage::Ratings => "ratings",
+        BuildStage::Similarity => "similarity",
+        BuildStage::Communities => "communities",
+        BuildStage::Layout => "layout",
+    }
+}
+
+pub fn run(
+    dataset: Option<&Path>,
+    duplicates: MergeStrategy,
+    synthetic: SyntheticOptions,
+    recommend_users: usize,
+) -> Result<(), String> {
+    let started = Instant::now();
+    let mut phases = Vec::new();
+
+    let dataset_started = Instant::now();
+    let (source, dataset) = match dataset {
+        Some(path) => {
+            let (dataset, _) = super::load(path, duplicates)?;
+            (path.display().to_string(), dataset)
+        }
+        None => ("synthetic".to_string(), synthetic::generate(synthetic)),
+    };
+    phases.push(Phase {
+        name: "load",
+        ms: since(dataset_started),
+    });
+    let users = dataset.users.len();
+    let ratings = dataset.users.iter().map(|user| user.ratings.len()).sum();
+
+    // Each stage runs from its first progress report to the next stage's.
+    let mut stage: Option<(BuildStage, Instant)> = None;
+    let graph = build_graph_with_progress(dataset, Viewport::default(), |progress| {
+        let BuildProgress { stage: next, .. } = progress;
+        if stage.is_some_and(|(current, _)| current == next) {
+            return;
+        }
+        if let Some((current, start)) = stage.replace((next, Instant::now())) {
+            phases.push(Phase {
+                name: phase_name(current),
+                ms: since(start),
+            });
+        }
+    });
+    if let Some((current, start)) = stage {
+        phases.push(Phase {
+            name: phase_name(current),
+            ms: since(start),
+        });
+    }
+
+    let recommend_started = Instant::now();
+    let user_nodes: Vec<usize> = graph
+        .nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| node.node_type == NodeType::User)
+        .map(|(index, _)| index)
+        .take(recommend_users)
+        .collect();
+    for &user in &user_nodes {
+        recommend::recommend(&graph, user, RECOMMENDATIONS);
+    }
+    phases.push(Phase {
+        name: "recommend",
+        ms: since(recommend_started),
+    });
+
+    let report = Report {
+        source,
+        users,
+        ratings,
+        nodes: graph.nodes.len(),
+        edges: graph.edges.len(),
+        recommended_users: user_nodes.len(),
+        phases,
+        total_ms: since(started),
+    };
+    let mut out = io::stdout().lock();
+    serde_json::to_writer_pretty(&mut out, &report).map_err(|err| err.to_string())?;
+    writeln!(out).map_err(|err| err.to_string())
+}
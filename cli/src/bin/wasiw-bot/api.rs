@@ -0,0 +1,110 @@
+//! A client for the `wasiw serve` HTTP API. Only the fields the bot shows are read.
+
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Recommendation {
+    pub anime_id: u32,
+    pub title: String,
+    pub score: f64,
+    pub contributions: Vec<Contribution>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Contribution {
+    pub title: String,
+    pub weighted_score: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarAnime {
+    pub title: String,
+    pub score: f64,
+    pub co_raters: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimeMatch {
+    pub anime_id: u32,
+    pub title: String,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    error: String,
+}
+
+pub struct Api {
+    client: Client,
+    /// e.g. `http://127.0.0.1:8787`, without a trailing slash.
+    base: String,
+}
+
+impl Api {
+    pub fn new(base: &str) -> Self {
+        Self {
+            client: Client::new(),
+            base: base.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub async fn recommendations(
+        &self,
+        user: &str,
+        top: usize,
+    ) -> Result<Vec<Recommendation>, String> {
+        self.get(
+            &format!("/recommendations/{user}"),
+            &[("top", top.to_string())],
+        )
+        .await
+    }
+
+    pub async fn similar(&self, anime_id: u32, top: usize) -> Result<Vec<SimilarAnime>, String> {
+        self.get(&format!("/similar/{anime_id}"), &[("top", top.to_string())])
+            .await
+    }
+
+    /// Anime whose title contains `title`, best match first.
+    pub async fn find_anime(&self, title: &str) -> Result<Vec<AnimeMatch>, String> {
+        self.get(
+            "/anime",
+            &[("q", title.to_string()), ("top", "1".to_string())],
+        )
+        .await
+    }
+
+    /// Answers the API's own `{"error": ...}` message for 4xx responses, so users see why.
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, String> {
+        let response = self
+            .client
+            .get(format!("{}{path}", self.base))
+            .query(query)
+            .send()
+            .await
+            .map_err(|err| format!("the recommendation server is unreachable: {err}"))?;
+        let status = response.status();
+        if status.is_client_error() {
+            return Err(response
+                .json::<ErrorBody>()
+                .await
+                .map_or_else(|_| status.to_string(), |body| body.error));
+        }
+        response
+            .error_for_status()
+            .map_err(|err| err.to_string())?
+            .json()
+            .await
+            .map_err(|err| format!("unexpected response from the server: {err}"))
+    }
+}
@@ -0,0 +1,75 @@
+//! Which dataset profile each Discord account is, kept in a small JSON file so `/link` only
+//! has to be run once.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serenity::all::UserId;
+
+pub struct Links {
+    path: PathBuf,
+    /// Discord user id to dataset user id.
+    users: Mutex<HashMap<u64, String>>,
+}
+
+impl Links {
+    /// Starts empty when the file doesn't exist yet.
+    pub fn load(path: PathBuf) -> Result<Self, String> {
+        let users = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|err| format!("{}: {err}", path.display()))?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(err) => return Err(format!("{}: {err}", path.display())),
+        };
+        Ok(Self {
+            path,
+            users: Mutex::new(users),
+        })
+    }
+
+    pub fn get(&self, discord: UserId) -> Option<String> {
+        self.lock().get(&discord.get()).cloned()
+    }
+
+    pub fn set(&self, discord: UserId, user: String) -> io::Result<()> {
+        let mut users = self.lock();
+        users.insert(discord.get(), user);
+        let json = serde_json::to_vec_pretty(&*users)?;
+        // Written aside and renamed, so a crash mid-write can't lose every link.
+        let staging = self.path.with_extension("json.tmp");
+        std::fs::write(&staging, json)?;
+        std::fs::rename(staging, &self.path)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u64, String>> {
+        self.users.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_survive_a_restart() {
+        let path = std::env::temp_dir().join(format!("wasiw-links-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let discord = UserId::new(80351110224678912);
+
+        let links = Links::load(path.clone()).unwrap();
+        assert_eq!(links.get(discord), None);
+        links.set(discord, "alice".to_string()).unwrap();
+        links.set(discord, "bob".to_string()).unwrap();
+        assert!(!path.with_extension("json.tmp").exists());
+
+        let reloaded = Links::load(path.clone()).unwrap();
+        assert_eq!(reloaded.get(discord).as_deref(), Some("bob"));
+        assert_eq!(reloaded.get(UserId::new(1)), None);
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(Links::load(path.clone()).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,389 @@
+//! `wasiw-bot`: a Discord bot over `wasiw serve`, so a server's members can ask for
+//! recommendations without leaving the chat. Build with `--features bot`.
+//!
+//! - `/link <user id>` — remember which dataset profile is yours.
+//! - `/recommend [member]` — recommendations for you or another linked member.
+//! - `/similar <title>` — the anime most similar to another.
+//! - `/groupwatch <member> [member…]` — a poll of the anime the group would most enjoy
+//!   together, voted on with reactions.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use serenity::all::{
+    Client, CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption,
+    EditInteractionResponse, EventHandler, GatewayIntents, Interaction, Mentionable, ReactionType,
+    Ready, ResolvedValue, UserId,
+};
+use serenity::async_trait;
+
+mod api;
+mod links;
+
+use api::{Api, Recommendation};
+use links::Links;
+
+/// Entries listed by `/recommend` and `/similar` unless `count` is given.
+const DEFAULT_COUNT: i64 = 5;
+/// Upper bound on `count`, so replies stay under Discord's message length limit.
+const MAX_COUNT: i64 = 15;
+/// Recommendations fetched per member when building a group-watch poll.
+const GROUP_CANDIDATES: usize = 100;
+/// Members a `/groupwatch` poll can include, besides whoever runs it.
+const GROUP_MEMBERS: usize = 5;
+/// Poll options, each voted on with its keycap reaction.
+const POLL_OPTIONS: [&str; 5] = ["1️⃣", "2️⃣", "3️⃣", "4️⃣", "5️⃣"];
+
+#[derive(Parser)]
+#[command(
+    name = "wasiw-bot",
+    version,
+    about = "Discord bot for What Anime Should I Watch"
+)]
+struct Args {
+    /// Bot token from the Discord developer portal.
+    #[arg(long, env = "DISCORD_TOKEN", hide_env_values = true)]
+    token: String,
+    /// Address of a running `wasiw serve`.
+    #[arg(long, env = "WASIW_API", default_value = "http://127.0.0.1:8787")]
+    api: String,
+    /// Where Discord accounts linked with `/link` are remembered.
+    #[arg(long, env = "WASIW_BOT_LINKS", default_value = "discord-links.json")]
+    links: PathBuf,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), String> {
+    let args = Args::parse();
+    let handler = Handler {
+        api: Api::new(&args.api),
+        links: Links::load(args.links)?,
+    };
+    // Slash commands need no privileged intents.
+    let mut client = Client::builder(&args.token, GatewayIntents::empty())
+        .event_handler(handler)
+        .await
+        .map_err(|err| err.to_string())?;
+    client.start().await.map_err(|err| err.to_string())
+}
+
+struct Handler {
+    api: Api,
+    links: Links,
+}
+
+#[async_trait]
+impl EventHandler for Handler {
+    async fn ready(&self, ctx: Context, ready: Ready) {
+        match serenity::all::Command::set_global_commands(&ctx.http, commands()).await {
+            Ok(_) => eprintln!("Connected as {}", ready.user.name),
+            Err(err) => eprintln!("Could not register commands: {err}"),
+        }
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        let Interaction::Command(command) = interaction else {
+            return;
+        };
+        // Only `/link` answers privately: it shows the caller's dataset id.
+        let deferred = if command.data.name == "link" {
+            command.defer_ephemeral(&ctx.http).await
+        } else {
+            command.defer(&ctx.http).await
+        };
+        if let Err(err) = deferred {
+            eprintln!("Could not acknowledge /{}: {err}", command.data.name);
+            return;
+        }
+        let reply = match command.data.name.as_str() {
+            "link" => self.link(&command).await,
+            "recommend" => self.recommend(&command).await,
+            "similar" => self.similar(&command).await,
+            "groupwatch" => self.group_watch(&command).await,
+            _ => Err("Unknown command.".to_string()),
+        };
+        let (content, poll_options) = match reply {
+            Ok(reply) => reply,
+            Err(message) => (format!("⚠️ {message}"), 0),
+        };
+        let message = match command
+            .edit_response(&ctx.http, EditInteractionResponse::new().content(content))
+            .await
+        {
+            Ok(message) => message,
+            Err(err) => {
+                eprintln!("Could not reply to /{}: {err}", command.data.name);
+                return;
+            }
+        };
+        for emoji in &POLL_OPTIONS[..poll_options] {
+            let reaction = ReactionType::Unicode(emoji.to_string());
+            if let Err(err) = message.react(&ctx.http, reaction).await {
+                eprintln!("Could not add poll reaction: {err}");
+                break;
+            }
+        }
+    }
+}
+
+/// A reply's text and how many poll reactions to add under it.
+type Reply = Result<(String, usize), String>;
+
+impl Handler {
+    async fn link(&self, command: &CommandInteraction) -> Reply {
+        let Some(user) = string_option(command, "user") else {
+            return Err("Give the user id from your dataset profile.".to_string());
+        };
+        // Rejects unknown ids and ambiguous prefixes before they're saved.
+        self.api.recommendations(&user, 1).await?;
+        self.links
+            .set(command.user.id, user.clone())
+            .map_err(|err| format!("Could not save the link: {err}"))?;
+        Ok((format!("Linked you to profile `{user}`."), 0))
+    }
+
+    async fn recommend(&self, command: &CommandInteraction) -> Reply {
+        let member = user_options(command).first().copied();
+        let discord = member.unwrap_or(command.user.id);
+        let user = self.profile(discord)?;
+        let count = count_option(command);
+        let recommendations = self.api.recommendations(&user, count).await?;
+        if recommendations.is_empty() {
+            return Ok((
+                format!("No recommendations for {} yet.", discord.mention()),
+                0,
+            ));
+        }
+        let mut content = format!("**Recommendations for {}**\n", discord.mention());
+        for (rank, recommendation) in recommendations.iter().enumerate() {
+            content.push_str(&format!(
+                "{}. **{}** ({:+.2}){}\n",
+                rank + 1,
+                recommendation.title,
+                recommendation.score,
+                because(recommendation)
+            ));
+        }
+        Ok((content, 0))
+    }
+
+    async fn similar(&self, command: &CommandInteraction) -> Reply {
+        let Some(title) = string_option(command, "title") else {
+            return Err("Give an anime title.".to_string());
+        };
+        let Some(anime) = self.api.find_anime(&title).await?.into_iter().next() else {
+            return Err(format!("No anime titled like “{title}”."));
+        };
+        let similar = self
+            .api
+            .similar(anime.anime_id, count_option(command))
+            .await?;
+        if similar.is_empty() {
+            return Ok((
+                format!("Nothing is rated much like **{}** yet.", anime.title),
+                0,
+            ));
+        }
+        let mut content = format!("**Similar to {}**\n", anime.title);
+        for (rank, item) in similar.iter().enumerate() {
+            content.push_str(&format!(
+                "{}. **{}** ({:+.2}, {} co-raters)\n",
+                rank + 1,
+                item.title,
+                item.score,
+                item.co_raters
+            ));
+        }
+        Ok((content, 0))
+    }
+
+    async fn group_watch(&self, command: &CommandInteraction) -> Reply {
+        let mut members = vec![command.user.id];
+        for member in user_options(command) {
+            if !members.contains(&member) {
+                members.push(member);
+            }
+        }
+        if members.len() < 2 {
+            return Err("Mention at least one other member.".to_string());
+        }
+        let mut lists = Vec::with_capacity(members.len());
+        for &member in &members {
+            let user = self.profile(member)?;
+            lists.push(self.api.recommendations(&user, GROUP_CANDIDATES).await?);
+        }
+        let picks = group_picks(&lists, POLL_OPTIONS.len());
+        if picks.is_empty() {
+            return Err("The group has no recommendations in common.".to_string());
+        }
+        let mentions: Vec<String> = members.iter().map(|id| id.mention().to_string()).collect();
+        let mut content = format!(
+            "**Group watch for {}** — vote with the reactions below.\n",
+            mentions.join(", ")
+        );
+        for (emoji, pick) in POLL_OPTIONS.iter().zip(&picks) {
+            content.push_str(&format!(
+                "{emoji} **{}** — recommended for {} of {} ({:+.2} on average)\n",
+                pick.title,
+                pick.members,
+                members.len(),
+                pick.mean_score
+            ));
+        }
+        Ok((content, picks.len()))
+    }
+
+    fn profile(&self, discord: UserId) -> Result<String, String> {
+        self.links.get(discord).ok_or_else(|| {
+            format!(
+                "{} hasn't linked a profile yet; they can run `/link` first.",
+                discord.mention()
+            )
+        })
+    }
+}
+
+/// “ — because you liked …” from the strongest positive contributions, or nothing.
+fn because(recommendation: &Recommendation) -> String {
+    let titles: Vec<&str> = recommendation
+        .contributions
+        .iter()
+        .filter(|contribution| contribution.weighted_score > 0.0)
+        .take(2)
+        .map(|contribution| contribution.title.as_str())
+        .collect();
+    if titles.is_empty() {
+        String::new()
+    } else {
+        format!(" — because of {}", titles.join(" and "))
+    }
+}
+
+struct GroupPick {
+    title: String,
+    /// Members this anime was recommended to.
+    members: usize,
+    mean_score: f64,
+}
+
+/// The anime recommended to the most members, then with the best mean score among them.
+fn group_picks(lists: &[Vec<Recommendation>], limit: usize) -> Vec<GroupPick> {
+    let mut picks: Vec<(u32, GroupPick)> = Vec::new();
+    for recommendation in lists.iter().flatten() {
+        match picks
+            .iter_mut()
+            .find(|(anime_id, _)| *anime_id == recommendation.anime_id)
+        {
+            Some((_, pick)) => {
+                pick.mean_score += recommendation.score;
+                pick.members += 1;
+            }
+            None => picks.push((
+                recommendation.anime_id,
+                GroupPick {
+                    title: recommendation.title.clone(),
+                    members: 1,
+                    mean_score: recommendation.score,
+                },
+            )),
+        }
+    }
+    let mut picks: Vec<GroupPick> = picks
+        .into_iter()
+        .map(|(_, mut pick)| {
+            pick.mean_score /= pick.members as f64;
+            pick
+        })
+        .filter(|pick| pick.mean_score > 0.0)
+        .collect();
+    picks.sort_by(|a, b| {
+        b.members
+            .cmp(&a.members)
+            .then(b.mean_score.total_cmp(&a.mean_score))
+    });
+    picks.truncate(limit);
+    picks
+}
+
+fn commands() -> Vec<CreateCommand> {
+    let count = || {
+        CreateCommandOption::new(CommandOptionType::Integer, "count", "How many to list")
+            .min_int_value(1)
+            .max_int_value(MAX_COUNT as u64)
+    };
+    let mut group_watch = CreateCommand::new("groupwatch")
+        .description("Start a poll of anime the group would enjoy together");
+    for index in 0..GROUP_MEMBERS {
+        group_watch = group_watch.add_option(
+            CreateCommandOption::new(
+                CommandOptionType::User,
+                format!("member{}", index + 1),
+                "Someone to watch with",
+            )
+            .required(index == 0),
+        );
+    }
+    vec![
+        CreateCommand::new("link")
+            .description("Link your Discord account to your dataset profile")
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "user",
+                    "Your user id in the dataset, or its first few characters",
+                )
+                .required(true),
+            ),
+        CreateCommand::new("recommend")
+            .description("Recommend anime for you or another member")
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::User,
+                "member",
+                "Defaults to you",
+            ))
+            .add_option(count()),
+        CreateCommand::new("similar")
+            .description("Anime similar to another")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::String, "title", "Anime title")
+                    .required(true),
+            )
+            .add_option(count()),
+        group_watch,
+    ]
+}
+
+fn string_option(command: &CommandInteraction, name: &str) -> Option<String> {
+    command.data.options().into_iter().find_map(|option| {
+        match (option.name == name, option.value) {
+            (true, ResolvedValue::String(value)) => Some(value.trim().to_string()),
+            _ => None,
+        }
+    })
+}
+
+fn count_option(command: &CommandInteraction) -> usize {
+    let count = command
+        .data
+        .options()
+        .into_iter()
+        .find_map(|option| match (option.name, option.value) {
+            ("count", ResolvedValue::Integer(value)) => Some(value),
+            _ => None,
+        })
+        .unwrap_or(DEFAULT_COUNT);
+    count.clamp(1, MAX_COUNT) as usize
+}
+
+/// The users given in the command's user options, in order.
+fn user_options(command: &CommandInteraction) -> Vec<UserId> {
+    command
+        .data
+        .options()
+        .into_iter()
+        .filter_map(|option| match option.value {
+            ResolvedValue::User(user, _) => Some(user.id),
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,443 @@
+//! `wasiw`: the recommendation engine without a window, for scripts and pipelines.
+//!
+//! Exit codes: 0 on success, 1 when `validate` finds problems, 2 when a command fails.
+//! `--bench` times the engine instead of running a command.
+
+use std::io::{self, BufWriter, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
+use wasiw_core::graph_export::{self, GraphFormat};
+use wasiw_core::health::{HealthReport, Severity};
+use wasiw_core::recommend;
+use wasiw_core::stats::Stats;
+use wasiw_core::synthetic::SyntheticOptions;
+use wasiw_core::tribes::{self, TribeReport};
+use wasiw_core::{
+    build_graph, compression, dataset, Dataset, MergeStrategy, QualityReport, Viewport,
+};
+
+mod bench;
+mod serve;
+mod tui;
+
+/// Explanation entries per direction in text output.
+const EXPLAINED_CONTRIBUTORS: usize = 3;
+
+#[derive(Parser)]
+#[command(
+    name = "wasiw",
+    version,
+    about = "What Anime Should I Watch from the command line",
+    arg_required_else_help = true,
+    args_conflicts_with_subcommands = true
+)]
+struct Cli {
+    /// How an anime rated more than once by the same user is resolved.
+    #[arg(long, global = true, value_enum, default_value_t = Duplicates::LatestWins)]
+    duplicates: Duplicates,
+    /// Time loading, graph building, layout and recommendations, and print the timings as JSON.
+    #[arg(long)]
+    bench: bool,
+    #[command(flatten)]
+    bench_options: BenchOptions,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(clap::Args)]
+struct BenchOptions {
+    /// Dataset to time; synthetic ratings are generated when omitted.
+    #[arg(long, requires = "bench")]
+    bench_dataset: Option<PathBuf>,
+    /// Users to generate without a dataset.
+    #[arg(long, requires = "bench", default_value_t = 5000)]
+    synthetic_users: usize,
+    /// Anime to generate without a dataset.
+    #[arg(long, requires = "bench", default_value_t = 2000)]
+    synthetic_anime: usize,
+    /// Users to ask recommendations for.
+    #[arg(long, requires = "bench", default_value_t = 100)]
+    recommend_users: usize,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Recommend anime for a user, best first.
+    Recommend {
+        #[command(flatten)]
+        dataset: DatasetArg,
+        /// User id, or an unambiguous prefix of one (as shown in `User 0123abcd` labels).
+        #[arg(short, long)]
+        user: String,
+        /// Number of recommendations.
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+        /// Print JSON instead of a ranked list.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print counts for the dataset and its graph.
+    Stats {
+        #[command(flatten)]
+        dataset: DatasetArg,
+        /// Print JSON instead of text.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Write the graph as GraphML, GEXF, DOT or the web app's graph JSON.
+    ExportGraph {
+        #[command(flatten)]
+        dataset: DatasetArg,
+        /// Defaults to the output file's extension, or GraphML.
+        #[arg(long, value_enum)]
+        format: Option<Format>,
+        /// File to write, compressed for `.gz` or `.zst`; standard output when omitted.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Group users into taste tribes and describe each by its signature shows, as Markdown.
+    Tribes {
+        #[command(flatten)]
+        dataset: DatasetArg,
+        /// Tribes to look for at most.
+        #[arg(long, default_value_t = tribes::DEFAULT_TRIBES)]
+        tribes: usize,
+        /// Print JSON instead of Markdown.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check a dataset's health: what would be dropped on load, and duplicate ids, mismatched
+    /// titles and copied users in what loads.
+    Validate {
+        /// Dataset file.
+        dataset: PathBuf,
+        /// Print the report as JSON instead of text.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Answer recommendation, similarity, graph and stats queries over HTTP.
+    Serve {
+        #[command(flatten)]
+        dataset: DatasetArg,
+        /// Address to listen on.
+        #[arg(long, default_value = "127.0.0.1:8787")]
+        addr: SocketAddr,
+    },
+    /// Browse stats, recommendations and similar anime in the terminal.
+    Tui {
+        #[command(flatten)]
+        dataset: DatasetArg,
+        /// Profile to open with, as for `recommend`.
+        #[arg(short, long)]
+        user: Option<String>,
+        /// Entries per recommendation and similar-anime list.
+        #[arg(long, default_value_t = 50)]
+        top: usize,
+    },
+}
+
+#[derive(clap::Args)]
+struct DatasetArg {
+    /// Dataset file: `anonymized-ratings.json`, optionally `.gz`/`.zst` compressed.
+    #[arg(short, long, env = "WASIW_DATASET")]
+    dataset: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Duplicates {
+    LatestWins,
+    Highest,
+    Average,
+}
+
+impl From<Duplicates> for MergeStrategy {
+    fn from(duplicates: Duplicates) -> Self {
+        match duplicates {
+            Duplicates::LatestWins => Self::LatestWins,
+            Duplicates::Highest => Self::Highest,
+            Duplicates::Average => Self::Average,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Format {
+    Graphml,
+    Gexf,
+    Dot,
+    Json,
+}
+
+impl From<Format> for GraphFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Graphml => Self::GraphMl,
+            Format::Gexf => Self::Gexf,
+            Format::Dot => Self::Dot,
+            Format::Json => Self::Json,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<ExitCode, String> {
+    let duplicates = MergeStrategy::from(cli.duplicates);
+    if cli.bench {
+        let options = cli.bench_options;
+        let synthetic = SyntheticOptions {
+            users: options.synthetic_users,
+            anime: options.synthetic_anime,
+            ..SyntheticOptions::default()
+        };
+        bench::run(
+            options.bench_dataset.as_deref(),
+            duplicates,
+            synthetic,
+            options.recommend_users,
+        )?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    let Some(command) = cli.command else {
+        return Err("a command or --bench is required; see --help".to_string());
+    };
+    match command {
+        Command::Recommend {
+            dataset,
+            user,
+            top,
+            json,
+        } => {
+            let (dataset, _) = load(&dataset.dataset, duplicates)?;
+            let graph = build_graph(dataset, Viewport::default());
+            let user = graph.find_user(&user).map_err(|err| err.to_string())?;
+            let recommendations = recommend::recommend(&graph, user, top);
+            let mut out = io::stdout().lock();
+            if json {
+                serde_json::to_writer_pretty(&mut out, &recommendations)
+                    .map_err(|err| err.to_string())?;
+                writeln!(out).map_err(|err| err.to_string())?;
+            } else {
+                for (rank, recommendation) in recommendations.iter().enumerate() {
+                    writeln!(
+                        out,
+                        "{:>3}. {:+7.2}  {} ({} supporting)\n       {}",
+                        rank + 1,
+                        recommendation.score,
+                        recommendation.title,
+                        recommendation.support_count,
+                        recommendation.explanation(EXPLAINED_CONTRIBUTORS)
+                    )
+                    .map_err(|err| err.to_string())?;
+                }
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Stats { dataset, json } => {
+            let (dataset, report) = load(&dataset.dataset, duplicates)?;
+            print_stats(dataset, &report, json).map_err(|err| err.to_string())?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::ExportGraph {
+            dataset,
+            format,
+            output,
+        } => {
+            let (dataset, _) = load(&dataset.dataset, duplicates)?;
+            let graph = build_graph(dataset, Viewport::default());
+            let format = format
+                .map(GraphFormat::from)
+                .or_else(|| output.as_deref().and_then(GraphFormat::from_path))
+                .unwrap_or(GraphFormat::GraphMl);
+            let result = match &output {
+                Some(path) => {
+                    compression::create(path, |writer| graph_export::write(writer, &graph, format))
+                }
+                None => {
+                    let mut out = BufWriter::new(io::stdout().lock());
+                    graph_export::write(&mut out, &graph, format).and_then(|()| out.flush())
+                }
+            };
+            result.map_err(|err| format!("could not write the graph: {err}"))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Tribes {
+            dataset,
+            tribes,
+            json,
+        } => {
+            let (dataset, _) = load(&dataset.dataset, duplicates)?;
+            let report = TribeReport::new(&dataset, tribes);
+            let mut out = io::stdout().lock();
+            if json {
+                serde_json::to_writer_pretty(&mut out, &report).map_err(|err| err.to_string())?;
+                writeln!(out).map_err(|err| err.to_string())?;
+            } else {
+                let markdown = report.to_markdown(|_, title| title.to_string());
+                write!(out, "{markdown}").map_err(|err| err.to_string())?;
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Validate { dataset, json } => {
+            let (dataset, report) = load(&dataset, duplicates)?;
+            let health = HealthReport::new(&dataset, &report);
+            print_health(&health, &report, json).map_err(|err| err.to_string())?;
+            Ok(if health.is_healthy() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::from(1)
+            })
+        }
+        Command::Serve { dataset, addr } => {
+            serve::run(dataset.dataset, duplicates, addr)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Tui { dataset, user, top } => {
+            let (dataset, _) = load(&dataset.dataset, duplicates)?;
+            tui::run(dataset, user.as_deref(), top)?;
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
+
+fn load(path: &Path, duplicates: MergeStrategy) -> Result<(Dataset, QualityReport), String> {
+    dataset::read(path, duplicates).map_err(|err| format!("{}: {err}", path.display()))
+}
+
+fn print_health(health: &HealthReport, report: &QualityReport, json: bool) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    if json {
+        serde_json::to_writer_pretty(&mut out, health)?;
+        return writeln!(out);
+    }
+    writeln!(out, "{} users, {} ratings.", health.users, health.ratings)?;
+    if health.is_healthy() {
+        return writeln!(out, "No problems found.");
+    }
+    if !report.is_clean() {
+        writeln!(out, "{}", report.summary())?;
+    }
+    for check in health.problems() {
+        let severity = match check.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        writeln!(out, "{severity}: {}: {}", check.kind.label(), check.count)?;
+        for example in &check.examples {
+            writeln!(out, "  {example}")?;
+        }
+        if !check.examples.is_empty() && check.count > check.examples.len() {
+            writeln!(out, "  … and {} more", check.count - check.examples.len())?;
+        }
+    }
+    Ok(())
+}
+
+fn print_stats(dataset: Dataset, report: &QualityReport, json: bool) -> io::Result<()> {
+    let (_, stats) = Stats::build(dataset, Viewport::default(), |_| {});
+    let mut out = io::stdout().lock();
+    if json {
+        let mut value = serde_json::to_value(&stats)?;
+        value["usersDropped"] = report.users_dropped.into();
+        value["ratingsDropped"] = report.ratings_dropped.into();
+        value["duplicatesResolved"] = report.duplicates_resolved.into();
+        serde_json::to_writer_pretty(&mut out, &value)?;
+        return writeln!(out);
+    }
+    writeln!(out, "Users             {}", stats.users)?;
+    writeln!(out, "Anime             {}", stats.anime)?;
+    writeln!(out, "Ratings           {}", stats.ratings)?;
+    writeln!(out, "Mean score        {:.2}", stats.mean_score)?;
+    writeln!(out, "Similarity edges  {}", stats.similarity_edges)?;
+    writeln!(out, "Communities       {}", stats.communities)?;
+    writeln!(out, "Components        {}", stats.components)?;
+    if !report.is_clean() {
+        writeln!(out, "{}", report.summary())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+    use wasiw_core::{Rating, UserRatings};
+
+    use super::*;
+
+    fn user(user_id: &str, ratings: &[(u32, f64)]) -> UserRatings {
+        UserRatings {
+            user_id: user_id.to_string(),
+            ratings: ratings
+                .iter()
+                .map(|&(anime_id, score)| Rating::new(anime_id, format!("Anime {anime_id}"), score))
+                .collect(),
+        }
+    }
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once("wasiw").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn commands_parse_and_export_the_graph_in_the_requested_format() {
+        Cli::command().debug_assert();
+        let cli = parse(&[
+            "recommend",
+            "-d",
+            "ratings.json",
+            "--user",
+            "0123",
+            "--top",
+            "5",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Recommend { ref user, top: 5, json: false, .. }) if user == "0123"
+        ));
+        assert!(parse(&["--bench-dataset", "ratings.json"]).is_err());
+        assert!(parse(&["stats", "-d", "ratings.json", "--duplicates", "highest"]).is_ok());
+
+        let dir = std::env::temp_dir().join(format!("wasiw-cli-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dataset = dir.join("ratings.json");
+        let ratings = Dataset::new(vec![
+            user("alice", &[(1, 9.0), (2, 4.0)]),
+            user("bob", &[(1, 8.0), (2, 3.0), (3, 7.0)]),
+        ]);
+        std::fs::write(&dataset, serde_json::to_vec(&ratings).unwrap()).unwrap();
+        let dataset = dataset.to_str().unwrap();
+
+        let output = dir.join("graph.gexf");
+        let cli = parse(&[
+            "export-graph",
+            "-d",
+            dataset,
+            "-o",
+            output.to_str().unwrap(),
+        ])
+        .unwrap();
+        assert_eq!(run(cli), Ok(ExitCode::SUCCESS));
+        let gexf = std::fs::read_to_string(&output).unwrap();
+        assert!(gexf.contains("<gexf"));
+        assert!(gexf.contains("Anime 3"));
+
+        let cli = parse(&["recommend", "-d", dataset, "-u", "nobody"]).unwrap();
+        assert!(run(cli).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
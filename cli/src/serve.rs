@@ -0,0 +1,525 @@
+//! `wasiw serve`: the engine behind a local HTTP API, for Discord bots, web frontends and
+//! anything else that would rather not load the dataset itself. The graph is built at startup
+//! and rebuilt in the background whenever the dataset file changes; requests keep reading the
+//! previous graph until the new one is ready.
+//!
+//! - `GET /recommendations/{user}?top=20` — ranked recommendations for a user id or prefix.
+//! - `GET /similar/{animeId}?top=20` — the anime most similar to another.
+//! - `GET /anime?q=<title>&top=20` — anime whose title contains `q`, best match first.
+//! - `GET /graph?format=json` — the graph as `json`, `graphml`, `gexf` or `dot`.
+//! - `GET /stats` — counts for the dataset and its graph.
+//! - `POST /reload` — rebuild now instead of waiting for the file to change.
+//! - `GET /events?user=&top=` — a WebSocket of [`Event`]s: build progress, fresh stats after
+//!   each rebuild and, when `user` is given, that user's updated recommendations.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::{broadcast, Mutex};
+use wasiw_core::graph_export::{self, GraphFormat};
+use wasiw_core::recommend::{self, Recommendation};
+use wasiw_core::stats::Stats;
+use wasiw_core::{BuildProgress, FindUserError, GraphModel, MergeStrategy, Viewport};
+
+use crate::{load, Format};
+
+/// Results per request when `top` isn't given.
+const DEFAULT_TOP: usize = 20;
+/// Upper bound on `top`, so one request can't ask for the whole catalogue.
+const MAX_TOP: usize = 500;
+/// How often the dataset file's modification time is checked.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+/// Events a slow WebSocket client may fall behind by before it misses some.
+const EVENT_BUFFER: usize = 256;
+
+/// A graph and its stats, replaced as a whole on every rebuild.
+struct Snapshot {
+    graph: GraphModel,
+    stats: Stats,
+}
+
+struct Engine {
+    dataset: PathBuf,
+    duplicates: MergeStrategy,
+    current: RwLock<Arc<Snapshot>>,
+    events: broadcast::Sender<Event>,
+    /// Held for the length of a rebuild, so changes during one don't start another.
+    rebuilding: Mutex<()>,
+    /// Set by every rebuild request; whoever holds `rebuilding` builds again while it's set,
+    /// so requests that arrive mid-build fold into one more build instead of queueing.
+    rerun: AtomicBool,
+}
+
+impl Engine {
+    fn snapshot(&self) -> Arc<Snapshot> {
+        Arc::clone(&self.current.read().unwrap_or_else(|err| err.into_inner()))
+    }
+
+    /// Loads `dataset` and builds the first graph.
+    fn start(dataset: PathBuf, duplicates: MergeStrategy) -> Result<Arc<Self>, String> {
+        let (loaded, _) = load(&dataset, duplicates)?;
+        let (graph, stats) = Stats::build(loaded, Viewport::default(), |_| {});
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+        Ok(Arc::new(Self {
+            dataset,
+            duplicates,
+            current: RwLock::new(Arc::new(Snapshot { graph, stats })),
+            events,
+            rebuilding: Mutex::new(()),
+            rerun: AtomicBool::new(false),
+        }))
+    }
+
+    /// Rebuilds the graph, or, when a rebuild is already under way, has it build once more
+    /// when it finishes; returns without waiting in that case.
+    async fn rebuild(self: Arc<Self>) {
+        self.rerun.store(true, Ordering::SeqCst);
+        loop {
+            let Ok(guard) = self.rebuilding.try_lock() else {
+                return;
+            };
+            while self.rerun.swap(false, Ordering::SeqCst) {
+                Arc::clone(&self).build().await;
+            }
+            drop(guard);
+            // A request between the last check and unlocking saw the lock still held.
+            if !self.rerun.load(Ordering::SeqCst) {
+                return;
+            }
+        }
+    }
+
+    /// Re-reads the dataset and rebuilds the graph off the async threads, streaming progress.
+    async fn build(self: Arc<Self>) {
+        let engine = Arc::clone(&self);
+        let built = tokio::task::spawn_blocking(move || {
+            let (dataset, _) = load(&engine.dataset, engine.duplicates)?;
+            let events = engine.events.clone();
+            Ok::<_, String>(Stats::build(dataset, Viewport::default(), |progress| {
+                let _ = events.send(Event::Progress(progress));
+            }))
+        })
+        .await
+        .unwrap_or_else(|err| Err(err.to_string()));
+        match built {
+            Ok((graph, stats)) => {
+                let snapshot = Arc::new(Snapshot { graph, stats });
+                *self.current.write().unwrap_or_else(|err| err.into_inner()) = snapshot;
+                let _ = self.events.send(Event::Ready {
+                    stats: self.snapshot().stats.clone(),
+                });
+            }
+            Err(message) => {
+                eprintln!("Rebuild failed: {message}");
+                let _ = self.events.send(Event::Error { message });
+            }
+        }
+    }
+}
+
+/// A message on the `/events` WebSocket, as JSON with a `type` field.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Event {
+    /// A rebuild is under way.
+    Progress(BuildProgress),
+    /// A new graph is being served.
+    Ready { stats: Stats },
+    /// A rebuild failed; the previous graph is still served.
+    Error { message: String },
+    /// The subscribed user's recommendations from the graph now being served.
+    Recommendations {
+        user: String,
+        items: Vec<Recommendation>,
+    },
+    /// The subscribed user isn't in the graph now being served (a rebuild dropped them).
+    UnknownUser { user: String, message: String },
+}
+
+#[derive(Deserialize)]
+struct TopQuery {
+    top: Option<usize>,
+}
+
+impl TopQuery {
+    fn limit(&self) -> usize {
+        self.top.unwrap_or(DEFAULT_TOP).min(MAX_TOP)
+    }
+}
+
+#[derive(Deserialize)]
+struct AnimeQuery {
+    q: String,
+    top: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AnimeMatch {
+    anime_id: u32,
+    title: String,
+}
+
+#[derive(Deserialize)]
+struct GraphQuery {
+    format: Option<Format>,
+}
+
+#[derive(Deserialize)]
+struct EventsQuery {
+    user: Option<String>,
+    top: Option<usize>,
+}
+
+/// A JSON `{"error": ...}` body with its status.
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<FindUserError> for ApiError {
+    fn from(err: FindUserError) -> Self {
+        let status = match err {
+            FindUserError::NotFound(_) => StatusCode::NOT_FOUND,
+            FindUserError::Ambiguous(..) => StatusCode::BAD_REQUEST,
+        };
+        Self(status, err.to_string())
+    }
+}
+
+/// Builds the graph for `dataset`, then serves until Ctrl-C.
+pub fn run(dataset: PathBuf, duplicates: MergeStrategy, addr: SocketAddr) -> Result<(), String> {
+    let seen = modified(&dataset);
+    let engine = Engine::start(dataset, duplicates)?;
+    let app = router(Arc::clone(&engine));
+
+    let runtime = tokio::runtime::Runtime::new().map_err(|err| err.to_string())?;
+    runtime.block_on(async {
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .map_err(|err| format!("could not listen on {addr}: {err}"))?;
+        eprintln!("Listening on http://{addr}");
+        tokio::spawn(watch(engine, seen));
+        axum::serve(listener, app)
+            .with_graceful_shutdown(async {
+                let _ = tokio::signal::ctrl_c().await;
+            })
+            .await
+            .map_err(|err| err.to_string())
+    })
+}
+
+fn router(engine: Arc<Engine>) -> Router {
+    Router::new()
+        .route("/recommendations/{user}", get(recommendations))
+        .route("/similar/{anime_id}", get(similar))
+        .route("/anime", get(anime_search))
+        .route("/graph", get(graph_file))
+        .route("/stats", get(stats_json))
+        .route("/reload", post(reload))
+        .route("/events", get(events_socket))
+        .with_state(engine)
+}
+
+fn modified(path: &std::path::Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// Rebuilds once the dataset file has changed and then stayed the same for one interval,
+/// so a pipeline still writing it isn't read half-way.
+async fn watch(engine: Arc<Engine>, mut seen: Option<SystemTime>) {
+    let mut pending = None;
+    let mut interval = tokio::time::interval(WATCH_INTERVAL);
+    loop {
+        interval.tick().await;
+        let path = engine.dataset.clone();
+        let current = tokio::task::spawn_blocking(move || modified(&path))
+            .await
+            .ok()
+            .flatten();
+        if current == seen {
+            pending = None;
+        } else if pending == Some(current) {
+            seen = current;
+            pending = None;
+            Arc::clone(&engine).rebuild().await;
+        } else {
+            pending = Some(current);
+        }
+    }
+}
+
+async fn recommendations(
+    State(engine): State<Arc<Engine>>,
+    Path(user): Path<String>,
+    Query(query): Query<TopQuery>,
+) -> Result<Response, ApiError> {
+    let snapshot = engine.snapshot();
+    let user = snapshot.graph.find_user(&user)?;
+    let recommendations = recommend::recommend(&snapshot.graph, user, query.limit());
+    Ok(Json(recommendations).into_response())
+}
+
+async fn similar(
+    State(engine): State<Arc<Engine>>,
+    Path(anime_id): Path<u32>,
+    Query(query): Query<TopQuery>,
+) -> Result<Response, ApiError> {
+    let snapshot = engine.snapshot();
+    let anime = snapshot.graph.find_anime(anime_id).ok_or_else(|| {
+        ApiError(
+            StatusCode::NOT_FOUND,
+            format!("no anime with id {anime_id}"),
+        )
+    })?;
+    Ok(Json(recommend::similar(&snapshot.graph, anime, query.limit())).into_response())
+}
+
+async fn anime_search(
+    State(engine): State<Arc<Engine>>,
+    Query(query): Query<AnimeQuery>,
+) -> Json<Vec<AnimeMatch>> {
+    let snapshot = engine.snapshot();
+    let limit = TopQuery { top: query.top }.limit();
+    let matches = snapshot
+        .graph
+        .find_anime_by_title(&query.q, limit)
+        .into_iter()
+        .filter_map(|node| {
+            let node = &snapshot.graph.nodes[node];
+            Some(AnimeMatch {
+                anime_id: node.anime_id()?,
+                title: node.label.clone(),
+            })
+        })
+        .collect();
+    Json(matches)
+}
+
+async fn graph_file(
+    State(engine): State<Arc<Engine>>,
+    Query(query): Query<GraphQuery>,
+) -> Result<Response, ApiError> {
+    let format = query.format.map_or(GraphFormat::Json, GraphFormat::from);
+    let content_type = match format {
+        GraphFormat::GraphMl | GraphFormat::Gexf => "application/xml",
+        GraphFormat::Dot => "text/vnd.graphviz",
+        GraphFormat::Json => "application/json",
+    };
+    let mut body = Vec::new();
+    graph_export::write(&mut body, &engine.snapshot().graph, format)
+        .map_err(|err| ApiError(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    Ok(([(header::CONTENT_TYPE, content_type)], body).into_response())
+}
+
+async fn stats_json(State(engine): State<Arc<Engine>>) -> Json<Stats> {
+    Json(engine.snapshot().stats.clone())
+}
+
+/// Starts a rebuild and answers straight away; progress goes to `/events`.
+async fn reload(State(engine): State<Arc<Engine>>) -> StatusCode {
+    tokio::spawn(engine.rebuild());
+    StatusCode::ACCEPTED
+}
+
+/// Refuses the upgrade with 404 or 400, like `/recommendations`, when `user` matches no user or
+/// several.
+async fn events_socket(
+    State(engine): State<Arc<Engine>>,
+    Query(query): Query<EventsQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    if let Some(user) = &query.user {
+        engine.snapshot().graph.find_user(user)?;
+    }
+    Ok(upgrade.on_upgrade(move |socket| stream_events(engine, query, socket)))
+}
+
+/// Sends the current stats (and recommendations) on connect, then every event as it happens.
+async fn stream_events(engine: Arc<Engine>, query: EventsQuery, mut socket: WebSocket) {
+    let mut events = engine.events.subscribe();
+    let limit = TopQuery { top: query.top }.limit();
+    let mut next = Some(Event::Ready {
+        stats: engine.snapshot().stats.clone(),
+    });
+    loop {
+        if let Some(event) = next.take() {
+            let ready = matches!(event, Event::Ready { .. });
+            if send(&mut socket, &event).await.is_err() {
+                return;
+            }
+            if let (true, Some(user)) = (ready, &query.user) {
+                let event = user_recommendations(&engine.snapshot().graph, user, limit);
+                if send(&mut socket, &event).await.is_err() {
+                    return;
+                }
+            }
+        }
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => next = Some(event),
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+fn user_recommendations(graph: &GraphModel, user: &str, limit: usize) -> Event {
+    match graph.find_user(user) {
+        Ok(node) => Event::Recommendations {
+            user: user.to_string(),
+            items: recommend::recommend(graph, node, limit),
+        },
+        Err(err) => Event::UnknownUser {
+            user: user.to_string(),
+            message: err.to_string(),
+        },
+    }
+}
+
+async fn send(socket: &mut WebSocket, event: &Event) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(event).unwrap_or_default();
+    socket.send(Message::Text(text.into())).await
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use serde_json::Value;
+    use tower::ServiceExt;
+    use wasiw_core::{Dataset, Rating, UserRatings};
+
+    use super::*;
+
+    fn user(user_id: &str, ratings: &[(u32, f64)]) -> UserRatings {
+        UserRatings {
+            user_id: user_id.to_string(),
+            ratings: ratings
+                .iter()
+                .map(|&(anime_id, score)| Rating::new(anime_id, format!("Anime {anime_id}"), score))
+                .collect(),
+        }
+    }
+
+    /// An engine over a small dataset written to a file named after the test, so `/reload`
+    /// has something to re-read.
+    fn engine(name: &str) -> (Arc<Engine>, PathBuf) {
+        let dataset = Dataset::new(vec![
+            user("alice1", &[(1, 9.0), (2, 4.0)]),
+            user("alice2", &[(1, 8.0), (3, 7.0)]),
+            user("bob", &[(1, 9.0), (2, 4.0), (3, 4.0), (4, 10.0), (5, 9.0)]),
+            user("carol", &[(2, 3.0), (4, 9.0), (5, 10.0)]),
+        ]);
+        let path =
+            std::env::temp_dir().join(format!("wasiw-serve-{name}-{}.json", std::process::id()));
+        std::fs::write(&path, serde_json::to_vec(&dataset).unwrap()).unwrap();
+        (
+            Engine::start(path.clone(), MergeStrategy::default()).unwrap(),
+            path,
+        )
+    }
+
+    async fn request(engine: &Arc<Engine>, method: &str, uri: &str) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+        let response = router(Arc::clone(engine)).oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    #[tokio::test]
+    async fn routes_answer_with_results_or_the_status_of_what_went_wrong() {
+        let (engine, path) = engine("routes");
+
+        let (status, body) = request(&engine, "GET", "/recommendations/bob?top=3").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.as_array().is_some_and(|items| items.len() <= 3));
+        let (status, body) = request(&engine, "GET", "/recommendations/carol").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.is_array());
+        let (status, body) = request(&engine, "GET", "/recommendations/nobody").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(body["error"].is_string());
+        let (status, body) = request(&engine, "GET", "/recommendations/alice").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("2 users"));
+
+        let (status, body) = request(&engine, "GET", "/similar/1?top=2").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.as_array().is_some_and(|items| items.len() <= 2));
+        let (status, body) = request(&engine, "GET", "/similar/999").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert!(body["error"].is_string());
+        let (status, _) = request(&engine, "GET", "/similar/not-a-number").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let mut events = engine.events.subscribe();
+        let (status, _) = request(&engine, "POST", "/reload").await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        let ready = tokio::time::timeout(Duration::from_secs(30), async {
+            loop {
+                if let Event::Ready { stats } = events.recv().await.unwrap() {
+                    return stats;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(ready.users, 4);
+        let (status, _) = request(&engine, "GET", "/reload").await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn rebuilds_requested_during_one_fold_into_a_single_rerun() {
+        let (engine, path) = engine("coalesce");
+        let mut events = engine.events.subscribe();
+
+        let running = engine.rebuilding.lock().await;
+        for _ in 0..3 {
+            tokio::time::timeout(Duration::from_secs(5), Arc::clone(&engine).rebuild())
+                .await
+                .expect("a rebuild during another returns instead of waiting");
+        }
+        assert!(engine.rerun.load(Ordering::SeqCst));
+        drop(running);
+
+        Arc::clone(&engine).rebuild().await;
+        assert!(!engine.rerun.load(Ordering::SeqCst));
+        let mut ready = 0;
+        while let Ok(event) = events.try_recv() {
+            ready += usize::from(matches!(event, Event::Ready { .. }));
+        }
+        assert_eq!(ready, 1);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,444 @@
+//! `wasiw tui`: the dataset in the terminal, for SSH sessions and quick checks. The header
+//! shows the dataset's stats; pick a profile on the left to list its recommendations, and the
+//! anime most similar to the highlighted recommendation are listed under them.
+
+use std::io::{self, Write};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use wasiw_core::recommend::{self, Recommendation, SimilarAnime};
+use wasiw_core::stats::Stats;
+use wasiw_core::{Dataset, GraphModel, NodeType, Viewport};
+
+/// Rows moved by Page Up / Page Down.
+const PAGE: isize = 10;
+
+/// Builds the graph, then runs the interface until `q`, Esc or Ctrl-C.
+pub fn run(dataset: Dataset, user: Option<&str>, top: usize) -> Result<(), String> {
+    let mut stderr = io::stderr();
+    let (graph, stats) = Stats::build(dataset, Viewport::default(), |progress| {
+        let _ = write!(stderr, "\rBuilding the graph: {progress}    ");
+    });
+    let _ = writeln!(stderr);
+    let mut app = App::new(&graph, &stats, top);
+    if let Some(user) = user {
+        let node = graph.find_user(user).map_err(|err| err.to_string())?;
+        app.select_node(node);
+    }
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result.map_err(|err| err.to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Users,
+    Recommendations,
+    Similar,
+}
+
+impl Pane {
+    const ALL: [Self; 3] = [Self::Users, Self::Recommendations, Self::Similar];
+
+    fn cycle(self, forward: bool) -> Self {
+        let index = Self::ALL.iter().position(|pane| *pane == self).unwrap_or(0);
+        let len = Self::ALL.len();
+        Self::ALL[if forward {
+            (index + 1) % len
+        } else {
+            (index + len - 1) % len
+        }]
+    }
+}
+
+struct App<'a> {
+    graph: &'a GraphModel,
+    stats: &'a Stats,
+    top: usize,
+    /// Every user node, by label.
+    users: Vec<usize>,
+    /// The users matching `filter`.
+    shown: Vec<usize>,
+    filter: String,
+    /// Typing goes to the filter rather than to key bindings.
+    editing: bool,
+    focus: Pane,
+    user_list: ListState,
+    recommendations: Vec<Recommendation>,
+    recommendation_list: ListState,
+    similar: Vec<SimilarAnime>,
+    similar_list: ListState,
+}
+
+impl<'a> App<'a> {
+    fn new(graph: &'a GraphModel, stats: &'a Stats, top: usize) -> Self {
+        let mut users: Vec<usize> = graph
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.node_type == NodeType::User)
+            .map(|(idx, _)| idx)
+            .collect();
+        users.sort_by(|&a, &b| graph.nodes[a].label.cmp(&graph.nodes[b].label));
+        let mut app = Self {
+            graph,
+            stats,
+            top,
+            shown: users.clone(),
+            users,
+            filter: String::new(),
+            editing: false,
+            focus: Pane::Users,
+            user_list: ListState::default(),
+            recommendations: Vec::new(),
+            recommendation_list: ListState::default(),
+            similar: Vec::new(),
+            similar_list: ListState::default(),
+        };
+        app.user_list.select((!app.shown.is_empty()).then_some(0));
+        app.user_changed();
+        app
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.handle_key(key) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Returns false to quit.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return false;
+        }
+        if self.editing {
+            match key.code {
+                KeyCode::Enter => self.editing = false,
+                KeyCode::Esc => {
+                    self.editing = false;
+                    self.filter.clear();
+                    self.apply_filter();
+                }
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                    self.apply_filter();
+                }
+                KeyCode::Char(c) => {
+                    self.filter.push(c);
+                    self.apply_filter();
+                }
+                _ => {}
+            }
+            return true;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('/') => {
+                self.focus = Pane::Users;
+                self.editing = true;
+            }
+            KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => {
+                self.focus = self.focus.cycle(true)
+            }
+            KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => {
+                self.focus = self.focus.cycle(false)
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.step(1),
+            KeyCode::Up | KeyCode::Char('k') => self.step(-1),
+            KeyCode::PageDown => self.step(PAGE),
+            KeyCode::PageUp => self.step(-PAGE),
+            KeyCode::Home | KeyCode::Char('g') => self.step(isize::MIN),
+            KeyCode::End | KeyCode::Char('G') => self.step(isize::MAX),
+            _ => {}
+        }
+        true
+    }
+
+    /// Moves the highlight in the focused pane, clamped to its ends.
+    fn step(&mut self, delta: isize) {
+        let (state, len) = match self.focus {
+            Pane::Users => (&mut self.user_list, self.shown.len()),
+            Pane::Recommendations => (&mut self.recommendation_list, self.recommendations.len()),
+            Pane::Similar => (&mut self.similar_list, self.similar.len()),
+        };
+        if len == 0 {
+            return;
+        }
+        let current = state.selected().unwrap_or(0) as isize;
+        let next = current.saturating_add(delta).clamp(0, len as isize - 1) as usize;
+        if state.selected() == Some(next) {
+            return;
+        }
+        state.select(Some(next));
+        match self.focus {
+            Pane::Users => self.user_changed(),
+            Pane::Recommendations => self.recommendation_changed(),
+            Pane::Similar => {}
+        }
+    }
+
+    fn apply_filter(&mut self) {
+        let current = self.selected_user();
+        let needle = self.filter.to_lowercase();
+        self.shown = self
+            .users
+            .iter()
+            .copied()
+            .filter(|&idx| self.graph.nodes[idx].label.to_lowercase().contains(&needle))
+            .collect();
+        let index = current
+            .and_then(|node| self.shown.iter().position(|&idx| idx == node))
+            .or((!self.shown.is_empty()).then_some(0));
+        self.user_list.select(index);
+        if self.selected_user() != current {
+            self.user_changed();
+        }
+    }
+
+    fn select_node(&mut self, node: usize) {
+        if let Some(index) = self.shown.iter().position(|&idx| idx == node) {
+            self.user_list.select(Some(index));
+            self.user_changed();
+        }
+    }
+
+    fn selected_user(&self) -> Option<usize> {
+        self.user_list
+            .selected()
+            .and_then(|index| self.shown.get(index).copied())
+    }
+
+    fn user_changed(&mut self) {
+        self.recommendations = self
+            .selected_user()
+            .map(|user| recommend::recommend(self.graph, user, self.top))
+            .unwrap_or_default();
+        self.recommendation_list
+            .select((!self.recommendations.is_empty()).then_some(0));
+        self.recommendation_changed();
+    }
+
+    fn selected_recommendation(&self) -> Option<&Recommendation> {
+        self.recommendation_list
+            .selected()
+            .and_then(|index| self.recommendations.get(index))
+    }
+
+    fn recommendation_changed(&mut self) {
+        self.similar = self
+            .selected_recommendation()
+            .map(|recommendation| recommend::similar(self.graph, recommendation.node, self.top))
+            .unwrap_or_default();
+        self.similar_list
+            .select((!self.similar.is_empty()).then_some(0));
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)])
+                .areas(body);
+        let [recommendations, explanation, similar] = Layout::vertical([
+            Constraint::Percentage(55),
+            Constraint::Length(4),
+            Constraint::Min(0),
+        ])
+        .areas(right);
+
+        let stats = self.stats;
+        frame.render_widget(
+            Paragraph::new(format!(
+                "Users {}  ·  Anime {}  ·  Ratings {}  ·  Mean score {:.2}  ·  Similarity edges {}  ·  Communities {}  ·  Components {}",
+                stats.users,
+                stats.anime,
+                stats.ratings,
+                stats.mean_score,
+                stats.similarity_edges,
+                stats.communities,
+                stats.components
+            ))
+            .block(Block::bordered().title(" What Anime Should I Watch ")),
+            header,
+        );
+
+        self.draw_users(frame, left);
+        self.draw_recommendations(frame, recommendations);
+
+        let explained = self
+            .selected_recommendation()
+            .map(|recommendation| recommendation.explanation(3))
+            .unwrap_or_default();
+        frame.render_widget(
+            Paragraph::new(explained)
+                .wrap(Wrap { trim: true })
+                .block(Block::bordered().title(" Why ")),
+            explanation,
+        );
+
+        self.draw_similar(frame, similar);
+
+        let help = if self.editing {
+            "type to filter users · Enter keep · Esc clear"
+        } else {
+            "↑↓ move · Tab switch pane · / filter users · q quit"
+        };
+        frame.render_widget(Line::from(help).dim(), footer);
+    }
+
+    fn draw_users(&mut self, frame: &mut Frame, area: Rect) {
+        let title = if self.editing || !self.filter.is_empty() {
+            format!(" Users /{} ({}) ", self.filter, self.shown.len())
+        } else {
+            format!(" Users ({}) ", self.shown.len())
+        };
+        let items = self
+            .shown
+            .iter()
+            .map(|&idx| ListItem::new(self.graph.nodes[idx].label.as_str()));
+        let list = List::new(items)
+            .block(self.pane_block(Pane::Users, title))
+            .highlight_style(Style::new().reversed())
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, area, &mut self.user_list);
+    }
+
+    fn draw_recommendations(&mut self, frame: &mut Frame, area: Rect) {
+        let title = match self.selected_user() {
+            Some(user) => format!(" Recommendations for {} ", self.graph.nodes[user].label),
+            None => " Recommendations ".to_string(),
+        };
+        let items = self.recommendations.iter().enumerate().map(|(rank, item)| {
+            ListItem::new(format!(
+                "{:>3}. {:+6.2}  {} ({} supporting)",
+                rank + 1,
+                item.score,
+                item.title,
+                item.support_count
+            ))
+        });
+        let list = List::new(items)
+            .block(self.pane_block(Pane::Recommendations, title))
+            .highlight_style(Style::new().reversed())
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, area, &mut self.recommendation_list);
+    }
+
+    fn draw_similar(&mut self, frame: &mut Frame, area: Rect) {
+        let title = match self.selected_recommendation() {
+            Some(recommendation) => format!(" Similar to {} ", recommendation.title),
+            None => " Similar anime ".to_string(),
+        };
+        let items = self.similar.iter().map(|item| {
+            ListItem::new(format!(
+                "{:+6.2}  {} ({} co-raters)",
+                item.score, item.title, item.co_raters
+            ))
+        });
+        let list = List::new(items)
+            .block(self.pane_block(Pane::Similar, title))
+            .highlight_style(Style::new().reversed())
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, area, &mut self.similar_list);
+    }
+
+    fn pane_block(&self, pane: Pane, title: String) -> Block<'static> {
+        let block = Block::bordered().title(title);
+        if pane == self.focus {
+            block.border_style(Style::new().fg(Color::Cyan))
+        } else {
+            block
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasiw_core::{Rating, UserRatings};
+
+    use super::*;
+
+    fn user(user_id: &str, ratings: &[(u32, f64)]) -> UserRatings {
+        UserRatings {
+            user_id: user_id.to_string(),
+            ratings: ratings
+                .iter()
+                .map(|&(anime_id, score)| Rating::new(anime_id, format!("Anime {anime_id}"), score))
+                .collect(),
+        }
+    }
+
+    fn press(app: &mut App, code: KeyCode) -> bool {
+        app.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn shown_labels(app: &App) -> Vec<String> {
+        app.shown
+            .iter()
+            .map(|&idx| app.graph.nodes[idx].label.clone())
+            .collect()
+    }
+
+    #[test]
+    fn keys_filter_the_users_and_move_within_the_focused_pane() {
+        let dataset = Dataset::new(vec![
+            user("carol", &[(2, 3.0), (4, 9.0), (5, 10.0)]),
+            user("alice2", &[(1, 8.0), (3, 7.0)]),
+            user("bob", &[(1, 9.0), (2, 4.0), (3, 4.0), (4, 10.0), (5, 9.0)]),
+            user("alice1", &[(1, 9.0), (2, 4.0)]),
+        ]);
+        let (graph, stats) = Stats::build(dataset, Viewport::default(), |_| {});
+        let mut app = App::new(&graph, &stats, 5);
+        let all = shown_labels(&app);
+        let mut sorted = all.clone();
+        sorted.sort();
+        assert_eq!(all, sorted);
+        assert_eq!(app.user_list.selected(), Some(0));
+
+        // Moves clamp to the ends of the list.
+        press(&mut app, KeyCode::End);
+        assert_eq!(app.user_list.selected(), Some(3));
+        press(&mut app, KeyCode::Down);
+        assert_eq!(app.user_list.selected(), Some(3));
+        press(&mut app, KeyCode::Home);
+        assert_eq!(app.user_list.selected(), Some(0));
+
+        // While filtering, letters go to the filter rather than to key bindings.
+        press(&mut app, KeyCode::Char('/'));
+        for c in "ALICE".chars() {
+            assert!(press(&mut app, KeyCode::Char(c)));
+        }
+        assert_eq!(shown_labels(&app).len(), 2);
+        assert!(shown_labels(&app)
+            .iter()
+            .all(|label| label.contains("alice")));
+        press(&mut app, KeyCode::Esc);
+        assert!(app.filter.is_empty());
+        assert_eq!(shown_labels(&app), all);
+
+        assert_eq!(app.focus, Pane::Users);
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.focus, Pane::Recommendations);
+        press(&mut app, KeyCode::BackTab);
+        press(&mut app, KeyCode::BackTab);
+        assert_eq!(app.focus, Pane::Similar);
+
+        assert!(!press(&mut app, KeyCode::Char('q')));
+        assert!(!app.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+    }
+}
@@ -0,0 +1,189 @@
+//! Rating activity by day, from the ratings' `updatedAt` dates, for a calendar of when a user or
+//! the whole corpus rated. Days are numbered from 1970-01-01 in the proleptic Gregorian
+//! calendar, the time of day and zone ignored.
+
+use std::collections::BTreeMap;
+
+use crate::{Rating, UserRatings};
+
+/// Days between 0000-03-01 and 1970-01-01.
+const EPOCH_SHIFT: i64 = 719_468;
+const DAYS_PER_ERA: i64 = 146_097;
+
+/// The day an ISO 8601 date or timestamp, such as `2024-05-01` or `2024-05-01T12:00:00Z`, falls
+/// on.
+pub fn parse_day(date: &str) -> Option<i64> {
+    let date = date.get(..10)?;
+    let mut parts = date.split('-');
+    let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return None;
+    }
+    let (year, month, day): (i64, i64, i64) =
+        (year.parse().ok()?, month.parse().ok()?, day.parse().ok()?);
+    (1..=12).contains(&month).then_some(())?;
+    (1..=days_in_month(year, month))
+        .contains(&day)
+        .then_some(())?;
+    Some(from_civil(year, month, day))
+}
+
+/// `day` as `YYYY-MM-DD`.
+pub fn format_day(day: i64) -> String {
+    let (year, month, day) = to_civil(day);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Days after Monday.
+pub fn weekday(day: i64) -> u8 {
+    // 1970-01-01 was a Thursday.
+    (day + 3).rem_euclid(7) as u8
+}
+
+/// The year `day` falls in.
+pub fn year_of(day: i64) -> i64 {
+    to_civil(day).0
+}
+
+/// How many ratings were made on each day.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Activity {
+    days: BTreeMap<i64, usize>,
+    /// Ratings without a readable date.
+    pub undated: usize,
+}
+
+impl Activity {
+    /// Counts the ratings of `users`, e.g. one user or the whole dataset.
+    pub fn new<'a>(users: impl IntoIterator<Item = &'a UserRatings>) -> Self {
+        let mut activity = Self::default();
+        for rating in users.into_iter().flat_map(|user| &user.ratings) {
+            match rating.updated_at.as_deref().and_then(parse_day) {
+                Some(day) => *activity.days.entry(day).or_default() += 1,
+                None => activity.undated += 1,
+            }
+        }
+        activity
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.days.is_empty()
+    }
+
+    /// The years with ratings, oldest first.
+    pub fn years(&self) -> Vec<i64> {
+        let mut years: Vec<i64> = self.days.keys().map(|&day| year_of(day)).collect();
+        years.dedup();
+        years
+    }
+
+    /// Every day of `year` in order, with its ratings.
+    pub fn year(&self, year: i64) -> Vec<(i64, usize)> {
+        (from_civil(year, 1, 1)..from_civil(year + 1, 1, 1))
+            .map(|day| (day, self.days.get(&day).copied().unwrap_or(0)))
+            .collect()
+    }
+}
+
+/// The ratings of `users` made on `day`, with whose they are.
+pub fn ratings_on<'a>(
+    users: impl IntoIterator<Item = &'a UserRatings>,
+    day: i64,
+) -> Vec<(&'a str, &'a Rating)> {
+    users
+        .into_iter()
+        .flat_map(|user| {
+            user.ratings
+                .iter()
+                .map(move |rating| (&*user.user_id, rating))
+        })
+        .filter(|(_, rating)| rating.updated_at.as_deref().and_then(parse_day) == Some(day))
+        .collect()
+}
+
+fn is_leap(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01, counting years from March so the leap day comes last.
+fn from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * DAYS_PER_ERA + day_of_era - EPOCH_SHIFT
+}
+
+/// The inverse of [`from_civil`].
+fn to_civil(day: i64) -> (i64, i64, i64) {
+    let day = day + EPOCH_SHIFT;
+    let era = day.div_euclid(DAYS_PER_ERA);
+    let day_of_era = day - era * DAYS_PER_ERA;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_round_trip_through_dates() {
+        assert_eq!(parse_day("1970-01-01"), Some(0));
+        assert_eq!(parse_day("2024-02-29T23:59:59Z"), Some(19_782));
+        assert_eq!(format_day(19_782), "2024-02-29");
+        assert_eq!(parse_day("2023-02-29"), None);
+        assert_eq!(parse_day("May 2024"), None);
+        // 2026-10-10 was a Saturday.
+        assert_eq!(weekday(parse_day("2026-10-10").unwrap()), 5);
+        for day in [-1, 59, 365, 11_016, 20_000] {
+            assert_eq!(parse_day(&format_day(day)), Some(day));
+        }
+    }
+
+    #[test]
+    fn counts_ratings_per_day() {
+        let dated = |date: Option<&str>| {
+            let mut rating = Rating::new(1, "Anime".to_string(), 7.0);
+            rating.updated_at = date.map(str::to_string);
+            rating
+        };
+        let user = UserRatings {
+            user_id: "a".to_string(),
+            ratings: vec![
+                dated(Some("2023-12-31")),
+                dated(Some("2024-01-01T08:00:00Z")),
+                dated(Some("2024-01-01")),
+                dated(None),
+            ],
+        };
+        let activity = Activity::new([&user]);
+        assert_eq!(activity.undated, 1);
+        assert_eq!(activity.years(), [2023, 2024]);
+        let year = activity.year(2024);
+        assert_eq!(year.len(), 366);
+        assert_eq!(year[0].1, 2);
+        let new_year = parse_day("2024-01-01").unwrap();
+        assert_eq!(ratings_on([&user], new_year).len(), 2);
+    }
+}
@@ -0,0 +1,88 @@
+//! Each node's edges in compressed sparse row form, for neighbor queries that would otherwise
+//! scan every edge of the graph.
+
+use crate::graph::Edge;
+
+/// The edges touching every node, as indices into `GraphModel::edges`: node `n`'s are
+/// `edges[offsets[n]..offsets[n + 1]]`, in edge order. An edge is listed under both its ends.
+#[derive(Debug, Clone, Default)]
+pub struct Adjacency {
+    offsets: Vec<u32>,
+    edges: Vec<u32>,
+}
+
+impl Adjacency {
+    /// The adjacency of `node_count` nodes joined by `edges`.
+    pub fn new(node_count: usize, edges: &[Edge]) -> Self {
+        let mut offsets = vec![0_u32; node_count + 1];
+        for edge in edges {
+            offsets[edge.source + 1] += 1;
+            offsets[edge.target + 1] += 1;
+        }
+        for node in 0..node_count {
+            offsets[node + 1] += offsets[node];
+        }
+        let mut next: Vec<u32> = offsets[..node_count].to_vec();
+        let mut listed = vec![0_u32; offsets[node_count] as usize];
+        for (index, edge) in edges.iter().enumerate() {
+            for end in [edge.source, edge.target] {
+                listed[next[end] as usize] = index as u32;
+                next[end] += 1;
+            }
+        }
+        Self {
+            offsets,
+            edges: listed,
+        }
+    }
+
+    /// Indices of the edges touching `node`, in edge order; none for a node out of range.
+    pub fn edges(&self, node: usize) -> impl ExactSizeIterator<Item = usize> + '_ {
+        self.listed(node).iter().map(|&index| index as usize)
+    }
+
+    /// The same indices as [`Adjacency::edges`], as stored.
+    pub(crate) fn listed(&self, node: usize) -> &[u32] {
+        match (self.offsets.get(node), self.offsets.get(node + 1)) {
+            (Some(&start), Some(&end)) => &self.edges[start as usize..end as usize],
+            _ => &[],
+        }
+    }
+
+    /// Bytes the index holds.
+    pub fn memory_bytes(&self) -> usize {
+        (self.offsets.capacity() + self.edges.capacity()) * std::mem::size_of::<u32>()
+    }
+
+    /// How many edges touch `node`.
+    pub fn degree(&self, node: usize) -> usize {
+        self.edges(node).len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EdgeKind;
+
+    fn edge(source: usize, target: usize) -> Edge {
+        Edge {
+            source,
+            target,
+            kind: EdgeKind::Similarity,
+            weight: 1.0,
+            co_raters: 1,
+            stroke_width: 1.0,
+        }
+    }
+
+    #[test]
+    fn lists_each_edge_under_both_ends_in_edge_order() {
+        let adjacency = Adjacency::new(4, &[edge(0, 1), edge(2, 0), edge(1, 2)]);
+        assert_eq!(adjacency.edges(0).collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(adjacency.edges(1).collect::<Vec<_>>(), [0, 2]);
+        assert_eq!(adjacency.edges(2).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(adjacency.degree(3), 0);
+        assert_eq!(adjacency.degree(9), 0);
+    }
+}
@@ -0,0 +1,82 @@
+//! When weekly shows air. Jikan lists a show's broadcast slot as a weekday and a time in
+//! Japan, which has no daylight saving time, so the slot falls on the same instant every week.
+
+/// Japan Standard Time, UTC+9.
+const JST_OFFSET_SECS: i64 = 9 * 3600;
+const DAY_SECS: i64 = 86_400;
+const WEEK_SECS: i64 = 7 * DAY_SECS;
+/// 1970-01-05, the first Monday after the Unix epoch, in days since it.
+const FIRST_MONDAY: i64 = 4;
+
+const WEEKDAYS: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+/// A weekly broadcast slot in Japan Standard Time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Broadcast {
+    /// Days after Monday.
+    pub weekday: u8,
+    /// Minutes after midnight.
+    pub minute: u16,
+}
+
+impl Broadcast {
+    /// The slot Jikan writes as e.g. day `Saturdays` and time `23:30`.
+    pub fn parse(day: &str, time: &str) -> Option<Self> {
+        let day = day.trim().to_lowercase();
+        let weekday = WEEKDAYS
+            .iter()
+            .position(|name| day.trim_end_matches('s') == *name)?;
+        let (hours, minutes) = time.trim().split_once(':')?;
+        let (hours, minutes): (u16, u16) = (hours.parse().ok()?, minutes.parse().ok()?);
+        (hours < 24 && minutes < 60).then_some(Self {
+            weekday: weekday as u8,
+            minute: hours * 60 + minutes,
+        })
+    }
+
+    /// The Unix time of the latest airing at or before `now`, also Unix seconds.
+    pub fn latest(self, now: i64) -> i64 {
+        let local = now + JST_OFFSET_SECS;
+        let monday = FIRST_MONDAY * DAY_SECS;
+        let week_start = (local - monday).div_euclid(WEEK_SECS) * WEEK_SECS + monday;
+        let slot = week_start + i64::from(self.weekday) * DAY_SECS + i64::from(self.minute) * 60;
+        let slot = if slot > local { slot - WEEK_SECS } else { slot };
+        slot - JST_OFFSET_SECS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_jikan_slots() {
+        assert_eq!(
+            Broadcast::parse("Saturdays", "23:30"),
+            Some(Broadcast {
+                weekday: 5,
+                minute: 23 * 60 + 30
+            })
+        );
+        assert_eq!(Broadcast::parse("Unknown", "23:30"), None);
+        assert_eq!(Broadcast::parse("Mondays", "25:00"), None);
+    }
+
+    #[test]
+    fn latest_airing_is_the_last_slot_before_now() {
+        // Saturday 23:00 JST is 14:00 UTC; 2026-10-10 was a Saturday.
+        let slot = Broadcast::parse("Saturdays", "23:00").unwrap();
+        let aired = 1_791_640_800; // 2026-10-10 14:00 UTC
+        assert_eq!(slot.latest(1_791_676_800), aired); // the next morning
+        assert_eq!(slot.latest(aired), aired);
+        assert_eq!(slot.latest(aired - 60), aired - WEEK_SECS);
+    }
+}
@@ -0,0 +1,276 @@
+//! [`GraphModel`] as a petgraph graph, so petgraph's algorithms run on it directly: nodes are
+//! `usize` indices into `nodes`, edges are indices into `edges` weighted by the [`Edge`], and
+//! neighbors come from the adjacency index without copying anything. For example
+//! `petgraph::algo::dijkstra(&graph, start, None, |link| 1.0 - link.weight().weight)`.
+//!
+//! [`Stats`](crate::stats::Stats) counts the graph's [`components`] this way. Algorithms over
+//! other graphs, such as the topological sort of franchise relations in
+//! [`watch_order`](crate::watch_order), work on a `petgraph::Graph` built for them instead.
+
+use std::iter::Enumerate;
+use std::ops::Range;
+use std::slice;
+
+use fixedbitset::FixedBitSet;
+pub use petgraph;
+use petgraph::algo;
+use petgraph::visit::{
+    Data, EdgeCount, EdgeRef, GraphBase, GraphProp, IntoEdgeReferences, IntoEdges, IntoNeighbors,
+    IntoNodeIdentifiers, IntoNodeReferences, NodeCompactIndexable, NodeCount, NodeIndexable,
+    Visitable,
+};
+use petgraph::Undirected;
+
+use crate::{Edge, GraphModel, Node};
+
+/// An edge seen from one of its ends: `source` is the node it was reached from.
+#[derive(Debug, Clone, Copy)]
+pub struct LinkRef<'a> {
+    source: usize,
+    target: usize,
+    index: usize,
+    edge: &'a Edge,
+}
+
+impl EdgeRef for LinkRef<'_> {
+    type NodeId = usize;
+    type EdgeId = usize;
+    type Weight = Edge;
+
+    fn source(&self) -> usize {
+        self.source
+    }
+
+    fn target(&self) -> usize {
+        self.target
+    }
+
+    fn weight(&self) -> &Edge {
+        self.edge
+    }
+
+    fn id(&self) -> usize {
+        self.index
+    }
+}
+
+/// The edges touching one node, from the adjacency index.
+pub struct Links<'a> {
+    graph: &'a GraphModel,
+    node: usize,
+    indices: slice::Iter<'a, u32>,
+}
+
+impl<'a> Iterator for Links<'a> {
+    type Item = LinkRef<'a>;
+
+    fn next(&mut self) -> Option<LinkRef<'a>> {
+        let index = *self.indices.next()? as usize;
+        let edge = &self.graph.edges[index];
+        let target = if edge.source == self.node {
+            edge.target
+        } else {
+            edge.source
+        };
+        Some(LinkRef {
+            source: self.node,
+            target,
+            index,
+            edge,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
+/// The nodes one edge away from a node.
+pub struct Neighbors<'a>(Links<'a>);
+
+impl Iterator for Neighbors<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        self.0.next().map(|link| link.target)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+/// Every edge of the graph, from its `source` end.
+pub struct AllLinks<'a>(Enumerate<slice::Iter<'a, Edge>>);
+
+impl<'a> Iterator for AllLinks<'a> {
+    type Item = LinkRef<'a>;
+
+    fn next(&mut self) -> Option<LinkRef<'a>> {
+        self.0.next().map(|(index, edge)| LinkRef {
+            source: edge.source,
+            target: edge.target,
+            index,
+            edge,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl GraphBase for GraphModel {
+    type NodeId = usize;
+    type EdgeId = usize;
+}
+
+impl Data for GraphModel {
+    type NodeWeight = Node;
+    type EdgeWeight = Edge;
+}
+
+impl GraphProp for GraphModel {
+    type EdgeType = Undirected;
+}
+
+impl NodeCount for GraphModel {
+    fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl EdgeCount for GraphModel {
+    fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+}
+
+impl NodeIndexable for GraphModel {
+    fn node_bound(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn to_index(&self, node: usize) -> usize {
+        node
+    }
+
+    fn from_index(&self, index: usize) -> usize {
+        index
+    }
+}
+
+impl NodeCompactIndexable for GraphModel {}
+
+impl Visitable for GraphModel {
+    type Map = FixedBitSet;
+
+    fn visit_map(&self) -> FixedBitSet {
+        FixedBitSet::with_capacity(self.nodes.len())
+    }
+
+    fn reset_map(&self, map: &mut FixedBitSet) {
+        map.clear();
+        map.grow(self.nodes.len());
+    }
+}
+
+impl<'a> IntoNeighbors for &'a GraphModel {
+    type Neighbors = Neighbors<'a>;
+
+    fn neighbors(self, node: usize) -> Neighbors<'a> {
+        Neighbors(self.edges(node))
+    }
+}
+
+impl<'a> IntoEdgeReferences for &'a GraphModel {
+    type EdgeRef = LinkRef<'a>;
+    type EdgeReferences = AllLinks<'a>;
+
+    fn edge_references(self) -> AllLinks<'a> {
+        AllLinks(self.edges.iter().enumerate())
+    }
+}
+
+impl<'a> IntoEdges for &'a GraphModel {
+    type Edges = Links<'a>;
+
+    fn edges(self, node: usize) -> Links<'a> {
+        Links {
+            graph: self,
+            node,
+            indices: self.adjacency.listed(node).iter(),
+        }
+    }
+}
+
+impl IntoNodeIdentifiers for &GraphModel {
+    type NodeIdentifiers = Range<usize>;
+
+    fn node_identifiers(self) -> Range<usize> {
+        0..self.nodes.len()
+    }
+}
+
+impl<'a> IntoNodeReferences for &'a GraphModel {
+    type NodeRef = (usize, &'a Node);
+    type NodeReferences = Enumerate<slice::Iter<'a, Node>>;
+
+    fn node_references(self) -> Self::NodeReferences {
+        self.nodes.iter().enumerate()
+    }
+}
+
+/// How many separate pieces the graph falls into, counting lone nodes.
+pub fn components(graph: &GraphModel) -> usize {
+    algo::connected_components(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adjacency::Adjacency;
+    use crate::{build_graph, Dataset, EdgeKind, NodeType, Viewport};
+
+    fn graph(edges: &[(usize, usize, f64)], nodes: usize) -> GraphModel {
+        let edges: Vec<Edge> = edges
+            .iter()
+            .map(|&(source, target, weight)| Edge {
+                source,
+                target,
+                kind: EdgeKind::Similarity,
+                weight,
+                co_raters: 1,
+                stroke_width: 1.0,
+            })
+            .collect();
+        let mut graph = build_graph(Dataset::new(Vec::new()), Viewport::default());
+        graph.nodes = (0..nodes)
+            .map(|n| Node {
+                id: format!("anime:{n}"),
+                label: format!("Anime {n}"),
+                node_type: NodeType::Anime,
+                x: 0.0,
+                y: 0.0,
+                radius: 1.0,
+                hidden: false,
+            })
+            .collect();
+        graph.adjacency = Adjacency::new(nodes, &edges);
+        graph.edges = edges;
+        graph
+    }
+
+    #[test]
+    fn runs_petgraph_algorithms_on_the_model() {
+        // A triangle 0–1–2 with a weak side, a tail 2–3, and 4 on its own.
+        let graph = graph(&[(0, 1, 0.9), (1, 2, 0.8), (0, 2, 0.1), (2, 3, 0.5)], 5);
+        assert_eq!(components(&graph), 2);
+        let (_, path) = algo::astar(&graph, 0, |node| node == 3, |_| 1, |_| 0).unwrap();
+        assert_eq!(path, [0, 2, 3]);
+
+        let distances = algo::dijkstra(&graph, 0, None, |link| 1.0 - link.weight().weight);
+        assert!((distances[&3] - 0.8).abs() < 1e-9);
+        assert_eq!(graph.neighbors(2).collect::<Vec<_>>(), [1, 0, 3]);
+    }
+}
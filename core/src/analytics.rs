@@ -0,0 +1,815 @@
+//! How one anime is rated: its score histogram against the dataset's mean, how divided raters
+//! are, the anime its raters rate most alongside it, and how its ratings moved over time when
+//! the dataset has their dates. And how one user rates: against everyone else, by genre, and
+//! where they part ways with the consensus. Two anime or two users side by side, and across
+//! the dataset, which anime divide raters most and which they agree on.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::stats::score_bucket;
+use crate::Dataset;
+
+/// Co-rated anime listed at most.
+pub const PARTNERS: usize = 10;
+/// Contrarian opinions listed at most.
+pub const CONTRARIAN: usize = 10;
+/// Other raters an anime needs before a user's score can go against its consensus.
+pub const MIN_CONSENSUS_RATERS: usize = 3;
+/// Genres on a user's affinity chart at most, their most rated first.
+pub const AFFINITY_GENRES: usize = 8;
+/// Shared raters listed as the biggest parts of a similarity score at most.
+pub const SIMILARITY_PARTS: usize = 10;
+/// Agreements and disagreements listed between two users at most.
+pub const SHARED_OPINIONS: usize = 10;
+/// Points apart two users' scores can be and still agree.
+pub const AGREEMENT_POINTS: f64 = 1.0;
+/// Anime listed in each controversy ranking at most.
+pub const RANKED_SPREADS: usize = 10;
+/// Raters an anime needs to be ranked by default; with fewer, a couple of odd scores decide.
+pub const MIN_RANKED_RATERS: usize = 5;
+
+/// An anime often rated by the same users as another.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Partner {
+    pub anime_id: u32,
+    pub title: String,
+    /// Users who rated both.
+    pub shared: usize,
+}
+
+/// Ratings given in one month.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonthTrend {
+    /// `YYYY-MM`.
+    pub month: String,
+    pub ratings: usize,
+    pub mean: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnimeAnalytics {
+    pub anime_id: u32,
+    /// As the dataset names it; empty when nobody rated the anime.
+    pub title: String,
+    pub ratings: usize,
+    /// Ratings by raw score rounded to a whole point; index 0 counts scores of 1.
+    pub scores: [usize; 10],
+    /// Mean raw score, on the 1–10 scale.
+    pub mean: f64,
+    /// Mean over every rating in the dataset.
+    pub global_mean: f64,
+    /// Variance of the raw scores: high when raters disagree.
+    pub controversy: f64,
+    /// Most shared raters first, at most [`PARTNERS`].
+    pub partners: Vec<Partner>,
+    /// Oldest month first; empty unless some ratings have an `updatedAt` date.
+    pub trend: Vec<MonthTrend>,
+}
+
+impl AnimeAnalytics {
+    pub fn new(dataset: &Dataset, anime_id: u32) -> Self {
+        let mut analytics = Self {
+            anime_id,
+            ..Self::default()
+        };
+        let mut total = 0.0;
+        let mut count = 0_usize;
+        let mut scores = Vec::new();
+        let mut partners: HashMap<u32, (&str, usize)> = HashMap::new();
+        let mut months: BTreeMap<&str, (usize, f64)> = BTreeMap::new();
+        for user in &dataset.users {
+            total += user
+                .ratings
+                .iter()
+                .map(|rating| rating.raw_score)
+                .sum::<f64>();
+            count += user.ratings.len();
+            let Some(rating) = user
+                .ratings
+                .iter()
+                .find(|rating| rating.anime_id == anime_id)
+            else {
+                continue;
+            };
+            if analytics.title.is_empty() {
+                analytics.title = rating.title.clone();
+            }
+            scores.push(rating.raw_score);
+            analytics.scores[score_bucket(rating.raw_score)] += 1;
+            if let Some(month) = rating.updated_at.as_deref().and_then(month) {
+                let entry = months.entry(month).or_default();
+                entry.0 += 1;
+                entry.1 += rating.raw_score;
+            }
+            for other in &user.ratings {
+                if other.anime_id != anime_id {
+                    partners
+                        .entry(other.anime_id)
+                        .or_insert((&other.title, 0))
+                        .1 += 1;
+                }
+            }
+        }
+        analytics.ratings = scores.len();
+        analytics.global_mean = mean(total, count);
+        analytics.mean = mean(scores.iter().sum(), scores.len());
+        analytics.controversy = mean(
+            scores
+                .iter()
+                .map(|score| (score - analytics.mean).powi(2))
+                .sum(),
+            scores.len(),
+        );
+        let mut partners: Vec<Partner> = partners
+            .into_iter()
+            .map(|(anime_id, (title, shared))| Partner {
+                anime_id,
+                title: title.to_string(),
+                shared,
+            })
+            .collect();
+        partners.sort_by(|a, b| b.shared.cmp(&a.shared).then(a.anime_id.cmp(&b.anime_id)));
+        partners.truncate(PARTNERS);
+        analytics.partners = partners;
+        analytics.trend = months
+            .into_iter()
+            .map(|(month, (ratings, total))| MonthTrend {
+                month: month.to_string(),
+                ratings,
+                mean: total / ratings as f64,
+            })
+            .collect();
+        analytics
+    }
+}
+
+/// One shared rater's part in two anime's similarity score. Scores are relative to the
+/// rater's own mean, as the graph's similarity edges use them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarityPart {
+    pub user_id: String,
+    pub left: f64,
+    pub right: f64,
+}
+
+impl SimilarityPart {
+    /// What this rater adds to the similarity before averaging.
+    pub fn pair_score(&self) -> f64 {
+        (self.left + self.right) / 2.0
+    }
+}
+
+/// Two anime side by side: who rated both, which of the two they liked more, what their
+/// similarity score is made of, and which genres they share.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnimeComparison {
+    pub left: u32,
+    pub right: u32,
+    /// As the dataset names them; empty for an anime nobody rated.
+    pub left_title: String,
+    pub right_title: String,
+    pub left_ratings: usize,
+    pub right_ratings: usize,
+    /// Users who rated both.
+    pub shared_raters: usize,
+    /// Shared raters who scored the left anime higher, the right one higher, or both the same.
+    pub prefer_left: usize,
+    pub prefer_right: usize,
+    pub tied: usize,
+    /// Shared raters' mean raw score for each.
+    pub left_mean: f64,
+    pub right_mean: f64,
+    /// The graph's similarity score: the mean over shared raters of [`SimilarityPart::pair_score`],
+    /// which is also the mean of `left_relative` and `right_relative`.
+    pub similarity: f64,
+    /// Shared raters' mean score for each, relative to their own means.
+    pub left_relative: f64,
+    pub right_relative: f64,
+    /// Biggest parts of the score either way first, at most [`SIMILARITY_PARTS`].
+    pub parts: Vec<SimilarityPart>,
+    /// Sorted.
+    pub shared_genres: Vec<String>,
+    pub left_genres: Vec<String>,
+    pub right_genres: Vec<String>,
+}
+
+impl AnimeComparison {
+    /// Compares `left` and `right`. Genres come from the ratings when the source had them, and
+    /// from `genres` otherwise.
+    pub fn new(
+        dataset: &Dataset,
+        left: u32,
+        right: u32,
+        genres: impl Fn(u32) -> Vec<String>,
+    ) -> Self {
+        let mut comparison = Self {
+            left,
+            right,
+            ..Self::default()
+        };
+        let mut source_genres: [&[String]; 2] = [&[], &[]];
+        let mut raw = [0.0, 0.0];
+        let mut relative = [0.0, 0.0];
+        let mut parts = Vec::new();
+        for user in &dataset.users {
+            let scored = [left, right].map(|anime_id| {
+                user.ratings
+                    .iter()
+                    .find(|rating| rating.anime_id == anime_id)
+            });
+            for (side, rating) in scored.iter().enumerate() {
+                let Some(rating) = rating else {
+                    continue;
+                };
+                let (title, count) = if side == 0 {
+                    (&mut comparison.left_title, &mut comparison.left_ratings)
+                } else {
+                    (&mut comparison.right_title, &mut comparison.right_ratings)
+                };
+                *count += 1;
+                if title.is_empty() {
+                    *title = rating.title.clone();
+                }
+                if source_genres[side].is_empty() {
+                    source_genres[side] = &rating.genres;
+                }
+            }
+            let [Some(on_left), Some(on_right)] = scored else {
+                continue;
+            };
+            comparison.shared_raters += 1;
+            match on_left.raw_score.total_cmp(&on_right.raw_score) {
+                Ordering::Greater => comparison.prefer_left += 1,
+                Ordering::Less => comparison.prefer_right += 1,
+                Ordering::Equal => comparison.tied += 1,
+            }
+            let user_mean = mean(
+                user.ratings.iter().map(|rating| rating.raw_score).sum(),
+                user.ratings.len(),
+            );
+            raw[0] += on_left.raw_score;
+            raw[1] += on_right.raw_score;
+            let part = SimilarityPart {
+                user_id: user.user_id.clone(),
+                left: on_left.raw_score - user_mean,
+                right: on_right.raw_score - user_mean,
+            };
+            relative[0] += part.left;
+            relative[1] += part.right;
+            parts.push(part);
+        }
+        let shared = comparison.shared_raters;
+        comparison.left_mean = mean(raw[0], shared);
+        comparison.right_mean = mean(raw[1], shared);
+        comparison.left_relative = mean(relative[0], shared);
+        comparison.right_relative = mean(relative[1], shared);
+        comparison.similarity = (comparison.left_relative + comparison.right_relative) / 2.0;
+        parts.sort_by(|a, b| {
+            b.pair_score()
+                .abs()
+                .total_cmp(&a.pair_score().abs())
+                .then_with(|| a.user_id.cmp(&b.user_id))
+        });
+        parts.truncate(SIMILARITY_PARTS);
+        comparison.parts = parts;
+
+        let [left_genres, right_genres] = [left, right].map(|anime_id| {
+            let side = usize::from(anime_id != left);
+            let known = if source_genres[side].is_empty() {
+                genres(anime_id)
+            } else {
+                source_genres[side].to_vec()
+            };
+            known.into_iter().collect::<BTreeSet<String>>()
+        });
+        comparison.shared_genres = left_genres.intersection(&right_genres).cloned().collect();
+        comparison.left_genres = left_genres.difference(&right_genres).cloned().collect();
+        comparison.right_genres = right_genres.difference(&left_genres).cloned().collect();
+        comparison
+    }
+
+    /// Shared genres out of all the two have, from 0 to 1.
+    pub fn genre_overlap(&self) -> f64 {
+        let all = self.shared_genres.len() + self.left_genres.len() + self.right_genres.len();
+        if all == 0 {
+            0.0
+        } else {
+            self.shared_genres.len() as f64 / all as f64
+        }
+    }
+}
+
+/// Two users' scores for an anime both rated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedOpinion {
+    pub anime_id: u32,
+    pub title: String,
+    pub left: f64,
+    pub right: f64,
+}
+
+impl SharedOpinion {
+    pub fn difference(&self) -> f64 {
+        (self.left - self.right).abs()
+    }
+}
+
+/// Two users' tastes side by side: how much of what they rated overlaps, how alike their
+/// scores for it are, and where they agree and disagree most.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UserComparison {
+    pub left: String,
+    pub right: String,
+    pub left_ratings: usize,
+    pub right_ratings: usize,
+    /// Anime both rated.
+    pub shared: usize,
+    /// Anime both rated out of all either rated, from 0 to 1.
+    pub jaccard: f64,
+    /// Pearson correlation of their scores for the shared anime, from -1 to 1; `None` with
+    /// fewer than two shared anime or when either scored them all the same.
+    pub pearson: Option<f64>,
+    /// Shared anime scored at most [`AGREEMENT_POINTS`] apart, best liked first, at most
+    /// [`SHARED_OPINIONS`].
+    pub agreements: Vec<SharedOpinion>,
+    /// Scored further apart, furthest first, at most [`SHARED_OPINIONS`].
+    pub disagreements: Vec<SharedOpinion>,
+}
+
+impl UserComparison {
+    /// Compares the users with dataset ids `left` and `right`; `None` unless both are in
+    /// `dataset`.
+    pub fn new(dataset: &Dataset, left: &str, right: &str) -> Option<Self> {
+        let find = |user_id: &str| dataset.users.iter().find(|user| user.user_id == user_id);
+        let (on_left, on_right) = (find(left)?, find(right)?);
+        let right_scores: HashMap<u32, f64> = on_right
+            .ratings
+            .iter()
+            .map(|rating| (rating.anime_id, rating.raw_score))
+            .collect();
+        let left_anime: HashSet<u32> = on_left.ratings.iter().map(|r| r.anime_id).collect();
+        let mut shared: Vec<SharedOpinion> = on_left
+            .ratings
+            .iter()
+            .filter_map(|rating| {
+                Some(SharedOpinion {
+                    anime_id: rating.anime_id,
+                    title: rating.title.clone(),
+                    left: rating.raw_score,
+                    right: *right_scores.get(&rating.anime_id)?,
+                })
+            })
+            .collect();
+        let union = left_anime.len() + right_scores.len() - shared.len();
+        let mut comparison = Self {
+            left: left.to_string(),
+            right: right.to_string(),
+            left_ratings: on_left.ratings.len(),
+            right_ratings: on_right.ratings.len(),
+            shared: shared.len(),
+            jaccard: if union == 0 {
+                0.0
+            } else {
+                shared.len() as f64 / union as f64
+            },
+            pearson: pearson(&shared),
+            ..Self::default()
+        };
+        shared.sort_by(|a, b| {
+            b.difference()
+                .total_cmp(&a.difference())
+                .then(a.anime_id.cmp(&b.anime_id))
+        });
+        let split = shared
+            .iter()
+            .position(|opinion| opinion.difference() <= AGREEMENT_POINTS)
+            .unwrap_or(shared.len());
+        let mut agreements = shared.split_off(split);
+        shared.truncate(SHARED_OPINIONS);
+        comparison.disagreements = shared;
+        agreements.sort_by(|a, b| {
+            (b.left + b.right)
+                .total_cmp(&(a.left + a.right))
+                .then(a.anime_id.cmp(&b.anime_id))
+        });
+        agreements.truncate(SHARED_OPINIONS);
+        comparison.agreements = agreements;
+        Some(comparison)
+    }
+}
+
+/// How spread out one anime's scores are.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreSpread {
+    pub anime_id: u32,
+    pub title: String,
+    pub ratings: usize,
+    /// Mean raw score, on the 1–10 scale.
+    pub mean: f64,
+    pub variance: f64,
+}
+
+/// The dataset's most polarizing anime and its strongest consensus picks.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ControversyRankings {
+    /// Highest variance first, at most [`RANKED_SPREADS`].
+    pub polarizing: Vec<ScoreSpread>,
+    /// Lowest variance first, the better rated first among equals, at most [`RANKED_SPREADS`].
+    pub consensus: Vec<ScoreSpread>,
+}
+
+impl ControversyRankings {
+    /// Ranks the anime in `dataset` rated by at least `min_raters` users.
+    pub fn new(dataset: &Dataset, min_raters: usize) -> Self {
+        let mut anime: HashMap<u32, (&str, Vec<f64>)> = HashMap::new();
+        for rating in dataset.users.iter().flat_map(|user| &user.ratings) {
+            anime
+                .entry(rating.anime_id)
+                .or_insert((&rating.title, Vec::new()))
+                .1
+                .push(rating.raw_score);
+        }
+        let mut spreads: Vec<ScoreSpread> = anime
+            .into_iter()
+            .filter(|(_, (_, scores))| scores.len() >= min_raters.max(1))
+            .map(|(anime_id, (title, scores))| {
+                let mean = mean(scores.iter().sum(), scores.len());
+                let variance = self::mean(
+                    scores.iter().map(|score| (score - mean).powi(2)).sum(),
+                    scores.len(),
+                );
+                ScoreSpread {
+                    anime_id,
+                    title: title.to_string(),
+                    ratings: scores.len(),
+                    mean,
+                    variance,
+                }
+            })
+            .collect();
+        spreads.sort_by(|a, b| {
+            b.variance
+                .total_cmp(&a.variance)
+                .then(a.anime_id.cmp(&b.anime_id))
+        });
+        let polarizing = spreads.iter().take(RANKED_SPREADS).cloned().collect();
+        spreads.sort_by(|a, b| {
+            a.variance
+                .total_cmp(&b.variance)
+                .then(b.mean.total_cmp(&a.mean))
+                .then(a.anime_id.cmp(&b.anime_id))
+        });
+        spreads.truncate(RANKED_SPREADS);
+        Self {
+            polarizing,
+            consensus: spreads,
+        }
+    }
+}
+
+/// A user's liking for one genre.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenreAffinity {
+    pub genre: String,
+    pub ratings: usize,
+    /// Mean raw score the user gave anime in the genre.
+    pub mean: f64,
+}
+
+/// A score far from what everyone else gave the anime.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Opinion {
+    pub anime_id: u32,
+    pub title: String,
+    pub score: f64,
+    /// Mean score of the anime's other raters.
+    pub consensus: f64,
+}
+
+impl Opinion {
+    pub fn deviation(&self) -> f64 {
+        self.score - self.consensus
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UserAnalytics {
+    pub user_id: String,
+    pub ratings: usize,
+    /// Ratings by raw score rounded to a whole point; index 0 counts scores of 1.
+    pub scores: [usize; 10],
+    /// Mean raw score, on the 1–10 scale.
+    pub mean: f64,
+    /// Mean over every rating in the dataset.
+    pub global_mean: f64,
+    /// How far below each anime's consensus the user scores on average: positive for a harsh
+    /// rater, negative for a generous one. Counts anime with [`MIN_CONSENSUS_RATERS`] other
+    /// raters.
+    pub harshness: f64,
+    /// The user's most rated genres, at most [`AFFINITY_GENRES`].
+    pub genres: Vec<GenreAffinity>,
+    /// Biggest deviations from the consensus first, at most [`CONTRARIAN`].
+    pub contrarian: Vec<Opinion>,
+    /// How many of the user's anime someone else rated too.
+    pub shared_anime: usize,
+    /// Anime in the whole dataset.
+    pub corpus_anime: usize,
+    /// Other users who rated at least one of the same anime.
+    pub overlapping_users: usize,
+}
+
+impl UserAnalytics {
+    /// Analytics for the user with dataset id `user_id`, or `None` without one. Genres come from
+    /// the ratings when the source had them, and from `genres` for the anime without.
+    pub fn new(
+        dataset: &Dataset,
+        user_id: &str,
+        genres: impl Fn(u32) -> Vec<String>,
+    ) -> Option<Self> {
+        let user = dataset.users.iter().find(|user| user.user_id == user_id)?;
+        let rated: HashSet<u32> = user.ratings.iter().map(|rating| rating.anime_id).collect();
+        // Anime id -> (sum of scores, raters) over everyone.
+        let mut anime: HashMap<u32, (f64, usize)> = HashMap::new();
+        let mut total = 0.0;
+        let mut count = 0_usize;
+        let mut overlapping_users = 0;
+        for other in &dataset.users {
+            for rating in &other.ratings {
+                let entry = anime.entry(rating.anime_id).or_default();
+                entry.0 += rating.raw_score;
+                entry.1 += 1;
+                total += rating.raw_score;
+            }
+            count += other.ratings.len();
+            if other.user_id != user.user_id
+                && other
+                    .ratings
+                    .iter()
+                    .any(|rating| rated.contains(&rating.anime_id))
+            {
+                overlapping_users += 1;
+            }
+        }
+
+        let mut analytics = Self {
+            user_id: user_id.to_string(),
+            ratings: user.ratings.len(),
+            global_mean: mean(total, count),
+            corpus_anime: anime.len(),
+            overlapping_users,
+            ..Self::default()
+        };
+        let mut genre_scores: HashMap<String, (f64, usize)> = HashMap::new();
+        let mut opinions = Vec::new();
+        let mut deviations = 0.0;
+        for rating in &user.ratings {
+            analytics.scores[score_bucket(rating.raw_score)] += 1;
+            let known = if rating.genres.is_empty() {
+                genres(rating.anime_id)
+            } else {
+                rating.genres.clone()
+            };
+            for genre in known {
+                let entry = genre_scores.entry(genre).or_default();
+                entry.0 += rating.raw_score;
+                entry.1 += 1;
+            }
+            let (sum, raters) = anime[&rating.anime_id];
+            let others = raters - 1;
+            if others > 0 {
+                analytics.shared_anime += 1;
+            }
+            if others >= MIN_CONSENSUS_RATERS {
+                let opinion = Opinion {
+                    anime_id: rating.anime_id,
+                    title: rating.title.clone(),
+                    score: rating.raw_score,
+                    consensus: (sum - rating.raw_score) / others as f64,
+                };
+                deviations += opinion.deviation();
+                opinions.push(opinion);
+            }
+        }
+        analytics.mean = mean(
+            user.ratings.iter().map(|rating| rating.raw_score).sum(),
+            user.ratings.len(),
+        );
+        analytics.harshness = -mean(deviations, opinions.len());
+
+        let mut genres: Vec<GenreAffinity> = genre_scores
+            .into_iter()
+            .map(|(genre, (sum, ratings))| GenreAffinity {
+                genre,
+                ratings,
+                mean: sum / ratings as f64,
+            })
+            .collect();
+        genres.sort_by(|a, b| {
+            b.ratings
+                .cmp(&a.ratings)
+                .then_with(|| a.genre.cmp(&b.genre))
+        });
+        genres.truncate(AFFINITY_GENRES);
+        analytics.genres = genres;
+
+        opinions.sort_by(|a, b| {
+            b.deviation()
+                .abs()
+                .total_cmp(&a.deviation().abs())
+                .then(a.anime_id.cmp(&b.anime_id))
+        });
+        opinions.truncate(CONTRARIAN);
+        analytics.contrarian = opinions;
+        Some(analytics)
+    }
+}
+
+fn mean(total: f64, count: usize) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        total / count as f64
+    }
+}
+
+fn pearson(opinions: &[SharedOpinion]) -> Option<f64> {
+    if opinions.len() < 2 {
+        return None;
+    }
+    let left_mean = mean(opinions.iter().map(|o| o.left).sum(), opinions.len());
+    let right_mean = mean(opinions.iter().map(|o| o.right).sum(), opinions.len());
+    let (mut covariance, mut left_square, mut right_square) = (0.0, 0.0, 0.0);
+    for opinion in opinions {
+        let (left, right) = (opinion.left - left_mean, opinion.right - right_mean);
+        covariance += left * right;
+        left_square += left * left;
+        right_square += right * right;
+    }
+    let spread = (left_square * right_square).sqrt();
+    (spread > 0.0).then(|| covariance / spread)
+}
+
+/// The `YYYY-MM` an ISO 8601 date or timestamp starts with.
+fn month(date: &str) -> Option<&str> {
+    let month = date.get(..7)?;
+    let (year, number) = month.split_once('-')?;
+    (year.len() == 4
+        && year
+            .bytes()
+            .chain(number.bytes())
+            .all(|b| b.is_ascii_digit()))
+    .then_some(month)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Rating, UserRatings};
+
+    fn user(user_id: &str, ratings: &[(u32, f64, Option<&str>)]) -> UserRatings {
+        UserRatings {
+            user_id: user_id.to_string(),
+            ratings: ratings
+                .iter()
+                .map(|&(anime_id, score, date)| {
+                    let mut rating = Rating::new(anime_id, format!("Anime {anime_id}"), score);
+                    rating.updated_at = date.map(str::to_string);
+                    rating
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn summarizes_one_anime_against_the_dataset() {
+        let dataset = Dataset::new(vec![
+            user("a", &[(1, 10.0, Some("2024-01-03")), (2, 4.0, None)]),
+            user(
+                "b",
+                &[(1, 6.0, Some("2024-02-10T12:00:00Z")), (2, 8.0, None)],
+            ),
+            user("c", &[(3, 8.0, None)]),
+        ]);
+        let analytics = AnimeAnalytics::new(&dataset, 1);
+        assert_eq!(analytics.ratings, 2);
+        assert_eq!(analytics.scores[9], 1);
+        assert!((analytics.mean - 8.0).abs() < 1e-9);
+        assert!((analytics.global_mean - 7.2).abs() < 1e-9);
+        assert!((analytics.controversy - 4.0).abs() < 1e-9);
+        assert_eq!(analytics.partners.len(), 1);
+        assert_eq!(analytics.partners[0].shared, 2);
+        let months: Vec<_> = analytics.trend.iter().map(|m| m.month.as_str()).collect();
+        assert_eq!(months, ["2024-01", "2024-02"]);
+    }
+
+    #[test]
+    fn user_analytics_finds_harsh_and_contrarian_scores() {
+        let dataset = Dataset::new(vec![
+            user("harsh", &[(1, 2.0, None), (2, 6.0, None)]),
+            user("b", &[(1, 9.0, None), (2, 7.0, None)]),
+            user("c", &[(1, 9.0, None), (2, 7.0, None)]),
+            user("d", &[(1, 9.0, None), (2, 7.0, None), (3, 5.0, None)]),
+        ]);
+        let analytics = UserAnalytics::new(&dataset, "harsh", |_| vec!["Drama".to_string()])
+            .expect("the user is in the dataset");
+        assert_eq!(analytics.ratings, 2);
+        assert!((analytics.mean - 4.0).abs() < 1e-9);
+        assert!((analytics.harshness - 4.0).abs() < 1e-9);
+        assert_eq!(analytics.contrarian[0].anime_id, 1);
+        assert!((analytics.contrarian[0].deviation() + 7.0).abs() < 1e-9);
+        assert_eq!(analytics.genres[0].ratings, 2);
+        assert_eq!(analytics.shared_anime, 2);
+        assert_eq!(analytics.corpus_anime, 3);
+        assert_eq!(analytics.overlapping_users, 3);
+        assert!(UserAnalytics::new(&dataset, "nobody", |_| Vec::new()).is_none());
+    }
+
+    #[test]
+    fn compares_two_anime_through_their_shared_raters() {
+        let mut dataset = Dataset::new(vec![
+            user("a", &[(1, 9.0, None), (2, 5.0, None)]),
+            user("b", &[(1, 6.0, None), (2, 8.0, None), (3, 10.0, None)]),
+            user("c", &[(1, 7.0, None)]),
+        ]);
+        dataset.users[0].ratings[0].genres = vec!["Drama".to_string(), "Romance".to_string()];
+        let comparison = AnimeComparison::new(&dataset, 1, 2, |_| vec!["Drama".to_string()]);
+        assert_eq!((comparison.left_ratings, comparison.right_ratings), (3, 2));
+        assert_eq!(comparison.shared_raters, 2);
+        assert_eq!((comparison.prefer_left, comparison.prefer_right), (1, 1));
+        assert!((comparison.left_mean - 7.5).abs() < 1e-9);
+        // a: +2 and -2 around 7; b: -2 and 0 around 8.
+        assert!((comparison.left_relative - 0.0).abs() < 1e-9);
+        assert!((comparison.right_relative + 1.0).abs() < 1e-9);
+        assert!((comparison.similarity + 0.5).abs() < 1e-9);
+        assert_eq!(comparison.parts[0].user_id, "b");
+        assert_eq!(comparison.shared_genres, ["Drama"]);
+        assert_eq!(comparison.left_genres, ["Romance"]);
+        assert!((comparison.genre_overlap() - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn compares_two_users_tastes() {
+        let dataset = Dataset::new(vec![
+            user(
+                "a",
+                &[
+                    (1, 9.0, None),
+                    (2, 7.0, None),
+                    (3, 2.0, None),
+                    (4, 8.0, None),
+                ],
+            ),
+            user(
+                "b",
+                &[
+                    (1, 10.0, None),
+                    (2, 7.0, None),
+                    (3, 9.0, None),
+                    (5, 6.0, None),
+                ],
+            ),
+        ]);
+        let comparison = UserComparison::new(&dataset, "a", "b").expect("both are in the dataset");
+        assert_eq!(comparison.shared, 3);
+        assert!((comparison.jaccard - 0.6).abs() < 1e-9);
+        let agreed: Vec<_> = comparison.agreements.iter().map(|o| o.anime_id).collect();
+        assert_eq!(agreed, [1, 2]);
+        assert_eq!(comparison.disagreements[0].anime_id, 3);
+        // Two close scores and one far apart: barely correlated.
+        assert!(comparison.pearson.is_some_and(|r| r > 0.0 && r < 0.2));
+        assert!(UserComparison::new(&dataset, "a", "nobody").is_none());
+    }
+
+    #[test]
+    fn ranks_spreads_among_anime_with_enough_raters() {
+        let dataset = Dataset::new(vec![
+            user(
+                "a",
+                &[
+                    (1, 1.0, None),
+                    (2, 8.0, None),
+                    (3, 7.0, None),
+                    (4, 1.0, None),
+                ],
+            ),
+            user("b", &[(1, 10.0, None), (2, 8.0, None), (3, 7.0, None)]),
+            user("c", &[(1, 7.0, None), (2, 8.0, None), (3, 7.0, None)]),
+        ]);
+        let rankings = ControversyRankings::new(&dataset, 3);
+        let polarizing: Vec<_> = rankings.polarizing.iter().map(|s| s.anime_id).collect();
+        assert_eq!(polarizing, [1, 2, 3]);
+        // Equally unanimous, so the better rated comes first; anime 4 has one rater.
+        let consensus: Vec<_> = rankings.consensus.iter().map(|s| s.anime_id).collect();
+        assert_eq!(consensus, [2, 3, 1]);
+        assert_eq!(rankings.polarizing[0].ratings, 3);
+        assert!((rankings.polarizing[0].variance - 14.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn months_need_a_year_and_month() {
+        assert_eq!(month("2024-05-01"), Some("2024-05"));
+        assert_eq!(month("May 2024"), None);
+        assert_eq!(month("2024"), None);
+    }
+}
@@ -0,0 +1,191 @@
+//! Fitting recommendations into a time budget ("I have 12 hours"): the set of whole
+//! candidates, shows and movies watched start to finish, whose summed recommendation scores are
+//! highest without their running times adding up to more than the budget. It is the 0/1
+//! knapsack problem, solved exactly by dynamic programming over the budget in minutes, or in
+//! coarser steps when the budget and candidate list are large; lengths are rounded up to a
+//! step, so a plan never runs over.
+
+use serde::{Deserialize, Serialize};
+
+/// Cells of the knapsack table at most, about 8 MB.
+const MAX_CELLS: usize = 8_000_000;
+
+/// What a candidate has to be to count.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    Any,
+    /// Single-episode anime only.
+    Movies,
+    /// Anime with more than one episode only.
+    Series,
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [Self::Any, Self::Movies, Self::Series];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Any => "Shows and movies",
+            Self::Movies => "Movies only",
+            Self::Series => "Series only",
+        }
+    }
+
+    fn allows(self, movie: bool) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Movies => movie,
+            Self::Series => !movie,
+        }
+    }
+}
+
+/// One recommendation to fit in, with everything it takes to watch it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetCandidate {
+    pub anime_id: u32,
+    pub title: String,
+    /// Predicted enjoyment; only positive scores are worth time.
+    pub score: f64,
+    /// Running time, including anything that has to be watched first.
+    pub minutes: u32,
+    pub movie: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimeBudget {
+    pub minutes: u32,
+    /// The longest a single candidate may run; `None` for no limit.
+    pub longest: Option<u32>,
+    pub format: Format,
+}
+
+/// The candidates picked, in the order they were given.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetPlan {
+    pub picks: Vec<BudgetCandidate>,
+    pub minutes: u32,
+    pub score: f64,
+}
+
+/// The candidates with the highest total score that fit `budget`.
+pub fn fit(candidates: &[BudgetCandidate], budget: &TimeBudget) -> BudgetPlan {
+    let eligible: Vec<&BudgetCandidate> = candidates
+        .iter()
+        .filter(|candidate| {
+            candidate.score > 0.0
+                && candidate.minutes > 0
+                && candidate.minutes <= budget.minutes
+                && budget
+                    .longest
+                    .is_none_or(|longest| candidate.minutes <= longest)
+                && budget.format.allows(candidate.movie)
+        })
+        .collect();
+    if eligible.is_empty() {
+        return BudgetPlan::default();
+    }
+
+    let minutes = budget.minutes as usize;
+    let step = (minutes * eligible.len()).div_ceil(MAX_CELLS).max(1);
+    let capacity = minutes / step;
+    let weight = |candidate: &BudgetCandidate| (candidate.minutes as usize).div_ceil(step);
+    // best[w]: the highest score within w steps; taken[i][w]: whether candidate i is in it.
+    let mut best = vec![0.0_f64; capacity + 1];
+    let mut taken = vec![vec![false; capacity + 1]; eligible.len()];
+    for (index, candidate) in eligible.iter().enumerate() {
+        let weight = weight(candidate);
+        for w in (weight..=capacity).rev() {
+            let with = best[w - weight] + candidate.score;
+            if with > best[w] {
+                best[w] = with;
+                taken[index][w] = true;
+            }
+        }
+    }
+
+    let mut picked = vec![false; eligible.len()];
+    let mut w = capacity;
+    for index in (0..eligible.len()).rev() {
+        if taken[index][w] {
+            picked[index] = true;
+            w -= weight(eligible[index]);
+        }
+    }
+    let picks: Vec<BudgetCandidate> = eligible
+        .into_iter()
+        .zip(picked)
+        .filter(|&(_, picked)| picked)
+        .map(|(candidate, _)| candidate.clone())
+        .collect();
+    BudgetPlan {
+        minutes: picks.iter().map(|pick| pick.minutes).sum(),
+        score: picks.iter().map(|pick| pick.score).sum(),
+        picks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(anime_id: u32, score: f64, minutes: u32, movie: bool) -> BudgetCandidate {
+        BudgetCandidate {
+            anime_id,
+            title: format!("Anime {anime_id}"),
+            score,
+            minutes,
+            movie,
+        }
+    }
+
+    fn picked(plan: &BudgetPlan) -> Vec<u32> {
+        plan.picks.iter().map(|pick| pick.anime_id).collect()
+    }
+
+    #[test]
+    fn the_best_set_that_fits_beats_the_best_single_pick() {
+        let candidates = [
+            // The top pick alone would use up ten of twelve hours.
+            candidate(1, 3.0, 600, false),
+            candidate(2, 2.0, 288, false),
+            candidate(3, 1.8, 300, false),
+            candidate(4, 1.5, 110, true),
+            candidate(5, -1.0, 30, true),
+            candidate(6, 9.0, 2000, false),
+        ];
+        let twelve_hours = TimeBudget {
+            minutes: 12 * 60,
+            ..TimeBudget::default()
+        };
+        let plan = fit(&candidates, &twelve_hours);
+        assert_eq!(picked(&plan), [2, 3, 4]);
+        assert_eq!(plan.minutes, 698);
+        assert!((plan.score - 5.3).abs() < 1e-9);
+
+        let movies = TimeBudget {
+            format: Format::Movies,
+            ..twelve_hours
+        };
+        assert_eq!(picked(&fit(&candidates, &movies)), [4]);
+        let short = TimeBudget {
+            longest: Some(295),
+            ..twelve_hours
+        };
+        assert_eq!(picked(&fit(&candidates, &short)), [2, 4]);
+
+        // A budget too large for one-minute steps still never runs over.
+        let many: Vec<_> = (0..2000)
+            .map(|i| candidate(i, 1.0 + f64::from(i % 7), 23 + i % 300, false))
+            .collect();
+        let week = TimeBudget {
+            minutes: 7 * 24 * 60,
+            ..TimeBudget::default()
+        };
+        assert!(fit(&many, &week).minutes <= week.minutes);
+    }
+}
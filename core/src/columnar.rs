@@ -0,0 +1,211 @@
+//! Parquet dataset format (behind the `parquet` feature).
+//!
+//! Ratings are stored as one flat table — `user_id`, `anime_id`, `title`, `raw_score`,
+//! `normalized_score` — with user ids and titles dictionary-encoded, so a large corpus is a
+//! fraction of its JSON size and loads without text parsing. Normalized scores are recomputed
+//! by the graph builder, so reading prunes that column. Files hold scores already converted
+//! to 1–10, since they are written from loaded datasets; [`crate::dataset::read`] checks them
+//! against that scale like any other rating.
+
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow_array::{Array, Float64Array, RecordBatch, StringArray, UInt32Array};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::{ArrowWriter, ProjectionMask};
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use parquet::file::reader::ChunkReader;
+
+use crate::scale::ScoreScale;
+use crate::{Dataset, DatasetError, Rating, UserRatings};
+
+pub const EXTENSION: &str = "parquet";
+/// Scale of the `raw_score` column.
+pub const SCORE_SCALE: ScoreScale = ScoreScale::Ten;
+const BATCH_ROWS: usize = 64 * 1024;
+/// Columns needed to rebuild a dataset; `normalized_score` is recomputed after loading.
+const READ_COLUMNS: [&str; 4] = ["user_id", "anime_id", "title", "raw_score"];
+
+fn schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("user_id", DataType::Utf8, false),
+        Field::new("anime_id", DataType::UInt32, false),
+        Field::new("title", DataType::Utf8, false),
+        Field::new("raw_score", DataType::Float64, false),
+        Field::new("normalized_score", DataType::Float64, false),
+    ]))
+}
+
+fn parse_error(err: impl std::fmt::Display) -> DatasetError {
+    DatasetError::Parse(err.to_string())
+}
+
+/// Reads a Parquet dataset, from a memory map of the file when built with the `mmap` feature
+/// so row groups are decoded in place rather than read into buffers first.
+pub fn read(path: &Path) -> Result<Dataset, DatasetError> {
+    #[cfg(feature = "mmap")]
+    if let Some(map) = crate::compression::map(path)? {
+        return read_from(bytes::Bytes::from_owner(map));
+    }
+    read_from(File::open(path)?)
+}
+
+fn read_from(source: impl ChunkReader + 'static) -> Result<Dataset, DatasetError> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(source).map_err(parse_error)?;
+    let file_schema = builder.parquet_schema();
+    let indices = READ_COLUMNS
+        .iter()
+        .map(|name| {
+            file_schema
+                .columns()
+                .iter()
+                .position(|column| column.name() == *name)
+                .ok_or_else(|| DatasetError::Parse(format!("missing column {name}")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mask = ProjectionMask::leaves(file_schema, indices);
+    let reader = builder
+        .with_projection(mask)
+        .with_batch_size(BATCH_ROWS)
+        .build()
+        .map_err(parse_error)?;
+
+    // Rows are written grouped by user, so consecutive rows with the same id form one user.
+    let mut users: Vec<UserRatings> = Vec::new();
+    for batch in reader {
+        let batch = batch.map_err(parse_error)?;
+        let user_ids: &StringArray = column(&batch, "user_id")?;
+        let anime_ids: &UInt32Array = column(&batch, "anime_id")?;
+        let titles: &StringArray = column(&batch, "title")?;
+        let scores: &Float64Array = column(&batch, "raw_score")?;
+        for row in 0..batch.num_rows() {
+            let user_id = user_ids.value(row);
+            if users.last().is_none_or(|user| user.user_id != user_id) {
+                users.push(UserRatings {
+                    user_id: user_id.to_string(),
+                    ratings: Vec::new(),
+                });
+            }
+            let user = users.last_mut().expect("a user was just pushed");
+            user.ratings.push(Rating::new(
+                anime_ids.value(row),
+                titles.value(row).to_string(),
+                scores.value(row),
+            ));
+        }
+    }
+
+    if users.is_empty() {
+        return Err(DatasetError::Empty);
+    }
+    Ok(Dataset::new(users))
+}
+
+fn column<'a, T: Array + 'static>(
+    batch: &'a RecordBatch,
+    name: &str,
+) -> Result<&'a T, DatasetError> {
+    batch
+        .column_by_name(name)
+        .and_then(|column| column.as_any().downcast_ref::<T>())
+        .ok_or_else(|| DatasetError::Parse(format!("column {name} has an unexpected type")))
+}
+
+/// Writes `dataset` (with normalized scores already filled in) as zstd-compressed Parquet.
+pub fn write(path: &Path, dataset: &Dataset) -> Result<(), DatasetError> {
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::ZSTD(ZstdLevel::default()))
+        .build();
+    let schema = schema();
+    let mut writer = ArrowWriter::try_new(File::create(path)?, schema.clone(), Some(properties))
+        .map_err(parse_error)?;
+
+    let rows = dataset
+        .users
+        .iter()
+        .flat_map(|user| user.ratings.iter().map(move |rating| (user, rating)));
+    let mut rows = rows.peekable();
+    while rows.peek().is_some() {
+        let chunk: Vec<_> = rows.by_ref().take(BATCH_ROWS).collect();
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from_iter_values(
+                    chunk.iter().map(|(user, _)| user.user_id.as_str()),
+                )),
+                Arc::new(UInt32Array::from_iter_values(
+                    chunk.iter().map(|(_, rating)| rating.anime_id),
+                )),
+                Arc::new(StringArray::from_iter_values(
+                    chunk.iter().map(|(_, rating)| rating.title.as_str()),
+                )),
+                Arc::new(Float64Array::from_iter_values(
+                    chunk.iter().map(|(_, rating)| rating.raw_score),
+                )),
+                Arc::new(Float64Array::from_iter_values(
+                    chunk.iter().map(|(_, rating)| rating.normalized_score),
+                )),
+            ],
+        )
+        .map_err(parse_error)?;
+        writer.write(&batch).map_err(parse_error)?;
+    }
+    writer.close().map_err(parse_error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dataset, MergeStrategy};
+
+    #[test]
+    fn datasets_round_trip_through_parquet() {
+        let user = |user_id: &str, ratings: &[(u32, &str, f64)]| UserRatings {
+            user_id: user_id.to_string(),
+            ratings: ratings
+                .iter()
+                .map(|&(anime_id, title, score)| Rating::new(anime_id, title.to_string(), score))
+                .collect(),
+        };
+        let mut written = Dataset::new(vec![
+            user("a", &[(1, "Monster", 9.0), (2, "Mushishi", 6.5)]),
+            user("b", &[(1, "Monster", 7.0)]),
+        ]);
+        written.normalize_scores();
+        let path =
+            std::env::temp_dir().join(format!("wasiw-columnar-{}.{EXTENSION}", std::process::id()));
+        write(&path, &written).unwrap();
+
+        let read_back = read(&path).unwrap();
+        let rows = |dataset: &Dataset| -> Vec<(String, u32, String, f64)> {
+            dataset
+                .users
+                .iter()
+                .flat_map(|user| {
+                    user.ratings.iter().map(|rating| {
+                        (
+                            user.user_id.clone(),
+                            rating.anime_id,
+                            rating.title.clone(),
+                            rating.raw_score,
+                        )
+                    })
+                })
+                .collect()
+        };
+        assert_eq!(rows(&read_back), rows(&written));
+        // The normalized column isn't read back; the builder recomputes it.
+        assert!(read_back.users[0]
+            .ratings
+            .iter()
+            .all(|rating| rating.normalized_score == 0.0));
+        let (loaded, report) = dataset::read(&path, MergeStrategy::default()).unwrap();
+        assert_eq!(loaded.users.len(), 2);
+        assert!(report.is_clean());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
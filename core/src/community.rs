@@ -0,0 +1,288 @@
+//! Community detection by label propagation over the similarity edges, and collapsing
+//! communities into super-nodes for zoomed-out views.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{EdgeKind, GraphModel, NodeType, RenderEdge};
+
+const LABEL_PROPAGATION_ROUNDS: usize = 20;
+
+/// Community assignment for every node in a graph.
+#[derive(Debug, Clone, Default)]
+pub struct Communities {
+    /// Community index per node, parallel to `GraphModel::nodes`.
+    pub node_community: Vec<usize>,
+    pub members: Vec<Vec<usize>>,
+}
+
+impl Communities {
+    pub fn count(&self) -> usize {
+        self.members.len()
+    }
+}
+
+/// Groups anime, or genres, with weighted label propagation over similarity edges, then places
+/// each user in the community most of their rated anime belong to.
+#[tracing::instrument(level = "debug", skip_all)]
+pub fn detect(graph: &GraphModel) -> Communities {
+    let node_count = graph.nodes.len();
+    let similar = |node: usize| {
+        graph
+            .links(node)
+            .filter(|(_, edge)| edge.kind == EdgeKind::Similarity)
+            // Negative pair scores still mean "co-rated", so they keep a small base weight.
+            .map(|(other, edge)| (other, 1.0 + edge.weight.max(0.0)))
+    };
+
+    let mut labels: Vec<usize> = (0..node_count).collect();
+    for _ in 0..LABEL_PROPAGATION_ROUNDS {
+        let mut changed = false;
+        for node in 0..node_count {
+            if graph.nodes[node].node_type == NodeType::User {
+                continue;
+            }
+            let best = strongest_label(similar(node).map(|(n, w)| (labels[n], w)));
+            if let Some(best) = best {
+                if best != labels[node] {
+                    labels[node] = best;
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    for node in 0..node_count {
+        if graph.nodes[node].node_type == NodeType::User {
+            let votes = graph
+                .links(node)
+                .filter(|(n, edge)| {
+                    edge.kind == EdgeKind::Rating && graph.nodes[*n].node_type == NodeType::Anime
+                })
+                .map(|(n, _)| (labels[n], 1.0));
+            if let Some(best) = strongest_label(votes) {
+                labels[node] = best;
+            }
+        }
+    }
+
+    let mut index_of_label: HashMap<usize, usize> = HashMap::new();
+    let mut members: Vec<Vec<usize>> = Vec::new();
+    let mut node_community = vec![0; node_count];
+    for (node, label) in labels.into_iter().enumerate() {
+        let community = *index_of_label.entry(label).or_insert_with(|| {
+            members.push(Vec::new());
+            members.len() - 1
+        });
+        members[community].push(node);
+        node_community[node] = community;
+    }
+
+    Communities {
+        node_community,
+        members,
+    }
+}
+
+/// Label with the largest summed weight; ties go to the smallest label so runs are deterministic.
+fn strongest_label(votes: impl Iterator<Item = (usize, f64)>) -> Option<usize> {
+    let mut totals: HashMap<usize, f64> = HashMap::new();
+    for (label, weight) in votes {
+        *totals.entry(label).or_insert(0.0) += weight;
+    }
+    totals
+        .into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(label, _)| label)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuperNode {
+    pub community: usize,
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    pub member_count: usize,
+}
+
+/// What to draw when some communities are collapsed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClusterScene {
+    pub super_nodes: Vec<SuperNode>,
+    /// Individual nodes that stay visible because their community is expanded.
+    pub visible: HashSet<usize>,
+    pub edges: Vec<RenderEdge>,
+}
+
+/// Collapses every community not in `expanded` into a super-node at its members' centroid and
+/// merges edges between collapsed communities into one edge per community pair.
+pub fn aggregate(
+    graph: &GraphModel,
+    communities: &Communities,
+    expanded: &HashSet<usize>,
+    min_stroke_width: f32,
+) -> ClusterScene {
+    let mut scene = ClusterScene::default();
+    let mut centers = vec![(0.0_f32, 0.0_f32); communities.count()];
+
+    for (community, members) in communities.members.iter().enumerate() {
+        let members: Vec<usize> = members
+            .iter()
+            .copied()
+            .filter(|n| !graph.nodes[*n].hidden)
+            .collect();
+        if expanded.contains(&community) {
+            scene.visible.extend(members);
+            continue;
+        }
+        if members.is_empty() {
+            continue;
+        }
+        let count = members.len().max(1) as f32;
+        let x = members.iter().map(|n| graph.nodes[*n].x).sum::<f32>() / count;
+        let y = members.iter().map(|n| graph.nodes[*n].y).sum::<f32>() / count;
+        centers[community] = (x, y);
+        scene.super_nodes.push(SuperNode {
+            community,
+            x,
+            y,
+            radius: 6.0 + (members.len() as f32).sqrt() * 3.0,
+            member_count: members.len(),
+        });
+    }
+
+    // Endpoint of an edge: either a visible node position or its community's super-node.
+    let endpoint = |node: usize| -> (Option<usize>, f32, f32) {
+        let community = communities.node_community[node];
+        if expanded.contains(&community) {
+            (None, graph.nodes[node].x, graph.nodes[node].y)
+        } else {
+            let (x, y) = centers[community];
+            (Some(community), x, y)
+        }
+    };
+
+    let mut merged: HashMap<(usize, usize, EdgeKind), (usize, RenderEdge)> = HashMap::new();
+    for (index, edge) in graph
+        .edges
+        .iter()
+        .enumerate()
+        .filter(|(_, edge)| graph.is_edge_visible(edge))
+    {
+        let (source_cluster, x1, y1) = endpoint(edge.source);
+        let (target_cluster, x2, y2) = endpoint(edge.target);
+        match (source_cluster, target_cluster) {
+            (None, None) => scene.edges.push(RenderEdge {
+                x1,
+                y1,
+                x2,
+                y2,
+                kind: edge.kind,
+                stroke_width: edge.stroke_width.max(min_stroke_width),
+                edge: Some(index),
+            }),
+            (Some(a), Some(b)) if a == b => {}
+            _ => {
+                // Key by the collapsed side(s) so edges into one cluster from the same node merge.
+                let source_key = source_cluster.unwrap_or(usize::MAX - edge.source);
+                let target_key = target_cluster.unwrap_or(usize::MAX - edge.target);
+                let key = (
+                    source_key.min(target_key),
+                    source_key.max(target_key),
+                    edge.kind,
+                );
+                let first = RenderEdge {
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    kind: edge.kind,
+                    stroke_width: 0.0,
+                    edge: None,
+                };
+                merged.entry(key).or_insert((0, first)).0 += 1;
+            }
+        }
+    }
+
+    for (count, mut edge) in merged.into_values() {
+        edge.stroke_width = (0.6 + (count as f32).ln_1p() * 0.8)
+            .clamp(0.6, 6.0)
+            .max(min_stroke_width);
+        scene.edges.push(edge);
+    }
+
+    scene
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_graph, Dataset, Rating, UserRatings, Viewport};
+
+    /// Two groups of users with no anime in common: `a*` rate 1–3 and `b*` rate 11–13.
+    fn two_circles() -> GraphModel {
+        let user = |user_id: &str, first: u32| UserRatings {
+            user_id: user_id.to_string(),
+            ratings: (first..first + 3)
+                .map(|anime_id| Rating::new(anime_id, format!("Anime {anime_id}"), 8.0))
+                .collect(),
+        };
+        build_graph(
+            Dataset::new(vec![
+                user("a1", 1),
+                user("a2", 1),
+                user("b1", 11),
+                user("b2", 11),
+            ]),
+            Viewport::default(),
+        )
+    }
+
+    #[test]
+    fn disconnected_circles_become_separate_communities() {
+        let graph = two_circles();
+        let communities = detect(&graph);
+        assert_eq!(communities.count(), 2);
+        let community = |id: &str| {
+            let node = graph.nodes.iter().position(|node| node.id == id).unwrap();
+            communities.node_community[node]
+        };
+        assert_eq!(community("anime:1"), community("anime:3"));
+        assert_eq!(community("user:a1"), community("anime:2"));
+        assert_eq!(community("user:b2"), community("anime:13"));
+        assert_ne!(community("anime:1"), community("anime:11"));
+    }
+
+    #[test]
+    fn collapsed_communities_become_super_nodes_with_merged_edges() {
+        let mut graph = two_circles();
+        graph.communities = detect(&graph);
+        let communities = graph.communities.clone();
+
+        let collapsed = aggregate(&graph, &communities, &HashSet::new(), 0.0);
+        assert_eq!(collapsed.super_nodes.len(), 2);
+        assert!(collapsed.visible.is_empty());
+        assert_eq!(
+            collapsed
+                .super_nodes
+                .iter()
+                .map(|node| node.member_count)
+                .sum::<usize>(),
+            graph.nodes.len()
+        );
+        // Every edge stays inside its community.
+        assert!(collapsed.edges.is_empty());
+
+        let expanded = aggregate(&graph, &communities, &HashSet::from([0]), 1.5);
+        assert_eq!(expanded.super_nodes.len(), 1);
+        assert_eq!(expanded.visible.len(), communities.members[0].len());
+        assert!(!expanded.edges.is_empty());
+        assert!(expanded
+            .edges
+            .iter()
+            .all(|edge| edge.edge.is_some() && edge.stroke_width >= 1.5));
+    }
+}
@@ -0,0 +1,213 @@
+//! Transparent gzip/zstd support for dataset and export files.
+//!
+//! Reading sniffs the magic bytes rather than trusting the extension, so a renamed file still
+//! loads. Writing picks the format from the extension (`.gz`, `.zst`, anything else is plain).
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+#[cfg(feature = "zstd")]
+const ZSTD_LEVEL: i32 = 9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    pub fn from_extension(path: &Path) -> Self {
+        match extension(path).as_deref() {
+            Some("gz") => Self::Gzip,
+            Some("zst") => Self::Zstd,
+            _ => Self::None,
+        }
+    }
+}
+
+/// Lower-cased extension of `path`, looking past a `.gz`/`.zst` suffix: `list.xml.gz` → `xml`.
+pub fn inner_extension(path: &Path) -> Option<String> {
+    match Compression::from_extension(path) {
+        Compression::None => extension(path),
+        _ => path.file_stem().and_then(|stem| extension(Path::new(stem))),
+    }
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+}
+
+/// Opens `path` for buffered reading, decompressing it if it starts with a gzip or zstd header.
+pub fn open(path: &Path, capacity: usize) -> io::Result<Box<dyn BufRead>> {
+    decompress(
+        BufReader::with_capacity(capacity, File::open(path)?),
+        capacity,
+    )
+}
+
+/// Wraps `reader` in a decoder if it starts with a gzip or zstd header, for data that doesn't
+/// come from a file (an upload, an HTTP body).
+pub fn decompress<'a>(
+    mut reader: impl BufRead + 'a,
+    capacity: usize,
+) -> io::Result<Box<dyn BufRead + 'a>> {
+    let header = reader.fill_buf()?;
+    let decoded: Box<dyn BufRead + 'a> = if header.starts_with(&GZIP_MAGIC) {
+        Box::new(BufReader::with_capacity(
+            capacity,
+            flate2::bufread::MultiGzDecoder::new(reader),
+        ))
+    } else if header.starts_with(&ZSTD_MAGIC) {
+        zstd_decoder(reader, capacity)?
+    } else {
+        Box::new(reader)
+    };
+    Ok(decoded)
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_decoder<'a>(
+    reader: impl BufRead + 'a,
+    capacity: usize,
+) -> io::Result<Box<dyn BufRead + 'a>> {
+    Ok(Box::new(BufReader::with_capacity(
+        capacity,
+        zstd::stream::read::Decoder::with_buffer(reader)?,
+    )))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_decoder<'a>(_: impl BufRead + 'a, _: usize) -> io::Result<Box<dyn BufRead + 'a>> {
+    Err(zstd_unsupported())
+}
+
+/// Whether `bytes` start with a gzip or zstd header.
+pub fn is_compressed(bytes: &[u8]) -> bool {
+    bytes.starts_with(&GZIP_MAGIC) || bytes.starts_with(&ZSTD_MAGIC)
+}
+
+/// The file at `path` mapped into memory, or `None` when it is empty, which can't be mapped
+/// everywhere.
+#[cfg(feature = "mmap")]
+pub fn map(path: &Path) -> io::Result<Option<memmap2::Mmap>> {
+    let file = File::open(path)?;
+    if file.metadata()?.len() == 0 {
+        return Ok(None);
+    }
+    // SAFETY: the map is only read. Another process truncating the file while it is mapped
+    // would fault those reads, the one risk of mapping files the user opens; replacing it by
+    // renaming a new file into place, as the desktop app saves its files, leaves it intact.
+    let map = unsafe { memmap2::Mmap::map(&file)? };
+    Ok(Some(map))
+}
+
+/// Reads a whole (possibly compressed) text file, for formats that only parse from a string.
+pub fn read_to_string(path: &Path) -> io::Result<String> {
+    let mut content = String::new();
+    open(path, 64 * 1024)?.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// Creates `path` and hands `write` a writer that compresses according to the extension.
+pub fn create(path: &Path, write: impl FnOnce(&mut dyn Write) -> io::Result<()>) -> io::Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    match Compression::from_extension(path) {
+        Compression::None => {
+            let mut file = file;
+            write(&mut file)?;
+            file.flush()
+        }
+        Compression::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+            write(&mut encoder)?;
+            encoder.finish()?.flush()
+        }
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => {
+            let mut encoder = zstd::stream::write::Encoder::new(file, ZSTD_LEVEL)?;
+            write(&mut encoder)?;
+            encoder.finish()?.flush()
+        }
+        #[cfg(not(feature = "zstd"))]
+        Compression::Zstd => Err(zstd_unsupported()),
+    }
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "zstd support was left out of this build",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{dataset, MergeStrategy};
+
+    const CORPUS: &str = r#"{"users": [{"userId": "a", "ratings": [{"animeId": 1, "title": "Monster", "rawScore": 9}]}]}"#;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("wasiw-compression-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn files_round_trip_in_the_format_their_extension_names() {
+        let mut names = vec!["ratings.json", "ratings.json.gz"];
+        if cfg!(feature = "zstd") {
+            names.push("ratings.json.zst");
+        }
+        for name in names {
+            let path = temp_path(name);
+            create(&path, |w| w.write_all(CORPUS.as_bytes())).unwrap();
+            let bytes = std::fs::read(&path).unwrap();
+            assert_eq!(
+                is_compressed(&bytes),
+                Compression::from_extension(&path) != Compression::None,
+                "{name}"
+            );
+            assert_eq!(read_to_string(&path).unwrap(), CORPUS, "{name}");
+            let (loaded, _) = dataset::read(&path, MergeStrategy::default()).unwrap();
+            assert_eq!(loaded.users[0].ratings[0].title, "Monster", "{name}");
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn reading_trusts_the_header_over_the_extension() {
+        let compressed = temp_path("renamed.json.gz");
+        create(&compressed, |w| w.write_all(CORPUS.as_bytes())).unwrap();
+        let renamed = temp_path("renamed.json");
+        std::fs::rename(&compressed, &renamed).unwrap();
+        assert_eq!(read_to_string(&renamed).unwrap(), CORPUS);
+        let (loaded, _) =
+            dataset::parse(&std::fs::read(&renamed).unwrap(), MergeStrategy::default()).unwrap();
+        assert_eq!(loaded.users.len(), 1);
+        std::fs::remove_file(&renamed).unwrap();
+    }
+
+    #[test]
+    fn inner_extensions_look_past_compression_suffixes() {
+        assert_eq!(
+            inner_extension(Path::new("list.XML.gz")).as_deref(),
+            Some("xml")
+        );
+        assert_eq!(
+            inner_extension(Path::new("graph.gexf.zst")).as_deref(),
+            Some("gexf")
+        );
+        assert_eq!(
+            inner_extension(Path::new("ratings.json")).as_deref(),
+            Some("json")
+        );
+        assert_eq!(inner_extension(Path::new("archive.gz")), None);
+    }
+}
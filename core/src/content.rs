@@ -0,0 +1,279 @@
+//! Content-based recommendations: anime scored by how much their genres, studios, year and
+//! mean score resemble what a user rated, weighted by how much they liked each one. This is
+//! the content backend of `ml/recommend_graph_mf.py` and needs no co-ratings, so it can rank
+//! anime nobody in the dataset has rated yet, such as a season that is still airing.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::recommend::{weight_factor, Contribution};
+use crate::{EdgeKind, GraphModel, NodeType};
+
+/// Weights of the similarity's parts, as in the ML backend.
+const GENRE_WEIGHT: f64 = 0.5;
+const STUDIO_WEIGHT: f64 = 0.2;
+const YEAR_WEIGHT: f64 = 0.2;
+const MEAN_SCORE_WEIGHT: f64 = 0.1;
+/// Years apart at which two anime stop counting as contemporaries.
+const YEAR_SPAN: f64 = 25.0;
+/// Rated anime kept as the explanation of each recommendation.
+const MAX_CONTRIBUTIONS: usize = 5;
+
+/// What content similarity compares. Genres and studios are matched case-insensitively.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContentFeatures {
+    pub genres: Vec<String>,
+    pub studios: Vec<String>,
+    pub year: Option<u32>,
+    /// Mean score on the 1–10 scale, e.g. MyAnimeList's.
+    pub mean_score: Option<f64>,
+}
+
+impl ContentFeatures {
+    fn is_empty(&self) -> bool {
+        self.genres.is_empty()
+            && self.studios.is_empty()
+            && self.year.is_none()
+            && self.mean_score.is_none()
+    }
+}
+
+/// Similarity of two anime from 0 (nothing in common) to 1.
+pub fn similarity(left: &ContentFeatures, right: &ContentFeatures) -> f64 {
+    let year = match (left.year, right.year) {
+        (Some(left), Some(right)) => (1.0 - f64::from(left.abs_diff(right)) / YEAR_SPAN).max(0.0),
+        _ => 0.0,
+    };
+    let mean_score = match (left.mean_score, right.mean_score) {
+        (Some(left), Some(right)) => (1.0 - (left - right).abs() / 10.0).max(0.0),
+        _ => 0.0,
+    };
+    GENRE_WEIGHT * jaccard(&left.genres, &right.genres)
+        + STUDIO_WEIGHT * jaccard(&left.studios, &right.studios)
+        + YEAR_WEIGHT * year
+        + MEAN_SCORE_WEIGHT * mean_score
+}
+
+fn jaccard(left: &[String], right: &[String]) -> f64 {
+    let left: HashSet<String> = left.iter().map(|item| item.to_lowercase()).collect();
+    let right: HashSet<String> = right.iter().map(|item| item.to_lowercase()).collect();
+    let union = left.union(&right).count();
+    if left.is_empty() || right.is_empty() || union == 0 {
+        return 0.0;
+    }
+    left.intersection(&right).count() as f64 / union as f64
+}
+
+/// An anime outside the graph to score, e.g. one airing this season.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub anime_id: u32,
+    pub title: String,
+    pub features: ContentFeatures,
+}
+
+/// A candidate ranked for a user, with the rated anime it resembles most.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentRecommendation {
+    pub anime_id: u32,
+    pub title: String,
+    /// Weighted mean similarity to the user's rated anime, from 0 to 1.
+    pub score: f64,
+    /// Strongest first; `edge_weight` is the content similarity.
+    pub contributions: Vec<Contribution>,
+}
+
+impl ContentRecommendation {
+    /// The `limit` most alike rated anime, e.g. `Like: Monster (0.62) | Steins;Gate (0.55)`.
+    pub fn explanation(&self, limit: usize) -> String {
+        let titles: Vec<String> = self
+            .contributions
+            .iter()
+            .take(limit)
+            .map(|c| format!("{} ({:.2})", c.title, c.edge_weight))
+            .collect();
+        if titles.is_empty() {
+            "nothing rated is alike".to_string()
+        } else {
+            format!("Like: {}", titles.join(" | "))
+        }
+    }
+}
+
+/// A rated anime in a taste profile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileEntry {
+    pub title: String,
+    pub features: ContentFeatures,
+    /// How much the user liked it, from their normalized score as in the graph scorer.
+    pub weight: f64,
+}
+
+/// What a user rated, as content features.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TasteProfile {
+    /// Every anime the user rated, with or without features.
+    pub rated: HashSet<u32>,
+    /// The rated anime `features` knew.
+    pub entries: Vec<ProfileEntry>,
+}
+
+impl TasteProfile {
+    /// The profile of the user at node `user`; `features` gives an anime's features, if known.
+    pub fn new(
+        graph: &GraphModel,
+        user: usize,
+        features: impl Fn(u32) -> Option<ContentFeatures>,
+    ) -> Self {
+        let mut profile = Self::default();
+        if graph.nodes.get(user).map(|node| node.node_type) != Some(NodeType::User) {
+            return profile;
+        }
+        for (_, edge) in graph.links(user) {
+            if edge.kind != EdgeKind::Rating || edge.source != user {
+                continue;
+            }
+            let anime = &graph.nodes[edge.target];
+            let Some(anime_id) = anime.anime_id() else {
+                continue;
+            };
+            profile.rated.insert(anime_id);
+            if let Some(features) = features(anime_id).filter(|f| !f.is_empty()) {
+                profile.entries.push(ProfileEntry {
+                    title: anime.label.clone(),
+                    features,
+                    weight: weight_factor(edge.weight),
+                });
+            }
+        }
+        profile
+    }
+
+    /// `candidates` the user hasn't rated, best first, at most `limit`.
+    #[tracing::instrument(level = "debug", skip_all, fields(candidates = candidates.len()))]
+    pub fn recommend(&self, candidates: &[Candidate], limit: usize) -> Vec<ContentRecommendation> {
+        let total_weight: f64 = self.entries.iter().map(|entry| entry.weight).sum();
+        if total_weight <= 0.0 {
+            return Vec::new();
+        }
+        let mut recommendations: Vec<ContentRecommendation> = candidates
+            .iter()
+            .filter(|candidate| {
+                !self.rated.contains(&candidate.anime_id) && !candidate.features.is_empty()
+            })
+            .map(|candidate| {
+                let mut contributions: Vec<Contribution> = self
+                    .entries
+                    .iter()
+                    .map(|entry| {
+                        let similarity = similarity(&candidate.features, &entry.features);
+                        Contribution {
+                            title: entry.title.clone(),
+                            edge_weight: similarity,
+                            weight_factor: entry.weight,
+                            weighted_score: similarity * entry.weight,
+                        }
+                    })
+                    .filter(|contribution| contribution.weighted_score > 0.0)
+                    .collect();
+                let score =
+                    contributions.iter().map(|c| c.weighted_score).sum::<f64>() / total_weight;
+                contributions.sort_by(|a, b| b.weighted_score.total_cmp(&a.weighted_score));
+                contributions.truncate(MAX_CONTRIBUTIONS);
+                ContentRecommendation {
+                    anime_id: candidate.anime_id,
+                    title: candidate.title.clone(),
+                    score,
+                    contributions,
+                }
+            })
+            .filter(|recommendation| recommendation.score > 0.0)
+            .collect();
+        recommendations.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.title.cmp(&b.title))
+        });
+        recommendations.truncate(limit);
+        recommendations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_graph, Dataset, Rating, UserRatings, Viewport};
+
+    fn features(genres: &[&str], studio: &str, year: u32) -> ContentFeatures {
+        ContentFeatures {
+            genres: genres.iter().map(|genre| genre.to_string()).collect(),
+            studios: vec![studio.to_string()],
+            year: Some(year),
+            mean_score: Some(8.0),
+        }
+    }
+
+    #[test]
+    fn similarity_weighs_genres_studios_years_and_scores() {
+        let steins_gate = features(&["Sci-Fi", "Thriller"], "White Fox", 2011);
+        let same = features(&["sci-fi", "THRILLER"], "white fox", 2011);
+        assert!((similarity(&steins_gate, &same) - 1.0).abs() < 1e-9);
+        let far = ContentFeatures {
+            genres: vec!["Comedy".to_string()],
+            year: Some(1980),
+            ..ContentFeatures::default()
+        };
+        assert_eq!(similarity(&steins_gate, &far), 0.0);
+        let half_genres = features(&["Sci-Fi", "Comedy", "Drama"], "Madhouse", 2011);
+        let expected = GENRE_WEIGHT * 0.25 + YEAR_WEIGHT + MEAN_SCORE_WEIGHT;
+        assert!((similarity(&steins_gate, &half_genres) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unrated_candidates_rank_by_their_likeness_to_what_the_user_liked() {
+        let ratings = |scores: &[(u32, f64)]| {
+            scores
+                .iter()
+                .map(|&(anime_id, score)| Rating::new(anime_id, format!("Anime {anime_id}"), score))
+                .collect()
+        };
+        let graph = build_graph(
+            Dataset::new(vec![UserRatings {
+                user_id: "a".to_string(),
+                ratings: ratings(&[(1, 10.0), (2, 4.0), (3, 7.0)]),
+            }]),
+            Viewport::default(),
+        );
+        let user = graph.find_user("a").unwrap();
+        let profile = TasteProfile::new(&graph, user, |anime_id| match anime_id {
+            1 => Some(features(&["Drama"], "Kyoto Animation", 2018)),
+            2 => Some(features(&["Comedy"], "Shaft", 2018)),
+            _ => None,
+        });
+        assert_eq!(profile.rated, HashSet::from([1, 2, 3]));
+        assert_eq!(profile.entries.len(), 2);
+
+        let candidate = |anime_id: u32, features: ContentFeatures| Candidate {
+            anime_id,
+            title: format!("New {anime_id}"),
+            features,
+        };
+        let candidates = [
+            candidate(10, features(&["Comedy"], "Shaft", 2024)),
+            candidate(11, features(&["Drama"], "Kyoto Animation", 2024)),
+            candidate(1, features(&["Drama"], "Kyoto Animation", 2018)),
+            candidate(12, ContentFeatures::default()),
+        ];
+        let picks = profile.recommend(&candidates, 10);
+        let ids: Vec<u32> = picks.iter().map(|pick| pick.anime_id).collect();
+        assert_eq!(ids, [11, 10]);
+        assert_eq!(picks[0].contributions[0].title, "Anime 1");
+        assert!(picks[0].explanation(1).starts_with("Like: Anime 1 ("));
+        assert_eq!(profile.recommend(&candidates, 1).len(), 1);
+        assert!(TasteProfile::default()
+            .recommend(&candidates, 10)
+            .is_empty());
+    }
+}
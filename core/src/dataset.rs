@@ -0,0 +1,252 @@
+//! The ratings dataset and reading it from disk.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "parquet")]
+use crate::columnar;
+use crate::merge::MergeStrategy;
+use crate::quality::{self, QualityReport};
+use crate::{compression, schema, DatasetError};
+
+/// Prefix that marks a user as a profile imported on this machine rather than part of the
+/// anonymized corpus.
+pub const LOCAL_PROFILE_PREFIX: &str = "local:";
+/// Read buffer for dataset files; large enough that parsing isn't dominated by syscalls.
+const DATASET_READ_BUFFER: usize = 256 * 1024;
+
+/// A ratings corpus in the pipeline's `anonymized-ratings.json` schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dataset {
+    #[serde(rename = "schemaVersion", default = "schema::legacy_version")]
+    pub schema_version: u32,
+    #[serde(
+        rename = "generatedAt",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub generated_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub users: Vec<UserRatings>,
+}
+
+impl Dataset {
+    /// A dataset at the current schema version.
+    pub fn new(users: Vec<UserRatings>) -> Self {
+        Self {
+            schema_version: schema::CURRENT_VERSION,
+            generated_at: None,
+            source: None,
+            users,
+        }
+    }
+
+    /// Sets each rating's normalized score to its distance from that user's mean score.
+    pub fn normalize_scores(&mut self) {
+        for user in &mut self.users {
+            let avg = if user.ratings.is_empty() {
+                0.0
+            } else {
+                user.ratings.iter().map(|r| r.raw_score).sum::<f64>() / user.ratings.len() as f64
+            };
+            for rating in &mut user.ratings {
+                rating.normalized_score = rating.raw_score - avg;
+            }
+        }
+    }
+
+    /// Every title in the corpus by anime id, for importers that only know one or the other.
+    pub fn titles(&self) -> HashMap<u32, String> {
+        self.users
+            .iter()
+            .flat_map(|user| &user.ratings)
+            .map(|rating| (rating.anime_id, rating.title.clone()))
+            .collect()
+    }
+
+    /// Adds an imported profile, replacing an earlier import with the same id.
+    pub fn merge_profile(&mut self, user: UserRatings) {
+        self.users
+            .retain(|existing| existing.user_id != user.user_id);
+        self.users.push(user);
+    }
+}
+
+/// One user's ratings. Anonymized users have a hashed id; profiles imported on this machine
+/// have an id starting with [`LOCAL_PROFILE_PREFIX`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserRatings {
+    #[serde(rename = "userId")]
+    pub user_id: String,
+    pub ratings: Vec<Rating>,
+}
+
+/// A score for one anime, on the 1–10 scale once loaded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rating {
+    #[serde(rename = "animeId")]
+    pub anime_id: u32,
+    pub title: String,
+    #[serde(rename = "rawScore")]
+    pub raw_score: f64,
+    /// `raw_score` minus the user's mean score, filled in by [`Dataset::normalize_scores`].
+    #[serde(rename = "normalizedScore", default)]
+    pub normalized_score: f64,
+    /// Watch status from the source list, e.g. `completed` (schema v2).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// When the rating was last changed, as an ISO 8601 date (schema v2).
+    #[serde(rename = "updatedAt", default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    /// Genre names, when the source provides them (schema v2).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub genres: Vec<String>,
+}
+
+impl Rating {
+    pub fn new(anime_id: u32, title: String, raw_score: f64) -> Self {
+        Self {
+            anime_id,
+            title,
+            raw_score,
+            normalized_score: 0.0,
+            status: None,
+            updated_at: None,
+            genres: Vec::new(),
+        }
+    }
+}
+
+/// Reads a dataset in the pipeline's `anonymized-ratings.json` format, optionally gzip- or
+/// zstd-compressed, or as Parquet when built with the `parquet` feature.
+///
+/// Malformed users and ratings are dropped and listed in the returned report rather than
+/// failing the load, and an anime a user rated more than once is resolved by `duplicates`.
+/// Uncompressed files are memory-mapped when built with the `mmap` feature and parsed in
+/// place, borrowing keys and strings from the map until they are kept; compressed ones are
+/// deserialized straight from a buffered decoder. Either way the file is never copied into a
+/// `String` first, so multi-hundred-MB dumps only cost the memory of the parsed ratings.
+#[tracing::instrument(level = "info", skip(duplicates), fields(path = %path.display()))]
+pub fn read(
+    path: &Path,
+    duplicates: MergeStrategy,
+) -> Result<(Dataset, QualityReport), DatasetError> {
+    #[cfg(feature = "parquet")]
+    if compression::inner_extension(path).as_deref() == Some(columnar::EXTENSION) {
+        let (dataset, report) =
+            quality::validate_dataset(columnar::read(path)?, columnar::SCORE_SCALE, duplicates);
+        if dataset.users.is_empty() {
+            return Err(DatasetError::Empty);
+        }
+        return Ok((dataset, report));
+    }
+    #[cfg(feature = "mmap")]
+    if let Some(map) = compression::map(path)? {
+        if !compression::is_compressed(&map) {
+            return from_json(serde_json::Deserializer::from_slice(&map), duplicates);
+        }
+    }
+    from_json(
+        serde_json::Deserializer::from_reader(compression::open(path, DATASET_READ_BUFFER)?),
+        duplicates,
+    )
+}
+
+/// [`read`] for a JSON dataset already in memory, such as a browser upload or an HTTP body;
+/// gzip and zstd are detected from the first bytes.
+pub fn parse(
+    bytes: &[u8],
+    duplicates: MergeStrategy,
+) -> Result<(Dataset, QualityReport), DatasetError> {
+    if !compression::is_compressed(bytes) {
+        return from_json(serde_json::Deserializer::from_slice(bytes), duplicates);
+    }
+    from_json(
+        serde_json::Deserializer::from_reader(compression::decompress(bytes, DATASET_READ_BUFFER)?),
+        duplicates,
+    )
+}
+
+fn from_json<'de, R: serde_json::de::Read<'de>>(
+    mut deserializer: serde_json::Deserializer<R>,
+    duplicates: MergeStrategy,
+) -> Result<(Dataset, QualityReport), DatasetError> {
+    let (mut dataset, report) = quality::deserialize(&mut deserializer, duplicates)?;
+    deserializer.end()?;
+    schema::migrate(&mut dataset)?;
+    if dataset.users.is_empty() {
+        return Err(DatasetError::Empty);
+    }
+    tracing::info!(
+        users = dataset.users.len(),
+        clean = report.is_clean(),
+        "dataset parsed"
+    );
+    Ok((dataset, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORPUS: &str = r#"{
+        "schemaVersion": 2,
+        "users": [
+            {"userId": "a", "ratings": [
+                {"animeId": 1, "title": "Monster", "rawScore": 9},
+                {"animeId": 2, "title": "Mushishi", "rawScore": 5, "status": "completed"},
+                {"animeId": "three", "title": "Broken", "rawScore": 7}
+            ]},
+            {"userId": "b", "ratings": [{"animeId": 1, "title": "Monster", "rawScore": 8}]}
+        ]
+    }"#;
+
+    #[test]
+    fn parsing_keeps_good_ratings_and_reports_the_rest() {
+        let (dataset, report) = parse(CORPUS.as_bytes(), MergeStrategy::default()).unwrap();
+        assert_eq!(dataset.users.len(), 2);
+        assert_eq!(dataset.users[0].ratings.len(), 2);
+        assert_eq!(
+            dataset.users[0].ratings[1].status.as_deref(),
+            Some("completed")
+        );
+        assert_eq!(report.ratings_dropped, 1);
+        assert!(!report.is_clean());
+        assert_eq!(dataset.titles()[&2], "Mushishi");
+
+        assert!(matches!(
+            parse(br#"{"users": []}"#, MergeStrategy::default()),
+            Err(DatasetError::Empty)
+        ));
+        assert!(parse(b"{\"users\": [", MergeStrategy::default()).is_err());
+    }
+
+    #[test]
+    fn scores_are_normalized_against_each_users_mean() {
+        let (mut dataset, _) = parse(CORPUS.as_bytes(), MergeStrategy::default()).unwrap();
+        dataset.normalize_scores();
+        let normalized: Vec<f64> = dataset.users[0]
+            .ratings
+            .iter()
+            .map(|rating| rating.normalized_score)
+            .collect();
+        assert_eq!(normalized, [2.0, -2.0]);
+        assert_eq!(dataset.users[1].ratings[0].normalized_score, 0.0);
+    }
+
+    #[test]
+    fn importing_a_profile_again_replaces_it() {
+        let mut dataset = Dataset::new(Vec::new());
+        let profile = |score| UserRatings {
+            user_id: format!("{LOCAL_PROFILE_PREFIX}me"),
+            ratings: vec![Rating::new(1, "Monster".to_string(), score)],
+        };
+        dataset.merge_profile(profile(6.0));
+        dataset.merge_profile(profile(9.0));
+        assert_eq!(dataset.users.len(), 1);
+        assert_eq!(dataset.users[0].ratings[0].raw_score, 9.0);
+    }
+}
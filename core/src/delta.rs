@@ -0,0 +1,268 @@
+//! Updating a rating graph after one user's ratings changed, typically the local profile,
+//! without the pairwise pass over every user. Only pairs with an anime that user rated before
+//! or rates now can change, so only those are summed again, over the users who rated one;
+//! the similarity edges between other anime are kept as they were. Communities and the layout
+//! are worked out afresh, as after a full build.
+//!
+//! The result matches a full build except for pairs of anime neither rated by the user that
+//! were dropped as no end's strongest: they are not reconsidered when a stronger edge of
+//! theirs weakens.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::adjacency::Adjacency;
+use crate::community::{self, Communities};
+use crate::graph::{add_rating_edge, rating_graph_node, similarity_edge, user_label};
+use crate::sampling::Sampling;
+use crate::{neighbors, Dataset, Edge, EdgeKind, GraphMode, GraphModel, NodeType, RatingMatrix};
+
+/// `graph` with `user_id`'s ratings as they are now in `dataset`, which must otherwise hold
+/// what `graph` was built from. `None` when the change needs a full build: a facet or sampled
+/// graph, a user without ratings, or an anime nobody else rated leaving the graph, which would
+/// move the nodes after it.
+pub fn update_user(graph: &GraphModel, dataset: &Dataset, user_id: &str) -> Option<GraphModel> {
+    if graph.mode != GraphMode::Ratings || graph.sampling != Sampling::All {
+        return None;
+    }
+    let user = dataset.users.iter().find(|user| user.user_id == user_id)?;
+    if user.ratings.is_empty() {
+        return None;
+    }
+    let user_node_id = format!("user:{user_id}");
+    let previous = graph.nodes.iter().position(|node| node.id == user_node_id);
+    let rating_links = |node: usize| {
+        graph
+            .links(node)
+            .filter(|(_, edge)| edge.kind == EdgeKind::Rating)
+    };
+    let rated_before: Vec<usize> = previous
+        .map(|node| rating_links(node).map(|(anime, _)| anime).collect())
+        .unwrap_or_default();
+    let rated_now: HashSet<u32> = user.ratings.iter().map(|rating| rating.anime_id).collect();
+    let leaves = rated_before.iter().any(|&anime| {
+        let still_rated = graph.nodes[anime]
+            .anime_id()
+            .is_some_and(|anime_id| rated_now.contains(&anime_id));
+        !still_rated && rating_links(anime).count() == 1
+    });
+    if leaves {
+        return None;
+    }
+
+    let mut nodes = graph.nodes.clone();
+    let mut anime_nodes: HashMap<u32, usize> = nodes
+        .iter()
+        .enumerate()
+        .filter_map(|(index, node)| Some((node.anime_id()?, index)))
+        .collect();
+    let user_index = previous.unwrap_or_else(|| {
+        nodes.push(rating_graph_node(
+            user_node_id,
+            user_label(user_id),
+            NodeType::User,
+        ));
+        nodes.len() - 1
+    });
+
+    let mut edges: Vec<Edge> = graph
+        .edges
+        .iter()
+        .filter(|edge| edge.kind == EdgeKind::Rating && edge.source != user_index)
+        .cloned()
+        .collect();
+    let mean = user.ratings.iter().map(|r| r.raw_score).sum::<f64>() / user.ratings.len() as f64;
+    let mut added = HashMap::new();
+    for rating in &user.ratings {
+        let anime = *anime_nodes.entry(rating.anime_id).or_insert_with(|| {
+            nodes.push(rating_graph_node(
+                format!("anime:{}", rating.anime_id),
+                rating.title.clone(),
+                NodeType::Anime,
+            ));
+            nodes.len() - 1
+        });
+        add_rating_edge(
+            &mut edges,
+            &mut added,
+            user_index,
+            anime,
+            rating.raw_score - mean,
+        );
+    }
+
+    let affected: HashSet<u32> = rated_before
+        .iter()
+        .filter_map(|&anime| graph.nodes[anime].anime_id())
+        .chain(rated_now)
+        .collect();
+    let pairs = affected_pairs(&RatingMatrix::new(dataset), &affected);
+
+    let is_affected: Vec<bool> = nodes
+        .iter()
+        .map(|node| {
+            node.anime_id()
+                .is_some_and(|anime_id| affected.contains(&anime_id))
+        })
+        .collect();
+    let mut candidates: Vec<Edge> = graph
+        .edges
+        .iter()
+        .filter(|edge| {
+            edge.kind == EdgeKind::Similarity
+                && !is_affected[edge.source]
+                && !is_affected[edge.target]
+        })
+        .cloned()
+        .collect();
+    for ((left, right), (sum, co_raters)) in pairs {
+        if let (Some(&source), Some(&target)) = (anime_nodes.get(&left), anime_nodes.get(&right)) {
+            candidates.push(similarity_edge(source, target, sum, co_raters));
+        }
+    }
+    let (similar, top_neighbors) = neighbors::keep_strongest(
+        nodes.len(),
+        candidates,
+        graph.similar_neighbors,
+        edges.len(),
+    );
+    edges.extend(similar);
+
+    let user_count = nodes
+        .iter()
+        .filter(|node| node.node_type == NodeType::User)
+        .count();
+    let mut next = GraphModel {
+        user_count,
+        anime_count: nodes.len() - user_count,
+        adjacency: Adjacency::new(nodes.len(), &edges),
+        nodes,
+        edges,
+        top_neighbors,
+        similar_neighbors: graph.similar_neighbors,
+        communities: Communities::default(),
+        layout: graph.layout,
+        layout_seed: graph.layout_seed,
+        filter: graph.filter,
+        sampling: graph.sampling,
+        mode: graph.mode,
+        layout_viewport: graph.layout_viewport,
+    };
+    next.communities = community::detect(&next);
+    next.apply_filter(graph.filter);
+    let positions = next.layout_targets();
+    next.set_positions(&positions);
+    Some(next)
+}
+
+/// The summed pair scores and co-raters of every pair of anime with at least one in
+/// `affected`, keyed by anime id, smaller first. Users are added in dataset order, as in a
+/// full build, so the sums come out the same.
+fn affected_pairs(
+    matrix: &RatingMatrix,
+    affected: &HashSet<u32>,
+) -> HashMap<(u32, u32), (f64, usize)> {
+    let is_affected: Vec<bool> = (0..matrix.column_count())
+        .map(|column| affected.contains(&matrix.anime_id(column as u32)))
+        .collect();
+    let mut pairs: HashMap<(u32, u32), (f64, usize)> = HashMap::new();
+    for row in 0..matrix.rows() {
+        let row = matrix.row(row);
+        for (i, &left) in row.columns.iter().enumerate() {
+            if !is_affected[left as usize] {
+                continue;
+            }
+            let left_id = matrix.anime_id(left);
+            for (j, &right) in row.columns.iter().enumerate() {
+                let right_id = matrix.anime_id(right);
+                // A pair of two affected anime is summed from its smaller id only.
+                if left_id == right_id || (is_affected[right as usize] && right_id < left_id) {
+                    continue;
+                }
+                let pair = (left_id.min(right_id), left_id.max(right_id));
+                let (sum, co_raters) = pairs.entry(pair).or_insert((0.0, 0));
+                *sum += (row.centred[i] + row.centred[j]) / 2.0;
+                *co_raters += 1;
+            }
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_graph, GraphSettings, Rating, UserRatings, Viewport};
+
+    fn dataset() -> Dataset {
+        Dataset::new(
+            (0..40_u32)
+                .map(|u| UserRatings {
+                    user_id: format!("u{u}"),
+                    ratings: (0..6)
+                        .map(|k| {
+                            let anime_id = 1 + (u * 5 + k * 3) % 30;
+                            let score = 1.0 + f64::from((u + k * 7) % 10);
+                            Rating::new(anime_id, format!("Anime {anime_id}"), score)
+                        })
+                        .collect(),
+                })
+                .collect(),
+        )
+    }
+
+    /// Similarity edges as anime ids, co-raters and weight, in a fixed order.
+    fn similarity(graph: &GraphModel) -> Vec<(u32, u32, usize, f64)> {
+        let mut edges: Vec<_> = graph
+            .edges
+            .iter()
+            .filter(|edge| edge.kind == EdgeKind::Similarity)
+            .map(|edge| {
+                let left = graph.nodes[edge.source].anime_id().unwrap();
+                let right = graph.nodes[edge.target].anime_id().unwrap();
+                (
+                    left.min(right),
+                    left.max(right),
+                    edge.co_raters,
+                    edge.weight,
+                )
+            })
+            .collect();
+        edges.sort_by_key(|edge| (edge.0, edge.1));
+        edges
+    }
+
+    #[test]
+    fn matches_a_full_build_when_every_edge_is_kept() {
+        let graph = build_graph(dataset(), Viewport::default());
+        let settings = GraphSettings {
+            similar_neighbors: usize::MAX,
+            ..graph.settings()
+        };
+        let graph = settings.build(dataset(), |_| {});
+
+        let mut changed = dataset();
+        let user = &mut changed.users[3];
+        user.ratings[0].raw_score = 10.0;
+        user.ratings
+            .push(Rating::new(30, "Anime 30".to_string(), 2.0));
+        changed.users.push(UserRatings {
+            user_id: "local:me".to_string(),
+            ratings: vec![Rating::new(2, "Anime 2".to_string(), 9.0)],
+        });
+
+        let updated = update_user(&graph, &changed, "u3").unwrap();
+        let updated = update_user(&updated, &changed, "local:me").unwrap();
+        let rebuilt = settings.build(changed.clone(), |_| {});
+        assert_eq!(updated.nodes.len(), rebuilt.nodes.len());
+        assert_eq!(updated.edges.len(), rebuilt.edges.len());
+        assert_eq!(similarity(&updated), similarity(&rebuilt));
+        assert_eq!(updated.user_count, rebuilt.user_count);
+
+        // Taking away an anime only that user rated needs a full build.
+        let only = changed.users.len() - 1;
+        changed.users[only].ratings[0] = Rating::new(99, "Anime 99".to_string(), 9.0);
+        let with_new = update_user(&updated, &changed, "local:me").unwrap();
+        changed.users[only].ratings[0] = Rating::new(2, "Anime 2".to_string(), 9.0);
+        assert!(update_user(&with_new, &changed, "local:me").is_none());
+    }
+}
@@ -0,0 +1,54 @@
+use std::fmt;
+use std::io;
+
+/// Why a dataset could not be read or written.
+#[derive(Debug)]
+pub enum DatasetError {
+    Io(io::Error),
+    Parse(String),
+    /// Malformed JSON at a known position (1-based line and column).
+    Syntax {
+        message: String,
+        line: usize,
+        column: usize,
+    },
+    /// The file parsed but contained no users.
+    Empty,
+}
+
+impl fmt::Display for DatasetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "could not read file: {err}"),
+            Self::Parse(msg) => write!(f, "could not parse dataset: {msg}"),
+            Self::Syntax {
+                message,
+                line,
+                column,
+            } => write!(f, "invalid JSON at line {line}, column {column}: {message}"),
+            Self::Empty => write!(f, "the dataset has no users"),
+        }
+    }
+}
+
+impl std::error::Error for DatasetError {}
+
+impl From<io::Error> for DatasetError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for DatasetError {
+    fn from(err: serde_json::Error) -> Self {
+        match err.classify() {
+            // Reader failures (e.g. a truncated gzip stream) are I/O, not a JSON position.
+            serde_json::error::Category::Io => Self::Io(err.into()),
+            _ => Self::Syntax {
+                line: err.line(),
+                column: err.column(),
+                message: err.to_string(),
+            },
+        }
+    }
+}
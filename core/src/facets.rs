@@ -0,0 +1,249 @@
+//! Graphs over what anime have in common instead of over users and anime: one node per genre,
+//! studio or director, joined by how many users like both. A corpus has a few dozen genres and
+//! a few hundred studios, so the map stays readable however large the dataset is.
+
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::adjacency::Adjacency;
+use crate::graph::{Edge, EdgeKind, GraphModel, Node, NodeType};
+use crate::intern::Interner;
+use crate::{community, layout, Dataset, Viewport};
+
+/// Strongest edges kept per node; the rest would make the map a hairball.
+pub const FACET_NEIGHBORS: usize = 6;
+
+/// What the nodes of a graph are.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphMode {
+    /// Users and the anime they rated.
+    #[default]
+    Ratings,
+    /// Genres, joined when the same users like anime of both.
+    Genres,
+    /// Studios, joined the same way.
+    Studios,
+    /// Directors, joined the same way.
+    Directors,
+}
+
+impl GraphMode {
+    pub const ALL: [GraphMode; 4] = [Self::Ratings, Self::Genres, Self::Studios, Self::Directors];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Ratings => "Ratings",
+            Self::Genres => "Genres",
+            Self::Studios => "Studios",
+            Self::Directors => "Directors",
+        }
+    }
+
+    /// The node type of a facet graph, or `None` for the rating graph.
+    pub fn node_type(self) -> Option<NodeType> {
+        match self {
+            Self::Ratings => None,
+            Self::Genres => Some(NodeType::Genre),
+            Self::Studios => Some(NodeType::Studio),
+            Self::Directors => Some(NodeType::Director),
+        }
+    }
+
+    /// Prefix of this mode's node ids, as in `genre:Drama`.
+    fn id_prefix(self) -> &'static str {
+        match self {
+            Self::Ratings => "anime",
+            Self::Genres => "genre",
+            Self::Studios => "studio",
+            Self::Directors => "director",
+        }
+    }
+}
+
+/// Builds the facet graph of `mode` for `dataset`, or the rating graph for
+/// [`GraphMode::Ratings`]. `facets` gives an anime's genres, studios or directors; genres come
+/// from the ratings instead when the source had them. A user likes a facet when they scored an
+/// anime with it above their own mean. Nodes are sized by how many users like them, and two are
+/// joined by the share of users liking either who like both, keeping each node's
+/// [`FACET_NEIGHBORS`] strongest edges.
+pub fn build_facet_graph(
+    dataset: &Dataset,
+    mode: GraphMode,
+    facets: impl Fn(u32) -> Vec<String>,
+    viewport: Viewport,
+) -> GraphModel {
+    let Some(node_type) = mode.node_type() else {
+        return crate::build_graph(dataset.clone(), viewport);
+    };
+    let mut names = Interner::default();
+    let mut cache: HashMap<u32, Vec<u32>> = HashMap::new();
+    let mut likers: HashMap<u32, usize> = HashMap::new();
+    let mut pairs: HashMap<(u32, u32), usize> = HashMap::new();
+    for user in &dataset.users {
+        if user.ratings.is_empty() {
+            continue;
+        }
+        let mean =
+            user.ratings.iter().map(|r| r.raw_score).sum::<f64>() / user.ratings.len() as f64;
+        let mut liked = BTreeSet::new();
+        for rating in user.ratings.iter().filter(|rating| rating.raw_score > mean) {
+            if mode == GraphMode::Genres && !rating.genres.is_empty() {
+                liked.extend(rating.genres.iter().map(|genre| names.intern(genre)));
+                continue;
+            }
+            let values = cache.entry(rating.anime_id).or_insert_with(|| {
+                let values = facets(rating.anime_id);
+                values.iter().map(|value| names.intern(value)).collect()
+            });
+            liked.extend(values.iter().copied());
+        }
+        let liked: Vec<u32> = liked.into_iter().collect();
+        for (i, &left) in liked.iter().enumerate() {
+            *likers.entry(left).or_default() += 1;
+            for &right in &liked[i + 1..] {
+                *pairs.entry((left, right)).or_default() += 1;
+            }
+        }
+    }
+
+    let mut order: Vec<u32> = likers.keys().copied().collect();
+    order.sort_by(|a, b| names.resolve(*a).cmp(names.resolve(*b)));
+    let most_liked = likers.values().copied().max().unwrap_or(1).max(1) as f32;
+    let index: HashMap<u32, usize> = order.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+    let nodes: Vec<Node> = order
+        .iter()
+        .map(|&name| Node {
+            id: format!("{}:{}", mode.id_prefix(), names.resolve(name)),
+            label: names.resolve(name).to_string(),
+            node_type,
+            x: layout::DEFAULT_WIDTH / 2.0,
+            y: layout::DEFAULT_HEIGHT / 2.0,
+            radius: 6.0 + 14.0 * (likers[&name] as f32 / most_liked).sqrt(),
+            hidden: false,
+        })
+        .collect();
+
+    let mut candidates: Vec<(usize, usize, f64, usize)> = pairs
+        .iter()
+        .map(|((left, right), &both)| {
+            let either = likers[left] + likers[right] - both;
+            // Lower node first, as the nodes are in name order and the pairs in interned order.
+            let (source, target) = (index[left].min(index[right]), index[left].max(index[right]));
+            (source, target, both as f64 / either as f64, both)
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.2.total_cmp(&a.2).then((a.0, a.1).cmp(&(b.0, b.1))));
+    let mut degree = vec![0_usize; nodes.len()];
+    let mut edges = Vec::new();
+    for (source, target, weight, both) in candidates {
+        // An edge stays when it is among the strongest for either end.
+        if degree[source] >= FACET_NEIGHBORS && degree[target] >= FACET_NEIGHBORS {
+            continue;
+        }
+        degree[source] += 1;
+        degree[target] += 1;
+        edges.push(Edge {
+            source,
+            target,
+            kind: EdgeKind::Similarity,
+            weight,
+            co_raters: both,
+            stroke_width: (0.5 + weight as f32 * 4.0).clamp(0.5, 4.0),
+        });
+    }
+
+    let mut graph = GraphModel {
+        user_count: 0,
+        anime_count: 0,
+        adjacency: Adjacency::new(nodes.len(), &edges),
+        top_neighbors: Default::default(),
+        similar_neighbors: crate::neighbors::DEFAULT_NEIGHBORS,
+        nodes,
+        edges,
+        communities: Default::default(),
+        layout: Default::default(),
+        layout_seed: 0,
+        filter: Default::default(),
+        sampling: Default::default(),
+        mode,
+        layout_viewport: viewport,
+    };
+    graph.communities = community::detect(&graph);
+    let positions = graph.layout_targets();
+    graph.set_positions(&positions);
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Rating, UserRatings};
+
+    fn user(user_id: &str, ratings: &[(u32, f64)]) -> UserRatings {
+        UserRatings {
+            user_id: user_id.to_string(),
+            ratings: ratings
+                .iter()
+                .map(|&(anime_id, score)| Rating::new(anime_id, format!("Anime {anime_id}"), score))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn genres_liked_by_the_same_users_are_joined() {
+        let dataset = Dataset::new(vec![
+            user("a", &[(1, 9.0), (2, 9.0), (3, 2.0)]),
+            user("b", &[(1, 8.0), (2, 8.0), (3, 3.0)]),
+            user("c", &[(1, 2.0), (3, 9.0)]),
+        ]);
+        let genres = |anime_id: u32| {
+            let genre = match anime_id {
+                1 => "Drama",
+                2 => "Romance",
+                _ => "Action",
+            };
+            vec![genre.to_string()]
+        };
+        let graph = build_facet_graph(&dataset, GraphMode::Genres, genres, Viewport::default());
+        let labels: Vec<_> = graph.nodes.iter().map(|node| node.label.as_str()).collect();
+        assert_eq!(labels, ["Action", "Drama", "Romance"]);
+        assert!(graph
+            .nodes
+            .iter()
+            .all(|node| node.node_type == NodeType::Genre));
+        // Only a and b like two genres, and both like Drama and Romance.
+        assert_eq!(graph.edges.len(), 1);
+        let edge = &graph.edges[0];
+        assert_eq!((edge.source, edge.target), (1, 2));
+        assert!((edge.weight - 1.0).abs() < 1e-9);
+        assert_eq!(edge.co_raters, 2);
+    }
+
+    #[test]
+    fn studio_graph_ignores_rating_genres() {
+        let mut dataset = Dataset::new(vec![
+            user("a", &[(1, 9.0), (2, 9.0), (3, 2.0)]),
+            user("b", &[(1, 8.0), (2, 8.0), (3, 3.0)]),
+        ]);
+        for user in &mut dataset.users {
+            for rating in &mut user.ratings {
+                rating.genres = vec!["Drama".to_string()];
+            }
+        }
+        let studios = |anime_id: u32| match anime_id {
+            1 => vec!["Madhouse".to_string()],
+            2 => vec!["Madhouse".to_string(), "Bones".to_string()],
+            _ => Vec::new(),
+        };
+        let graph = build_facet_graph(&dataset, GraphMode::Studios, studios, Viewport::default());
+        let ids: Vec<_> = graph.nodes.iter().map(|node| node.id.as_str()).collect();
+        assert_eq!(ids, ["studio:Bones", "studio:Madhouse"]);
+        assert!(graph
+            .nodes
+            .iter()
+            .all(|node| node.node_type == NodeType::Studio));
+        assert_eq!(graph.edges.len(), 1);
+    }
+}
@@ -0,0 +1,903 @@
+//! The rating graph: nodes, edges and building them from a dataset.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::Range;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::adjacency::Adjacency;
+use crate::community::{self, Communities};
+use crate::dataset::{Dataset, LOCAL_PROFILE_PREFIX};
+use crate::facets::{self, GraphMode};
+use crate::intern::Interner;
+use crate::layout::{self, LayoutKind, Viewport};
+use crate::matrix::RatingMatrix;
+use crate::neighbors::{self, TopNeighbors};
+use crate::sampling::Sampling;
+
+/// Progress reports per stage of [`build_graph_with_progress`].
+const PROGRESS_STEPS: usize = 100;
+/// Maps the anime pair scores are summed into, split by pair so threads fill them side by side.
+const PAIR_SHARDS: usize = 64;
+
+/// Sum of pair scores and number of co-raters per anime pair, lower id first, for the mean.
+type PairShard = HashMap<(u32, u32), (f64, usize)>;
+/// A chunk's pairs with their pair scores, one list per shard.
+type PairBuckets = Vec<Vec<((u32, u32), f64)>>;
+
+/// A node while the graph is built: a user by their interned id, an anime by its own, so
+/// finding one never formats its [`Node::id`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum NodeKey {
+    User(u32),
+    Anime(u32),
+}
+
+/// What an edge stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeKind {
+    /// User rated the anime.
+    Rating,
+    /// Two anime co-rated by the same users, or two genres, studios or directors liked by the
+    /// same users.
+    Similarity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeType {
+    User,
+    Anime,
+    /// In the genre graph.
+    Genre,
+    /// In the studio graph.
+    Studio,
+    /// In the director graph.
+    Director,
+}
+
+/// A user or anime in the graph, at its current layout position.
+#[derive(Debug, Clone)]
+pub struct Node {
+    /// `user:<userId>` or `anime:<animeId>`, or `genre:<name>`, `studio:<name>` or
+    /// `director:<name>` in the facet graphs.
+    pub id: String,
+    pub label: String,
+    pub node_type: NodeType,
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    /// Excluded by the active `GraphFilter`; hidden nodes are neither drawn nor laid out.
+    pub hidden: bool,
+}
+
+impl Node {
+    /// The anime id behind an anime node, parsed from its `anime:` node id.
+    pub fn anime_id(&self) -> Option<u32> {
+        self.id.strip_prefix("anime:")?.parse().ok()
+    }
+}
+
+/// An edge between two nodes, by index into [`GraphModel::nodes`].
+#[derive(Debug, Clone)]
+pub struct Edge {
+    /// The user for rating edges.
+    pub source: usize,
+    pub target: usize,
+    pub kind: EdgeKind,
+    /// Normalized score for rating edges, averaged pair score for similarity edges.
+    pub weight: f64,
+    /// What the edge was combined from: the ratings of that user for that anime for rating
+    /// edges, one unless the user is listed more than once, and everyone who rated both anime
+    /// otherwise.
+    pub co_raters: usize,
+    pub stroke_width: f32,
+}
+
+/// An edge as a line segment to draw.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderEdge {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+    pub kind: EdgeKind,
+    pub stroke_width: f32,
+    /// Index into `GraphModel::edges`, or `None` for edges aggregated from several.
+    pub edge: Option<usize>,
+}
+
+/// The user–anime rating graph with anime–anime similarity edges, detected communities and a
+/// layout. Build one with [`build_graph`].
+#[derive(Debug, Clone)]
+pub struct GraphModel {
+    pub user_count: usize,
+    pub anime_count: usize,
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    /// The edges touching each node; see [`GraphModel::links`].
+    pub adjacency: Adjacency,
+    /// Each anime's strongest similarity edges, which are the only ones kept.
+    pub top_neighbors: TopNeighbors,
+    /// How many of each anime's similarity edges were kept.
+    pub similar_neighbors: usize,
+    pub communities: Communities,
+    pub layout: LayoutKind,
+    /// Shuffles node order within the layout; see [`layout::compute`].
+    pub layout_seed: u64,
+    pub filter: GraphFilter,
+    /// Subsample of the dataset the graph was built from.
+    pub sampling: Sampling,
+    pub mode: GraphMode,
+    /// Canvas size the current node positions were computed for.
+    pub layout_viewport: Viewport,
+}
+
+/// Which nodes and edges are shown; changing it re-runs the layout over the remaining nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphFilter {
+    pub show_users: bool,
+    /// Anime rated by fewer users than this are hidden.
+    pub min_anime_ratings: usize,
+    pub show_rating_edges: bool,
+    pub show_similarity_edges: bool,
+}
+
+impl GraphFilter {
+    pub fn shows_edges(&self, kind: EdgeKind) -> bool {
+        match kind {
+            EdgeKind::Rating => self.show_rating_edges,
+            EdgeKind::Similarity => self.show_similarity_edges,
+        }
+    }
+
+    /// The filter with edges of `kind` shown or hidden.
+    pub fn with_edges(self, kind: EdgeKind, shown: bool) -> Self {
+        match kind {
+            EdgeKind::Rating => Self {
+                show_rating_edges: shown,
+                ..self
+            },
+            EdgeKind::Similarity => Self {
+                show_similarity_edges: shown,
+                ..self
+            },
+        }
+    }
+}
+
+impl Default for GraphFilter {
+    fn default() -> Self {
+        Self {
+            show_users: true,
+            min_anime_ratings: 1,
+            show_rating_edges: true,
+            show_similarity_edges: true,
+        }
+    }
+}
+
+/// Why [`GraphModel::find_user`] found no single user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FindUserError {
+    NotFound(String),
+    /// The query is a prefix of this many user ids.
+    Ambiguous(String, usize),
+}
+
+impl fmt::Display for FindUserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(query) => write!(f, "no user with id {query}"),
+            Self::Ambiguous(query, count) => write!(
+                f,
+                "{count} users have an id starting with {query}; give more of it"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FindUserError {}
+
+/// Everything a graph is rebuilt with besides its data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GraphSettings {
+    pub sampling: Sampling,
+    pub mode: GraphMode,
+    /// Similarity edges kept per anime; see [`neighbors::keep_strongest`].
+    pub similar_neighbors: usize,
+    pub layout: LayoutKind,
+    pub layout_seed: u64,
+    pub filter: GraphFilter,
+    pub viewport: Viewport,
+}
+
+impl GraphSettings {
+    /// Builds a graph for `dataset` with these settings, reporting each step to `progress`.
+    pub fn build(self, dataset: Dataset, progress: impl FnMut(BuildProgress)) -> GraphModel {
+        self.build_with_facets(dataset, |_, _| Vec::new(), progress)
+    }
+
+    /// [`Self::build`], with `facets` giving an anime's genres, studios or directors for the
+    /// facet graphs, e.g. from fetched metadata.
+    pub fn build_with_facets(
+        self,
+        dataset: Dataset,
+        facets: impl Fn(u32, GraphMode) -> Vec<String>,
+        progress: impl FnMut(BuildProgress),
+    ) -> GraphModel {
+        let dataset = self.sampling.apply(dataset);
+        let mut next = match self.mode {
+            GraphMode::Ratings => {
+                build_rating_graph(dataset, self.viewport, self.similar_neighbors, progress)
+            }
+            mode => facets::build_facet_graph(
+                &dataset,
+                mode,
+                |anime_id| facets(anime_id, mode),
+                self.viewport,
+            ),
+        };
+        next.sampling = self.sampling;
+        next.similar_neighbors = self.similar_neighbors;
+        next.layout = self.layout;
+        next.layout_seed = self.layout_seed;
+        next.apply_filter(self.filter);
+        let positions = next.layout_targets();
+        next.set_positions(&positions);
+        next
+    }
+}
+
+/// A step of building the graph, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildStage {
+    Ratings,
+    Similarity,
+    Communities,
+    Layout,
+}
+
+impl BuildStage {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Ratings => "ratings",
+            Self::Similarity => "similarity edges",
+            Self::Communities => "communities",
+            Self::Layout => "layout",
+        }
+    }
+}
+
+/// How far [`build_graph_with_progress`] has got: `done` of `total` users, anime pairs or
+/// steps of the current stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BuildProgress {
+    pub stage: BuildStage,
+    pub done: usize,
+    pub total: usize,
+}
+
+impl fmt::Display for BuildProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = (self.done * 100).checked_div(self.total).unwrap_or(100);
+        write!(f, "{} {percent}%", self.stage.label())
+    }
+}
+
+impl GraphModel {
+    /// The node of the user with id `query`, or of the only user whose id starts with it (as
+    /// in the short `User 0123abcd` labels).
+    pub fn find_user(&self, query: &str) -> Result<usize, FindUserError> {
+        let id = format!("user:{query}");
+        if let Some(index) = self.nodes.iter().position(|node| node.id == id) {
+            return Ok(index);
+        }
+        let matches: Vec<usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.node_type == NodeType::User && node.id.starts_with(&id))
+            .map(|(index, _)| index)
+            .collect();
+        match matches[..] {
+            [index] => Ok(index),
+            [] => Err(FindUserError::NotFound(query.to_string())),
+            _ => Err(FindUserError::Ambiguous(query.to_string(), matches.len())),
+        }
+    }
+
+    /// The edges touching `node`, in edge order, each with the node at its other end.
+    pub fn links(&self, node: usize) -> impl Iterator<Item = (usize, &Edge)> + '_ {
+        self.adjacency.edges(node).map(move |index| {
+            let edge = &self.edges[index];
+            let other = if edge.source == node {
+                edge.target
+            } else {
+                edge.source
+            };
+            (other, edge)
+        })
+    }
+
+    /// The node of the anime with MyAnimeList id `anime_id`.
+    pub fn find_anime(&self, anime_id: u32) -> Option<usize> {
+        let id = format!("anime:{anime_id}");
+        self.nodes.iter().position(|node| node.id == id)
+    }
+
+    /// Anime nodes whose title contains `query` (case-insensitive), at most `limit`: exact
+    /// matches first, then titles starting with it, then shorter titles.
+    pub fn find_anime_by_title(&self, query: &str, limit: usize) -> Vec<usize> {
+        let needle = query.trim().to_lowercase();
+        if needle.is_empty() {
+            return Vec::new();
+        }
+        let mut matches: Vec<(u8, usize, usize)> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.node_type == NodeType::Anime)
+            .filter_map(|(index, node)| {
+                let title = node.label.to_lowercase();
+                let rank = match title.find(&needle)? {
+                    _ if title == needle => 0,
+                    0 => 1,
+                    _ => 2,
+                };
+                Some((rank, title.chars().count(), index))
+            })
+            .collect();
+        matches.sort_unstable();
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(_, _, index)| index)
+            .collect()
+    }
+
+    /// Builds a graph for `dataset` with this graph's sampling, layout, filter and viewport.
+    pub fn rebuild(&self, dataset: Dataset) -> GraphModel {
+        self.settings().build(dataset, |_| {})
+    }
+
+    pub fn settings(&self) -> GraphSettings {
+        GraphSettings {
+            sampling: self.sampling,
+            mode: self.mode,
+            similar_neighbors: self.similar_neighbors,
+            layout: self.layout,
+            layout_seed: self.layout_seed,
+            filter: self.filter,
+            viewport: self.layout_viewport,
+        }
+    }
+
+    pub fn positions(&self) -> Vec<(f32, f32)> {
+        self.nodes.iter().map(|node| (node.x, node.y)).collect()
+    }
+
+    pub fn set_positions(&mut self, positions: &[(f32, f32)]) {
+        for (node, (x, y)) in self.nodes.iter_mut().zip(positions) {
+            node.x = *x;
+            node.y = *y;
+        }
+    }
+
+    /// Positions for the current layout kind, seed, filter and viewport.
+    pub fn layout_targets(&self) -> Vec<(f32, f32)> {
+        layout::compute(
+            &self.nodes,
+            &self.communities,
+            self.layout,
+            self.layout_seed,
+            self.layout_viewport,
+        )
+    }
+
+    /// Relabels anime nodes with `title` of their anime id; anime it has no title for keep
+    /// their label.
+    pub fn relabel_anime(&mut self, title: impl Fn(u32) -> Option<String>) {
+        for node in &mut self.nodes {
+            if let Some(label) = node.anime_id().and_then(&title) {
+                node.label = label;
+            }
+        }
+    }
+
+    pub fn apply_filter(&mut self, filter: GraphFilter) {
+        let mut rating_counts = vec![0_usize; self.nodes.len()];
+        for edge in &self.edges {
+            if edge.kind == EdgeKind::Rating {
+                rating_counts[edge.target] += 1;
+            }
+        }
+        for (node, ratings) in self.nodes.iter_mut().zip(rating_counts) {
+            node.hidden = match node.node_type {
+                NodeType::User => !filter.show_users,
+                NodeType::Anime => ratings < filter.min_anime_ratings,
+                NodeType::Genre | NodeType::Studio | NodeType::Director => false,
+            };
+        }
+        self.filter = filter;
+    }
+
+    pub fn is_edge_visible(&self, edge: &Edge) -> bool {
+        self.filter.shows_edges(edge.kind)
+            && !self.nodes[edge.source].hidden
+            && !self.nodes[edge.target].hidden
+    }
+
+    /// Indices of nodes whose label contains `query` (case-insensitive), or `None` for an empty query.
+    pub fn search(&self, query: &str) -> Option<HashSet<usize>> {
+        let needle = query.trim().to_lowercase();
+        if needle.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, node)| !node.hidden && node.label.to_lowercase().contains(&needle))
+                .map(|(idx, _)| idx)
+                .collect(),
+        )
+    }
+
+    /// Up to `limit` visible edges as line segments, at least `min_stroke_width` wide.
+    pub fn render_edges(&self, limit: usize, min_stroke_width: f32) -> Vec<RenderEdge> {
+        self.edges
+            .iter()
+            .enumerate()
+            .filter(|(_, edge)| self.is_edge_visible(edge))
+            .take(limit)
+            .map(|(idx, edge)| RenderEdge {
+                x1: self.nodes[edge.source].x,
+                y1: self.nodes[edge.source].y,
+                x2: self.nodes[edge.target].x,
+                y2: self.nodes[edge.target].y,
+                kind: edge.kind,
+                stroke_width: edge.stroke_width.max(min_stroke_width),
+                edge: Some(idx),
+            })
+            .collect()
+    }
+}
+
+fn shard_of((left, right): (u32, u32)) -> usize {
+    (left as usize)
+        .wrapping_mul(0x9E37_79B9)
+        .wrapping_add(right as usize)
+        % PAIR_SHARDS
+}
+
+/// Every pair of anime the user at `row` rated, with the mean of their normalized scores.
+fn bucket_pairs(matrix: &RatingMatrix, mut buckets: PairBuckets, row: usize) -> PairBuckets {
+    let row = matrix.row(row);
+    for (i, (&left, &left_score)) in row.columns.iter().zip(row.centred).enumerate() {
+        let left = matrix.anime_id(left);
+        for (&right, &right_score) in row.columns[i + 1..].iter().zip(&row.centred[i + 1..]) {
+            let right = matrix.anime_id(right);
+            // The same anime twice in one list is one rating, not a pair.
+            if left == right {
+                continue;
+            }
+            let pair_key = if left < right {
+                (left, right)
+            } else {
+                (right, left)
+            };
+            let pair_score = (left_score + right_score) / 2.0;
+            buckets[shard_of(pair_key)].push((pair_key, pair_score));
+        }
+    }
+    buckets
+}
+
+/// Sums the anime pairs of the users at `rows` into `shards`: the pairs are listed per user
+/// range and shard in parallel, then each shard takes its lists in user order, so the sums come
+/// out the same on any number of threads.
+fn add_pairs(shards: &mut [PairShard], matrix: &RatingMatrix, rows: Range<usize>) {
+    let empty = || vec![Vec::new(); PAIR_SHARDS];
+    let bucket = |buckets, row| bucket_pairs(matrix, buckets, row);
+    #[cfg(feature = "parallel")]
+    let parts: Vec<PairBuckets> = rows.into_par_iter().fold(empty, bucket).collect();
+    #[cfg(not(feature = "parallel"))]
+    let parts: Vec<PairBuckets> = vec![rows.fold(empty(), bucket)];
+    let sum = |(index, shard): (usize, &mut PairShard)| {
+        for &(pair_key, pair_score) in parts.iter().flat_map(|part| &part[index]) {
+            let (sum, co_raters) = shard.entry(pair_key).or_insert((0.0, 0));
+            *sum += pair_score;
+            *co_raters += 1;
+        }
+    };
+    #[cfg(feature = "parallel")]
+    shards.par_iter_mut().enumerate().for_each(sum);
+    #[cfg(not(feature = "parallel"))]
+    shards.iter_mut().enumerate().for_each(sum);
+}
+
+/// Builds the graph for `dataset`, laid out for `viewport` with the default layout.
+///
+/// Every rating becomes a user–anime edge weighted by its normalized score, and every pair of
+/// anime a user rated together gets a similarity edge weighted by the averaged pair score of
+/// everyone who rated both.
+pub fn build_graph(dataset: Dataset, viewport: Viewport) -> GraphModel {
+    build_graph_with_progress(dataset, viewport, |_| {})
+}
+
+/// [`build_graph`], reporting progress about a hundred times per stage so a frontend can show
+/// it while a large dataset builds.
+pub fn build_graph_with_progress(
+    dataset: Dataset,
+    viewport: Viewport,
+    progress: impl FnMut(BuildProgress),
+) -> GraphModel {
+    build_rating_graph(dataset, viewport, neighbors::DEFAULT_NEIGHBORS, progress)
+}
+
+/// [`build_graph_with_progress`], keeping the `similar_neighbors` strongest similarity edges of
+/// each anime.
+#[tracing::instrument(level = "info", skip_all, fields(users = dataset.users.len()))]
+fn build_rating_graph(
+    mut dataset: Dataset,
+    viewport: Viewport,
+    similar_neighbors: usize,
+    mut progress: impl FnMut(BuildProgress),
+) -> GraphModel {
+    let mut report = |stage: BuildStage, done: usize, total: usize| {
+        let step = (total / PROGRESS_STEPS).max(1);
+        if done.is_multiple_of(step) || done == total {
+            progress(BuildProgress { stage, done, total });
+        }
+    };
+    dataset.normalize_scores();
+    let matrix = RatingMatrix::new(&dataset);
+
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut node_index: HashMap<NodeKey, usize> = HashMap::new();
+    let mut user_ids = Interner::default();
+    let mut pair_shards: Vec<PairShard> = vec![PairShard::new(); PAIR_SHARDS];
+    let mut edges: Vec<Edge> = Vec::new();
+    let mut rating_edges: HashMap<(usize, usize), usize> = HashMap::new();
+
+    // A chunk at a time, so progress is reported while the pairs are summed in parallel
+    // and only one chunk's pairs are held as a list.
+    let user_total = dataset.users.len();
+    let chunk_size = (user_total / PROGRESS_STEPS).max(1);
+    for (chunk_index, chunk) in dataset.users.chunks(chunk_size).enumerate() {
+        report(BuildStage::Ratings, chunk_index * chunk_size, user_total);
+        for user in chunk {
+            let user_key = NodeKey::User(user_ids.intern(&user.user_id));
+            let user_idx = upsert_node(&mut nodes, &mut node_index, user_key, || {
+                (format!("user:{}", user.user_id), user_label(&user.user_id))
+            });
+
+            for rating in &user.ratings {
+                let anime_key = NodeKey::Anime(rating.anime_id);
+                let anime_idx = upsert_node(&mut nodes, &mut node_index, anime_key, || {
+                    (format!("anime:{}", rating.anime_id), rating.title.clone())
+                });
+
+                add_rating_edge(
+                    &mut edges,
+                    &mut rating_edges,
+                    user_idx,
+                    anime_idx,
+                    rating.normalized_score,
+                );
+            }
+        }
+        let start = chunk_index * chunk_size;
+        add_pairs(&mut pair_shards, &matrix, start..start + chunk.len());
+    }
+
+    report(BuildStage::Ratings, user_total, user_total);
+
+    let pair_total: usize = pair_shards.iter().map(PairShard::len).sum();
+    let mut similar = Vec::with_capacity(pair_total);
+    let mut done = 0;
+    for shard in pair_shards {
+        for ((left, right), (sum, co_raters)) in shard {
+            report(BuildStage::Similarity, done, pair_total);
+            done += 1;
+            if let (Some(&source), Some(&target)) = (
+                node_index.get(&NodeKey::Anime(left)),
+                node_index.get(&NodeKey::Anime(right)),
+            ) {
+                similar.push(similarity_edge(source, target, sum, co_raters));
+            }
+        }
+    }
+
+    let (similar, top_neighbors) =
+        neighbors::keep_strongest(nodes.len(), similar, similar_neighbors, edges.len());
+    edges.extend(similar);
+
+    let user_count = nodes
+        .iter()
+        .filter(|n| n.node_type == NodeType::User)
+        .count();
+    let anime_count = nodes.len() - user_count;
+
+    let adjacency = Adjacency::new(nodes.len(), &edges);
+    let mut graph = GraphModel {
+        user_count,
+        anime_count,
+        nodes,
+        edges,
+        adjacency,
+        top_neighbors,
+        similar_neighbors,
+        communities: Communities::default(),
+        layout: LayoutKind::default(),
+        layout_seed: 0,
+        filter: GraphFilter::default(),
+        sampling: Sampling::default(),
+        mode: GraphMode::Ratings,
+        layout_viewport: viewport,
+    };
+    report(BuildStage::Similarity, pair_total, pair_total);
+    report(BuildStage::Communities, 0, 1);
+    graph.communities = community::detect(&graph);
+    report(BuildStage::Layout, 0, 1);
+    let positions = graph.layout_targets();
+    graph.set_positions(&positions);
+    report(BuildStage::Layout, 1, 1);
+    tracing::info!(
+        nodes = graph.nodes.len(),
+        edges = graph.edges.len(),
+        communities = graph.communities.count(),
+        "graph built"
+    );
+    graph
+}
+
+/// Display name for a user: `You (<name>)` for imported profiles, a short hash otherwise.
+pub fn user_label(user_id: &str) -> String {
+    match user_id.strip_prefix(LOCAL_PROFILE_PREFIX) {
+        Some(name) => format!("You ({name})"),
+        None => format!("User {}", user_id.chars().take(8).collect::<String>()),
+    }
+}
+
+/// The index of the node for `key`, adding it with the id and label from `describe` the first
+/// time, so those strings are only made once per node.
+fn upsert_node(
+    nodes: &mut Vec<Node>,
+    node_index: &mut HashMap<NodeKey, usize>,
+    key: NodeKey,
+    describe: impl FnOnce() -> (String, String),
+) -> usize {
+    if let Some(existing) = node_index.get(&key) {
+        return *existing;
+    }
+
+    let (id, label) = describe();
+    let node_type = match key {
+        NodeKey::User(_) => NodeType::User,
+        NodeKey::Anime(_) => NodeType::Anime,
+    };
+    let idx = nodes.len();
+    nodes.push(rating_graph_node(id, label, node_type));
+    node_index.insert(key, idx);
+    idx
+}
+
+/// A user or anime node of the rating graph, in the middle of the canvas until laid out.
+pub(crate) fn rating_graph_node(id: String, label: String, node_type: NodeType) -> Node {
+    Node {
+        id,
+        label,
+        node_type,
+        x: layout::DEFAULT_WIDTH / 2.0,
+        y: layout::DEFAULT_HEIGHT / 2.0,
+        radius: if node_type == NodeType::User {
+            7.0
+        } else {
+            3.8
+        },
+        hidden: false,
+    }
+}
+
+/// The edge for a user's rating of an anime, weighted by its normalized score.
+fn rating_edge(user: usize, anime: usize, normalized_score: f64) -> Edge {
+    Edge {
+        source: user,
+        target: anime,
+        kind: EdgeKind::Rating,
+        weight: normalized_score,
+        co_raters: 1,
+        stroke_width: 1.5,
+    }
+}
+
+/// Adds the rating edge from `user` to `anime`, or folds the rating into the one already
+/// there when the same user rated the same anime twice, e.g. when listed twice: the weight
+/// becomes the mean of the normalized scores and `co_raters` counts the ratings. `added` maps
+/// each user and anime pair to its edge in `edges`.
+pub(crate) fn add_rating_edge(
+    edges: &mut Vec<Edge>,
+    added: &mut HashMap<(usize, usize), usize>,
+    user: usize,
+    anime: usize,
+    normalized_score: f64,
+) {
+    match added.entry((user, anime)) {
+        Entry::Occupied(entry) => {
+            let edge = &mut edges[*entry.get()];
+            let ratings = edge.co_raters as f64;
+            edge.weight = (edge.weight * ratings + normalized_score) / (ratings + 1.0);
+            edge.co_raters += 1;
+        }
+        Entry::Vacant(entry) => {
+            entry.insert(edges.len());
+            edges.push(rating_edge(user, anime, normalized_score));
+        }
+    }
+}
+
+/// The similarity edge between two anime whose `co_raters` pair scores add up to `sum`.
+pub(crate) fn similarity_edge(source: usize, target: usize, sum: f64, co_raters: usize) -> Edge {
+    let weight = sum / co_raters as f64;
+    Edge {
+        source,
+        target,
+        kind: EdgeKind::Similarity,
+        weight,
+        co_raters,
+        stroke_width: (0.35 + weight.abs() as f32 * 0.12).clamp(0.35, 2.2),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::{Rating, UserRatings};
+
+    fn user(user_id: &str, scores: [f64; 3]) -> UserRatings {
+        UserRatings {
+            user_id: user_id.to_string(),
+            ratings: scores
+                .iter()
+                .zip(1..)
+                .map(|(&score, anime_id)| Rating::new(anime_id, format!("Anime {anime_id}"), score))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn similarity_weight_is_the_mean_pair_score() {
+        // Normalized pair scores for anime 1 and 2: 2, -1 and 2, so the mean is 1.
+        let dataset = Dataset::new(vec![
+            user("a", [10.0, 10.0, 4.0]),
+            user("b", [5.0, 5.0, 8.0]),
+            user("c", [9.0, 9.0, 3.0]),
+        ]);
+        let graph = build_graph(dataset, Viewport::default());
+        let first = graph.find_anime(1).unwrap();
+        let second = graph.find_anime(2).unwrap();
+        let edge = graph
+            .edges
+            .iter()
+            .find(|edge| {
+                edge.kind == EdgeKind::Similarity
+                    && [edge.source, edge.target].contains(&first)
+                    && [edge.source, edge.target].contains(&second)
+            })
+            .unwrap();
+        assert_eq!(edge.co_raters, 3);
+        assert!((edge.weight - 1.0).abs() < 1e-9, "{}", edge.weight);
+    }
+
+    #[test]
+    fn pairs_summed_across_chunks_and_threads_match_a_single_pass() {
+        // Enough users for many chunks, rating overlapping spans of anime.
+        let users: Vec<UserRatings> = (0..500_u32)
+            .map(|u| UserRatings {
+                user_id: format!("u{u}"),
+                ratings: (0..8)
+                    .map(|k| {
+                        let anime_id = 1 + (u * 7 + k * 3) % 40;
+                        let score = 1.0 + f64::from((u + k * 5) % 10);
+                        Rating::new(anime_id, format!("Anime {anime_id}"), score)
+                    })
+                    .collect(),
+            })
+            .collect();
+        let mut dataset = Dataset::new(users);
+        let graph = build_rating_graph(dataset.clone(), Viewport::default(), usize::MAX, |_| {});
+
+        dataset.normalize_scores();
+        let mut expected: HashMap<(u32, u32), (f64, usize)> = HashMap::new();
+        for user in &dataset.users {
+            let ratings = &user.ratings;
+            for (i, left) in ratings.iter().enumerate() {
+                for right in &ratings[i + 1..] {
+                    let key = (
+                        left.anime_id.min(right.anime_id),
+                        left.anime_id.max(right.anime_id),
+                    );
+                    let entry = expected.entry(key).or_default();
+                    entry.0 += (left.normalized_score + right.normalized_score) / 2.0;
+                    entry.1 += 1;
+                }
+            }
+        }
+        let similarity: Vec<&Edge> = graph
+            .edges
+            .iter()
+            .filter(|edge| edge.kind == EdgeKind::Similarity)
+            .collect();
+        assert_eq!(similarity.len(), expected.len());
+        for edge in similarity {
+            let left = graph.nodes[edge.source].anime_id().unwrap();
+            let right = graph.nodes[edge.target].anime_id().unwrap();
+            let (sum, co_raters) = expected[&(left.min(right), left.max(right))];
+            assert_eq!(edge.co_raters, co_raters);
+            assert!((edge.weight - sum / co_raters as f64).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn a_user_listed_twice_gets_one_edge_per_anime() {
+        let dataset = Dataset::new(vec![
+            user("a", [10.0, 10.0, 4.0]),
+            user("a", [4.0, 7.0, 7.0]),
+            user("b", [5.0, 5.0, 8.0]),
+        ]);
+        let graph = build_graph(dataset, Viewport::default());
+        let ratings: Vec<&Edge> = graph
+            .edges
+            .iter()
+            .filter(|edge| edge.kind == EdgeKind::Rating)
+            .collect();
+        assert_eq!(ratings.len(), 6);
+        let first = graph.find_anime(1).unwrap();
+        let folded = ratings
+            .iter()
+            .find(|edge| edge.source == 0 && edge.target == first)
+            .unwrap();
+        // Normalized 2 in the first list and -2 in the second.
+        assert_eq!(folded.co_raters, 2);
+        assert!(folded.weight.abs() < 1e-9, "{}", folded.weight);
+        assert!(graph.edges.iter().all(|edge| edge.source != edge.target));
+    }
+
+    #[test]
+    fn hidden_edge_kinds_are_not_rendered() {
+        let dataset = Dataset::new(vec![
+            user("a", [10.0, 10.0, 4.0]),
+            user("b", [5.0, 5.0, 8.0]),
+        ]);
+        let mut graph = build_graph(dataset, Viewport::default());
+        graph.apply_filter(GraphFilter::default().with_edges(EdgeKind::Similarity, false));
+        let edges = graph.render_edges(usize::MAX, 0.0);
+        assert!(!edges.is_empty());
+        assert!(edges.iter().all(|edge| edge.kind == EdgeKind::Rating));
+    }
+
+    #[test]
+    fn relabelled_anime_are_searched_by_their_new_title() {
+        let dataset = Dataset::new(vec![user("a", [10.0, 10.0, 4.0])]);
+        let mut graph = build_graph(dataset, Viewport::default());
+        graph.relabel_anime(|anime_id| (anime_id == 2).then(|| "蟲師".to_string()));
+        let relabelled = graph.find_anime(2).unwrap();
+        assert_eq!(graph.find_anime_by_title("蟲師", 5), vec![relabelled]);
+        assert_eq!(graph.nodes[graph.find_anime(1).unwrap()].label, "Anime 1");
+    }
+
+    #[test]
+    fn user_label_shortens_ids_on_character_boundaries() {
+        assert_eq!(user_label("0123456789abcdef"), "User 01234567");
+        assert_eq!(user_label("ユーザー名ユーザー名"), "User ユーザー名ユーザ");
+        assert_eq!(user_label("短い"), "User 短い");
+    }
+}
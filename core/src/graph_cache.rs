@@ -0,0 +1,370 @@
+//! A built [`GraphModel`] as bytes, so a frontend can keep it between launches and skip
+//! building when the same dataset opens with the same settings. Graphs are keyed by
+//! [`key`], a hash of the dataset and the settings; a cached graph with another key, another
+//! format version or anything out of place decodes to `None` and is simply rebuilt.
+//!
+//! Only rating graphs are worth keying this way: facet graphs also depend on fetched
+//! metadata, which the key does not cover.
+
+use crate::adjacency::Adjacency;
+use crate::community::Communities;
+use crate::neighbors::TopNeighbors;
+use crate::{Dataset, Edge, EdgeKind, GraphModel, GraphSettings, Node, NodeType};
+
+const MAGIC: &[u8; 8] = b"WASIWGRF";
+/// Bumped whenever the layout below or the graph it stores changes, so older caches are
+/// rebuilt. 2: duplicate ratings fold into one edge and an anime has no edge to itself.
+const VERSION: u32 = 2;
+
+/// 64-bit FNV-1a, which unlike the standard hasher is the same in every build.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn str(&mut self, text: &str) {
+        self.bytes(&(text.len() as u64).to_le_bytes());
+        self.bytes(text.as_bytes());
+    }
+}
+
+/// Identifies the graph `settings` build from `dataset`: its users, ratings and titles, and
+/// every setting.
+pub fn key(dataset: &Dataset, settings: &GraphSettings) -> u64 {
+    let mut hash = Fnv::new();
+    for user in &dataset.users {
+        hash.str(&user.user_id);
+        hash.bytes(&(user.ratings.len() as u64).to_le_bytes());
+        for rating in &user.ratings {
+            hash.bytes(&rating.anime_id.to_le_bytes());
+            hash.bytes(&rating.raw_score.to_le_bytes());
+            hash.str(&rating.title);
+        }
+    }
+    let serialized = serde_json::to_string(&(
+        settings.sampling,
+        settings.mode,
+        settings.layout,
+        settings.filter,
+    ))
+    .unwrap_or_default();
+    hash.str(&serialized);
+    hash.bytes(&(settings.similar_neighbors as u64).to_le_bytes());
+    hash.bytes(&settings.layout_seed.to_le_bytes());
+    let viewport = settings.viewport;
+    for value in [viewport.width, viewport.height, viewport.scale_factor] {
+        hash.bytes(&value.to_le_bytes());
+    }
+    hash.0
+}
+
+/// `graph` under `key`, little-endian: the magic, version and key, then the nodes, edges,
+/// strongest neighbors and communities. The adjacency index is rebuilt on decoding.
+pub fn encode(graph: &GraphModel, key: u64) -> Vec<u8> {
+    let mut out = Writer(Vec::with_capacity(
+        64 + graph.nodes.len() * 64 + graph.edges.len() * 32,
+    ));
+    out.0.extend_from_slice(MAGIC);
+    out.u32(VERSION);
+    out.u64(key);
+
+    out.len(graph.nodes.len());
+    for node in &graph.nodes {
+        out.str(&node.id);
+        out.str(&node.label);
+        out.0.push(match node.node_type {
+            NodeType::User => 0,
+            NodeType::Anime => 1,
+            NodeType::Genre => 2,
+            NodeType::Studio => 3,
+            NodeType::Director => 4,
+        });
+        out.f32(node.x);
+        out.f32(node.y);
+        out.f32(node.radius);
+        out.0.push(u8::from(node.hidden));
+    }
+
+    out.len(graph.edges.len());
+    for edge in &graph.edges {
+        out.len(edge.source);
+        out.len(edge.target);
+        out.0.push(match edge.kind {
+            EdgeKind::Rating => 0,
+            EdgeKind::Similarity => 1,
+        });
+        out.0.extend_from_slice(&edge.weight.to_le_bytes());
+        out.len(edge.co_raters);
+        out.f32(edge.stroke_width);
+    }
+
+    let (offsets, edges) = graph.top_neighbors.parts();
+    out.len(graph.top_neighbors.k());
+    out.u32s(offsets);
+    out.u32s(edges);
+
+    let communities = &graph.communities;
+    out.len(communities.node_community.len());
+    for &community in &communities.node_community {
+        out.len(community);
+    }
+    out.len(communities.members.len());
+    for members in &communities.members {
+        out.len(members.len());
+        for &member in members {
+            out.len(member);
+        }
+    }
+    out.0
+}
+
+/// The graph [`encode`] stored in `bytes`, if it was stored under `key`, with the settings it
+/// was built with filled back in from `settings`.
+pub fn decode(bytes: &[u8], key: u64, settings: GraphSettings) -> Option<GraphModel> {
+    let mut input = Reader(bytes);
+    if input.take(MAGIC.len())? != MAGIC || input.u32()? != VERSION || input.u64()? != key {
+        return None;
+    }
+
+    let node_count = input.len()?;
+    let mut nodes = Vec::with_capacity(node_count.min(bytes.len()));
+    for _ in 0..node_count {
+        nodes.push(Node {
+            id: input.str()?,
+            label: input.str()?,
+            node_type: match input.u8()? {
+                0 => NodeType::User,
+                1 => NodeType::Anime,
+                2 => NodeType::Genre,
+                3 => NodeType::Studio,
+                4 => NodeType::Director,
+                _ => return None,
+            },
+            x: input.f32()?,
+            y: input.f32()?,
+            radius: input.f32()?,
+            hidden: input.u8()? != 0,
+        });
+    }
+
+    let edge_count = input.len()?;
+    let mut edges = Vec::with_capacity(edge_count.min(bytes.len()));
+    for _ in 0..edge_count {
+        let edge = Edge {
+            source: input.len()?,
+            target: input.len()?,
+            kind: match input.u8()? {
+                0 => EdgeKind::Rating,
+                1 => EdgeKind::Similarity,
+                _ => return None,
+            },
+            weight: f64::from_le_bytes(input.take(8)?.try_into().ok()?),
+            co_raters: input.len()?,
+            stroke_width: input.f32()?,
+        };
+        if edge.source >= node_count || edge.target >= node_count {
+            return None;
+        }
+        edges.push(edge);
+    }
+
+    let k = input.len()?;
+    let offsets = input.u32s()?;
+    let listed = input.u32s()?;
+    // Facet graphs keep no strongest neighbors at all.
+    let offsets_fit = (offsets.is_empty() && listed.is_empty())
+        || (offsets.len() == node_count + 1
+            && offsets.first() == Some(&0)
+            && offsets.windows(2).all(|pair| pair[0] <= pair[1])
+            && offsets.last().map(|&end| end as usize) == Some(listed.len()));
+    if !offsets_fit || listed.iter().any(|&edge| edge as usize >= edge_count) {
+        return None;
+    }
+
+    let assigned = input.len()?;
+    let node_community = (0..assigned)
+        .map(|_| input.len())
+        .collect::<Option<Vec<_>>>()?;
+    let community_count = input.len()?;
+    let mut members = Vec::with_capacity(community_count.min(bytes.len()));
+    for _ in 0..community_count {
+        let count = input.len()?;
+        members.push(
+            (0..count)
+                .map(|_| input.len())
+                .collect::<Option<Vec<_>>>()?,
+        );
+    }
+    let members_fit = members.iter().flatten().all(|&member| member < node_count);
+    if assigned != node_count || !members_fit || !input.0.is_empty() {
+        return None;
+    }
+
+    let user_count = nodes
+        .iter()
+        .filter(|node| node.node_type == NodeType::User)
+        .count();
+    Some(GraphModel {
+        user_count,
+        anime_count: nodes.len() - user_count,
+        adjacency: Adjacency::new(nodes.len(), &edges),
+        nodes,
+        edges,
+        top_neighbors: TopNeighbors::from_parts(k, offsets, listed),
+        similar_neighbors: settings.similar_neighbors,
+        communities: Communities {
+            node_community,
+            members,
+        },
+        layout: settings.layout,
+        layout_seed: settings.layout_seed,
+        filter: settings.filter,
+        sampling: settings.sampling,
+        mode: settings.mode,
+        layout_viewport: settings.viewport,
+    })
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn len(&mut self, value: usize) {
+        self.u64(value as u64);
+    }
+
+    fn str(&mut self, text: &str) {
+        self.len(text.len());
+        self.0.extend_from_slice(text.as_bytes());
+    }
+
+    fn u32s(&mut self, values: &[u32]) {
+        self.len(values.len());
+        for &value in values {
+            self.u32(value);
+        }
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Option<&'a [u8]> {
+        if self.0.len() < count {
+            return None;
+        }
+        let (taken, rest) = self.0.split_at(count);
+        self.0 = rest;
+        Some(taken)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+
+    fn f32(&mut self) -> Option<f32> {
+        Some(f32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn len(&mut self) -> Option<usize> {
+        usize::try_from(self.u64()?).ok()
+    }
+
+    fn str(&mut self) -> Option<String> {
+        let len = self.len()?;
+        String::from_utf8(self.take(len)?.to_vec()).ok()
+    }
+
+    fn u32s(&mut self) -> Option<Vec<u32>> {
+        let count = self.len()?;
+        if count > self.0.len() / 4 {
+            return None;
+        }
+        (0..count).map(|_| self.u32()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_graph, Rating, UserRatings};
+
+    fn dataset() -> Dataset {
+        Dataset::new(
+            (0..12_u32)
+                .map(|u| UserRatings {
+                    user_id: format!("user-{u}"),
+                    ratings: (0..6)
+                        .map(|k| {
+                            let anime_id = 1 + (u + k * 3) % 15;
+                            Rating::new(anime_id, format!("Anime {anime_id}"), f64::from(k + 4))
+                        })
+                        .collect(),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn round_trips_a_graph_under_its_key() {
+        let graph = build_graph(dataset(), crate::Viewport::default());
+        let settings = graph.settings();
+        let key = key(&dataset(), &settings);
+        let bytes = encode(&graph, key);
+
+        let decoded = decode(&bytes, key, settings).expect("decodes");
+        assert_eq!(decoded.nodes.len(), graph.nodes.len());
+        assert_eq!(decoded.positions(), graph.positions());
+        let weights = |graph: &GraphModel| -> Vec<(usize, usize, f64)> {
+            graph
+                .edges
+                .iter()
+                .map(|edge| (edge.source, edge.target, edge.weight))
+                .collect()
+        };
+        assert_eq!(weights(&decoded), weights(&graph));
+        assert_eq!(
+            decoded.communities.node_community,
+            graph.communities.node_community
+        );
+        assert_eq!(
+            decoded.top_neighbors.edges(0).collect::<Vec<_>>(),
+            graph.top_neighbors.edges(0).collect::<Vec<_>>()
+        );
+        assert_eq!(decoded.links(0).count(), graph.links(0).count());
+
+        // Another key, or a cut-off file, is a miss.
+        assert!(decode(&bytes, key ^ 1, settings).is_none());
+        assert!(decode(&bytes[..bytes.len() - 1], key, settings).is_none());
+        let mut reseeded = settings;
+        reseeded.layout_seed += 1;
+        assert_ne!(super::key(&dataset(), &reseeded), key);
+    }
+}
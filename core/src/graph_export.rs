@@ -0,0 +1,479 @@
+//! Writes the graph as shown — visible nodes, typed and weighted edges, community labels and
+//! layout positions — as GraphML, GEXF (Gephi) or DOT (Graphviz), or as the graph JSON the web
+//! app reads (`graph.json` from the pipeline).
+
+use std::io::{self, Write};
+use std::path::Path;
+
+use quick_xml::escape::escape;
+use serde_json::json;
+
+use crate::{compression, EdgeKind, GraphModel, Node, NodeType};
+
+/// Graphviz colour scheme with 12 distinct fill colours, cycled by community.
+const DOT_COLOR_SCHEME: &str = "set312";
+const DOT_COLORS: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    GraphMl,
+    Gexf,
+    Dot,
+    /// The web app's `graph.json` shape, without layout or communities.
+    Json,
+}
+
+impl GraphFormat {
+    pub const ALL: [GraphFormat; 4] = [Self::GraphMl, Self::Gexf, Self::Dot, Self::Json];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::GraphMl => "GraphML",
+            Self::Gexf => "GEXF (Gephi)",
+            Self::Dot => "DOT (Graphviz)",
+            Self::Json => "Graph JSON (web app)",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::GraphMl => "graphml",
+            Self::Gexf => "gexf",
+            Self::Dot => "dot",
+            Self::Json => "json",
+        }
+    }
+
+    /// Format for a file name, looking past a `.gz` or `.zst` suffix.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = compression::inner_extension(path)?;
+        match extension.as_str() {
+            "gv" => Some(Self::Dot),
+            other => Self::ALL
+                .into_iter()
+                .find(|format| format.extension() == other),
+        }
+    }
+}
+
+pub fn write(writer: &mut dyn Write, graph: &GraphModel, format: GraphFormat) -> io::Result<()> {
+    match format {
+        GraphFormat::GraphMl => write_graphml(writer, graph),
+        GraphFormat::Gexf => write_gexf(writer, graph),
+        GraphFormat::Dot => write_dot(writer, graph),
+        GraphFormat::Json => write_json(writer, graph),
+    }
+}
+
+fn node_type(node: &Node) -> &'static str {
+    match node.node_type {
+        NodeType::User => "user",
+        NodeType::Anime => "anime",
+        NodeType::Genre => "genre",
+        NodeType::Studio => "studio",
+        NodeType::Director => "director",
+    }
+}
+
+fn edge_kind(kind: EdgeKind) -> &'static str {
+    match kind {
+        EdgeKind::Rating => "rating",
+        EdgeKind::Similarity => "similarity",
+    }
+}
+
+fn community(graph: &GraphModel, node: usize) -> Option<usize> {
+    graph.communities.node_community.get(node).copied()
+}
+
+fn visible_nodes(graph: &GraphModel) -> impl Iterator<Item = (usize, &Node)> {
+    graph
+        .nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| !node.hidden)
+}
+
+fn visible_edges(graph: &GraphModel) -> impl Iterator<Item = (usize, &crate::Edge)> {
+    graph
+        .edges
+        .iter()
+        .enumerate()
+        .filter(|(_, edge)| graph.is_edge_visible(edge))
+}
+
+fn write_graphml(w: &mut dyn Write, graph: &GraphModel) -> io::Result<()> {
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        w,
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+    )?;
+    for (id, domain, kind) in [
+        ("label", "node", "string"),
+        ("type", "node", "string"),
+        ("community", "node", "int"),
+        ("x", "node", "float"),
+        ("y", "node", "float"),
+        ("kind", "edge", "string"),
+        ("weight", "edge", "double"),
+        ("co_raters", "edge", "int"),
+    ] {
+        writeln!(
+            w,
+            r#"  <key id="{id}" for="{domain}" attr.name="{id}" attr.type="{kind}"/>"#
+        )?;
+    }
+    writeln!(w, r#"  <graph id="anime-graph" edgedefault="undirected">"#)?;
+    for (index, node) in visible_nodes(graph) {
+        writeln!(w, r#"    <node id="{}">"#, escape(node.id.as_str()))?;
+        writeln!(
+            w,
+            r#"      <data key="label">{}</data>"#,
+            escape(node.label.as_str())
+        )?;
+        writeln!(w, r#"      <data key="type">{}</data>"#, node_type(node))?;
+        if let Some(community) = community(graph, index) {
+            writeln!(w, r#"      <data key="community">{community}</data>"#)?;
+        }
+        writeln!(w, r#"      <data key="x">{}</data>"#, node.x)?;
+        writeln!(w, r#"      <data key="y">{}</data>"#, node.y)?;
+        writeln!(w, "    </node>")?;
+    }
+    for (index, edge) in visible_edges(graph) {
+        writeln!(
+            w,
+            r#"    <edge id="e{index}" source="{}" target="{}">"#,
+            escape(graph.nodes[edge.source].id.as_str()),
+            escape(graph.nodes[edge.target].id.as_str())
+        )?;
+        writeln!(
+            w,
+            r#"      <data key="kind">{}</data>"#,
+            edge_kind(edge.kind)
+        )?;
+        writeln!(w, r#"      <data key="weight">{}</data>"#, edge.weight)?;
+        writeln!(
+            w,
+            r#"      <data key="co_raters">{}</data>"#,
+            edge.co_raters
+        )?;
+        writeln!(w, "    </edge>")?;
+    }
+    writeln!(w, "  </graph>")?;
+    writeln!(w, "</graphml>")
+}
+
+fn write_gexf(w: &mut dyn Write, graph: &GraphModel) -> io::Result<()> {
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        w,
+        r#"<gexf xmlns="http://gexf.net/1.3" xmlns:viz="http://gexf.net/1.3/viz" version="1.3">"#
+    )?;
+    writeln!(w, r#"  <graph defaultedgetype="undirected" mode="static">"#)?;
+    writeln!(w, r#"    <attributes class="node">"#)?;
+    writeln!(
+        w,
+        r#"      <attribute id="type" title="type" type="string"/>"#
+    )?;
+    writeln!(
+        w,
+        r#"      <attribute id="community" title="community" type="integer"/>"#
+    )?;
+    writeln!(w, "    </attributes>")?;
+    writeln!(w, r#"    <attributes class="edge">"#)?;
+    writeln!(
+        w,
+        r#"      <attribute id="kind" title="kind" type="string"/>"#
+    )?;
+    writeln!(
+        w,
+        r#"      <attribute id="co_raters" title="co_raters" type="integer"/>"#
+    )?;
+    writeln!(w, "    </attributes>")?;
+
+    writeln!(w, "    <nodes>")?;
+    for (index, node) in visible_nodes(graph) {
+        writeln!(
+            w,
+            r#"      <node id="{}" label="{}">"#,
+            escape(node.id.as_str()),
+            escape(node.label.as_str())
+        )?;
+        writeln!(w, "        <attvalues>")?;
+        writeln!(
+            w,
+            r#"          <attvalue for="type" value="{}"/>"#,
+            node_type(node)
+        )?;
+        if let Some(community) = community(graph, index) {
+            writeln!(
+                w,
+                r#"          <attvalue for="community" value="{community}"/>"#
+            )?;
+        }
+        writeln!(w, "        </attvalues>")?;
+        // GEXF's y axis points up; the canvas's points down.
+        writeln!(
+            w,
+            r#"        <viz:position x="{}" y="{}" z="0"/>"#,
+            node.x, -node.y
+        )?;
+        writeln!(w, r#"        <viz:size value="{}"/>"#, node.radius)?;
+        writeln!(w, "      </node>")?;
+    }
+    writeln!(w, "    </nodes>")?;
+
+    writeln!(w, "    <edges>")?;
+    for (index, edge) in visible_edges(graph) {
+        writeln!(
+            w,
+            r#"      <edge id="{index}" source="{}" target="{}" weight="{}">"#,
+            escape(graph.nodes[edge.source].id.as_str()),
+            escape(graph.nodes[edge.target].id.as_str()),
+            edge.weight
+        )?;
+        writeln!(w, "        <attvalues>")?;
+        writeln!(
+            w,
+            r#"          <attvalue for="kind" value="{}"/>"#,
+            edge_kind(edge.kind)
+        )?;
+        writeln!(
+            w,
+            r#"          <attvalue for="co_raters" value="{}"/>"#,
+            edge.co_raters
+        )?;
+        writeln!(w, "        </attvalues>")?;
+        writeln!(w, "      </edge>")?;
+    }
+    writeln!(w, "    </edges>")?;
+    writeln!(w, "  </graph>")?;
+    writeln!(w, "</gexf>")
+}
+
+/// Quotes a DOT identifier.
+fn dot_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Weights go into a custom `score` attribute: Graphviz's own `weight` must be a
+/// non-negative integer, and normalized scores are often negative.
+fn write_dot(w: &mut dyn Write, graph: &GraphModel) -> io::Result<()> {
+    writeln!(w, "graph anime_graph {{")?;
+    writeln!(
+        w,
+        "  node [style=filled, colorscheme={DOT_COLOR_SCHEME}, fontsize=10];"
+    )?;
+    for (index, node) in visible_nodes(graph) {
+        let shape = match node.node_type {
+            NodeType::User => "circle",
+            NodeType::Anime => "ellipse",
+            NodeType::Genre | NodeType::Studio | NodeType::Director => "box",
+        };
+        write!(
+            w,
+            "  {} [label={}, type={}, shape={shape}, pos=\"{},{}\"",
+            dot_string(&node.id),
+            dot_string(&node.label),
+            node_type(node),
+            node.x,
+            -node.y
+        )?;
+        if let Some(community) = community(graph, index) {
+            write!(
+                w,
+                ", community={community}, fillcolor={}",
+                community % DOT_COLORS + 1
+            )?;
+        }
+        writeln!(w, "];")?;
+    }
+    for (_, edge) in visible_edges(graph) {
+        writeln!(
+            w,
+            "  {} -- {} [kind={}, score={}, co_raters={}, penwidth={}];",
+            dot_string(&graph.nodes[edge.source].id),
+            dot_string(&graph.nodes[edge.target].id),
+            edge_kind(edge.kind),
+            edge.weight,
+            edge.co_raters,
+            edge.stroke_width
+        )?;
+    }
+    writeln!(w, "}}")
+}
+
+/// Node and edge ids follow the pipeline's `build-graph`: `ua:<userId>:<animeId>` for
+/// ratings and `aa:<low>:<high>` for similarity pairs.
+fn write_json(w: &mut dyn Write, graph: &GraphModel) -> io::Result<()> {
+    let nodes: Vec<serde_json::Value> = visible_nodes(graph)
+        .map(|(_, node)| json!({ "id": node.id, "label": node.label, "nodeType": node_type(node) }))
+        .collect();
+    let edges: Vec<serde_json::Value> = visible_edges(graph)
+        .map(|(_, edge)| {
+            let (source, target) = (&graph.nodes[edge.source], &graph.nodes[edge.target]);
+            let local = |node: &Node| node.id.split_once(':').map_or("", |(_, id)| id).to_string();
+            let (id, edge_type) = match edge.kind {
+                EdgeKind::Rating => (
+                    format!("ua:{}:{}", local(source), local(target)),
+                    "user-anime",
+                ),
+                EdgeKind::Similarity => {
+                    let (a, b) = (source.anime_id(), target.anime_id());
+                    let (low, high) = (a.min(b), a.max(b));
+                    let id = format!(
+                        "aa:{}:{}",
+                        low.unwrap_or_default(),
+                        high.unwrap_or_default()
+                    );
+                    (id, "anime-anime")
+                }
+            };
+            json!({
+                "id": id,
+                "source": source.id,
+                "target": target.id,
+                "edgeType": edge_type,
+                "weight": edge.weight,
+            })
+        })
+        .collect();
+    let users = visible_nodes(graph)
+        .filter(|(_, node)| node.node_type == NodeType::User)
+        .count();
+    let data = json!({
+        "userCount": users,
+        "animeCount": nodes.len() - users,
+        "nodeCount": nodes.len(),
+        "edgeCount": edges.len(),
+        "nodes": nodes,
+        "edges": edges,
+    });
+    serde_json::to_writer(&mut *w, &data).map_err(io::Error::other)?;
+    writeln!(w)
+}
+
+#[cfg(test)]
+mod tests {
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    use super::*;
+    use crate::{build_graph, Dataset, Rating, UserRatings, Viewport};
+
+    const TRICKY_TITLE: &str = r#"Kaguya-sama <Love & "War">"#;
+
+    fn graph() -> GraphModel {
+        let ratings = |user_id: &str| UserRatings {
+            user_id: user_id.to_string(),
+            ratings: vec![
+                Rating::new(1, TRICKY_TITLE.to_string(), 9.0),
+                Rating::new(2, r"Back\slash".to_string(), 7.0),
+                Rating::new(3, "Hidden".to_string(), 5.0),
+            ],
+        };
+        build_graph(
+            Dataset::new(vec![ratings("a"), ratings("b"), ratings("c")]),
+            Viewport::default(),
+        )
+    }
+
+    fn export(graph: &GraphModel, format: GraphFormat) -> String {
+        let mut out = Vec::new();
+        write(&mut out, graph, format).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    /// Every `label` attribute or `label` data element in a well-formed XML document.
+    fn xml_labels(xml: &str) -> Vec<String> {
+        let mut reader = Reader::from_str(xml);
+        let mut labels = Vec::new();
+        let mut in_label = false;
+        loop {
+            match reader.read_event().unwrap() {
+                Event::Eof => return labels,
+                Event::Start(tag) | Event::Empty(tag) => {
+                    in_label = tag
+                        .try_get_attribute("key")
+                        .unwrap()
+                        .is_some_and(|key| key.value.as_ref() == b"label");
+                    if let Some(label) = tag.try_get_attribute("label").unwrap() {
+                        labels.push(label.unescape_value().unwrap().into_owned());
+                    }
+                }
+                Event::Text(text) if in_label => {
+                    labels.push(text.unescape().unwrap().into_owned());
+                    in_label = false;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn xml_formats_escape_titles() {
+        let graph = graph();
+        for format in [GraphFormat::GraphMl, GraphFormat::Gexf] {
+            let xml = export(&graph, format);
+            assert!(!xml.contains(TRICKY_TITLE), "{format:?}");
+            assert!(
+                xml_labels(&xml).iter().any(|label| label == TRICKY_TITLE),
+                "{format:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn dot_quotes_labels() {
+        let dot = export(&graph(), GraphFormat::Dot);
+        assert!(dot.contains(r#"label="Kaguya-sama <Love & \"War\">""#));
+        assert!(dot.contains(r#"label="Back\\slash""#));
+        assert!(dot.starts_with("graph anime_graph {") && dot.trim_end().ends_with('}'));
+    }
+
+    #[test]
+    fn only_visible_nodes_and_their_edges_are_written() {
+        let mut graph = graph();
+        let hidden = graph.find_anime(3).unwrap();
+        graph.nodes[hidden].hidden = true;
+        for format in GraphFormat::ALL {
+            let out = export(&graph, format);
+            assert!(!out.contains("Hidden"), "{format:?}");
+            assert!(
+                !out.contains(&format!("\"{}\"", graph.nodes[hidden].id)),
+                "{format:?}"
+            );
+        }
+
+        let json: serde_json::Value =
+            serde_json::from_str(&export(&graph, GraphFormat::Json)).unwrap();
+        let visible = graph.nodes.iter().filter(|node| !node.hidden).count();
+        let edges = graph
+            .edges
+            .iter()
+            .filter(|edge| graph.is_edge_visible(edge))
+            .count();
+        assert_eq!(json["nodeCount"], visible);
+        assert_eq!(json["userCount"], 3);
+        assert_eq!(json["edgeCount"], edges);
+        assert_eq!(json["edges"].as_array().unwrap().len(), edges);
+        assert!(json["edges"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|edge| edge["id"] == "ua:a:1"));
+    }
+
+    #[test]
+    fn formats_are_recognized_past_compression_suffixes() {
+        assert_eq!(
+            GraphFormat::from_path(Path::new("graph.gexf.gz")),
+            Some(GraphFormat::Gexf)
+        );
+        assert_eq!(
+            GraphFormat::from_path(Path::new("graph.gv")),
+            Some(GraphFormat::Dot)
+        );
+        assert_eq!(GraphFormat::from_path(Path::new("graph.txt")), None);
+    }
+}
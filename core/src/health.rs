@@ -0,0 +1,362 @@
+//! Dataset health: what lenient loading dropped, sorted into checks, together with what it
+//! lets through but still skews the graph. User ids that differ only in case or spacing, one
+//! anime listed under different titles or one title under different ids, and users whose
+//! ratings are a copy of another's usually mean the same person or show was counted twice.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde::Serialize;
+
+use crate::quality::{IssueKind, QualityReport};
+use crate::Dataset;
+
+/// Examples listed per check at most; the count covers the rest.
+pub const EXAMPLES: usize = 20;
+/// Ratings a user needs before an identical list counts as a copy; a couple of shared ratings
+/// match by chance.
+pub const MIN_COPIED_RATINGS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Data was dropped on load.
+    Error,
+    /// Loaded, but probably not what the source meant.
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CheckKind {
+    ScoresOutOfRange,
+    EmptyUsers,
+    OtherDrops,
+    IgnoredFields,
+    DuplicateRatings,
+    UserIdCollisions,
+    TitleMismatches,
+    SharedTitles,
+    CopiedUsers,
+}
+
+impl CheckKind {
+    /// Every check, in the order reports list them.
+    pub const ALL: [CheckKind; 9] = [
+        Self::ScoresOutOfRange,
+        Self::EmptyUsers,
+        Self::OtherDrops,
+        Self::IgnoredFields,
+        Self::DuplicateRatings,
+        Self::UserIdCollisions,
+        Self::TitleMismatches,
+        Self::SharedTitles,
+        Self::CopiedUsers,
+    ];
+
+    pub fn severity(self) -> Severity {
+        match self {
+            Self::ScoresOutOfRange | Self::EmptyUsers | Self::OtherDrops => Severity::Error,
+            _ => Severity::Warning,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::ScoresOutOfRange => "Scores outside their scale (dropped)",
+            Self::EmptyUsers => "Users without usable ratings (dropped)",
+            Self::OtherDrops => "Other users and ratings dropped",
+            Self::IgnoredFields => "Optional fields ignored",
+            Self::DuplicateRatings => "Repeated ratings resolved",
+            Self::UserIdCollisions => "User ids differing only in case or spacing",
+            Self::TitleMismatches => "Anime listed under different titles",
+            Self::SharedTitles => "Titles shared by different anime ids",
+            Self::CopiedUsers => "Users whose ratings copy another's",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Check {
+    pub kind: CheckKind,
+    pub severity: Severity,
+    /// Ratings, users, anime or titles found; 0 when the check passed.
+    pub count: usize,
+    /// At most [`EXAMPLES`], e.g. `#5114: "Fullmetal Alchemist" ×12, "Hagane no Renkin" ×3`.
+    pub examples: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    pub users: usize,
+    pub ratings: usize,
+    /// Failed checks of each severity.
+    pub errors: usize,
+    pub warnings: usize,
+    /// Every check in [`CheckKind::ALL`] order, passed ones included.
+    pub checks: Vec<Check>,
+}
+
+impl HealthReport {
+    /// Checks `dataset` as loaded, with `load` saying what loading it dropped.
+    pub fn new(dataset: &Dataset, load: &QualityReport) -> Self {
+        let issues = |kind: IssueKind| -> Vec<String> {
+            load.issues
+                .iter()
+                .filter(|issue| issue.kind == kind)
+                .take(EXAMPLES)
+                .map(|issue| match issue.anime_id {
+                    Some(anime_id) => format!("{} anime {anime_id}: {}", issue.user, issue.reason),
+                    None => format!("{}: {}", issue.user, issue.reason),
+                })
+                .collect()
+        };
+        let other_drops = (load.users_dropped + load.ratings_dropped)
+            .saturating_sub(load.scores_out_of_range + load.empty_users);
+        let (collisions, collision_examples) = user_id_collisions(dataset);
+        let (mismatches, mismatch_examples) = title_mismatches(dataset);
+        let (shared, shared_examples) = shared_titles(dataset);
+        let (copies, copy_examples) = copied_users(dataset);
+        let found = |kind: CheckKind| match kind {
+            CheckKind::ScoresOutOfRange => {
+                (load.scores_out_of_range, issues(IssueKind::ScoreOutOfRange))
+            }
+            CheckKind::EmptyUsers => (load.empty_users, issues(IssueKind::NoRatings)),
+            CheckKind::OtherDrops => (other_drops, issues(IssueKind::Other)),
+            CheckKind::IgnoredFields => (load.fields_ignored, issues(IssueKind::IgnoredField)),
+            CheckKind::DuplicateRatings => (load.duplicates_resolved, Vec::new()),
+            CheckKind::UserIdCollisions => (collisions, collision_examples.clone()),
+            CheckKind::TitleMismatches => (mismatches, mismatch_examples.clone()),
+            CheckKind::SharedTitles => (shared, shared_examples.clone()),
+            CheckKind::CopiedUsers => (copies, copy_examples.clone()),
+        };
+        let checks: Vec<Check> = CheckKind::ALL
+            .into_iter()
+            .map(|kind| {
+                let (count, examples) = found(kind);
+                Check {
+                    kind,
+                    severity: kind.severity(),
+                    count,
+                    examples,
+                }
+            })
+            .collect();
+        let failed = |severity| {
+            checks
+                .iter()
+                .filter(|check| check.severity == severity && check.count > 0)
+                .count()
+        };
+        Self {
+            users: dataset.users.len(),
+            ratings: dataset.users.iter().map(|user| user.ratings.len()).sum(),
+            errors: failed(Severity::Error),
+            warnings: failed(Severity::Warning),
+            checks,
+        }
+    }
+
+    /// Whether every check passed.
+    pub fn is_healthy(&self) -> bool {
+        self.errors == 0 && self.warnings == 0
+    }
+
+    /// The checks that failed, errors first.
+    pub fn problems(&self) -> impl Iterator<Item = &Check> {
+        self.checks.iter().filter(|check| check.count > 0)
+    }
+}
+
+/// Case and spacing folded away, for comparing ids and titles.
+fn fold(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Groups of users whose ids fold to the same text, counting the users beyond the first.
+fn user_id_collisions(dataset: &Dataset) -> (usize, Vec<String>) {
+    let mut groups: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for user in &dataset.users {
+        groups
+            .entry(fold(&user.user_id))
+            .or_default()
+            .push(&user.user_id);
+    }
+    let groups: Vec<_> = groups.into_values().filter(|ids| ids.len() > 1).collect();
+    let count = groups.iter().map(|ids| ids.len() - 1).sum();
+    let examples = groups
+        .iter()
+        .take(EXAMPLES)
+        .map(|ids| {
+            ids.iter()
+                .map(|id| format!("{id:?}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .collect();
+    (count, examples)
+}
+
+/// Anime whose raters gave them titles that differ beyond case and spacing.
+fn title_mismatches(dataset: &Dataset) -> (usize, Vec<String>) {
+    // Anime id -> folded title -> (title as first seen, ratings).
+    let mut titles: BTreeMap<u32, BTreeMap<String, (&str, usize)>> = BTreeMap::new();
+    for rating in dataset.users.iter().flat_map(|user| &user.ratings) {
+        titles
+            .entry(rating.anime_id)
+            .or_default()
+            .entry(fold(&rating.title))
+            .or_insert((&rating.title, 0))
+            .1 += 1;
+    }
+    let mismatched: Vec<_> = titles
+        .into_iter()
+        .filter(|(_, variants)| variants.len() > 1)
+        .collect();
+    let examples = mismatched
+        .iter()
+        .take(EXAMPLES)
+        .map(|(anime_id, variants)| {
+            let mut variants: Vec<_> = variants.values().collect();
+            variants.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+            let listed: Vec<_> = variants
+                .iter()
+                .map(|(title, ratings)| format!("{title:?} ×{ratings}"))
+                .collect();
+            format!("#{anime_id}: {}", listed.join(", "))
+        })
+        .collect();
+    (mismatched.len(), examples)
+}
+
+/// Titles, folded, that more than one anime id is listed under.
+fn shared_titles(dataset: &Dataset) -> (usize, Vec<String>) {
+    let mut ids: BTreeMap<String, (&str, BTreeSet<u32>)> = BTreeMap::new();
+    for rating in dataset.users.iter().flat_map(|user| &user.ratings) {
+        ids.entry(fold(&rating.title))
+            .or_insert((&rating.title, BTreeSet::new()))
+            .1
+            .insert(rating.anime_id);
+    }
+    let shared: Vec<_> = ids.into_values().filter(|(_, ids)| ids.len() > 1).collect();
+    let examples = shared
+        .iter()
+        .take(EXAMPLES)
+        .map(|(title, ids)| {
+            let ids: Vec<_> = ids.iter().map(|id| format!("#{id}")).collect();
+            format!("{title:?}: {}", ids.join(", "))
+        })
+        .collect();
+    (shared.len(), examples)
+}
+
+/// Users with at least [`MIN_COPIED_RATINGS`] ratings, all of them the same anime with the
+/// same scores as an earlier user's, counting the copies beyond the first.
+fn copied_users(dataset: &Dataset) -> (usize, Vec<String>) {
+    let mut lists: HashMap<Vec<(u32, u64)>, Vec<&str>> = HashMap::new();
+    for user in &dataset.users {
+        if user.ratings.len() < MIN_COPIED_RATINGS {
+            continue;
+        }
+        let mut list: Vec<(u32, u64)> = user
+            .ratings
+            .iter()
+            .map(|rating| (rating.anime_id, rating.raw_score.to_bits()))
+            .collect();
+        list.sort_unstable();
+        lists.entry(list).or_default().push(&user.user_id);
+    }
+    let mut groups: Vec<(usize, Vec<&str>)> = lists
+        .into_iter()
+        .filter(|(_, users)| users.len() > 1)
+        .map(|(list, users)| (list.len(), users))
+        .collect();
+    groups.sort_by(|a, b| a.1.cmp(&b.1));
+    let count = groups.iter().map(|(_, users)| users.len() - 1).sum();
+    let examples = groups
+        .iter()
+        .take(EXAMPLES)
+        .map(|(ratings, users)| format!("{} ({ratings} ratings)", users.join(", ")))
+        .collect();
+    (count, examples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MergeStrategy, Rating, UserRatings};
+
+    fn user(user_id: &str, ratings: &[(u32, &str, f64)]) -> UserRatings {
+        UserRatings {
+            user_id: user_id.to_string(),
+            ratings: ratings
+                .iter()
+                .map(|&(anime_id, title, score)| Rating::new(anime_id, title.to_string(), score))
+                .collect(),
+        }
+    }
+
+    fn check(report: &HealthReport, kind: CheckKind) -> &Check {
+        report
+            .checks
+            .iter()
+            .find(|check| check.kind == kind)
+            .unwrap()
+    }
+
+    #[test]
+    fn finds_collisions_mismatches_and_copies() {
+        let many: Vec<(u32, &str, f64)> = (1..=5).map(|id| (id, "Show", 7.0)).collect();
+        let dataset = Dataset::new(vec![
+            user("Alice", &[(10, "Clannad", 9.0), (11, "Kanon", 8.0)]),
+            user("alice ", &[(10, "CLANNAD", 8.0), (12, "Clannad", 7.0)]),
+            user("bob", &many),
+            user("carol", &many),
+        ]);
+        let report = HealthReport::new(&dataset, &QualityReport::default());
+        assert_eq!(check(&report, CheckKind::UserIdCollisions).count, 1);
+        // Case alone isn't a mismatch, but every "Show" id shares one title.
+        assert_eq!(check(&report, CheckKind::TitleMismatches).count, 0);
+        let shared = check(&report, CheckKind::SharedTitles);
+        assert_eq!(shared.count, 2);
+        assert!(shared
+            .examples
+            .contains(&"\"Clannad\": #10, #12".to_string()));
+        let copies = check(&report, CheckKind::CopiedUsers);
+        assert_eq!(copies.count, 1);
+        assert_eq!(copies.examples, ["bob, carol (5 ratings)"]);
+        assert_eq!((report.errors, report.warnings), (0, 3));
+        assert!(!report.is_healthy());
+    }
+
+    #[test]
+    fn sorts_what_loading_dropped_into_checks() {
+        let json = r#"{"users": [
+            {"userId": "a", "ratings": [
+                {"animeId": 1, "title": "One", "rawScore": 8},
+                {"animeId": 1, "title": "Uno", "rawScore": 6},
+                {"animeId": 2, "title": "Two", "rawScore": 12},
+                {"animeId": 3, "rawScore": 5}
+            ]},
+            {"userId": "b", "ratings": []}
+        ]}"#;
+        let (dataset, load) =
+            crate::dataset::parse(json.as_bytes(), MergeStrategy::default()).unwrap();
+        let report = HealthReport::new(&dataset, &load);
+        assert_eq!(check(&report, CheckKind::ScoresOutOfRange).count, 1);
+        assert_eq!(check(&report, CheckKind::EmptyUsers).count, 1);
+        assert_eq!(check(&report, CheckKind::OtherDrops).count, 1);
+        assert_eq!(check(&report, CheckKind::DuplicateRatings).count, 1);
+        assert_eq!(
+            check(&report, CheckKind::ScoresOutOfRange).examples,
+            ["a anime 2: score 12 is outside 1–10"]
+        );
+        assert_eq!(report.errors, 3);
+        let first = report.problems().next().unwrap();
+        assert_eq!(first.kind, CheckKind::ScoresOutOfRange);
+    }
+}
@@ -0,0 +1,43 @@
+//! Small numbers for the strings graphs are built from, so the construction loops hash and
+//! copy `u32`s instead of formatting and cloning ids for every rating and pair.
+
+use std::collections::HashMap;
+
+/// Numbers strings `0, 1, 2, …` in the order they are first seen; each string is copied once.
+#[derive(Debug, Default)]
+pub(crate) struct Interner {
+    ids: HashMap<String, u32>,
+    strings: Vec<String>,
+}
+
+impl Interner {
+    /// The number of `value`, handing out the next one the first time it is seen.
+    pub(crate) fn intern(&mut self, value: &str) -> u32 {
+        if let Some(&id) = self.ids.get(value) {
+            return id;
+        }
+        let id = self.strings.len() as u32;
+        self.ids.insert(value.to_string(), id);
+        self.strings.push(value.to_string());
+        id
+    }
+
+    /// The string numbered `id`.
+    pub(crate) fn resolve(&self, id: u32) -> &str {
+        &self.strings[id as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_strings_in_first_seen_order() {
+        let mut interner = Interner::default();
+        assert_eq!(interner.intern("b"), 0);
+        assert_eq!(interner.intern("a"), 1);
+        assert_eq!(interner.intern("b"), 0);
+        assert_eq!(interner.resolve(1), "a");
+    }
+}
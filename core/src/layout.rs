@@ -0,0 +1,280 @@
+//! Node layouts: concentric rings, or one disc per community.
+
+use std::f32::consts::TAU;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+use crate::community::Communities;
+use crate::graph::{Node, NodeType};
+
+/// Canvas size assumed until the real one is known.
+pub const DEFAULT_WIDTH: f32 = 1040.0;
+pub const DEFAULT_HEIGHT: f32 = 760.0;
+/// Relative change in canvas width or height that calls for a fresh layout.
+const RELAYOUT_THRESHOLD: f32 = 0.2;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayoutKind {
+    /// Users on an outer ring, anime in a banded inner ring.
+    #[default]
+    Rings,
+    /// Each community gathered into its own disc, discs arranged on a circle.
+    Communities,
+}
+
+impl LayoutKind {
+    pub const ALL: [LayoutKind; 2] = [Self::Rings, Self::Communities];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Rings => "Rings",
+            Self::Communities => "Communities",
+        }
+    }
+}
+
+/// The canvas a layout is computed for, in CSS pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub width: f32,
+    pub height: f32,
+    pub scale_factor: f32,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self {
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            scale_factor: 1.0,
+        }
+    }
+}
+
+impl Viewport {
+    /// Whether a layout computed for `other` looks out of place on this canvas.
+    pub fn differs_significantly(&self, other: &Viewport) -> bool {
+        let relative = |a: f32, b: f32| (a - b).abs() / b.max(1.0);
+        relative(self.width, other.width) > RELAYOUT_THRESHOLD
+            || relative(self.height, other.height) > RELAYOUT_THRESHOLD
+    }
+}
+
+/// Target position for every node. Hidden nodes keep their current position so they
+/// reappear where they were when a filter is relaxed. A `seed` of 0 places nodes in dataset
+/// order; any other seed shuffles the order around each ring and inside each disc, the same way
+/// every time.
+#[tracing::instrument(level = "debug", skip(nodes, communities), fields(nodes = nodes.len()))]
+pub fn compute(
+    nodes: &[Node],
+    communities: &Communities,
+    kind: LayoutKind,
+    seed: u64,
+    viewport: Viewport,
+) -> Vec<(f32, f32)> {
+    let mut positions: Vec<(f32, f32)> = nodes.iter().map(|n| (n.x, n.y)).collect();
+    let mut rng = (seed != 0).then(|| StdRng::seed_from_u64(seed));
+    let mut shuffle = |order: &mut [usize]| {
+        if let Some(rng) = &mut rng {
+            order.shuffle(rng);
+        }
+    };
+    match kind {
+        LayoutKind::Rings => rings(nodes, viewport, &mut shuffle, &mut positions),
+        LayoutKind::Communities => {
+            clustered(nodes, communities, viewport, &mut shuffle, &mut positions)
+        }
+    }
+    positions
+}
+
+fn rings(
+    nodes: &[Node],
+    viewport: Viewport,
+    shuffle: &mut impl FnMut(&mut [usize]),
+    positions: &mut [(f32, f32)],
+) {
+    let mut users = Vec::new();
+    let mut anime = Vec::new();
+
+    for (idx, node) in nodes.iter().enumerate() {
+        if node.hidden {
+            continue;
+        }
+        if node.node_type == NodeType::User {
+            users.push(idx);
+        } else {
+            anime.push(idx);
+        }
+    }
+
+    shuffle(&mut users);
+    shuffle(&mut anime);
+
+    let center_x = viewport.width / 2.0;
+    let center_y = viewport.height / 2.0;
+
+    for (i, idx) in users.iter().enumerate() {
+        let angle = (i as f32 / users.len().max(1) as f32) * TAU;
+        let radius = (viewport.height.min(viewport.width) * 0.38).max(200.0);
+        positions[*idx] = (
+            center_x + radius * angle.cos(),
+            center_y + radius * angle.sin(),
+        );
+    }
+
+    for (i, idx) in anime.iter().enumerate() {
+        let angle = (i as f32 / anime.len().max(1) as f32) * TAU;
+        let band = 120.0 + ((i % 7) as f32 * 17.0);
+        let jitter = ((i * 29 % 17) as f32) - 8.0;
+        positions[*idx] = (
+            center_x + (band + jitter) * angle.cos(),
+            center_y + (band - jitter) * angle.sin(),
+        );
+    }
+}
+
+fn clustered(
+    nodes: &[Node],
+    communities: &Communities,
+    viewport: Viewport,
+    shuffle: &mut impl FnMut(&mut [usize]),
+    positions: &mut [(f32, f32)],
+) {
+    let mut groups: Vec<Vec<usize>> = communities
+        .members
+        .iter()
+        .map(|members| {
+            members
+                .iter()
+                .copied()
+                .filter(|n| !nodes[*n].hidden)
+                .collect::<Vec<_>>()
+        })
+        .filter(|members| !members.is_empty())
+        .collect();
+    for members in &mut groups {
+        shuffle(members);
+    }
+
+    let center_x = viewport.width / 2.0;
+    let center_y = viewport.height / 2.0;
+    let ring = if groups.len() > 1 {
+        viewport.height.min(viewport.width) * 0.34
+    } else {
+        0.0
+    };
+
+    for (g, members) in groups.iter().enumerate() {
+        let angle = (g as f32 / groups.len() as f32) * TAU;
+        let group_x = center_x + ring * angle.cos();
+        let group_y = center_y + ring * angle.sin();
+        // Sunflower spiral keeps members evenly spaced inside the disc.
+        for (i, idx) in members.iter().enumerate() {
+            let r = 9.0 * (i as f32).sqrt();
+            let theta = i as f32 * 2.399_963;
+            positions[*idx] = (group_x + r * theta.cos(), group_y + r * theta.sin());
+        }
+    }
+}
+
+/// Smoothstep easing for transition progress in `[0, 1]`.
+pub fn ease(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+pub fn interpolate(from: &[(f32, f32)], to: &[(f32, f32)], t: f32) -> Vec<(f32, f32)> {
+    from.iter()
+        .zip(to)
+        .map(|(a, b)| (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_graph, Dataset, GraphModel, Rating, UserRatings};
+
+    fn graph() -> GraphModel {
+        let users = (0..6)
+            .map(|index| UserRatings {
+                user_id: format!("user{index}"),
+                ratings: (0..4)
+                    .map(|anime_id| {
+                        let anime_id = anime_id + 10 * (index % 2);
+                        Rating::new(anime_id, format!("Anime {anime_id}"), 8.0)
+                    })
+                    .collect(),
+            })
+            .collect();
+        build_graph(Dataset::new(users), Viewport::default())
+    }
+
+    #[test]
+    fn rings_put_users_outside_the_anime() {
+        let graph = graph();
+        let viewport = Viewport::default();
+        let positions = compute(
+            &graph.nodes,
+            &graph.communities,
+            LayoutKind::Rings,
+            0,
+            viewport,
+        );
+        let center = (viewport.width / 2.0, viewport.height / 2.0);
+        let distance = |(x, y): (f32, f32)| (x - center.0).hypot(y - center.1);
+        let outer = viewport.height.min(viewport.width) * 0.38;
+        for (node, &position) in graph.nodes.iter().zip(&positions) {
+            if node.node_type == NodeType::User {
+                assert!((distance(position) - outer).abs() < 0.01);
+            } else {
+                assert!(distance(position) < outer * 0.85);
+            }
+        }
+    }
+
+    #[test]
+    fn seeds_shuffle_the_same_way_every_time_and_hidden_nodes_stay_put() {
+        let mut graph = graph();
+        graph.nodes[0].hidden = true;
+        graph.nodes[0].x = -5.0;
+        let layout = |kind, seed| {
+            compute(
+                &graph.nodes,
+                &graph.communities,
+                kind,
+                seed,
+                Viewport::default(),
+            )
+        };
+        for kind in LayoutKind::ALL {
+            assert_eq!(layout(kind, 7), layout(kind, 7), "{kind:?}");
+            assert_eq!(layout(kind, 7)[0].0, -5.0, "{kind:?}");
+        }
+        assert_ne!(layout(LayoutKind::Rings, 7), layout(LayoutKind::Rings, 0));
+    }
+
+    #[test]
+    fn transitions_ease_between_layouts() {
+        assert_eq!((ease(-1.0), ease(0.5), ease(2.0)), (0.0, 0.5, 1.0));
+        let from = [(0.0, 0.0), (10.0, 10.0)];
+        let to = [(10.0, 20.0), (10.0, 10.0)];
+        assert_eq!(interpolate(&from, &to, 0.5), [(5.0, 10.0), (10.0, 10.0)]);
+        let viewport = Viewport::default();
+        let wider = Viewport {
+            width: viewport.width * 1.5,
+            ..viewport
+        };
+        assert!(wider.differs_significantly(&viewport));
+        let nudged = Viewport {
+            height: viewport.height * 1.1,
+            ..viewport
+        };
+        assert!(!nudged.differs_significantly(&viewport));
+    }
+}
@@ -0,0 +1,92 @@
+//! The engine behind What Anime Should I Watch, shared by every frontend.
+//!
+//! - [`dataset`] reads the pipeline's `anonymized-ratings.json` (plain, gzip or zstd, or
+//!   Parquet with the `parquet` feature), checking every user and rating on the way in
+//!   ([`quality`]), migrating older schema versions ([`schema`]) and converting score scales
+//!   ([`scale`]); [`health`] sorts what was dropped into checks alongside ones for duplicate
+//!   ids, mismatched titles and copied users.
+//! - [`graph`] builds the user–anime rating graph with anime–anime similarity edges, detects
+//!   communities ([`community`]) and lays it out ([`layout`]), and [`delta`] updates it for one
+//!   user's changed ratings; [`facets`] builds the genre, studio or director graph instead.
+//!   [`matrix`] holds the ratings as a sparse users × anime matrix for the passes over all of
+//!   them, and [`memory`] estimates what it all takes.
+//!   [`algorithms`] lets petgraph's algorithms run on the graph as it is, and [`graph_cache`]
+//!   keeps a built one between launches.
+//! - [`recommend`] ranks unrated anime for a user through the similarity edges, and lists the
+//!   anime most similar to another or that two users would both enjoy; [`content`] ranks anime
+//!   outside the graph, such as a new season, by their genres, studios and year instead.
+//!   [`relations`] links sequels, prequels and side stories into franchises, so a list can
+//!   name each franchise once, and [`watch_order`] puts a franchise or a list in the order to
+//!   watch it; [`budget`] picks the recommendations that best fill a given amount of time;
+//!   [`moods`] groups tags into moods such as "cozy" or "dark" to filter lists by.
+//! - [`stats`] sums up a dataset and its graph, [`analytics`] one anime's or user's ratings or
+//!   two side by side, [`activity`] the days ratings were made on, and [`tribes`] groups users
+//!   by taste; [`titles`] picks between an anime's romaji, English and native titles;
+//!   [`airing`] works out when weekly shows last aired.
+//! - [`graph_export`] writes the graph as GraphML, GEXF, DOT or the web app's graph JSON;
+//!   [`merge`] combines datasets; [`sampling`] builds graphs from part of a large corpus;
+//!   [`synthetic`] makes up realistic ratings for demos and benchmarks.
+//!
+//! ```no_run
+//! use wasiw_core::{build_graph, dataset, recommend, MergeStrategy, Viewport};
+//!
+//! let (dataset, report) =
+//!     dataset::read("anonymized-ratings.json".as_ref(), MergeStrategy::default())?;
+//! println!("{}", report.summary());
+//! let graph = build_graph(dataset, Viewport::default());
+//! let user = graph.nodes.iter().position(|node| node.id == "user:0123abcd").unwrap();
+//! for recommendation in recommend::recommend(&graph, user, 10) {
+//!     println!("{:+.2} {}", recommendation.score, recommendation.title);
+//! }
+//! # Ok::<(), wasiw_core::DatasetError>(())
+//! ```
+
+pub mod activity;
+pub mod adjacency;
+pub mod airing;
+pub mod algorithms;
+pub mod analytics;
+pub mod budget;
+#[cfg(feature = "parquet")]
+pub mod columnar;
+pub mod community;
+pub mod compression;
+pub mod content;
+pub mod dataset;
+pub mod delta;
+mod error;
+pub mod facets;
+pub mod graph;
+pub mod graph_cache;
+pub mod graph_export;
+pub mod health;
+mod intern;
+pub mod layout;
+pub mod matrix;
+pub mod memory;
+pub mod merge;
+pub mod moods;
+pub mod neighbors;
+pub mod quality;
+pub mod recommend;
+pub mod relations;
+pub mod sampling;
+pub mod scale;
+pub mod schema;
+pub mod stats;
+pub mod synthetic;
+pub mod titles;
+pub mod tribes;
+pub mod watch_order;
+
+pub use dataset::{Dataset, Rating, UserRatings};
+pub use error::DatasetError;
+pub use facets::GraphMode;
+pub use graph::{
+    build_graph, build_graph_with_progress, BuildProgress, BuildStage, Edge, EdgeKind,
+    FindUserError, GraphFilter, GraphModel, GraphSettings, Node, NodeType, RenderEdge,
+};
+pub use layout::{LayoutKind, Viewport};
+pub use matrix::RatingMatrix;
+pub use merge::MergeStrategy;
+pub use quality::QualityReport;
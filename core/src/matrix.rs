@@ -0,0 +1,172 @@
+//! The users × anime rating matrix in compressed sparse row form, so code that walks every
+//! rating reads a few flat arrays instead of each user's `Vec<Rating>` with its titles.
+
+use std::collections::HashMap;
+
+use crate::Dataset;
+
+/// One row per user, in dataset order, and one column per anime, in the order they first
+/// appear. Row `r`'s ratings are at `row_offsets[r]..row_offsets[r + 1]` of the entry arrays,
+/// in the user's own order.
+#[derive(Debug, Clone, Default)]
+pub struct RatingMatrix {
+    row_offsets: Vec<usize>,
+    columns: Vec<u32>,
+    scores: Vec<f64>,
+    /// Each score minus its user's mean, as in [`Dataset::normalize_scores`].
+    centred: Vec<f64>,
+    anime_ids: Vec<u32>,
+    column_of: HashMap<u32, u32>,
+}
+
+/// One user's ratings in a [`RatingMatrix`], as parallel slices.
+#[derive(Debug, Clone, Copy)]
+pub struct MatrixRow<'a> {
+    pub columns: &'a [u32],
+    pub scores: &'a [f64],
+    pub centred: &'a [f64],
+}
+
+impl MatrixRow<'_> {
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /// The user's mean score, or 0 without ratings.
+    pub fn mean(&self) -> f64 {
+        if self.is_empty() {
+            0.0
+        } else {
+            self.scores.iter().sum::<f64>() / self.len() as f64
+        }
+    }
+}
+
+impl RatingMatrix {
+    pub fn new(dataset: &Dataset) -> Self {
+        let entries: usize = dataset.users.iter().map(|user| user.ratings.len()).sum();
+        let mut matrix = Self {
+            row_offsets: Vec::with_capacity(dataset.users.len() + 1),
+            columns: Vec::with_capacity(entries),
+            scores: Vec::with_capacity(entries),
+            centred: Vec::with_capacity(entries),
+            ..Self::default()
+        };
+        matrix.row_offsets.push(0);
+        for user in &dataset.users {
+            let mean = if user.ratings.is_empty() {
+                0.0
+            } else {
+                user.ratings.iter().map(|r| r.raw_score).sum::<f64>() / user.ratings.len() as f64
+            };
+            for rating in &user.ratings {
+                let next = matrix.anime_ids.len() as u32;
+                let column = *matrix.column_of.entry(rating.anime_id).or_insert(next);
+                if column == next {
+                    matrix.anime_ids.push(rating.anime_id);
+                }
+                matrix.columns.push(column);
+                matrix.scores.push(rating.raw_score);
+                matrix.centred.push(rating.raw_score - mean);
+            }
+            matrix.row_offsets.push(matrix.columns.len());
+        }
+        matrix
+    }
+
+    /// Users, the same as the dataset's.
+    pub fn rows(&self) -> usize {
+        self.row_offsets.len().saturating_sub(1)
+    }
+
+    /// Distinct anime rated.
+    pub fn column_count(&self) -> usize {
+        self.anime_ids.len()
+    }
+
+    /// Ratings in all.
+    pub fn entries(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// The ratings of the user at `row` of the dataset.
+    pub fn row(&self, row: usize) -> MatrixRow<'_> {
+        let range = self.row_offsets[row]..self.row_offsets[row + 1];
+        MatrixRow {
+            columns: &self.columns[range.clone()],
+            scores: &self.scores[range.clone()],
+            centred: &self.centred[range],
+        }
+    }
+
+    /// The anime id of `column`.
+    pub fn anime_id(&self, column: u32) -> u32 {
+        self.anime_ids[column as usize]
+    }
+
+    /// The column of `anime_id`, if anyone rated it.
+    pub fn column(&self, anime_id: u32) -> Option<u32> {
+        self.column_of.get(&anime_id).copied()
+    }
+
+    /// Sum and count of each column's scores.
+    pub fn column_totals(&self) -> Vec<(f64, usize)> {
+        let mut totals = vec![(0.0, 0); self.column_count()];
+        for (&column, &score) in self.columns.iter().zip(&self.scores) {
+            let total = &mut totals[column as usize];
+            total.0 += score;
+            total.1 += 1;
+        }
+        totals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Rating, UserRatings};
+
+    fn user(user_id: &str, ratings: &[(u32, f64)]) -> UserRatings {
+        UserRatings {
+            user_id: user_id.to_string(),
+            ratings: ratings
+                .iter()
+                .map(|&(anime_id, score)| Rating::new(anime_id, format!("Anime {anime_id}"), score))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn rows_hold_each_users_ratings_centred_on_their_mean() {
+        let mut dataset = Dataset::new(vec![
+            user("a", &[(7, 9.0), (3, 5.0)]),
+            user("b", &[]),
+            user("c", &[(3, 8.0)]),
+        ]);
+        let matrix = RatingMatrix::new(&dataset);
+        assert_eq!(
+            (matrix.rows(), matrix.column_count(), matrix.entries()),
+            (3, 2, 3)
+        );
+        let first = matrix.row(0);
+        assert_eq!(first.columns, [0, 1]);
+        assert_eq!(first.centred, [2.0, -2.0]);
+        assert_eq!(first.mean(), 7.0);
+        assert!(matrix.row(1).is_empty());
+        assert_eq!(matrix.row(2).columns, [matrix.column(3).unwrap()]);
+        assert_eq!(matrix.anime_id(0), 7);
+        assert_eq!(matrix.column_totals(), [(9.0, 1), (13.0, 2)]);
+
+        dataset.normalize_scores();
+        let normalized: Vec<f64> = dataset.users[0]
+            .ratings
+            .iter()
+            .map(|r| r.normalized_score)
+            .collect();
+        assert_eq!(first.centred, normalized);
+    }
+}
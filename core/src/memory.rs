@@ -0,0 +1,205 @@
+//! Rough memory accounting: what the dataset, the graph and its similarity structures take,
+//! worked out from their lengths and capacities rather than measured, and the sample a budget
+//! allows when the whole corpus would not fit.
+
+use std::collections::HashSet;
+use std::mem::size_of;
+
+use crate::sampling::Sampling;
+use crate::{Dataset, Edge, EdgeKind, GraphModel, Node, Rating, UserRatings};
+
+/// Users a budget never samples below; fewer says little about anyone's taste.
+pub const MIN_BUDGET_USERS: usize = 100;
+/// Bytes per summed anime pair while a graph builds: the map entry and its share of the
+/// table's spare room.
+const PAIR_ENTRY_BYTES: usize = 48;
+
+/// Approximate bytes held by each part of an open dataset and its graph.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    pub dataset: usize,
+    /// Nodes, rating edges and communities.
+    pub graph: usize,
+    /// Similarity edges and the adjacency index.
+    pub similarity: usize,
+    /// Whatever the frontend keeps besides, e.g. posters; filled in by the caller.
+    pub caches: usize,
+}
+
+impl MemoryUsage {
+    pub fn new(dataset: &Dataset, graph: &GraphModel, caches: usize) -> Self {
+        let similarity_edges = graph
+            .edges
+            .iter()
+            .filter(|edge| edge.kind == EdgeKind::Similarity)
+            .count();
+        let rating_edges = graph.edges.len() - similarity_edges;
+        let nodes: usize = graph
+            .nodes
+            .iter()
+            .map(|node| size_of::<Node>() + node.id.capacity() + node.label.capacity())
+            .sum();
+        let communities = graph.communities.node_community.capacity() * size_of::<usize>()
+            + graph
+                .communities
+                .members
+                .iter()
+                .map(|members| size_of::<Vec<usize>>() + members.capacity() * size_of::<usize>())
+                .sum::<usize>();
+        Self {
+            dataset: dataset_bytes(dataset),
+            graph: nodes + rating_edges * size_of::<Edge>() + communities,
+            similarity: similarity_edges * size_of::<Edge>() + graph.adjacency.memory_bytes(),
+            caches,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.dataset + self.graph + self.similarity + self.caches
+    }
+}
+
+/// Approximate bytes held by `dataset`'s users and ratings.
+pub fn dataset_bytes(dataset: &Dataset) -> usize {
+    let string = |text: &String| text.capacity();
+    let optional = |text: &Option<String>| text.as_ref().map_or(0, String::capacity);
+    dataset
+        .users
+        .iter()
+        .map(|user| {
+            let ratings: usize = user
+                .ratings
+                .iter()
+                .map(|rating| {
+                    string(&rating.title)
+                        + optional(&rating.status)
+                        + optional(&rating.updated_at)
+                        + rating.genres.capacity() * size_of::<String>()
+                        + rating.genres.iter().map(string).sum::<usize>()
+                })
+                .sum();
+            size_of::<UserRatings>()
+                + string(&user.user_id)
+                + user.ratings.capacity() * size_of::<Rating>()
+                + ratings
+        })
+        .sum()
+}
+
+/// Approximate peak bytes of building the rating graph for `dataset`, beyond the dataset
+/// itself. Anime pairs are counted once per co-rater, so it errs high for dense corpora.
+pub fn estimate_graph_bytes(dataset: &Dataset) -> usize {
+    let mut anime = HashSet::new();
+    let mut ratings = 0;
+    let mut pairs = 0_usize;
+    for user in &dataset.users {
+        let count = user.ratings.len();
+        ratings += count;
+        pairs = pairs.saturating_add(count * count.saturating_sub(1) / 2);
+        anime.extend(user.ratings.iter().map(|rating| rating.anime_id));
+    }
+    let pairs = pairs.min(anime.len() * anime.len().saturating_sub(1) / 2);
+    // A node's id and label are a few dozen bytes.
+    let nodes = (dataset.users.len() + anime.len()) * (size_of::<Node>() + 32);
+    // Every edge is listed twice in the adjacency index.
+    let per_edge = size_of::<Edge>() + 2 * size_of::<u32>();
+    nodes + ratings * per_edge + pairs.saturating_mul(per_edge + PAIR_ENTRY_BYTES)
+}
+
+/// A sample of `dataset` whose graph fits in `budget` bytes along with the dataset, or `None`
+/// when `current` already fits or keeps fewer users than that. Stratified, so light and heavy
+/// raters keep their share, and never below [`MIN_BUDGET_USERS`].
+pub fn fit_sampling(dataset: &Dataset, current: Sampling, budget: usize) -> Option<Sampling> {
+    let users = dataset.users.len();
+    let available = budget.saturating_sub(dataset_bytes(dataset));
+    let needed = match current {
+        Sampling::All => estimate_graph_bytes(dataset),
+        _ => estimate_graph_bytes(&current.apply(dataset.clone())),
+    };
+    if needed <= available {
+        return None;
+    }
+    // Pairs grow with each user's ratings rather than with the user count, so the estimate
+    // shrinks about in proportion to the users kept.
+    let whole = estimate_graph_bytes(dataset).max(1);
+    let count = ((users as f64 * available as f64 / whole as f64) as usize)
+        .max(MIN_BUDGET_USERS)
+        .min(users);
+    let kept = match current {
+        Sampling::RandomUsers { count } | Sampling::Stratified { count } => count,
+        Sampling::All | Sampling::TopAnime { .. } => users,
+    };
+    (count < kept.min(users)).then_some(Sampling::Stratified { count })
+}
+
+/// `bytes` for display, e.g. `12.5 MB`.
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_graph, Viewport};
+
+    fn dataset(users: u32) -> Dataset {
+        Dataset::new(
+            (0..users)
+                .map(|u| UserRatings {
+                    user_id: format!("user-{u}"),
+                    ratings: (0..20)
+                        .map(|k| {
+                            let anime_id = 1 + (u + k * 7) % 300;
+                            Rating::new(
+                                anime_id,
+                                format!("Anime {anime_id}"),
+                                1.0 + f64::from(k % 10),
+                            )
+                        })
+                        .collect(),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn usage_counts_every_part() {
+        let dataset = dataset(30);
+        let graph = build_graph(dataset.clone(), Viewport::default());
+        let usage = MemoryUsage::new(&dataset, &graph, 10);
+        assert!(usage.dataset > 30 * 20 * size_of::<Rating>());
+        assert!(usage.graph > 0 && usage.similarity > 0);
+        assert_eq!(
+            usage.total(),
+            usage.dataset + usage.graph + usage.similarity + 10
+        );
+        assert!(estimate_graph_bytes(&dataset) >= usage.graph + usage.similarity);
+    }
+
+    #[test]
+    fn samples_users_only_when_over_budget() {
+        let dataset = dataset(2000);
+        let whole = dataset_bytes(&dataset) + estimate_graph_bytes(&dataset);
+        assert_eq!(fit_sampling(&dataset, Sampling::All, whole), None);
+
+        let Some(Sampling::Stratified { count }) = fit_sampling(&dataset, Sampling::All, whole / 2)
+        else {
+            panic!("expected a stratified sample");
+        };
+        assert!((MIN_BUDGET_USERS..2000).contains(&count));
+        // A smaller sample the user picked is kept.
+        let small = Sampling::RandomUsers { count: 50 };
+        assert_eq!(fit_sampling(&dataset, small, whole / 2), None);
+        assert_eq!(format_bytes(1536), "1.5 KB");
+    }
+}
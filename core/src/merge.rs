@@ -0,0 +1,220 @@
+//! Combines several rating datasets, e.g. community-contributed dumps, into one. Users are
+//! matched by id; when both sides rated the same anime the chosen [`MergeStrategy`] decides the
+//! score.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Dataset, Rating, UserRatings};
+
+/// How a rating present in more than one dataset, or more than once for a user in a single
+/// file, is resolved.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStrategy {
+    /// Keep the rating with the later `updatedAt`; undated ratings lose to dated ones, and
+    /// between two undated ratings the later file wins.
+    #[default]
+    LatestWins,
+    /// Keep the copy with the highest raw score.
+    Highest,
+    /// Average the raw scores of every copy.
+    Average,
+}
+
+impl MergeStrategy {
+    pub const ALL: [MergeStrategy; 3] = [Self::LatestWins, Self::Highest, Self::Average];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::LatestWins => "Latest rating wins",
+            Self::Highest => "Highest score wins",
+            Self::Average => "Average the scores",
+        }
+    }
+
+    /// Folds another copy of the same rating into `existing`. `copies` counts the copies
+    /// already folded into `existing`, for averaging, and is incremented.
+    pub fn resolve(self, existing: &mut Rating, rating: Rating, copies: &mut u32) {
+        match self {
+            Self::LatestWins => {
+                // ISO 8601 dates compare correctly as strings; `None` sorts first.
+                if rating.updated_at >= existing.updated_at {
+                    *existing = rating;
+                }
+            }
+            Self::Highest => {
+                if rating.raw_score > existing.raw_score {
+                    *existing = rating;
+                }
+            }
+            Self::Average => {
+                let total = existing.raw_score * f64::from(*copies) + rating.raw_score;
+                existing.raw_score = total / f64::from(*copies + 1);
+                if rating.updated_at > existing.updated_at {
+                    existing.updated_at = rating.updated_at;
+                    existing.status = rating.status.or(existing.status.take());
+                }
+                if existing.genres.is_empty() {
+                    existing.genres = rating.genres;
+                }
+            }
+        }
+        *copies += 1;
+    }
+}
+
+/// Counts shown after a merge.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MergeStats {
+    pub datasets: usize,
+    pub users_added: usize,
+    /// Users that already existed and received ratings from a later dataset.
+    pub users_merged: usize,
+    pub ratings_added: usize,
+    /// Ratings present in more than one dataset, resolved by the strategy.
+    pub ratings_resolved: usize,
+}
+
+impl MergeStats {
+    pub fn summary(&self) -> String {
+        format!(
+            "Merged {} datasets: {} new users, {} users combined, {} ratings added, {} duplicate ratings resolved.",
+            self.datasets,
+            self.users_added,
+            self.users_merged,
+            self.ratings_added,
+            self.ratings_resolved
+        )
+    }
+}
+
+/// Accumulates datasets one at a time so only the merged result and one input are in memory.
+pub struct Merger {
+    strategy: MergeStrategy,
+    users: Vec<UserRatings>,
+    /// User id -> index into `users`.
+    by_user: HashMap<String, usize>,
+    /// (user index, anime id) -> (index into that user's ratings, copies averaged so far).
+    by_rating: HashMap<(usize, u32), (usize, u32)>,
+    stats: MergeStats,
+}
+
+impl Merger {
+    pub fn new(strategy: MergeStrategy) -> Self {
+        Self {
+            strategy,
+            users: Vec::new(),
+            by_user: HashMap::new(),
+            by_rating: HashMap::new(),
+            stats: MergeStats::default(),
+        }
+    }
+
+    pub fn add(&mut self, dataset: Dataset) {
+        self.stats.datasets += 1;
+        for user in dataset.users {
+            let index = match self.by_user.get(&user.user_id) {
+                Some(&index) => {
+                    self.stats.users_merged += 1;
+                    index
+                }
+                None => {
+                    let index = self.users.len();
+                    self.by_user.insert(user.user_id.clone(), index);
+                    self.users.push(UserRatings {
+                        user_id: user.user_id,
+                        ratings: Vec::with_capacity(user.ratings.len()),
+                    });
+                    self.stats.users_added += 1;
+                    index
+                }
+            };
+            for rating in user.ratings {
+                self.add_rating(index, rating);
+            }
+        }
+    }
+
+    fn add_rating(&mut self, user: usize, rating: Rating) {
+        let ratings = &mut self.users[user].ratings;
+        let Some((position, copies)) = self.by_rating.get_mut(&(user, rating.anime_id)) else {
+            self.by_rating
+                .insert((user, rating.anime_id), (ratings.len(), 1));
+            ratings.push(rating);
+            self.stats.ratings_added += 1;
+            return;
+        };
+
+        self.stats.ratings_resolved += 1;
+        self.strategy
+            .resolve(&mut ratings[*position], rating, copies);
+    }
+
+    pub fn finish(self) -> (Dataset, MergeStats) {
+        (Dataset::new(self.users), self.stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rating(score: f64, updated_at: Option<&str>) -> Rating {
+        let mut rating = Rating::new(1, "One".to_string(), score);
+        rating.updated_at = updated_at.map(str::to_string);
+        rating
+    }
+
+    #[test]
+    fn latest_wins_keeps_the_later_date() {
+        let mut existing = rating(6.0, Some("2024-03-01"));
+        let mut copies = 1;
+        MergeStrategy::LatestWins.resolve(
+            &mut existing,
+            rating(9.0, Some("2023-01-01")),
+            &mut copies,
+        );
+        assert_eq!(existing.raw_score, 6.0);
+        MergeStrategy::LatestWins.resolve(
+            &mut existing,
+            rating(8.0, Some("2024-05-01")),
+            &mut copies,
+        );
+        assert_eq!(existing.raw_score, 8.0);
+        assert_eq!(copies, 3);
+    }
+
+    #[test]
+    fn latest_wins_prefers_dated_and_then_later_copies() {
+        let mut existing = rating(6.0, Some("2024-03-01"));
+        let mut copies = 1;
+        MergeStrategy::LatestWins.resolve(&mut existing, rating(9.0, None), &mut copies);
+        assert_eq!(existing.raw_score, 6.0);
+
+        let mut undated = rating(6.0, None);
+        MergeStrategy::LatestWins.resolve(&mut undated, rating(9.0, None), &mut copies);
+        assert_eq!(undated.raw_score, 9.0);
+    }
+
+    #[test]
+    fn highest_keeps_the_highest_score() {
+        let mut existing = rating(6.0, None);
+        let mut copies = 1;
+        MergeStrategy::Highest.resolve(&mut existing, rating(9.0, None), &mut copies);
+        MergeStrategy::Highest.resolve(&mut existing, rating(7.0, None), &mut copies);
+        assert_eq!(existing.raw_score, 9.0);
+    }
+
+    #[test]
+    fn average_weights_every_copy_equally() {
+        let mut existing = rating(6.0, Some("2024-01-01"));
+        let mut copies = 1;
+        MergeStrategy::Average.resolve(&mut existing, rating(9.0, Some("2024-02-01")), &mut copies);
+        MergeStrategy::Average.resolve(&mut existing, rating(3.0, None), &mut copies);
+        assert!((existing.raw_score - 6.0).abs() < 1e-9);
+        assert_eq!(existing.updated_at.as_deref(), Some("2024-02-01"));
+        assert_eq!(copies, 3);
+    }
+}
@@ -0,0 +1,159 @@
+//! Moods over tags: "cozy", "dark", "tearjerker", "hype" and whatever else a user defines,
+//! each standing for a handful of the AniList tags, Jikan themes and genres anime are listed
+//! with. An anime has a mood when it has any of the mood's tags; the table is the user's to
+//! edit, starting from [`MoodTable::default`].
+
+use serde::{Deserialize, Serialize};
+
+/// One mood and the tags that make an anime have it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mood {
+    pub name: String,
+    pub tags: Vec<String>,
+}
+
+impl Mood {
+    pub fn new(name: &str, tags: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+
+    /// Whether any of `tags` is one of the mood's, ignoring case.
+    pub fn matches(&self, tags: &[String]) -> bool {
+        self.tags
+            .iter()
+            .any(|mood_tag| tags.iter().any(|tag| tag.eq_ignore_ascii_case(mood_tag)))
+    }
+}
+
+/// The moods in the order they are offered.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MoodTable {
+    pub moods: Vec<Mood>,
+}
+
+impl Default for MoodTable {
+    fn default() -> Self {
+        Self {
+            moods: vec![
+                Mood::new(
+                    "Cozy",
+                    &[
+                        "Iyashikei",
+                        "CGDCT",
+                        "Slice of Life",
+                        "Found Family",
+                        "Food",
+                        "Camping",
+                        "Rural",
+                    ],
+                ),
+                Mood::new(
+                    "Dark",
+                    &[
+                        "Psychological",
+                        "Horror",
+                        "Gore",
+                        "Tragedy",
+                        "Revenge",
+                        "Death Game",
+                        "Dystopian",
+                    ],
+                ),
+                Mood::new(
+                    "Tearjerker",
+                    &["Tragedy", "Coming of Age", "Family Life", "Disability"],
+                ),
+                Mood::new(
+                    "Hype",
+                    &[
+                        "Shounen",
+                        "Super Power",
+                        "Martial Arts",
+                        "Battle Royale",
+                        "Mecha",
+                        "Sports",
+                    ],
+                ),
+            ],
+        }
+    }
+}
+
+impl MoodTable {
+    pub fn get(&self, name: &str) -> Option<&Mood> {
+        self.moods.iter().find(|mood| mood.name == name)
+    }
+
+    /// The names of the moods an anime with `tags` has.
+    pub fn moods_of<'a>(&'a self, tags: &'a [String]) -> impl Iterator<Item = &'a str> {
+        self.moods
+            .iter()
+            .filter(|mood| mood.matches(tags))
+            .map(|mood| mood.name.as_str())
+    }
+
+    /// Whether an anime with `tags` has every mood in `names`; moods not in the table are
+    /// ignored.
+    pub fn matches_all<'a>(
+        &self,
+        names: impl IntoIterator<Item = &'a str>,
+        tags: &[String],
+    ) -> bool {
+        names
+            .into_iter()
+            .filter_map(|name| self.get(name))
+            .all(|mood| mood.matches(tags))
+    }
+}
+
+/// Tags typed as a comma-separated list, trimmed and each once.
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+        if !tags.iter().any(|seen| seen.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(list: &[&str]) -> Vec<String> {
+        list.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    #[test]
+    fn anime_have_the_moods_of_any_of_their_tags() {
+        let table = MoodTable::default();
+        let yuru_camp = tags(&["Slice of Life", "camping", "CGDCT"]);
+        let made_in_abyss = tags(&["Adventure", "Tragedy", "Gore"]);
+        assert_eq!(table.moods_of(&yuru_camp).collect::<Vec<_>>(), ["Cozy"]);
+        assert_eq!(
+            table.moods_of(&made_in_abyss).collect::<Vec<_>>(),
+            ["Dark", "Tearjerker"]
+        );
+        assert!(table.matches_all(["Dark", "Tearjerker"], &made_in_abyss));
+        assert!(!table.matches_all(["Dark", "Cozy"], &made_in_abyss));
+        // A mood since removed from the table doesn't filter anything out.
+        assert!(table.matches_all(["Spooky"], &yuru_camp));
+
+        assert_eq!(
+            parse_tags(" Iyashikei, ,Food,iyashikei ,Rural"),
+            ["Iyashikei", "Food", "Rural"]
+        );
+        let edited = MoodTable {
+            moods: vec![Mood {
+                name: "Comfy".to_string(),
+                tags: parse_tags("food, rural"),
+            }],
+        };
+        assert!(edited.matches_all(["Comfy"], &tags(&["Food"])));
+    }
+}
@@ -0,0 +1,153 @@
+//! Each anime's strongest similarity edges. Only those are kept when the rating graph is built,
+//! so its similarity edges grow with the number of anime rather than with the pairs co-rated,
+//! recommendations walk fewer of them, and under the canvas's edge cap every anime still shows
+//! its best neighbors.
+
+use crate::graph::Edge;
+
+/// Similarity edges kept per anime unless the settings say otherwise.
+pub const DEFAULT_NEIGHBORS: usize = 30;
+
+/// Every node's own strongest similarity edges, at most `k`, strongest first, as indices into
+/// `GraphModel::edges`: node `n`'s are `edges[offsets[n]..offsets[n + 1]]`.
+#[derive(Debug, Clone, Default)]
+pub struct TopNeighbors {
+    k: usize,
+    offsets: Vec<u32>,
+    edges: Vec<u32>,
+}
+
+impl TopNeighbors {
+    /// Edges kept per node at most.
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// Indices of `node`'s strongest similarity edges, strongest first.
+    pub fn edges(&self, node: usize) -> impl ExactSizeIterator<Item = usize> + '_ {
+        let range = match (self.offsets.get(node), self.offsets.get(node + 1)) {
+            (Some(&start), Some(&end)) => start as usize..end as usize,
+            _ => 0..0,
+        };
+        self.edges[range].iter().map(|&index| index as usize)
+    }
+
+    /// The offsets and edge indices, for [`crate::graph_cache`].
+    pub(crate) fn parts(&self) -> (&[u32], &[u32]) {
+        (&self.offsets, &self.edges)
+    }
+
+    pub(crate) fn from_parts(k: usize, offsets: Vec<u32>, edges: Vec<u32>) -> Self {
+        Self { k, offsets, edges }
+    }
+}
+
+/// Keeps the `candidates` among the `k` strongest of either of their ends, by score, then
+/// co-raters. The kept edges come back ordered by the best rank they hold at an end, so
+/// everyone's strongest edge comes before anyone's second; the index counts them as if they
+/// were appended to a graph that already has `first_index` edges.
+pub fn keep_strongest(
+    node_count: usize,
+    candidates: Vec<Edge>,
+    k: usize,
+    first_index: usize,
+) -> (Vec<Edge>, TopNeighbors) {
+    let mut order: Vec<usize> = (0..candidates.len()).collect();
+    order.sort_unstable_by(|&a, &b| {
+        let (a, b) = (&candidates[a], &candidates[b]);
+        b.weight
+            .total_cmp(&a.weight)
+            .then(b.co_raters.cmp(&a.co_raters))
+            .then((a.source, a.target).cmp(&(b.source, b.target)))
+    });
+
+    // Walking all candidates strongest first, each one's rank at an end is how many edges of
+    // that end came before it.
+    let mut taken = vec![0_usize; node_count];
+    let mut kept: Vec<(usize, usize)> = Vec::new();
+    let mut own: Vec<(usize, usize)> = Vec::new();
+    for &candidate in &order {
+        let edge = &candidates[candidate];
+        let mut best = usize::MAX;
+        for end in [edge.source, edge.target] {
+            let rank = taken[end];
+            taken[end] += 1;
+            if rank < k {
+                own.push((end, candidate));
+            }
+            best = best.min(rank);
+        }
+        if best < k {
+            kept.push((best, candidate));
+        }
+    }
+    kept.sort_by_key(|&(rank, _)| rank);
+
+    let mut position = vec![u32::MAX; candidates.len()];
+    for (index, &(_, candidate)) in kept.iter().enumerate() {
+        position[candidate] = (first_index + index) as u32;
+    }
+    let mut offsets = vec![0_u32; node_count + 1];
+    for &(node, _) in &own {
+        offsets[node + 1] += 1;
+    }
+    for node in 0..node_count {
+        offsets[node + 1] += offsets[node];
+    }
+    let mut next: Vec<u32> = offsets[..node_count].to_vec();
+    let mut edges = vec![0_u32; own.len()];
+    for (node, candidate) in own {
+        edges[next[node] as usize] = position[candidate];
+        next[node] += 1;
+    }
+
+    let mut candidates: Vec<Option<Edge>> = candidates.into_iter().map(Some).collect();
+    let kept = kept
+        .into_iter()
+        .filter_map(|(_, candidate)| candidates[candidate].take())
+        .collect();
+    (kept, TopNeighbors { k, offsets, edges })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EdgeKind;
+
+    fn edge(source: usize, target: usize, weight: f64) -> Edge {
+        Edge {
+            source,
+            target,
+            kind: EdgeKind::Similarity,
+            weight,
+            co_raters: 1,
+            stroke_width: 1.0,
+        }
+    }
+
+    #[test]
+    fn keeps_the_strongest_of_either_end_best_ranks_first() {
+        // Node 0 touches everything; 3's only edge is 0's weakest, so it stays for 3's sake.
+        let candidates = || {
+            vec![
+                edge(0, 1, 0.9),
+                edge(0, 2, 0.8),
+                edge(0, 3, 0.1),
+                edge(1, 2, 0.5),
+            ]
+        };
+        let (kept, top) = keep_strongest(4, candidates(), 1, 10);
+        let pairs: Vec<(usize, usize)> = kept.iter().map(|e| (e.source, e.target)).collect();
+        // 1–2 is neither end's strongest.
+        assert_eq!(pairs, [(0, 1), (0, 2), (0, 3)]);
+        assert_eq!(top.edges(0).collect::<Vec<_>>(), [10]);
+        assert_eq!(top.edges(2).collect::<Vec<_>>(), [11]);
+        assert_eq!(top.edges(3).collect::<Vec<_>>(), [12]);
+        assert_eq!(top.k(), 1);
+
+        // With two each, 1–2 is kept as second best, after every end's best.
+        let (kept, _) = keep_strongest(4, candidates(), 2, 0);
+        let pairs: Vec<(usize, usize)> = kept.iter().map(|e| (e.source, e.target)).collect();
+        assert_eq!(pairs, [(0, 1), (0, 2), (0, 3), (1, 2)]);
+    }
+}
@@ -0,0 +1,732 @@
+//! Lenient dataset loading. Malformed users and ratings are dropped instead of failing the whole
+//! file, and every drop is recorded in a [`QualityReport`]. An anime rated more than once by
+//! the same user is collapsed into one rating by the configured [`MergeStrategy`].
+//!
+//! Users are validated one at a time while the `users` array streams in, so only a single
+//! user is ever held as an untyped JSON value, and that value borrows its keys and strings
+//! from the input when it is read from memory. Scores are converted from the declared
+//! `scoreScale` once the whole file has been read, since the dataset's own declaration may
+//! come after the users.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use serde::de::{Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+
+use crate::merge::MergeStrategy;
+use crate::scale::ScoreScale;
+use crate::{schema, Dataset, Rating, UserRatings};
+
+/// Issues kept with full detail; beyond this only the counts grow.
+const MAX_LISTED_ISSUES: usize = 500;
+
+/// What kind of problem an [`Issue`] is, for sorting them into checks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IssueKind {
+    /// A score outside the declared scale.
+    ScoreOutOfRange,
+    /// A user without a single usable rating.
+    NoRatings,
+    /// An optional field with the wrong type, ignored.
+    IgnoredField,
+    #[default]
+    Other,
+}
+
+/// One dropped user or rating and why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    /// Position in the `users` array (0-based), plus the user id when it was readable.
+    pub user: String,
+    pub anime_id: Option<u32>,
+    pub reason: String,
+    pub kind: IssueKind,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QualityReport {
+    pub users_dropped: usize,
+    pub ratings_dropped: usize,
+    /// Optional fields that were ignored because they had the wrong type.
+    pub fields_ignored: usize,
+    /// Extra copies of a rating folded into the first one; not listed as issues.
+    pub duplicates_resolved: usize,
+    /// Of the dropped ratings, those with a score outside their scale.
+    pub scores_out_of_range: usize,
+    /// Of the dropped users, those left without a single usable rating.
+    pub empty_users: usize,
+    pub issues: Vec<Issue>,
+}
+
+impl QualityReport {
+    pub fn is_clean(&self) -> bool {
+        self.users_dropped == 0
+            && self.ratings_dropped == 0
+            && self.fields_ignored == 0
+            && self.duplicates_resolved == 0
+    }
+
+    /// Issues beyond the listed ones that were only counted.
+    pub fn unlisted(&self) -> usize {
+        (self.users_dropped + self.ratings_dropped + self.fields_ignored)
+            .saturating_sub(self.issues.len())
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "Dropped {} users and {} ratings; ignored {} invalid optional fields; resolved {} duplicate ratings.",
+            self.users_dropped, self.ratings_dropped, self.fields_ignored, self.duplicates_resolved
+        )
+    }
+
+    /// Adds another file's findings, e.g. when several datasets are merged.
+    pub fn absorb(&mut self, other: QualityReport) {
+        self.users_dropped += other.users_dropped;
+        self.ratings_dropped += other.ratings_dropped;
+        self.fields_ignored += other.fields_ignored;
+        self.duplicates_resolved += other.duplicates_resolved;
+        self.scores_out_of_range += other.scores_out_of_range;
+        self.empty_users += other.empty_users;
+        let room = MAX_LISTED_ISSUES.saturating_sub(self.issues.len());
+        self.issues.extend(other.issues.into_iter().take(room));
+    }
+
+    fn note(&mut self, user: &str, anime_id: Option<u32>, reason: impl Into<String>) {
+        self.note_as(IssueKind::Other, user, anime_id, reason);
+    }
+
+    /// Notes an issue of `kind`, counting it in that kind's total as well.
+    fn note_as(
+        &mut self,
+        kind: IssueKind,
+        user: &str,
+        anime_id: Option<u32>,
+        reason: impl Into<String>,
+    ) {
+        match kind {
+            IssueKind::ScoreOutOfRange => self.scores_out_of_range += 1,
+            IssueKind::NoRatings => self.empty_users += 1,
+            IssueKind::IgnoredField | IssueKind::Other => {}
+        }
+        if self.issues.len() < MAX_LISTED_ISSUES {
+            self.issues.push(Issue {
+                user: user.to_string(),
+                anime_id,
+                reason: reason.into(),
+                kind,
+            });
+        }
+    }
+}
+
+/// Top-level shape read leniently; mirrors `Dataset`.
+#[derive(Deserialize)]
+struct LenientDataset {
+    #[serde(rename = "schemaVersion", default = "schema::legacy_version")]
+    schema_version: u32,
+    #[serde(rename = "generatedAt", default)]
+    generated_at: Option<String>,
+    #[serde(default)]
+    source: Option<String>,
+    #[serde(rename = "scoreScale", default)]
+    score_scale: ScoreScale,
+    users: LenientUsers,
+}
+
+struct LenientUsers {
+    /// Each user with the scale it declared, if it overrides the dataset's.
+    users: Vec<(UserRatings, Option<ScoreScale>)>,
+    report: QualityReport,
+}
+
+impl<'de> Deserialize<'de> for LenientUsers {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(UsersVisitor)
+    }
+}
+
+struct UsersVisitor;
+
+impl<'de> Visitor<'de> for UsersVisitor {
+    type Value = LenientUsers;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of users")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<LenientUsers, A::Error> {
+        let mut users = Vec::new();
+        let mut report = QualityReport::default();
+        let mut seen = HashSet::new();
+        let mut index = 0;
+        while let Some(value) = seq.next_element::<Loose<'de>>()? {
+            if let Some((user, scale)) = validate_user(index, value, &mut report) {
+                if seen.insert(user.user_id.clone()) {
+                    users.push((user, scale));
+                } else {
+                    report.users_dropped += 1;
+                    report.note(&user.user_id, None, "duplicate user id");
+                }
+            }
+            index += 1;
+        }
+        Ok(LenientUsers { users, report })
+    }
+}
+
+/// A JSON value with only the distinctions the checks make. Strings and keys borrow from the
+/// input when the deserializer can lend them, i.e. when reading from a slice and the string
+/// has no escapes.
+#[derive(Debug)]
+enum Loose<'a> {
+    Null,
+    Other,
+    /// `u64` when the number is a non-negative integer, as `Value::as_u64` reads it.
+    Number(Option<u64>, f64),
+    Str(Cow<'a, str>),
+    Array(Vec<Loose<'a>>),
+    /// In input order; the last copy of a repeated key wins, as in a `serde_json::Map`.
+    Object(Vec<(Cow<'a, str>, Loose<'a>)>),
+}
+
+impl<'a> Loose<'a> {
+    fn remove(&mut self, key: &str) -> Option<Loose<'a>> {
+        let Self::Object(fields) = self else {
+            return None;
+        };
+        let position = fields.iter().rposition(|(name, _)| name == key)?;
+        Some(fields.swap_remove(position).1)
+    }
+
+    fn get(&self, key: &str) -> Option<&Loose<'a>> {
+        let Self::Object(fields) = self else {
+            return None;
+        };
+        fields
+            .iter()
+            .rev()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value)
+    }
+
+    fn as_u64(&self) -> Option<u64> {
+        match self {
+            Self::Number(int, _) => *int,
+            _ => None,
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(_, float) => Some(*float),
+            _ => None,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Loose<'de> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(LooseVisitor)
+    }
+}
+
+struct LooseVisitor;
+
+impl<'de> Visitor<'de> for LooseVisitor {
+    type Value = Loose<'de>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_unit<E>(self) -> Result<Loose<'de>, E> {
+        Ok(Loose::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Loose<'de>, E> {
+        Ok(Loose::Null)
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<Loose<'de>, E> {
+        Ok(Loose::Other)
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Loose<'de>, E> {
+        Ok(Loose::Number(Some(value), value as f64))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Loose<'de>, E> {
+        Ok(Loose::Number(u64::try_from(value).ok(), value as f64))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Loose<'de>, E> {
+        Ok(Loose::Number(None, value))
+    }
+
+    fn visit_borrowed_str<E>(self, value: &'de str) -> Result<Loose<'de>, E> {
+        Ok(Loose::Str(Cow::Borrowed(value)))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Loose<'de>, E> {
+        Ok(Loose::Str(Cow::Owned(value.to_string())))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Loose<'de>, E> {
+        Ok(Loose::Str(Cow::Owned(value)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Loose<'de>, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Loose::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Loose<'de>, A::Error> {
+        let mut fields = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(Key(key)) = map.next_key()? {
+            fields.push((key, map.next_value()?));
+        }
+        Ok(Loose::Object(fields))
+    }
+}
+
+/// An object key, borrowed when it can be.
+struct Key<'a>(Cow<'a, str>);
+
+impl<'de> Deserialize<'de> for Key<'de> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match deserializer.deserialize_str(LooseVisitor)? {
+            Loose::Str(key) => Ok(Key(key)),
+            _ => Err(serde::de::Error::custom("object keys are strings")),
+        }
+    }
+}
+
+/// Reads a dataset, dropping what can't be used and resolving repeated ratings with
+/// `duplicates`. Syntax errors still fail the whole file.
+pub fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    duplicates: MergeStrategy,
+) -> Result<(Dataset, QualityReport), D::Error> {
+    let raw = LenientDataset::deserialize(deserializer)?;
+    let mut report = raw.users.report;
+    let users = raw
+        .users
+        .users
+        .into_iter()
+        .filter_map(|(user, scale)| {
+            let user = rescale(user, scale.unwrap_or(raw.score_scale), &mut report)?;
+            Some(resolve_duplicates(user, duplicates, &mut report))
+        })
+        .collect();
+    let dataset = Dataset {
+        schema_version: raw.schema_version,
+        generated_at: raw.generated_at,
+        source: raw.source,
+        users,
+    };
+    Ok((dataset, report))
+}
+
+/// Converts a user's scores to 1–10, dropping the ones that don't fit the declared scale.
+fn rescale(
+    mut user: UserRatings,
+    scale: ScoreScale,
+    report: &mut QualityReport,
+) -> Option<UserRatings> {
+    user.ratings
+        .retain_mut(|rating| match scale.to_ten(rating.raw_score) {
+            Ok(score) => {
+                rating.raw_score = score;
+                true
+            }
+            Err(reason) => {
+                report.ratings_dropped += 1;
+                report.note_as(
+                    IssueKind::ScoreOutOfRange,
+                    &user.user_id,
+                    Some(rating.anime_id),
+                    reason,
+                );
+                false
+            }
+        });
+    if user.ratings.is_empty() {
+        report.users_dropped += 1;
+        report.note_as(
+            IssueKind::NoRatings,
+            &user.user_id,
+            None,
+            "no valid ratings",
+        );
+        return None;
+    }
+    Some(user)
+}
+
+/// Checks a dataset read from Parquet the way [`deserialize`] checks JSON: users without an
+/// id or repeating one are dropped, as are ratings without an anime id or title and scores
+/// outside `scale`, and repeated ratings are resolved with `duplicates`.
+#[cfg(any(feature = "parquet", test))]
+pub fn validate_dataset(
+    mut dataset: Dataset,
+    scale: ScoreScale,
+    duplicates: MergeStrategy,
+) -> (Dataset, QualityReport) {
+    let mut report = QualityReport::default();
+    let mut seen = HashSet::new();
+    dataset.users = std::mem::take(&mut dataset.users)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, mut user)| {
+            if user.user_id.trim().is_empty() {
+                report.users_dropped += 1;
+                report.note(&format!("#{index}"), None, "missing userId");
+                return None;
+            }
+            if !seen.insert(user.user_id.clone()) {
+                report.users_dropped += 1;
+                report.note(&user.user_id, None, "duplicate user id");
+                return None;
+            }
+            let label = format!("#{index} ({})", user.user_id);
+            user.ratings.retain(|rating| {
+                let problem = if rating.anime_id == 0 {
+                    Some((None, "missing or invalid animeId"))
+                } else if rating.title.trim().is_empty() {
+                    Some((Some(rating.anime_id), "missing title"))
+                } else {
+                    None
+                };
+                if let Some((anime_id, reason)) = problem {
+                    report.ratings_dropped += 1;
+                    report.note(&label, anime_id, reason);
+                }
+                problem.is_none()
+            });
+            let user = rescale(user, scale, &mut report)?;
+            Some(resolve_duplicates(user, duplicates, &mut report))
+        })
+        .collect();
+    (dataset, report)
+}
+
+/// Collapses ratings of the same anime, after rescaling so averages are on one scale. The
+/// resolved rating keeps the position of the first copy.
+fn resolve_duplicates(
+    mut user: UserRatings,
+    strategy: MergeStrategy,
+    report: &mut QualityReport,
+) -> UserRatings {
+    // Anime id -> (index into `ratings`, copies folded in so far).
+    let mut seen: HashMap<u32, (usize, u32)> = HashMap::new();
+    let mut ratings: Vec<Rating> = Vec::with_capacity(user.ratings.len());
+    for rating in user.ratings {
+        match seen.get_mut(&rating.anime_id) {
+            Some((position, copies)) => {
+                report.duplicates_resolved += 1;
+                strategy.resolve(&mut ratings[*position], rating, copies);
+            }
+            None => {
+                seen.insert(rating.anime_id, (ratings.len(), 1));
+                ratings.push(rating);
+            }
+        }
+    }
+    user.ratings = ratings;
+    user
+}
+
+fn validate_user(
+    index: usize,
+    mut object: Loose<'_>,
+    report: &mut QualityReport,
+) -> Option<(UserRatings, Option<ScoreScale>)> {
+    let Loose::Object(_) = object else {
+        report.users_dropped += 1;
+        report.note(&format!("#{index}"), None, "user is not an object");
+        return None;
+    };
+    let user_id = match object.remove("userId") {
+        Some(Loose::Str(id)) if !id.trim().is_empty() => id.into_owned(),
+        _ => {
+            report.users_dropped += 1;
+            report.note(&format!("#{index}"), None, "missing userId");
+            return None;
+        }
+    };
+    let label = format!("#{index} ({user_id})");
+    let scale = match object.remove("scoreScale") {
+        None | Some(Loose::Null) => None,
+        Some(value) => match scale_of(value) {
+            Some(scale) => Some(scale),
+            None => {
+                report.users_dropped += 1;
+                report.note(&label, None, "unknown scoreScale");
+                return None;
+            }
+        },
+    };
+    let Some(Loose::Array(entries)) = object.remove("ratings") else {
+        report.users_dropped += 1;
+        report.note_as(IssueKind::NoRatings, &label, None, "missing ratings array");
+        return None;
+    };
+
+    let mut ratings = Vec::with_capacity(entries.len());
+    for entry in entries {
+        match validate_rating(entry, &label, report) {
+            Ok(rating) => ratings.push(rating),
+            Err((anime_id, reason)) => {
+                report.ratings_dropped += 1;
+                report.note(&label, anime_id, reason);
+            }
+        }
+    }
+
+    if ratings.is_empty() {
+        report.users_dropped += 1;
+        report.note_as(IssueKind::NoRatings, &label, None, "no valid ratings");
+        return None;
+    }
+    Some((UserRatings { user_id, ratings }, scale))
+}
+
+/// A user's declared `scoreScale`, if it names one.
+fn scale_of(value: Loose<'_>) -> Option<ScoreScale> {
+    let Loose::Str(name) = value else {
+        return None;
+    };
+    ScoreScale::deserialize(
+        serde::de::value::StrDeserializer::<serde::de::value::Error>::new(&name),
+    )
+    .ok()
+}
+
+fn validate_rating(
+    mut object: Loose<'_>,
+    label: &str,
+    report: &mut QualityReport,
+) -> Result<Rating, (Option<u32>, String)> {
+    let Loose::Object(_) = object else {
+        return Err((None, "rating is not an object".to_string()));
+    };
+    let anime_id = object
+        .get("animeId")
+        .and_then(Loose::as_u64)
+        .and_then(|id| u32::try_from(id).ok())
+        .filter(|id| *id > 0)
+        .ok_or((None, "missing or invalid animeId".to_string()))?;
+    let title = match object.remove("title") {
+        Some(Loose::Str(title)) if !title.trim().is_empty() => title.into_owned(),
+        _ => return Err((Some(anime_id), "missing title".to_string())),
+    };
+    // Checked against the declared scale in `rescale`.
+    let raw_score = object
+        .get("rawScore")
+        .and_then(Loose::as_f64)
+        .ok_or((Some(anime_id), "missing rawScore".to_string()))?;
+
+    let mut rating = Rating::new(anime_id, title, raw_score);
+    let mut ignore = |field: &str| {
+        report.fields_ignored += 1;
+        report.note_as(
+            IssueKind::IgnoredField,
+            label,
+            Some(anime_id),
+            format!("ignored invalid {field}"),
+        );
+    };
+    match object.remove("status") {
+        None | Some(Loose::Null) => {}
+        Some(Loose::Str(status)) => rating.status = Some(status.into_owned()),
+        Some(_) => ignore("status"),
+    }
+    match object.remove("updatedAt") {
+        None | Some(Loose::Null) => {}
+        Some(Loose::Str(date)) => rating.updated_at = Some(date.into_owned()),
+        Some(_) => ignore("updatedAt"),
+    }
+    match object.remove("genres") {
+        None | Some(Loose::Null) => {}
+        Some(Loose::Array(genres)) if genres.iter().all(|g| matches!(g, Loose::Str(_))) => {
+            rating.genres = genres
+                .into_iter()
+                .filter_map(|genre| match genre {
+                    Loose::Str(genre) => Some(genre.into_owned()),
+                    _ => None,
+                })
+                .collect();
+        }
+        Some(_) => ignore("genres"),
+    }
+    Ok(rating)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(user_id: &str, ratings: Vec<Rating>) -> UserRatings {
+        UserRatings {
+            user_id: user_id.to_string(),
+            ratings,
+        }
+    }
+
+    fn load(json: &str, duplicates: MergeStrategy) -> (Dataset, QualityReport) {
+        crate::dataset::parse(json.as_bytes(), duplicates).unwrap()
+    }
+
+    #[test]
+    fn lenient_loading_drops_bad_users_and_ratings() {
+        let (dataset, report) = load(
+            r#"{"users": [
+                {"userId": "a", "ratings": [
+                    {"animeId": 1, "title": "One", "rawScore": 8},
+                    {"animeId": 0, "title": "No id", "rawScore": 8},
+                    {"animeId": 2, "title": "", "rawScore": 8},
+                    {"animeId": 3, "title": "No score"},
+                    {"animeId": 4, "title": "Too high", "rawScore": 11},
+                    {"animeId": 5, "title": "Bad status", "rawScore": 7, "status": 3},
+                    "not a rating"
+                ]},
+                {"userId": "", "ratings": [{"animeId": 1, "title": "One", "rawScore": 8}]},
+                {"userId": "a", "ratings": [{"animeId": 1, "title": "One", "rawScore": 8}]},
+                {"userId": "b"},
+                {"userId": "c", "scoreScale": "1-3", "ratings": []},
+                {"userId": "d", "ratings": [{"animeId": 1, "title": "One", "rawScore": 0}]},
+                42
+            ]}"#,
+            MergeStrategy::default(),
+        );
+        assert_eq!(dataset.users.len(), 1);
+        let ids: Vec<u32> = dataset.users[0]
+            .ratings
+            .iter()
+            .map(|r| r.anime_id)
+            .collect();
+        assert_eq!(ids, [1, 5]);
+        assert!(dataset.users[0].ratings[1].status.is_none());
+        assert_eq!(report.users_dropped, 6);
+        assert_eq!(report.ratings_dropped, 6);
+        assert_eq!(report.fields_ignored, 1);
+        assert_eq!(report.duplicates_resolved, 0);
+        // The 11 for a, and d's 0 before d is left empty.
+        assert_eq!(report.scores_out_of_range, 2);
+        // b without ratings and d; c is dropped for its scale first.
+        assert_eq!(report.empty_users, 2);
+        assert_eq!(report.issues.len(), 13);
+        assert_eq!(report.unlisted(), 0);
+    }
+
+    #[test]
+    fn lenient_loading_converts_declared_scales() {
+        let (dataset, report) = load(
+            r#"{"scoreScale": "1-5", "users": [
+                {"userId": "a", "ratings": [{"animeId": 1, "title": "One", "rawScore": 4}]},
+                {"userId": "b", "scoreScale": "1-100", "ratings": [
+                    {"animeId": 1, "title": "One", "rawScore": 90},
+                    {"animeId": 2, "title": "Two", "rawScore": 101}
+                ]}
+            ]}"#,
+            MergeStrategy::default(),
+        );
+        assert_eq!(dataset.users[0].ratings[0].raw_score, 8.0);
+        assert_eq!(dataset.users[1].ratings[0].raw_score, 9.0);
+        assert_eq!(dataset.users[1].ratings.len(), 1);
+        assert_eq!(report.ratings_dropped, 1);
+    }
+
+    #[test]
+    fn lenient_loading_resolves_and_counts_duplicates() {
+        let json = r#"{"users": [{"userId": "a", "ratings": [
+            {"animeId": 1, "title": "One", "rawScore": 4},
+            {"animeId": 2, "title": "Two", "rawScore": 5},
+            {"animeId": 1, "title": "One", "rawScore": 8}
+        ]}]}"#;
+        let (dataset, report) = load(json, MergeStrategy::Average);
+        let ratings = &dataset.users[0].ratings;
+        assert_eq!(ratings.len(), 2);
+        assert_eq!((ratings[0].anime_id, ratings[0].raw_score), (1, 6.0));
+        assert_eq!(report.duplicates_resolved, 1);
+        assert!(!report.is_clean());
+        assert!(report.issues.is_empty());
+    }
+
+    #[test]
+    fn slices_and_streams_read_the_same() {
+        // Escapes can't be borrowed, the repeated key keeps its last value, and the ignored
+        // genres still have to be read past.
+        let json = r#"{"users": [{"userId": "a\u00e9", "ratings": [
+            {"animeId": 1, "title": "Fate\/Zero \"UBW\"", "title": "Fate/Zero", "rawScore": 8},
+            {"animeId": 2, "title": "Mushishi", "rawScore": 7.5, "genres": ["Drama", 3]},
+            {"animeId": -4, "title": "Negative", "rawScore": 7}
+        ]}]}"#;
+        let slice = load(json, MergeStrategy::default());
+        let mut gzipped = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        std::io::Write::write_all(&mut gzipped, json.as_bytes()).unwrap();
+        let stream =
+            crate::dataset::parse(&gzipped.finish().unwrap(), MergeStrategy::default()).unwrap();
+
+        for (dataset, report) in [&slice, &stream] {
+            let user = &dataset.users[0];
+            assert_eq!(user.user_id, "aé");
+            let titles: Vec<&str> = user.ratings.iter().map(|r| r.title.as_str()).collect();
+            assert_eq!(titles, ["Fate/Zero", "Mushishi"]);
+            assert_eq!(user.ratings[1].raw_score, 7.5);
+            assert!(user.ratings[1].genres.is_empty());
+            assert_eq!(report.fields_ignored, 1);
+            assert_eq!(report.ratings_dropped, 1);
+        }
+        assert_eq!(slice.1, stream.1);
+    }
+
+    #[test]
+    fn listed_issues_are_capped() {
+        let ratings: Vec<String> = (0..MAX_LISTED_ISSUES + 10)
+            .map(|_| r#"{"animeId": 0, "title": "x", "rawScore": 5}"#.to_string())
+            .collect();
+        let json = format!(
+            r#"{{"users": [{{"userId": "a", "ratings": [{{"animeId": 1, "title": "One", "rawScore": 5}}, {}]}}]}}"#,
+            ratings.join(",")
+        );
+        let (_, report) = load(&json, MergeStrategy::default());
+        assert_eq!(report.ratings_dropped, MAX_LISTED_ISSUES + 10);
+        assert_eq!(report.issues.len(), MAX_LISTED_ISSUES);
+        assert_eq!(report.unlisted(), 10);
+    }
+
+    #[test]
+    fn validate_dataset_drops_what_json_loading_would() {
+        let dataset = Dataset::new(vec![
+            user(
+                "a",
+                vec![
+                    Rating::new(1, "One".to_string(), 8.0),
+                    Rating::new(0, "No id".to_string(), 8.0),
+                    Rating::new(2, " ".to_string(), 8.0),
+                    Rating::new(3, "Zero".to_string(), 0.0),
+                    Rating::new(4, "Too high".to_string(), 11.0),
+                    Rating::new(1, "One again".to_string(), 6.0),
+                ],
+            ),
+            user("", vec![Rating::new(1, "One".to_string(), 8.0)]),
+            user("a", vec![Rating::new(1, "One".to_string(), 8.0)]),
+            user("b", vec![Rating::new(5, "Five".to_string(), f64::NAN)]),
+        ]);
+        let (dataset, report) = validate_dataset(dataset, ScoreScale::Ten, MergeStrategy::Highest);
+        assert_eq!(dataset.users.len(), 1);
+        let ratings = &dataset.users[0].ratings;
+        assert_eq!(ratings.len(), 1);
+        assert_eq!((ratings[0].anime_id, ratings[0].raw_score), (1, 8.0));
+        assert_eq!(report.users_dropped, 3);
+        assert_eq!(report.ratings_dropped, 5);
+        assert_eq!(report.duplicates_resolved, 1);
+        assert_eq!(report.issues.len(), 8);
+    }
+}
@@ -0,0 +1,300 @@
+//! Graph recommendations, scored the way the web app's graph engine scores them: each anime a
+//! user rated passes its similarity edges on to the anime it was co-rated with, scaled by how
+//! much the user liked it, and unrated anime are ranked by what they collect.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::{EdgeKind, GraphModel, NodeType};
+
+/// Length of the recommendation list, as in the web app.
+pub const MAX_RECOMMENDATIONS: usize = 40;
+/// Bounds on how strongly one rated anime counts, as in the web app.
+const MIN_WATCH_WEIGHT: f64 = 0.2;
+const MAX_WATCH_WEIGHT: f64 = 3.0;
+
+/// One rated anime's share of a recommendation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Contribution {
+    pub title: String,
+    /// Similarity between the rated anime and the recommended one.
+    pub edge_weight: f64,
+    /// How much the user liked the rated anime, from their normalized score.
+    pub weight_factor: f64,
+    pub weighted_score: f64,
+}
+
+/// An unrated anime ranked for a user, with the rated anime that put it there.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Recommendation {
+    pub anime_id: u32,
+    /// Index into `GraphModel::nodes`.
+    #[serde(skip)]
+    pub node: usize,
+    pub title: String,
+    pub score: f64,
+    pub strongest: f64,
+    /// Rated anime with a positive similarity to this one.
+    pub support_count: usize,
+    /// Strongest first. Negative contributions are listed but don't add to the score.
+    pub contributions: Vec<Contribution>,
+}
+
+impl Recommendation {
+    /// Up to `limit` positive and `limit` negative contributors, e.g.
+    /// `Why+: Monster (+1.20) | Why-: K-On! (-0.40)`.
+    pub fn explanation(&self, limit: usize) -> String {
+        let list = |positive: bool| {
+            let mut items: Vec<&Contribution> = self
+                .contributions
+                .iter()
+                .filter(|c| (c.weighted_score > 0.0) == positive && c.weighted_score != 0.0)
+                .collect();
+            if !positive {
+                items.reverse();
+            }
+            items
+                .into_iter()
+                .take(limit)
+                .map(|c| format!("{} ({:+.2})", c.title, c.weighted_score))
+                .collect::<Vec<_>>()
+                .join(" | ")
+        };
+        let (positives, negatives) = (list(true), list(false));
+        match (positives.is_empty(), negatives.is_empty()) {
+            (true, true) => "no direct contributing anime".to_string(),
+            (false, true) => format!("Why+: {positives}"),
+            (true, false) => format!("Why-: {negatives}"),
+            (false, false) => format!("Why+: {positives} | Why-: {negatives}"),
+        }
+    }
+}
+
+/// How much a rating counts: a score one point above the user's mean counts 1.5 times.
+pub(crate) fn weight_factor(normalized_score: f64) -> f64 {
+    (1.0 + normalized_score / 2.0).clamp(MIN_WATCH_WEIGHT, MAX_WATCH_WEIGHT)
+}
+
+/// Recommendations for the user at node `user`, best first, at most `limit`.
+#[tracing::instrument(level = "debug", skip(graph))]
+pub fn recommend(graph: &GraphModel, user: usize, limit: usize) -> Vec<Recommendation> {
+    if graph.nodes.get(user).map(|node| node.node_type) != Some(NodeType::User) {
+        return Vec::new();
+    }
+    // Rated anime node -> weight factor.
+    let rated: HashMap<usize, f64> = graph
+        .links(user)
+        .map(|(_, edge)| edge)
+        .filter(|edge| edge.kind == EdgeKind::Rating && edge.source == user)
+        .map(|edge| (edge.target, weight_factor(edge.weight)))
+        .collect();
+
+    struct Scored {
+        score: f64,
+        strongest: f64,
+        support_count: usize,
+        contributions: Vec<(usize, f64, f64, f64)>,
+    }
+    let mut scored: HashMap<usize, Scored> = HashMap::new();
+    // Dissimilar rated anime only explain anime something else recommends.
+    let mut against: HashMap<usize, Vec<(usize, f64, f64, f64)>> = HashMap::new();
+    // Only the rated anime's own edges, in node order so the sums come out the same each time.
+    let mut watched_nodes: Vec<(usize, f64)> = rated.iter().map(|(&n, &f)| (n, f)).collect();
+    watched_nodes.sort_unstable_by_key(|&(node, _)| node);
+    for (watched, factor) in watched_nodes {
+        for (other, edge) in graph.links(watched) {
+            if edge.kind != EdgeKind::Similarity || rated.contains_key(&other) {
+                continue;
+            }
+            let weighted = edge.weight * factor;
+            let contribution = (watched, edge.weight, factor, weighted);
+            if edge.weight <= 0.0 {
+                against.entry(other).or_default().push(contribution);
+                continue;
+            }
+            let current = scored.entry(other).or_insert(Scored {
+                score: 0.0,
+                strongest: f64::NEG_INFINITY,
+                support_count: 0,
+                contributions: Vec::new(),
+            });
+            current.score += weighted;
+            current.strongest = current.strongest.max(weighted);
+            current.support_count += 1;
+            current.contributions.push(contribution);
+        }
+    }
+
+    let mut recommendations: Vec<Recommendation> = scored
+        .into_iter()
+        .filter_map(|(node, scored)| {
+            let anime_id = graph.nodes[node].anime_id()?;
+            let mut contributions: Vec<Contribution> = scored
+                .contributions
+                .into_iter()
+                .chain(against.remove(&node).unwrap_or_default())
+                .map(
+                    |(watched, edge_weight, weight_factor, weighted_score)| Contribution {
+                        title: graph.nodes[watched].label.clone(),
+                        edge_weight,
+                        weight_factor,
+                        weighted_score,
+                    },
+                )
+                .collect();
+            contributions.sort_by(|a, b| b.weighted_score.total_cmp(&a.weighted_score));
+            Some(Recommendation {
+                anime_id,
+                node,
+                title: graph.nodes[node].label.clone(),
+                score: scored.score,
+                strongest: scored.strongest,
+                support_count: scored.support_count,
+                contributions,
+            })
+        })
+        .collect();
+    recommendations.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(b.support_count.cmp(&a.support_count))
+            .then(b.strongest.total_cmp(&a.strongest))
+    });
+    recommendations.truncate(limit);
+    recommendations
+}
+
+/// An anime neither of two users rated, recommended to both.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedRecommendation {
+    pub anime_id: u32,
+    /// Index into `GraphModel::nodes`.
+    #[serde(skip)]
+    pub node: usize,
+    pub title: String,
+    /// Its recommendation score for each of the two users.
+    pub scores: [f64; 2],
+}
+
+impl SharedRecommendation {
+    /// The lower of the two scores: an anime is only as good a pick as it is for the user it
+    /// suits less.
+    pub fn score(&self) -> f64 {
+        self.scores[0].min(self.scores[1])
+    }
+}
+
+/// Anime recommended to both users at nodes `users`, and rated by neither, best for both
+/// first, at most `limit`.
+pub fn recommend_together(
+    graph: &GraphModel,
+    users: [usize; 2],
+    limit: usize,
+) -> Vec<SharedRecommendation> {
+    let [first, second] = users.map(|user| recommend(graph, user, usize::MAX));
+    let second: HashMap<usize, f64> = second
+        .into_iter()
+        .map(|recommendation| (recommendation.node, recommendation.score))
+        .collect();
+    let mut together: Vec<SharedRecommendation> = first
+        .into_iter()
+        .filter_map(|recommendation| {
+            Some(SharedRecommendation {
+                scores: [recommendation.score, *second.get(&recommendation.node)?],
+                anime_id: recommendation.anime_id,
+                node: recommendation.node,
+                title: recommendation.title,
+            })
+        })
+        .collect();
+    together.sort_by(|a, b| {
+        b.score()
+            .total_cmp(&a.score())
+            .then(a.anime_id.cmp(&b.anime_id))
+    });
+    together.truncate(limit);
+    together
+}
+
+/// An anime co-rated with another, by the pair's similarity score.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarAnime {
+    pub anime_id: u32,
+    /// Index into `GraphModel::nodes`.
+    #[serde(skip)]
+    pub node: usize,
+    pub title: String,
+    pub score: f64,
+    /// Users who rated both.
+    pub co_raters: usize,
+}
+
+/// Anime most similar to the anime at node `anime`, best first, at most `limit` and no more
+/// than the graph keeps per anime ([`GraphModel::similar_neighbors`]). Only pairs with a
+/// positive score count as similar.
+pub fn similar(graph: &GraphModel, anime: usize, limit: usize) -> Vec<SimilarAnime> {
+    // The anime's own strongest edges, already in order.
+    graph
+        .top_neighbors
+        .edges(anime)
+        .map(|index| &graph.edges[index])
+        .take_while(|edge| edge.weight > 0.0)
+        .filter_map(|edge| {
+            let other = if edge.source == anime {
+                edge.target
+            } else {
+                edge.source
+            };
+            Some(SimilarAnime {
+                anime_id: graph.nodes[other].anime_id()?,
+                node: other,
+                title: graph.nodes[other].label.clone(),
+                score: edge.weight,
+                co_raters: edge.co_raters,
+            })
+        })
+        .take(limit)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{build_graph, Dataset, Rating, UserRatings, Viewport};
+
+    fn user(user_id: &str, ratings: &[(u32, f64)]) -> UserRatings {
+        UserRatings {
+            user_id: user_id.to_string(),
+            ratings: ratings
+                .iter()
+                .map(|&(anime_id, score)| Rating::new(anime_id, format!("Anime {anime_id}"), score))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn together_only_lists_anime_neither_user_rated() {
+        let dataset = Dataset::new(vec![
+            user("a", &[(1, 9.0), (2, 4.0)]),
+            user("b", &[(1, 9.0), (3, 4.0)]),
+            user("c", &[(1, 9.0), (2, 4.0), (3, 4.0), (4, 10.0), (5, 9.0)]),
+            user("d", &[(2, 3.0), (4, 9.0), (5, 10.0)]),
+        ]);
+        let graph = build_graph(dataset, Viewport::default());
+        let node = |user_id: &str| {
+            let id = format!("user:{user_id}");
+            graph.nodes.iter().position(|node| node.id == id).unwrap()
+        };
+        let together = recommend_together(&graph, [node("a"), node("b")], 10);
+        assert!(!together.is_empty());
+        for pick in &together {
+            assert!(![1, 2, 3].contains(&pick.anime_id));
+            assert!(pick.score() <= pick.scores[0] && pick.score() <= pick.scores[1]);
+        }
+    }
+}
@@ -0,0 +1,348 @@
+//! Relations between anime from their metadata: sequels, prequels, side stories and the like.
+//! [`Relations`] keeps them as directed edges in watch order, so a prequel points at its sequel
+//! and a parent story at its side story, whichever of the two listed the relation, and groups
+//! anime joined by story relations into franchises. Frontends draw the edges over the graph;
+//! recommendations keep one entry per franchise through [`Relations::collapse`], and name the
+//! entry to start from with [`Relations::entry_point`] rather than a third season.
+
+use std::collections::{HashMap, HashSet};
+
+use petgraph::unionfind::UnionFind;
+use serde::{Deserialize, Serialize};
+
+use crate::GraphModel;
+
+/// What another anime is to this one. In order of precedence: AniList lists a spin-off's
+/// original as its parent story, for one, so a spin-off on one end wins over a side story on
+/// the other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationKind {
+    Sequel,
+    Prequel,
+    SpinOff,
+    SideStory,
+    ParentStory,
+    Summary,
+    Alternative,
+    Other,
+}
+
+impl RelationKind {
+    /// AniList's `MediaRelation` name, e.g. `SIDE_STORY`. `None` for adaptations, shared
+    /// characters and other relations that aren't about the story.
+    pub fn from_anilist(name: &str) -> Option<Self> {
+        Some(match name {
+            "SEQUEL" => Self::Sequel,
+            "PREQUEL" => Self::Prequel,
+            "SIDE_STORY" => Self::SideStory,
+            "PARENT" => Self::ParentStory,
+            "SPIN_OFF" => Self::SpinOff,
+            "SUMMARY" | "COMPILATION" => Self::Summary,
+            "ALTERNATIVE" => Self::Alternative,
+            "OTHER" => Self::Other,
+            _ => return None,
+        })
+    }
+
+    /// Whether anime related this way belong to the same franchise. A spin-off stands on its
+    /// own, and "other" relations are too loose to count.
+    pub fn joins_franchise(self) -> bool {
+        !matches!(self, Self::SpinOff | Self::Other)
+    }
+}
+
+/// One relation as an anime's metadata lists it: the anime `anime_id` is this one's `kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Relation {
+    pub kind: RelationKind,
+    pub anime_id: u32,
+}
+
+/// A relation pointing the way the two are watched: `to` is `from`'s sequel, side story,
+/// spin-off or summary. Alternative versions and other relations have no order and point from
+/// the smaller id. Never [`RelationKind::Prequel`] or [`RelationKind::ParentStory`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelationEdge {
+    pub from: u32,
+    pub to: u32,
+    pub kind: RelationKind,
+}
+
+impl RelationEdge {
+    /// `relation` of `anime_id`, turned to point in watch order.
+    pub fn new(anime_id: u32, relation: Relation) -> Self {
+        let (from, to, kind) = match relation.kind {
+            RelationKind::Prequel => (relation.anime_id, anime_id, RelationKind::Sequel),
+            RelationKind::ParentStory => (relation.anime_id, anime_id, RelationKind::SideStory),
+            kind @ (RelationKind::Alternative | RelationKind::Other) => (
+                anime_id.min(relation.anime_id),
+                anime_id.max(relation.anime_id),
+                kind,
+            ),
+            kind => (anime_id, relation.anime_id, kind),
+        };
+        Self { from, to, kind }
+    }
+}
+
+/// The relations of a set of anime, and the franchises they make up.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Relations {
+    /// Sorted by `from`, then `to`, one per pair and direction.
+    edges: Vec<RelationEdge>,
+    /// Anime id -> the smallest anime id of its franchise, for anime with story relations.
+    franchises: HashMap<u32, u32>,
+    /// Anime id -> its prequels, parent stories and, for summaries, what they sum up.
+    before: HashMap<u32, Vec<u32>>,
+}
+
+impl Relations {
+    /// Relations from each anime's listed ones. Both ends usually list a relation, the sequel
+    /// as a prequel; they make one edge, and when the two disagree on the kind the first in
+    /// [`RelationKind`] order wins.
+    pub fn new<'a>(listed: impl IntoIterator<Item = (u32, &'a [Relation])>) -> Self {
+        let mut edges: Vec<RelationEdge> = listed
+            .into_iter()
+            .flat_map(|(anime_id, relations)| {
+                relations
+                    .iter()
+                    .filter(move |relation| relation.anime_id != anime_id)
+                    .map(move |&relation| RelationEdge::new(anime_id, relation))
+            })
+            .collect();
+        edges.sort_unstable_by_key(|edge| (edge.from, edge.to, edge.kind));
+        edges.dedup_by_key(|edge| (edge.from, edge.to));
+
+        let joined: Vec<&RelationEdge> = edges
+            .iter()
+            .filter(|edge| edge.kind.joins_franchise())
+            .collect();
+        let mut members: Vec<u32> = joined
+            .iter()
+            .flat_map(|edge| [edge.from, edge.to])
+            .collect();
+        members.sort_unstable();
+        members.dedup();
+        let index = |anime_id: u32| {
+            members
+                .binary_search(&anime_id)
+                .expect("every end is a member")
+        };
+        let mut sets = UnionFind::<usize>::new(members.len());
+        for edge in joined {
+            sets.union(index(edge.from), index(edge.to));
+        }
+        // Members are sorted, so the first seen of each set is its smallest anime id.
+        let mut names: HashMap<usize, u32> = HashMap::new();
+        let franchises = members
+            .iter()
+            .enumerate()
+            .map(|(position, &anime_id)| {
+                let name = *names.entry(sets.find(position)).or_insert(anime_id);
+                (anime_id, name)
+            })
+            .collect();
+        let mut before: HashMap<u32, Vec<u32>> = HashMap::new();
+        for edge in &edges {
+            if matches!(
+                edge.kind,
+                RelationKind::Sequel | RelationKind::SideStory | RelationKind::Summary
+            ) {
+                before.entry(edge.to).or_default().push(edge.from);
+            }
+        }
+        Self {
+            edges,
+            franchises,
+            before,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    pub fn edges(&self) -> &[RelationEdge] {
+        &self.edges
+    }
+
+    /// The franchise `anime_id` belongs to, named by its smallest anime id. An anime without
+    /// story relations is a franchise of its own.
+    pub fn franchise(&self, anime_id: u32) -> u32 {
+        self.franchises.get(&anime_id).copied().unwrap_or(anime_id)
+    }
+
+    /// Every anime of `anime_id`'s franchise, sorted; just `anime_id` when it has no story
+    /// relations.
+    pub fn franchise_members(&self, anime_id: u32) -> Vec<u32> {
+        let franchise = self.franchise(anime_id);
+        let mut members: Vec<u32> = self
+            .franchises
+            .iter()
+            .filter(|&(_, &root)| root == franchise)
+            .map(|(&member, _)| member)
+            .collect();
+        if members.is_empty() {
+            members.push(anime_id);
+        }
+        members.sort_unstable();
+        members
+    }
+
+    /// What comes before `anime_id` in watch order: its prequels, parent stories and, for a
+    /// summary, what it sums up.
+    pub fn prior(&self, anime_id: u32) -> &[u32] {
+        self.before.get(&anime_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Where to start watching `anime_id`: back along its prequels and parent stories as far as
+    /// they go, through the earliest aired by `year` where there are several, and stopping
+    /// short of anime already `watched`. `anime_id` itself when nothing unwatched comes before
+    /// it, and for spin-offs, which start on their own.
+    pub fn entry_point(
+        &self,
+        anime_id: u32,
+        watched: impl Fn(u32) -> bool,
+        year: impl Fn(u32) -> Option<u32>,
+    ) -> u32 {
+        let path = self.path_to_entry(anime_id, watched, year);
+        path.last().copied().unwrap_or(anime_id)
+    }
+
+    /// The anime on the way back from `anime_id` to its [`Relations::entry_point`], both
+    /// included.
+    pub fn path_to_entry(
+        &self,
+        anime_id: u32,
+        watched: impl Fn(u32) -> bool,
+        year: impl Fn(u32) -> Option<u32>,
+    ) -> Vec<u32> {
+        let mut path = vec![anime_id];
+        loop {
+            let current = path[path.len() - 1];
+            let earliest = self
+                .prior(current)
+                .iter()
+                .copied()
+                .filter(|&prior| !watched(prior) && !path.contains(&prior))
+                .min_by_key(|&prior| (year(prior).unwrap_or(u32::MAX), prior));
+            let Some(prior) = earliest else {
+                return path;
+            };
+            path.push(prior);
+        }
+    }
+
+    /// Relations between anime that are both nodes of `graph`, as `(from, to, kind)` with
+    /// node indices, for drawing over it.
+    pub fn in_graph(&self, graph: &GraphModel) -> Vec<(usize, usize, RelationKind)> {
+        if self.edges.is_empty() {
+            return Vec::new();
+        }
+        let nodes: HashMap<u32, usize> = graph
+            .nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, node)| Some((node.anime_id()?, index)))
+            .collect();
+        self.edges
+            .iter()
+            .filter_map(|edge| Some((*nodes.get(&edge.from)?, *nodes.get(&edge.to)?, edge.kind)))
+            .collect()
+    }
+
+    /// Keeps the first of `items` from each franchise, so a ranked list names a franchise once
+    /// rather than once per season.
+    pub fn collapse<T>(&self, items: &mut Vec<T>, anime_id: impl Fn(&T) -> u32) {
+        if self.franchises.is_empty() {
+            return;
+        }
+        let mut seen = HashSet::new();
+        items.retain(|item| seen.insert(self.franchise(anime_id(item))));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relation(kind: RelationKind, anime_id: u32) -> Relation {
+        Relation { kind, anime_id }
+    }
+
+    #[test]
+    fn both_ends_make_one_edge_in_watch_order() {
+        let zero = [relation(RelationKind::Sequel, 22_297)];
+        let stay_night = [
+            relation(RelationKind::Prequel, 10_087),
+            relation(RelationKind::SideStory, 30),
+        ];
+        let side = [relation(RelationKind::ParentStory, 22_297)];
+        let spin_off = [relation(RelationKind::SpinOff, 22_297)];
+        let relations = Relations::new([
+            (10_087, &zero[..]),
+            (22_297, &stay_night[..]),
+            (30, &side[..]),
+            (5, &spin_off[..]),
+        ]);
+        assert_eq!(
+            relations.edges(),
+            [
+                RelationEdge {
+                    from: 5,
+                    to: 22_297,
+                    kind: RelationKind::SpinOff
+                },
+                RelationEdge {
+                    from: 10_087,
+                    to: 22_297,
+                    kind: RelationKind::Sequel
+                },
+                RelationEdge {
+                    from: 22_297,
+                    to: 30,
+                    kind: RelationKind::SideStory
+                },
+            ]
+        );
+        // The spin-off stands apart; the rest is one franchise named by its smallest id.
+        assert_eq!(relations.franchise(10_087), 30);
+        assert_eq!(relations.franchise(22_297), 30);
+        assert_eq!(relations.franchise(5), 5);
+        assert_eq!(relations.franchise(99), 99);
+
+        let mut ranked = vec![22_297, 5, 10_087, 99, 30];
+        relations.collapse(&mut ranked, |&anime_id| anime_id);
+        assert_eq!(ranked, [22_297, 5, 99]);
+    }
+
+    #[test]
+    fn entry_points_go_back_to_the_first_unwatched_prequel() {
+        // 1 -> 2 -> 3 in order, 4 a side story of 3, 9 a recap of 1 and 8 a spin-off of 2.
+        let listed = [
+            (2, vec![relation(RelationKind::Prequel, 1)]),
+            (3, vec![relation(RelationKind::Prequel, 2)]),
+            (4, vec![relation(RelationKind::ParentStory, 3)]),
+            (9, vec![relation(RelationKind::ParentStory, 1)]),
+            (1, vec![relation(RelationKind::Summary, 9)]),
+            (8, vec![relation(RelationKind::ParentStory, 2)]),
+            (2, vec![relation(RelationKind::SpinOff, 8)]),
+        ];
+        let relations = Relations::new(
+            listed
+                .iter()
+                .map(|(anime_id, relations)| (*anime_id, &relations[..])),
+        );
+        let year = |anime_id: u32| Some(2000 + anime_id);
+        let nothing = |_: u32| false;
+        assert_eq!(relations.entry_point(4, nothing, year), 1);
+        assert_eq!(relations.entry_point(9, nothing, year), 1);
+        assert_eq!(relations.entry_point(1, nothing, year), 1);
+        // Having watched the first season, the second is where to pick up.
+        assert_eq!(relations.entry_point(4, |anime_id| anime_id == 1, year), 2);
+        assert_eq!(relations.entry_point(8, nothing, year), 8);
+    }
+}
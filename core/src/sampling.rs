@@ -0,0 +1,256 @@
+//! Optional subsampling applied before the graph is built, so very large corpora stay
+//! explorable. Samples are seeded, so rebuilding the graph (e.g. after an import) keeps the
+//! same users; imported local profiles are always kept.
+
+use std::collections::{HashMap, HashSet};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+use crate::dataset::LOCAL_PROFILE_PREFIX;
+use crate::{Dataset, UserRatings};
+
+const SAMPLE_SEED: u64 = 0x5eed_a11e;
+/// Activity bands for the stratified sample, from lightest to heaviest raters.
+const STRATA: usize = 5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Sampling {
+    #[default]
+    All,
+    /// `count` users picked uniformly at random.
+    RandomUsers { count: usize },
+    /// Only ratings of the `count` anime with the most ratings.
+    TopAnime { count: usize },
+    /// `count` users drawn proportionally from each rating-count band, so light and heavy
+    /// raters keep their share.
+    Stratified { count: usize },
+}
+
+impl Sampling {
+    pub const DEFAULT_COUNT: usize = 1000;
+
+    /// Each kind with the default count, as offered in the picker.
+    pub const KINDS: [Sampling; 4] = [
+        Self::All,
+        Self::RandomUsers {
+            count: Self::DEFAULT_COUNT,
+        },
+        Self::TopAnime {
+            count: Self::DEFAULT_COUNT,
+        },
+        Self::Stratified {
+            count: Self::DEFAULT_COUNT,
+        },
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::All => "All data",
+            Self::RandomUsers { .. } => "Random users",
+            Self::TopAnime { .. } => "Most-rated anime",
+            Self::Stratified { .. } => "Stratified users",
+        }
+    }
+
+    pub fn count(self) -> Option<usize> {
+        match self {
+            Self::All => None,
+            Self::RandomUsers { count } | Self::TopAnime { count } | Self::Stratified { count } => {
+                Some(count)
+            }
+        }
+    }
+
+    pub fn with_count(self, count: usize) -> Self {
+        match self {
+            Self::All => Self::All,
+            Self::RandomUsers { .. } => Self::RandomUsers { count },
+            Self::TopAnime { .. } => Self::TopAnime { count },
+            Self::Stratified { .. } => Self::Stratified { count },
+        }
+    }
+
+    pub fn apply(self, mut dataset: Dataset) -> Dataset {
+        let mut rng = StdRng::seed_from_u64(SAMPLE_SEED);
+        let (local, mut users): (Vec<UserRatings>, Vec<UserRatings>) = dataset
+            .users
+            .into_iter()
+            .partition(|user| user.user_id.starts_with(LOCAL_PROFILE_PREFIX));
+
+        match self {
+            Self::All => {}
+            Self::RandomUsers { count } => {
+                if count < users.len() {
+                    let mut picked =
+                        rand::seq::index::sample(&mut rng, users.len(), count).into_vec();
+                    picked.sort_unstable();
+                    users = take_indices(users, &picked);
+                }
+            }
+            Self::TopAnime { count } => {
+                let mut counts: HashMap<u32, usize> = HashMap::new();
+                for rating in users.iter().flat_map(|user| &user.ratings) {
+                    *counts.entry(rating.anime_id).or_default() += 1;
+                }
+                let mut ranked: Vec<(u32, usize)> = counts.into_iter().collect();
+                ranked.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+                let kept: HashSet<u32> = ranked.into_iter().take(count).map(|(id, _)| id).collect();
+                for user in &mut users {
+                    user.ratings
+                        .retain(|rating| kept.contains(&rating.anime_id));
+                }
+                users.retain(|user| !user.ratings.is_empty());
+            }
+            Self::Stratified { count } => {
+                if count < users.len() {
+                    let mut by_activity: Vec<usize> = (0..users.len()).collect();
+                    by_activity.sort_by_key(|&index| users[index].ratings.len());
+                    let band = by_activity.len().div_ceil(STRATA);
+                    let strata: Vec<&[usize]> = by_activity.chunks(band).collect();
+                    let amounts = apportion(&strata, count, users.len());
+                    let mut picked = Vec::with_capacity(count);
+                    for (stratum, amount) in strata.into_iter().zip(amounts) {
+                        picked.extend(
+                            rand::seq::index::sample(&mut rng, stratum.len(), amount)
+                                .into_iter()
+                                .map(|position| stratum[position]),
+                        );
+                    }
+                    picked.sort_unstable();
+                    users = take_indices(users, &picked);
+                }
+            }
+        }
+
+        users.extend(local);
+        dataset.users = users;
+        dataset
+    }
+}
+
+/// Splits `count` of `total` users across `strata` in proportion to their sizes, handing the
+/// places left after rounding down to the strata that lost the most, so the amounts add up to
+/// exactly `count`.
+fn apportion(strata: &[&[usize]], count: usize, total: usize) -> Vec<usize> {
+    let share = count as f64 / total as f64;
+    let quotas: Vec<f64> = strata
+        .iter()
+        .map(|stratum| stratum.len() as f64 * share)
+        .collect();
+    let mut amounts: Vec<usize> = quotas.iter().map(|quota| quota.floor() as usize).collect();
+    let mut by_remainder: Vec<usize> = (0..strata.len()).collect();
+    by_remainder.sort_by(|&a, &b| {
+        (quotas[b] - quotas[b].floor()).total_cmp(&(quotas[a] - quotas[a].floor()))
+    });
+    let left = count - amounts.iter().sum::<usize>();
+    for &stratum in by_remainder.iter().take(left) {
+        amounts[stratum] += 1;
+    }
+    amounts
+}
+
+/// Keeps the users at `indices`, which must be sorted.
+fn take_indices(users: Vec<UserRatings>, indices: &[usize]) -> Vec<UserRatings> {
+    let mut wanted = indices.iter().peekable();
+    users
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, user)| {
+            (wanted.peek() == Some(&&index)).then(|| {
+                wanted.next();
+                user
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rating;
+
+    /// 100 anonymized users rating 1 to 20 anime each, the most-rated anime having the lowest
+    /// ids, and one local profile.
+    fn dataset() -> Dataset {
+        let mut users: Vec<UserRatings> = (0..100)
+            .map(|index| UserRatings {
+                user_id: format!("user{index}"),
+                ratings: (0..1 + index % 20)
+                    .map(|anime_id| Rating::new(anime_id, format!("Anime {anime_id}"), 7.0))
+                    .collect(),
+            })
+            .collect();
+        users.insert(
+            40,
+            UserRatings {
+                user_id: format!("{LOCAL_PROFILE_PREFIX}me"),
+                ratings: vec![Rating::new(99, "Rare".to_string(), 9.0)],
+            },
+        );
+        Dataset::new(users)
+    }
+
+    fn ids(dataset: &Dataset) -> Vec<&str> {
+        dataset
+            .users
+            .iter()
+            .map(|user| user.user_id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn user_samples_keep_exactly_count_users_and_the_local_profile() {
+        for sampling in [
+            Sampling::RandomUsers { count: 37 },
+            Sampling::Stratified { count: 37 },
+        ] {
+            let sampled = sampling.apply(dataset());
+            let kept = ids(&sampled);
+            assert_eq!(kept.len(), 38, "{sampling:?}");
+            assert_eq!(kept.last(), Some(&"local:me"));
+            // The same seed picks the same users on every rebuild.
+            assert_eq!(kept, ids(&sampling.apply(dataset())), "{sampling:?}");
+        }
+        let everyone = Sampling::RandomUsers { count: 500 }.apply(dataset());
+        assert_eq!(everyone.users.len(), 101);
+    }
+
+    #[test]
+    fn stratified_samples_take_light_and_heavy_raters_alike() {
+        let sampled = Sampling::Stratified { count: 50 }.apply(dataset());
+        let light = sampled
+            .users
+            .iter()
+            .filter(|user| !user.user_id.starts_with(LOCAL_PROFILE_PREFIX))
+            .filter(|user| user.ratings.len() <= 4)
+            .count();
+        // A fifth of the users rate at most four anime.
+        assert_eq!(light, 10);
+    }
+
+    #[test]
+    fn top_anime_drops_every_other_rating() {
+        let sampled = Sampling::TopAnime { count: 3 }.apply(dataset());
+        assert_eq!(sampled.users.len(), 101);
+        for user in &sampled.users {
+            if user.user_id.starts_with(LOCAL_PROFILE_PREFIX) {
+                assert_eq!(user.ratings[0].anime_id, 99);
+            } else {
+                assert!(user.ratings.iter().all(|rating| rating.anime_id < 3));
+            }
+        }
+
+        // Users left with no ratings are dropped with them.
+        let mut only_rare = dataset();
+        only_rare.users.push(UserRatings {
+            user_id: "rare".to_string(),
+            ratings: vec![Rating::new(50, "Rare".to_string(), 5.0)],
+        });
+        let sampled = Sampling::TopAnime { count: 3 }.apply(only_rare);
+        assert!(!ids(&sampled).contains(&"rare"));
+        assert_eq!(Sampling::All.apply(dataset()).users.len(), 101);
+    }
+}
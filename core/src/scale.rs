@@ -0,0 +1,137 @@
+//! Rating scales. Everything past loading works on MAL's 1–10 scale, so scores declared on
+//! another scale — by a dataset's `scoreScale`, a CSV mapping or an importer's API — are
+//! converted once, here, as they come in.
+
+use serde::{Deserialize, Serialize};
+
+/// Score a thumbs-up becomes: a clear positive, like a 4 out of 5.
+const THUMBS_UP: f64 = 8.0;
+/// Score a thumbs-down becomes: clearly negative without being the worst possible.
+const THUMBS_DOWN: f64 = 3.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScoreScale {
+    /// MyAnimeList's scale, used internally.
+    #[default]
+    #[serde(rename = "1-10")]
+    Ten,
+    #[serde(rename = "1-5")]
+    Five,
+    /// Kitsu's `ratingTwenty`.
+    #[serde(rename = "1-20")]
+    Twenty,
+    /// AniList's `POINT_100`.
+    #[serde(rename = "1-100")]
+    Hundred,
+    /// `1` for thumbs up, `0` for thumbs down.
+    #[serde(rename = "thumbs")]
+    Thumbs,
+}
+
+impl ScoreScale {
+    pub const ALL: [ScoreScale; 5] = [
+        Self::Ten,
+        Self::Five,
+        Self::Twenty,
+        Self::Hundred,
+        Self::Thumbs,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Ten => "1–10",
+            Self::Five => "1–5",
+            Self::Twenty => "1–20",
+            Self::Hundred => "1–100",
+            Self::Thumbs => "Thumbs up/down",
+        }
+    }
+
+    fn max(self) -> f64 {
+        match self {
+            Self::Ten => 10.0,
+            Self::Five => 5.0,
+            Self::Twenty => 20.0,
+            Self::Hundred => 100.0,
+            Self::Thumbs => 1.0,
+        }
+    }
+
+    /// Converts a score on this scale to 1–10, proportionally to the scale's top score and
+    /// floored at 1. Errors name the accepted range.
+    pub fn to_ten(self, score: f64) -> Result<f64, String> {
+        if self == Self::Thumbs {
+            return match score {
+                1.0 => Ok(THUMBS_UP),
+                0.0 => Ok(THUMBS_DOWN),
+                _ => Err(format!("score {score} is not a thumbs value (0 or 1)")),
+            };
+        }
+        let max = self.max();
+        if !(score.is_finite() && score > 0.0 && score <= max) {
+            return Err(format!("score {score} is outside {}", self.label()));
+        }
+        Ok((score / max * 10.0).max(1.0))
+    }
+
+    /// Reads a thumbs value written as text, e.g. `up`/`down` or `yes`/`no`.
+    pub fn parse_thumbs(value: &str) -> Option<f64> {
+        match value.trim().to_lowercase().as_str() {
+            "1" | "up" | "yes" | "true" | "like" | "👍" => Some(1.0),
+            "0" | "-1" | "down" | "no" | "false" | "dislike" | "👎" => Some(0.0),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_ten_accepts_each_scale_up_to_its_top_score() {
+        assert_eq!(ScoreScale::Ten.to_ten(10.0), Ok(10.0));
+        assert_eq!(ScoreScale::Five.to_ten(5.0), Ok(10.0));
+        assert_eq!(ScoreScale::Twenty.to_ten(20.0), Ok(10.0));
+        assert_eq!(ScoreScale::Hundred.to_ten(100.0), Ok(10.0));
+        assert_eq!(ScoreScale::Five.to_ten(2.5), Ok(5.0));
+    }
+
+    #[test]
+    fn to_ten_floors_small_scores_at_one() {
+        assert_eq!(ScoreScale::Ten.to_ten(0.5), Ok(1.0));
+        assert_eq!(ScoreScale::Hundred.to_ten(1.0), Ok(1.0));
+        assert_eq!(ScoreScale::Hundred.to_ten(10.0), Ok(1.0));
+    }
+
+    #[test]
+    fn to_ten_rejects_scores_outside_the_scale() {
+        for scale in [
+            ScoreScale::Ten,
+            ScoreScale::Five,
+            ScoreScale::Twenty,
+            ScoreScale::Hundred,
+        ] {
+            assert!(scale.to_ten(0.0).is_err(), "{scale:?}");
+            assert!(scale.to_ten(-1.0).is_err(), "{scale:?}");
+            assert!(scale.to_ten(scale.max() + 0.1).is_err(), "{scale:?}");
+            assert!(scale.to_ten(f64::NAN).is_err(), "{scale:?}");
+            assert!(scale.to_ten(f64::INFINITY).is_err(), "{scale:?}");
+        }
+    }
+
+    #[test]
+    fn to_ten_maps_thumbs_to_fixed_scores() {
+        assert_eq!(ScoreScale::Thumbs.to_ten(1.0), Ok(THUMBS_UP));
+        assert_eq!(ScoreScale::Thumbs.to_ten(0.0), Ok(THUMBS_DOWN));
+        assert!(ScoreScale::Thumbs.to_ten(0.5).is_err());
+        assert!(ScoreScale::Thumbs.to_ten(2.0).is_err());
+    }
+
+    #[test]
+    fn parse_thumbs_reads_common_spellings() {
+        assert_eq!(ScoreScale::parse_thumbs(" Up "), Some(1.0));
+        assert_eq!(ScoreScale::parse_thumbs("dislike"), Some(0.0));
+        assert_eq!(ScoreScale::parse_thumbs("maybe"), None);
+    }
+}
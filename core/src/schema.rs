@@ -0,0 +1,102 @@
+//! Dataset schema versions and the migrations between them.
+//!
+//! Files without `schemaVersion` are version 1, the shape the pipeline has always written.
+//! Every loaded dataset is migrated step by step up to [`CURRENT_VERSION`], so later versions
+//! can add fields without breaking older files.
+//!
+//! - v1: `users[].ratings[]` with `animeId`, `title`, `rawScore`, `normalizedScore`.
+//! - v2: adds `schemaVersion`, and optional per-rating `status`, `updatedAt` and `genres`.
+//! - v3: adds an optional `scoreScale` (`1-10`, `1-5`, `1-20`, `1-100` or `thumbs`) on the
+//!   dataset and on each user, which overrides the dataset's. Scores are converted to 1–10 on
+//!   load, so files this app writes are always on the default `1-10` and omit it.
+//!
+//! The Parquet and SQLite backends store only the v1 rating columns; the v2 extras round-trip
+//! through JSON.
+
+use crate::{Dataset, DatasetError};
+
+pub const CURRENT_VERSION: u32 = 3;
+/// Version assumed when a file has no `schemaVersion` field.
+pub const LEGACY_VERSION: u32 = 1;
+
+/// Migration from version `i + 1` to `i + 2`.
+const MIGRATIONS: [fn(&mut Dataset); (CURRENT_VERSION - 1) as usize] = [v1_to_v2, v2_to_v3];
+
+pub fn legacy_version() -> u32 {
+    LEGACY_VERSION
+}
+
+/// Brings `dataset` up to the current schema, refusing versions this build doesn't know.
+pub fn migrate(dataset: &mut Dataset) -> Result<(), DatasetError> {
+    match dataset.schema_version {
+        0 => Err(DatasetError::Parse(
+            "schemaVersion 0 is not valid".to_string(),
+        )),
+        version if version > CURRENT_VERSION => Err(DatasetError::Parse(format!(
+            "schemaVersion {version} is newer than this app supports ({CURRENT_VERSION})"
+        ))),
+        version => {
+            for migration in &MIGRATIONS[(version - 1) as usize..] {
+                migration(dataset);
+                dataset.schema_version += 1;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// The new v2 fields are optional and default to empty, so v1 data carries over unchanged.
+fn v1_to_v2(_dataset: &mut Dataset) {}
+
+/// Files without `scoreScale` were always on 1–10, the default.
+fn v2_to_v3(_dataset: &mut Dataset) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::parse;
+    use crate::merge::MergeStrategy;
+
+    fn load(json: &str) -> Result<Dataset, DatasetError> {
+        parse(json.as_bytes(), MergeStrategy::default()).map(|(dataset, _)| dataset)
+    }
+
+    #[test]
+    fn migrates_v1_files_to_the_current_version() {
+        let dataset = load(
+            r#"{"users": [{"userId": "a", "ratings": [
+                {"animeId": 1, "title": "One", "rawScore": 8, "normalizedScore": 0.5}
+            ]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(dataset.schema_version, CURRENT_VERSION);
+        let rating = &dataset.users[0].ratings[0];
+        assert_eq!((rating.anime_id, rating.raw_score), (1, 8.0));
+        assert!(rating.status.is_none() && rating.genres.is_empty());
+    }
+
+    #[test]
+    fn migrates_v2_files_keeping_their_extra_fields() {
+        let dataset = load(
+            r#"{"schemaVersion": 2, "users": [{"userId": "a", "ratings": [
+                {"animeId": 1, "title": "One", "rawScore": 8, "status": "completed",
+                 "updatedAt": "2024-01-05", "genres": ["Drama"]}
+            ]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(dataset.schema_version, CURRENT_VERSION);
+        let rating = &dataset.users[0].ratings[0];
+        assert_eq!(rating.status.as_deref(), Some("completed"));
+        assert_eq!(rating.updated_at.as_deref(), Some("2024-01-05"));
+        assert_eq!(rating.genres, ["Drama"]);
+        assert_eq!(rating.raw_score, 8.0);
+    }
+
+    #[test]
+    fn refuses_unknown_versions() {
+        let users = r#""users": [{"userId": "a", "ratings": [{"animeId": 1, "title": "One", "rawScore": 8}]}]"#;
+        assert!(load(&format!(r#"{{"schemaVersion": 0, {users}}}"#)).is_err());
+        let newer = CURRENT_VERSION + 1;
+        assert!(load(&format!(r#"{{"schemaVersion": {newer}, {users}}}"#)).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+//! Headline counts for a dataset and its graph, and the aggregates the stats charts draw.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::algorithms;
+use crate::{build_graph_with_progress, BuildProgress, Dataset, EdgeKind, GraphModel, Viewport};
+
+/// Anime listed as most and as least rated.
+pub const RANKED_ANIME: usize = 10;
+/// Genres charted; the rest are summed into "other".
+pub const CHARTED_GENRES: usize = 12;
+/// Users plotted by mean and variance at most, spread evenly through the dataset.
+pub const SPREAD_USERS: usize = 500;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    pub users: usize,
+    pub anime: usize,
+    pub ratings: usize,
+    /// Mean raw score over every rating, on the 1–10 scale.
+    pub mean_score: f64,
+    pub similarity_edges: usize,
+    pub communities: usize,
+    /// Separate pieces the graph falls into, counting anime no one else rated alongside.
+    pub components: usize,
+}
+
+impl Stats {
+    /// Builds the graph for `dataset`, counting the ratings on the way in. `progress` is
+    /// passed to [`build_graph_with_progress`].
+    pub fn build(
+        dataset: Dataset,
+        viewport: Viewport,
+        progress: impl FnMut(BuildProgress),
+    ) -> (GraphModel, Self) {
+        let ratings: usize = dataset.users.iter().map(|user| user.ratings.len()).sum();
+        let total: f64 = dataset
+            .users
+            .iter()
+            .flat_map(|user| &user.ratings)
+            .map(|rating| rating.raw_score)
+            .sum();
+        let graph = build_graph_with_progress(dataset, viewport, progress);
+        let stats = Self {
+            users: graph.user_count,
+            anime: graph.anime_count,
+            ratings,
+            mean_score: if ratings == 0 {
+                0.0
+            } else {
+                total / ratings as f64
+            },
+            similarity_edges: graph
+                .edges
+                .iter()
+                .filter(|edge| edge.kind == EdgeKind::Similarity)
+                .count(),
+            communities: graph.communities.count(),
+            components: algorithms::components(&graph),
+        };
+        (graph, stats)
+    }
+}
+
+/// One user's scores, for plotting how users rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UserSpread {
+    pub ratings: usize,
+    /// Mean raw score, on the 1–10 scale.
+    pub mean: f64,
+    pub variance: f64,
+}
+
+/// Aggregates over a dataset, worked out once so the stats charts only draw them.
+/// The index of `score` in a ten-bucket histogram of raw scores: rounded to a whole point and
+/// clamped to 1–10, so index 0 counts scores of 1.
+pub fn score_bucket(score: f64) -> usize {
+    (score.round().clamp(1.0, 10.0) as usize) - 1
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Breakdown {
+    pub users: usize,
+    pub anime: usize,
+    pub ratings: usize,
+    /// Ratings by raw score rounded to a whole point; index 0 counts scores of 1.
+    pub scores: [usize; 10],
+    /// At most [`SPREAD_USERS`] users with at least one rating.
+    pub user_spread: Vec<UserSpread>,
+    /// Titles with their rating counts, most rated first, at most [`RANKED_ANIME`].
+    pub most_rated: Vec<(String, usize)>,
+    /// Least rated first, at most [`RANKED_ANIME`].
+    pub least_rated: Vec<(String, usize)>,
+    /// Ratings of anime in each genre, most first, at most [`CHARTED_GENRES`]. An anime
+    /// counts towards each of its genres.
+    pub genres: Vec<(String, usize)>,
+    /// Ratings of anime with genres outside the charted ones.
+    pub other_genres: usize,
+    /// Ratings of anime without known genres.
+    pub unknown_genres: usize,
+}
+
+impl Breakdown {
+    /// Aggregates `dataset`. Genres come from the ratings when the source had them, and from
+    /// `genres` for the anime without.
+    pub fn new(dataset: &Dataset, genres: impl Fn(u32) -> Vec<String>) -> Self {
+        let mut breakdown = Self {
+            users: dataset.users.len(),
+            ..Self::default()
+        };
+        // Anime id -> (title, rating count, genres from the source).
+        let mut anime: HashMap<u32, (&str, usize, &[String])> = HashMap::new();
+        let step = dataset.users.len().div_ceil(SPREAD_USERS).max(1);
+        for (index, user) in dataset.users.iter().enumerate() {
+            let count = user.ratings.len();
+            breakdown.ratings += count;
+            for rating in &user.ratings {
+                breakdown.scores[score_bucket(rating.raw_score)] += 1;
+                let entry =
+                    anime
+                        .entry(rating.anime_id)
+                        .or_insert((&rating.title, 0, &rating.genres));
+                entry.1 += 1;
+                if entry.2.is_empty() {
+                    entry.2 = &rating.genres;
+                }
+            }
+            if count > 0 && index % step == 0 {
+                let mean = user.ratings.iter().map(|r| r.raw_score).sum::<f64>() / count as f64;
+                let variance = user
+                    .ratings
+                    .iter()
+                    .map(|r| (r.raw_score - mean).powi(2))
+                    .sum::<f64>()
+                    / count as f64;
+                breakdown.user_spread.push(UserSpread {
+                    ratings: count,
+                    mean,
+                    variance,
+                });
+            }
+        }
+        breakdown.anime = anime.len();
+
+        let mut genre_counts: HashMap<String, usize> = HashMap::new();
+        for (&anime_id, &(_, count, source)) in &anime {
+            let known = if source.is_empty() {
+                genres(anime_id)
+            } else {
+                source.to_vec()
+            };
+            if known.is_empty() {
+                breakdown.unknown_genres += count;
+            }
+            for genre in known {
+                *genre_counts.entry(genre).or_default() += count;
+            }
+        }
+        let mut genre_counts: Vec<_> = genre_counts.into_iter().collect();
+        genre_counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        breakdown.other_genres = genre_counts
+            .iter()
+            .skip(CHARTED_GENRES)
+            .map(|(_, count)| count)
+            .sum();
+        genre_counts.truncate(CHARTED_GENRES);
+        breakdown.genres = genre_counts;
+
+        let mut ranked: Vec<(String, usize)> = anime
+            .into_values()
+            .map(|(title, count, _)| (title.to_string(), count))
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        breakdown.most_rated = ranked.iter().take(RANKED_ANIME).cloned().collect();
+        breakdown.least_rated = ranked.iter().rev().take(RANKED_ANIME).cloned().collect();
+        breakdown
+    }
+
+    /// Ratings out of every possible user–anime pair, from 0 to 1.
+    pub fn density(&self) -> f64 {
+        let pairs = self.users * self.anime;
+        if pairs == 0 {
+            0.0
+        } else {
+            self.ratings as f64 / pairs as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Rating, UserRatings};
+
+    fn rating(anime_id: u32, score: f64, genres: &[&str]) -> Rating {
+        let mut rating = Rating::new(anime_id, format!("Anime {anime_id}"), score);
+        rating.genres = genres.iter().map(|genre| genre.to_string()).collect();
+        rating
+    }
+
+    #[test]
+    fn scores_round_into_one_of_ten_buckets() {
+        assert_eq!(score_bucket(1.0), 0);
+        assert_eq!(score_bucket(7.5), 7);
+        assert_eq!(score_bucket(7.4), 6);
+        assert_eq!(score_bucket(0.2), 0);
+        assert_eq!(score_bucket(12.0), 9);
+    }
+
+    #[test]
+    fn breakdown_counts_scores_anime_and_genres() {
+        let dataset = Dataset::new(vec![
+            UserRatings {
+                user_id: "a".to_string(),
+                ratings: vec![rating(1, 9.0, &["Drama"]), rating(2, 7.4, &[])],
+            },
+            UserRatings {
+                user_id: "b".to_string(),
+                ratings: vec![rating(1, 5.0, &[])],
+            },
+        ]);
+        let breakdown = Breakdown::new(&dataset, |anime_id| {
+            if anime_id == 2 {
+                vec!["Comedy".to_string()]
+            } else {
+                Vec::new()
+            }
+        });
+        assert_eq!(breakdown.ratings, 3);
+        assert_eq!(breakdown.scores[8], 1);
+        assert_eq!(breakdown.scores[6], 1);
+        assert_eq!(breakdown.scores[4], 1);
+        assert_eq!(breakdown.most_rated[0], ("Anime 1".to_string(), 2));
+        assert_eq!(breakdown.least_rated[0], ("Anime 2".to_string(), 1));
+        assert_eq!(
+            breakdown.genres,
+            [("Drama".to_string(), 2), ("Comedy".to_string(), 1)]
+        );
+        assert_eq!(breakdown.user_spread.len(), 2);
+        assert!((breakdown.user_spread[1].variance).abs() < 1e-9);
+        assert!((breakdown.density() - 0.75).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,79 @@
+//! The forms an anime's title comes in. Datasets name anime by their MyAnimeList title, which
+//! is usually romaji; metadata sources add the English and native titles when there are any,
+//! and [`TitleForm`] picks which one labels, lists and search show.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TitleForm {
+    /// The Japanese title in Latin letters, e.g. `Shingeki no Kyojin`.
+    #[default]
+    Romaji,
+    /// The official English title, e.g. `Attack on Titan`.
+    English,
+    /// The title in its original script, e.g. `進撃の巨人`.
+    Native,
+}
+
+impl TitleForm {
+    pub const ALL: [TitleForm; 3] = [Self::Romaji, Self::English, Self::Native];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Romaji => "Romaji",
+            Self::English => "English",
+            Self::Native => "Native",
+        }
+    }
+}
+
+/// Every known form of one anime's title.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnimeTitles {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub romaji: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub english: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub native: Option<String>,
+}
+
+impl AnimeTitles {
+    pub fn is_empty(&self) -> bool {
+        self.romaji.is_none() && self.english.is_none() && self.native.is_none()
+    }
+
+    /// The title in `form`. Anime without that form, like the many with no English title,
+    /// fall back to romaji, then English, then the native title. `None` only when no form is
+    /// known.
+    pub fn get(&self, form: TitleForm) -> Option<&str> {
+        let preferred = match form {
+            TitleForm::Romaji => &self.romaji,
+            TitleForm::English => &self.english,
+            TitleForm::Native => &self.native,
+        };
+        [preferred, &self.romaji, &self.english, &self.native]
+            .into_iter()
+            .flatten()
+            .map(|title| title.trim())
+            .find(|title| !title.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_forms_fall_back_to_romaji() {
+        let titles = AnimeTitles {
+            romaji: Some("Mushishi".to_string()),
+            english: Some(" ".to_string()),
+            native: Some("蟲師".to_string()),
+        };
+        assert_eq!(titles.get(TitleForm::Native), Some("蟲師"));
+        assert_eq!(titles.get(TitleForm::English), Some("Mushishi"));
+        assert_eq!(AnimeTitles::default().get(TitleForm::Romaji), None);
+    }
+}
@@ -0,0 +1,373 @@
+//! Taste tribes: users grouped by how they score, not just by what they watched. Each user's
+//! ratings become a vector of scores minus their own mean, normalized, so a harsh and a
+//! generous rater who love the same shows land together; spherical k-means groups the vectors,
+//! and each tribe is described by its signature shows, the ones it rates furthest above
+//! everyone else.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::{Dataset, RatingMatrix};
+
+/// Tribes asked for unless the caller says otherwise.
+pub const DEFAULT_TRIBES: usize = 6;
+/// Rounds of k-means at most; it usually settles well before.
+pub const MAX_ROUNDS: usize = 30;
+/// Signature shows listed per tribe at most.
+pub const SIGNATURE_SHOWS: usize = 10;
+/// Members of a tribe who must have rated a show before it can be one of its signatures.
+pub const MIN_SIGNATURE_RATERS: usize = 3;
+/// Members listed per tribe at most, the ones closest to its centre first.
+pub const LISTED_MEMBERS: usize = 50;
+
+/// A show a tribe rates above the rest of the dataset.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Signature {
+    pub anime_id: u32,
+    pub title: String,
+    /// Members who rated it.
+    pub raters: usize,
+    /// Members' mean raw score.
+    pub tribe_mean: f64,
+    /// Everyone else's mean raw score; the dataset's mean for the anime when only the tribe
+    /// rated it.
+    pub others_mean: f64,
+}
+
+impl Signature {
+    /// How many points the tribe scores it above everyone else.
+    pub fn lift(&self) -> f64 {
+        self.tribe_mean - self.others_mean
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tribe {
+    pub size: usize,
+    /// Mean cosine similarity of the members to the tribe's centre: near 1 when they rate
+    /// alike.
+    pub cohesion: f64,
+    /// The members closest to the centre first, at most [`LISTED_MEMBERS`].
+    pub members: Vec<String>,
+    /// Highest lift first, at most [`SIGNATURE_SHOWS`].
+    pub signatures: Vec<Signature>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TribeReport {
+    /// Largest first.
+    pub tribes: Vec<Tribe>,
+    /// Users left out for having fewer than two ratings or scoring everything the same, which
+    /// says nothing about their taste.
+    pub unassigned: usize,
+}
+
+impl TribeReport {
+    /// Groups `dataset`'s users into at most `count` tribes. The same dataset always gives the
+    /// same tribes: the first centre is the user with the most ratings and each next one the
+    /// user least like the centres so far.
+    pub fn new(dataset: &Dataset, count: usize) -> Self {
+        let matrix = RatingMatrix::new(dataset);
+        let mut vectors: Vec<(usize, Vec<(usize, f64)>)> = Vec::new();
+        for user_index in 0..matrix.rows() {
+            let row = matrix.row(user_index);
+            if row.len() < 2 {
+                continue;
+            }
+            let mut vector: Vec<(usize, f64)> = row
+                .columns
+                .iter()
+                .zip(row.centred)
+                .map(|(&column, &value)| (column as usize, value))
+                .collect();
+            let norm = vector.iter().map(|(_, v)| v * v).sum::<f64>().sqrt();
+            if norm < 1e-9 {
+                continue;
+            }
+            for (_, value) in &mut vector {
+                *value /= norm;
+            }
+            vectors.push((user_index, vector));
+        }
+        let unassigned = dataset.users.len() - vectors.len();
+        let count = count.clamp(1, vectors.len().max(1));
+        if vectors.is_empty() {
+            return Self {
+                tribes: Vec::new(),
+                unassigned,
+            };
+        }
+
+        let mut centres = seed_centres(&vectors, matrix.column_count(), count);
+        let mut assignment = vec![usize::MAX; vectors.len()];
+        for _ in 0..MAX_ROUNDS {
+            let mut changed = false;
+            for (slot, (_, vector)) in assignment.iter_mut().zip(&vectors) {
+                let best = nearest(&centres, vector).0;
+                changed |= *slot != best;
+                *slot = best;
+            }
+            if !changed {
+                break;
+            }
+            for (tribe, centre) in centres.iter_mut().enumerate() {
+                centre.fill(0.0);
+                for ((_, vector), _) in vectors.iter().zip(&assignment).filter(|(_, &t)| t == tribe)
+                {
+                    for &(column, value) in vector {
+                        centre[column] += value;
+                    }
+                }
+                normalize(centre);
+            }
+        }
+
+        let overall = matrix.column_totals();
+        let mut tribes: Vec<Tribe> = (0..centres.len())
+            .filter_map(|tribe| {
+                let mut members: Vec<(usize, f64)> = vectors
+                    .iter()
+                    .zip(&assignment)
+                    .filter(|(_, &t)| t == tribe)
+                    .map(|((user_index, vector), _)| (*user_index, dot(&centres[tribe], vector)))
+                    .collect();
+                if members.is_empty() {
+                    return None;
+                }
+                members.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+                let cohesion = members.iter().map(|(_, s)| s).sum::<f64>() / members.len() as f64;
+                let signatures = signatures(dataset, &matrix, &members, &overall);
+                Some(Tribe {
+                    size: members.len(),
+                    cohesion,
+                    members: members
+                        .iter()
+                        .take(LISTED_MEMBERS)
+                        .map(|(user_index, _)| dataset.users[*user_index].user_id.clone())
+                        .collect(),
+                    signatures,
+                })
+            })
+            .collect();
+        tribes.sort_by(|a, b| b.size.cmp(&a.size).then(b.cohesion.total_cmp(&a.cohesion)));
+        Self { tribes, unassigned }
+    }
+
+    /// The report as Markdown, one section per tribe, naming anime through `title` (given the
+    /// anime's id and its dataset title).
+    pub fn to_markdown(&self, title: impl Fn(u32, &str) -> String) -> String {
+        let mut out = String::from("# Taste tribes\n");
+        for (index, tribe) in self.tribes.iter().enumerate() {
+            out.push_str(&format!(
+                "\n## Tribe {}: {} users, cohesion {:.2}\n\n",
+                index + 1,
+                tribe.size,
+                tribe.cohesion
+            ));
+            if tribe.signatures.is_empty() {
+                out.push_str("No show stands out for this tribe.\n");
+                continue;
+            }
+            out.push_str("| Anime | Raters | Tribe mean | Others' mean |\n|---|---|---|---|\n");
+            for signature in &tribe.signatures {
+                out.push_str(&format!(
+                    "| {} | {} | {:.2} | {:.2} |\n",
+                    title(signature.anime_id, &signature.title).replace('|', "\\|"),
+                    signature.raters,
+                    signature.tribe_mean,
+                    signature.others_mean
+                ));
+            }
+        }
+        if self.unassigned > 0 {
+            out.push_str(&format!(
+                "\n{} users aren't in a tribe: they rated fewer than two anime or scored \
+                 everything the same.\n",
+                self.unassigned
+            ));
+        }
+        out
+    }
+}
+
+/// Farthest-first centres: the user with the most ratings, then again and again the user
+/// whose best match among the centres so far is worst.
+fn seed_centres(
+    vectors: &[(usize, Vec<(usize, f64)>)],
+    columns: usize,
+    count: usize,
+) -> Vec<Vec<f64>> {
+    let dense = |vector: &[(usize, f64)]| {
+        let mut centre = vec![0.0; columns];
+        for &(column, value) in vector {
+            centre[column] = value;
+        }
+        centre
+    };
+    let first = (0..vectors.len())
+        .max_by(|&a, &b| vectors[a].1.len().cmp(&vectors[b].1.len()).then(b.cmp(&a)))
+        .unwrap_or_default();
+    let mut centres = vec![dense(&vectors[first].1)];
+    while centres.len() < count {
+        let farthest = (0..vectors.len())
+            .map(|index| (index, nearest(&centres, &vectors[index].1).1))
+            .min_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        match farthest {
+            // Everyone left is already a centre's twin.
+            Some((_, similarity)) if similarity > 1.0 - 1e-9 => break,
+            Some((index, _)) => centres.push(dense(&vectors[index].1)),
+            None => break,
+        }
+    }
+    centres
+}
+
+/// The centre most like `vector`, and how alike they are.
+fn nearest(centres: &[Vec<f64>], vector: &[(usize, f64)]) -> (usize, f64) {
+    centres
+        .iter()
+        .enumerate()
+        .map(|(index, centre)| (index, dot(centre, vector)))
+        .fold((0, f64::NEG_INFINITY), |best, next| {
+            if next.1 > best.1 {
+                next
+            } else {
+                best
+            }
+        })
+}
+
+fn dot(centre: &[f64], vector: &[(usize, f64)]) -> f64 {
+    vector
+        .iter()
+        .map(|&(column, value)| centre[column] * value)
+        .sum()
+}
+
+fn normalize(centre: &mut [f64]) {
+    let norm = centre.iter().map(|v| v * v).sum::<f64>().sqrt();
+    if norm > 1e-9 {
+        for value in centre {
+            *value /= norm;
+        }
+    }
+}
+
+/// The shows `members` rate furthest above everyone else, among those enough of them rated.
+/// `overall` holds the sum and count of every column's scores.
+fn signatures(
+    dataset: &Dataset,
+    matrix: &RatingMatrix,
+    members: &[(usize, f64)],
+    overall: &[(f64, usize)],
+) -> Vec<Signature> {
+    let mut rated: HashMap<u32, (&str, f64, usize)> = HashMap::new();
+    for (user_index, _) in members {
+        for rating in &dataset.users[*user_index].ratings {
+            let entry = rated
+                .entry(rating.anime_id)
+                .or_insert((&rating.title, 0.0, 0));
+            entry.1 += rating.raw_score;
+            entry.2 += 1;
+        }
+    }
+    let mut signatures: Vec<Signature> = rated
+        .into_iter()
+        .filter(|(_, (_, _, raters))| *raters >= MIN_SIGNATURE_RATERS.min(members.len()))
+        .map(|(anime_id, (title, total, raters))| {
+            let column = matrix
+                .column(anime_id)
+                .expect("every rated anime has a column");
+            let (all_total, all_raters) = overall[column as usize];
+            let others_mean = if all_raters > raters {
+                (all_total - total) / (all_raters - raters) as f64
+            } else {
+                all_total / all_raters as f64
+            };
+            Signature {
+                anime_id,
+                title: title.to_string(),
+                raters,
+                tribe_mean: total / raters as f64,
+                others_mean,
+            }
+        })
+        .filter(|signature| signature.lift() > 0.0)
+        .collect();
+    signatures.sort_by(|a, b| {
+        b.lift()
+            .total_cmp(&a.lift())
+            .then(b.raters.cmp(&a.raters))
+            .then(a.anime_id.cmp(&b.anime_id))
+    });
+    signatures.truncate(SIGNATURE_SHOWS);
+    signatures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Rating, UserRatings};
+
+    fn user(user_id: &str, ratings: &[(u32, f64)]) -> UserRatings {
+        UserRatings {
+            user_id: user_id.to_string(),
+            ratings: ratings
+                .iter()
+                .map(|&(anime_id, score)| Rating::new(anime_id, format!("Anime {anime_id}"), score))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn users_who_score_alike_form_a_tribe() {
+        // a–c love 1 and 2 and dislike 3 and 4; d–f the other way round; g says nothing.
+        let dataset = Dataset::new(vec![
+            user("a", &[(1, 10.0), (2, 9.0), (3, 3.0), (4, 2.0)]),
+            user("b", &[(1, 9.0), (2, 9.0), (3, 4.0), (4, 3.0)]),
+            user("c", &[(1, 8.0), (2, 10.0), (3, 2.0)]),
+            user("d", &[(1, 2.0), (2, 3.0), (3, 9.0), (4, 10.0)]),
+            user("e", &[(1, 3.0), (3, 10.0), (4, 9.0)]),
+            user("f", &[(2, 2.0), (3, 8.0), (4, 9.0)]),
+            user("g", &[(1, 7.0), (2, 7.0)]),
+        ]);
+        let report = TribeReport::new(&dataset, 2);
+        assert_eq!(report.unassigned, 1);
+        assert_eq!(report.tribes.len(), 2);
+        let mut groups: Vec<Vec<&str>> = report
+            .tribes
+            .iter()
+            .map(|tribe| {
+                let mut members: Vec<_> = tribe.members.iter().map(String::as_str).collect();
+                members.sort();
+                members
+            })
+            .collect();
+        groups.sort();
+        assert_eq!(groups, [["a", "b", "c"], ["d", "e", "f"]]);
+        let lovers = report
+            .tribes
+            .iter()
+            .find(|tribe| tribe.members.contains(&"a".to_string()))
+            .unwrap();
+        let top = &lovers.signatures[0];
+        assert!(top.anime_id == 1 || top.anime_id == 2);
+        assert!(top.lift() > 5.0);
+        assert!(lovers.signatures.iter().all(|s| s.anime_id != 3));
+    }
+
+    #[test]
+    fn asks_for_no_more_tribes_than_users() {
+        let dataset = Dataset::new(vec![user("a", &[(1, 9.0), (2, 3.0)])]);
+        let report = TribeReport::new(&dataset, 5);
+        assert_eq!(report.tribes.len(), 1);
+        assert_eq!(report.tribes[0].members, ["a"]);
+        assert!(TribeReport::new(&Dataset::new(Vec::new()), 3)
+            .tribes
+            .is_empty());
+    }
+}
@@ -0,0 +1,274 @@
+//! Watch orders: a franchise, or any set of anime such as a recommendation list, in the order
+//! to watch them, with how long that takes. Prequels come before their sequels and parent
+//! stories before their side stories ([`Relations::prior`]); otherwise, and where relations
+//! go round in a circle, the earlier-aired goes first. The relations are sorted as a petgraph
+//! graph, with circles condensed into one block each. Lengths are estimated from episode
+//! counts and, where it is known, episode length.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use petgraph::algo;
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::Direction;
+use serde::Serialize;
+
+use crate::relations::Relations;
+
+/// Episode length assumed when the metadata doesn't give one, as for a TV episode.
+pub const DEFAULT_EPISODE_MINUTES: u32 = 24;
+
+/// What planning needs to know about one anime.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlanInfo {
+    pub title: String,
+    pub year: Option<u32>,
+    pub episodes: Option<u32>,
+    pub episode_minutes: Option<u32>,
+}
+
+/// One anime in a watch order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedAnime {
+    pub anime_id: u32,
+    pub title: String,
+    pub year: Option<u32>,
+    pub episodes: Option<u32>,
+    /// Estimated running time; `None` when the episode count isn't known.
+    pub minutes: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchOrder {
+    pub entries: Vec<PlannedAnime>,
+}
+
+impl WatchOrder {
+    /// `anime` in watch order, each once, described by `info`.
+    pub fn plan(relations: &Relations, anime: &[u32], info: impl Fn(u32) -> PlanInfo) -> Self {
+        let mut infos: HashMap<u32, PlanInfo> = HashMap::new();
+        for &anime_id in anime {
+            infos.entry(anime_id).or_insert_with(|| info(anime_id));
+        }
+        let year = |anime_id: u32| infos[&anime_id].year.unwrap_or(u32::MAX);
+        let mut ids: Vec<u32> = infos.keys().copied().collect();
+        ids.sort_unstable_by_key(|&anime_id| (year(anime_id), anime_id));
+
+        let mut graph = DiGraph::<u32, ()>::with_capacity(ids.len(), ids.len());
+        let nodes: HashMap<u32, NodeIndex> = ids
+            .iter()
+            .map(|&anime_id| (anime_id, graph.add_node(anime_id)))
+            .collect();
+        for &anime_id in &ids {
+            for prior in relations.prior(anime_id) {
+                if let Some(&from) = nodes.get(prior) {
+                    graph.add_edge(from, nodes[&anime_id], ());
+                }
+            }
+        }
+        // Anime whose relations go round in a circle become one block, watched earliest-aired
+        // first, which leaves a graph without cycles to sort.
+        let mut blocks = algo::condensation(graph, true);
+        for members in blocks.node_weights_mut() {
+            members.sort_unstable_by_key(|&anime_id| (year(anime_id), anime_id));
+        }
+        let sorted = algo::toposort(&blocks, None).expect("condensed graphs have no cycles");
+        // A block goes by when it aired or, if later, when what it follows did, so each anime
+        // comes as early as its airing and its prequels allow; ties keep the sorted order.
+        let mut airs = vec![0; blocks.node_count()];
+        for &block in &sorted {
+            let follows = blocks
+                .neighbors_directed(block, Direction::Incoming)
+                .map(|prior| airs[prior.index()])
+                .max()
+                .unwrap_or(0);
+            airs[block.index()] = year(blocks[block][0]).max(follows);
+        }
+        let mut ranked: Vec<(usize, NodeIndex)> = sorted.into_iter().enumerate().collect();
+        ranked.sort_by_key(|&(rank, block)| (airs[block.index()], rank));
+        let order: Vec<u32> = ranked
+            .into_iter()
+            .flat_map(|(_, block)| blocks[block].iter().copied())
+            .collect();
+
+        let entries = order
+            .into_iter()
+            .map(|anime_id| {
+                let info = infos.remove(&anime_id).unwrap_or_default();
+                PlannedAnime {
+                    anime_id,
+                    minutes: info.episodes.map(|episodes| {
+                        episodes * info.episode_minutes.unwrap_or(DEFAULT_EPISODE_MINUTES)
+                    }),
+                    title: info.title,
+                    year: info.year,
+                    episodes: info.episodes,
+                }
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// The estimated running time of the entries whose length is known.
+    pub fn total_minutes(&self) -> u32 {
+        self.entries.iter().filter_map(|entry| entry.minutes).sum()
+    }
+
+    /// Entries whose length isn't known, left out of [`WatchOrder::total_minutes`].
+    pub fn unknown_lengths(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.minutes.is_none())
+            .count()
+    }
+
+    /// E.g. `about 31.5 hours`, or `about 31.5 hours, plus 2 anime of unknown length`.
+    pub fn total_line(&self) -> String {
+        let mut line = format!("about {} hours", hours(self.total_minutes()));
+        match self.unknown_lengths() {
+            0 => {}
+            1 => line.push_str(", plus 1 anime of unknown length"),
+            unknown => line.push_str(&format!(", plus {unknown} anime of unknown length")),
+        }
+        line
+    }
+
+    /// The order as a Markdown task list under `heading`, to tick off in a notes app or an
+    /// issue.
+    pub fn checklist(&self, heading: &str) -> String {
+        let mut out = format!("# {heading}\n\n{}\n\n", capitalize(&self.total_line()));
+        for entry in &self.entries {
+            let _ = write!(out, "- [ ] {}", entry.title);
+            let mut details = Vec::new();
+            if let Some(year) = entry.year {
+                details.push(year.to_string());
+            }
+            match entry.episodes {
+                Some(1) => details.push("1 episode".to_string()),
+                Some(episodes) => details.push(format!("{episodes} episodes")),
+                None => {}
+            }
+            if let Some(minutes) = entry.minutes {
+                details.push(format!("{} h", hours(minutes)));
+            }
+            if !details.is_empty() {
+                let _ = write!(out, " ({})", details.join(" · "));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Minutes as hours to one decimal, without a trailing `.0`.
+pub fn hours(minutes: u32) -> String {
+    let tenths = (minutes * 10 + 30) / 60;
+    if tenths.is_multiple_of(10) {
+        (tenths / 10).to_string()
+    } else {
+        format!("{}.{}", tenths / 10, tenths % 10)
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relations::{Relation, RelationKind};
+
+    #[test]
+    fn sequels_follow_their_prequels_whatever_they_aired() {
+        // Fate/Zero (2011) is the prequel of Unlimited Blade Works (2014), and a recap (2012)
+        // sums up Fate/Zero; an unrelated 2005 show goes first.
+        let listed = [
+            (
+                22_297,
+                vec![Relation {
+                    kind: RelationKind::Prequel,
+                    anime_id: 10_087,
+                }],
+            ),
+            (
+                10_087,
+                vec![Relation {
+                    kind: RelationKind::Summary,
+                    anime_id: 500,
+                }],
+            ),
+        ];
+        let relations = Relations::new(
+            listed
+                .iter()
+                .map(|(anime_id, relations)| (*anime_id, &relations[..])),
+        );
+        let info = |anime_id: u32| {
+            let (title, year, episodes, episode_minutes) = match anime_id {
+                22_297 => ("Unlimited Blade Works", 2014, Some(12), None),
+                10_087 => ("Fate/Zero", 2011, Some(13), Some(24)),
+                500 => ("Fate/Zero Remix", 2012, None, None),
+                _ => ("Older Show", 2005, Some(1), Some(90)),
+            };
+            PlanInfo {
+                title: title.to_string(),
+                year: Some(year),
+                episodes,
+                episode_minutes,
+            }
+        };
+        let order = WatchOrder::plan(&relations, &[22_297, 500, 7, 10_087, 22_297], info);
+        let ids: Vec<u32> = order.entries.iter().map(|entry| entry.anime_id).collect();
+        assert_eq!(ids, [7, 10_087, 500, 22_297]);
+        assert_eq!(order.total_minutes(), 90 + 13 * 24 + 12 * 24);
+        assert_eq!(order.unknown_lengths(), 1);
+        assert_eq!(
+            order.total_line(),
+            "about 11.5 hours, plus 1 anime of unknown length"
+        );
+        let checklist = order.checklist("Fate");
+        assert!(checklist.starts_with("# Fate\n\nAbout 11.5 hours"));
+        assert!(checklist.contains("- [ ] Fate/Zero (2011 · 13 episodes · 5.2 h)\n"));
+        assert!(checklist.contains("- [ ] Fate/Zero Remix (2012)\n"));
+    }
+
+    #[test]
+    fn circles_are_watched_together_and_prequels_first_whenever_they_aired() {
+        let sequel = |anime_id: u32| Relation {
+            kind: RelationKind::Sequel,
+            anime_id,
+        };
+        // 1 and 2 are listed as each other's sequel; 3 is a prequel that aired after 4, its
+        // sequel; 5 is on its own.
+        let listed = [
+            (1, vec![sequel(2)]),
+            (2, vec![sequel(1)]),
+            (3, vec![sequel(4)]),
+        ];
+        let relations = Relations::new(
+            listed
+                .iter()
+                .map(|(anime_id, relations)| (*anime_id, &relations[..])),
+        );
+        let info = |anime_id: u32| PlanInfo {
+            title: format!("Anime {anime_id}"),
+            year: Some(match anime_id {
+                1 => 2010,
+                2 => 2008,
+                3 => 2015,
+                4 => 2012,
+                _ => 2009,
+            }),
+            ..PlanInfo::default()
+        };
+        let order = WatchOrder::plan(&relations, &[5, 4, 3, 2, 1], info);
+        let ids: Vec<u32> = order.entries.iter().map(|entry| entry.anime_id).collect();
+        assert_eq!(ids, [2, 1, 5, 3, 4]);
+    }
+}
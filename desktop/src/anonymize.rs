@@ -0,0 +1,325 @@
+//! Turns raw rating exports, where user ids are real usernames, into the anonymized format the
+//! pipeline publishes: salted-hash user ids, optional score noise, and k-anonymity filtering.
+
+use std::collections::{HashMap, HashSet};
+
+use rand::RngExt;
+use sha2::{Digest, Sha256};
+use wasiw_core::{Dataset, UserRatings};
+
+/// Hex characters kept from the hash; matches `anonymizeUsername` in the pipeline.
+const USER_ID_LENGTH: usize = 24;
+const SCORE_MIN: f64 = 1.0;
+const SCORE_MAX: f64 = 10.0;
+
+/// Random perturbation applied to every raw score before release.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ScoreNoise {
+    #[default]
+    None,
+    /// Uniform jitter of up to ± this many points.
+    Jitter(f64),
+    /// Laplace noise calibrated for ε-differential privacy per rating, with the score range as
+    /// sensitivity. Smaller ε means more noise.
+    Laplace { epsilon: f64 },
+}
+
+impl ScoreNoise {
+    /// Each kind with its default amount, as offered in the export dialog.
+    pub const KINDS: [ScoreNoise; 3] = [
+        Self::None,
+        Self::Jitter(0.5),
+        Self::Laplace { epsilon: 1.0 },
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::None => "No noise",
+            Self::Jitter(_) => "Uniform jitter (± points)",
+            Self::Laplace { .. } => "Differential privacy (ε)",
+        }
+    }
+
+    pub fn amount(self) -> Option<f64> {
+        match self {
+            Self::None => None,
+            Self::Jitter(amplitude) => Some(amplitude),
+            Self::Laplace { epsilon } => Some(epsilon),
+        }
+    }
+
+    pub fn with_amount(self, amount: f64) -> Self {
+        match self {
+            Self::None => Self::None,
+            Self::Jitter(_) => Self::Jitter(amount),
+            Self::Laplace { .. } => Self::Laplace { epsilon: amount },
+        }
+    }
+
+    fn sample(self, rng: &mut impl RngExt) -> f64 {
+        match self {
+            Self::None => 0.0,
+            Self::Jitter(amplitude) if amplitude > 0.0 => rng.random_range(-amplitude..=amplitude),
+            Self::Jitter(_) => 0.0,
+            Self::Laplace { epsilon } => {
+                let scale = (SCORE_MAX - SCORE_MIN) / epsilon.max(f64::MIN_POSITIVE);
+                // Inverse CDF of the Laplace distribution.
+                let u: f64 = rng.random_range(-0.5..0.5);
+                -scale * u.signum() * (1.0 - 2.0 * u.abs()).ln()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnonymizeOptions {
+    pub salt: String,
+    pub noise: ScoreNoise,
+    /// Users whose rating fingerprint is shared by fewer than `k` users are dropped; 1 keeps
+    /// everyone.
+    pub k: usize,
+    /// Keep `updatedAt`. Dates narrow down who a user is, so they are removed by default.
+    pub keep_dates: bool,
+}
+
+impl Default for AnonymizeOptions {
+    fn default() -> Self {
+        Self {
+            salt: String::new(),
+            noise: ScoreNoise::None,
+            k: 1,
+            keep_dates: false,
+        }
+    }
+}
+
+/// Counts shown after an anonymized export.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnonymizeStats {
+    pub users_kept: usize,
+    /// Usernames that hashed to an id already seen, i.e. the same account listed twice.
+    pub duplicates_dropped: usize,
+    /// Users removed because their fingerprint was shared by fewer than `k` users.
+    pub unique_dropped: usize,
+}
+
+impl AnonymizeStats {
+    pub fn summary(&self) -> String {
+        format!(
+            "Anonymized {} users; dropped {} duplicate accounts and {} users with identifying rating fingerprints.",
+            self.users_kept, self.duplicates_dropped, self.unique_dropped
+        )
+    }
+}
+
+/// Same scheme as the pipeline: SHA-256 of `salt:username` (trimmed, lower-cased), truncated.
+pub fn hash_user_id(username: &str, salt: &str) -> String {
+    let digest = Sha256::digest(format!("{salt}:{}", username.trim().to_lowercase()));
+    digest
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>()[..USER_ID_LENGTH]
+        .to_string()
+}
+
+/// A random salt for users who don't need ids to match an earlier export.
+pub fn random_salt() -> String {
+    let mut rng = rand::rng();
+    (0..16)
+        .map(|_| format!("{:02x}", rng.random::<u8>()))
+        .collect()
+}
+
+/// The ratings a user could be recognized by: the anime they rated and the whole-point score
+/// they gave, before noise.
+fn fingerprint(user: &UserRatings) -> Vec<(u32, u8)> {
+    let mut fingerprint: Vec<(u32, u8)> = user
+        .ratings
+        .iter()
+        .map(|rating| (rating.anime_id, rating.raw_score.round() as u8))
+        .collect();
+    fingerprint.sort_unstable();
+    fingerprint
+}
+
+pub fn anonymize(dataset: &Dataset, options: &AnonymizeOptions) -> (Dataset, AnonymizeStats) {
+    let mut stats = AnonymizeStats::default();
+    let mut seen = HashSet::new();
+    let users: Vec<&UserRatings> = dataset
+        .users
+        .iter()
+        .filter(|user| {
+            let unique = seen.insert(user.user_id.trim().to_lowercase());
+            if !unique {
+                stats.duplicates_dropped += 1;
+            }
+            unique
+        })
+        .collect();
+
+    let fingerprints: Vec<Vec<(u32, u8)>> = users.iter().map(|user| fingerprint(user)).collect();
+    let mut shared: HashMap<&[(u32, u8)], usize> = HashMap::new();
+    for fingerprint in &fingerprints {
+        *shared.entry(fingerprint).or_default() += 1;
+    }
+
+    let mut rng = rand::rng();
+    let mut anonymized = Vec::with_capacity(users.len());
+    for (user, fingerprint) in users.into_iter().zip(&fingerprints) {
+        if shared[fingerprint.as_slice()] < options.k {
+            stats.unique_dropped += 1;
+            continue;
+        }
+        let mut ratings = user.ratings.clone();
+        for rating in &mut ratings {
+            rating.raw_score =
+                (rating.raw_score + options.noise.sample(&mut rng)).clamp(SCORE_MIN, SCORE_MAX);
+            if !options.keep_dates {
+                rating.updated_at = None;
+            }
+        }
+        anonymized.push(UserRatings {
+            user_id: hash_user_id(&user.user_id, &options.salt),
+            ratings,
+        });
+    }
+    stats.users_kept = anonymized.len();
+
+    let mut output = Dataset::new(anonymized);
+    output.source = dataset.source.clone();
+    output.normalize_scores();
+    (output, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use wasiw_core::Rating;
+
+    use super::*;
+
+    fn user(user_id: &str, ratings: &[(u32, f64)]) -> UserRatings {
+        UserRatings {
+            user_id: user_id.to_string(),
+            ratings: ratings
+                .iter()
+                .map(|&(anime_id, score)| Rating::new(anime_id, format!("Anime {anime_id}"), score))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn user_ids_hash_the_same_as_the_pipeline() {
+        // From `anonymizeUsername` in pipeline/src/anonymize.ts.
+        assert_eq!(
+            hash_user_id("  SomeUser ", "pepper"),
+            "e6f6d829e48c8f388c0e0a2f"
+        );
+        assert_eq!(
+            hash_user_id("someuser", "pepper"),
+            "e6f6d829e48c8f388c0e0a2f"
+        );
+        assert_eq!(hash_user_id("Alice", ""), "3b8d32346718b5afb363b17c");
+        assert_ne!(
+            hash_user_id("someuser", "salt"),
+            hash_user_id("someuser", "pepper")
+        );
+    }
+
+    #[test]
+    fn users_with_rare_fingerprints_are_dropped() {
+        let dataset = Dataset::new(vec![
+            user("a", &[(1, 7.8), (2, 4.0)]),
+            user("b", &[(2, 4.2), (1, 8.1)]),
+            user("c", &[(1, 8.0), (3, 4.0)]),
+        ]);
+        let options = AnonymizeOptions {
+            k: 2,
+            ..AnonymizeOptions::default()
+        };
+        let (output, stats) = anonymize(&dataset, &options);
+        assert_eq!(
+            stats,
+            AnonymizeStats {
+                users_kept: 2,
+                duplicates_dropped: 0,
+                unique_dropped: 1,
+            }
+        );
+        let ids: Vec<&str> = output
+            .users
+            .iter()
+            .map(|user| user.user_id.as_str())
+            .collect();
+        assert_eq!(ids, [hash_user_id("a", ""), hash_user_id("b", "")]);
+
+        let (output, stats) = anonymize(&dataset, &AnonymizeOptions::default());
+        assert_eq!(output.users.len(), 3);
+        assert_eq!(stats.unique_dropped, 0);
+    }
+
+    #[test]
+    fn an_account_listed_twice_is_kept_once_without_its_dates() {
+        let mut first = user("Alice", &[(1, 9.0)]);
+        first.ratings[0].updated_at = Some("2024-01-05".to_string());
+        let dataset = Dataset::new(vec![
+            first,
+            user(" alice ", &[(2, 3.0)]),
+            user("bob", &[(1, 9.0)]),
+        ]);
+        let options = AnonymizeOptions {
+            salt: "pepper".to_string(),
+            ..AnonymizeOptions::default()
+        };
+        let (output, stats) = anonymize(&dataset, &options);
+        assert_eq!(stats.duplicates_dropped, 1);
+        assert_eq!(stats.users_kept, 2);
+        assert_eq!(output.users[0].user_id, hash_user_id("alice", "pepper"));
+        assert_eq!(output.users[0].ratings[0].anime_id, 1);
+        assert_eq!(output.users[0].ratings[0].updated_at, None);
+
+        let keep_dates = AnonymizeOptions {
+            keep_dates: true,
+            ..options
+        };
+        let (output, _) = anonymize(&dataset, &keep_dates);
+        assert_eq!(
+            output.users[0].ratings[0].updated_at.as_deref(),
+            Some("2024-01-05")
+        );
+    }
+
+    #[test]
+    fn noisy_scores_stay_on_the_rating_scale() {
+        let ratings: Vec<(u32, f64)> = (0..200)
+            .map(|anime_id| (anime_id, if anime_id % 2 == 0 { 1.0 } else { 10.0 }))
+            .collect();
+        let dataset = Dataset::new(vec![user("a", &ratings)]);
+        for noise in [
+            ScoreNoise::Laplace { epsilon: 0.1 },
+            ScoreNoise::Jitter(3.0),
+        ] {
+            let options = AnonymizeOptions {
+                noise,
+                ..AnonymizeOptions::default()
+            };
+            let (output, _) = anonymize(&dataset, &options);
+            let scores: Vec<f64> = output.users[0]
+                .ratings
+                .iter()
+                .map(|r| r.raw_score)
+                .collect();
+            assert!(scores
+                .iter()
+                .all(|score| (SCORE_MIN..=SCORE_MAX).contains(score)));
+            assert!(scores
+                .iter()
+                .any(|&score| score != SCORE_MIN && score != SCORE_MAX));
+        }
+        let (output, _) = anonymize(&dataset, &AnonymizeOptions::default());
+        assert!(output.users[0]
+            .ratings
+            .iter()
+            .zip(&ratings)
+            .all(|(rating, &(_, score))| rating.raw_score == score));
+    }
+}
@@ -0,0 +1,204 @@
+//! What the command palette (Ctrl+K) offers: the menu bar's actions, the tabs, layouts,
+//! sampling and edge filters, the keyboard shortcuts' actions, and every anime in the graph to
+//! jump to.
+
+use wasiw_core::sampling::Sampling;
+use wasiw_core::{EdgeKind, GraphModel, LayoutKind, NodeType};
+use wasiw_ui::i18n::{self, tr};
+use wasiw_ui::keymap::{Keymap, Shortcut};
+use wasiw_ui::palette::PaletteCommand;
+
+use crate::menu;
+use crate::routes::Route;
+
+const TAB_PREFIX: &str = "tab:";
+const LAYOUT_PREFIX: &str = "layout:";
+const SAMPLING_PREFIX: &str = "sampling:";
+const SHORTCUT_PREFIX: &str = "shortcut:";
+const TOGGLE_USERS_ID: &str = "toggle-users";
+const TOGGLE_RATING_EDGES_ID: &str = "toggle-rating-edges";
+const TOGGLE_SIMILARITY_EDGES_ID: &str = "toggle-similarity-edges";
+const START_FRESH_ID: &str = "start-fresh";
+const TOGGLE_LOG_ID: &str = "toggle-log";
+const TOGGLE_TEXT_VIEW_ID: &str = "toggle-text-view";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// A menu bar action, by its menu id.
+    Menu(&'static str),
+    Tab(Route),
+    Layout(LayoutKind),
+    /// A sampling kind, keeping the current count.
+    Sampling(Sampling),
+    ToggleUsers,
+    ToggleEdges(EdgeKind),
+    /// What a keyboard shortcut does.
+    Shortcut(Shortcut),
+    StartFresh,
+    ToggleLog,
+    /// Switches between drawing the graph and listing it as text.
+    ToggleTextView,
+    /// Opens the details of the anime node at this index.
+    Anime(usize),
+}
+
+impl Command {
+    /// The command a palette id stands for in `graph`, or `None` if it is gone, like an anime
+    /// no longer in the graph.
+    pub fn parse(id: &str, graph: &GraphModel) -> Option<Self> {
+        if let Some(action) = menu::ACTIONS.iter().find(|action| action.id == id) {
+            return Some(Self::Menu(action.id));
+        }
+        if let Some(path) = id.strip_prefix(TAB_PREFIX) {
+            return path.parse().ok().map(Self::Tab);
+        }
+        if let Some(label) = id.strip_prefix(LAYOUT_PREFIX) {
+            return LayoutKind::ALL
+                .into_iter()
+                .find(|kind| kind.label() == label)
+                .map(Self::Layout);
+        }
+        if let Some(label) = id.strip_prefix(SAMPLING_PREFIX) {
+            return Sampling::KINDS
+                .into_iter()
+                .find(|kind| kind.label() == label)
+                .map(Self::Sampling);
+        }
+        if let Some(name) = id.strip_prefix(SHORTCUT_PREFIX) {
+            return Shortcut::ALL
+                .into_iter()
+                .find(|shortcut| shortcut.name() == name)
+                .map(Self::Shortcut);
+        }
+        if id.starts_with("anime:") {
+            return graph
+                .nodes
+                .iter()
+                .position(|node| node.id == id && !node.hidden)
+                .map(Self::Anime);
+        }
+        Some(match id {
+            TOGGLE_USERS_ID => Self::ToggleUsers,
+            TOGGLE_RATING_EDGES_ID => Self::ToggleEdges(EdgeKind::Rating),
+            TOGGLE_SIMILARITY_EDGES_ID => Self::ToggleEdges(EdgeKind::Similarity),
+            START_FRESH_ID => Self::StartFresh,
+            TOGGLE_LOG_ID => Self::ToggleLog,
+            TOGGLE_TEXT_VIEW_ID => Self::ToggleTextView,
+            _ => return None,
+        })
+    }
+}
+
+/// Everything the palette lists for `graph`; toggles are labelled with what they would do.
+pub fn palette_commands(
+    graph: &GraphModel,
+    keymap: &Keymap,
+    log_open: bool,
+    text_view: bool,
+) -> Vec<PaletteCommand> {
+    let command = |id: String, label: String, group: String| PaletteCommand {
+        id,
+        label,
+        group,
+        keys: None,
+        searched_only: false,
+    };
+    // The text key for hiding something shown, or showing something hidden.
+    let toggle = |shown: bool, what: &str| {
+        let verb = if shown { "hide" } else { "show" };
+        tr(&format!("command-{verb}-{what}"))
+    };
+    let graph_group = tr("command-group-graph");
+    let view_group = tr("command-group-view");
+    let mut commands: Vec<PaletteCommand> = menu::ACTIONS
+        .iter()
+        .map(|action| command(action.id.to_string(), action.label(), action.menu()))
+        .collect();
+    commands.extend(Route::tabs().into_iter().map(|tab| {
+        command(
+            format!("{TAB_PREFIX}{tab}"),
+            tr!("command-go-to", tab = tab.label()),
+            tr("command-group-tab"),
+        )
+    }));
+    commands.extend(LayoutKind::ALL.into_iter().map(|kind| {
+        command(
+            format!("{LAYOUT_PREFIX}{}", kind.label()),
+            tr!("command-layout", layout = i18n::layout_name(kind)),
+            graph_group.clone(),
+        )
+    }));
+    commands.extend(Sampling::KINDS.into_iter().map(|kind| {
+        command(
+            format!("{SAMPLING_PREFIX}{}", kind.label()),
+            tr!("command-sample", sampling = i18n::sampling_name(kind)),
+            graph_group.clone(),
+        )
+    }));
+    let filter = graph.filter;
+    commands.extend([
+        command(
+            TOGGLE_USERS_ID.to_string(),
+            toggle(filter.show_users, "users"),
+            graph_group.clone(),
+        ),
+        command(
+            TOGGLE_RATING_EDGES_ID.to_string(),
+            toggle(filter.shows_edges(EdgeKind::Rating), "rating-edges"),
+            graph_group.clone(),
+        ),
+        command(
+            TOGGLE_SIMILARITY_EDGES_ID.to_string(),
+            toggle(filter.shows_edges(EdgeKind::Similarity), "similarity-edges"),
+            graph_group,
+        ),
+        command(
+            START_FRESH_ID.to_string(),
+            tr("settings-start-fresh"),
+            view_group.clone(),
+        ),
+        command(
+            TOGGLE_LOG_ID.to_string(),
+            tr(if log_open {
+                "settings-hide-log"
+            } else {
+                "settings-show-log"
+            }),
+            view_group.clone(),
+        ),
+        command(
+            TOGGLE_TEXT_VIEW_ID.to_string(),
+            tr(if text_view {
+                "graph-drawing-view"
+            } else {
+                "graph-text-view"
+            }),
+            view_group.clone(),
+        ),
+    ]);
+    commands.extend(
+        Shortcut::ALL
+            .into_iter()
+            .filter(|&shortcut| shortcut != Shortcut::CommandPalette)
+            .map(|shortcut| PaletteCommand {
+                keys: keymap.chord(shortcut).map(ToString::to_string),
+                ..command(
+                    format!("{SHORTCUT_PREFIX}{}", shortcut.name()),
+                    shortcut.label(),
+                    view_group.clone(),
+                )
+            }),
+    );
+    let anime_group = tr("command-group-anime");
+    commands.extend(
+        graph
+            .nodes
+            .iter()
+            .filter(|node| node.node_type == NodeType::Anime && !node.hidden)
+            .map(|node| PaletteCommand {
+                searched_only: true,
+                ..command(node.id.clone(), node.label.clone(), anime_group.clone())
+            }),
+    );
+    commands
+}
@@ -0,0 +1,328 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use wasiw_core::moods::MoodTable;
+use wasiw_core::neighbors;
+use wasiw_core::sampling::Sampling;
+use wasiw_core::titles::TitleForm;
+use wasiw_core::{GraphMode, LayoutKind, MergeStrategy};
+use wasiw_ui::canvas::MAX_RENDERED_EDGES;
+use wasiw_ui::i18n::Locale;
+use wasiw_ui::keymap::Shortcut;
+use wasiw_ui::theme::{Palette, ThemePreference, UiScale};
+
+use crate::notifications::NotificationSettings;
+use crate::ollama::OllamaSettings;
+
+pub const APP_DIR_NAME: &str = "what-anime-should-i-watch";
+const CONFIG_FILE_NAME: &str = "config.toml";
+const MAX_RECENT_FILES: usize = 8;
+const MAX_RECENT_COMMANDS: usize = 10;
+
+/// User preferences persisted as TOML in the platform config directory.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub theme: ThemePreference,
+    /// Size of text, controls and spacing.
+    pub ui_scale: UiScale,
+    /// Interface language; unset follows the system's.
+    pub language: Option<Locale>,
+    /// Which form of anime titles to show.
+    pub titles: TitleSettings,
+    /// Name of the active palette, built-in or user-defined.
+    pub palette: Option<String>,
+    /// User-defined palettes offered alongside the built-in ones.
+    pub palettes: Vec<Palette>,
+    /// Recently opened datasets and exports, most recent first.
+    pub recent_files: Vec<PathBuf>,
+    /// Ids of the commands last run from the command palette, most recent first.
+    pub recent_commands: Vec<String>,
+    /// Chords rebinding keyboard shortcuts, e.g. `undo = "Ctrl+U"`; the rest keep their
+    /// defaults.
+    pub keymap: BTreeMap<Shortcut, String>,
+    /// Dataset opened at startup; unset opens the most recently opened one.
+    pub dataset: Option<PathBuf>,
+    /// Layout the graph opens with.
+    pub layout: LayoutKind,
+    /// Shuffles node order within the layout; 0 keeps dataset order.
+    pub layout_seed: u64,
+    /// Edges the canvas draws at most; unset uses the built-in cap.
+    pub max_rendered_edges: Option<usize>,
+    /// Similarity edges kept per anime; unset uses the built-in number.
+    pub similar_neighbors: Option<usize>,
+    /// Memory the app tries to stay under, in megabytes; unset or 0 has no budget.
+    pub memory_budget_mb: Option<u64>,
+    /// Start every launch fresh instead of restoring the last session.
+    pub fresh_start: bool,
+    /// Show the graph as ranked lists of nodes and their connections instead of drawing it,
+    /// for screen readers.
+    pub text_view: bool,
+    /// Draw sequel, prequel and side-story relations over the graph as arrows.
+    pub show_relations: bool,
+    /// Show a tray icon with quick actions, and keep running in it when the window is closed.
+    pub tray: bool,
+    /// Desktop notifications for aired episodes and strong seasonal matches.
+    pub notifications: NotificationSettings,
+    /// The moods offered as filters over recommendations, and the tags each stands for.
+    pub moods: MoodTable,
+    /// Subsample the graph is built from, kept so large corpora open sampled next time.
+    pub sampling: Sampling,
+    /// What the graph's nodes are: users and anime, or genres, studios or directors.
+    pub graph_mode: GraphMode,
+    /// anime-offline-database file used to map AniList and Kitsu ids onto the dataset's ids.
+    pub id_map: Option<PathBuf>,
+    /// How an anime a user rated more than once in a loaded file is resolved.
+    pub duplicates: MergeStrategy,
+    /// Client id of the user's own MyAnimeList API client, for pushing ratings back.
+    pub mal_client_id: Option<String>,
+    /// Client id of the user's own AniList API client, for pushing ratings back.
+    pub anilist_client_id: Option<String>,
+    /// Two-letter region code, e.g. `US`, recommendations' streaming services are looked up
+    /// for. Unset turns the lookup off.
+    pub streaming_region: Option<String>,
+    /// TMDB API read access token for region-specific streaming availability.
+    pub tmdb_token: Option<String>,
+    /// Local model for recommendation blurbs; unset leaves them off.
+    pub ollama: Option<OllamaSettings>,
+    /// Directory with the sentence encoder's `model.onnx` and `tokenizer.json`, when it isn't
+    /// the bundled one. Used by builds with the `semantic` feature.
+    pub semantic_model: Option<PathBuf>,
+}
+
+/// The title form overall, and for the views that choose their own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TitleSettings {
+    pub form: TitleForm,
+    /// Graph labels and the search over them.
+    pub graph: Option<TitleForm>,
+    /// Recommendation lists, here and in the details card.
+    pub lists: Option<TitleForm>,
+}
+
+impl TitleSettings {
+    pub fn graph(&self) -> TitleForm {
+        self.graph.unwrap_or(self.form)
+    }
+
+    pub fn lists(&self) -> TitleForm {
+        self.lists.unwrap_or(self.form)
+    }
+}
+
+impl AppConfig {
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(APP_DIR_NAME).join(CONFIG_FILE_NAME))
+    }
+
+    /// Reads the config file, falling back to defaults when it is missing or unreadable.
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Moves `path` to the front of the recent-files list, dropping the oldest entries.
+    pub fn remember_file(&mut self, path: &Path) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.recent_files.retain(|existing| *existing != path);
+        self.recent_files.insert(0, path);
+        self.recent_files.truncate(MAX_RECENT_FILES);
+    }
+
+    /// Moves command `id` to the front of the recent-commands list.
+    pub fn remember_command(&mut self, id: &str) {
+        self.recent_commands.retain(|existing| existing != id);
+        self.recent_commands.insert(0, id.to_string());
+        self.recent_commands.truncate(MAX_RECENT_COMMANDS);
+    }
+
+    /// The interface language to use.
+    pub fn locale(&self) -> Locale {
+        self.language.unwrap_or_else(|| {
+            ["LC_ALL", "LC_MESSAGES", "LANG"]
+                .into_iter()
+                .find_map(|name| std::env::var(name).ok().filter(|tag| !tag.is_empty()))
+                .map_or_else(Locale::default, |tag| Locale::from_tag(&tag))
+        })
+    }
+
+    /// The edge cap to render with.
+    pub fn max_rendered_edges(&self) -> usize {
+        self.max_rendered_edges.unwrap_or(MAX_RENDERED_EDGES)
+    }
+
+    /// Similarity edges to keep per anime.
+    pub fn similar_neighbors(&self) -> usize {
+        self.similar_neighbors
+            .unwrap_or(neighbors::DEFAULT_NEIGHBORS)
+    }
+
+    /// The memory budget in bytes, if there is one.
+    pub fn memory_budget(&self) -> Option<usize> {
+        self.memory_budget_mb
+            .filter(|&mb| mb > 0)
+            .map(|mb| (mb as usize).saturating_mul(1024 * 1024))
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        let content = toml::to_string_pretty(self).map_err(io::Error::other)?;
+        write_atomic(&path, content.as_bytes())
+    }
+}
+
+/// `name` in the app's folder of the platform data directory, where caches, profiles and
+/// window state are kept.
+pub fn data_file(name: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_DIR_NAME).join(name))
+}
+
+/// The contents of data file `name`, or `None` when it is missing or unreadable.
+pub fn read_data_file(name: &str) -> Option<Vec<u8>> {
+    fs::read(data_file(name)?).ok()
+}
+
+/// Replaces data file `name` with `contents`; see [`write_atomic`].
+pub fn write_data_file(name: &str, contents: &[u8]) -> io::Result<()> {
+    let path = data_file(name)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+    write_atomic(&path, contents)
+}
+
+/// Writes `contents` to a temporary file next to `path` and renames it over `path`, so a crash
+/// mid-write leaves the previous file intact instead of a truncated one. Creates the parent
+/// directory.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    replace_file(path, contents, false)
+}
+
+/// [`write_atomic`] for secrets: on Unix the file ends up readable by the owner only, even when
+/// an older copy had looser permissions.
+pub fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    replace_file(path, contents, true)
+}
+
+fn replace_file(path: &Path, contents: &[u8], private: bool) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    let temporary = PathBuf::from(temporary);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    if private {
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    }
+    let mut file = options.open(&temporary)?;
+    // The mode only applies when the file is created, not to a temporary left by a crash.
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    fs::rename(&temporary, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hand_written_configs_fill_in_defaults_and_survive_a_save() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            theme = "dark"
+            ui_scale = "large"
+            language = "ja"
+            memory_budget_mb = 0
+
+            [titles]
+            form = "english"
+            graph = "native"
+
+            [keymap]
+            undo = "Ctrl+U"
+
+            [ollama]
+            model = "llama3.2"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.theme, ThemePreference::Dark);
+        assert_eq!(config.ui_scale, UiScale::Large);
+        assert_eq!(config.locale(), Locale::Japanese);
+        assert_eq!(config.titles.graph(), TitleForm::Native);
+        assert_eq!(config.titles.lists(), TitleForm::English);
+        assert_eq!(config.memory_budget(), None);
+        assert_eq!(config.keymap[&Shortcut::Undo], "Ctrl+U");
+        assert_eq!(
+            config.ollama.as_ref().unwrap().endpoint,
+            "http://127.0.0.1:11434"
+        );
+        assert_eq!(config.layout, LayoutKind::default());
+        assert_eq!(config.max_rendered_edges(), MAX_RENDERED_EDGES);
+        assert_eq!(config.similar_neighbors(), neighbors::DEFAULT_NEIGHBORS);
+        assert_eq!(config.moods, MoodTable::default());
+
+        let saved = toml::to_string_pretty(&config).unwrap();
+        assert_eq!(toml::from_str::<AppConfig>(&saved).unwrap(), config);
+
+        let budgeted = AppConfig {
+            memory_budget_mb: Some(64),
+            ..AppConfig::default()
+        };
+        assert_eq!(budgeted.memory_budget(), Some(64 * 1024 * 1024));
+    }
+
+    #[test]
+    fn recent_lists_put_the_latest_first_once() {
+        let mut config = AppConfig::default();
+        for id in 0..12 {
+            config.remember_command(&format!("command-{id}"));
+        }
+        config.remember_command("command-5");
+        assert_eq!(config.recent_commands.len(), MAX_RECENT_COMMANDS);
+        assert_eq!(
+            config.recent_commands[..3],
+            ["command-5", "command-11", "command-10"]
+        );
+
+        for name in ["a.json", "b.json", "a.json"] {
+            config.remember_file(Path::new(name));
+        }
+        assert_eq!(
+            config.recent_files,
+            [PathBuf::from("a.json"), PathBuf::from("b.json")]
+        );
+    }
+
+    #[test]
+    fn files_are_replaced_whole_and_secrets_kept_private() {
+        let dir = std::env::temp_dir().join(format!("wasiw-config-{}", std::process::id()));
+        let path = dir.join("nested").join("tokens.json");
+        write_atomic(&path, b"old").unwrap();
+        write_private(&path, b"new").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        assert!(!path.with_extension("json.tmp").exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
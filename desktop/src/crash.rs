@@ -0,0 +1,140 @@
+//! What happens when something goes wrong that the app didn't plan for. A panic while a
+//! component renders is caught by Dioxus and shown in the in-app error dialog, with the panic's
+//! message and location kept here for it; any other panic on the main thread (an event handler,
+//! startup) gets a native dialog before the app closes. A worker thread that panics is only
+//! logged, and whoever waits on it reports the failure. The dialogs name the dataset file that was
+//! being read, since a broken file is the likeliest cause, and offer to start fresh so the next
+//! launch doesn't open it again.
+
+use std::fmt;
+use std::panic::{self, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use dioxus::core::Runtime;
+use rfd::{MessageButtons, MessageDialog, MessageDialogResult, MessageLevel};
+
+use crate::config::AppConfig;
+use crate::session::Session;
+use crate::APP_TITLE;
+
+const START_FRESH: &str = "Start fresh next time";
+const QUIT: &str = "Quit";
+
+/// The dataset file read last, for the dialogs to name.
+static CURRENT_FILE: Mutex<Option<PathBuf>> = Mutex::new(None);
+/// The panic the in-app dialog is showing.
+static LAST_PANIC: Mutex<Option<PanicReport>> = Mutex::new(None);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PanicReport {
+    pub message: String,
+    /// Source file and line, e.g. `desktop/src/main.rs:120:5`.
+    pub location: Option<String>,
+}
+
+impl PanicReport {
+    fn new(info: &PanicHookInfo<'_>) -> Self {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown error".to_string());
+        Self {
+            message,
+            location: info.location().map(ToString::to_string),
+        }
+    }
+}
+
+impl fmt::Display for PanicReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{} (at {location})", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Installs the panic hook; call once, before launching.
+pub fn install() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let report = PanicReport::new(info);
+        tracing::error!(
+            location = report.location.as_deref(),
+            "panic: {}",
+            report.message
+        );
+        let rendering = Runtime::try_current().is_some_and(|runtime| runtime.vdom_is_rendering());
+        if rendering {
+            if let Ok(mut last) = LAST_PANIC.lock() {
+                *last = Some(report);
+            }
+            return;
+        }
+        default_hook(info);
+        if std::thread::current().name() == Some("main") {
+            show_native(&report);
+        }
+    }));
+}
+
+/// Remembers `path` as the file being read, until another is.
+pub fn note_file(path: &Path) {
+    if let Ok(mut current) = CURRENT_FILE.lock() {
+        *current = Some(path.to_path_buf());
+    }
+}
+
+pub fn current_file() -> Option<PathBuf> {
+    CURRENT_FILE.lock().ok().and_then(|current| current.clone())
+}
+
+pub fn last_panic() -> Option<PanicReport> {
+    LAST_PANIC.lock().ok().and_then(|last| last.clone())
+}
+
+pub fn clear_panic() {
+    if let Ok(mut last) = LAST_PANIC.lock() {
+        *last = None;
+    }
+}
+
+/// Forgets the saved session, and unpins and forgets the current file, so the next launch
+/// opens neither. Best effort: a failed save leaves the config as it was.
+pub fn start_fresh() {
+    let _ = Session::clear();
+    let Some(file) = current_file() else {
+        return;
+    };
+    let mut config = AppConfig::load();
+    if config.dataset.as_ref() == Some(&file) {
+        config.dataset = None;
+    }
+    config.recent_files.retain(|path| *path != file);
+    let _ = config.save();
+}
+
+/// Blocks on a native dialog describing `report`, then exits: a panic outside rendering leaves
+/// the app in a state it can't continue from.
+fn show_native(report: &PanicReport) {
+    let mut description = format!("The app ran into a problem and has to close.\n\n{report}");
+    if let Some(file) = current_file() {
+        description.push_str(&format!("\n\nLast file read: {}", file.display()));
+    }
+    let choice = MessageDialog::new()
+        .set_level(MessageLevel::Error)
+        .set_title(APP_TITLE)
+        .set_description(description)
+        .set_buttons(MessageButtons::OkCancelCustom(
+            START_FRESH.to_string(),
+            QUIT.to_string(),
+        ))
+        .show();
+    if choice == MessageDialogResult::Custom(START_FRESH.to_string()) {
+        start_fresh();
+    }
+    std::process::exit(1);
+}
@@ -0,0 +1,264 @@
+//! The metadata fetch queue: anime ids waiting for metadata, kept in `fetch-queue.json` in the
+//! platform data directory so a fetch interrupted by closing the app, a crash or cancelling it
+//! picks up where it stopped on the next launch. Ids that fail, alone or with their whole batch,
+//! wait out an exponential backoff before they are tried again, and are given up on after
+//! [`MAX_ATTEMPTS`]. Requests themselves are spaced by the metadata sources' rate limiters.
+
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+
+const QUEUE_FILE_NAME: &str = "fetch-queue.json";
+/// Tries per id before it is dropped from the queue.
+pub const MAX_ATTEMPTS: u32 = 6;
+const BASE_BACKOFF_SECS: u64 = 30;
+const MAX_BACKOFF_SECS: u64 = 60 * 60;
+/// Longest pause after whole batches failed in a row, e.g. while offline.
+const MAX_OUTAGE_SECS: u64 = 5 * 60;
+
+/// An id that failed, and when it may be tried again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Retry {
+    attempts: u32,
+    /// Seconds since the Unix epoch.
+    not_before: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FetchQueue {
+    /// Waiting ids, in the order they were queued.
+    pending: Vec<u32>,
+    retries: HashMap<u32, Retry>,
+    /// Fetched since the queue was last empty, for progress.
+    done: usize,
+    /// Given up on since the queue was last empty.
+    abandoned: usize,
+}
+
+impl FetchQueue {
+    /// Reads the queue, starting empty when it is missing or unreadable.
+    pub fn load() -> Self {
+        config::read_data_file(QUEUE_FILE_NAME)
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let content = serde_json::to_vec(self).map_err(io::Error::other)?;
+        config::write_data_file(QUEUE_FILE_NAME, &content)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn done(&self) -> usize {
+        self.done
+    }
+
+    pub fn abandoned(&self) -> usize {
+        self.abandoned
+    }
+
+    /// Queues the `ids` that aren't queued yet; returns how many were added.
+    pub fn enqueue(&mut self, ids: impl IntoIterator<Item = u32>) -> usize {
+        if self.pending.is_empty() {
+            self.done = 0;
+            self.abandoned = 0;
+        }
+        let before = self.pending.len();
+        let mut queued: HashSet<u32> = self.pending.iter().copied().collect();
+        self.pending
+            .extend(ids.into_iter().filter(|&id| queued.insert(id)));
+        self.pending.len() - before
+    }
+
+    /// Up to `limit` ids that are due now, oldest first.
+    pub fn next_batch(&self, limit: usize) -> Vec<u32> {
+        let now = unix_now();
+        self.pending
+            .iter()
+            .copied()
+            .filter(|id| {
+                self.retries
+                    .get(id)
+                    .is_none_or(|retry| retry.not_before <= now)
+            })
+            .take(limit)
+            .collect()
+    }
+
+    /// How long until the next backing-off id is due, when nothing is due now.
+    pub fn next_due(&self) -> Option<Duration> {
+        let now = unix_now();
+        self.pending
+            .iter()
+            .filter_map(|id| self.retries.get(id))
+            .map(|retry| retry.not_before.saturating_sub(now))
+            .min()
+            .map(Duration::from_secs)
+    }
+
+    /// Takes the fetched `ids` out of the queue.
+    pub fn complete(&mut self, ids: &[u32]) {
+        self.pending.retain(|id| !ids.contains(id));
+        for id in ids {
+            self.retries.remove(id);
+        }
+        self.done += ids.len();
+    }
+
+    /// Backs the failed `ids` off, doubling the wait each time, and drops those that have had
+    /// [`MAX_ATTEMPTS`].
+    pub fn fail(&mut self, ids: &[u32]) {
+        let now = unix_now();
+        let mut given_up = Vec::new();
+        for &id in ids {
+            let retry = self.retries.entry(id).or_insert(Retry {
+                attempts: 0,
+                not_before: now,
+            });
+            retry.attempts += 1;
+            if retry.attempts >= MAX_ATTEMPTS {
+                given_up.push(id);
+                continue;
+            }
+            let backoff = BASE_BACKOFF_SECS.saturating_mul(1 << (retry.attempts - 1));
+            retry.not_before = now + backoff.min(MAX_BACKOFF_SECS);
+        }
+        for id in &given_up {
+            self.retries.remove(id);
+        }
+        self.pending.retain(|id| !given_up.contains(id));
+        self.abandoned += given_up.len();
+    }
+
+    /// E.g. `120 of 4000 · 3 backing off`.
+    pub fn progress_line(&self) -> String {
+        let total = self.done + self.abandoned + self.pending.len();
+        let mut line = format!("{} of {total}", self.done);
+        let waiting = self.retries.len();
+        if waiting > 0 {
+            line.push_str(&format!(" · {waiting} backing off"));
+        }
+        if self.abandoned > 0 {
+            line.push_str(&format!(" · {} given up", self.abandoned));
+        }
+        line
+    }
+
+    /// Fetched so far as a fraction of everything queued since the queue was last empty.
+    pub fn fraction(&self) -> f32 {
+        let total = self.done + self.abandoned + self.pending.len();
+        if total == 0 {
+            1.0
+        } else {
+            (self.done + self.abandoned) as f32 / total as f32
+        }
+    }
+}
+
+/// How long to pause after `failures` whole batches in a row failed, doubling each time.
+pub fn outage_backoff(failures: u32) -> Duration {
+    let secs = BASE_BACKOFF_SECS.saturating_mul(1 << failures.saturating_sub(1).min(16));
+    Duration::from_secs(secs.min(MAX_OUTAGE_SECS))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seconds until `id` may be tried again.
+    fn wait(queue: &FetchQueue, id: u32) -> u64 {
+        queue.retries[&id].not_before.saturating_sub(unix_now())
+    }
+
+    #[test]
+    fn enqueue_skips_ids_already_waiting() {
+        let mut queue = FetchQueue::default();
+        assert_eq!(queue.enqueue([3, 1, 3, 2]), 3);
+        assert_eq!(queue.enqueue([2, 4]), 1);
+        assert_eq!(queue.next_batch(10), [3, 1, 2, 4]);
+        assert_eq!(queue.next_batch(2), [3, 1]);
+    }
+
+    #[test]
+    fn failed_ids_back_off_twice_as_long_each_time_then_are_given_up() {
+        let mut queue = FetchQueue::default();
+        queue.enqueue([1, 2]);
+        queue.fail(&[1]);
+        assert_eq!(queue.next_batch(10), [2]);
+        assert!((BASE_BACKOFF_SECS - 1..=BASE_BACKOFF_SECS).contains(&wait(&queue, 1)));
+        assert!(queue
+            .next_due()
+            .is_some_and(|due| due <= Duration::from_secs(30)));
+        queue.fail(&[1]);
+        assert!((2 * BASE_BACKOFF_SECS - 1..=2 * BASE_BACKOFF_SECS).contains(&wait(&queue, 1)));
+
+        for _ in 2..MAX_ATTEMPTS {
+            assert_eq!(queue.abandoned(), 0);
+            queue.fail(&[1]);
+        }
+        assert_eq!(queue.abandoned(), 1);
+        assert_eq!(queue.next_batch(10), [2]);
+        assert!(queue.retries.is_empty());
+        assert_eq!(queue.next_due(), None);
+
+        assert_eq!(outage_backoff(1), Duration::from_secs(BASE_BACKOFF_SECS));
+        assert_eq!(
+            outage_backoff(2),
+            Duration::from_secs(2 * BASE_BACKOFF_SECS)
+        );
+        assert_eq!(outage_backoff(40), Duration::from_secs(MAX_OUTAGE_SECS));
+    }
+
+    #[test]
+    fn progress_counts_everything_queued_since_the_queue_was_empty() {
+        let mut queue = FetchQueue::default();
+        assert_eq!(queue.fraction(), 1.0);
+        queue.enqueue([1, 2, 3, 4]);
+        queue.complete(&[1]);
+        queue.fail(&[2]);
+        assert_eq!(queue.progress_line(), "1 of 4 · 1 backing off");
+        assert_eq!(queue.fraction(), 0.25);
+        for _ in 1..MAX_ATTEMPTS {
+            queue.fail(&[2]);
+        }
+        queue.complete(&[3, 4]);
+        assert_eq!(queue.progress_line(), "3 of 4 · 1 given up");
+        assert!(queue.is_empty());
+        assert_eq!(queue.fraction(), 1.0);
+
+        queue.enqueue([5]);
+        assert_eq!((queue.done(), queue.abandoned()), (0, 0));
+        assert_eq!(queue.progress_line(), "0 of 1");
+    }
+
+    #[test]
+    fn the_queue_survives_a_round_trip_through_its_file_format() {
+        let mut queue = FetchQueue::default();
+        queue.enqueue([7, 8, 9]);
+        queue.complete(&[8]);
+        queue.fail(&[9]);
+        let content = serde_json::to_vec(&queue).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<FetchQueue>(&content).unwrap(),
+            queue
+        );
+        // Fields missing from an older file start empty.
+        let old: FetchQueue = serde_json::from_str(r#"{"pending":[1,2]}"#).unwrap();
+        assert_eq!(old.next_batch(10), [1, 2]);
+        assert_eq!(old.done(), 0);
+    }
+}
@@ -0,0 +1,37 @@
+//! The startup graph, kept in `graph-cache.bin` in the platform data directory so that
+//! reopening the same dataset with the same settings skips building it. Only the most recent
+//! graph is kept; any change to the data or the settings builds afresh and replaces it.
+
+use wasiw_core::{graph_cache, Dataset, GraphMode, GraphModel, GraphSettings};
+
+use crate::config;
+
+const CACHE_FILE_NAME: &str = "graph-cache.bin";
+
+/// The graph `settings` build from `dataset`: the cached one when it was built from the same
+/// data with the same settings, otherwise a new one, which is cached in the background. Facet
+/// graphs depend on fetched metadata as well, so they are always built.
+pub fn build(
+    dataset: &Dataset,
+    settings: GraphSettings,
+    facets: impl Fn(u32, GraphMode) -> Vec<String>,
+) -> GraphModel {
+    if settings.mode != GraphMode::Ratings {
+        return settings.build_with_facets(dataset.clone(), facets, |_| {});
+    }
+    let key = graph_cache::key(dataset, &settings);
+    let cached = config::read_data_file(CACHE_FILE_NAME)
+        .and_then(|bytes| graph_cache::decode(&bytes, key, settings));
+    if let Some(graph) = cached {
+        tracing::info!(nodes = graph.nodes.len(), "opened the cached graph");
+        return graph;
+    }
+    let graph = settings.build(dataset.clone(), |_| {});
+    let bytes = graph_cache::encode(&graph, key);
+    std::thread::spawn(move || {
+        if let Err(err) = config::write_data_file(CACHE_FILE_NAME, &bytes) {
+            tracing::warn!("could not cache the graph: {err}");
+        }
+    });
+    graph
+}
@@ -0,0 +1,213 @@
+//! Cross-site id mapping from the [anime-offline-database], so the same show imported from
+//! MyAnimeList, AniList or Kitsu resolves to one anime id.
+//!
+//! The dataset is keyed by MAL ids, so a show with a MAL entry keeps its MAL id. Shows MAL
+//! doesn't list get a stable id derived from another site's id, offset above any MAL id.
+//!
+//! [anime-offline-database]: https://github.com/manami-project/anime-offline-database
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Deserialize;
+use wasiw_core::compression;
+
+use crate::import::ImportError;
+
+/// Ids at or above this are derived from a non-MAL site; MAL ids are far below it.
+const UNMAPPED_ID_BASE: u32 = 1 << 30;
+/// Width of each site's block above `UNMAPPED_ID_BASE`.
+const SITE_BLOCK: u32 = 1 << 28;
+
+/// Whether `anime_id` was derived from another site's id rather than being a MAL id.
+pub fn is_derived(anime_id: u32) -> bool {
+    anime_id >= UNMAPPED_ID_BASE
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Site {
+    MyAnimeList,
+    AniList,
+    Kitsu,
+    AniDb,
+}
+
+impl Site {
+    /// Sites in order of preference for deriving an id when there is no MAL entry.
+    const FALLBACKS: [Site; 3] = [Self::AniList, Self::Kitsu, Self::AniDb];
+
+    /// Parses source URLs such as `https://anilist.co/anime/21`.
+    fn parse_source(url: &str) -> Option<(Site, u32)> {
+        let rest = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))?;
+        let (host, path) = rest.split_once('/')?;
+        let site = match host.trim_start_matches("www.") {
+            "myanimelist.net" => Self::MyAnimeList,
+            "anilist.co" => Self::AniList,
+            "kitsu.app" | "kitsu.io" => Self::Kitsu,
+            "anidb.net" => Self::AniDb,
+            _ => return None,
+        };
+        let id = path
+            .strip_prefix("anime/")?
+            .split('/')
+            .next()?
+            .parse()
+            .ok()?;
+        Some((site, id))
+    }
+
+    fn block(self) -> u32 {
+        match self {
+            Self::MyAnimeList | Self::AniList => 0,
+            Self::Kitsu => 1,
+            Self::AniDb => 2,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Database {
+    data: Vec<DatabaseEntry>,
+}
+
+#[derive(Deserialize)]
+struct DatabaseEntry {
+    sources: Vec<String>,
+    title: String,
+    #[serde(default)]
+    synonyms: Vec<String>,
+}
+
+pub struct IdMap {
+    ids: HashMap<(Site, u32), u32>,
+    /// Main title and synonyms for every mapped anime, main title first.
+    titles: Vec<(u32, String)>,
+    main_titles: HashMap<u32, usize>,
+}
+
+impl IdMap {
+    /// Reads `anime-offline-database(-minified).json`, optionally gzip- or zstd-compressed.
+    pub fn load(path: &Path) -> Result<Self, ImportError> {
+        let reader = compression::open(path, 256 * 1024)?;
+        let database: Database = serde_json::from_reader(reader)?;
+
+        let mut ids = HashMap::new();
+        let mut titles = Vec::new();
+        let mut main_titles = HashMap::new();
+        for entry in database.data {
+            let sources: Vec<(Site, u32)> = entry
+                .sources
+                .iter()
+                .filter_map(|url| Site::parse_source(url))
+                .collect();
+            let mal = sources
+                .iter()
+                .find(|(site, _)| *site == Site::MyAnimeList)
+                .map(|(_, id)| *id);
+            let derived = Site::FALLBACKS.iter().find_map(|fallback| {
+                sources
+                    .iter()
+                    .find(|(site, id)| site == fallback && *id < SITE_BLOCK)
+                    .map(|(site, id)| UNMAPPED_ID_BASE + site.block() * SITE_BLOCK + id)
+            });
+            let Some(anime_id) = mal.or(derived) else {
+                continue;
+            };
+            for source in sources {
+                ids.entry(source).or_insert(anime_id);
+            }
+            main_titles.entry(anime_id).or_insert(titles.len());
+            titles.push((anime_id, entry.title));
+            titles.extend(entry.synonyms.into_iter().map(|title| (anime_id, title)));
+        }
+        if ids.is_empty() {
+            return Err(ImportError::Empty);
+        }
+        Ok(Self {
+            ids,
+            titles,
+            main_titles,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.main_titles.len()
+    }
+
+    /// The dataset's anime id for a site's anime id.
+    pub fn resolve(&self, site: Site, id: u32) -> Option<u32> {
+        self.ids.get(&(site, id)).copied()
+    }
+
+    pub fn title(&self, anime_id: u32) -> Option<&str> {
+        let index = *self.main_titles.get(&anime_id)?;
+        Some(self.titles[index].1.as_str())
+    }
+
+    /// Every title and synonym, for matching shows known only by name.
+    pub fn titles(&self) -> impl Iterator<Item = (u32, &str)> {
+        self.titles
+            .iter()
+            .map(|(anime_id, title)| (*anime_id, title.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(json: &str) -> Result<IdMap, ImportError> {
+        let path = std::env::temp_dir().join(format!("wasiw-idmap-{}.json", std::process::id()));
+        std::fs::write(&path, json).unwrap();
+        let map = IdMap::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        map
+    }
+
+    #[test]
+    fn shows_resolve_to_their_mal_id_or_a_stable_derived_one() {
+        let map = load(
+            r#"{ "data": [
+                { "sources": ["https://myanimelist.net/anime/1", "https://anilist.co/anime/1",
+                              "https://kitsu.app/anime/1", "https://anidb.net/anime/23"],
+                  "title": "Cowboy Bebop", "synonyms": ["カウボーイビバップ"] },
+                { "sources": ["https://anilist.co/anime/500"], "title": "AniList only" },
+                { "sources": ["https://kitsu.io/anime/77/episodes"], "title": "Kitsu only" },
+                { "sources": ["https://example.com/anime/9"], "title": "Nowhere" }
+            ] }"#,
+        )
+        .unwrap();
+        assert_eq!(map.len(), 3);
+        for (site, id) in [
+            (Site::MyAnimeList, 1),
+            (Site::AniList, 1),
+            (Site::Kitsu, 1),
+            (Site::AniDb, 23),
+        ] {
+            assert_eq!(map.resolve(site, id), Some(1), "{site:?}");
+        }
+        assert!(!is_derived(1));
+
+        let anilist_only = map.resolve(Site::AniList, 500).unwrap();
+        let kitsu_only = map.resolve(Site::Kitsu, 77).unwrap();
+        assert!(is_derived(anilist_only) && is_derived(kitsu_only));
+        assert_ne!(anilist_only, kitsu_only);
+        assert_eq!(map.title(kitsu_only), Some("Kitsu only"));
+        assert_eq!(map.resolve(Site::AniList, 77), None);
+
+        assert_eq!(map.title(1), Some("Cowboy Bebop"));
+        let bebop: Vec<&str> = map
+            .titles()
+            .filter(|(anime_id, _)| *anime_id == 1)
+            .map(|(_, title)| title)
+            .collect();
+        assert_eq!(bebop, ["Cowboy Bebop", "カウボーイビバップ"]);
+
+        assert!(matches!(
+            load(r#"{ "data": [{ "sources": [], "title": "None" }] }"#),
+            Err(ImportError::Empty)
+        ));
+    }
+}
@@ -0,0 +1,212 @@
+//! AniList import through the public GraphQL API (no authentication needed for public lists).
+
+use serde::Deserialize;
+use serde_json::json;
+use wasiw_core::scale::ScoreScale;
+use wasiw_core::Rating;
+
+use super::{ImportError, ImportedProfile};
+use crate::idmap::{IdMap, Site};
+
+const ENDPOINT: &str = "https://graphql.anilist.co";
+
+const LIST_QUERY: &str = r#"
+query ($name: String) {
+  MediaListCollection(userName: $name, type: ANIME) {
+    lists {
+      entries {
+        score(format: POINT_100)
+        media {
+          id
+          idMal
+          title { romaji english }
+        }
+      }
+    }
+  }
+}
+"#;
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    data: Option<Data>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Data {
+    #[serde(rename = "MediaListCollection")]
+    collection: Option<Collection>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Collection {
+    lists: Vec<List>,
+}
+
+#[derive(Debug, Deserialize)]
+struct List {
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Entry {
+    score: f64,
+    media: Media,
+}
+
+#[derive(Debug, Deserialize)]
+struct Media {
+    id: u32,
+    #[serde(rename = "idMal")]
+    id_mal: Option<u32>,
+    title: Title,
+}
+
+#[derive(Debug, Deserialize)]
+struct Title {
+    romaji: Option<String>,
+    english: Option<String>,
+}
+
+/// Fetches a public AniList list by username.
+///
+/// Scores are requested on AniList's 100-point scale and converted so they line up with the
+/// corpus' 1–10 MAL scores. The corpus is keyed by MAL ids; entries AniList has no MAL id
+/// for are resolved through `ids` when an id map is loaded and skipped otherwise, as are
+/// unscored entries.
+pub async fn fetch_user(
+    client: &reqwest::Client,
+    username: &str,
+    ids: Option<&IdMap>,
+) -> Result<ImportedProfile, ImportError> {
+    let username = username.trim();
+    let response = client
+        .post(ENDPOINT)
+        .json(&json!({ "query": LIST_QUERY, "variables": { "name": username } }))
+        .send()
+        .await
+        .map_err(|err| ImportError::Http(err.to_string()))?;
+
+    let body: Response = response
+        .json()
+        .await
+        .map_err(|err| ImportError::Parse(err.to_string()))?;
+    profile(username, body, ids)
+}
+
+/// Converts a list response, the part of [`fetch_user`] after the request.
+fn profile(
+    username: &str,
+    body: Response,
+    ids: Option<&IdMap>,
+) -> Result<ImportedProfile, ImportError> {
+    if let Some(error) = body.errors.first() {
+        return Err(ImportError::Http(error.message.clone()));
+    }
+
+    let entries: Vec<Entry> = body
+        .data
+        .and_then(|data| data.collection)
+        .map(|collection| {
+            collection
+                .lists
+                .into_iter()
+                .flat_map(|list| list.entries)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut seen = std::collections::HashSet::new();
+    let total = entries.len();
+    let ratings: Vec<Rating> = entries
+        .into_iter()
+        .filter(|entry| entry.score > 0.0)
+        .filter_map(|entry| {
+            let anime_id = entry
+                .media
+                .id_mal
+                .or_else(|| ids?.resolve(Site::AniList, entry.media.id))?;
+            let title = entry
+                .media
+                .title
+                .english
+                .or(entry.media.title.romaji)
+                .unwrap_or_else(|| format!("MAL #{anime_id}"));
+            let score = ScoreScale::Hundred.to_ten(entry.score).ok()?;
+            // Custom lists repeat entries that already appear in a status list.
+            seen.insert(anime_id)
+                .then(|| Rating::new(anime_id, title, score))
+        })
+        .collect();
+
+    if ratings.is_empty() {
+        return Err(ImportError::Empty);
+    }
+
+    Ok(ImportedProfile {
+        name: format!("anilist/{username}"),
+        skipped: total - ratings.len(),
+        ratings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(json: &str) -> Response {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn lists_convert_to_mal_ids_on_the_ten_point_scale() {
+        let body = response(
+            r#"{"data": {"MediaListCollection": {"lists": [
+                {"entries": [
+                    {"score": 85, "media": {"id": 1, "idMal": 1,
+                        "title": {"romaji": "Cowboy Bebop", "english": "Cowboy Bebop"}}},
+                    {"score": 60, "media": {"id": 20, "idMal": 20,
+                        "title": {"romaji": "Naruto", "english": null}}},
+                    {"score": 0, "media": {"id": 21, "idMal": 21,
+                        "title": {"romaji": "One Piece", "english": "One Piece"}}},
+                    {"score": 70, "media": {"id": 999999, "idMal": null,
+                        "title": {"romaji": "AniList only", "english": null}}}
+                ]},
+                {"entries": [
+                    {"score": 85, "media": {"id": 1, "idMal": 1,
+                        "title": {"romaji": "Cowboy Bebop", "english": "Cowboy Bebop"}}}
+                ]}
+            ]}}}"#,
+        );
+        let profile = profile("spike", body, None).unwrap();
+        assert_eq!(profile.name, "anilist/spike");
+        let ratings: Vec<(u32, &str, f64)> = profile
+            .ratings
+            .iter()
+            .map(|rating| (rating.anime_id, rating.title.as_str(), rating.raw_score))
+            .collect();
+        assert_eq!(ratings, [(1, "Cowboy Bebop", 8.5), (20, "Naruto", 6.0)]);
+        assert_eq!(profile.skipped, 3);
+    }
+
+    #[test]
+    fn errors_and_empty_lists_are_reported() {
+        let refused = response(r#"{"data": null, "errors": [{"message": "User not found"}]}"#);
+        assert!(matches!(
+            profile("nobody", refused, None),
+            Err(ImportError::Http(message)) if message == "User not found"
+        ));
+        let empty = response(r#"{"data": {"MediaListCollection": {"lists": []}}}"#);
+        assert!(matches!(
+            profile("new", empty, None),
+            Err(ImportError::Empty)
+        ));
+    }
+}
@@ -0,0 +1,471 @@
+//! Generic CSV ratings import. The user maps columns in a dialog; rows that fail validation
+//! are skipped and reported by line number. Datasets can also be written back out as CSV.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+use wasiw_core::scale::ScoreScale;
+use wasiw_core::{compression, Dataset, Rating};
+
+use super::{ImportError, ImportedProfile};
+
+/// Columns of an exported dataset, one row per rating. Names follow the JSON schema.
+const EXPORT_HEADERS: [&str; 8] = [
+    "userId",
+    "animeId",
+    "title",
+    "rawScore",
+    "normalizedScore",
+    "status",
+    "updatedAt",
+    "genres",
+];
+
+/// Rows shown in the mapping dialog's preview.
+pub const PREVIEW_ROWS: usize = 5;
+
+/// Statuses meaning the user has not watched the show yet; their scores are placeholders.
+const UNWATCHED_STATUSES: [&str; 5] = [
+    "plan to watch",
+    "plan_to_watch",
+    "planning",
+    "planned",
+    "ptw",
+];
+
+/// A parsed CSV file waiting for its columns to be mapped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvTable {
+    pub name: String,
+    pub headers: Vec<String>,
+    /// Records with the 1-based file line each started on.
+    pub rows: Vec<(usize, Vec<String>)>,
+}
+
+impl CsvTable {
+    pub fn preview(&self) -> Vec<Vec<String>> {
+        self.rows
+            .iter()
+            .take(PREVIEW_ROWS)
+            .map(|(_, row)| row.clone())
+            .collect()
+    }
+}
+
+/// How the anime column identifies a show.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AnimeKey {
+    /// MyAnimeList anime id.
+    #[default]
+    Id,
+    /// Title, matched case-insensitively against titles already in the corpus.
+    Title,
+}
+
+impl AnimeKey {
+    pub const ALL: [AnimeKey; 2] = [Self::Id, Self::Title];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Id => "MyAnimeList id",
+            Self::Title => "Title",
+        }
+    }
+}
+
+/// Column assignment chosen in the mapping dialog. Columns are header indices.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColumnMapping {
+    pub anime: Option<usize>,
+    pub key: AnimeKey,
+    pub score: Option<usize>,
+    /// The file's rating scale; scores are rescaled onto 1–10.
+    pub scale: ScoreScale,
+    pub status: Option<usize>,
+    /// When a show appears more than once, the row with the latest date wins.
+    pub date: Option<usize>,
+}
+
+impl ColumnMapping {
+    /// Best guess from common header names, so typical exports need no changes.
+    pub fn guess(headers: &[String]) -> Self {
+        let find = |names: &[&str]| {
+            headers.iter().position(|header| {
+                let header = header.trim().to_lowercase();
+                names.iter().any(|name| header == *name)
+            })
+        };
+        let id = find(&["anime_id", "animeid", "mal_id", "id", "series_animedb_id"]);
+        let title = find(&["title", "anime_title", "name", "series_title"]);
+        Self {
+            anime: id.or(title),
+            key: if id.is_some() || title.is_none() {
+                AnimeKey::Id
+            } else {
+                AnimeKey::Title
+            },
+            score: find(&["score", "rating", "my_score", "rawscore"]),
+            scale: ScoreScale::Ten,
+            status: find(&["status", "my_status", "watch_status"]),
+            date: find(&[
+                "date",
+                "updated_at",
+                "updatedat",
+                "finished",
+                "my_finish_date",
+                "watched",
+            ]),
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.anime.is_some() && self.score.is_some()
+    }
+}
+
+/// A row that was not imported and why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedLine {
+    pub line: usize,
+    pub reason: String,
+}
+
+pub fn read_file(path: &Path) -> Result<CsvTable, ImportError> {
+    let file = compression::open(path, 64 * 1024)?;
+    let mut reader = ::csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(::csv::Trim::All)
+        .from_reader(file);
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|err| ImportError::Parse(err.to_string()))?
+        .iter()
+        .map(str::to_string)
+        .collect();
+    if headers.is_empty() {
+        return Err(ImportError::Empty);
+    }
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|err| ImportError::Parse(err.to_string()))?;
+        let line = record.position().map_or(0, |p| p.line() as usize);
+        rows.push((line, record.iter().map(str::to_string).collect()));
+    }
+    if rows.is_empty() {
+        return Err(ImportError::Empty);
+    }
+
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "ratings".to_string());
+    Ok(CsvTable {
+        name,
+        headers,
+        rows,
+    })
+}
+
+/// Lower-cased, whitespace-collapsed title used as the lookup key for `AnimeKey::Title`.
+fn title_key(title: &str) -> String {
+    title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Applies `mapping` to every row. `known_titles` holds the corpus' titles by anime id; it
+/// names id-keyed rows and resolves title-keyed ones.
+pub fn import(
+    table: &CsvTable,
+    mapping: ColumnMapping,
+    known_titles: &HashMap<u32, String>,
+) -> Result<(ImportedProfile, Vec<SkippedLine>), ImportError> {
+    let (Some(anime_column), Some(score_column)) = (mapping.anime, mapping.score) else {
+        return Err(ImportError::Parse(
+            "map both an anime column and a score column".to_string(),
+        ));
+    };
+
+    let by_title: HashMap<String, u32> = known_titles
+        .iter()
+        .map(|(id, title)| (title_key(title), *id))
+        .collect();
+    let mut skipped = Vec::new();
+    // Anime id -> (date, line, rating); the latest dated row for an anime wins.
+    let mut kept: HashMap<u32, (String, usize, Rating)> = HashMap::new();
+    let mut order = Vec::new();
+
+    for (line, row) in &table.rows {
+        let skip = |reason: String| SkippedLine {
+            line: *line,
+            reason,
+        };
+        let cell = |column: usize| row.get(column).map(String::as_str).unwrap_or("");
+
+        if let Some(status) = mapping.status {
+            if UNWATCHED_STATUSES.contains(&cell(status).to_lowercase().as_str()) {
+                skipped.push(skip(format!("status \"{}\" is not watched", cell(status))));
+                continue;
+            }
+        }
+
+        let anime = cell(anime_column);
+        let resolved = match mapping.key {
+            AnimeKey::Id => anime
+                .parse::<u32>()
+                .ok()
+                .filter(|id| *id > 0)
+                .ok_or_else(|| format!("\"{anime}\" is not a MyAnimeList id")),
+            AnimeKey::Title if anime.is_empty() => Err("missing title".to_string()),
+            AnimeKey::Title => by_title
+                .get(&title_key(anime))
+                .copied()
+                .ok_or_else(|| format!("no anime titled \"{anime}\" in the dataset")),
+        };
+        let anime_id = match resolved {
+            Ok(found) => found,
+            Err(reason) => {
+                skipped.push(skip(reason));
+                continue;
+            }
+        };
+
+        let raw_score = match parse_score(cell(score_column), mapping.scale) {
+            Ok(score) => score,
+            Err(reason) => {
+                skipped.push(skip(reason));
+                continue;
+            }
+        };
+
+        let date = match mapping.date.map(cell) {
+            None | Some("") => String::new(),
+            Some(value) => match parse_date(value) {
+                Some(date) => date,
+                None => {
+                    skipped.push(skip(format!("\"{value}\" is not a date (YYYY-MM-DD)")));
+                    continue;
+                }
+            },
+        };
+
+        let title = known_titles
+            .get(&anime_id)
+            .cloned()
+            .unwrap_or_else(|| format!("MAL #{anime_id}"));
+        let mut rating = Rating::new(anime_id, title, raw_score);
+        rating.status = mapping
+            .status
+            .map(|column| cell(column).to_lowercase())
+            .filter(|status| !status.is_empty());
+        rating.updated_at = (!date.is_empty()).then(|| date.clone());
+        match kept.get(&anime_id) {
+            Some((previous, _, _)) if *previous > date => {
+                skipped.push(skip(format!("older duplicate of anime {anime_id}")));
+            }
+            Some(&(_, previous_line, _)) => {
+                skipped.push(SkippedLine {
+                    line: previous_line,
+                    reason: format!("superseded duplicate of anime {anime_id}"),
+                });
+                kept.insert(anime_id, (date, *line, rating));
+            }
+            None => {
+                order.push(anime_id);
+                kept.insert(anime_id, (date, *line, rating));
+            }
+        }
+    }
+
+    let ratings: Vec<Rating> = order
+        .into_iter()
+        .filter_map(|id| kept.remove(&id).map(|(_, _, rating)| rating))
+        .collect();
+    if ratings.is_empty() {
+        return Err(ImportError::Empty);
+    }
+
+    skipped.sort_by_key(|skipped| skipped.line);
+    Ok((
+        ImportedProfile {
+            name: format!("csv/{}", table.name),
+            skipped: skipped.len(),
+            ratings,
+        },
+        skipped,
+    ))
+}
+
+/// Parses scores like `8`, `8.5` or `8/10`, or `up`/`down` on the thumbs scale, and rescales
+/// them onto 1–10. Zero means unscored, except for a thumbs-down.
+fn parse_score(value: &str, scale: ScoreScale) -> Result<f64, String> {
+    if scale == ScoreScale::Thumbs {
+        let thumbs = ScoreScale::parse_thumbs(value)
+            .ok_or_else(|| format!("\"{value}\" is not thumbs up or down"))?;
+        return scale.to_ten(thumbs);
+    }
+    let number = value.split('/').next().unwrap_or("").trim();
+    let score: f64 = number
+        .parse()
+        .map_err(|_| format!("score \"{value}\" is not a number"))?;
+    if score == 0.0 {
+        return Err("unscored".to_string());
+    }
+    scale.to_ten(score)
+}
+
+/// Accepts `YYYY-MM-DD`, optionally followed by a time, and returns the date part.
+fn parse_date(value: &str) -> Option<String> {
+    let date = value.get(..10)?;
+    let bytes = date.as_bytes();
+    let digits = |range: std::ops::Range<usize>| bytes[range].iter().all(u8::is_ascii_digit);
+    let shaped =
+        digits(0..4) && bytes[4] == b'-' && digits(5..7) && bytes[7] == b'-' && digits(8..10);
+    if !shaped {
+        return None;
+    }
+    let month: u32 = date[5..7].parse().ok()?;
+    let day: u32 = date[8..10].parse().ok()?;
+    ((1..=12).contains(&month) && (1..=31).contains(&day)).then(|| date.to_string())
+}
+
+/// Writes every rating of `dataset` as a CSV row; genres are joined with `|`.
+pub fn write(writer: &mut dyn Write, dataset: &Dataset) -> io::Result<()> {
+    let mut csv = ::csv::Writer::from_writer(writer);
+    csv.write_record(EXPORT_HEADERS)?;
+    for user in &dataset.users {
+        for rating in &user.ratings {
+            csv.write_record([
+                user.user_id.as_str(),
+                &rating.anime_id.to_string(),
+                &rating.title,
+                &rating.raw_score.to_string(),
+                &rating.normalized_score.to_string(),
+                rating.status.as_deref().unwrap_or(""),
+                rating.updated_at.as_deref().unwrap_or(""),
+                &rating.genres.join("|"),
+            ])?;
+        }
+    }
+    csv.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use wasiw_core::UserRatings;
+
+    use super::*;
+
+    fn table(headers: &[&str], rows: &[&[&str]]) -> CsvTable {
+        CsvTable {
+            name: "list".to_string(),
+            headers: headers.iter().map(|header| header.to_string()).collect(),
+            rows: rows
+                .iter()
+                .enumerate()
+                .map(|(index, row)| (index + 2, row.iter().map(|cell| cell.to_string()).collect()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn mappings_are_guessed_from_common_headers() {
+        let headers = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+        let mal =
+            ColumnMapping::guess(&headers(&["series_animedb_id", "series_title", "my_score"]));
+        assert_eq!(
+            (mal.anime, mal.key, mal.score),
+            (Some(0), AnimeKey::Id, Some(2))
+        );
+        let titled = ColumnMapping::guess(&headers(&["Name", "Rating", "Watched"]));
+        assert_eq!(
+            (titled.anime, titled.key, titled.score, titled.date),
+            (Some(0), AnimeKey::Title, Some(1), Some(2))
+        );
+        assert!(!ColumnMapping::guess(&headers(&["show", "stars"])).is_complete());
+    }
+
+    #[test]
+    fn rows_are_validated_rescaled_and_deduplicated_by_date() {
+        let table = table(
+            &["title", "score", "status", "date"],
+            &[
+                &["cowboy  BEBOP", "80", "Completed", "2020-01-01"],
+                &["Trigun", "60", "plan to watch", ""],
+                &["Unknown Show", "70", "completed", ""],
+                &["Cowboy Bebop", "90/100", "completed", "2023-05-01T10:00"],
+                &["Cowboy Bebop", "50", "completed", "2021-01-01"],
+                &["Monster", "abc", "completed", ""],
+                &["Monster", "0", "completed", ""],
+                &["Monster", "100", "completed", "yesterday"],
+            ],
+        );
+        let known = HashMap::from([
+            (1, "Cowboy Bebop".to_string()),
+            (6, "Trigun".to_string()),
+            (19, "Monster".to_string()),
+        ]);
+        let mapping = ColumnMapping {
+            scale: ScoreScale::Hundred,
+            ..ColumnMapping::guess(&table.headers)
+        };
+        let (profile, skipped) = import(&table, mapping, &known).unwrap();
+        assert_eq!(profile.name, "csv/list");
+        assert_eq!(profile.ratings.len(), 1);
+        let rating = &profile.ratings[0];
+        assert_eq!((rating.anime_id, rating.raw_score), (1, 9.0));
+        assert_eq!(rating.updated_at.as_deref(), Some("2023-05-01"));
+        assert_eq!(rating.status.as_deref(), Some("completed"));
+        let lines: Vec<usize> = skipped.iter().map(|skipped| skipped.line).collect();
+        assert_eq!(lines, [2, 3, 4, 6, 7, 8, 9]);
+        assert_eq!(profile.skipped, 7);
+
+        let unmapped = ColumnMapping {
+            score: None,
+            ..mapping
+        };
+        assert!(matches!(
+            import(&table, unmapped, &known),
+            Err(ImportError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn exported_datasets_import_back() {
+        let mut rating = Rating::new(1, "Cowboy Bebop, \"the\" series".to_string(), 9.0);
+        rating.genres = vec!["Action".to_string(), "Sci-Fi".to_string()];
+        let dataset = Dataset::new(vec![UserRatings {
+            user_id: "a".to_string(),
+            ratings: vec![rating, Rating::new(6, "Trigun".to_string(), 7.0)],
+        }]);
+        let path = std::env::temp_dir().join(format!("wasiw-csv-{}.csv", std::process::id()));
+        let mut out = Vec::new();
+        write(&mut out, &dataset).unwrap();
+        std::fs::write(&path, &out).unwrap();
+
+        let table = read_file(&path).unwrap();
+        assert_eq!(table.headers, EXPORT_HEADERS);
+        assert_eq!(table.rows[0].1[2], "Cowboy Bebop, \"the\" series");
+        assert_eq!(table.rows[0].1[7], "Action|Sci-Fi");
+        let mapping = ColumnMapping::guess(&table.headers);
+        let known = dataset.titles();
+        let (profile, skipped) = import(&table, mapping, &known).unwrap();
+        assert!(skipped.is_empty());
+        let ratings: Vec<(u32, f64)> = profile
+            .ratings
+            .iter()
+            .map(|rating| (rating.anime_id, rating.raw_score))
+            .collect();
+        assert_eq!(ratings, [(1, 9.0), (6, 7.0)]);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
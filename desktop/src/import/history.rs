@@ -0,0 +1,317 @@
+//! Viewing-history imports (Netflix, Crunchyroll). Histories list what was watched, not how
+//! much it was liked, so each show becomes an implicit positive rating that grows with the
+//! number of episodes finished. Titles are matched to anime already in the dataset and, when an
+//! id map is loaded, to its titles and synonyms, which include English names.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde_json::Value;
+use wasiw_core::{compression, Rating};
+
+use super::titles::TitleMatcher;
+use super::{ImportError, ImportedProfile};
+use crate::idmap::IdMap;
+
+/// Parts of a Netflix title that start the episode description, e.g. `Season 2`.
+const EPISODE_MARKERS: [&str; 6] = [
+    "season",
+    "part",
+    "episode",
+    "chapter",
+    "limited series",
+    "collection",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistorySource {
+    Netflix,
+    Crunchyroll,
+}
+
+impl HistorySource {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Netflix => "netflix",
+            Self::Crunchyroll => "crunchyroll",
+        }
+    }
+}
+
+/// One history entry before matching.
+struct Watch {
+    /// Titles to try, most specific first.
+    candidates: Vec<String>,
+    date: Option<String>,
+    finished: bool,
+}
+
+/// Implicit score for a show: a single finished episode or film is a mild positive, a whole
+/// season a strong one.
+fn implicit_score(episodes: usize) -> f64 {
+    match episodes {
+        0 | 1 => 6.0,
+        2..=3 => 7.0,
+        4..=11 => 8.0,
+        _ => 9.0,
+    }
+}
+
+/// Reads a Netflix `ViewingActivity`/`NetflixViewingHistory.csv` or a Crunchyroll history
+/// (watch-history JSON or CSV) and returns the profile plus the titles that matched nothing.
+pub fn import_file(
+    path: &Path,
+    known_titles: &HashMap<u32, String>,
+    ids: Option<&IdMap>,
+) -> Result<(ImportedProfile, Vec<String>), ImportError> {
+    let content = compression::read_to_string(path)?;
+    let (source, watches) = if content.trim_start().starts_with(['{', '[']) {
+        (HistorySource::Crunchyroll, crunchyroll_json(&content)?)
+    } else {
+        read_csv(&content)?
+    };
+
+    let matcher = TitleMatcher::new(
+        known_titles
+            .iter()
+            .map(|(anime_id, title)| (*anime_id, title.as_str()))
+            .chain(ids.into_iter().flat_map(IdMap::titles)),
+    );
+    // Anime id -> (finished episodes, latest date).
+    let mut shows: HashMap<u32, (usize, Option<String>)> = HashMap::new();
+    let mut order = Vec::new();
+    let mut unmatched = Vec::new();
+    let mut skipped = 0;
+    // Histories repeat a show once per episode; match each distinct title only once.
+    let mut matched: HashMap<Vec<String>, Option<u32>> = HashMap::new();
+    for watch in watches {
+        let found = *matched
+            .entry(watch.candidates.clone())
+            .or_insert_with(|| matcher.find(&watch.candidates));
+        let Some(anime_id) = found else {
+            skipped += 1;
+            if let Some(title) = watch.candidates.first() {
+                if !unmatched.contains(title) {
+                    unmatched.push(title.clone());
+                }
+            }
+            continue;
+        };
+        let show = shows.entry(anime_id).or_insert_with(|| {
+            order.push(anime_id);
+            (0, None)
+        });
+        if watch.finished {
+            show.0 += 1;
+        }
+        if watch.date > show.1 {
+            show.1 = watch.date;
+        }
+    }
+
+    let ratings: Vec<Rating> = order
+        .into_iter()
+        .filter_map(|anime_id| {
+            let (episodes, date) = shows.remove(&anime_id)?;
+            if episodes == 0 {
+                // Started but never finished anything: not a signal either way.
+                return None;
+            }
+            let title = known_titles
+                .get(&anime_id)
+                .cloned()
+                .or_else(|| Some(ids?.title(anime_id)?.to_string()))
+                .unwrap_or_default();
+            let mut rating = Rating::new(anime_id, title, implicit_score(episodes));
+            rating.status = Some("watched".to_string());
+            rating.updated_at = date;
+            Some(rating)
+        })
+        .collect();
+    if ratings.is_empty() {
+        return Err(ImportError::Empty);
+    }
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "history".to_string());
+    Ok((
+        ImportedProfile {
+            name: format!("{}/{stem}", source.name()),
+            ratings,
+            skipped,
+        },
+        unmatched,
+    ))
+}
+
+fn read_csv(content: &str) -> Result<(HistorySource, Vec<Watch>), ImportError> {
+    let mut reader = ::csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(::csv::Trim::All)
+        .from_reader(content.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|err| ImportError::Parse(err.to_string()))?
+        .iter()
+        .map(|header| header.to_lowercase())
+        .collect();
+    let find = |names: &[&str]| headers.iter().position(|h| names.contains(&h.as_str()));
+
+    let series = find(&["series_title", "series title", "series", "show"]);
+    let (source, title_column) = match (series, find(&["title"])) {
+        (Some(column), _) => (HistorySource::Crunchyroll, column),
+        (None, Some(column)) => (HistorySource::Netflix, column),
+        (None, None) => {
+            return Err(ImportError::Parse(
+                "expected a Title column (Netflix) or a series title column (Crunchyroll)"
+                    .to_string(),
+            ))
+        }
+    };
+    let date_column = find(&["date", "date_played", "start time", "watched_at"]);
+    let finished_column = find(&["fully_watched", "completed"]);
+
+    let mut watches = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|err| ImportError::Parse(err.to_string()))?;
+        let cell = |column: Option<usize>| column.and_then(|c| record.get(c)).unwrap_or("");
+        let title = cell(Some(title_column));
+        if title.is_empty() {
+            continue;
+        }
+        let candidates = match source {
+            HistorySource::Netflix => netflix_candidates(title),
+            HistorySource::Crunchyroll => vec![title.to_string()],
+        };
+        watches.push(Watch {
+            candidates,
+            date: parse_date(cell(date_column)),
+            finished: !matches!(
+                cell(finished_column).to_lowercase().as_str(),
+                "false" | "0" | "no"
+            ),
+        });
+    }
+    Ok((source, watches))
+}
+
+/// Crunchyroll's watch-history response: `{"data": [...]}` or a bare array of entries.
+fn crunchyroll_json(content: &str) -> Result<Vec<Watch>, ImportError> {
+    let root: Value = serde_json::from_str(content)?;
+    let items = match &root {
+        Value::Array(items) => items,
+        Value::Object(object) => match object.get("data") {
+            Some(Value::Array(items)) => items,
+            _ => return Err(ImportError::Parse("expected a data array".to_string())),
+        },
+        _ => return Err(ImportError::Parse("expected a data array".to_string())),
+    };
+    Ok(items
+        .iter()
+        .filter_map(|item| {
+            let title = item
+                .pointer("/panel/episode_metadata/series_title")
+                .or_else(|| item.get("series_title"))
+                .and_then(Value::as_str)?;
+            Some(Watch {
+                candidates: vec![title.to_string()],
+                date: item
+                    .get("date_played")
+                    .and_then(Value::as_str)
+                    .and_then(parse_date),
+                finished: item
+                    .get("fully_watched")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(true),
+            })
+        })
+        .collect())
+}
+
+/// Netflix writes `Show: Season 1: Episode title`. Returns the show title, then its shorter
+/// prefixes, so both `Film: Subtitle` and a show whose first part is the MAL title match.
+fn netflix_candidates(title: &str) -> Vec<String> {
+    let parts: Vec<&str> = title.split(": ").collect();
+    let series_len = parts
+        .iter()
+        .position(|part| {
+            let part = part.to_lowercase();
+            EPISODE_MARKERS
+                .iter()
+                .any(|marker| part.starts_with(marker))
+        })
+        .unwrap_or(parts.len())
+        .max(1);
+    (1..=series_len)
+        .rev()
+        .map(|len| parts[..len].join(": "))
+        .collect()
+}
+
+/// Accepts ISO dates and Netflix's `M/D/YY` (or `D/M/YY` when the first part can't be a
+/// month); returns `YYYY-MM-DD`.
+fn parse_date(value: &str) -> Option<String> {
+    let value = value.trim();
+    if let Some(date) = value.get(..10).filter(|date| is_iso_date(date)) {
+        return Some(date.to_string());
+    }
+    let mut parts = value
+        .split(['/', ' '])
+        .take(3)
+        .map(|part| part.parse::<u32>().ok());
+    let (first, second, year) = (parts.next()??, parts.next()??, parts.next()??);
+    let (month, day) = if first > 12 {
+        (second, first)
+    } else {
+        (first, second)
+    };
+    let year = if year < 100 { 2000 + year } else { year };
+    ((1..=12).contains(&month) && (1..=31).contains(&day))
+        .then(|| format!("{year:04}-{month:02}-{day:02}"))
+}
+
+/// Whether `date` is `YYYY-MM-DD`, digits and dashes only.
+fn is_iso_date(date: &str) -> bool {
+    date.bytes().enumerate().all(|(i, byte)| match i {
+        4 | 7 => byte == b'-',
+        _ => byte.is_ascii_digit(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_date_rejects_non_ascii_where_a_date_is_expected() {
+        assert_eq!(
+            parse_date("2024-01-05T10:00"),
+            Some("2024-01-05".to_string())
+        );
+        assert_eq!(parse_date("2024-01-0é"), None);
+        assert_eq!(parse_date("1/5/24"), Some("2024-01-05".to_string()));
+    }
+
+    #[test]
+    fn netflix_titles_lose_their_season_and_episode() {
+        assert_eq!(
+            netflix_candidates("Attack on Titan: Season 2: Beast Titan"),
+            ["Attack on Titan"]
+        );
+        assert_eq!(
+            netflix_candidates("Re:ZERO -Starting Life in Another World-: Part 2: Episode 3"),
+            ["Re:ZERO -Starting Life in Another World-"]
+        );
+        assert_eq!(
+            netflix_candidates("Violet Evergarden: The Movie"),
+            ["Violet Evergarden: The Movie", "Violet Evergarden"]
+        );
+        assert_eq!(
+            netflix_candidates("Castlevania: Nocturne: Limited Series: Episode 1"),
+            ["Castlevania: Nocturne", "Castlevania"]
+        );
+        assert_eq!(netflix_candidates("Season 1: Pilot"), ["Season 1"]);
+    }
+}
@@ -0,0 +1,105 @@
+//! Rate-limited JSON fetching with retry and backoff, shared by the REST importers.
+
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use super::ImportError;
+
+const MAX_RETRIES: u32 = 5;
+const MAX_BACKOFF_MS: u64 = 20_000;
+
+/// Spaces requests at least `min_interval` apart across every caller sharing the limiter.
+pub struct RateLimiter {
+    min_interval: Duration,
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl RateLimiter {
+    pub const fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            next_slot: Mutex::new(None),
+        }
+    }
+
+    pub async fn wait(&self) {
+        let wait_until = {
+            let mut next_slot = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let slot = next_slot.map_or(now, |slot| slot.max(now));
+            *next_slot = Some(slot + self.min_interval);
+            slot
+        };
+        tokio::time::sleep_until(wait_until.into()).await;
+    }
+}
+
+/// GETs `url` as JSON. Retries 405/408/429/5xx with exponential backoff (or the server's
+/// `Retry-After`) plus jitter, matching the pipeline's collectors.
+pub async fn get_json<T: DeserializeOwned>(
+    client: &Client,
+    limiter: &RateLimiter,
+    url: &str,
+) -> Result<T, ImportError> {
+    for attempt in 0..=MAX_RETRIES {
+        limiter.wait().await;
+        let response = client
+            .get(url)
+            .send()
+            .await
+            .map_err(|err| ImportError::Http(format!("{url}: {err}")))?;
+
+        let status = response.status();
+        if status.is_success() {
+            return response
+                .json()
+                .await
+                .map_err(|err| ImportError::Http(format!("{url}: invalid response: {err}")));
+        }
+
+        if is_retryable(status) && attempt < MAX_RETRIES {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u64>().ok());
+            let backoff_ms = match retry_after {
+                Some(seconds) => seconds.max(1) * 1000,
+                None => (1000 * 2_u64.pow(attempt)).min(MAX_BACKOFF_MS),
+            };
+            tokio::time::sleep(Duration::from_millis(backoff_ms + jitter_ms())).await;
+            continue;
+        }
+
+        return Err(ImportError::Http(format!("{url}: {status}")));
+    }
+
+    Err(ImportError::Http(format!("{url}: retries exhausted")))
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 405 | 408 | 429) || status.is_server_error()
+}
+
+/// Up to 350 ms of jitter so parallel clients do not retry in lockstep.
+fn jitter_ms() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    u64::from(nanos % 350)
+}
+
+/// Percent-encodes a username for use in a URL path segment.
+pub fn path_segment(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
@@ -0,0 +1,207 @@
+//! MyAnimeList import by username through the Jikan REST API.
+//!
+//! Jikan has disabled its user-list endpoint for many accounts since MAL changed its list
+//! pages, so when Jikan refuses the request the importer falls back to MAL's public
+//! `load.json` list endpoint — the same source `pipeline/src/mal.ts` collects from. Both go
+//! through one shared rate limiter.
+
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::time::Duration;
+use wasiw_core::Rating;
+
+use super::http::{get_json, path_segment, RateLimiter};
+use super::{ImportError, ImportedProfile};
+
+/// Jikan allows 3 requests per second; stay comfortably under it.
+pub static LIMITER: RateLimiter = RateLimiter::new(Duration::from_millis(400));
+const MAL_PAGE_SIZE: usize = 300;
+const MAX_PAGES: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct JikanPage {
+    data: Vec<JikanEntry>,
+    pagination: Option<JikanPagination>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JikanPagination {
+    has_next_page: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct JikanEntry {
+    #[serde(default)]
+    score: u32,
+    anime: JikanAnime,
+}
+
+#[derive(Debug, Deserialize)]
+struct JikanAnime {
+    mal_id: u32,
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MalListEntry {
+    anime_id: u32,
+    anime_title: serde_json::Value,
+    #[serde(default)]
+    score: u32,
+}
+
+impl MalListEntry {
+    fn into_entry(self) -> (u32, String, u32) {
+        // MAL serializes numeric-looking titles as numbers.
+        let title = match self.anime_title {
+            serde_json::Value::String(title) => title,
+            other => other.to_string(),
+        };
+        (self.anime_id, title, self.score)
+    }
+}
+
+/// Fetches every scored entry on a public MAL list. `progress` receives short status lines.
+pub async fn fetch_user(
+    client: &Client,
+    username: &str,
+    mut progress: impl FnMut(String),
+) -> Result<ImportedProfile, ImportError> {
+    let username = username.trim();
+    let encoded = path_segment(username);
+
+    let entries = match fetch_from_jikan(client, &encoded, &mut progress).await {
+        Ok(entries) => entries,
+        Err(jikan_error) => {
+            progress(format!(
+                "Jikan list unavailable ({}); trying MAL directly…",
+                jikan_error
+            ));
+            fetch_from_mal(client, &encoded, &mut progress).await?
+        }
+    };
+    profile(username, entries)
+}
+
+/// Keeps the first scored `(anime id, title, score)` entry per anime.
+fn profile(
+    username: &str,
+    entries: Vec<(u32, String, u32)>,
+) -> Result<ImportedProfile, ImportError> {
+    let total = entries.len();
+    let mut seen = HashSet::new();
+    let ratings: Vec<Rating> = entries
+        .into_iter()
+        .filter(|(_, _, score)| *score > 0)
+        .filter(|(anime_id, _, _)| seen.insert(*anime_id))
+        .map(|(anime_id, title, score)| Rating::new(anime_id, title, f64::from(score)))
+        .collect();
+
+    if ratings.is_empty() {
+        return Err(ImportError::Empty);
+    }
+
+    Ok(ImportedProfile {
+        name: format!("mal/{username}"),
+        skipped: total - ratings.len(),
+        ratings,
+    })
+}
+
+async fn fetch_from_jikan(
+    client: &Client,
+    username: &str,
+    progress: &mut impl FnMut(String),
+) -> Result<Vec<(u32, String, u32)>, ImportError> {
+    let mut entries = Vec::new();
+    for page in 1..=MAX_PAGES {
+        progress(format!("Fetching Jikan page {page}…"));
+        let url = format!("https://api.jikan.moe/v4/users/{username}/animelist?page={page}");
+        let body: JikanPage = get_json(client, &LIMITER, &url).await?;
+        let has_next = body.pagination.is_some_and(|p| p.has_next_page);
+        entries.extend(
+            body.data
+                .into_iter()
+                .map(|entry| (entry.anime.mal_id, entry.anime.title, entry.score)),
+        );
+        if !has_next {
+            break;
+        }
+    }
+    Ok(entries)
+}
+
+async fn fetch_from_mal(
+    client: &Client,
+    username: &str,
+    progress: &mut impl FnMut(String),
+) -> Result<Vec<(u32, String, u32)>, ImportError> {
+    let mut entries = Vec::new();
+    for page in 0..MAX_PAGES {
+        let offset = page * MAL_PAGE_SIZE;
+        progress(format!("Fetching MAL entries from {offset}…"));
+        let url = format!(
+            "https://myanimelist.net/animelist/{username}/load.json?status=7&offset={offset}"
+        );
+        let body: Vec<MalListEntry> = get_json(client, &LIMITER, &url).await?;
+        let count = body.len();
+        entries.extend(body.into_iter().map(MalListEntry::into_entry));
+        if count < MAL_PAGE_SIZE {
+            break;
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_keep_the_first_scored_entry_per_anime() {
+        let entries = vec![
+            (1, "Cowboy Bebop".to_string(), 9),
+            (5, "Cowboy Bebop: The Movie".to_string(), 0),
+            (1, "Cowboy Bebop".to_string(), 4),
+            (30, "Neon Genesis Evangelion".to_string(), 7),
+        ];
+        let imported = profile("spike", entries).unwrap();
+        assert_eq!(imported.name, "mal/spike");
+        let ratings: Vec<(u32, f64)> = imported
+            .ratings
+            .iter()
+            .map(|rating| (rating.anime_id, rating.raw_score))
+            .collect();
+        assert_eq!(ratings, [(1, 9.0), (30, 7.0)]);
+        assert_eq!(imported.skipped, 2);
+        let unscored = vec![(1, "Cowboy Bebop".to_string(), 0)];
+        assert!(matches!(profile("new", unscored), Err(ImportError::Empty)));
+    }
+
+    #[test]
+    fn both_list_sources_parse_into_entries() {
+        let jikan: JikanPage = serde_json::from_str(
+            r#"{"data": [{"score": 8, "anime": {"mal_id": 1, "title": "Cowboy Bebop"}},
+                         {"anime": {"mal_id": 6, "title": "Trigun"}}],
+                "pagination": {"has_next_page": false}}"#,
+        )
+        .unwrap();
+        assert_eq!(jikan.data[1].score, 0);
+        assert!(!jikan.pagination.unwrap().has_next_page);
+
+        let mal: Vec<MalListEntry> = serde_json::from_str(
+            r#"[{"anime_id": 9253, "anime_title": "Steins;Gate", "score": 10},
+                {"anime_id": 2167, "anime_title": 91, "score": 6}]"#,
+        )
+        .unwrap();
+        let entries: Vec<_> = mal.into_iter().map(MalListEntry::into_entry).collect();
+        assert_eq!(
+            entries,
+            [
+                (9253, "Steins;Gate".to_string(), 10),
+                (2167, "91".to_string(), 6)
+            ]
+        );
+    }
+}
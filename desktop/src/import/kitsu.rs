@@ -0,0 +1,270 @@
+//! Kitsu import through the public JSON:API (no authentication needed for public libraries).
+
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use wasiw_core::scale::ScoreScale;
+use wasiw_core::Rating;
+
+use super::http::{get_json, path_segment, RateLimiter};
+use super::{ImportError, ImportedProfile};
+use crate::idmap::{IdMap, Site};
+
+const API: &str = "https://kitsu.io/api/edge";
+const PAGE_SIZE: usize = 200;
+const MAX_PAGES: usize = 100;
+const MAL_MAPPING_SITE: &str = "myanimelist/anime";
+
+static LIMITER: RateLimiter = RateLimiter::new(Duration::from_millis(250));
+
+#[derive(Debug, Deserialize)]
+struct Document<T> {
+    data: T,
+    #[serde(default)]
+    included: Vec<Resource>,
+    links: Option<Links>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Links {
+    next: Option<String>,
+}
+
+/// Any JSON:API resource; attributes are read per type from the raw value.
+#[derive(Debug, Deserialize)]
+struct Resource {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    attributes: serde_json::Value,
+    #[serde(default)]
+    relationships: HashMap<String, Relationship>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Relationship {
+    #[serde(default)]
+    data: Option<Linkage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Linkage {
+    One(ResourceId),
+    Many(Vec<ResourceId>),
+}
+
+#[derive(Debug, Deserialize)]
+struct ResourceId {
+    id: String,
+}
+
+impl Resource {
+    fn related(&self, name: &str) -> Vec<&str> {
+        match self.relationships.get(name).and_then(|r| r.data.as_ref()) {
+            Some(Linkage::One(one)) => vec![one.id.as_str()],
+            Some(Linkage::Many(many)) => many.iter().map(|r| r.id.as_str()).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn attribute(&self, name: &str) -> Option<&serde_json::Value> {
+        self.attributes.get(name).filter(|value| !value.is_null())
+    }
+}
+
+/// Fetches a public Kitsu library by username (profile slug).
+///
+/// Kitsu stores ratings on a 20-point scale (`ratingTwenty`, 2–20), converted here to match the
+/// corpus' 1–10 MAL scores. The corpus is keyed by MAL ids; entries without a MyAnimeList
+/// mapping are resolved through `ids` when an id map is loaded and skipped otherwise, as are
+/// unrated entries.
+pub async fn fetch_user(
+    client: &Client,
+    username: &str,
+    ids: Option<&IdMap>,
+    mut progress: impl FnMut(String),
+) -> Result<ImportedProfile, ImportError> {
+    let username = username.trim();
+    let lookup = format!("{API}/users?filter[slug]={}", path_segment(username));
+    let users: Document<Vec<Resource>> = get_json(client, &LIMITER, &lookup).await?;
+    let user_id = users
+        .data
+        .first()
+        .map(|user| user.id.clone())
+        .ok_or_else(|| ImportError::Http(format!("no Kitsu user named {username}")))?;
+
+    let mut next = Some(format!(
+        "{API}/library-entries?filter[userId]={user_id}&filter[kind]=anime\
+         &include=anime.mappings&fields[anime]=canonicalTitle,mappings\
+         &fields[mappings]=externalSite,externalId&page[limit]={PAGE_SIZE}"
+    ));
+    let mut library = Library::default();
+    for page in 1..=MAX_PAGES {
+        let Some(url) = next.take() else { break };
+        progress(format!("Fetching Kitsu page {page}…"));
+        next = library.add_page(get_json(client, &LIMITER, &url).await?);
+    }
+    library.into_profile(username, ids)
+}
+
+/// Library entries fetched so far, with the anime and mappings included alongside them.
+#[derive(Default)]
+struct Library {
+    entries: Vec<Resource>,
+    anime_titles: HashMap<String, String>,
+    anime_mappings: HashMap<String, Vec<String>>,
+    mal_ids: HashMap<String, u32>,
+}
+
+impl Library {
+    /// Takes in one page of entries; returns the next page's link.
+    fn add_page(&mut self, body: Document<Vec<Resource>>) -> Option<String> {
+        for resource in body.included {
+            match resource.kind.as_str() {
+                "anime" => {
+                    if let Some(title) = resource
+                        .attribute("canonicalTitle")
+                        .and_then(|t| t.as_str())
+                    {
+                        self.anime_titles
+                            .insert(resource.id.clone(), title.to_string());
+                    }
+                    let mappings = resource.related("mappings").into_iter().map(str::to_string);
+                    self.anime_mappings
+                        .insert(resource.id.clone(), mappings.collect());
+                }
+                "mappings" => {
+                    let site = resource.attribute("externalSite").and_then(|s| s.as_str());
+                    let external = resource.attribute("externalId").and_then(|s| s.as_str());
+                    if let (Some(MAL_MAPPING_SITE), Some(external)) = (site, external) {
+                        if let Ok(mal_id) = external.parse() {
+                            self.mal_ids.insert(resource.id.clone(), mal_id);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.entries.extend(body.data);
+        body.links.and_then(|links| links.next)
+    }
+
+    fn into_profile(
+        self,
+        username: &str,
+        ids: Option<&IdMap>,
+    ) -> Result<ImportedProfile, ImportError> {
+        let total = self.entries.len();
+        let mut seen = HashSet::new();
+        let ratings: Vec<Rating> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let rating = entry.attribute("ratingTwenty")?.as_f64()?;
+                let rating = ScoreScale::Twenty.to_ten(rating).ok()?;
+                let anime = *entry.related("anime").first()?;
+                let anime_id = self
+                    .anime_mappings
+                    .get(anime)
+                    .and_then(|mappings| {
+                        mappings
+                            .iter()
+                            .find_map(|mapping| self.mal_ids.get(mapping).copied())
+                    })
+                    .or_else(|| ids?.resolve(Site::Kitsu, anime.parse().ok()?))?;
+                let title = self
+                    .anime_titles
+                    .get(anime)
+                    .cloned()
+                    .unwrap_or_else(|| format!("MAL #{anime_id}"));
+                seen.insert(anime_id)
+                    .then(|| Rating::new(anime_id, title, rating))
+            })
+            .collect();
+
+        if ratings.is_empty() {
+            return Err(ImportError::Empty);
+        }
+
+        Ok(ImportedProfile {
+            name: format!("kitsu/{username}"),
+            skipped: total - ratings.len(),
+            ratings,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(json: &str) -> Document<Vec<Resource>> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn libraries_resolve_mal_ids_through_included_mappings() {
+        let mut library = Library::default();
+        let next = library.add_page(page(
+            r#"{
+                "data": [
+                    {"id": "e1", "type": "libraryEntries", "attributes": {"ratingTwenty": 18},
+                     "relationships": {"anime": {"data": {"type": "anime", "id": "1"}}}},
+                    {"id": "e2", "type": "libraryEntries", "attributes": {"ratingTwenty": null},
+                     "relationships": {"anime": {"data": {"type": "anime", "id": "7"}}}}
+                ],
+                "included": [
+                    {"id": "1", "type": "anime", "attributes": {"canonicalTitle": "Cowboy Bebop"},
+                     "relationships": {"mappings": {"data": [{"type": "mappings", "id": "m1"},
+                                                             {"type": "mappings", "id": "m2"}]}}},
+                    {"id": "m1", "type": "mappings",
+                     "attributes": {"externalSite": "anidb", "externalId": "23"}},
+                    {"id": "m2", "type": "mappings",
+                     "attributes": {"externalSite": "myanimelist/anime", "externalId": "1"}}
+                ],
+                "links": {"next": "https://kitsu.io/api/edge/library-entries?page[offset]=2"}
+            }"#,
+        ));
+        assert!(next.is_some_and(|link| link.ends_with("offset]=2")));
+        let last = library.add_page(page(
+            r#"{
+                "data": [
+                    {"id": "e3", "type": "libraryEntries", "attributes": {"ratingTwenty": 10},
+                     "relationships": {"anime": {"data": {"type": "anime", "id": "2"}}}},
+                    {"id": "e4", "type": "libraryEntries", "attributes": {"ratingTwenty": 14},
+                     "relationships": {"anime": {"data": {"type": "anime", "id": "1"}}}}
+                ],
+                "included": [
+                    {"id": "2", "type": "anime", "attributes": {"canonicalTitle": "Kitsu only"},
+                     "relationships": {"mappings": {"data": []}}}
+                ],
+                "links": {}
+            }"#,
+        ));
+        assert_eq!(last, None);
+
+        let profile = library.into_profile("spike", None).unwrap();
+        assert_eq!(profile.name, "kitsu/spike");
+        let ratings: Vec<(u32, &str, f64)> = profile
+            .ratings
+            .iter()
+            .map(|rating| (rating.anime_id, rating.title.as_str(), rating.raw_score))
+            .collect();
+        // Unrated, unmapped and repeated entries are skipped.
+        assert_eq!(ratings, [(1, "Cowboy Bebop", 9.0)]);
+        assert_eq!(profile.skipped, 3);
+    }
+
+    #[test]
+    fn libraries_without_ratings_are_empty() {
+        let mut library = Library::default();
+        library.add_page(page(r#"{"data": []}"#));
+        assert!(matches!(
+            library.into_profile("new", None),
+            Err(ImportError::Empty)
+        ));
+    }
+}
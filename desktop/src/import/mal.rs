@@ -0,0 +1,125 @@
+//! MyAnimeList list export (`animelist.xml`, from Profile → Export).
+
+use serde::Deserialize;
+use std::path::Path;
+use wasiw_core::{compression, Rating};
+
+use super::{ImportError, ImportedProfile};
+
+#[derive(Debug, Deserialize)]
+struct MalExport {
+    myinfo: Option<MalInfo>,
+    #[serde(default)]
+    anime: Vec<MalAnime>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MalInfo {
+    user_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MalAnime {
+    series_animedb_id: Option<u32>,
+    #[serde(default)]
+    series_title: String,
+    #[serde(default)]
+    my_score: u32,
+}
+
+pub fn import_file(path: &Path) -> Result<ImportedProfile, ImportError> {
+    let content = compression::read_to_string(path)?;
+    parse(&content)
+}
+
+/// Parses a MAL export. Unscored entries (`my_score` of 0) are skipped, since MAL uses 0 for
+/// "not rated" rather than a real score.
+pub fn parse(xml: &str) -> Result<ImportedProfile, ImportError> {
+    let export: MalExport =
+        quick_xml::de::from_str(xml).map_err(|err| ImportError::Parse(err.to_string()))?;
+
+    let name = export
+        .myinfo
+        .and_then(|info| info.user_name)
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| "export".to_string());
+
+    let total = export.anime.len();
+    let ratings: Vec<Rating> = export
+        .anime
+        .into_iter()
+        .filter(|entry| entry.my_score > 0)
+        .filter_map(|entry| {
+            Some(Rating::new(
+                entry.series_animedb_id?,
+                entry.series_title.trim().to_string(),
+                f64::from(entry.my_score),
+            ))
+        })
+        .collect();
+
+    if ratings.is_empty() {
+        return Err(ImportError::Empty);
+    }
+
+    Ok(ImportedProfile {
+        name: format!("mal/{name}"),
+        skipped: total - ratings.len(),
+        ratings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exports_keep_scored_entries_with_an_id() {
+        let profile = parse(
+            r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <myanimelist>
+                <myinfo><user_name>Spike</user_name></myinfo>
+                <anime>
+                    <series_animedb_id>1</series_animedb_id>
+                    <series_title><![CDATA[ Cowboy Bebop ]]></series_title>
+                    <my_score>9</my_score>
+                </anime>
+                <anime>
+                    <series_animedb_id>5</series_animedb_id>
+                    <series_title><![CDATA[Cowboy Bebop: Tengoku no Tobira]]></series_title>
+                    <my_score>0</my_score>
+                </anime>
+                <anime>
+                    <series_title>No id</series_title>
+                    <my_score>7</my_score>
+                </anime>
+            </myanimelist>"#,
+        )
+        .unwrap();
+        assert_eq!(profile.name, "mal/Spike");
+        assert_eq!(profile.skipped, 2);
+        assert_eq!(profile.ratings.len(), 1);
+        assert_eq!(
+            (
+                profile.ratings[0].anime_id,
+                profile.ratings[0].title.as_str()
+            ),
+            (1, "Cowboy Bebop")
+        );
+        assert_eq!(profile.ratings[0].raw_score, 9.0);
+    }
+
+    #[test]
+    fn exports_without_scores_or_xml_are_refused() {
+        let unscored = "<myanimelist><anime><series_animedb_id>1</series_animedb_id>\
+                        <my_score>0</my_score></anime></myanimelist>";
+        assert!(matches!(parse(unscored), Err(ImportError::Empty)));
+        assert!(matches!(
+            parse("<myanimelist><anime>"),
+            Err(ImportError::Parse(_))
+        ));
+        let anonymous = "<myanimelist><anime><series_animedb_id>1</series_animedb_id>\
+                         <my_score>8</my_score></anime></myanimelist>";
+        assert_eq!(parse(anonymous).unwrap().name, "mal/export");
+    }
+}
@@ -0,0 +1,146 @@
+//! Converters from external anime-list formats into the dataset schema.
+
+use std::fmt;
+use std::io;
+
+use wasiw_core::{DatasetError, Rating, UserRatings};
+
+pub mod anilist;
+pub mod csv;
+pub mod history;
+pub mod http;
+pub mod jikan;
+pub mod kitsu;
+pub mod mal;
+pub mod panel;
+pub mod titles;
+
+pub use wasiw_core::dataset::LOCAL_PROFILE_PREFIX;
+
+#[derive(Debug)]
+pub enum ImportError {
+    Io(io::Error),
+    Parse(String),
+    /// Malformed JSON at a known position (1-based line and column).
+    Syntax {
+        message: String,
+        line: usize,
+        column: usize,
+    },
+    /// The remote service could not be reached or rejected the request.
+    Http(String),
+    /// The source parsed but contained no scored entries.
+    Empty,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "could not read file: {err}"),
+            Self::Parse(msg) => write!(f, "could not parse list: {msg}"),
+            Self::Syntax {
+                message,
+                line,
+                column,
+            } => write!(f, "invalid JSON at line {line}, column {column}: {message}"),
+            Self::Http(msg) => write!(f, "request failed: {msg}"),
+            Self::Empty => write!(f, "the list has no scored entries"),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<io::Error> for ImportError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<DatasetError> for ImportError {
+    fn from(err: DatasetError) -> Self {
+        match err {
+            DatasetError::Io(err) => Self::Io(err),
+            DatasetError::Parse(msg) => Self::Parse(msg),
+            DatasetError::Syntax {
+                message,
+                line,
+                column,
+            } => Self::Syntax {
+                message,
+                line,
+                column,
+            },
+            DatasetError::Empty => Self::Empty,
+        }
+    }
+}
+
+impl From<serde_json::Error> for ImportError {
+    fn from(err: serde_json::Error) -> Self {
+        match err.classify() {
+            // Reader failures (e.g. a truncated gzip stream) are I/O, not a JSON position.
+            serde_json::error::Category::Io => Self::Io(err.into()),
+            _ => Self::Syntax {
+                line: err.line(),
+                column: err.column(),
+                message: err.to_string(),
+            },
+        }
+    }
+}
+
+/// A user's list converted to the dataset schema, ready to merge into the corpus.
+#[derive(Debug, Clone)]
+pub struct ImportedProfile {
+    pub name: String,
+    pub ratings: Vec<Rating>,
+    /// Entries dropped because they were unscored, duplicated, or had no MAL id.
+    pub skipped: usize,
+}
+
+impl ImportedProfile {
+    pub fn into_user(self) -> UserRatings {
+        UserRatings {
+            user_id: format!("{LOCAL_PROFILE_PREFIX}{}", self.name),
+            ratings: self.ratings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_errors_point_at_their_line_and_column() {
+        let err = serde_json::from_str::<serde_json::Value>("{\n  \"users\": [,]\n}").unwrap_err();
+        let err = ImportError::from(err);
+        assert!(
+            matches!(
+                err,
+                ImportError::Syntax {
+                    line: 2,
+                    column: 13,
+                    ..
+                }
+            ),
+            "{err:?}"
+        );
+        assert!(err
+            .to_string()
+            .starts_with("invalid JSON at line 2, column 13: "));
+
+        struct Failing;
+        impl io::Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "truncated gzip",
+                ))
+            }
+        }
+        let err = serde_json::from_reader::<_, serde_json::Value>(Failing).unwrap_err();
+        assert!(matches!(ImportError::from(err), ImportError::Io(_)));
+    }
+}
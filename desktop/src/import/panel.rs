@@ -0,0 +1,387 @@
+//! The side panel's import fields and the CSV column-mapping dialog, and what they do with the
+//! imported ratings: they go into the active profile when there is one, otherwise each import
+//! becomes a local user of its own, and the graph is rebuilt around them.
+
+use std::path::Path;
+
+use dioxus::prelude::*;
+use wasiw_core::scale::ScoreScale;
+use wasiw_core::UserRatings;
+use wasiw_ui::accessibility::focus_on_mount;
+
+use super::csv::{AnimeKey, ColumnMapping, CsvTable, SkippedLine};
+use super::{anilist, history, jikan, kitsu, mal, ImportError, ImportedProfile};
+use crate::state::AppState;
+use crate::tasks::{self, TaskKind};
+use crate::{http_client, store_user};
+
+/// The import fields of the side panel, with the status line most actions report to.
+#[component]
+pub fn ImportPanel(app: AppState, csv_table: Signal<Option<CsvTable>>) -> Element {
+    let busy = app.tasks.read().is_running(TaskKind::Import);
+    let mut path = use_signal(String::new);
+    let mut csv_path = use_signal(String::new);
+    let mut history_path = use_signal(String::new);
+    let mut anilist_user = use_signal(String::new);
+    let mut mal_user = use_signal(String::new);
+    let mut kitsu_user = use_signal(String::new);
+    rsx! {
+        div { class: "field",
+            span { class: "tiny", "Your MyAnimeList export (extracted animelist.xml)" }
+            input {
+                r#type: "text",
+                placeholder: "/path/to/animelist.xml",
+                value: "{path}",
+                oninput: move |evt| path.set(evt.value()),
+            }
+            button {
+                class: "action",
+                disabled: busy || path.read().trim().is_empty(),
+                onclick: move |_| import_mal(app, &path()),
+                "Import MAL list"
+            }
+        }
+        div { class: "field",
+            span { class: "tiny", "Any CSV of ratings (columns are mapped next)" }
+            input {
+                r#type: "text",
+                placeholder: "/path/to/ratings.csv",
+                value: "{csv_path}",
+                oninput: move |evt| csv_path.set(evt.value()),
+            }
+            button {
+                class: "action",
+                disabled: busy || csv_path.read().trim().is_empty(),
+                onclick: move |_| open_csv(app, csv_table, &csv_path()),
+                "Map CSV columns…"
+            }
+        }
+        div { class: "field",
+            span { class: "tiny", "Netflix or Crunchyroll viewing history (CSV or JSON)" }
+            input {
+                r#type: "text",
+                placeholder: "/path/to/NetflixViewingHistory.csv",
+                value: "{history_path}",
+                oninput: move |evt| history_path.set(evt.value()),
+            }
+            button {
+                class: "action",
+                disabled: busy || history_path.read().trim().is_empty(),
+                onclick: move |_| import_history(app, &history_path()),
+                "Import viewing history"
+            }
+        }
+        div { class: "field",
+            span { class: "tiny", "MyAnimeList username (public list)" }
+            input {
+                r#type: "text",
+                placeholder: "username",
+                value: "{mal_user}",
+                oninput: move |evt| mal_user.set(evt.value()),
+            }
+            button {
+                class: "action",
+                disabled: busy || mal_user.read().trim().is_empty(),
+                onclick: move |_| import_jikan(app, mal_user()),
+                "Import from MyAnimeList"
+            }
+        }
+        div { class: "field",
+            span { class: "tiny", "AniList username" }
+            input {
+                r#type: "text",
+                placeholder: "username",
+                value: "{anilist_user}",
+                oninput: move |evt| anilist_user.set(evt.value()),
+            }
+            button {
+                class: "action",
+                disabled: busy || anilist_user.read().trim().is_empty(),
+                onclick: move |_| import_anilist(app, anilist_user()),
+                "Import from AniList"
+            }
+        }
+        div { class: "field",
+            span { class: "tiny", "Kitsu username" }
+            input {
+                r#type: "text",
+                placeholder: "username",
+                value: "{kitsu_user}",
+                oninput: move |evt| kitsu_user.set(evt.value()),
+            }
+            button {
+                class: "action",
+                disabled: busy || kitsu_user.read().trim().is_empty(),
+                onclick: move |_| import_kitsu(app, kitsu_user()),
+                "Import from Kitsu"
+            }
+            if let Some(status) = app.import_status.cloned() {
+                span { class: "tiny", role: "status", "{status}" }
+            }
+        }
+    }
+}
+
+/// Lets the user assign CSV columns before importing, previewing the first few rows.
+#[component]
+pub fn CsvMappingDialog(
+    app: AppState,
+    /// The table being mapped; cleared when the dialog closes.
+    csv_table: Signal<Option<CsvTable>>,
+    headers: Vec<String>,
+    preview: Vec<Vec<String>>,
+    row_count: usize,
+) -> Element {
+    let guessed = ColumnMapping::guess(&headers);
+    let mut mapping = use_signal(move || guessed);
+    let current = mapping();
+    let parse_column = |value: String| value.parse::<usize>().ok();
+    let column_value = |column: Option<usize>| column.map_or(String::new(), |c| c.to_string());
+
+    let column_select = |label: &'static str,
+                         column: Option<usize>,
+                         optional: bool,
+                         set: fn(&mut ColumnMapping, Option<usize>)| {
+        rsx! {
+            label { class: "field",
+                span { class: "tiny", "{label}" }
+                select {
+                    value: column_value(column),
+                    onchange: move |evt| set(&mut mapping.write(), parse_column(evt.value())),
+                    option { value: "", selected: column.is_none(),
+                        if optional { "(none)" } else { "Choose a column…" }
+                    }
+                    for (index, header) in headers.iter().enumerate() {
+                        option { value: "{index}", selected: column == Some(index), "{header}" }
+                    }
+                }
+            }
+        }
+    };
+
+    rsx! {
+        div { class: "dialog-backdrop",
+            div {
+                class: "dialog",
+                role: "dialog",
+                aria_modal: "true",
+                aria_label: "Map CSV columns",
+                tabindex: "-1",
+                onmounted: focus_on_mount,
+                div { class: "row",
+                    strong { "Map CSV columns" }
+                    button { class: "close", onclick: move |_| csv_table.set(None), "×" }
+                }
+                p { class: "tiny", "{row_count} rows. Unmatched or invalid rows are skipped and listed after import." }
+                table { class: "preview",
+                    thead {
+                        tr {
+                            for header in headers.iter() {
+                                th { "{header}" }
+                            }
+                        }
+                    }
+                    tbody {
+                        for row in preview {
+                            tr {
+                                for value in row {
+                                    td { "{value}" }
+                                }
+                            }
+                        }
+                    }
+                }
+                div { class: "dialog-grid",
+                    {column_select("Anime", current.anime, false, |m, c| m.anime = c)}
+                    label { class: "field",
+                        span { class: "tiny", "Anime column holds" }
+                        select {
+                            onchange: move |evt| {
+                                let key = AnimeKey::ALL
+                                    .into_iter()
+                                    .find(|key| key.label() == evt.value())
+                                    .unwrap_or_default();
+                                mapping.write().key = key;
+                            },
+                            for key in AnimeKey::ALL {
+                                option { value: key.label(), selected: key == current.key, "{key.label()}" }
+                            }
+                        }
+                    }
+                    {column_select("Score", current.score, false, |m, c| m.score = c)}
+                    label { class: "field",
+                        span { class: "tiny", "Score scale" }
+                        select {
+                            onchange: move |evt| {
+                                let scale = ScoreScale::ALL
+                                    .into_iter()
+                                    .find(|scale| scale.label() == evt.value())
+                                    .unwrap_or_default();
+                                mapping.write().scale = scale;
+                            },
+                            for scale in ScoreScale::ALL {
+                                option { value: scale.label(), selected: scale == current.scale, "{scale.label()}" }
+                            }
+                        }
+                    }
+                    {column_select("Status (optional)", current.status, true, |m, c| m.status = c)}
+                    {column_select("Date (optional)", current.date, true, |m, c| m.date = c)}
+                }
+                if current.key == AnimeKey::Title {
+                    p { class: "tiny", "Titles are matched against anime already in the dataset." }
+                }
+                div { class: "row",
+                    button { class: "action", onclick: move |_| csv_table.set(None), "Cancel" }
+                    button {
+                        class: "action",
+                        disabled: !current.is_complete(),
+                        onclick: move |_| import_csv(app, csv_table, mapping()),
+                        "Import"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Adds imported ratings to the active profile, or as a local user of their own without one.
+/// Returns the dataset user id they went to.
+fn add_imported(mut app: AppState, user: UserRatings) -> String {
+    let mut profiles = app.profiles.write();
+    if let Some(profile) = profiles.active_mut() {
+        profile.rate(user.ratings);
+        let user_id = profile.user_id();
+        // Best effort, like saving the config: the ratings still apply this session.
+        let _ = profiles.save();
+        user_id
+    } else {
+        store_user(&user);
+        let user_id = user.user_id.clone();
+        app.dataset.write().merge_profile(user);
+        user_id
+    }
+}
+
+/// Adds a finished import and rebuilds the graph, or reports why it failed. `details` is
+/// appended to the summary.
+fn finish_import(mut app: AppState, result: Result<ImportedProfile, ImportError>, details: &str) {
+    match result {
+        Ok(profile) => {
+            let summary = import_summary(&profile);
+            tracing::info!("{summary}");
+            let user_id = add_imported(app, profile.into_user());
+            app.update_user_graph(&user_id);
+            app.import_status.set(Some(format!("{summary}{details}")));
+        }
+        Err(err) => {
+            tracing::warn!("import failed: {err}");
+            app.import_status.set(Some(format!("Import failed: {err}")));
+        }
+    }
+}
+
+pub fn import_mal(app: AppState, path: &str) {
+    finish_import(app, mal::import_file(Path::new(path.trim())), "");
+}
+
+/// Reads a CSV and opens the column-mapping dialog for it.
+pub fn open_csv(mut app: AppState, mut csv_table: Signal<Option<CsvTable>>, path: &str) {
+    match super::csv::read_file(Path::new(path.trim())) {
+        Ok(table) => {
+            app.import_status.set(None);
+            csv_table.set(Some(table));
+        }
+        Err(err) => app
+            .import_status
+            .set(Some(format!("Could not read CSV: {err}"))),
+    }
+}
+
+fn import_csv(app: AppState, mut csv_table: Signal<Option<CsvTable>>, mapping: ColumnMapping) {
+    let Some(table) = csv_table.take() else {
+        return;
+    };
+    let known_titles = app.dataset.peek().titles();
+    match super::csv::import(&table, mapping, &known_titles) {
+        Ok((profile, skipped)) => finish_import(app, Ok(profile), &skipped_report(&skipped)),
+        Err(err) => finish_import(app, Err(err), ""),
+    }
+}
+
+fn import_history(app: AppState, path: &str) {
+    let known_titles = app.dataset.peek().titles();
+    let ids = app.id_map.peek().clone();
+    match history::import_file(Path::new(path.trim()), &known_titles, ids.as_deref()) {
+        Ok((profile, unmatched)) => finish_import(app, Ok(profile), &unmatched_report(&unmatched)),
+        Err(err) => finish_import(app, Err(err), ""),
+    }
+}
+
+fn import_anilist(app: AppState, username: String) {
+    let ids = app.id_map.peek().clone();
+    let name = format!("Importing {}'s AniList list", username.trim());
+    tasks::spawn_async(app.tasks, TaskKind::Import, name, |_| async move {
+        let result = anilist::fetch_user(&http_client(), &username, ids.as_deref()).await;
+        finish_import(app, result, "");
+    });
+}
+
+fn import_jikan(app: AppState, username: String) {
+    let name = format!("Importing {}'s MyAnimeList list", username.trim());
+    tasks::spawn_async(app.tasks, TaskKind::Import, name, |progress| async move {
+        let result =
+            jikan::fetch_user(&http_client(), &username, |line| progress.report(line)).await;
+        finish_import(app, result, "");
+    });
+}
+
+fn import_kitsu(app: AppState, username: String) {
+    let ids = app.id_map.peek().clone();
+    let name = format!("Importing {}'s Kitsu library", username.trim());
+    tasks::spawn_async(app.tasks, TaskKind::Import, name, |progress| async move {
+        let result = kitsu::fetch_user(&http_client(), &username, ids.as_deref(), |line| {
+            progress.report(line)
+        })
+        .await;
+        finish_import(app, result, "");
+    });
+}
+
+fn import_summary(profile: &ImportedProfile) -> String {
+    format!(
+        "Imported {} ratings for {} ({} entries skipped).",
+        profile.ratings.len(),
+        profile.name,
+        profile.skipped
+    )
+}
+
+/// Up to a few skipped CSV lines with reasons, appended to the import summary.
+fn skipped_report(skipped: &[SkippedLine]) -> String {
+    const SHOWN: usize = 5;
+    if skipped.is_empty() {
+        return String::new();
+    }
+    let mut report: Vec<String> = skipped
+        .iter()
+        .take(SHOWN)
+        .map(|line| format!("line {}: {}", line.line, line.reason))
+        .collect();
+    if skipped.len() > SHOWN {
+        report.push(format!("and {} more", skipped.len() - SHOWN));
+    }
+    format!(" Skipped {}.", report.join("; "))
+}
+
+/// Up to a few history titles that matched no anime in the dataset.
+fn unmatched_report(unmatched: &[String]) -> String {
+    const SHOWN: usize = 5;
+    if unmatched.is_empty() {
+        return String::new();
+    }
+    let mut report: Vec<String> = unmatched.iter().take(SHOWN).cloned().collect();
+    if unmatched.len() > SHOWN {
+        report.push(format!("and {} more", unmatched.len() - SHOWN));
+    }
+    format!(" No match for {}.", report.join(", "))
+}
@@ -0,0 +1,138 @@
+//! Fuzzy title matching for sources that only know a show by name.
+
+use std::collections::{HashMap, HashSet};
+
+/// Minimum bigram similarity (Dice coefficient) for a fuzzy match.
+const FUZZY_THRESHOLD: f64 = 0.8;
+
+/// Looks up anime ids by title: exact after normalization first, then the most similar title.
+pub struct TitleMatcher {
+    exact: HashMap<String, u32>,
+    /// Bigrams of each normalized title with its anime id, for the fuzzy pass.
+    entries: Vec<(HashSet<[char; 2]>, u32)>,
+}
+
+impl TitleMatcher {
+    /// `titles` may list several titles per anime (e.g. synonyms); the first one wins on ties.
+    pub fn new<'a>(titles: impl IntoIterator<Item = (u32, &'a str)>) -> Self {
+        let mut exact = HashMap::new();
+        let mut entries = Vec::new();
+        for (anime_id, title) in titles {
+            let key = normalize(title);
+            if key.is_empty() || exact.contains_key(&key) {
+                continue;
+            }
+            entries.push((bigrams(&key), anime_id));
+            exact.insert(key, anime_id);
+        }
+        Self { exact, entries }
+    }
+
+    pub fn exact(&self, title: &str) -> Option<u32> {
+        self.exact.get(&normalize(title)).copied()
+    }
+
+    pub fn fuzzy(&self, title: &str) -> Option<u32> {
+        let wanted = bigrams(&normalize(title));
+        if wanted.is_empty() {
+            return None;
+        }
+        self.entries
+            .iter()
+            .map(|(candidate, anime_id)| {
+                let shared = candidate.intersection(&wanted).count();
+                let score = 2.0 * shared as f64 / (candidate.len() + wanted.len()) as f64;
+                (score, *anime_id)
+            })
+            .filter(|(score, _)| *score >= FUZZY_THRESHOLD)
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, anime_id)| anime_id)
+    }
+
+    /// Tries each candidate exactly, in order, then the first one fuzzily.
+    pub fn find(&self, candidates: &[String]) -> Option<u32> {
+        candidates
+            .iter()
+            .find_map(|candidate| self.exact(candidate))
+            .or_else(|| candidates.first().and_then(|title| self.fuzzy(title)))
+    }
+}
+
+/// Lower-cased letters and digits with single spaces; punctuation and symbols are dropped.
+pub fn normalize(title: &str) -> String {
+    title
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_lowercase().next().unwrap_or(c)
+            } else {
+                ' '
+            }
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn bigrams(key: &str) -> HashSet<[char; 2]> {
+    let chars: Vec<char> = key.chars().collect();
+    chars.windows(2).map(|pair| [pair[0], pair[1]]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher() -> TitleMatcher {
+        TitleMatcher::new([
+            (5114, "Fullmetal Alchemist: Brotherhood"),
+            (9253, "Steins;Gate"),
+            (30484, "Steins;Gate 0"),
+            (16498, "Shingeki no Kyojin"),
+            (16498, "Attack on Titan"),
+            (1, "steins gate"),
+        ])
+    }
+
+    #[test]
+    fn exact_matches_ignore_case_and_punctuation() {
+        let matcher = matcher();
+        assert_eq!(normalize("  Steins;Gate:  0! "), "steins gate 0");
+        assert_eq!(matcher.exact("steins gate"), Some(9253));
+        assert_eq!(matcher.exact("STEINS;GATE 0"), Some(30484));
+        assert_eq!(matcher.exact("Attack on Titan"), Some(16498));
+        assert_eq!(matcher.exact("Steins Gate Zero"), None);
+    }
+
+    #[test]
+    fn fuzzy_matches_need_most_bigrams_in_common() {
+        let matcher = matcher();
+        assert_eq!(matcher.fuzzy("Fullmetal Alchemist Brotherhod"), Some(5114));
+        assert_eq!(matcher.fuzzy("Shingeki no Kyoujin"), Some(16498));
+        assert_eq!(matcher.fuzzy("Steins;Gate 0"), Some(30484));
+        // A shorter title shares too few bigrams with the longer one it starts.
+        assert_eq!(matcher.fuzzy("Fullmetal Alchemist"), None);
+        assert_eq!(matcher.fuzzy("Cowboy Bebop"), None);
+        assert_eq!(matcher.fuzzy("!!"), None);
+    }
+
+    #[test]
+    fn find_tries_every_candidate_exactly_before_the_first_fuzzily() {
+        let matcher = matcher();
+        let candidates = |titles: &[&str]| titles.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            matcher.find(&candidates(&["Attack on Titan: Final", "Steins;Gate"])),
+            Some(9253)
+        );
+        assert_eq!(
+            matcher.find(&candidates(&["Shingeki no Kyoujin", "Cowboy Bebop"])),
+            Some(16498)
+        );
+        assert_eq!(
+            matcher.find(&candidates(&["Cowboy Bebop", "Shingeki no Kyoujin"])),
+            None
+        );
+        assert_eq!(matcher.find(&[]), None);
+    }
+}
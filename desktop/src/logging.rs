@@ -0,0 +1,222 @@
+//! Tracing output: events and phase timings from the app and `wasiw_core` (loading, graph
+//! builds, layouts, recommendations, imports) are written to `app.log` in the platform data
+//! directory, replaced at every launch, and kept in memory for the log panel. Other crates only
+//! log warnings and errors.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::config;
+
+const LOG_FILE_NAME: &str = "app.log";
+/// Entries the log panel can show; older ones are dropped.
+const CAPACITY: usize = 2000;
+/// Crates whose debug output is kept.
+const OWN_TARGETS: [&str; 2] = ["anime_graph_desktop", "wasiw_core"];
+
+static ENTRIES: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+static STARTED: OnceLock<Instant> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    /// Seconds since launch.
+    pub elapsed: f64,
+    pub level: Level,
+    /// Module path, e.g. `wasiw_core::graph`.
+    pub target: String,
+    pub message: String,
+}
+
+impl LogEntry {
+    /// The crate and first module of the target, e.g. `wasiw_core::graph`, to filter by.
+    pub fn module(&self) -> String {
+        self.target
+            .split("::")
+            .take(2)
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+}
+
+pub fn path() -> Option<PathBuf> {
+    config::data_file(LOG_FILE_NAME)
+}
+
+/// Installs the subscriber; call before launching so Dioxus doesn't install its own. Without a
+/// writable data directory only the in-memory log is kept.
+pub fn init() {
+    STARTED.get_or_init(Instant::now);
+    let filter = Targets::new()
+        .with_default(LevelFilter::WARN)
+        .with_targets(OWN_TARGETS.map(|target| (target, LevelFilter::DEBUG)));
+    let file = path().and_then(|path| {
+        fs::create_dir_all(path.parent()?).ok()?;
+        File::create(path).ok()
+    });
+    let file_layer = file.map(|file| {
+        tracing_subscriber::fmt::layer()
+            .with_ansi(false)
+            .with_span_events(FmtSpan::CLOSE)
+            .with_writer(Mutex::new(file))
+    });
+    let _ = tracing_subscriber::registry()
+        .with(file_layer)
+        .with(MemoryLayer)
+        .with(filter)
+        .try_init();
+}
+
+/// A copy of the kept entries, oldest first.
+pub fn entries() -> Vec<LogEntry> {
+    ENTRIES
+        .lock()
+        .map(|entries| entries.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+fn push(level: Level, target: &str, message: String) {
+    let elapsed = STARTED
+        .get()
+        .map_or(0.0, |started| started.elapsed().as_secs_f64());
+    if let Ok(mut entries) = ENTRIES.lock() {
+        if entries.len() == CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(LogEntry {
+            elapsed,
+            level,
+            target: target.to_string(),
+            message,
+        });
+    }
+}
+
+/// Keeps events, and how long each span took, for the log panel.
+struct MemoryLayer;
+
+/// When a span was created, and its fields, stored in the span's extensions.
+struct SpanTiming {
+    started: Instant,
+    fields: String,
+}
+
+impl<S> Layer<S> for MemoryLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        attrs.record(&mut visitor);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanTiming {
+                started: Instant::now(),
+                fields: visitor.fields,
+            });
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        push(*metadata.level(), metadata.target(), visitor.finish());
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let extensions = span.extensions();
+        let Some(timing) = extensions.get::<SpanTiming>() else {
+            return;
+        };
+        let metadata = span.metadata();
+        let millis = timing.started.elapsed().as_secs_f64() * 1000.0;
+        push(
+            *metadata.level(),
+            metadata.target(),
+            format!("{} took {millis:.1} ms{}", metadata.name(), timing.fields),
+        );
+    }
+}
+
+/// Collects an event's message and its other fields as ` key=value`.
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl MessageVisitor {
+    fn finish(self) -> String {
+        format!("{}{}", self.message, self.fields)
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            let _ = write!(self.fields, " {}={value}", field.name());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{value:?}");
+        } else {
+            let _ = write!(self.fields, " {}={value:?}", field.name());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_and_span_timings_are_kept_with_their_fields() {
+        let subscriber = tracing_subscriber::registry().with(MemoryLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(target: "wasiw_core::graph::build", nodes = 12, "built graph");
+            let span = tracing::debug_span!(target: "wasiw_core::layout", "layout", kind = "rings");
+            drop(span.entered());
+        });
+        let kept: Vec<LogEntry> = entries()
+            .into_iter()
+            .filter(|entry| entry.target.starts_with("wasiw_core::"))
+            .collect();
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[0].level, Level::INFO);
+        assert_eq!(kept[0].message, "built graph nodes=12");
+        assert_eq!(kept[0].module(), "wasiw_core::graph");
+        assert_eq!(kept[1].level, Level::DEBUG);
+        assert!(
+            kept[1].message.starts_with("layout took ")
+                && kept[1].message.ends_with(" ms kind=rings"),
+            "{}",
+            kept[1].message
+        );
+
+        for index in 0..=CAPACITY {
+            push(Level::TRACE, "flood", index.to_string());
+        }
+        let entries = entries();
+        assert_eq!(entries.len(), CAPACITY);
+        assert_eq!(entries.last().unwrap().message, CAPACITY.to_string());
+    }
+}
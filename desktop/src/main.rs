@@ -1,56 +1,225 @@
+use dioxus::html::HasFileData;
 use dioxus::prelude::*;
-use serde::Deserialize;
-use std::collections::HashMap;
-use std::fs;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-const WIDTH: f32 = 1040.0;
-const HEIGHT: f32 = 760.0;
-const MAX_RENDERED_EDGES: usize = 1400;
+mod anonymize;
+mod commands;
+mod config;
+mod crash;
+mod fetch_queue;
+mod graph_cache;
+mod idmap;
+mod import;
+mod logging;
+mod menu;
+mod metadata;
+mod notifications;
+mod ollama;
+mod pages;
+mod posters;
+mod profiles;
+mod recommend_export;
+mod report;
+mod routes;
+mod seasonal;
+#[cfg(feature = "semantic")]
+mod semantic;
+mod session;
+mod state;
+#[cfg(feature = "sqlite")]
+mod store;
+mod streaming;
+mod sync;
+mod tasks;
+mod tray;
+mod undo;
+mod watch;
+mod window_state;
+
+use anonymize::{AnonymizeOptions, ScoreNoise};
+use commands::Command;
+use config::AppConfig;
+use dioxus::desktop::muda::MenuEvent;
+use dioxus::desktop::tao::event::{Event as WindowLoopEvent, WindowEvent};
+use dioxus::desktop::tao::window::WindowBuilder;
+use dioxus::desktop::trayicon::TrayIcon;
+use dioxus::history::{History, MemoryHistory};
+use dioxus::router::components::HistoryProvider;
+use fetch_queue::FetchQueue;
+use import::panel::CsvMappingDialog;
+use metadata::{AnimeMetadata, MetadataCache};
+use notifications::Notified;
+use profiles::Profiles;
+use routes::Route;
+use seasonal::Season;
+use session::Session;
+use state::{AppState, Shell};
+use streaming::AvailabilityCache;
+use sync::dialog::ConnectDialog;
+use sync::SyncSite;
+use tasks::{TaskKind, TaskList};
+use undo::{GraphView, UndoStack};
+#[cfg(feature = "parquet")]
+use wasiw_core::columnar;
+use wasiw_core::content::{ContentRecommendation, TasteProfile};
+use wasiw_core::graph_export;
+use wasiw_core::layout;
+use wasiw_core::memory::{self, MemoryUsage};
+use wasiw_core::merge::Merger;
+use wasiw_core::recommend;
+use wasiw_core::relations::Relations;
+use wasiw_core::sampling::Sampling;
+use wasiw_core::synthetic::{self, SyntheticOptions};
+use wasiw_core::tribes::TribeReport;
+use wasiw_core::{
+    compression, schema, Dataset, DatasetError, GraphFilter, GraphMode, GraphModel, GraphSettings,
+    MergeStrategy, QualityReport, UserRatings, Viewport,
+};
+use wasiw_ui::accessibility::{focus_on_mount, GraphOutline};
+use wasiw_ui::camera::{pinch_metrics, Camera, Gesture, ViewBox, LONG_PRESS_MS, LONG_PRESS_SLOP};
+use wasiw_ui::canvas::{
+    ContextMenu, EdgeHover, EdgeTooltip, EdgeTooltipCard, GraphCanvas, NodeContextMenu, Scene,
+    SUPER_NODE_ZOOM,
+};
+use wasiw_ui::controls::SearchBox;
+use wasiw_ui::cull::{use_cull_index, CullIndex};
+use wasiw_ui::details::{NodeDetails, NodeDetailsData, SeasonalPicks, DETAIL_NEIGHBORS};
+use wasiw_ui::i18n::{self, tr};
+use wasiw_ui::keymap::{Keymap, Shortcut, ShortcutSheet};
+use wasiw_ui::navigation::{self, Direction, TabCursor};
+use wasiw_ui::palette::CommandPalette;
+use wasiw_ui::render::{Frame, GraphRenderer, Highlight, SvgRenderer};
+use wasiw_ui::theme::{self, ThemeVariant};
+use window_state::WindowState;
+
+/// How long a layout or filter change takes to animate into place.
+const TRANSITION_MS: f32 = 450.0;
+const TRANSITION_FRAME_MS: u64 = 16;
+const APP_TITLE: &str = "What Anime Should I Watch";
+/// How often the open log panel picks up new entries.
+const LOG_REFRESH_MS: u64 = 500;
+/// How far the zoom shortcuts zoom per press.
+const ZOOM_STEP: f32 = 1.25;
+/// Focuses the search box once the Graph tab has rendered.
+const FOCUS_SEARCH_JS: &str = "requestAnimationFrame(() => requestAnimationFrame(() => \
+    document.querySelector('.search input')?.focus()));";
 
 fn main() {
-    dioxus::launch(App);
+    logging::init();
+    crash::install();
+    let config = AppConfig::load();
+    // Before the menu bar is built, so it is in the same language.
+    i18n::set_locale(config.locale());
+    let recent_files = config.recent_files;
+    let window = WindowState::load().apply(WindowBuilder::new().with_title(APP_TITLE));
+    // Without a menu bar the same actions stay reachable from the side panel and shortcuts.
+    let menu = menu::build(&recent_files)
+        .inspect_err(|err| tracing::warn!("could not build the menu bar: {err}"))
+        .ok();
+    dioxus::LaunchBuilder::desktop()
+        .with_cfg(
+            dioxus::desktop::Config::new()
+                .with_window(window)
+                .with_menu(menu),
+        )
+        .launch(Root);
 }
 
+/// The app inside an error boundary: an error or panic while rendering replaces it with
+/// [`CrashDialog`] instead of closing the window.
 #[component]
-fn App() -> Element {
-    let graph = build_graph(load_dataset());
+fn Root() -> Element {
+    // Reopen the tab that was open when the window last closed.
+    let initial_tab = use_hook(|| {
+        Session::restore(AppConfig::load().fresh_start)
+            .map(|session| session.tab)
+            .filter(|tab| tab.parse::<Route>().is_ok())
+            .unwrap_or_else(|| Route::Graph {}.to_string())
+    });
+    rsx! {
+        ErrorBoundary {
+            handle_error: |errors: ErrorContext| rsx! {
+                CrashDialog { errors }
+            },
+            HistoryProvider {
+                history: move |_| {
+                    Rc::new(MemoryHistory::with_initial_path(initial_tab.clone())) as Rc<dyn History>
+                },
+                Router::<Route> {}
+            }
+        }
+    }
+}
 
+/// What went wrong, the dataset file that was being read, and ways to carry on.
+#[component]
+fn CrashDialog(errors: ErrorContext) -> Element {
+    let panic = crash::last_panic();
+    let message = match (&panic, errors.error()) {
+        (Some(panic), _) => panic.message.clone(),
+        (None, Some(error)) => error.to_string(),
+        (None, None) => "unknown error".to_string(),
+    };
+    let location = panic.and_then(|panic| panic.location);
+    let file = crash::current_file().map(|file| file.display().to_string());
+    let config = AppConfig::load();
+    let theme_class = config
+        .theme
+        .resolve(os_theme(dioxus::desktop::window().theme()))
+        .class();
+    let scale_class = config.ui_scale.class();
+    let retry = {
+        let errors = errors.clone();
+        move || {
+            crash::clear_panic();
+            errors.clear_errors();
+        }
+    };
+    let start_fresh = {
+        let retry = retry.clone();
+        move |_| {
+            crash::start_fresh();
+            retry();
+        }
+    };
     rsx! {
-        style { {APP_CSS} }
-        main { class: "app",
-            section { class: "panel",
-                h1 { "What Anime Should I Watch" }
-                p { class: "muted", "Desktop Dioxus graph from anonymized user ratings." }
-                div { class: "stats",
-                    StatRow { label: "Users", value: graph.user_count.to_string() }
-                    StatRow { label: "Anime", value: graph.anime_count.to_string() }
-                    StatRow { label: "Nodes", value: graph.nodes.len().to_string() }
-                    StatRow { label: "Edges (rendered)", value: graph.edges.len().to_string() }
-                }
-                p { class: "tiny", "For readability, the SVG caps visible edges at 1,400." }
-            }
-            section { class: "canvas-wrap",
-                svg {
-                    width: "{WIDTH}",
-                    height: "{HEIGHT}",
-                    view_box: "0 0 {WIDTH} {HEIGHT}",
-                    for edge in graph.edges.iter().take(MAX_RENDERED_EDGES) {
-                        line {
-                            x1: "{edge.x1}",
-                            y1: "{edge.y1}",
-                            x2: "{edge.x2}",
-                            y2: "{edge.y2}",
-                            stroke: "{edge.color}",
-                            stroke_width: "{edge.stroke_width}",
-                            stroke_opacity: "0.55"
+        style { {theme::APP_CSS} }
+        main { class: "app {theme_class} {scale_class}",
+            div { class: "dialog-backdrop",
+                div {
+                    class: "dialog",
+                    role: "dialog",
+                    aria_modal: "true",
+                    aria_label: tr!("crash-title"),
+                    tabindex: "-1",
+                    onmounted: focus_on_mount,
+                    strong { {tr!("crash-title")} }
+                    p { "{message}" }
+                    if let Some(location) = location {
+                        p { class: "tiny", {tr!("crash-location", location = location)} }
+                    }
+                    if let Some(file) = file {
+                        p { class: "tiny",
+                            {tr!("crash-last-file")}
+                            " "
+                            code { "{file}" }
                         }
                     }
-                    for node in &graph.nodes {
-                        circle {
-                            cx: "{node.x}",
-                            cy: "{node.y}",
-                            r: "{node.radius}",
-                            fill: "{node.color}"
+                    p { class: "tiny",
+                        {tr!("crash-fresh-hint")}
+                    }
+                    div { class: "row",
+                        button { class: "action", onclick: move |_| retry(), {tr!("crash-retry")} }
+                        button { class: "action", onclick: start_fresh, {tr!("crash-start-fresh")} }
+                        button {
+                            class: "action",
+                            onclick: move |_| dioxus::desktop::window().close(),
+                            {tr!("crash-quit")}
                         }
                     }
                 }
@@ -60,317 +229,2882 @@ fn App() -> Element {
 }
 
 #[component]
-fn StatRow(label: String, value: String) -> Element {
+fn App() -> Element {
+    // Startup loading yields both the dataset and any failed sources; split them into signals.
+    // The last session, unless starting fresh; its dataset is opened ahead of the defaults.
+    let restored = use_hook(|| Session::restore(AppConfig::load().fresh_start));
+    let startup = use_hook(|| {
+        let session_dataset = restored
+            .as_ref()
+            .and_then(|session| session.dataset.as_deref());
+        Rc::new(RefCell::new(load_startup_dataset(
+            &AppConfig::load(),
+            session_dataset,
+        )))
+    });
+    // Unreadable profiles are set aside and reported once the status line exists.
+    let startup_profiles = use_hook(|| Rc::new(RefCell::new(Profiles::load())));
+    let profiles = use_signal(|| {
+        startup_profiles
+            .borrow_mut()
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    });
+    let mut dataset = use_signal(|| {
+        let mut dataset =
+            std::mem::replace(&mut startup.borrow_mut().dataset, Dataset::new(Vec::new()));
+        profiles.peek().apply(&mut dataset);
+        dataset
+    });
+    let mut load_failures = use_signal(|| {
+        let failures = startup.borrow().failures.clone();
+        for failure in &failures {
+            tracing::warn!(source = %failure.source, "could not load: {}", failure.message);
+        }
+        failures
+    });
+    let mut watched_path = use_signal(|| startup.borrow().path.clone());
+    let mut load_report = use_signal(|| std::mem::take(&mut startup.borrow_mut().report));
+    let mut quality_report = use_signal(|| {
+        let report = load_report.peek().clone();
+        (!report.is_clean()).then_some(report)
+    });
+    // Cached metadata is available right away; fetching what's missing is started from the menu.
+    let mut anime_metadata = use_signal(MetadataCache::load);
+    let mut fetch_queue = use_signal(FetchQueue::load);
+    // Worked out again whenever fetched metadata comes in.
+    let relations = use_memo(move || anime_metadata.read().relations());
+    // Opening the same data with the same settings as last time reuses the graph built then.
+    let mut graph = use_signal(|| {
+        let config = AppConfig::load();
+        let settings = GraphSettings {
+            sampling: config.sampling,
+            mode: config.graph_mode,
+            similar_neighbors: config.similar_neighbors(),
+            layout: config.layout,
+            layout_seed: config.layout_seed,
+            filter: restored
+                .as_ref()
+                .map_or_else(GraphFilter::default, |session| session.filter),
+            viewport: Viewport::default(),
+        };
+        let mut graph = graph_cache::build(&dataset.peek(), settings, |anime_id, mode| {
+            anime_metadata.peek().facets(anime_id, mode)
+        });
+        anime_metadata
+            .peek()
+            .label_titles(&mut graph, config.titles.graph());
+        graph
+    });
+    // Filed over the frames after each change, so pans and zooms stay quick meanwhile.
+    let cull_index = use_cull_index(graph.into());
+    let mut viewport = use_signal(Viewport::default);
+    let fullscreen = use_signal(|| false);
+    let restored_session = restored.clone().unwrap_or_default();
+    let mut search = use_signal(|| restored_session.search.clone());
+    let mut camera = use_signal(|| restored_session.camera);
+    let mut gesture = use_signal(Gesture::default);
+    let mut press_token = use_signal(|| 0_u64);
+    let mut context_menu = use_signal(|| None::<ContextMenu>);
+    let mut canvas_element = use_signal(|| None::<Rc<MountedData>>);
+    let mut canvas_origin = use_signal(|| (0.0_f32, 0.0_f32));
+    let mut config = use_signal(AppConfig::load);
+    let mut expanded_communities = use_signal(HashSet::<usize>::new);
+    let compare_with = use_signal(|| None::<u32>);
+    let compare_user_with = use_signal(|| None::<String>);
+    let mut tribes = use_signal(|| None::<TribeReport>);
+    let mut memory = use_signal(MemoryUsage::default);
+    let mut hovered_edge = use_signal(|| None::<EdgeHover>);
+    let mut selected = use_signal(|| {
+        let node_id = restored_session.selected.as_ref()?;
+        graph
+            .peek()
+            .nodes
+            .iter()
+            .position(|node| node.id == *node_id)
+    });
+    let mut details_open =
+        use_signal(|| restored_session.details_open && selected.peek().is_some());
+    // Whether the side panel shows the settings instead of the graph controls.
+    // The open tab, mirrored from the router for the saved session.
+    let route = use_route::<Route>();
+    let mut tab = use_signal(|| route.clone());
+    use_effect(use_reactive((&route,), move |(route,)| tab.set(route)));
+    let mut log_open = use_signal(|| false);
+    let mut tab_cursor = use_signal(|| None::<TabCursor>);
+    // The configured id map loads with the app; a broken file only costs a status message.
+    let startup_ids = use_hook(|| Rc::new(RefCell::new(load_startup_id_map())));
+    let mut import_status = use_signal(|| {
+        let profiles_error = startup_profiles.borrow().as_ref().err().cloned();
+        startup_ids
+            .borrow()
+            .as_ref()
+            .err()
+            .cloned()
+            .or(profiles_error)
+    });
+    let mut id_map = use_signal(|| {
+        let loaded = startup_ids
+            .borrow_mut()
+            .as_mut()
+            .ok()
+            .and_then(Option::take);
+        loaded.map(Rc::new)
+    });
+    // Imports, fetches, reloads, searches and layouts running in the background.
+    let mut tasks = use_signal(TaskList::default);
+    // This season's anime, fetched when the details card's season tab first asks for it.
+    let mut season = use_signal(|| None::<Season>);
+    let mut availability = use_signal(AvailabilityCache::load);
+    // Anime whose streaming services were looked up this session, found or not.
+    let mut streaming_requested = use_signal(HashSet::<u32>::new);
+    // Blurbs from the local model by user node id and anime id; `None` while being written.
+    let mut blurbs = use_signal(HashMap::<(String, u32), Option<String>>::new);
+    // The sentence encoder and synopsis index live on worker threads between searches.
+    #[cfg(feature = "semantic")]
+    let semantic_engine = use_hook(|| {
+        std::sync::Arc::new(std::sync::Mutex::new(semantic::SemanticEngine::default()))
+    });
+    #[cfg(feature = "semantic")]
+    let mut semantic_results = use_signal(|| None::<(String, Vec<semantic::SemanticMatch>)>);
+    // Poster `data:` URIs by anime id; `None` while loading or when there is no poster.
+    let mut poster_uris = use_signal(HashMap::<u32, Option<Rc<str>>>::new);
+    let csv_table = use_signal(|| None::<import::csv::CsvTable>);
+    let mut merge_files = use_signal(|| None::<Vec<PathBuf>>);
+    let mut anonymize_open = use_signal(|| false);
+    let mut generate_open = use_signal(|| false);
+    let accounts = use_signal(sync::Accounts::load);
+    let mut connect_site = use_signal(|| None::<SyncSite>);
+    let mut dropping = use_signal(|| false);
+    let mut system_theme = use_signal(|| os_theme(dioxus::desktop::window().theme()));
+
+    dioxus::desktop::use_wry_event_handler(move |event, _| {
+        if let WindowLoopEvent::WindowEvent {
+            event: WindowEvent::ThemeChanged(theme),
+            ..
+        } = event
+        {
+            system_theme.set(os_theme(*theme));
+        }
+    });
+
+    // A window restored onto a monitor that is gone is moved back on screen.
+    use_hook(|| WindowState::load().keep_on_screen(&dioxus::desktop::window().window));
+
+    // The session and window placement are saved when the window closes, including through
+    // File → Quit, and restored at the next launch.
+    let save_session = move || {
+        let model = graph.peek();
+        let session = Session {
+            dataset: watched_path.peek().clone(),
+            tab: tab.peek().to_string(),
+            camera: *camera.peek(),
+            filter: model.filter,
+            search: search.peek().clone(),
+            selected: selected
+                .peek()
+                .and_then(|idx| Some(model.nodes.get(idx)?.id.clone())),
+            details_open: *details_open.peek(),
+        };
+        // Best effort: a session that fails to save just isn't restored.
+        let _ = session.save();
+    };
+    let save_on_exit = move || {
+        save_session();
+        let window = dioxus::desktop::window();
+        let _ = WindowState::capture(&window.window, &WindowState::load()).save();
+    };
+    dioxus::desktop::use_wry_event_handler(move |event, _| {
+        if matches!(
+            event,
+            WindowLoopEvent::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } | WindowLoopEvent::LoopDestroyed
+        ) {
+            save_on_exit();
+        }
+    });
+
+    let mut transition = use_signal(|| 0_u64);
+
+    // Moves nodes from `from` to `to` unless transition `id` has been superseded.
+    let tween_positions = move |from: Vec<(f32, f32)>, to: Vec<(f32, f32)>, id: u64| {
+        spawn(async move {
+            let started = Instant::now();
+            loop {
+                if *transition.peek() != id {
+                    return;
+                }
+                let t = started.elapsed().as_secs_f32() * 1000.0 / TRANSITION_MS;
+                graph
+                    .write()
+                    .set_positions(&layout::interpolate(&from, &to, layout::ease(t)));
+                if t >= 1.0 {
+                    return;
+                }
+                tokio::time::sleep(Duration::from_millis(TRANSITION_FRAME_MS)).await;
+            }
+        });
+    };
+
+    // Computes the layout for `target` as a background job, then moves nodes from their
+    // current positions to it over a short tween. Starting a new transition cancels the one in
+    // flight, and a layout finished for a graph that has since been replaced is dropped.
+    let mut animate_layout = move |target: Viewport| {
+        let (nodes, communities, kind, seed) = {
+            let mut graph = graph.write();
+            graph.layout_viewport = target;
+            (
+                graph.nodes.clone(),
+                graph.communities.clone(),
+                graph.layout,
+                graph.layout_seed,
+            )
+        };
+        let id = *transition.peek() + 1;
+        transition.set(id);
+        tasks.write().cancel_kind(TaskKind::Layout);
+
+        let name = format!("Laying out {} nodes", nodes.len());
+        tasks::spawn_blocking(
+            tasks,
+            TaskKind::Layout,
+            name,
+            move |_| layout::compute(&nodes, &communities, kind, seed, target),
+            move |to| {
+                let from = graph.peek().positions();
+                if let Some(to) = to.filter(|to| to.len() == from.len()) {
+                    tween_positions(from, to, id);
+                }
+            },
+        );
+    };
+
+    let on_canvas_resize = move |evt: Event<ResizeData>| {
+        let Ok(size) = evt.get_content_box_size() else {
+            return;
+        };
+        let next = Viewport {
+            width: size.width as f32,
+            height: size.height as f32,
+            scale_factor: dioxus::desktop::window().scale_factor() as f32,
+        };
+        if next.width < 1.0 || next.height < 1.0 {
+            return;
+        }
+
+        let laid_out_for = graph.read().layout_viewport;
+        if next.differs_significantly(&laid_out_for) {
+            animate_layout(next);
+        }
+        viewport.set(next);
+
+        // The canvas moves when the side panel collapses, so re-measure its page offset.
+        spawn(async move {
+            let Some(element) = canvas_element() else {
+                return;
+            };
+            if let Ok(rect) = element.get_client_rect().await {
+                canvas_origin.set((rect.origin.x as f32, rect.origin.y as f32));
+            }
+        });
+    };
+
+    // Converts a client-space point into canvas-local pixels.
+    let to_canvas = move |x: f64, y: f64| {
+        let (origin_x, origin_y) = canvas_origin();
+        (x as f32 - origin_x, y as f32 - origin_y)
+    };
+    let fitted_view = move || ViewBox::fit(&graph.read().nodes, viewport());
+
+    let on_wheel = move |evt: Event<WheelData>| {
+        evt.prevent_default();
+        let delta = evt.delta().strip_units();
+        let point = evt.client_coordinates();
+        let (px, py) = to_canvas(point.x, point.y);
+        let factor = (-delta.y as f32 * 0.0015).exp();
+        camera
+            .write()
+            .zoom_about(factor, px, py, fitted_view(), viewport());
+    };
+
+    let on_touch_start = move |evt: Event<TouchData>| {
+        let touches = evt.touches();
+        if touches.len() == 2 {
+            evt.prevent_default();
+            let a = touches[0].client_coordinates();
+            let b = touches[1].client_coordinates();
+            let (distance, mid_x, mid_y) = pinch_metrics(to_canvas(a.x, a.y), to_canvas(b.x, b.y));
+            gesture.set(Gesture::Pinch {
+                distance,
+                mid_x,
+                mid_y,
+            });
+        }
+    };
+
+    let on_touch_move = move |evt: Event<TouchData>| {
+        let touches = evt.touches();
+        match gesture() {
+            Gesture::Pinch {
+                distance,
+                mid_x,
+                mid_y,
+            } if touches.len() >= 2 => {
+                evt.prevent_default();
+                let a = touches[0].client_coordinates();
+                let b = touches[1].client_coordinates();
+                let (next_distance, next_mid_x, next_mid_y) =
+                    pinch_metrics(to_canvas(a.x, a.y), to_canvas(b.x, b.y));
+                let fitted = fitted_view();
+                let viewport = viewport();
+                let mut cam = camera.write();
+                cam.pan_by_pixels(next_mid_x - mid_x, next_mid_y - mid_y, fitted, viewport);
+                if distance > 0.0 {
+                    cam.zoom_about(
+                        next_distance / distance,
+                        next_mid_x,
+                        next_mid_y,
+                        fitted,
+                        viewport,
+                    );
+                }
+                gesture.set(Gesture::Pinch {
+                    distance: next_distance,
+                    mid_x: next_mid_x,
+                    mid_y: next_mid_y,
+                });
+            }
+            Gesture::Press { x, y, .. } => {
+                if let Some(touch) = touches.first() {
+                    let point = touch.client_coordinates();
+                    let (px, py) = to_canvas(point.x, point.y);
+                    if (px - x).hypot(py - y) > LONG_PRESS_SLOP {
+                        gesture.set(Gesture::Idle);
+                    }
+                }
+            }
+            _ => {}
+        }
+    };
+
+    let on_touch_end = move |evt: Event<TouchData>| {
+        if evt.touches().len() < 2 {
+            gesture.set(Gesture::Idle);
+        }
+    };
+
+    let mut on_node_touch = move |node: usize, evt: Event<TouchData>| {
+        let touches = evt.touches();
+        if touches.len() != 1 {
+            return;
+        }
+        let point = touches[0].client_coordinates();
+        let (x, y) = to_canvas(point.x, point.y);
+        let token = press_token() + 1;
+        press_token.set(token);
+        gesture.set(Gesture::Press { node, x, y, token });
+
+        spawn(async move {
+            tokio::time::sleep(Duration::from_millis(LONG_PRESS_MS)).await;
+            if let Gesture::Press {
+                node,
+                x,
+                y,
+                token: pending,
+            } = gesture()
+            {
+                if pending == token {
+                    gesture.set(Gesture::Idle);
+                    context_menu.set(Some(ContextMenu { node, x, y }));
+                }
+            }
+        });
+    };
+
+    let mut on_node_context_menu = move |node: usize, evt: Event<MouseData>| {
+        evt.prevent_default();
+        let point = evt.client_coordinates();
+        let (x, y) = to_canvas(point.x, point.y);
+        context_menu.set(Some(ContextMenu { node, x, y }));
+    };
+
+    // Zooming back in shows individual nodes everywhere, so forget per-cluster expansions.
+    use_effect(move || {
+        if camera().zoom >= SUPER_NODE_ZOOM && !expanded_communities.peek().is_empty() {
+            expanded_communities.write().clear();
+        }
+    });
+
+    let app = AppState {
+        dataset,
+        profiles,
+        graph,
+        config,
+        accounts,
+        tasks,
+        id_map,
+        metadata: anime_metadata,
+        relations,
+        import_status,
+        selected,
+        details_open,
+        camera,
+        viewport,
+        transition,
+        tab_cursor,
+        context_menu,
+        hovered_edge,
+        expanded_communities,
+        compare_with,
+        compare_user_with,
+        tribes,
+        load_report,
+        memory,
+    };
+
+    // Tribes found in one dataset say nothing about the next.
+    use_effect(move || {
+        dataset.read();
+        tribes.set(None);
+    });
+
+    // Keeps the memory estimate current and, over the budget, drops the session's caches
+    // first and then rebuilds the graph from a sample of users.
+    use_effect(move || {
+        let caches = cache_bytes(&poster_uris.read(), &blurbs.read(), tribes.read().as_ref());
+        let usage = MemoryUsage::new(&dataset.read(), &graph.read(), caches);
+        memory.set(usage);
+        let Some(budget) = config.read().memory_budget() else {
+            return;
+        };
+        if usage.total() <= budget {
+            return;
+        }
+        // Outside the effect, so clearing what it reads doesn't happen mid-run.
+        spawn(async move {
+            if usage.caches > 0 {
+                poster_uris.write().clear();
+                blurbs.write().clear();
+                tribes.set(None);
+                import_status.set(Some(tr("memory-dropped-caches")));
+                return;
+            }
+            let current = graph.peek().sampling;
+            let Some(sampling) = memory::fit_sampling(&dataset.peek(), current, budget) else {
+                return;
+            };
+            tracing::info!(
+                ?sampling,
+                budget,
+                "sampling to stay within the memory budget"
+            );
+            graph.write().sampling = sampling;
+            app.rebuild_graph();
+            let users = sampling.count().unwrap_or_default();
+            import_status.set(Some(tr!("memory-sampled", users = users)));
+        });
+    });
+
+    let on_canvas_key = move |evt: Event<KeyboardData>| {
+        let direction = match evt.key() {
+            Key::ArrowUp => Some(Direction::Up),
+            Key::ArrowDown => Some(Direction::Down),
+            Key::ArrowLeft => Some(Direction::Left),
+            Key::ArrowRight => Some(Direction::Right),
+            _ => None,
+        };
+
+        if let Some(direction) = direction {
+            evt.prevent_default();
+            let next = {
+                let graph = graph.read();
+                match selected() {
+                    Some(from) => navigation::nearest_in_direction(&graph, from, direction),
+                    None => {
+                        let view = camera().apply(fitted_view());
+                        navigation::nearest_to(
+                            &graph,
+                            view.x + view.width / 2.0,
+                            view.y + view.height / 2.0,
+                        )
+                    }
+                }
+            };
+            if let Some(next) = next {
+                tab_cursor.set(None);
+                app.select_node(next);
+            }
+            return;
+        }
+
+        match evt.key() {
+            Key::Tab => {
+                evt.prevent_default();
+                let step = navigation::tab_step(
+                    &graph.read(),
+                    selected(),
+                    tab_cursor(),
+                    evt.modifiers().shift(),
+                );
+                if let Some((cursor, next)) = step {
+                    tab_cursor.set(cursor);
+                    app.select_node(next);
+                }
+            }
+            Key::Enter if selected().is_some() => details_open.set(true),
+            Key::Escape if selected().is_some() || details_open() => {
+                // Handled here so the same Esc does not also leave fullscreen.
+                evt.stop_propagation();
+                selected.set(None);
+                details_open.set(false);
+                tab_cursor.set(None);
+            }
+            _ => {}
+        }
+    };
+
+    // Re-reads the watched file after it changed on disk. Reading and building run as a
+    // background job with progress in the task list, so large datasets don't freeze the
+    // window; the current graph stays interactive until the new one is ready. The camera is
+    // left alone, imported profiles are carried over and the selection is restored if its
+    // node still exists. A change while a reload is building is held until that one is done,
+    // then read once, however many more arrive meanwhile: building can't be interrupted, so
+    // starting another would only stack builds on the worker threads.
+    let mut queued_reload = use_signal(|| None::<PathBuf>);
+    let mut reload_dataset = move |path: PathBuf| {
+        if tasks.peek().is_running(TaskKind::Reload) {
+            tracing::debug!(path = %path.display(), "reload queued behind the running one");
+            queued_reload.set(Some(path));
+            return;
+        }
+        let local: Vec<UserRatings> = dataset
+            .peek()
+            .users
+            .iter()
+            .filter(|user| user.user_id.starts_with(import::LOCAL_PROFILE_PREFIX))
+            .cloned()
+            .collect();
+        let settings = graph.peek().settings();
+        // Facet graphs read fetched metadata off the UI thread.
+        let metadata = (settings.mode != GraphMode::Ratings).then(|| anime_metadata.peek().clone());
+        let duplicates = config.peek().duplicates;
+        let source = path.clone();
+        tasks::spawn_blocking(
+            tasks,
+            TaskKind::Reload,
+            format!("Reloading {}", path.display()),
+            move |worker| -> Result<_, DatasetError> {
+                let (mut next, report) = read_dataset(&source, duplicates)?;
+                if worker.is_cancelled() {
+                    return Ok(None);
+                }
+                for user in local {
+                    next.merge_profile(user);
+                }
+                let facets = |anime_id, mode| {
+                    metadata
+                        .as_ref()
+                        .map(|metadata: &MetadataCache| metadata.facets(anime_id, mode))
+                        .unwrap_or_default()
+                };
+                let graph = settings.build_with_facets(next.clone(), facets, |progress| {
+                    worker.progress(progress.to_string());
+                });
+                Ok(Some(Box::new((next, report, graph))))
+            },
+            move |built| {
+                let (next, report, next_graph) = match built {
+                    Some(Ok(Some(built))) => *built,
+                    Some(Ok(None)) => return,
+                    Some(Err(err)) => {
+                        tracing::warn!(path = %path.display(), "reload failed: {err}");
+                        import_status
+                            .set(Some(format!("Could not reload {}: {err}", path.display())));
+                        return;
+                    }
+                    None => {
+                        import_status.set(Some(format!(
+                            "Could not reload {}: reading it failed unexpectedly; see the log.",
+                            path.display()
+                        )));
+                        return;
+                    }
+                };
+                let selected_id = selected().map(|node| graph.peek().nodes[node].id.clone());
+                let reopen_details = details_open();
+
+                let users = next.users.len();
+                tracing::info!(path = %path.display(), users, "reloaded dataset");
+                load_report.set(report.clone());
+                quality_report.set((!report.is_clean()).then_some(report));
+                dataset.set(next);
+                app.install_graph(next_graph);
+                if let Some(id) = selected_id {
+                    let found = graph.peek().nodes.iter().position(|node| node.id == id);
+                    selected.set(found);
+                    details_open.set(reopen_details && found.is_some());
+                }
+                import_status.set(Some(format!(
+                    "Reloaded {} after it changed ({users} users).",
+                    path.display()
+                )));
+            },
+        );
+    };
+
+    // One watch task for the file the current dataset came from; replaced whenever it changes.
+    let mut watch_task = use_signal(|| None::<dioxus::core::Task>);
+    use_effect(move || {
+        let path = watched_path();
+        if let Some(task) = watch_task.write().take() {
+            task.cancel();
+        }
+        let Some(path) = path else {
+            return;
+        };
+        let task = spawn(async move {
+            let mut watcher = match watch::DatasetWatcher::new(&path) {
+                Ok(watcher) => watcher,
+                Err(err) => {
+                    import_status.set(Some(format!(
+                        "Not watching {} for changes: {err}",
+                        path.display()
+                    )));
+                    return;
+                }
+            };
+            while watcher.changed().await {
+                reload_dataset(path.clone());
+            }
+        });
+        watch_task.set(Some(task));
+    });
+
+    use_effect(move || {
+        if tasks.read().is_running(TaskKind::Reload) {
+            return;
+        }
+        if let Some(path) = queued_reload.write().take() {
+            reload_dataset(path);
+        }
+    });
+
+    // Opened files are routed by extension: a dataset JSON replaces the corpus, while MAL XML
+    // and CSV exports go through their importers.
+    let mut open_file = move |path: PathBuf| {
+        let extension = compression::inner_extension(&path).unwrap_or_default();
+        let supported = menu::file_filters()
+            .iter()
+            .any(|(_, extensions)| extensions.contains(&extension.as_str()));
+        if !supported {
+            import_status.set(Some(format!(
+                "Open a dataset .json, a MyAnimeList .xml export or a .csv, optionally .gz or .zst compressed ({} isn't supported).",
+                path.display()
+            )));
+            return;
+        }
+        if path.is_file() {
+            let mut config = config.write();
+            config.remember_file(&path);
+            menu::set_recent(&config.recent_files);
+            let _ = config.save();
+        }
+        match extension.as_str() {
+            "json" | "parquet" => match read_dataset(&path, config.peek().duplicates) {
+                Ok((next, report)) => {
+                    load_failures.write().clear();
+                    let users = next.users.len();
+                    tracing::info!(path = %path.display(), users, "opened dataset");
+                    let dropped = if report.is_clean() {
+                        String::new()
+                    } else {
+                        format!(" {}", report.summary())
+                    };
+                    load_report.set(report.clone());
+                    quality_report.set((!report.is_clean()).then_some(report));
+                    // Best effort: the dataset is still usable this session if storing fails.
+                    #[cfg(feature = "sqlite")]
+                    let _ =
+                        store::RatingStore::open().and_then(|mut store| store.save_dataset(&next));
+                    dataset.set(next);
+                    app.rebuild_graph();
+                    watched_path.set(Some(path.clone()));
+                    import_status.set(Some(format!(
+                        "Loaded {users} users from {}.{dropped}",
+                        path.display()
+                    )));
+                }
+                Err(err) => {
+                    tracing::warn!(path = %path.display(), "could not open dataset: {err}");
+                    load_failures.write().push(LoadFailure {
+                        source: path.display().to_string(),
+                        message: err.to_string(),
+                    });
+                }
+            },
+            "xml" => import::panel::import_mal(app, &path.display().to_string()),
+            _ => import::panel::open_csv(app, csv_table, &path.display().to_string()),
+        }
+    };
+
+    // Merges the picked files, optionally on top of the current dataset. Files that fail to
+    // load are listed in the error panel and the rest are still merged.
+    let on_merge = move |(strategy, include_current): (MergeStrategy, bool)| {
+        let Some(files) = merge_files.take() else {
+            return;
+        };
+        let mut merger = Merger::new(strategy);
+        if include_current {
+            merger.add(dataset.peek().clone());
+        }
+        let mut report = QualityReport::default();
+        let mut merged_any = false;
+        let duplicates = config.peek().duplicates;
+        for path in &files {
+            match read_dataset(path, duplicates) {
+                Ok((next, file_report)) => {
+                    merger.add(next);
+                    report.absorb(file_report);
+                    merged_any = true;
+                }
+                Err(err) => load_failures.write().push(LoadFailure {
+                    source: path.display().to_string(),
+                    message: err.to_string(),
+                }),
+            }
+        }
+        if !merged_any {
+            return;
+        }
+
+        let (merged, stats) = merger.finish();
+        watched_path.set(None);
+        #[cfg(feature = "sqlite")]
+        let _ = store::RatingStore::open().and_then(|mut store| store.save_dataset(&merged));
+        dataset.set(merged);
+        app.rebuild_graph();
+        let dropped = if report.is_clean() {
+            String::new()
+        } else {
+            format!(" {}", report.summary())
+        };
+        import_status.set(Some(format!("{}{dropped}", stats.summary())));
+        load_report.set(report.clone());
+        quality_report.set((!report.is_clean()).then_some(report));
+    };
+
+    let on_generate = move |options: SyntheticOptions| {
+        generate_open.set(false);
+        let next = synthetic::generate(options);
+        let ratings: usize = next.users.iter().map(|user| user.ratings.len()).sum();
+        import_status.set(Some(format!(
+            "Generated {} synthetic users with {ratings} ratings of {} anime.",
+            next.users.len(),
+            options.anime
+        )));
+        load_failures.write().clear();
+        load_report.set(QualityReport::default());
+        quality_report.set(None);
+        watched_path.set(None);
+        dataset.set(next);
+        app.rebuild_graph();
+    };
+
+    let on_anonymize = move |options: AnonymizeOptions| {
+        anonymize_open.set(false);
+        spawn(async move {
+            let Some(path) = menu::pick_save_file().await else {
+                return;
+            };
+            let (anonymized, stats) = anonymize::anonymize(&dataset.peek(), &options);
+            let result = write_dataset(&path, &anonymized);
+            import_status.set(Some(match result {
+                Ok(()) => format!("{} Saved to {}.", stats.summary(), path.display()),
+                Err(err) => format!("Could not save dataset: {err}"),
+            }));
+        });
+    };
+
+    let on_drop = move |evt: Event<DragData>| {
+        evt.prevent_default();
+        dropping.set(false);
+        if let Some(file) = evt.files().into_iter().next() {
+            open_file(file.path());
+        }
+    };
+
+    let open_picker = move || {
+        let start_in = config
+            .peek()
+            .recent_files
+            .first()
+            .and_then(|path| path.parent().map(Path::to_path_buf));
+        spawn(async move {
+            if let Some(path) = menu::pick_file(start_in.as_deref()).await {
+                open_file(path);
+            }
+        });
+    };
+
+    // Works through the fetch queue a batch at a time, saving the cache and the queue after
+    // each batch so a cancelled or interrupted run keeps its progress and resumes next launch.
+    // Failed ids back off before they are tried again; when whole batches fail, e.g. offline,
+    // the run pauses between them for longer each time.
+    let run_fetch_queue = move || {
+        if tasks.peek().is_running(TaskKind::Metadata) {
+            return;
+        }
+        let name = "Fetching anime metadata";
+        tasks::spawn_async(tasks, TaskKind::Metadata, name, |progress| async move {
+            let client = http_client();
+            let mut failures_in_a_row = 0;
+            let mut last_error = None;
+            loop {
+                let (line, fraction) = {
+                    let queue = fetch_queue.peek();
+                    (queue.progress_line(), queue.fraction())
+                };
+                progress.report_fraction(line, fraction);
+                let batch = fetch_queue.peek().next_batch(metadata::BATCH_SIZE);
+                if batch.is_empty() {
+                    let Some(wait) = fetch_queue.peek().next_due() else {
+                        break;
+                    };
+                    tokio::time::sleep(wait.max(Duration::from_secs(1))).await;
+                    continue;
+                }
+                match metadata::fetch_batch(&client, &batch).await {
+                    Ok(entries) => {
+                        failures_in_a_row = 0;
+                        let fetched: Vec<u32> = entries.iter().map(|(id, _)| *id).collect();
+                        anime_metadata.write().extend(entries);
+                        let _ = anime_metadata.peek().save();
+                        app.relabel_graph();
+                        let unfetched: Vec<u32> = batch
+                            .into_iter()
+                            .filter(|id| !fetched.contains(id))
+                            .collect();
+                        let mut queue = fetch_queue.write();
+                        queue.complete(&fetched);
+                        queue.fail(&unfetched);
+                    }
+                    Err(err) => {
+                        tracing::warn!("metadata fetch failed: {err}");
+                        fetch_queue.write().fail(&batch);
+                        failures_in_a_row += 1;
+                        last_error = Some(err);
+                    }
+                }
+                let _ = fetch_queue.peek().save();
+                if failures_in_a_row > 0 {
+                    progress.report(format!(
+                        "{} · paused after an error",
+                        fetch_queue.peek().progress_line()
+                    ));
+                    tokio::time::sleep(fetch_queue::outage_backoff(failures_in_a_row)).await;
+                }
+            }
+            // Facet graphs gain the genres, studios and directors just fetched.
+            if app.graph.peek().mode != GraphMode::Ratings {
+                app.rebuild_graph();
+            }
+            let (done, abandoned) = {
+                let queue = fetch_queue.peek();
+                (queue.done(), queue.abandoned())
+            };
+            let cached = anime_metadata.peek().len();
+            import_status.set(Some(match (abandoned, last_error) {
+                (0, _) => format!("Fetched metadata for {done} anime; {cached} are cached."),
+                (abandoned, Some(err)) => format!(
+                    "Fetched metadata for {done} anime; gave up on {abandoned} after repeated \
+                     failures, the last: {err}"
+                ),
+                (abandoned, None) => format!(
+                    "Fetched metadata for {done} anime; gave up on {abandoned} that neither \
+                     source could serve."
+                ),
+            }));
+        });
+    };
+    // A queue left over from the last session resumes straight away.
+    use_hook(move || {
+        if !fetch_queue.peek().is_empty() {
+            run_fetch_queue();
+        }
+    });
+
+    // Queues every anime in the dataset without cached metadata.
+    let mut fetch_metadata = move || {
+        let missing = anime_metadata.peek().missing(
+            dataset
+                .peek()
+                .users
+                .iter()
+                .flat_map(|user| user.ratings.iter().map(|rating| rating.anime_id)),
+        );
+        let added = fetch_queue.write().enqueue(missing);
+        let _ = fetch_queue.peek().save();
+        if fetch_queue.peek().is_empty() {
+            import_status.set(Some(
+                "Metadata is already cached for every anime in the dataset.".to_string(),
+            ));
+            return;
+        }
+        tracing::info!(added, "queued anime for metadata");
+        run_fetch_queue();
+    };
+
+    // Their metadata goes into the cache too, for their summaries and posters.
+    let fetch_season = move |_| {
+        if tasks.peek().is_running(TaskKind::Season) {
+            return;
+        }
+        let name = "Fetching this season's anime";
+        tasks::spawn_async(tasks, TaskKind::Season, name, |progress| async move {
+            let fetched =
+                seasonal::fetch_current(&http_client(), |line| progress.report(line)).await;
+            import_status.set(Some(match fetched {
+                Ok(fetched) => {
+                    let status =
+                        format!("{} has {} anime airing.", fetched.name, fetched.anime.len());
+                    anime_metadata.write().extend(
+                        fetched
+                            .anime
+                            .iter()
+                            .map(|anime| (anime.anime_id, anime.metadata.clone())),
+                    );
+                    let _ = anime_metadata.peek().save();
+                    season.set(Some(fetched));
+                    status
+                }
+                Err(err) => {
+                    tracing::warn!("season fetch failed: {err}");
+                    format!("Could not fetch this season's anime: {err}")
+                }
+            }));
+        });
+    };
+
+    // With notifications on, this season's schedule is kept fresh and checked every few
+    // minutes for watchlisted episodes that aired and strong matches for the active profile.
+    let mut notified = use_signal(Notified::load);
+    let mut check_notifications = move || {
+        let settings = config.peek().notifications;
+        if !settings.allowed_now() {
+            return;
+        }
+        let season = season.peek();
+        let Some(season) = season.as_ref() else {
+            return;
+        };
+        let Some((user_id, watchlist)) = profiles
+            .peek()
+            .active()
+            .map(|profile| (profile.user_id(), profile.watchlist.clone()))
+        else {
+            return;
+        };
+        let picks = app.find_user_node(&user_id).map_or_else(Vec::new, |user| {
+            seasonal_picks(&graph.peek(), user, season, &anime_metadata.peek()).1
+        });
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs() as i64);
+        let notices = notified.write().collect(
+            season,
+            &watchlist,
+            &picks,
+            settings.min_match(),
+            now,
+            |anime_id, title| app.list_title(anime_id, title),
+        );
+        if !notices.is_empty() {
+            let _ = notified.peek().save();
+        }
+        for notice in notices {
+            notifications::send(notice);
+        }
+    };
+    use_effect(move || {
+        if season.read().is_some() {
+            check_notifications();
+        }
+    });
+    use_future(move || async move {
+        let refresh = Duration::from_secs(notifications::SEASON_REFRESH_HOURS * 3600);
+        let mut fetched: Option<Instant> = None;
+        loop {
+            if config.peek().notifications.enabled {
+                if fetched.is_none_or(|at| at.elapsed() >= refresh) {
+                    fetched = Some(Instant::now());
+                    fetch_season(());
+                }
+                check_notifications();
+            }
+            tokio::time::sleep(Duration::from_secs(notifications::CHECK_MINUTES * 60)).await;
+        }
+    });
+
+    // Loads posters for what is on screen: the details card with its neighbors and
+    // recommendations, and the hovered edge's anime. Each poster is requested once per session.
+    use_effect(move || {
+        let model = graph.read();
+        let mut shown = Vec::new();
+        if let Some(idx) = selected().filter(|_| details_open()) {
+            shown.push(idx);
+            shown.extend(
+                navigation::neighbor_summaries(&model, idx, DETAIL_NEIGHBORS)
+                    .into_iter()
+                    .map(|neighbor| neighbor.node),
+            );
+            shown.extend(
+                recommend::recommend(&model, idx, recommend::MAX_RECOMMENDATIONS)
+                    .into_iter()
+                    .map(|recommendation| recommendation.node),
+            );
+        }
+        if let Some(edge) = hovered_edge().and_then(|hover| model.edges.get(hover.edge)) {
+            shown.extend([edge.source, edge.target]);
+        }
+        let metadata = anime_metadata.read();
+        let mut shown: Vec<u32> = shown
+            .into_iter()
+            .filter_map(|idx| model.nodes.get(idx)?.anime_id())
+            .collect();
+        if let (Some(idx), Some(season)) = (selected().filter(|_| details_open()), &*season.read())
+        {
+            let (_, picks) = seasonal_picks(&model, idx, season, &metadata);
+            shown.extend(picks.into_iter().map(|pick| pick.anime_id));
+        }
+        for anime_id in shown {
+            if poster_uris.peek().contains_key(&anime_id) {
+                continue;
+            }
+            let Some(url) = metadata
+                .get(anime_id)
+                .and_then(|entry| entry.poster_url.clone())
+            else {
+                continue;
+            };
+            poster_uris.write().insert(anime_id, None);
+            spawn(async move {
+                let bytes = match posters::cached(anime_id) {
+                    Some(bytes) => Some(bytes),
+                    None => posters::download(&http_client(), anime_id, &url).await.ok(),
+                };
+                if let Some(uri) = bytes.as_deref().and_then(posters::data_uri) {
+                    poster_uris.write().insert(anime_id, Some(Rc::from(uri)));
+                }
+            });
+        }
+    });
+
+    // Looks up where the selected user's recommendations stream, when a region is configured.
+    use_effect(move || {
+        let config = config.read();
+        let Some(region) = config
+            .streaming_region
+            .clone()
+            .filter(|region| !region.trim().is_empty())
+        else {
+            return;
+        };
+        let Some(idx) = selected().filter(|_| details_open()) else {
+            return;
+        };
+        let token = config.tmdb_token.clone().filter(|token| !token.is_empty());
+        let model = graph.read();
+        for recommendation in recommend::recommend(&model, idx, recommend::MAX_RECOMMENDATIONS) {
+            let anime_id = recommendation.anime_id;
+            // Jikan only knows MyAnimeList ids; TMDB is searched by title.
+            if (token.is_none() && idmap::is_derived(anime_id))
+                || availability
+                    .peek()
+                    .get(&region, anime_id, token.is_some())
+                    .is_some()
+                || !streaming_requested.write().insert(anime_id)
+            {
+                continue;
+            }
+            let (region, token) = (region.clone(), token.clone());
+            spawn(async move {
+                let found = streaming::lookup(
+                    &http_client(),
+                    token.as_deref(),
+                    &region,
+                    anime_id,
+                    &recommendation.title,
+                )
+                .await;
+                if let Ok(found) = found {
+                    availability.write().insert(&region, anime_id, found);
+                    let _ = availability.peek().save();
+                }
+            });
+        }
+    });
+
+    let export_recommendations = move |_| {
+        let Some(user) = selected() else {
+            return;
+        };
+        spawn(async move {
+            let Some(path) = menu::pick_recommendations_export().await else {
+                return;
+            };
+            let Some(format) = recommend_export::RecommendationFormat::from_path(&path) else {
+                import_status.set(Some(format!(
+                    "Export recommendations as .md, .csv or .json ({} isn't supported).",
+                    path.display()
+                )));
+                return;
+            };
+            let model = graph.peek();
+            let recommendations = app.recommend(&model, user, recommend::MAX_RECOMMENDATIONS);
+            let result = compression::create(&path, |writer| {
+                recommend_export::write(
+                    writer,
+                    &model.nodes[user].label,
+                    &recommendations,
+                    &anime_metadata.peek(),
+                    format,
+                )
+            });
+            import_status.set(Some(match result {
+                Ok(()) => format!(
+                    "Exported {} recommendations to {}.",
+                    recommendations.len(),
+                    path.display()
+                ),
+                Err(err) => format!("Could not export recommendations: {err}"),
+            }));
+        });
+    };
+
+    let export_report = move |_| {
+        let Some(user) = selected() else {
+            return;
+        };
+        spawn(async move {
+            let Some(path) = menu::pick_report().await else {
+                return;
+            };
+            let model = graph.peek();
+            let node_id = &model.nodes[user].id;
+            let dataset = dataset.peek();
+            let Some(ratings) = dataset
+                .users
+                .iter()
+                .find(|candidate| node_id.strip_prefix("user:") == Some(&candidate.user_id))
+            else {
+                return;
+            };
+            let metadata = anime_metadata.peek();
+            let profile = report::TasteProfile::new(ratings, &metadata);
+            let recommendations = app.recommend(&model, user, recommend::MAX_RECOMMENDATIONS);
+            let palette = {
+                let config = config.peek();
+                theme::active_palette(&config.palettes, config.palette.as_deref())
+            };
+            let result = compression::create(&path, |writer| {
+                report::write(
+                    writer,
+                    &report::Report {
+                        graph: &model,
+                        user,
+                        profile: &profile,
+                        recommendations: &recommendations,
+                        metadata: &metadata,
+                        palette: &palette,
+                    },
+                )
+            });
+            import_status.set(Some(match result {
+                Ok(()) => format!("Saved the report to {}.", path.display()),
+                Err(err) => format!("Could not save the report: {err}"),
+            }));
+        });
+    };
+
+    // Searches synopses by meaning on a worker thread, indexing any not embedded yet, and
+    // re-ranks by the selected user's taste (or the first imported profile's).
+    #[cfg(feature = "semantic")]
+    let on_semantic_search = move |query: String| {
+        if tasks.peek().is_running(TaskKind::Search) || query.trim().is_empty() {
+            return;
+        }
+        let Some(model_dir) = semantic::model_dir(config.peek().semantic_model.as_deref()) else {
+            import_status.set(Some(
+                "No sentence encoder found; see the README for where to put it.".to_string(),
+            ));
+            return;
+        };
+        let metadata = anime_metadata.peek().clone();
+        let (titles, documents, taste) = {
+            let model = graph.peek();
+            let titles: HashMap<u32, String> = model
+                .nodes
+                .iter()
+                .filter_map(|node| Some((node.anime_id()?, node.label.clone())))
+                .collect();
+            let documents: Vec<(u32, String)> = titles
+                .iter()
+                .filter_map(|(&anime_id, title)| {
+                    Some((
+                        anime_id,
+                        semantic::document(title, metadata.get(anime_id)?)?,
+                    ))
+                })
+                .collect();
+            let user = selected()
+                .filter(|&idx| model.nodes[idx].node_type == wasiw_core::NodeType::User)
+                .or_else(|| {
+                    model.nodes.iter().position(|node| {
+                        node.id
+                            .strip_prefix("user:")
+                            .is_some_and(|id| id.starts_with(import::LOCAL_PROFILE_PREFIX))
+                    })
+                });
+            let taste = user.map(|idx| {
+                let profile = TasteProfile::new(&model, idx, |id| {
+                    metadata.get(id).map(AnimeMetadata::content_features)
+                });
+                (model.nodes[idx].label.clone(), profile)
+            });
+            (titles, documents, taste)
+        };
+        if documents.is_empty() {
+            import_status.set(Some(
+                "No synopses to search yet; fetch anime metadata first.".to_string(),
+            ));
+            return;
+        }
+        let engine = semantic_engine.clone();
+        let shown_query = query.clone();
+        let for_user = taste.as_ref().map(|(label, _)| label.clone());
+        tasks::spawn_blocking(
+            tasks,
+            TaskKind::Search,
+            format!("Searching for \"{}\"", query.trim()),
+            move |worker| {
+                let mut engine = engine.lock().unwrap_or_else(|err| err.into_inner());
+                engine
+                    .prepare(&model_dir, documents, |line| worker.progress(line))
+                    .and_then(|()| {
+                        let profile = taste.as_ref().map(|(_, profile)| profile);
+                        engine.search(&query, &titles, &metadata, profile, semantic::MAX_RESULTS)
+                    })
+            },
+            move |found| match found {
+                Some(Ok(found)) => {
+                    import_status.set(Some(match &for_user {
+                        Some(label) => format!("{} matches, ranked for {label}.", found.len()),
+                        None => format!("{} matches.", found.len()),
+                    }));
+                    semantic_results.set(Some((shown_query, found)));
+                }
+                Some(Err(err)) => import_status.set(Some(format!("Search failed: {err}"))),
+                None => import_status.set(Some("Search failed; see the log.".to_string())),
+            },
+        );
+    };
+
+    // Asks the local model for a blurb on one of the selected user's recommendations.
+    let explain = move |anime_id: u32| {
+        let Some(settings) = config.peek().ollama.clone() else {
+            return;
+        };
+        let Some(user) = selected() else {
+            return;
+        };
+        let (node_id, recommendation) = {
+            let model = graph.peek();
+            let recommendation = recommend::recommend(&model, user, recommend::MAX_RECOMMENDATIONS)
+                .into_iter()
+                .find(|recommendation| recommendation.anime_id == anime_id);
+            (model.nodes[user].id.clone(), recommendation)
+        };
+        let Some(recommendation) = recommendation else {
+            return;
+        };
+        let genres = anime_metadata
+            .peek()
+            .get(anime_id)
+            .map(|metadata| metadata.genres.clone())
+            .unwrap_or_default();
+        let key = (node_id, anime_id);
+        blurbs.write().insert(key.clone(), None);
+        spawn(async move {
+            match ollama::blurb(&http_client(), &settings, &recommendation, &genres).await {
+                Ok(blurb) => {
+                    blurbs.write().insert(key, Some(blurb));
+                }
+                Err(err) => {
+                    blurbs.write().remove(&key);
+                    import_status.set(Some(format!("Could not write an explanation: {err}")));
+                }
+            }
+        });
+    };
+
+    // The escape hatch from a restored session that got in the way: default view, filter and
+    // selection, and no saved session until the window next closes.
+    let start_fresh = move |()| {
+        let _ = Session::clear();
+        camera.set(Camera::default());
+        search.set(String::new());
+        selected.set(None);
+        details_open.set(false);
+        graph.write().apply_filter(GraphFilter::default());
+        animate_layout(*viewport.peek());
+        import_status.set(Some("Started fresh.".to_string()));
+    };
+    // What the tab pages share with the graph around them.
+    use_context_provider(|| app);
+    let relayout = use_callback(move |target: Viewport| animate_layout(target));
+    let reload = use_callback(move |path: PathBuf| reload_dataset(path));
+    let start_fresh = use_callback(start_fresh);
+    #[cfg(feature = "semantic")]
+    let semantic_search = use_callback(on_semantic_search);
+    use_context_provider(|| Shell {
+        search,
+        fullscreen,
+        log_open,
+        watched_path,
+        csv_table,
+        relayout,
+        reload,
+        start_fresh,
+        #[cfg(feature = "semantic")]
+        semantic_results,
+        #[cfg(feature = "semantic")]
+        semantic_search,
+    });
+
+    let mark_watched =
+        move |(anime_id, score): (u32, u8)| profiles::panel::mark_watched(app, anime_id, score);
+    let toggle_watchlist = move |anime_id: u32| profiles::panel::toggle_watchlist(app, anime_id);
+    let hide_recommendation =
+        move |anime_id: u32| profiles::panel::hide_recommendation(app, anime_id);
+
+    // While the setting is on the tray icon shows, and closing the window hides it there.
+    let mut tray_icon = use_signal(|| None::<TrayIcon>);
+    let tray_on = use_memo(move || config.read().tray);
+    use_effect(move || {
+        let icon = tray_on()
+            .then(|| {
+                tray::build(APP_TITLE)
+                    .inspect_err(|err| tracing::warn!("could not add the tray icon: {err}"))
+                    .ok()
+            })
+            .flatten();
+        // Without an icon to come back from, closing the window still quits.
+        tray::set_keep_running(icon.is_some());
+        tray_icon.set(icon);
+    });
+    // Recommend something now moves on to the next pick each time.
+    let mut tray_picks = use_signal(|| 0_usize);
+
+    // The File and Accounts menu actions and the tray's, run from the menu bar, the tray or the
+    // command palette.
+    let mut run_menu_action = move |id: &str| {
+        if id == menu::OPEN_DATASET_ID {
+            open_picker();
+        } else if id == menu::MERGE_DATASETS_ID {
+            spawn(async move {
+                let files = menu::pick_datasets().await;
+                if !files.is_empty() {
+                    merge_files.set(Some(files));
+                }
+            });
+        } else if id == menu::SAVE_DATASET_ID {
+            spawn(async move {
+                let Some(path) = menu::pick_save_file().await else {
+                    return;
+                };
+                let result = write_dataset(&path, &dataset.peek());
+                import_status.set(Some(match result {
+                    Ok(()) => format!("Saved dataset to {}.", path.display()),
+                    Err(err) => format!("Could not save dataset: {err}"),
+                }));
+            });
+        } else if id == menu::LOAD_ID_MAP_ID {
+            spawn(async move {
+                let Some(path) = menu::pick_id_map().await else {
+                    return;
+                };
+                match idmap::IdMap::load(&path) {
+                    Ok(ids) => {
+                        import_status.set(Some(format!(
+                            "Loaded id mappings for {} anime; AniList, Kitsu and history imports now resolve through them.",
+                            ids.len()
+                        )));
+                        id_map.set(Some(Rc::new(ids)));
+                        let mut config = config.write();
+                        config.id_map = Some(path);
+                        let _ = config.save();
+                    }
+                    Err(err) => import_status.set(Some(format!(
+                        "Could not load the id map {}: {err}",
+                        path.display()
+                    ))),
+                }
+            });
+        } else if id == menu::FETCH_METADATA_ID {
+            fetch_metadata();
+        } else if id == menu::GENERATE_ID {
+            generate_open.set(true);
+        } else if id == menu::ANONYMIZE_ID {
+            anonymize_open.set(true);
+        } else if id == menu::EXPORT_GRAPH_ID {
+            spawn(async move {
+                let Some(path) = menu::pick_graph_export().await else {
+                    return;
+                };
+                let Some(format) = graph_export::GraphFormat::from_path(&path) else {
+                    import_status.set(Some(format!(
+                        "Export the graph as .graphml, .gexf or .dot ({} isn't supported).",
+                        path.display()
+                    )));
+                    return;
+                };
+                let result = compression::create(&path, |writer| {
+                    graph_export::write(writer, &graph.peek(), format)
+                });
+                import_status.set(Some(match result {
+                    Ok(()) => format!("Exported the graph to {}.", path.display()),
+                    Err(err) => format!("Could not export the graph: {err}"),
+                }));
+            });
+        } else if id == menu::CONNECT_MAL_ID {
+            connect_site.set(Some(SyncSite::MyAnimeList));
+        } else if id == menu::CONNECT_ANILIST_ID {
+            connect_site.set(Some(SyncSite::AniList));
+        } else if id == menu::DISCONNECT_ID {
+            sync::dialog::disconnect_all(app);
+        } else if id == tray::RECOMMEND_ID {
+            let index = tray_picks();
+            tray_picks += 1;
+            match tray::pick(app, index) {
+                Some(pick) => {
+                    let title = app.list_title(pick.anime_id, &pick.title);
+                    let because = pick
+                        .contributions
+                        .iter()
+                        .find(|contribution| contribution.edge_weight > 0.0);
+                    let detail = match because {
+                        Some(because) => tr!("tray-because", title = because.title),
+                        None => tr!("tray-score", score = format!("{:.2}", pick.score)),
+                    };
+                    tray::show_toast(title, detail);
+                }
+                None => tray::show_toast(tr("tray-no-pick"), tr("tray-no-pick-hint")),
+            }
+        } else if id == tray::OPEN_ID {
+            tray::show_window();
+        } else if id == tray::QUIT_ID {
+            save_on_exit();
+            std::process::exit(0);
+        }
+    };
+    // Menu bar and tray clicks share one event handler, so either hook may receive both.
+    let on_menu_event = move |event: &MenuEvent| match menu::recent_index(event.id()) {
+        Some(index) => {
+            let path = config.peek().recent_files.get(index).cloned();
+            if let Some(path) = path {
+                open_file(path);
+            }
+        }
+        None => run_menu_action(event.id().as_ref()),
+    };
+    dioxus::desktop::use_muda_event_handler(on_menu_event);
+    dioxus::desktop::use_tray_menu_event_handler(on_menu_event);
+
+    let mut palette_open = use_signal(|| false);
+    let navigator = use_navigator();
+    let mut sheet_open = use_signal(|| false);
+    let keymap = use_memo(move || {
+        let (keymap, problems) = Keymap::new(&config.read().keymap);
+        for problem in problems {
+            tracing::warn!("keymap: {problem}");
+        }
+        keymap
+    });
+
+    // Layout, filter and sampling changes, however they were made, can be undone.
+    let mut undo = use_signal(UndoStack::default);
+    use_effect(move || {
+        let view = GraphView::of(&graph.read());
+        undo.write().observe(view);
+    });
+    let mut restore_view = move |view: GraphView| {
+        if graph.peek().sampling != view.sampling {
+            graph.write().sampling = view.sampling;
+            app.rebuild_graph();
+        }
+        {
+            let mut model = graph.write();
+            model.layout = view.layout;
+            model.layout_seed = view.layout_seed;
+            model.apply_filter(view.filter);
+        }
+        animate_layout(*viewport.peek());
+    };
+
+    let save_screenshot = move || {
+        spawn(async move {
+            let Some(path) = menu::pick_screenshot().await else {
+                return;
+            };
+            // The view as it is now, drawn as a standalone document rather than copied from
+            // the page, so it doesn't depend on the stylesheet.
+            let svg = {
+                let model = graph.peek();
+                let config = config.peek();
+                if model.nodes.is_empty() {
+                    None
+                } else {
+                    let scene = graph_scene(
+                        &model,
+                        *viewport.peek(),
+                        *camera.peek(),
+                        &expanded_communities.peek(),
+                        &config,
+                        cull_index.peek().as_ref(),
+                        &relations.peek(),
+                    );
+                    let palette =
+                        theme::active_palette(&config.palettes, config.palette.as_deref());
+                    let matches = model.search(&search.peek());
+                    let highlight = Highlight {
+                        matches: matches.as_ref(),
+                        selected: *selected.peek(),
+                        hovered_edge: None,
+                    };
+                    let frame = Frame::new(&model, &scene, &palette, highlight, usize::MAX);
+                    Some(SvgRenderer::default().render(&frame))
+                }
+            };
+            import_status.set(Some(match svg {
+                Some(svg) => match std::fs::write(&path, svg) {
+                    Ok(()) => format!("Saved a screenshot to {}.", path.display()),
+                    Err(err) => format!("Could not save the screenshot: {err}"),
+                },
+                None => "There is no graph to take a screenshot of.".to_string(),
+            }));
+        });
+    };
+
+    let mut run_shortcut = move |shortcut: Shortcut| match shortcut {
+        Shortcut::ZoomIn | Shortcut::ZoomOut => {
+            let factor = if shortcut == Shortcut::ZoomIn {
+                ZOOM_STEP
+            } else {
+                1.0 / ZOOM_STEP
+            };
+            let viewport = *viewport.peek();
+            let fitted = fitted_view();
+            camera.write().zoom_about(
+                factor,
+                viewport.width / 2.0,
+                viewport.height / 2.0,
+                fitted,
+                viewport,
+            );
+        }
+        Shortcut::FitView => camera.set(Camera::default()),
+        Shortcut::Search => {
+            if !fullscreen() {
+                navigator.push(Route::Graph {});
+            }
+            document::eval(FOCUS_SEARCH_JS);
+        }
+        Shortcut::NextTab | Shortcut::PreviousTab => {
+            let tabs = Route::tabs();
+            let current = tabs
+                .iter()
+                .position(|open| *open == *tab.peek())
+                .unwrap_or(0);
+            let step = if shortcut == Shortcut::NextTab {
+                1
+            } else {
+                tabs.len() - 1
+            };
+            navigator.push(tabs[(current + step) % tabs.len()].clone());
+        }
+        Shortcut::Undo => {
+            let view = undo.write().undo();
+            if let Some(view) = view {
+                restore_view(view);
+            }
+        }
+        Shortcut::Redo => {
+            let view = undo.write().redo();
+            if let Some(view) = view {
+                restore_view(view);
+            }
+        }
+        Shortcut::Screenshot => save_screenshot(),
+        Shortcut::CommandPalette => palette_open.toggle(),
+        Shortcut::Fullscreen => set_fullscreen(fullscreen, !fullscreen()),
+        Shortcut::Shortcuts => sheet_open.toggle(),
+    };
+
+    let run_command = move |id: String| {
+        palette_open.set(false);
+        let Some(command) = Command::parse(&id, &graph.peek()) else {
+            return;
+        };
+        {
+            let mut config = config.write();
+            config.remember_command(&id);
+            let _ = config.save();
+        }
+        match command {
+            Command::Menu(id) => run_menu_action(id),
+            Command::Tab(route) => {
+                navigator.push(route);
+            }
+            Command::Layout(kind) => {
+                graph.write().layout = kind;
+                animate_layout(*viewport.peek());
+                let mut config = config.write();
+                config.layout = kind;
+                let _ = config.save();
+            }
+            Command::Sampling(kind) => {
+                let count = graph.peek().sampling.count();
+                let sampling = kind.with_count(count.unwrap_or(Sampling::DEFAULT_COUNT));
+                graph.write().sampling = sampling;
+                app.rebuild_graph();
+                let mut config = config.write();
+                config.sampling = sampling;
+                let _ = config.save();
+            }
+            Command::ToggleUsers => {
+                let filter = graph.peek().filter;
+                graph.write().apply_filter(GraphFilter {
+                    show_users: !filter.show_users,
+                    ..filter
+                });
+                animate_layout(*viewport.peek());
+            }
+            Command::ToggleEdges(kind) => {
+                let filter = graph.peek().filter;
+                let filter = filter.with_edges(kind, !filter.shows_edges(kind));
+                graph.write().apply_filter(filter);
+            }
+            Command::Shortcut(shortcut) => run_shortcut(shortcut),
+            Command::StartFresh => start_fresh.call(()),
+            Command::ToggleLog => log_open.toggle(),
+            Command::ToggleTextView => {
+                let mut config = config.write();
+                config.text_view = !config.text_view;
+                let _ = config.save();
+            }
+            Command::Anime(node) => app.open_node(node),
+        }
+    };
+
+    let on_key = move |evt: Event<KeyboardData>| {
+        if evt.key() == Key::Escape {
+            if sheet_open() {
+                sheet_open.set(false);
+            } else if fullscreen() {
+                set_fullscreen(fullscreen, false);
+            }
+            return;
+        }
+        let shortcut = keymap.read().shortcut(&evt.key(), evt.modifiers());
+        if let Some(shortcut) = shortcut {
+            evt.prevent_default();
+            run_shortcut(shortcut);
+        }
+    };
+
+    let model = graph.read();
+    let viewport = viewport();
+    let scene = graph_scene(
+        &model,
+        viewport,
+        camera(),
+        &expanded_communities.read(),
+        &config.read(),
+        cull_index.read().as_ref(),
+        &relations.read(),
+    );
+    let fitted = scene.fitted;
+    let matches = model.search(&search.read());
+    let match_count = matches.as_ref().map(|m| m.len());
+    let palette = theme::active_palette(&config.read().palettes, config.read().palette.as_deref());
+    let theme_preference = config.read().theme;
+    let text_view = config.read().text_view;
+    let theme_class = theme_preference.resolve(system_theme()).class();
+    let scale_class = config.read().ui_scale.class();
+    let poster = |idx: usize| {
+        let anime_id = model.nodes.get(idx)?.anime_id()?;
+        poster_uris.read().get(&anime_id).cloned().flatten()
+    };
+    let edge_tooltip = hovered_edge().and_then(|hover| EdgeTooltip::new(&model, hover, poster));
+    let details = selected().filter(|_| details_open()).and_then(|idx| {
+        let mut details = NodeDetailsData::new(&model, idx, poster)?;
+        let metadata = anime_metadata.read();
+        if let Some(metadata) = model.nodes[idx].anime_id().and_then(|id| metadata.get(id)) {
+            details.summary = Some(metadata.summary());
+            details.synopsis = metadata.synopsis.clone();
+        }
+        let node_id = &model.nodes[idx].id;
+        let profiles = profiles.read();
+        let profile = node_id
+            .strip_prefix("user:")
+            .and_then(|user_id| profiles.for_user(user_id));
+        let hidden = |anime_id: u32| profile.is_some_and(|p| p.blacklist.contains(&anime_id));
+        relations
+            .read()
+            .collapse(&mut details.recommendations, |(recommendation, _)| {
+                recommendation.anime_id
+            });
+        if let Some(profile) = profile {
+            details
+                .recommendations
+                .retain(|(recommendation, _)| !hidden(recommendation.anime_id));
+            details.watchlist = profile.watchlist.iter().copied().collect();
+        }
+        for (recommendation, _) in &mut details.recommendations {
+            recommendation.title = app.list_title(recommendation.anime_id, &recommendation.title);
+        }
+        details.entry_points = app.entry_points(
+            &model,
+            idx,
+            details
+                .recommendations
+                .iter()
+                .map(|(recommendation, _)| recommendation.anime_id),
+        );
+        details.blurbs = blurbs
+            .read()
+            .iter()
+            .filter(|((user, _), _)| user == node_id)
+            .map(|((_, anime_id), blurb)| (*anime_id, blurb.clone()))
+            .collect();
+        let config = config.read();
+        if let Some(region) = &config.streaming_region {
+            let regional = config
+                .tmdb_token
+                .as_ref()
+                .is_some_and(|token| !token.is_empty());
+            let availability = availability.read();
+            details.streaming = details
+                .recommendations
+                .iter()
+                .filter_map(|(recommendation, _)| {
+                    let anime_id = recommendation.anime_id;
+                    let found = availability.get(region, anime_id, regional)?;
+                    Some((anime_id, found.summary()))
+                })
+                .collect();
+        }
+        if let Some(season) = &*season.read() {
+            let (profile, picks) = seasonal_picks(&model, idx, season, &metadata);
+            details.seasonal = Some(SeasonalPicks {
+                season: season.name.clone(),
+                picks: picks
+                    .into_iter()
+                    .filter(|pick| !hidden(pick.anime_id))
+                    .map(|mut pick| {
+                        pick.title = app.list_title(pick.anime_id, &pick.title);
+                        let summary = metadata
+                            .get(pick.anime_id)
+                            .map(AnimeMetadata::summary)
+                            .unwrap_or_default();
+                        let thumbnail = poster_uris.read().get(&pick.anime_id).cloned().flatten();
+                        (pick, summary, thumbnail)
+                    })
+                    .collect(),
+                profiled: profile.entries.len(),
+                rated: profile.rated.len(),
+            });
+        }
+        Some(details)
+    });
+    let profile_selected = selected().is_some_and(|idx| {
+        model.nodes[idx]
+            .id
+            .strip_prefix("user:")
+            .is_some_and(|id| profiles.read().for_user(id).is_some())
+    });
+    let local_profile = selected().is_some_and(|idx| {
+        model.nodes[idx]
+            .id
+            .strip_prefix("user:")
+            .is_some_and(|id| id.starts_with(import::LOCAL_PROFILE_PREFIX))
+    });
+    let menu_target = context_menu().map(|menu| {
+        let node = &model.nodes[menu.node];
+        (menu, node.x, node.y)
+    });
+
     rsx! {
-        div { class: "row",
-            span { "{label}" }
-            strong { "{value}" }
+        style { {theme::APP_CSS} }
+        main {
+            class: if fullscreen() { "app fullscreen {theme_class} {scale_class}" } else { "app {theme_class} {scale_class}" },
+            class: if dropping() { "dropping" },
+            tabindex: "0",
+            onkeydown: on_key,
+            ondragover: move |evt| {
+                evt.prevent_default();
+                dropping.set(true);
+            },
+            ondragleave: move |_| dropping.set(false),
+            ondrop: on_drop,
+            button {
+                class: "skip-link",
+                onclick: move |_| {
+                    if let Some(canvas) = canvas_element() {
+                        spawn(async move {
+                            let _ = canvas.set_focus(true).await;
+                        });
+                    }
+                },
+                {tr!("skip-to-graph")}
+            }
+            if !fullscreen() {
+                section { class: "panel", aria_label: tr!("panel-label"),
+                    h1 { {tr!("app-title")} }
+                    p { class: "muted", {tr!("app-tagline")} }
+                    nav { class: "tabs", aria_label: tr!("tabs-label"),
+                        for tab in Route::tabs() {
+                            Link { to: tab.clone(), active_class: "active", "{tab.label()}" }
+                        }
+                    }
+                    if !tasks.read().jobs().is_empty() {
+                        TaskListPanel { tasks }
+                    }
+                    Outlet::<Route> {}
+                }
+            }
+            section {
+                class: "canvas-wrap",
+                // Screen readers stop reading lists inside an application, so only the drawing is one.
+                role: if !text_view { "application" },
+                aria_label: tr!("canvas-keys"),
+                onresize: on_canvas_resize,
+                tabindex: "0",
+                onmounted: move |evt| canvas_element.set(Some(evt.data())),
+                onclick: move |_| context_menu.set(None),
+                onkeydown: on_canvas_key,
+                if fullscreen() {
+                    div { class: "hud",
+                        SearchBox { query: search, match_count }
+                        div { class: "hud-stats",
+                            span { "{model.user_count} users" }
+                            span { "{model.anime_count} anime" }
+                            span { "{scene.edges.len()} edges" }
+                        }
+                        button {
+                            class: "action",
+                            onclick: move |_| set_fullscreen(fullscreen, false),
+                            {tr!("graph-exit-fullscreen")}
+                        }
+                    }
+                }
+                if !load_failures.read().is_empty() {
+                    LoadErrorPanel {
+                        failures: load_failures(),
+                        has_dataset: !dataset.read().users.is_empty(),
+                        on_use_sample: move |_| {
+                            match sample_dataset() {
+                                Ok(sample) => {
+                                    watched_path.set(None);
+                                    dataset.set(sample);
+                                    app.rebuild_graph();
+                                    load_failures.write().clear();
+                                }
+                                Err(failure) => load_failures.write().push(failure),
+                            }
+                        },
+                        on_open: move |_| open_picker(),
+                        on_dismiss: move |_| load_failures.write().clear(),
+                    }
+                }
+                if log_open() {
+                    LogPanel { on_close: move |_| log_open.set(false) }
+                }
+                if let Some(report) = quality_report() {
+                    QualityReportPanel {
+                        report,
+                        on_close: move |_| quality_report.set(None),
+                    }
+                }
+                if let Some(files) = merge_files() {
+                    MergeDialog {
+                        files,
+                        on_merge,
+                        on_cancel: move |_| merge_files.set(None),
+                    }
+                }
+                if generate_open() {
+                    SyntheticDialog {
+                        on_generate,
+                        on_cancel: move |_| generate_open.set(false),
+                    }
+                }
+                if anonymize_open() {
+                    AnonymizeDialog {
+                        on_export: on_anonymize,
+                        on_cancel: move |_| anonymize_open.set(false),
+                    }
+                }
+                if palette_open() {
+                    CommandPalette {
+                        commands: commands::palette_commands(
+                            &model,
+                            &keymap.read(),
+                            log_open(),
+                            config.read().text_view,
+                        ),
+                        recent: config.read().recent_commands.clone(),
+                        on_run: run_command,
+                        on_close: move |_| palette_open.set(false),
+                    }
+                }
+                if sheet_open() {
+                    ShortcutSheet {
+                        keymap: keymap(),
+                        on_close: move |_| sheet_open.set(false),
+                    }
+                }
+                if let Some(site) = connect_site() {
+                    ConnectDialog { key: "{site.label()}", app, site, connect_site }
+                }
+                if let Some(table) = csv_table() {
+                    CsvMappingDialog {
+                        key: "{table.name}",
+                        app,
+                        csv_table,
+                        headers: table.headers.clone(),
+                        preview: table.preview(),
+                        row_count: table.rows.len(),
+                    }
+                }
+                if let Some(details) = details {
+                    NodeDetails {
+                        details,
+                        on_export_recommendations: export_recommendations,
+                        on_export_report: export_report,
+                        on_mark_watched: local_profile.then(|| EventHandler::new(mark_watched)),
+                        on_explain: config.read().ollama.is_some().then(|| EventHandler::new(explain)),
+                        on_watchlist: profile_selected.then(|| EventHandler::new(toggle_watchlist)),
+                        on_hide: profile_selected.then(|| EventHandler::new(hide_recommendation)),
+                        on_fetch_season: fetch_season,
+                        on_close: move |_| details_open.set(false),
+                    }
+                }
+                if let Some(tooltip) = edge_tooltip {
+                    EdgeTooltipCard { tooltip }
+                }
+                if let Some((menu, node_x, node_y)) = menu_target {
+                    NodeContextMenu {
+                        x: menu.x,
+                        y: menu.y,
+                        label: model.nodes[menu.node].label.clone(),
+                        on_search: move |label: String| {
+                            search.set(label);
+                            context_menu.set(None);
+                        },
+                        on_focus: move |_| {
+                            let mut cam = camera.write();
+                            cam.center_on(node_x, node_y, fitted);
+                            cam.zoom = cam.zoom.max(3.0);
+                            context_menu.set(None);
+                        },
+                    }
+                }
+                // Keyboard navigation moves the selection, not the focus, so it is announced.
+                p { class: "sr-only", aria_live: "polite",
+                    if let Some(node) = selected().and_then(|idx| model.nodes.get(idx)) {
+                        {tr!("canvas-selected", name = node.label)}
+                    }
+                }
+                if text_view {
+                    GraphOutline {
+                        graph,
+                        matches,
+                        selected: selected(),
+                        on_pick: move |idx| app.open_node(idx),
+                    }
+                } else {
+                    GraphCanvas {
+                        graph,
+                        scene,
+                        palette,
+                        matches,
+                        selected: selected(),
+                        hovered_edge: hovered_edge().map(|hover| hover.edge),
+                        on_select: move |idx| {
+                            context_menu.set(None);
+                            tab_cursor.set(None);
+                            selected.set(Some(idx));
+                        },
+                        on_open: move |idx| app.open_node(idx),
+                        on_expand: move |community| {
+                            expanded_communities.write().insert(community);
+                        },
+                        on_edge_enter: move |(edge, evt): (usize, Event<MouseData>)| {
+                            let point = evt.client_coordinates();
+                            let (x, y) = to_canvas(point.x, point.y);
+                            hovered_edge.set(Some(EdgeHover { edge, x, y }));
+                        },
+                        on_edge_leave: move |_| hovered_edge.set(None),
+                        on_wheel,
+                        on_touch_start,
+                        on_touch_move,
+                        on_touch_end,
+                        on_node_touch: move |(idx, evt)| on_node_touch(idx, evt),
+                        on_node_context_menu: move |(idx, evt)| on_node_context_menu(idx, evt),
+                    }
+                }
+            }
         }
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
-struct Dataset {
-    users: Vec<UserRatings>,
+/// What the canvas draws for `camera`, with relations over it when they are shown.
+fn graph_scene(
+    model: &GraphModel,
+    viewport: Viewport,
+    camera: Camera,
+    expanded_communities: &HashSet<usize>,
+    config: &AppConfig,
+    cull: Option<&CullIndex>,
+    relations: &Relations,
+) -> Scene {
+    let scene = Scene::new(
+        model,
+        viewport,
+        camera,
+        expanded_communities,
+        config.max_rendered_edges(),
+        cull,
+    );
+    if config.show_relations {
+        scene.with_relations(model, relations)
+    } else {
+        scene
+    }
 }
 
-#[derive(Debug, Clone, Deserialize)]
-struct UserRatings {
-    #[serde(rename = "userId")]
-    user_id: String,
-    ratings: Vec<Rating>,
+fn os_theme(theme: dioxus::desktop::tao::window::Theme) -> ThemeVariant {
+    match theme {
+        dioxus::desktop::tao::window::Theme::Light => ThemeVariant::Light,
+        _ => ThemeVariant::Dark,
+    }
 }
 
-#[derive(Debug, Clone, Deserialize)]
-struct Rating {
-    #[serde(rename = "animeId")]
-    anime_id: u32,
-    title: String,
-    #[serde(rename = "rawScore")]
-    raw_score: f64,
-    #[serde(rename = "normalizedScore")]
-    normalized_score: f64,
+fn set_fullscreen(mut fullscreen: Signal<bool>, enabled: bool) {
+    fullscreen.set(enabled);
+    dioxus::desktop::window().set_fullscreen(enabled);
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum NodeType {
-    User,
-    Anime,
+/// This season's anime ranked for the user at node `user` by the content backend, with the
+/// taste profile they were compared to.
+fn seasonal_picks(
+    graph: &GraphModel,
+    user: usize,
+    season: &Season,
+    metadata: &MetadataCache,
+) -> (TasteProfile, Vec<ContentRecommendation>) {
+    let profile = TasteProfile::new(graph, user, |id| {
+        metadata.get(id).map(AnimeMetadata::content_features)
+    });
+    let picks = profile.recommend(&season.candidates(), recommend::MAX_RECOMMENDATIONS);
+    (profile, picks)
 }
 
-#[derive(Debug, Clone)]
-struct Node {
-    id: String,
-    label: String,
-    node_type: NodeType,
-    x: f32,
-    y: f32,
-    radius: f32,
-    color: &'static str,
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
 }
 
-#[derive(Debug, Clone)]
-struct Edge {
-    source: usize,
-    target: usize,
-    color: &'static str,
-    stroke_width: f32,
+/// Roughly the bytes of the session's in-memory caches: poster data URIs, blurbs and tribes.
+fn cache_bytes(
+    posters: &HashMap<u32, Option<Rc<str>>>,
+    blurbs: &HashMap<(String, u32), Option<String>>,
+    tribes: Option<&TribeReport>,
+) -> usize {
+    let posters: usize = posters.values().flatten().map(|uri| uri.len()).sum();
+    let blurbs: usize = blurbs
+        .iter()
+        .map(|((user, _), blurb)| user.len() + blurb.as_ref().map_or(0, String::len))
+        .sum();
+    let tribes: usize = tribes.map_or(0, |report| {
+        report
+            .tribes
+            .iter()
+            .flat_map(|tribe| {
+                let members = tribe.members.iter().map(String::len);
+                members.chain(
+                    tribe
+                        .signatures
+                        .iter()
+                        .map(|signature| signature.title.len()),
+                )
+            })
+            .sum()
+    });
+    posters + blurbs + tribes
 }
 
-#[derive(Debug, Clone)]
-struct RenderEdge {
-    x1: f32,
-    y1: f32,
-    x2: f32,
-    y2: f32,
-    color: &'static str,
-    stroke_width: f32,
+/// Shared HTTP client for importers; reusing it keeps connections alive between requests.
+fn http_client() -> reqwest::Client {
+    static CLIENT: std::sync::OnceLock<reqwest::Client> = std::sync::OnceLock::new();
+    CLIENT
+        .get_or_init(|| {
+            reqwest::Client::builder()
+                .user_agent(concat!(
+                    "what-anime-should-i-watch/",
+                    env!("CARGO_PKG_VERSION")
+                ))
+                .timeout(Duration::from_secs(30))
+                .build()
+                .expect("static HTTP client configuration is valid")
+        })
+        .clone()
 }
 
-#[derive(Debug, Clone)]
-struct GraphModel {
-    user_count: usize,
-    anime_count: usize,
-    nodes: Vec<Node>,
-    edges: Vec<RenderEdge>,
+/// Lists dataset sources that failed to load. With nothing loaded, the sample is only used
+/// once the user asks for it.
+#[component]
+fn LoadErrorPanel(
+    failures: Vec<LoadFailure>,
+    has_dataset: bool,
+    on_use_sample: EventHandler<()>,
+    on_open: EventHandler<()>,
+    on_dismiss: EventHandler<()>,
+) -> Element {
+    rsx! {
+        div { class: "dialog-backdrop",
+            div {
+                class: "dialog",
+                role: "dialog",
+                aria_modal: "true",
+                aria_label: tr!("load-error-title"),
+                tabindex: "-1",
+                onmounted: focus_on_mount,
+                div { class: "row",
+                    strong { {tr!("load-error-title")} }
+                    if has_dataset {
+                        button { class: "close", onclick: move |_| on_dismiss.call(()), "×" }
+                    }
+                }
+                ul { class: "load-errors",
+                    for failure in failures {
+                        li {
+                            code { "{failure.source}" }
+                            p { class: "tiny", "{failure.message}" }
+                        }
+                    }
+                }
+                div { class: "row",
+                    if has_dataset {
+                        button { class: "action", onclick: move |_| on_dismiss.call(()), {tr!("load-error-keep")} }
+                    } else {
+                        button { class: "action", onclick: move |_| on_use_sample.call(()), {tr!("load-error-sample")} }
+                    }
+                    button { class: "action", onclick: move |_| on_open.call(()), {tr!("load-error-open")} }
+                }
+            }
+        }
+    }
 }
 
-fn load_dataset() -> Dataset {
-    let candidates = [
-        "../data/anonymized-ratings.json",
-        "data/anonymized-ratings.json",
-        "../../data/anonymized-ratings.json",
-    ];
+/// What lenient loading dropped from the last dataset, and why.
+#[component]
+fn QualityReportPanel(report: QualityReport, on_close: EventHandler<()>) -> Element {
+    let unlisted = report.unlisted();
+    rsx! {
+        div { class: "dialog-backdrop",
+            div {
+                class: "dialog",
+                role: "dialog",
+                aria_modal: "true",
+                aria_label: tr!("quality-title"),
+                tabindex: "-1",
+                onmounted: focus_on_mount,
+                div { class: "row",
+                    strong { {tr!("quality-title")} }
+                    button { class: "close", onclick: move |_| on_close.call(()), "×" }
+                }
+                p { class: "tiny", "{report.summary()} " {tr!("quality-rest")} }
+                table { class: "preview",
+                    thead {
+                        tr {
+                            th { {tr!("quality-user")} }
+                            th { {tr!("quality-anime")} }
+                            th { {tr!("quality-problem")} }
+                        }
+                    }
+                    tbody {
+                        for issue in report.issues.iter() {
+                            tr {
+                                td { "{issue.user}" }
+                                td {
+                                    if let Some(anime_id) = issue.anime_id {
+                                        "{anime_id}"
+                                    }
+                                }
+                                td { "{issue.reason}" }
+                            }
+                        }
+                    }
+                }
+                if unlisted > 0 {
+                    p { class: "tiny", {tr!("quality-unlisted", count = unlisted)} }
+                }
+                div { class: "row",
+                    button { class: "action", onclick: move |_| on_close.call(()), {tr!("dialog-close")} }
+                }
+            }
+        }
+    }
+}
 
-    for candidate in candidates {
-        if let Ok(content) = fs::read_to_string(candidate) {
-            if let Ok(dataset) = serde_json::from_str::<Dataset>(&content) {
-                return dataset;
+/// The running background jobs with their progress and how long they have taken, each with a
+/// button to cancel it.
+#[component]
+fn TaskListPanel(tasks: Signal<TaskList>) -> Element {
+    // Redraws every second so the elapsed times keep counting.
+    let mut tick = use_signal(|| 0_u64);
+    use_future(move || async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            tick += 1;
+        }
+    });
+    let _ = tick();
+    struct Row {
+        id: u64,
+        name: String,
+        progress: Option<String>,
+        fraction: Option<f32>,
+        seconds: u64,
+    }
+    let jobs: Vec<Row> = tasks
+        .read()
+        .jobs()
+        .iter()
+        .map(|job| Row {
+            id: job.id,
+            name: job.name.clone(),
+            progress: job.progress.clone(),
+            fraction: job.fraction,
+            seconds: job.elapsed().as_secs(),
+        })
+        .collect();
+    rsx! {
+        ul { class: "task-list", aria_live: "polite",
+            for Row { id, name, progress, fraction, seconds } in jobs {
+                li { key: "{id}",
+                    div { class: "row",
+                        span { "{name}" }
+                        span { class: "tiny", "{seconds}s" }
+                        button {
+                            class: "close",
+                            title: tr!("dialog-cancel"),
+                            onclick: move |_| tasks.write().cancel(id),
+                            "×"
+                        }
+                    }
+                    if let Some(fraction) = fraction {
+                        progress { class: "task-progress", max: "1", value: "{fraction}" }
+                    }
+                    if let Some(progress) = progress {
+                        span { class: "tiny", "{progress}" }
+                    }
+                }
             }
         }
     }
+}
+
+/// The latest log entries, refreshed while open, filtered by minimum level and by module.
+/// Collapses to its header so it can stay open without covering the graph.
+#[component]
+fn LogPanel(on_close: EventHandler<()>) -> Element {
+    let mut entries = use_signal(logging::entries);
+    let mut collapsed = use_signal(|| false);
+    let mut min_level = use_signal(|| tracing::Level::INFO);
+    let mut module = use_signal(String::new);
+    use_future(move || async move {
+        loop {
+            tokio::time::sleep(Duration::from_millis(LOG_REFRESH_MS)).await;
+            if !collapsed() {
+                entries.set(logging::entries());
+            }
+        }
+    });
 
-    serde_json::from_str(SAMPLE_DATASET).expect("embedded sample dataset is valid JSON")
+    let modules: Vec<String> = entries
+        .read()
+        .iter()
+        .map(logging::LogEntry::module)
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let shown: Vec<logging::LogEntry> = entries
+        .read()
+        .iter()
+        .rev()
+        .filter(|entry| entry.level <= min_level())
+        .filter(|entry| module.read().is_empty() || entry.module() == *module.read())
+        .cloned()
+        .collect();
+    let file = logging::path().map(|path| path.display().to_string());
+    let title = tr!("log-title");
+    rsx! {
+        div { class: "log-panel",
+            div { class: "row",
+                button {
+                    class: "link",
+                    onclick: move |_| collapsed.toggle(),
+                    if collapsed() { "▸ {title}" } else { "▾ {title}" }
+                }
+                if !collapsed() {
+                    select {
+                        onchange: move |evt| {
+                            if let Ok(level) = evt.value().parse() {
+                                min_level.set(level);
+                            }
+                        },
+                        for level in [tracing::Level::ERROR, tracing::Level::WARN, tracing::Level::INFO, tracing::Level::DEBUG] {
+                            option {
+                                value: "{level}",
+                                selected: min_level() == level,
+                                {tr!("log-level", level = level)}
+                            }
+                        }
+                    }
+                    select {
+                        onchange: move |evt| module.set(evt.value()),
+                        option { value: "", selected: module.read().is_empty(), {tr!("log-all-modules")} }
+                        for name in modules {
+                            option {
+                                value: "{name}",
+                                selected: *module.read() == name,
+                                "{name}"
+                            }
+                        }
+                    }
+                }
+                button { class: "close", onclick: move |_| on_close.call(()), "×" }
+            }
+            if !collapsed() {
+                if shown.is_empty() {
+                    p { class: "tiny", {tr!("log-empty")} }
+                } else {
+                    ul { class: "log-entries",
+                        for entry in shown {
+                            li { class: "log-{entry.level.as_str().to_lowercase()}",
+                                span { class: "tiny", "{entry.elapsed:.2}s {entry.level} {entry.target}" }
+                                " {entry.message}"
+                            }
+                        }
+                    }
+                }
+                if let Some(file) = file {
+                    p { class: "tiny", {tr!("log-file", file = file)} }
+                }
+            }
+        }
+    }
 }
 
-fn build_graph(mut dataset: Dataset) -> GraphModel {
-    for user in &mut dataset.users {
-        let avg = if user.ratings.is_empty() {
-            0.0
-        } else {
-            user.ratings.iter().map(|r| r.raw_score).sum::<f64>() / user.ratings.len() as f64
-        };
-        for rating in &mut user.ratings {
-            rating.normalized_score = rating.raw_score - avg;
+/// Confirms which files to merge and how duplicate ratings are resolved.
+#[component]
+fn MergeDialog(
+    files: Vec<PathBuf>,
+    on_merge: EventHandler<(MergeStrategy, bool)>,
+    on_cancel: EventHandler<()>,
+) -> Element {
+    let mut strategy = use_signal(MergeStrategy::default);
+    let mut include_current = use_signal(|| true);
+    rsx! {
+        div { class: "dialog-backdrop",
+            div {
+                class: "dialog",
+                role: "dialog",
+                aria_modal: "true",
+                aria_label: tr!("merge-title"),
+                tabindex: "-1",
+                onmounted: focus_on_mount,
+                div { class: "row",
+                    strong { {tr!("merge-title")} }
+                    button { class: "close", onclick: move |_| on_cancel.call(()), "×" }
+                }
+                p { class: "tiny", {tr!("merge-hint")} }
+                ul { class: "load-errors",
+                    for file in files.iter() {
+                        li {
+                            code { "{file.display()}" }
+                        }
+                    }
+                }
+                div { class: "dialog-grid",
+                    label { class: "field",
+                        span { class: "tiny", {tr!("merge-duplicates")} }
+                        select {
+                            onchange: move |evt| {
+                                let next = MergeStrategy::ALL
+                                    .into_iter()
+                                    .find(|strategy| strategy.label() == evt.value())
+                                    .unwrap_or_default();
+                                strategy.set(next);
+                            },
+                            for option in MergeStrategy::ALL {
+                                option { value: option.label(), selected: option == strategy(), {i18n::duplicates_name(option)} }
+                            }
+                        }
+                    }
+                }
+                label { class: "toggle",
+                    input {
+                        r#type: "checkbox",
+                        checked: include_current(),
+                        onchange: move |evt| include_current.set(evt.checked()),
+                    }
+                    span { {tr!("merge-include-current")} }
+                }
+                div { class: "row",
+                    button { class: "action", onclick: move |_| on_cancel.call(()), {tr!("dialog-cancel")} }
+                    button {
+                        class: "action",
+                        onclick: move |_| on_merge.call((strategy(), include_current())),
+                        {tr!("merge-confirm")}
+                    }
+                }
+            }
         }
     }
+}
 
-    let mut nodes: Vec<Node> = Vec::new();
-    let mut node_index: HashMap<String, usize> = HashMap::new();
-    let mut anime_pair_weights: HashMap<(u32, u32), f64> = HashMap::new();
-    let mut edges: Vec<Edge> = Vec::new();
-
-    for user in &dataset.users {
-        let user_node_id = format!("user:{}", user.user_id);
-        let user_idx = upsert_node(
-            &mut nodes,
-            &mut node_index,
-            user_node_id,
-            format!("User {}", &user.user_id[..8.min(user.user_id.len())]),
-            NodeType::User,
-        );
+/// Sizes for a generated demo or benchmark dataset, which replaces the current one.
+#[component]
+fn SyntheticDialog(
+    on_generate: EventHandler<SyntheticOptions>,
+    on_cancel: EventHandler<()>,
+) -> Element {
+    let mut options = use_signal(SyntheticOptions::default);
+    let current = options();
+    let number_field =
+        |label: String, value: String, step: &'static str, set: fn(&mut SyntheticOptions, f64)| {
+            rsx! {
+                label { class: "field",
+                    span { class: "tiny", "{label}" }
+                    input {
+                        r#type: "number",
+                        min: "0",
+                        step,
+                        value,
+                        onchange: move |evt| {
+                            if let Ok(number) = evt.value().parse::<f64>() {
+                                set(&mut options.write(), number.max(0.0));
+                            }
+                        },
+                    }
+                }
+            }
+        };
+    rsx! {
+        div { class: "dialog-backdrop",
+            div {
+                class: "dialog",
+                role: "dialog",
+                aria_modal: "true",
+                aria_label: tr!("synthetic-title"),
+                tabindex: "-1",
+                onmounted: focus_on_mount,
+                div { class: "row",
+                    strong { {tr!("synthetic-title")} }
+                    button { class: "close", onclick: move |_| on_cancel.call(()), "×" }
+                }
+                p { class: "tiny", {tr!("synthetic-hint")} }
+                div { class: "dialog-grid",
+                    {number_field(tr!("synthetic-users"), current.users.to_string(), "1", |o, n| o.users = n as usize)}
+                    {number_field(tr!("synthetic-anime"), current.anime.to_string(), "1", |o, n| o.anime = (n as usize).max(1))}
+                    {number_field(tr!("synthetic-density"), current.density.to_string(), "0.01", |o, n| o.density = n.min(1.0))}
+                    {number_field(tr!("synthetic-skew"), current.popularity_exponent.to_string(), "0.1", |o, n| o.popularity_exponent = n)}
+                    {number_field(tr!("synthetic-seed"), current.seed.to_string(), "1", |o, n| o.seed = n as u64)}
+                }
+                div { class: "row",
+                    button { class: "action", onclick: move |_| on_cancel.call(()), {tr!("dialog-cancel")} }
+                    button {
+                        class: "action",
+                        disabled: current.users == 0,
+                        onclick: move |_| on_generate.call(options()),
+                        {tr!("synthetic-generate")}
+                    }
+                }
+            }
+        }
+    }
+}
 
-        for rating in &user.ratings {
-            let anime_node_id = format!("anime:{}", rating.anime_id);
-            let anime_idx = upsert_node(
-                &mut nodes,
-                &mut node_index,
-                anime_node_id,
-                rating.title.clone(),
-                NodeType::Anime,
-            );
+/// Signs in to MyAnimeList or AniList so ratings marked in the app are pushed to the list.
+/// The natural-language search box and its results; a result selects the anime in the graph.
+#[cfg(feature = "semantic")]
+#[component]
+fn SemanticSearchPanel(
+    busy: bool,
+    results: Option<(String, Vec<semantic::SemanticMatch>)>,
+    on_search: EventHandler<String>,
+    on_pick: EventHandler<u32>,
+) -> Element {
+    let mut query = use_signal(String::new);
+    rsx! {
+        div { class: "field",
+            span { class: "tiny", {tr!("semantic-label")} }
+            input {
+                r#type: "text",
+                placeholder: tr!("semantic-placeholder"),
+                value: "{query}",
+                oninput: move |evt| query.set(evt.value()),
+                onkeydown: move |evt| {
+                    if evt.key() == Key::Enter {
+                        on_search.call(query());
+                    }
+                },
+            }
+            button {
+                class: "action",
+                disabled: busy,
+                onclick: move |_| on_search.call(query()),
+                if busy { {tr!("semantic-searching")} } else { {tr!("semantic-search")} }
+            }
+        }
+        if let Some((asked, results)) = results {
+            p { class: "tiny", {tr!("semantic-results", query = asked)} }
+            ol { class: "semantic-results",
+                for found in results {
+                    li {
+                        button {
+                            class: "link",
+                            onclick: move |_| on_pick.call(found.anime_id),
+                            "{found.title}"
+                        }
+                        span { class: "tiny",
+                            match found.taste {
+                                Some(taste) => format!(
+                                    " — {}",
+                                    tr!(
+                                        "semantic-match-taste",
+                                        relevance = format!("{:.2}", found.relevance),
+                                        taste = format!("{taste:.2}"),
+                                    )
+                                ),
+                                None => format!(
+                                    " — {}",
+                                    tr!("semantic-match", relevance = format!("{:.2}", found.relevance))
+                                ),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
 
-            edges.push(Edge {
-                source: user_idx,
-                target: anime_idx,
-                color: "#f4d35ea6",
-                stroke_width: 1.5,
-            });
+/// Options for exporting the current dataset with usernames hashed and scores perturbed.
+#[component]
+fn AnonymizeDialog(
+    on_export: EventHandler<AnonymizeOptions>,
+    on_cancel: EventHandler<()>,
+) -> Element {
+    let mut options = use_signal(|| AnonymizeOptions {
+        salt: anonymize::random_salt(),
+        ..AnonymizeOptions::default()
+    });
+    let current = options();
+    rsx! {
+        div { class: "dialog-backdrop",
+            div {
+                class: "dialog",
+                role: "dialog",
+                aria_modal: "true",
+                aria_label: tr!("anonymize-title"),
+                tabindex: "-1",
+                onmounted: focus_on_mount,
+                div { class: "row",
+                    strong { {tr!("anonymize-title")} }
+                    button { class: "close", onclick: move |_| on_cancel.call(()), "×" }
+                }
+                p { class: "tiny", {tr!("anonymize-hint")} }
+                div { class: "dialog-grid",
+                    label { class: "field",
+                        span { class: "tiny", {tr!("anonymize-salt")} }
+                        input {
+                            r#type: "text",
+                            value: "{current.salt}",
+                            oninput: move |evt| options.write().salt = evt.value(),
+                        }
+                    }
+                    label { class: "field",
+                        span { class: "tiny", {tr!("anonymize-k")} }
+                        input {
+                            r#type: "number",
+                            min: "1",
+                            value: "{current.k}",
+                            onchange: move |evt| {
+                                if let Ok(k) = evt.value().parse::<usize>() {
+                                    options.write().k = k.max(1);
+                                }
+                            },
+                        }
+                    }
+                    label { class: "field",
+                        span { class: "tiny", {tr!("anonymize-noise")} }
+                        select {
+                            onchange: move |evt| {
+                                let noise = ScoreNoise::KINDS
+                                    .into_iter()
+                                    .find(|noise| noise.label() == evt.value())
+                                    .unwrap_or_default();
+                                options.write().noise = noise;
+                            },
+                            for noise in ScoreNoise::KINDS {
+                                option {
+                                    value: noise.label(),
+                                    selected: noise.label() == current.noise.label(),
+                                    {noise_name(noise)}
+                                }
+                            }
+                        }
+                    }
+                    if let Some(amount) = current.noise.amount() {
+                        label { class: "field",
+                            span { class: "tiny", {tr!("anonymize-amount")} }
+                            input {
+                                r#type: "number",
+                                min: "0.01",
+                                step: "0.1",
+                                value: "{amount}",
+                                onchange: move |evt| {
+                                    if let Ok(amount) = evt.value().parse::<f64>() {
+                                        let noise = options.peek().noise.with_amount(amount.max(0.01));
+                                        options.write().noise = noise;
+                                    }
+                                },
+                            }
+                        }
+                    }
+                }
+                label { class: "toggle",
+                    input {
+                        r#type: "checkbox",
+                        checked: current.keep_dates,
+                        onchange: move |evt| options.write().keep_dates = evt.checked(),
+                    }
+                    span { {tr!("anonymize-keep-dates")} }
+                }
+                div { class: "row",
+                    button { class: "action", onclick: move |_| on_cancel.call(()), {tr!("dialog-cancel")} }
+                    button {
+                        class: "action",
+                        disabled: current.salt.trim().is_empty(),
+                        onclick: move |_| on_export.call(options()),
+                        {tr!("anonymize-export")}
+                    }
+                }
+            }
         }
+    }
+}
 
-        for i in 0..user.ratings.len() {
-            for j in (i + 1)..user.ratings.len() {
-                let left = &user.ratings[i];
-                let right = &user.ratings[j];
-                let pair_key = if left.anime_id < right.anime_id {
-                    (left.anime_id, right.anime_id)
-                } else {
-                    (right.anime_id, left.anime_id)
-                };
-                let pair_score = (left.normalized_score + right.normalized_score) / 2.0;
+fn noise_name(noise: ScoreNoise) -> String {
+    tr!(match noise {
+        ScoreNoise::None => "anonymize-noise-none",
+        ScoreNoise::Jitter(_) => "anonymize-noise-jitter",
+        ScoreNoise::Laplace { .. } => "anonymize-noise-laplace",
+    })
+}
 
-                anime_pair_weights
-                    .entry(pair_key)
-                    .and_modify(|weight| *weight = (*weight + pair_score) / 2.0)
-                    .or_insert(pair_score);
+/// A dataset source that failed to load, listed in the load-error panel.
+#[derive(Debug, Clone, PartialEq)]
+struct LoadFailure {
+    source: String,
+    message: String,
+}
+
+/// Result of trying the startup dataset sources.
+struct StartupLoad {
+    /// The first source that loaded; the sample when none were configured; empty when every
+    /// source failed, so the sample is only used once the user confirms.
+    dataset: Dataset,
+    failures: Vec<LoadFailure>,
+    report: QualityReport,
+    /// File the dataset was read from, watched for changes.
+    path: Option<PathBuf>,
+}
+
+/// Tries, in order: a path passed on the command line or else the restored session's dataset,
+/// `ratings.db` (with the `sqlite` feature), the dataset pinned in the settings, and the most
+/// recently opened dataset. Every failure is kept for the error panel.
+fn load_startup_dataset(config: &AppConfig, session_dataset: Option<&Path>) -> StartupLoad {
+    let mut failures = Vec::new();
+    let mut tried = false;
+
+    let requested = std::env::args_os()
+        .skip(1)
+        .find(|arg| arg != session::FRESH_FLAG)
+        .map(PathBuf::from)
+        .or_else(|| session_dataset.map(Path::to_path_buf));
+    if let Some(path) = requested {
+        tried = true;
+        match read_dataset(&path, config.duplicates) {
+            Ok((dataset, report)) => {
+                return StartupLoad {
+                    dataset,
+                    failures,
+                    report,
+                    path: Some(path.clone()),
+                }
             }
+            Err(err) => failures.push(LoadFailure {
+                source: path.display().to_string(),
+                message: err.to_string(),
+            }),
         }
     }
 
-    for ((left, right), weight) in anime_pair_weights {
-        if let (Some(source), Some(target)) = (
-            node_index.get(&format!("anime:{left}")),
-            node_index.get(&format!("anime:{right}")),
-        ) {
-            let width = (0.35 + weight.abs() as f32 * 0.12).clamp(0.35, 2.2);
-            edges.push(Edge {
-                source: *source,
-                target: *target,
-                color: "#6fffe980",
-                stroke_width: width,
+    #[cfg(feature = "sqlite")]
+    match store::RatingStore::open().and_then(|store| store.load()) {
+        Ok(Some(dataset)) => {
+            return StartupLoad {
+                dataset,
+                failures,
+                report: QualityReport::default(),
+                path: None,
+            }
+        }
+        Ok(None) => {}
+        Err(err) => {
+            tried = true;
+            failures.push(LoadFailure {
+                source: store::RatingStore::path()
+                    .map_or_else(|| "ratings.db".to_string(), |p| p.display().to_string()),
+                message: err.to_string(),
             });
         }
     }
 
-    layout_nodes(&mut nodes);
-
-    let render_edges = edges
-        .into_iter()
-        .map(|edge| RenderEdge {
-            x1: nodes[edge.source].x,
-            y1: nodes[edge.source].y,
-            x2: nodes[edge.target].x,
-            y2: nodes[edge.target].y,
-            color: edge.color,
-            stroke_width: edge.stroke_width,
+    let last_dataset = config.dataset.as_ref().or_else(|| {
+        config.recent_files.iter().find(|path| {
+            matches!(
+                compression::inner_extension(path).as_deref(),
+                Some("json" | "parquet")
+            )
         })
-        .collect::<Vec<_>>();
-
-    let user_count = nodes.iter().filter(|n| n.node_type == NodeType::User).count();
-    let anime_count = nodes.len() - user_count;
+    });
+    if let Some(path) = last_dataset {
+        tried = true;
+        match read_dataset(path, config.duplicates) {
+            Ok((dataset, report)) => {
+                return StartupLoad {
+                    dataset,
+                    failures,
+                    report,
+                    path: Some(path.clone()),
+                }
+            }
+            Err(err) => failures.push(LoadFailure {
+                source: path.display().to_string(),
+                message: err.to_string(),
+            }),
+        }
+    }
 
-    GraphModel {
-        user_count,
-        anime_count,
-        nodes,
-        edges: render_edges,
+    let dataset = if tried {
+        Dataset::new(Vec::new())
+    } else {
+        sample_dataset().unwrap_or_else(|failure| {
+            failures.push(failure);
+            Dataset::new(Vec::new())
+        })
+    };
+    StartupLoad {
+        dataset,
+        failures,
+        report: QualityReport::default(),
+        path: None,
     }
 }
 
-fn upsert_node(
-    nodes: &mut Vec<Node>,
-    node_index: &mut HashMap<String, usize>,
-    id: String,
-    label: String,
-    node_type: NodeType,
-) -> usize {
-    if let Some(existing) = node_index.get(&id) {
-        return *existing;
-    }
+/// The id map from the config, if one is set; errors are already formatted for the status line.
+fn load_startup_id_map() -> Result<Option<idmap::IdMap>, String> {
+    let Some(path) = AppConfig::load().id_map else {
+        return Ok(None);
+    };
+    idmap::IdMap::load(&path)
+        .map(Some)
+        .map_err(|err| format!("Could not load the id map {}: {err}", path.display()))
+}
 
-    let node = match node_type {
-        NodeType::User => Node {
-            id: id.clone(),
-            label,
-            node_type,
-            x: WIDTH / 2.0,
-            y: HEIGHT / 2.0,
-            radius: 7.0,
-            color: "#ff8a00",
-        },
-        NodeType::Anime => Node {
-            id: id.clone(),
-            label,
-            node_type,
-            x: WIDTH / 2.0,
-            y: HEIGHT / 2.0,
-            radius: 3.8,
-            color: "#0f8b8d",
-        },
-    };
-
-    let idx = nodes.len();
-    nodes.push(node);
-    node_index.insert(id, idx);
-    idx
+/// The sample bundled into the binary; it only fails to load if the build embedded a broken
+/// file, which is reported like any other dataset that failed.
+fn sample_dataset() -> Result<Dataset, LoadFailure> {
+    let failure = |message: String| LoadFailure {
+        source: "the built-in sample dataset".to_string(),
+        message,
+    };
+    let mut dataset: Dataset =
+        serde_json::from_str(SAMPLE_DATASET).map_err(|err| failure(err.to_string()))?;
+    schema::migrate(&mut dataset).map_err(|err| failure(err.to_string()))?;
+    Ok(dataset)
 }
 
-fn layout_nodes(nodes: &mut [Node]) {
-    let mut users = Vec::new();
-    let mut anime = Vec::new();
+/// Reads a dataset file, noting it first so a crash while reading or building it can name it.
+fn read_dataset(
+    path: &Path,
+    duplicates: MergeStrategy,
+) -> Result<(Dataset, QualityReport), DatasetError> {
+    crash::note_file(path);
+    wasiw_core::dataset::read(path, duplicates)
+}
 
-    for (idx, node) in nodes.iter().enumerate() {
-        if node.node_type == NodeType::User {
-            users.push(idx);
-        } else {
-            anime.push(idx);
-        }
-    }
+/// Persists an imported profile in `ratings.db` when built with the `sqlite` feature.
+/// Best effort, like saving the config: the import still applies this session on failure.
+fn store_user(user: &UserRatings) {
+    #[cfg(feature = "sqlite")]
+    let _ = store::RatingStore::open().and_then(|mut store| store.save_user(user));
+    #[cfg(not(feature = "sqlite"))]
+    let _ = user;
+}
 
-    for (i, idx) in users.iter().enumerate() {
-        let angle = (i as f32 / users.len().max(1) as f32) * std::f32::consts::TAU;
-        let radius = (HEIGHT.min(WIDTH) * 0.38).max(200.0);
-        nodes[*idx].x = WIDTH / 2.0 + radius * angle.cos();
-        nodes[*idx].y = HEIGHT / 2.0 + radius * angle.sin();
+/// Writes the current dataset, imported profiles included, as JSON or CSV (compressed per the
+/// extension) or Parquet.
+fn write_dataset(path: &Path, dataset: &Dataset) -> Result<(), import::ImportError> {
+    let mut dataset = dataset.clone();
+    dataset.normalize_scores();
+    let extension = compression::inner_extension(path);
+    #[cfg(feature = "parquet")]
+    if extension.as_deref() == Some(columnar::EXTENSION) {
+        return Ok(columnar::write(path, &dataset)?);
     }
-
-    for (i, idx) in anime.iter().enumerate() {
-        let angle = (i as f32 / anime.len().max(1) as f32) * std::f32::consts::TAU;
-        let band = 120.0 + ((i % 7) as f32 * 17.0);
-        let jitter = ((i * 29 % 17) as f32) - 8.0;
-        nodes[*idx].x = WIDTH / 2.0 + (band + jitter) * angle.cos();
-        nodes[*idx].y = HEIGHT / 2.0 + (band - jitter) * angle.sin();
+    if extension.as_deref() == Some("csv") {
+        compression::create(path, |writer| import::csv::write(writer, &dataset))?;
+        return Ok(());
     }
+    compression::create(path, |writer| {
+        serde_json::to_writer(writer, &dataset).map_err(io::Error::other)
+    })?;
+    Ok(())
 }
 
-const APP_CSS: &str = r#"
-  .app {
-    margin: 0;
-    min-height: 100vh;
-    display: grid;
-    grid-template-columns: 320px 1fr;
-    gap: 16px;
-    padding: 16px;
-    background: radial-gradient(circle at 20% 20%, #2e5678 0%, transparent 45%),
-      linear-gradient(160deg, #091019 0%, #17354f 100%);
-    color: #f4f1de;
-    font-family: Segoe UI, sans-serif;
-    box-sizing: border-box;
-  }
-  .panel {
-    border: 1px solid #ffffff26;
-    border-radius: 14px;
-    padding: 14px;
-    background: #0e1723cc;
-  }
-  .muted {
-    color: #b0b8c0;
-    margin-top: 0;
-  }
-  .stats {
-    margin-top: 14px;
-    border: 1px solid #ffffff1f;
-    border-radius: 12px;
-    padding: 10px;
-  }
-  .row {
-    display: flex;
-    justify-content: space-between;
-    font-size: 14px;
-    padding: 2px 0;
-  }
-  .tiny {
-    color: #b0b8c0;
-    font-size: 12px;
-  }
-  .canvas-wrap {
-    border: 1px solid #ffffff26;
-    border-radius: 14px;
-    overflow: hidden;
-    background: #070d14;
-  }
-"#;
-
 const SAMPLE_DATASET: &str = r#"
 {
   "users": [
@@ -0,0 +1,338 @@
+//! Native menu bar: File → Open dataset…, Open recent, Merge datasets…, Generate synthetic
+//! dataset…, Load id map…, Fetch anime metadata, Save dataset as…, Export anonymized… and
+//! Export graph…; Accounts → Connect MyAnimeList…, Connect AniList… and Disconnect accounts;
+//! plus the usual window items.
+
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+use dioxus::desktop::muda::{self, Menu, MenuId, MenuItem, PredefinedMenuItem, Submenu};
+
+use wasiw_core::graph_export::GraphFormat;
+use wasiw_ui::i18n::tr;
+
+use crate::recommend_export::RecommendationFormat;
+
+pub const OPEN_DATASET_ID: &str = "open-dataset";
+pub const MERGE_DATASETS_ID: &str = "merge-datasets";
+pub const GENERATE_ID: &str = "generate-synthetic";
+pub const LOAD_ID_MAP_ID: &str = "load-id-map";
+pub const FETCH_METADATA_ID: &str = "fetch-metadata";
+pub const SAVE_DATASET_ID: &str = "save-dataset";
+pub const ANONYMIZE_ID: &str = "export-anonymized";
+pub const EXPORT_GRAPH_ID: &str = "export-graph";
+pub const CONNECT_MAL_ID: &str = "connect-mal";
+pub const CONNECT_ANILIST_ID: &str = "connect-anilist";
+pub const DISCONNECT_ID: &str = "disconnect-accounts";
+const RECENT_ID_PREFIX: &str = "recent:";
+
+const FILE_MENU: &str = "menu-file";
+const ACCOUNTS_MENU: &str = "menu-accounts";
+
+/// A File or Accounts menu item; the command palette offers the same actions.
+pub struct MenuAction {
+    pub id: &'static str,
+    /// Text key of the menu it is in.
+    menu: &'static str,
+}
+
+impl MenuAction {
+    pub fn label(&self) -> String {
+        tr(&format!("menu-{}", self.id))
+    }
+
+    pub fn menu(&self) -> String {
+        tr(self.menu)
+    }
+}
+
+pub const ACTIONS: [MenuAction; 11] = [
+    action(OPEN_DATASET_ID, FILE_MENU),
+    action(MERGE_DATASETS_ID, FILE_MENU),
+    action(GENERATE_ID, FILE_MENU),
+    action(LOAD_ID_MAP_ID, FILE_MENU),
+    action(FETCH_METADATA_ID, FILE_MENU),
+    action(SAVE_DATASET_ID, FILE_MENU),
+    action(ANONYMIZE_ID, FILE_MENU),
+    action(EXPORT_GRAPH_ID, FILE_MENU),
+    action(CONNECT_MAL_ID, ACCOUNTS_MENU),
+    action(CONNECT_ANILIST_ID, ACCOUNTS_MENU),
+    action(DISCONNECT_ID, ACCOUNTS_MENU),
+];
+
+const fn action(id: &'static str, menu: &'static str) -> MenuAction {
+    MenuAction { id, menu }
+}
+
+/// The menu item for the action with `id`.
+fn item(id: &str) -> MenuItem {
+    MenuItem::with_id(id, tr(&format!("menu-{id}")), true, None)
+}
+
+thread_local! {
+    // Muda menus are not `Send`; the submenu is rebuilt from the UI thread when recents change.
+    static RECENT_MENU: RefCell<Option<Submenu>> = const { RefCell::new(None) };
+}
+
+/// File types offered by the open dialog and accepted by `open_file`.
+pub fn file_filters() -> Vec<(&'static str, &'static [&'static str])> {
+    #[allow(unused_mut)] // Only extended with the `parquet` feature.
+    let mut filters: Vec<(&'static str, &'static [&'static str])> = vec![
+        ("Rating dataset", &["json"]),
+        ("MyAnimeList export", &["xml"]),
+        ("CSV ratings", &["csv"]),
+    ];
+    #[cfg(feature = "parquet")]
+    filters.insert(1, ("Parquet dataset", &[wasiw_core::columnar::EXTENSION]));
+    filters
+}
+
+/// Text formats above may also be gzip- or zstd-compressed.
+const COMPRESSED_EXTENSIONS: [&str; 2] = ["gz", "zst"];
+
+/// Output formats offered when saving; the format follows the chosen extension.
+fn save_filters() -> Vec<(&'static str, &'static str)> {
+    #[allow(unused_mut)] // Only extended with the `parquet` feature.
+    let mut filters = vec![
+        ("Gzip-compressed JSON", "gz"),
+        ("Zstandard-compressed JSON", "zst"),
+        ("JSON", "json"),
+        ("CSV, one row per rating", "csv"),
+    ];
+    #[cfg(feature = "parquet")]
+    filters.insert(0, ("Parquet", wasiw_core::columnar::EXTENSION));
+    filters
+}
+
+pub fn build(recent: &[PathBuf]) -> muda::Result<Menu> {
+    let open = item(OPEN_DATASET_ID);
+    let merge = item(MERGE_DATASETS_ID);
+    let generate = item(GENERATE_ID);
+    let id_map = item(LOAD_ID_MAP_ID);
+    let metadata = item(FETCH_METADATA_ID);
+    let save = item(SAVE_DATASET_ID);
+    let anonymize = item(ANONYMIZE_ID);
+    let export_graph = item(EXPORT_GRAPH_ID);
+    let recent_menu = Submenu::new(tr("menu-open-recent"), !recent.is_empty());
+    fill_recent(&recent_menu, recent);
+
+    let file_menu = Submenu::new(tr(FILE_MENU), true);
+    file_menu.append_items(&[
+        &open,
+        &recent_menu,
+        &merge,
+        &generate,
+        &id_map,
+        &metadata,
+        &save,
+        &anonymize,
+        &export_graph,
+        &PredefinedMenuItem::separator(),
+        &PredefinedMenuItem::quit(None),
+    ])?;
+
+    let accounts_menu = Submenu::new(tr(ACCOUNTS_MENU), true);
+    accounts_menu.append_items(&[
+        &item(CONNECT_MAL_ID),
+        &item(CONNECT_ANILIST_ID),
+        &PredefinedMenuItem::separator(),
+        &item(DISCONNECT_ID),
+    ])?;
+
+    let window_menu = Submenu::new(tr("menu-window"), true);
+    window_menu.append_items(&[
+        &PredefinedMenuItem::fullscreen(None),
+        &PredefinedMenuItem::maximize(None),
+        &PredefinedMenuItem::minimize(None),
+        &PredefinedMenuItem::separator(),
+        &PredefinedMenuItem::close_window(None),
+    ])?;
+
+    let menu = Menu::new();
+    menu.append_items(&[&file_menu, &accounts_menu, &window_menu])?;
+    RECENT_MENU.with(|slot| *slot.borrow_mut() = Some(recent_menu));
+    Ok(menu)
+}
+
+/// Replaces the Open recent entries after the list in the config changed.
+pub fn set_recent(recent: &[PathBuf]) {
+    RECENT_MENU.with(|slot| {
+        if let Some(menu) = slot.borrow().as_ref() {
+            while menu.remove_at(0).is_some() {}
+            fill_recent(menu, recent);
+            menu.set_enabled(!recent.is_empty());
+        }
+    });
+}
+
+fn fill_recent(menu: &Submenu, recent: &[PathBuf]) {
+    for (index, path) in recent.iter().enumerate() {
+        let item = MenuItem::with_id(
+            format!("{RECENT_ID_PREFIX}{index}"),
+            path.display().to_string(),
+            true,
+            None,
+        );
+        let _ = menu.append(&item);
+    }
+}
+
+/// Index into the recent-files list for an Open recent menu event.
+pub fn recent_index(id: &MenuId) -> Option<usize> {
+    id.as_ref().strip_prefix(RECENT_ID_PREFIX)?.parse().ok()
+}
+
+/// Shows the native open dialog filtered to the supported file types.
+pub async fn pick_file(start_in: Option<&Path>) -> Option<PathBuf> {
+    let mut dialog = rfd::AsyncFileDialog::new().set_title("Open dataset");
+    // Filters only see the last extension, so compressed files are offered under every type.
+    let with_compressed = |extensions: &[&'static str]| -> Vec<&'static str> {
+        extensions
+            .iter()
+            .copied()
+            .chain(COMPRESSED_EXTENSIONS)
+            .collect()
+    };
+    let filters = file_filters();
+    let all: Vec<&str> = filters
+        .iter()
+        .flat_map(|(_, extensions)| extensions.iter().copied())
+        .collect();
+    dialog = dialog.add_filter("All supported files", &with_compressed(&all));
+    for (name, extensions) in filters {
+        dialog = dialog.add_filter(name, &with_compressed(extensions));
+    }
+    if let Some(dir) = start_in {
+        dialog = dialog.set_directory(dir);
+    }
+    dialog
+        .pick_file()
+        .await
+        .map(|file| file.path().to_path_buf())
+}
+
+/// Shows the native open dialog for one or more dataset files to merge.
+pub async fn pick_datasets() -> Vec<PathBuf> {
+    let mut extensions = vec!["json"];
+    #[cfg(feature = "parquet")]
+    extensions.push(wasiw_core::columnar::EXTENSION);
+    extensions.extend(COMPRESSED_EXTENSIONS);
+    rfd::AsyncFileDialog::new()
+        .set_title("Merge datasets")
+        .add_filter("Rating datasets", &extensions)
+        .pick_files()
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|file| file.path().to_path_buf())
+        .collect()
+}
+
+/// Shows the native open dialog for an anime-offline-database file.
+pub async fn pick_id_map() -> Option<PathBuf> {
+    let mut extensions = vec!["json"];
+    extensions.extend(COMPRESSED_EXTENSIONS);
+    rfd::AsyncFileDialog::new()
+        .set_title("Load anime-offline-database")
+        .add_filter("anime-offline-database", &extensions)
+        .pick_file()
+        .await
+        .map(|file| file.path().to_path_buf())
+}
+
+/// Shows the native save dialog; the default name is compressed since rating JSON shrinks ~10×.
+pub async fn pick_save_file() -> Option<PathBuf> {
+    let mut dialog = rfd::AsyncFileDialog::new()
+        .set_title("Save dataset")
+        .set_file_name("anonymized-ratings.json.gz");
+    for (name, extension) in save_filters() {
+        dialog = dialog.add_filter(name, &[extension]);
+    }
+    dialog
+        .save_file()
+        .await
+        .map(|file| file.path().to_path_buf())
+}
+
+/// Save dialog for a graph export; the format follows the chosen extension.
+pub async fn pick_graph_export() -> Option<PathBuf> {
+    let mut dialog = rfd::AsyncFileDialog::new()
+        .set_title("Export graph")
+        .set_file_name("anime-graph.graphml");
+    for format in GraphFormat::ALL {
+        dialog = dialog.add_filter(format.label(), &[format.extension()]);
+    }
+    dialog
+        .save_file()
+        .await
+        .map(|file| file.path().to_path_buf())
+}
+
+/// Save dialog for a screenshot of the graph.
+pub async fn pick_screenshot() -> Option<PathBuf> {
+    rfd::AsyncFileDialog::new()
+        .set_title("Save screenshot")
+        .set_file_name("anime-graph.svg")
+        .add_filter("SVG image", &["svg"])
+        .save_file()
+        .await
+        .map(|file| file.path().to_path_buf())
+}
+
+/// Save dialog for a recommendations export; the format follows the chosen extension.
+pub async fn pick_recommendations_export() -> Option<PathBuf> {
+    let mut dialog = rfd::AsyncFileDialog::new()
+        .set_title("Export recommendations")
+        .set_file_name("recommendations.md");
+    for format in RecommendationFormat::ALL {
+        dialog = dialog.add_filter(format.label(), &[format.extension()]);
+    }
+    dialog
+        .save_file()
+        .await
+        .map(|file| file.path().to_path_buf())
+}
+
+/// Save dialog for the dataset health report.
+pub async fn pick_health_report() -> Option<PathBuf> {
+    rfd::AsyncFileDialog::new()
+        .set_title("Save health report")
+        .set_file_name("dataset-health.json")
+        .add_filter("JSON", &["json"])
+        .save_file()
+        .await
+        .map(|file| file.path().to_path_buf())
+}
+
+/// Save dialog for the taste tribes report.
+pub async fn pick_tribe_report() -> Option<PathBuf> {
+    rfd::AsyncFileDialog::new()
+        .set_title("Save tribes report")
+        .set_file_name("taste-tribes.md")
+        .add_filter("Markdown", &["md"])
+        .save_file()
+        .await
+        .map(|file| file.path().to_path_buf())
+}
+
+/// Save dialog for a watch-order checklist.
+pub async fn pick_checklist() -> Option<PathBuf> {
+    rfd::AsyncFileDialog::new()
+        .set_title("Save watch order")
+        .set_file_name("watch-order.md")
+        .add_filter("Markdown checklist", &["md", "markdown"])
+        .save_file()
+        .await
+        .map(|file| file.path().to_path_buf())
+}
+
+/// Save dialog for a shareable HTML report.
+pub async fn pick_report() -> Option<PathBuf> {
+    rfd::AsyncFileDialog::new()
+        .set_title("Save report")
+        .set_file_name("anime-report.html")
+        .add_filter("HTML page", &["html", "htm"])
+        .save_file()
+        .await
+        .map(|file| file.path().to_path_buf())
+}
@@ -0,0 +1,721 @@
+//! Anime metadata enrichment: titles, genres, tags, studios, directors, episode count, year,
+//! mean score, synopsis, poster and sequel, prequel and side-story relations for the anime in
+//! the dataset. Metadata is fetched from AniList in batches, with Jikan filling in what AniList
+//! doesn't list, and cached in `metadata.json` in the platform data directory so each anime is
+//! only fetched once.
+
+use std::collections::HashMap;
+use std::io;
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use wasiw_core::content::ContentFeatures;
+use wasiw_core::relations::{Relation, RelationKind, Relations};
+use wasiw_core::titles::{AnimeTitles, TitleForm};
+use wasiw_core::{GraphMode, GraphModel};
+
+use crate::config;
+use crate::idmap;
+use crate::import::http::{get_json, RateLimiter};
+use crate::import::{jikan, ImportError};
+
+const CACHE_FILE_NAME: &str = "metadata.json";
+/// AniList's page size limit.
+pub const BATCH_SIZE: usize = 50;
+
+/// AniList ranks how well each tag fits an anime out of 100; tags below this are left out.
+const MIN_TAG_RANK: u32 = 60;
+
+/// AniList allows 90 requests a minute.
+static ANILIST_LIMITER: RateLimiter = RateLimiter::new(Duration::from_millis(700));
+
+const ANILIST_ENDPOINT: &str = "https://graphql.anilist.co";
+const MEDIA_QUERY: &str = r#"
+query ($ids: [Int]) {
+  Page(perPage: 50) {
+    media(idMal_in: $ids, type: ANIME) {
+      idMal
+      title { romaji english native }
+      genres
+      tags { name rank isMediaSpoiler }
+      studios(isMain: true) { nodes { name } }
+      staff(sort: RELEVANCE, perPage: 8) { edges { role node { name { full } } } }
+      averageScore
+      episodes
+      duration
+      seasonYear
+      startDate { year }
+      description(asHtml: false)
+      coverImage { large }
+      relations { edges { relationType(version: 2) node { idMal type } } }
+    }
+  }
+}
+"#;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnimeMetadata {
+    #[serde(default, skip_serializing_if = "AnimeTitles::is_empty")]
+    pub titles: AnimeTitles,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub genres: Vec<String>,
+    /// AniList's tags, or Jikan's themes and demographics, such as `Iyashikei`. `None` when
+    /// the anime was cached before tags were fetched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub studios: Vec<String>,
+    /// `None` when the source doesn't list staff, or the anime was cached before directors
+    /// were fetched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub directors: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub episodes: Option<u32>,
+    /// Minutes per episode, for estimating how long a show takes to watch.
+    #[serde(
+        rename = "episodeMinutes",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub episode_minutes: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub year: Option<u32>,
+    /// Mean user score on the 1–10 scale.
+    #[serde(rename = "meanScore", default, skip_serializing_if = "Option::is_none")]
+    pub mean_score: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub synopsis: Option<String>,
+    #[serde(rename = "posterUrl", default, skip_serializing_if = "Option::is_none")]
+    pub poster_url: Option<String>,
+    /// Other anime this one is a sequel, prequel, side story and so on of. `None` when the
+    /// source doesn't list them, or the anime was cached before relations were fetched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relations: Option<Vec<Relation>>,
+}
+
+impl AnimeMetadata {
+    /// Year, episode count and genres, e.g. `2006 · 37 episodes · Mystery, Thriller`,
+    /// skipping whatever is unknown.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(year) = self.year {
+            parts.push(year.to_string());
+        }
+        match self.episodes {
+            Some(1) => parts.push("1 episode".to_string()),
+            Some(episodes) => parts.push(format!("{episodes} episodes")),
+            None => {}
+        }
+        if !self.genres.is_empty() {
+            parts.push(self.genres.join(", "));
+        }
+        parts.join(" · ")
+    }
+
+    pub fn content_features(&self) -> ContentFeatures {
+        ContentFeatures {
+            genres: self.genres.clone(),
+            studios: self.studios.clone(),
+            year: self.year,
+            mean_score: self.mean_score,
+        }
+    }
+}
+
+/// Metadata by anime id. Anime that neither source knows are kept with empty metadata, so
+/// they aren't requested again.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetadataCache {
+    anime: HashMap<u32, AnimeMetadata>,
+}
+
+impl MetadataCache {
+    /// Reads the cache, starting empty when it is missing or unreadable.
+    pub fn load() -> Self {
+        config::read_data_file(CACHE_FILE_NAME)
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let content = serde_json::to_vec(self).map_err(io::Error::other)?;
+        config::write_data_file(CACHE_FILE_NAME, &content)
+    }
+
+    pub fn len(&self) -> usize {
+        self.anime.len()
+    }
+
+    pub fn get(&self, anime_id: u32) -> Option<&AnimeMetadata> {
+        self.anime.get(&anime_id)
+    }
+
+    pub fn extend(&mut self, entries: impl IntoIterator<Item = (u32, AnimeMetadata)>) {
+        self.anime.extend(entries);
+    }
+
+    /// An anime's values of the facet graph `mode` is about, such as its genres.
+    pub fn facets(&self, anime_id: u32, mode: GraphMode) -> Vec<String> {
+        match (self.get(anime_id), mode) {
+            (Some(metadata), GraphMode::Genres) => metadata.genres.clone(),
+            (Some(metadata), GraphMode::Studios) => metadata.studios.clone(),
+            (Some(metadata), GraphMode::Directors) => {
+                metadata.directors.clone().unwrap_or_default()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// An anime's genres and tags, the labels moods are made of.
+    pub fn mood_tags(&self, anime_id: u32) -> Vec<String> {
+        self.get(anime_id).map_or_else(Vec::new, |metadata| {
+            let tags = metadata.tags.iter().flatten();
+            metadata.genres.iter().chain(tags).cloned().collect()
+        })
+    }
+
+    /// The relations of every cached anime.
+    pub fn relations(&self) -> Relations {
+        Relations::new(
+            self.anime.iter().filter_map(|(&anime_id, metadata)| {
+                Some((anime_id, metadata.relations.as_deref()?))
+            }),
+        )
+    }
+
+    /// Labels `graph`'s anime with their title in `form`; anime without cached titles keep the
+    /// dataset's.
+    pub fn label_titles(&self, graph: &mut GraphModel, form: TitleForm) {
+        graph.relabel_anime(|anime_id| {
+            let title = self.get(anime_id)?.titles.get(form)?;
+            Some(title.to_string())
+        });
+    }
+
+    /// Anime in `ids` that still need fetching, sorted, including ones cached before titles,
+    /// directors, relations or tags were. Ids derived through the id map aren't MyAnimeList ids
+    /// and can't be looked up.
+    pub fn missing(&self, ids: impl IntoIterator<Item = u32>) -> Vec<u32> {
+        let mut missing: Vec<u32> = ids
+            .into_iter()
+            .filter(|id| !idmap::is_derived(*id))
+            .filter(|id| {
+                self.anime.get(id).is_none_or(|metadata| {
+                    (metadata.titles.is_empty()
+                        || metadata.directors.is_none()
+                        || metadata.relations.is_none()
+                        || metadata.tags.is_none())
+                        && *metadata != AnimeMetadata::default()
+                })
+            })
+            .collect();
+        missing.sort_unstable();
+        missing.dedup();
+        missing
+    }
+}
+
+#[derive(Deserialize)]
+struct AniListResponse {
+    data: Option<AniListData>,
+    #[serde(default)]
+    errors: Vec<AniListError>,
+}
+
+#[derive(Deserialize)]
+struct AniListError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct AniListData {
+    #[serde(rename = "Page")]
+    page: AniListPage,
+}
+
+#[derive(Deserialize)]
+struct AniListPage {
+    media: Vec<AniListMedia>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AniListMedia {
+    id_mal: Option<u32>,
+    title: Option<AniListTitle>,
+    #[serde(default)]
+    genres: Vec<String>,
+    tags: Option<Vec<AniListTag>>,
+    studios: Option<AniListStudios>,
+    staff: Option<AniListStaff>,
+    average_score: Option<f64>,
+    episodes: Option<u32>,
+    duration: Option<u32>,
+    season_year: Option<u32>,
+    start_date: Option<AniListDate>,
+    description: Option<String>,
+    cover_image: Option<AniListCover>,
+    relations: Option<AniListRelations>,
+}
+
+impl AniListMedia {
+    /// The anime's MyAnimeList id and metadata; AniList entries MAL doesn't list are skipped.
+    fn into_entry(self) -> Option<(u32, AnimeMetadata)> {
+        let anime_id = self.id_mal?;
+        let metadata = AnimeMetadata {
+            titles: self
+                .title
+                .map(|title| AnimeTitles {
+                    romaji: title.romaji,
+                    english: title.english,
+                    native: title.native,
+                })
+                .unwrap_or_default(),
+            genres: self.genres,
+            tags: Some(self.tags.map(AniListTag::names).unwrap_or_default()),
+            studios: self
+                .studios
+                .map(|studios| {
+                    studios
+                        .nodes
+                        .into_iter()
+                        .map(|studio| studio.name)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            directors: Some(self.staff.map(AniListStaff::directors).unwrap_or_default()),
+            mean_score: self.average_score.map(|score| score / 10.0),
+            episodes: self.episodes,
+            episode_minutes: self.duration,
+            year: self
+                .season_year
+                .or(self.start_date.and_then(|date| date.year)),
+            synopsis: self.description.as_deref().map(strip_markup),
+            poster_url: self.cover_image.and_then(|cover| cover.large),
+            relations: Some(
+                self.relations
+                    .map(AniListRelations::anime)
+                    .unwrap_or_default(),
+            ),
+        };
+        Some((anime_id, metadata))
+    }
+}
+
+#[derive(Deserialize)]
+struct AniListTitle {
+    romaji: Option<String>,
+    english: Option<String>,
+    native: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AniListTag {
+    name: String,
+    rank: Option<u32>,
+    #[serde(default)]
+    is_media_spoiler: bool,
+}
+
+impl AniListTag {
+    /// The names of the tags that fit well and don't give the story away.
+    fn names(tags: Vec<Self>) -> Vec<String> {
+        tags.into_iter()
+            .filter(|tag| !tag.is_media_spoiler && tag.rank.unwrap_or_default() >= MIN_TAG_RANK)
+            .map(|tag| tag.name)
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct AniListStudios {
+    nodes: Vec<AniListStudio>,
+}
+
+#[derive(Deserialize)]
+struct AniListStudio {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct AniListStaff {
+    edges: Vec<AniListStaffEdge>,
+}
+
+#[derive(Deserialize)]
+struct AniListStaffEdge {
+    role: Option<String>,
+    node: Option<AniListStaffMember>,
+}
+
+#[derive(Deserialize)]
+struct AniListStaffMember {
+    name: AniListStaffName,
+}
+
+#[derive(Deserialize)]
+struct AniListStaffName {
+    full: Option<String>,
+}
+
+impl AniListStaff {
+    /// The staff credited as the show's director; episode, art and sound directors and the like
+    /// are left out.
+    fn directors(self) -> Vec<String> {
+        let mut directors = Vec::new();
+        for edge in self.edges {
+            let role = edge.role.unwrap_or_default();
+            let role = role.split(" (").next().unwrap_or_default();
+            if !matches!(role, "Director" | "Chief Director" | "Series Director") {
+                continue;
+            }
+            if let Some(name) = edge.node.and_then(|node| node.name.full) {
+                if !directors.contains(&name) {
+                    directors.push(name);
+                }
+            }
+        }
+        directors
+    }
+}
+
+#[derive(Deserialize)]
+struct AniListRelations {
+    edges: Vec<AniListRelationEdge>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AniListRelationEdge {
+    relation_type: Option<String>,
+    node: Option<AniListRelated>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AniListRelated {
+    id_mal: Option<u32>,
+    #[serde(rename = "type")]
+    media_type: Option<String>,
+}
+
+impl AniListRelations {
+    /// Relations to other anime with a MyAnimeList id; manga sources and the like are left
+    /// out.
+    fn anime(self) -> Vec<Relation> {
+        self.edges
+            .into_iter()
+            .filter_map(|edge| {
+                let node = edge.node?;
+                if node.media_type.as_deref() != Some("ANIME") {
+                    return None;
+                }
+                Some(Relation {
+                    kind: RelationKind::from_anilist(edge.relation_type.as_deref()?)?,
+                    anime_id: node.id_mal?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct AniListDate {
+    year: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct AniListCover {
+    large: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct JikanResponse {
+    data: JikanAnime,
+}
+
+/// An anime as Jikan lists it, on its own page or in a season.
+#[derive(Deserialize)]
+pub(crate) struct JikanAnime {
+    pub mal_id: u32,
+    pub title: String,
+    title_english: Option<String>,
+    title_japanese: Option<String>,
+    #[serde(default)]
+    genres: Vec<JikanName>,
+    #[serde(default)]
+    themes: Vec<JikanName>,
+    #[serde(default)]
+    demographics: Vec<JikanName>,
+    #[serde(default)]
+    studios: Vec<JikanName>,
+    episodes: Option<u32>,
+    /// e.g. `24 min per ep` or `1 hr 55 min`.
+    duration: Option<String>,
+    pub year: Option<u32>,
+    score: Option<f64>,
+    synopsis: Option<String>,
+    images: Option<JikanImages>,
+}
+
+impl JikanAnime {
+    pub fn into_metadata(self) -> AnimeMetadata {
+        let poster_url = self
+            .images
+            .and_then(|images| images.jpg)
+            .and_then(|jpg| jpg.large_image_url.or(jpg.image_url));
+        AnimeMetadata {
+            titles: AnimeTitles {
+                romaji: Some(self.title),
+                english: self.title_english,
+                native: self.title_japanese,
+            },
+            genres: self.genres.into_iter().map(|genre| genre.name).collect(),
+            tags: Some(
+                self.themes
+                    .into_iter()
+                    .chain(self.demographics)
+                    .map(|theme| theme.name)
+                    .collect(),
+            ),
+            studios: self.studios.into_iter().map(|studio| studio.name).collect(),
+            directors: None,
+            episodes: self.episodes,
+            episode_minutes: self.duration.as_deref().and_then(jikan_minutes),
+            year: self.year,
+            mean_score: self.score,
+            synopsis: self.synopsis,
+            poster_url,
+            relations: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct JikanName {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct JikanImages {
+    jpg: Option<JikanImage>,
+}
+
+#[derive(Deserialize)]
+struct JikanImage {
+    large_image_url: Option<String>,
+    image_url: Option<String>,
+}
+
+/// Fetches metadata for up to [`BATCH_SIZE`] MyAnimeList ids: one AniList request, then
+/// Jikan one by one for the ids AniList didn't return. Ids Jikan can't serve either come back
+/// with empty metadata; ids that failed for another reason are left out and retried next time.
+pub async fn fetch_batch(
+    client: &Client,
+    ids: &[u32],
+) -> Result<Vec<(u32, AnimeMetadata)>, ImportError> {
+    ANILIST_LIMITER.wait().await;
+    let response: AniListResponse = client
+        .post(ANILIST_ENDPOINT)
+        .json(&json!({ "query": MEDIA_QUERY, "variables": { "ids": ids } }))
+        .send()
+        .await
+        .map_err(|err| ImportError::Http(err.to_string()))?
+        .json()
+        .await
+        .map_err(|err| ImportError::Parse(err.to_string()))?;
+    if let Some(error) = response.errors.first() {
+        return Err(ImportError::Http(error.message.clone()));
+    }
+
+    let mut found: HashMap<u32, AnimeMetadata> = response
+        .data
+        .map(|data| data.page.media)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(AniListMedia::into_entry)
+        .collect();
+
+    for &anime_id in ids {
+        if found.contains_key(&anime_id) {
+            continue;
+        }
+        let url = format!("https://api.jikan.moe/v4/anime/{anime_id}");
+        match get_json::<JikanResponse>(client, &jikan::LIMITER, &url).await {
+            Ok(JikanResponse { data }) => {
+                // Jikan lists staff and relations on pages of their own; settle for none
+                // rather than asking again every fetch.
+                let metadata = AnimeMetadata {
+                    directors: Some(Vec::new()),
+                    relations: Some(Vec::new()),
+                    ..data.into_metadata()
+                };
+                found.insert(anime_id, metadata);
+            }
+            Err(ImportError::Http(message)) if message.ends_with("404 Not Found") => {
+                found.insert(anime_id, AnimeMetadata::default());
+            }
+            Err(_) => {}
+        }
+    }
+    Ok(found.into_iter().collect())
+}
+
+/// Minutes in a Jikan duration such as `24 min per ep`, `1 hr 55 min` or `45 sec`.
+fn jikan_minutes(duration: &str) -> Option<u32> {
+    let mut minutes = 0;
+    let mut words = duration.split_whitespace();
+    while let Some(word) = words.next() {
+        let Ok(count) = word.parse::<u32>() else {
+            continue;
+        };
+        match words.next() {
+            Some("hr" | "hrs") => minutes += count * 60,
+            Some("min" | "mins") => minutes += count,
+            _ => {}
+        }
+    }
+    // Shorts under a minute still take one.
+    (minutes > 0 || duration.contains("sec")).then_some(minutes.max(1))
+}
+
+/// AniList descriptions keep a few HTML tags (`<br>`, `<i>`) even as plain text.
+fn strip_markup(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => plain.push(c),
+            _ => {}
+        }
+    }
+    plain.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anilist_media_keep_directors_safe_tags_and_anime_relations() {
+        let media: AniListMedia = serde_json::from_str(
+            r#"{
+                "idMal": 5114,
+                "title": { "romaji": "Hagane no Renkinjutsushi", "english": "Fullmetal Alchemist: Brotherhood" },
+                "genres": ["Action", "Drama"],
+                "tags": [
+                    { "name": "Alchemy", "rank": 95 },
+                    { "name": "Male Protagonist", "rank": 40 },
+                    { "name": "Homunculus", "rank": 80, "isMediaSpoiler": true }
+                ],
+                "studios": { "nodes": [{ "name": "Bones" }] },
+                "staff": { "edges": [
+                    { "role": "Director", "node": { "name": { "full": "Yasuhiro Irie" } } },
+                    { "role": "Episode Director (ep 3)", "node": { "name": { "full": "Someone Else" } } },
+                    { "role": "Director (eps 1-64)", "node": { "name": { "full": "Yasuhiro Irie" } } }
+                ] },
+                "averageScore": 90,
+                "episodes": 64,
+                "duration": 24,
+                "startDate": { "year": 2009 },
+                "description": "Two brothers<br><i>search</i> for the stone.",
+                "coverImage": { "large": "https://img.example/fmab.jpg" },
+                "relations": { "edges": [
+                    { "relationType": "SIDE_STORY", "node": { "idMal": 6421, "type": "ANIME" } },
+                    { "relationType": "ADAPTATION", "node": { "idMal": 25, "type": "MANGA" } },
+                    { "relationType": "CHARACTER", "node": { "idMal": 121, "type": "ANIME" } }
+                ] }
+            }"#,
+        )
+        .unwrap();
+        let (anime_id, metadata) = media.into_entry().unwrap();
+        assert_eq!(anime_id, 5114);
+        assert_eq!(
+            metadata.titles.get(TitleForm::English),
+            Some("Fullmetal Alchemist: Brotherhood")
+        );
+        assert_eq!(metadata.tags.as_deref(), Some(&["Alchemy".to_string()][..]));
+        assert_eq!(
+            metadata.directors.as_deref(),
+            Some(&["Yasuhiro Irie".to_string()][..])
+        );
+        assert_eq!(metadata.mean_score, Some(9.0));
+        assert_eq!(metadata.year, Some(2009));
+        assert_eq!(
+            metadata.synopsis.as_deref(),
+            Some("Two brotherssearch for the stone.")
+        );
+        assert_eq!(
+            metadata.relations.as_deref(),
+            Some(
+                &[Relation {
+                    kind: RelationKind::SideStory,
+                    anime_id: 6421,
+                }][..]
+            )
+        );
+        assert_eq!(metadata.summary(), "2009 · 64 episodes · Action, Drama");
+
+        let unlisted: AniListMedia = serde_json::from_str(r#"{ "idMal": null }"#).unwrap();
+        assert!(unlisted.into_entry().is_none());
+    }
+
+    #[test]
+    fn jikan_anime_prefer_large_posters_and_count_minutes() {
+        let anime: JikanAnime = serde_json::from_str(
+            r#"{
+                "mal_id": 199, "title": "Sen to Chihiro no Kamikakushi",
+                "title_english": "Spirited Away",
+                "themes": [{ "name": "Mythology" }], "demographics": [{ "name": "Kids" }],
+                "episodes": 1, "duration": "2 hr 4 min", "year": null, "score": 8.8,
+                "images": { "jpg": { "image_url": "small.jpg", "large_image_url": "large.jpg" } }
+            }"#,
+        )
+        .unwrap();
+        let metadata = anime.into_metadata();
+        assert_eq!(metadata.poster_url.as_deref(), Some("large.jpg"));
+        assert_eq!(metadata.episode_minutes, Some(124));
+        assert_eq!(
+            metadata.tags,
+            Some(vec!["Mythology".to_string(), "Kids".to_string()])
+        );
+        assert_eq!(metadata.summary(), "1 episode");
+
+        assert_eq!(jikan_minutes("24 min per ep"), Some(24));
+        assert_eq!(jikan_minutes("45 sec per ep"), Some(1));
+        assert_eq!(jikan_minutes("Unknown"), None);
+    }
+
+    #[test]
+    fn only_unknown_or_outdated_anime_are_fetched_again() {
+        let complete = AnimeMetadata {
+            titles: AnimeTitles {
+                romaji: Some("Complete".to_string()),
+                ..AnimeTitles::default()
+            },
+            tags: Some(Vec::new()),
+            directors: Some(Vec::new()),
+            relations: Some(Vec::new()),
+            ..AnimeMetadata::default()
+        };
+        let before_directors = AnimeMetadata {
+            directors: None,
+            ..complete.clone()
+        };
+        let mut cache = MetadataCache::default();
+        cache.extend([
+            (1, complete),
+            (2, before_directors),
+            // Neither source knows it; asking again won't help.
+            (3, AnimeMetadata::default()),
+        ]);
+        let derived = (1 << 30) + 5;
+        assert_eq!(cache.missing([5, 4, 3, 2, 1, 4, derived]), [2, 4, 5]);
+    }
+}
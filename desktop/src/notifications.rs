@@ -0,0 +1,188 @@
+//! Desktop notifications, opt-in from the Settings tab: a new episode of a watchlisted show in
+//! this season has aired, or this season has a strong match for the active profile. Quiet
+//! hours hold them back until they end. Each episode and match is announced once; what was
+//! announced is kept in `notified.json` in the platform data directory.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::process::Command;
+
+use chrono::Timelike;
+use serde::{Deserialize, Serialize};
+use wasiw_core::content::ContentRecommendation;
+use wasiw_ui::i18n::tr;
+
+use crate::config;
+use crate::seasonal::Season;
+use crate::tray;
+
+const NOTIFIED_FILE_NAME: &str = "notified.json";
+/// Seasonal matches scoring at least this are announced, unless the settings say otherwise.
+pub const MIN_MATCH: f64 = 0.6;
+/// Matches announced per season at most, the best first.
+const MAX_MATCHES: usize = 3;
+/// Episodes are announced for this long after airing, e.g. once quiet hours end.
+const EPISODE_WINDOW_SECS: i64 = 24 * 3600;
+/// How often the app looks for newly aired episodes.
+pub const CHECK_MINUTES: u64 = 5;
+/// How often this season's schedule is fetched again while notifications are on.
+pub const SEASON_REFRESH_HOURS: u64 = 12;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    /// Hours of the day, in local time, without notifications.
+    pub quiet_hours: Option<QuietHours>,
+    /// Lowest seasonal match score announced, from 0 to 1; unset uses [`MIN_MATCH`].
+    pub min_match: Option<f64>,
+}
+
+impl NotificationSettings {
+    pub fn min_match(&self) -> f64 {
+        self.min_match.unwrap_or(MIN_MATCH)
+    }
+
+    /// Whether notifications may be shown right now.
+    pub fn allowed_now(&self) -> bool {
+        let hour = chrono::Local::now().hour() as u8;
+        self.enabled && !self.quiet_hours.is_some_and(|quiet| quiet.contains(hour))
+    }
+}
+
+/// From `start` up to `end`, in whole hours; wraps past midnight when `end` is earlier, like
+/// 22 to 7.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHours {
+    pub start: u8,
+    pub end: u8,
+}
+
+impl QuietHours {
+    pub fn contains(self, hour: u8) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&hour)
+        } else {
+            hour >= self.start || hour < self.end
+        }
+    }
+}
+
+/// One notification's text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notice {
+    pub title: String,
+    pub body: String,
+}
+
+/// What has been announced already.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Notified {
+    /// Anime id to the Unix time of the latest episode announced.
+    episodes: BTreeMap<u32, i64>,
+    /// Seasons by name, with the anime announced as matches in each.
+    matches: BTreeMap<String, BTreeSet<u32>>,
+}
+
+impl Notified {
+    pub fn load() -> Self {
+        config::read_data_file(NOTIFIED_FILE_NAME)
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let content = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        config::write_data_file(NOTIFIED_FILE_NAME, &content)
+    }
+
+    /// Notices not shown yet for `season` at Unix time `now`: episodes of `watchlist` anime
+    /// that aired in the last day, and the best few `picks` scoring at least `min_match`.
+    /// Marks them shown. `title` gives the name an anime is listed by.
+    pub fn collect(
+        &mut self,
+        season: &Season,
+        watchlist: &BTreeSet<u32>,
+        picks: &[ContentRecommendation],
+        min_match: f64,
+        now: i64,
+        title: impl Fn(u32, &str) -> String,
+    ) -> Vec<Notice> {
+        let mut notices = Vec::new();
+        for anime in &season.anime {
+            let Some(broadcast) = anime.broadcast else {
+                continue;
+            };
+            if !watchlist.contains(&anime.anime_id) {
+                continue;
+            }
+            let aired = broadcast.latest(now);
+            let announced = self.episodes.get(&anime.anime_id).copied();
+            if now - aired > EPISODE_WINDOW_SECS || announced.is_some_and(|last| last >= aired) {
+                continue;
+            }
+            self.episodes.insert(anime.anime_id, aired);
+            notices.push(Notice {
+                title: tr("notify-episode"),
+                body: title(anime.anime_id, &anime.title),
+            });
+        }
+        let announced = self.matches.entry(season.name.clone()).or_default();
+        for pick in picks.iter().filter(|pick| pick.score >= min_match) {
+            if announced.len() >= MAX_MATCHES {
+                break;
+            }
+            if announced.insert(pick.anime_id) {
+                notices.push(Notice {
+                    title: tr!("notify-match", season = season.name),
+                    body: title(pick.anime_id, &pick.title),
+                });
+            }
+        }
+        // Older seasons won't come round again.
+        let current = season.name.clone();
+        self.matches.retain(|name, _| *name == current);
+        notices
+    }
+}
+
+/// Shows `notice` through the system's notifications: `notify-send` on Linux and the BSDs,
+/// AppleScript on macOS. Elsewhere, or when that fails, it appears in the tray's toast window
+/// instead.
+pub fn send(notice: Notice) {
+    if cfg!(target_os = "windows") {
+        return tray::show_toast(notice.title, notice.body);
+    }
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("osascript");
+        command.args([
+            "-e",
+            &format!(
+                "display notification {} with title {}",
+                applescript_string(&notice.body),
+                applescript_string(&notice.title)
+            ),
+        ]);
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.args(["--app-name", crate::APP_TITLE, &notice.title, &notice.body]);
+        command
+    };
+    match command.spawn() {
+        // Reaped off the UI thread; the notification itself outlives the command.
+        Ok(mut child) => {
+            std::thread::spawn(move || child.wait());
+        }
+        Err(err) => {
+            tracing::debug!("no system notifications: {err}");
+            tray::show_toast(notice.title, notice.body);
+        }
+    }
+}
+
+/// `text` as a quoted AppleScript string.
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
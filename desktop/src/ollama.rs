@@ -0,0 +1,196 @@
+//! Natural-language blurbs for recommendations from a local [Ollama](https://ollama.com) model,
+//! written from the same numbers the details card shows: the rated anime that push a
+//! recommendation up or down and its genres. Opt-in through `[ollama]` in `config.toml`, and
+//! only loopback endpoints are accepted, so nothing about the user's ratings leaves the machine.
+
+use std::net::IpAddr;
+use std::time::Duration;
+
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use wasiw_core::recommend::Recommendation;
+
+use crate::import::ImportError;
+
+const DEFAULT_ENDPOINT: &str = "http://127.0.0.1:11434";
+/// Small models on a CPU can take a while for the first answer, while the model loads.
+const GENERATE_TIMEOUT: Duration = Duration::from_secs(180);
+/// Contributing anime named in the prompt, each way.
+const PROMPT_CONTRIBUTIONS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OllamaSettings {
+    /// Model to generate with, e.g. `llama3.2`; it must already be pulled.
+    pub model: String,
+    #[serde(default = "default_endpoint")]
+    pub endpoint: String,
+}
+
+fn default_endpoint() -> String {
+    DEFAULT_ENDPOINT.to_string()
+}
+
+#[derive(Deserialize)]
+struct GenerateResponse {
+    response: String,
+}
+
+/// A two-sentence blurb on why `recommendation` suits the user, given its genres.
+pub async fn blurb(
+    client: &Client,
+    settings: &OllamaSettings,
+    recommendation: &Recommendation,
+    genres: &[String],
+) -> Result<String, ImportError> {
+    let endpoint = local_endpoint(&settings.endpoint)?;
+    let url = endpoint
+        .join("api/generate")
+        .map_err(|err| ImportError::Http(err.to_string()))?;
+    let body = json!({
+        "model": settings.model,
+        "prompt": prompt(recommendation, genres),
+        "stream": false,
+        "options": { "temperature": 0.3, "num_predict": 120 },
+    });
+    let response: GenerateResponse = client
+        .post(url)
+        .json(&body)
+        .timeout(GENERATE_TIMEOUT)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| ImportError::Http(format!("Ollama: {err}")))?
+        .json()
+        .await
+        .map_err(|err| ImportError::Parse(format!("Ollama: {err}")))?;
+    let blurb = response.response.trim();
+    if blurb.is_empty() {
+        return Err(ImportError::Parse(
+            "Ollama returned an empty answer".to_string(),
+        ));
+    }
+    Ok(blurb.to_string())
+}
+
+/// The endpoint, refused unless it is on this machine.
+fn local_endpoint(endpoint: &str) -> Result<Url, ImportError> {
+    let url = Url::parse(endpoint.trim_end_matches('/'))
+        .map_err(|err| ImportError::Parse(format!("Ollama endpoint {endpoint}: {err}")))?;
+    let local = url.host_str().is_some_and(|host| {
+        host.eq_ignore_ascii_case("localhost")
+            || host
+                .trim_matches(['[', ']'])
+                .parse::<IpAddr>()
+                .is_ok_and(|ip| ip.is_loopback())
+    });
+    if !local {
+        return Err(ImportError::Parse(format!(
+            "Ollama endpoint {endpoint} isn't on this machine; only local endpoints are used"
+        )));
+    }
+    Ok(url)
+}
+
+fn prompt(recommendation: &Recommendation, genres: &[String]) -> String {
+    let listed = |positive: bool| {
+        let mut items: Vec<String> = recommendation
+            .contributions
+            .iter()
+            .filter(|c| (c.weighted_score > 0.0) == positive && c.weighted_score != 0.0)
+            .map(|c| format!("{} ({:+.2})", c.title, c.weighted_score))
+            .collect();
+        if !positive {
+            items.reverse();
+        }
+        items.truncate(PROMPT_CONTRIBUTIONS);
+        if items.is_empty() {
+            "none".to_string()
+        } else {
+            items.join(", ")
+        }
+    };
+    let genres = if genres.is_empty() {
+        "unknown".to_string()
+    } else {
+        genres.join(", ")
+    };
+    format!(
+        "You explain anime recommendations. In at most two short sentences and without \
+         inventing plot details, tell the viewer why they might enjoy \"{title}\". It was \
+         recommended because people rate it like these anime the viewer liked (higher numbers \
+         count more): {liked}. Anime the viewer rated that count against it: {disliked}. \
+         Its genres: {genres}. Address the viewer as \"you\" and don't mention the numbers.",
+        title = recommendation.title,
+        liked = listed(true),
+        disliked = listed(false),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasiw_core::recommend::Contribution;
+
+    #[test]
+    fn only_endpoints_on_this_machine_are_used() {
+        for endpoint in [
+            "http://127.0.0.1:11434/",
+            "http://localhost:11434",
+            "http://[::1]:11434",
+        ] {
+            let url = local_endpoint(endpoint).unwrap();
+            assert!(url
+                .join("api/generate")
+                .unwrap()
+                .path()
+                .ends_with("/api/generate"));
+        }
+        for endpoint in [
+            "http://192.168.1.20:11434",
+            "https://ollama.example.com",
+            "nonsense",
+        ] {
+            assert!(local_endpoint(endpoint).is_err(), "{endpoint}");
+        }
+        let settings: OllamaSettings = serde_json::from_str(r#"{ "model": "llama3.2" }"#).unwrap();
+        assert_eq!(settings.endpoint, DEFAULT_ENDPOINT);
+    }
+
+    #[test]
+    fn prompts_name_the_strongest_contributors_each_way() {
+        let contribution = |title: &str, weighted_score: f64| Contribution {
+            title: title.to_string(),
+            edge_weight: 0.5,
+            weight_factor: 1.0,
+            weighted_score,
+        };
+        let mut recommendation = Recommendation {
+            anime_id: 1,
+            node: 0,
+            title: "Mushishi".to_string(),
+            score: 2.0,
+            strongest: 1.2,
+            support_count: 4,
+            contributions: vec![
+                contribution("Aria", 1.2),
+                contribution("Natsume", 0.8),
+                contribution("Non Non Biyori", 0.5),
+                contribution("Yuru Camp", 0.3),
+                contribution("K-On!", -0.1),
+                contribution("Devilman Crybaby", -0.9),
+            ],
+        };
+        let text = prompt(&recommendation, &["Slice of Life".to_string()]);
+        assert!(text.contains("\"Mushishi\""));
+        assert!(text.contains("liked (higher numbers count more): Aria (+1.20), Natsume (+0.80), Non Non Biyori (+0.50)."));
+        assert!(!text.contains("Yuru Camp"));
+        assert!(text.contains("count against it: Devilman Crybaby (-0.90), K-On! (-0.10)."));
+        assert!(text.contains("Its genres: Slice of Life."));
+
+        recommendation.contributions.clear();
+        let text = prompt(&recommendation, &[]);
+        assert!(text.contains("liked (higher numbers count more): none."));
+        assert!(text.contains("Its genres: unknown."));
+    }
+}
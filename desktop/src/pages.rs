@@ -0,0 +1,1864 @@
+//! The pages of the side panel's tabs. Each reads the shared signals from context: the
+//! [`AppState`] and the [`Shell`] that `App` provides.
+
+use std::path::PathBuf;
+
+use dioxus::prelude::*;
+use wasiw_core::activity::{self, Activity};
+use wasiw_core::algorithms;
+use wasiw_core::analytics::{
+    AnimeAnalytics, AnimeComparison, ControversyRankings, ScoreSpread, SharedOpinion,
+    UserAnalytics, UserComparison, MIN_RANKED_RATERS,
+};
+use wasiw_core::budget::{self, BudgetCandidate, Format, TimeBudget};
+use wasiw_core::health::{Check, HealthReport, Severity};
+use wasiw_core::memory::format_bytes;
+use wasiw_core::moods::{self, Mood, MoodTable};
+use wasiw_core::recommend;
+use wasiw_core::sampling::Sampling;
+use wasiw_core::stats::Breakdown;
+use wasiw_core::titles::TitleForm;
+use wasiw_core::tribes::{self, Tribe, TribeReport};
+use wasiw_core::watch_order::{self, WatchOrder};
+use wasiw_core::{GraphFilter, GraphMode, LayoutKind, MergeStrategy};
+use wasiw_ui::camera::Camera;
+use wasiw_ui::charts::{
+    CalendarHeatmap, Histogram, LineChart, RadarChart, RankedBars, ScatterPlot, ShareBar,
+};
+use wasiw_ui::controls::{
+    DuplicatePicker, GraphModePicker, LanguagePicker, LayoutControls, NotificationControls,
+    PalettePicker, RenderControls, SamplingControls, ScalePicker, SearchBox, StartupDataset,
+    StatRow, ThemePicker, TitlePicker,
+};
+use wasiw_ui::i18n::{self, tr, Locale};
+use wasiw_ui::theme::{self, ThemePreference, UiScale};
+
+use crate::config::TitleSettings;
+use crate::import::panel::ImportPanel;
+use crate::menu;
+use crate::notifications::QuietHours;
+use crate::profiles::panel::{ProfilePanel, Watchlist};
+use crate::routes::Route;
+use crate::set_fullscreen;
+use crate::state::{AppState, Shell};
+use crate::tasks::{self, TaskKind};
+
+/// Recommendations listed on the Recommendations tab.
+const LISTED_RECOMMENDATIONS: usize = 25;
+/// Anime listed as ones two compared users would both enjoy.
+const LISTED_TOGETHER: usize = 10;
+/// Members shown per taste tribe; the rest are counted.
+const SHOWN_TRIBE_MEMBERS: usize = 12;
+
+/// Search, layout and sampling controls for the graph.
+#[component]
+pub fn GraphPage() -> Element {
+    let mut app = use_context::<AppState>();
+    let shell = use_context::<Shell>();
+    let model = app.graph.read();
+    let match_count = model
+        .search(&shell.search.read())
+        .map(|matches| matches.len());
+    let max_edges = app.config.read().max_rendered_edges();
+    let titles = app.config.read().titles;
+    let text_view = app.config.read().text_view;
+    let show_relations = app.config.read().show_relations;
+    rsx! {
+        SearchBox { query: shell.search, match_count }
+        p { class: "tiny", {tr!("graph-edge-cap", count = max_edges)} }
+        p { class: "tiny", {tr!("graph-super-nodes")} }
+        {semantic_panel(app, shell)}
+        GraphModePicker {
+            mode: model.mode,
+            on_change: move |mode: GraphMode| {
+                app.graph.write().mode = mode;
+                app.rebuild_graph();
+                let mut config = app.config.write();
+                config.graph_mode = mode;
+                let _ = config.save();
+            },
+        }
+        if model.mode != GraphMode::Ratings {
+            p { class: "tiny", {tr!("graph-mode-facets-hint")} }
+        }
+        LayoutControls {
+            layout: model.layout,
+            filter: model.filter,
+            on_layout: move |kind: LayoutKind| {
+                app.graph.write().layout = kind;
+                shell.relayout.call(*app.viewport.peek());
+                let mut config = app.config.write();
+                config.layout = kind;
+                let _ = config.save();
+            },
+            on_filter: move |filter: GraphFilter| {
+                app.graph.write().apply_filter(filter);
+                shell.relayout.call(*app.viewport.peek());
+            },
+        }
+        SamplingControls {
+            sampling: model.sampling,
+            total_users: app.dataset.read().users.len(),
+            shown_users: model.user_count,
+            on_change: move |sampling: Sampling| {
+                app.graph.write().sampling = sampling;
+                app.rebuild_graph();
+                let mut config = app.config.write();
+                config.sampling = sampling;
+                let _ = config.save();
+            },
+        }
+        TitlePicker {
+            label: tr!("titles-graph"),
+            active: titles.graph,
+            default: Some(titles.form),
+            on_select: move |form: Option<TitleForm>| set_titles(app, |titles| titles.graph = form),
+        }
+        button {
+            class: "action",
+            onclick: move |_| app.camera.set(Camera::default()),
+            {tr!("graph-reset-view")}
+        }
+        button {
+            class: "action",
+            onclick: move |_| set_fullscreen(shell.fullscreen, true),
+            {tr!("graph-fullscreen")}
+        }
+        button {
+            class: "action",
+            aria_pressed: text_view,
+            onclick: move |_| {
+                let mut config = app.config.write();
+                config.text_view = !config.text_view;
+                let _ = config.save();
+            },
+            {tr!("graph-text-view")}
+        }
+        button {
+            class: "action",
+            aria_pressed: show_relations,
+            onclick: move |_| {
+                let mut config = app.config.write();
+                config.show_relations = !config.show_relations;
+                let _ = config.save();
+            },
+            {tr!("graph-relations")}
+        }
+        if show_relations && app.relations.read().is_empty() {
+            p { class: "tiny", {tr!("graph-relations-none")} }
+        }
+    }
+}
+
+/// Changes the title settings and relabels the graph to match.
+fn set_titles(mut app: AppState, change: impl FnOnce(&mut TitleSettings)) {
+    {
+        let mut config = app.config.write();
+        change(&mut config.titles);
+        let _ = config.save();
+    }
+    app.relabel_graph();
+}
+
+#[cfg(feature = "semantic")]
+fn semantic_panel(app: AppState, shell: Shell) -> Element {
+    rsx! {
+        crate::SemanticSearchPanel {
+            busy: app.tasks.read().is_running(TaskKind::Search),
+            results: shell.semantic_results.cloned(),
+            on_search: shell.semantic_search,
+            on_pick: move |anime_id: u32| {
+                let found = app.graph.peek().find_anime(anime_id);
+                if let Some(idx) = found {
+                    app.open_node(idx);
+                }
+            },
+        }
+    }
+}
+
+#[cfg(not(feature = "semantic"))]
+fn semantic_panel(_app: AppState, _shell: Shell) -> Element {
+    rsx! {}
+}
+
+/// Recommendations for the active profile, or for the selected user without one. An entry
+/// opens the anime's details in the graph.
+#[component]
+pub fn RecommendationsPage() -> Element {
+    let app = use_context::<AppState>();
+    let model = app.graph.read();
+    let active = app
+        .profiles
+        .read()
+        .active()
+        .map(|profile| profile.user_id());
+    let user = match active {
+        Some(user_id) => app.find_user_node(&user_id),
+        None => app
+            .selected
+            .cloned()
+            .filter(|&idx| model.nodes[idx].id.starts_with("user:")),
+    };
+    let Some(user) = user else {
+        return rsx! {
+            p { class: "tiny",
+                {tr!("recommendations-pick")}
+            }
+        };
+    };
+    let hidden = app
+        .profiles
+        .read()
+        .for_user(model.nodes[user].id.trim_start_matches("user:"))
+        .map(|profile| profile.blacklist.clone())
+        .unwrap_or_default();
+    let mut moods = use_signal(Vec::<String>::new);
+    let mood_table = app.config.read().moods.clone();
+    // Filtering by mood can pass over any number of recommendations, so rank them all then.
+    let pool = if moods.read().is_empty() {
+        LISTED_RECOMMENDATIONS + hidden.len()
+    } else {
+        usize::MAX
+    };
+    let recommendations: Vec<_> = {
+        let metadata = app.metadata.read();
+        let moods = moods.read();
+        app.recommend(&model, user, pool)
+            .into_iter()
+            .filter(|recommendation| !hidden.contains(&recommendation.anime_id))
+            .filter(|recommendation| {
+                mood_table.matches_all(
+                    moods.iter().map(String::as_str),
+                    &metadata.mood_tags(recommendation.anime_id),
+                )
+            })
+            .take(LISTED_RECOMMENDATIONS)
+            .collect()
+    };
+    let entry_points = app.entry_points(
+        &model,
+        user,
+        recommendations
+            .iter()
+            .map(|recommendation| recommendation.anime_id),
+    );
+    let mut planning = use_signal(|| false);
+    let plan = planning().then(|| {
+        let listed: Vec<u32> = recommendations
+            .iter()
+            .map(|recommendation| recommendation.anime_id)
+            .collect();
+        app.watch_order(&app.with_lead_up(&model, user, &listed))
+    });
+    let mut budgeting = use_signal(|| false);
+    let candidates = budgeting().then(|| app.budget_candidates(&model, user, &recommendations));
+    let name = model.nodes[user].label.clone();
+    let titles = app.config.read().titles;
+    rsx! {
+        TitlePicker {
+            label: tr!("titles-lists"),
+            active: titles.lists,
+            default: Some(titles.form),
+            on_select: move |form: Option<TitleForm>| set_titles(app, |titles| titles.lists = form),
+        }
+        div { class: "field",
+            div { class: "row",
+                span { class: "tiny", {tr!("recommendations-for", name = name)} }
+                button {
+                    class: "action",
+                    onclick: move |_| app.open_details(user),
+                    {tr!("recommendations-details")}
+                }
+                if !recommendations.is_empty() {
+                    button {
+                        class: "action",
+                        aria_pressed: planning(),
+                        onclick: move |_| planning.toggle(),
+                        {tr!("recommendations-plan")}
+                    }
+                    button {
+                        class: "action",
+                        aria_pressed: budgeting(),
+                        onclick: move |_| budgeting.toggle(),
+                        {tr!("recommendations-budget")}
+                    }
+                }
+            }
+            if let Some(order) = plan {
+                WatchOrderPanel { order, heading: tr!("watch-order-for", name = name.as_str()) }
+            }
+            if !mood_table.moods.is_empty() {
+                div { class: "chips", role: "group", aria_label: tr!("moods-label"),
+                    for mood in mood_table.moods {
+                        button {
+                            key: "{mood.name}",
+                            class: "chip",
+                            title: mood.tags.join(", "),
+                            aria_pressed: moods.read().contains(&mood.name),
+                            onclick: {
+                                let name = mood.name.clone();
+                                move |_| {
+                                    let mut moods = moods.write();
+                                    match moods.iter().position(|picked| *picked == name) {
+                                        Some(index) => {
+                                            moods.remove(index);
+                                        }
+                                        None => moods.push(name.clone()),
+                                    }
+                                }
+                            },
+                            "{mood.name}"
+                        }
+                    }
+                }
+            }
+            if let Some(candidates) = candidates {
+                TimeBudgetPanel { candidates }
+            }
+            if recommendations.is_empty() {
+                if moods.read().is_empty() {
+                    span { class: "tiny", {tr!("recommendations-empty")} }
+                } else {
+                    span { class: "tiny", {tr!("recommendations-mood-empty")} }
+                }
+            }
+            ul { class: "semantic-results",
+                for recommendation in recommendations {
+                    li { key: "{recommendation.anime_id}",
+                        button {
+                            class: "link",
+                            onclick: move |_| app.open_node(recommendation.node),
+                            {app.list_title(recommendation.anime_id, &recommendation.title)}
+                        }
+                        span { class: "tiny", " {recommendation.score:.2}" }
+                        if let Some(entry) = entry_points.get(&recommendation.anime_id) {
+                            span { class: "tiny",
+                                " · "
+                                {tr!("recommendations-start-with", title = entry.as_str())}
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Counts for the dataset and the graph built from it.
+#[component]
+pub fn StatsPage() -> Element {
+    let app = use_context::<AppState>();
+    let model = app.graph.read();
+    let ratings: usize = app
+        .dataset
+        .read()
+        .users
+        .iter()
+        .map(|user| user.ratings.len())
+        .sum();
+    let mut min_raters = use_signal(|| MIN_RANKED_RATERS);
+    // Aggregated once per dataset or metadata change, not on every render.
+    let breakdown = use_memo(move || {
+        let metadata = app.metadata.read();
+        Breakdown::new(&app.dataset.read(), |anime_id| {
+            metadata
+                .get(anime_id)
+                .map(|metadata| metadata.genres.clone())
+                .unwrap_or_default()
+        })
+    });
+    let breakdown = breakdown.read();
+    let config = app.config.read();
+    let palette = theme::active_palette(&config.palettes, config.palette.as_deref());
+    let scores: Vec<_> = (1..=10)
+        .map(|score| (score.to_string(), breakdown.scores[score - 1]))
+        .collect();
+    let spread: Vec<_> = breakdown
+        .user_spread
+        .iter()
+        .map(|user| (user.mean, user.variance))
+        .collect();
+    let max_variance = spread
+        .iter()
+        .map(|(_, variance)| *variance)
+        .fold(1.0, f64::max);
+    let mut genres = breakdown.genres.clone();
+    if breakdown.other_genres > 0 {
+        genres.push((tr!("stats-genres-other"), breakdown.other_genres));
+    }
+    let density = breakdown.density();
+    let rankings = use_memo(move || ControversyRankings::new(&app.dataset.read(), min_raters()));
+    let components = use_memo(move || algorithms::components(&app.graph.read()));
+    let usage = *app.memory.read();
+    let total = match config.memory_budget() {
+        Some(budget) => tr!(
+            "stats-memory-of-budget",
+            used = format_bytes(usage.total()),
+            budget = format_bytes(budget)
+        ),
+        None => format_bytes(usage.total()),
+    };
+    rsx! {
+        div { class: "stats",
+            StatRow { label: tr!("stats-users"), value: model.user_count.to_string() }
+            StatRow { label: tr!("stats-anime"), value: model.anime_count.to_string() }
+            StatRow { label: tr!("stats-ratings"), value: ratings.to_string() }
+            StatRow { label: tr!("stats-nodes"), value: model.nodes.len().to_string() }
+            StatRow { label: tr!("stats-edges"), value: model.edges.len().to_string() }
+            StatRow { label: tr!("stats-communities"), value: model.communities.count().to_string() }
+            StatRow { label: tr!("stats-components"), value: components().to_string() }
+            StatRow { label: tr!("stats-memory-dataset"), value: format_bytes(usage.dataset) }
+            StatRow { label: tr!("stats-memory-graph"), value: format_bytes(usage.graph) }
+            StatRow { label: tr!("stats-memory-similarity"), value: format_bytes(usage.similarity) }
+            StatRow { label: tr!("stats-memory-caches"), value: format_bytes(usage.caches) }
+            StatRow { label: tr!("stats-memory-total"), value: total }
+        }
+        DatasetHealth {}
+        Histogram {
+            title: tr!("stats-chart-scores"),
+            bars: scores,
+            color: palette.anime_node.clone(),
+        }
+        ScatterPlot {
+            title: tr!("stats-chart-spread"),
+            points: spread,
+            x_max: 10.0,
+            y_max: max_variance,
+            x_label: tr!("stats-axis-mean"),
+            y_label: tr!("stats-axis-variance"),
+            color: palette.user_node.clone(),
+        }
+        if breakdown.user_spread.len() < breakdown.users {
+            p { class: "tiny",
+                {tr!("stats-spread-sampled", shown = breakdown.user_spread.len(), total = breakdown.users)}
+            }
+        }
+        RankedBars {
+            title: tr!("stats-chart-most-rated"),
+            bars: breakdown.most_rated.clone(),
+            color: palette.anime_node.clone(),
+        }
+        RankedBars {
+            title: tr!("stats-chart-least-rated"),
+            bars: breakdown.least_rated.clone(),
+            color: palette.anime_node.clone(),
+        }
+        RankedBars {
+            title: tr!("stats-chart-genres"),
+            bars: genres,
+            color: palette.anime_node.clone(),
+        }
+        if breakdown.unknown_genres > 0 {
+            p { class: "tiny", {tr!("stats-genres-unknown", count = breakdown.unknown_genres)} }
+        }
+        ShareBar {
+            title: tr!("stats-chart-density"),
+            share: density,
+            label: tr!("stats-density", percent = format!("{:.3}", density * 100.0)),
+            color: palette.user_node.clone(),
+        }
+        label { class: "field",
+            span { class: "tiny", {tr!("stats-min-raters")} }
+            input {
+                r#type: "number",
+                min: "1",
+                value: "{min_raters}",
+                onchange: move |evt| {
+                    if let Ok(count) = evt.value().parse::<usize>() {
+                        min_raters.set(count.max(1));
+                    }
+                },
+            }
+        }
+        SpreadTable {
+            title: tr!("stats-polarizing"),
+            spreads: rankings.read().polarizing.clone(),
+        }
+        SpreadTable {
+            title: tr!("stats-consensus"),
+            spreads: rankings.read().consensus.clone(),
+        }
+        ActivityCalendar { user_id: None }
+    }
+}
+
+/// Ratings per day over one year, of `user_id` or of everyone, and the ratings of the day
+/// clicked. Only a note when no rating has a date.
+#[component]
+fn ActivityCalendar(user_id: Option<String>) -> Element {
+    let app = use_context::<AppState>();
+    let mut year = use_signal(|| None::<i64>);
+    let mut day = use_signal(|| None::<i64>);
+    let activity = use_memo(use_reactive!(|user_id| {
+        let dataset = app.dataset.read();
+        match user_id {
+            Some(user_id) => {
+                Activity::new(dataset.users.iter().filter(|user| user.user_id == user_id))
+            }
+            None => Activity::new(&dataset.users),
+        }
+    }));
+    let activity = activity.read();
+    let years = activity.years();
+    let Some(&latest) = years.last() else {
+        return rsx! {
+            p { class: "tiny", {tr!("activity-undated")} }
+        };
+    };
+    let shown = year().filter(|year| years.contains(year)).unwrap_or(latest);
+    let days = activity.year(shown);
+    let first_weekday = activity::weekday(days[0].0);
+    let selected = day().and_then(|day| days.iter().position(|&(other, _)| other == day));
+    let total: usize = days.iter().map(|(_, count)| count).sum();
+    let cells: Vec<_> = days
+        .iter()
+        .map(|&(day, count)| (activity::format_day(day), count))
+        .collect();
+    let ratings: Vec<_> = match day() {
+        Some(day) => {
+            let dataset = app.dataset.read();
+            let users = dataset.users.iter().filter(|user| {
+                user_id
+                    .as_ref()
+                    .is_none_or(|user_id| user.user_id == *user_id)
+            });
+            activity::ratings_on(users, day)
+                .into_iter()
+                .map(|(rater, rating)| {
+                    (
+                        rater.to_string(),
+                        app.find_user_node(rater),
+                        app.graph.read().find_anime(rating.anime_id),
+                        app.list_title(rating.anime_id, &rating.title),
+                        rating.raw_score,
+                    )
+                })
+                .collect()
+        }
+        None => Vec::new(),
+    };
+    let config = app.config.read();
+    let palette = theme::active_palette(&config.palettes, config.palette.as_deref());
+    let color = if user_id.is_some() {
+        palette.user_node.clone()
+    } else {
+        palette.anime_node.clone()
+    };
+    rsx! {
+        if years.len() > 1 {
+            div { class: "row",
+                for other in years.iter().copied() {
+                    button {
+                        key: "{other}",
+                        class: "action",
+                        aria_pressed: other == shown,
+                        onclick: move |_| {
+                            year.set(Some(other));
+                            day.set(None);
+                        },
+                        "{other}"
+                    }
+                }
+            }
+        }
+        CalendarHeatmap {
+            title: tr!("activity-title", year = shown, count = total),
+            first_weekday,
+            days: cells,
+            selected,
+            color,
+            on_select: move |index: usize| {
+                let clicked = days[index].0;
+                day.set((day() != Some(clicked)).then_some(clicked));
+            },
+        }
+        if activity.undated > 0 {
+            p { class: "tiny", {tr!("activity-some-undated", count = activity.undated)} }
+        }
+        if let Some(day) = day() {
+            div { class: "field",
+                span { class: "tiny",
+                    {tr!("activity-day", date = activity::format_day(day), count = ratings.len())}
+                }
+                ul { class: "semantic-results",
+                    for (index, (rater, user_node, anime_node, title, score)) in ratings.into_iter().enumerate() {
+                        li { key: "{index}",
+                            if let Some(node) = anime_node {
+                                button {
+                                    class: "link",
+                                    onclick: move |_| app.open_node(node),
+                                    "{title}"
+                                }
+                            } else {
+                                span { "{title}" }
+                            }
+                            span { class: "tiny", " {score:.1} " }
+                            if user_id.is_none() {
+                                if let Some(node) = user_node {
+                                    button {
+                                        class: "link",
+                                        onclick: move |_| app.open_node(node),
+                                        {app.user_name(&rater)}
+                                    }
+                                } else {
+                                    span { class: "tiny", "{rater}" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The open dataset's health checks: what loading dropped, and duplicate ids, mismatched titles
+/// and copied users in what it kept. Failed checks list their examples; Save report writes the
+/// whole report as JSON, as `wasiw validate --json` prints it.
+#[component]
+fn DatasetHealth() -> Element {
+    let mut app = use_context::<AppState>();
+    let report = use_memo(move || HealthReport::new(&app.dataset.read(), &app.load_report.read()));
+    let save = move |_| {
+        spawn(async move {
+            let Some(path) = menu::pick_health_report().await else {
+                return;
+            };
+            let result = serde_json::to_vec_pretty(&*report.peek())
+                .map_err(std::io::Error::other)
+                .and_then(|content| std::fs::write(&path, content));
+            app.import_status.set(Some(match result {
+                Ok(()) => tr!("health-saved", path = path.display().to_string()),
+                Err(err) => tr!("health-save-failed", error = err.to_string()),
+            }));
+        });
+    };
+    let report = report.read();
+    let problems: Vec<Check> = report.problems().cloned().collect();
+    rsx! {
+        div { class: "field",
+            div { class: "row",
+                span { class: "tiny",
+                    if report.is_healthy() {
+                        {tr!("health-healthy")}
+                    } else {
+                        {tr!("health-summary", errors = report.errors, warnings = report.warnings)}
+                    }
+                }
+                button { class: "action", onclick: save, {tr!("health-save")} }
+            }
+            for check in problems {
+                div { key: "{check.kind.label()}",
+                    strong {
+                        class: match check.severity {
+                            Severity::Error => "health-error",
+                            Severity::Warning => "health-warning",
+                        },
+                        {i18n::health_check_name(check.kind)}
+                    }
+                    span { class: "tiny", " {check.count}" }
+                    if !check.examples.is_empty() {
+                        ul { class: "load-errors",
+                            for (index, example) in check.examples.iter().enumerate() {
+                                li { key: "{index}", class: "tiny", "{example}" }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Anime with their raters, mean and variance; titles open the anime.
+#[component]
+fn SpreadTable(title: String, spreads: Vec<ScoreSpread>) -> Element {
+    let app = use_context::<AppState>();
+    let rows: Vec<_> = spreads
+        .into_iter()
+        .map(|spread| {
+            let node = app.graph.read().find_anime(spread.anime_id);
+            let title = app.list_title(spread.anime_id, &spread.title);
+            (spread, node, title)
+        })
+        .collect();
+    rsx! {
+        div { class: "field",
+            span { class: "tiny", "{title}" }
+            if rows.is_empty() {
+                span { class: "tiny", {tr!("stats-ranking-empty")} }
+            } else {
+                table { class: "ranking",
+                    thead {
+                        tr {
+                            th { {tr!("stats-column-anime")} }
+                            th { {tr!("stats-column-raters")} }
+                            th { {tr!("stats-column-mean")} }
+                            th { {tr!("stats-column-variance")} }
+                        }
+                    }
+                    tbody {
+                        for (spread, node, title) in rows {
+                            tr { key: "{spread.anime_id}",
+                                td {
+                                    if let Some(node) = node {
+                                        button {
+                                            class: "link",
+                                            onclick: move |_| app.open_node(node),
+                                            "{title}"
+                                        }
+                                    } else {
+                                        "{title}"
+                                    }
+                                }
+                                td { "{spread.ratings}" }
+                                td { {format!("{:.2}", spread.mean)} }
+                                td { {format!("{:.2}", spread.variance)} }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// One anime's ratings: their spread against the dataset's average, how divided raters are,
+/// what else they rated, and the trend over time when the ratings are dated.
+#[component]
+pub fn AnimePage(anime_id: u32) -> Element {
+    let mut app = use_context::<AppState>();
+    let analytics = use_memo(use_reactive!(|anime_id| AnimeAnalytics::new(
+        &app.dataset.read(),
+        anime_id
+    )));
+    let analytics = analytics.read();
+    let title = app.list_title(anime_id, &analytics.title);
+    let config = app.config.read();
+    let palette = theme::active_palette(&config.palettes, config.palette.as_deref());
+    let back = back_button();
+    if analytics.ratings == 0 {
+        return rsx! {
+            {back}
+            p { class: "tiny", {tr!("anime-unrated")} }
+        };
+    }
+    let scores: Vec<_> = (1..=10)
+        .map(|score| (score.to_string(), analytics.scores[score - 1]))
+        .collect();
+    let per_month: Vec<_> = analytics
+        .trend
+        .iter()
+        .map(|month| (month.month.clone(), month.ratings as f64))
+        .collect();
+    let most_in_a_month = per_month
+        .iter()
+        .map(|(_, count)| *count)
+        .fold(1.0, f64::max);
+    let mean_by_month: Vec<_> = analytics
+        .trend
+        .iter()
+        .map(|month| (month.month.clone(), month.mean))
+        .collect();
+    let partners: Vec<_> = analytics
+        .partners
+        .iter()
+        .map(|partner| {
+            let node = app.graph.read().find_anime(partner.anime_id);
+            (
+                partner.clone(),
+                node,
+                app.list_title(partner.anime_id, &partner.title),
+            )
+        })
+        .collect();
+    let picked = (app.compare_with)().filter(|&other| other != anime_id);
+    let picked_title = picked.map(|other| app.anime_title(other));
+    let franchise = app.relations.read().franchise_members(anime_id);
+    let franchise_order = (franchise.len() > 1).then(|| app.watch_order(&franchise));
+    rsx! {
+        {back}
+        h2 { "{title}" }
+        div { class: "row",
+            if let (Some(other), Some(other_title)) = (picked, picked_title) {
+                button {
+                    class: "action",
+                    onclick: move |_| {
+                        navigator().push(Route::Compare { left: other, right: anime_id });
+                    },
+                    {tr!("anime-compare-with", title = other_title)}
+                }
+            }
+            button {
+                class: "action",
+                disabled: (app.compare_with)() == Some(anime_id),
+                onclick: move |_| app.compare_with.set(Some(anime_id)),
+                {tr!("anime-pick-compare")}
+            }
+        }
+        div { class: "stats",
+            StatRow { label: tr!("anime-ratings"), value: analytics.ratings.to_string() }
+            StatRow { label: tr!("anime-mean"), value: format!("{:.2}", analytics.mean) }
+            StatRow { label: tr!("anime-global-mean"), value: format!("{:.2}", analytics.global_mean) }
+            StatRow {
+                label: tr!("anime-difference"),
+                value: format!("{:+.2}", analytics.mean - analytics.global_mean),
+            }
+            StatRow { label: tr!("anime-controversy"), value: format!("{:.2}", analytics.controversy) }
+        }
+        Histogram {
+            title: tr!("anime-scores"),
+            bars: scores,
+            color: palette.anime_node.clone(),
+        }
+        if analytics.trend.len() > 1 {
+            LineChart {
+                title: tr!("anime-trend-mean"),
+                points: mean_by_month,
+                y_max: 10.0,
+                color: palette.anime_node.clone(),
+            }
+            LineChart {
+                title: tr!("anime-trend-ratings"),
+                points: per_month,
+                y_max: most_in_a_month,
+                color: palette.user_node.clone(),
+            }
+        } else {
+            p { class: "tiny", {tr!("anime-no-trend")} }
+        }
+        div { class: "field",
+            span { class: "tiny", {tr!("anime-partners")} }
+            ul { class: "semantic-results",
+                for (partner, node, name) in partners {
+                    li { key: "{partner.anime_id}",
+                        if let Some(node) = node {
+                            button {
+                                class: "link",
+                                onclick: move |_| app.open_node(node),
+                                "{name}"
+                            }
+                        } else {
+                            span { "{name}" }
+                        }
+                        span { class: "tiny",
+                            " "
+                            {tr!("anime-shared", count = partner.shared)}
+                            " "
+                        }
+                        button {
+                            class: "link",
+                            onclick: move |_| {
+                                navigator()
+                                    .push(Route::Compare {
+                                        left: anime_id,
+                                        right: partner.anime_id,
+                                    });
+                            },
+                            {tr!("anime-compare")}
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(order) = franchise_order {
+            div { class: "field",
+                span { class: "tiny", {tr!("anime-watch-order")} }
+                WatchOrderPanel { order, heading: tr!("watch-order-for", name = title.as_str()) }
+            }
+        }
+    }
+}
+
+/// A watch order with its estimated length, saved as a Markdown checklist under `heading` on
+/// request.
+#[component]
+fn WatchOrderPanel(order: WatchOrder, heading: String) -> Element {
+    let mut app = use_context::<AppState>();
+    let total = match order.unknown_lengths() {
+        0 => tr!(
+            "watch-order-total",
+            hours = watch_order::hours(order.total_minutes())
+        ),
+        unknown => tr!(
+            "watch-order-total-unknown",
+            hours = watch_order::hours(order.total_minutes()),
+            count = unknown
+        ),
+    };
+    let checklist = order.checklist(&heading);
+    let save = move |_| {
+        let checklist = checklist.clone();
+        spawn(async move {
+            let Some(path) = menu::pick_checklist().await else {
+                return;
+            };
+            app.import_status
+                .set(Some(match std::fs::write(&path, checklist) {
+                    Ok(()) => tr!("watch-order-saved", path = path.display().to_string()),
+                    Err(err) => tr!("watch-order-save-failed", error = err.to_string()),
+                }));
+        });
+    };
+    rsx! {
+        ol { class: "semantic-results",
+            for entry in order.entries {
+                li { key: "{entry.anime_id}",
+                    span { "{entry.title}" }
+                    if let Some(year) = entry.year {
+                        span { class: "tiny", " {year}" }
+                    }
+                    if let Some(minutes) = entry.minutes {
+                        span { class: "tiny", " · {watch_order::hours(minutes)} h" }
+                    }
+                }
+            }
+        }
+        div { class: "row",
+            span { class: "tiny", "{total}" }
+            button { class: "action", onclick: save, {tr!("watch-order-export")} }
+        }
+    }
+}
+
+/// The recommendations that best fill a given number of hours, each watched start to finish
+/// along with what leads up to it.
+#[component]
+fn TimeBudgetPanel(candidates: Vec<BudgetCandidate>) -> Element {
+    let mut hours = use_signal(|| 12_u32);
+    let mut longest = use_signal(|| None::<u32>);
+    let mut format = use_signal(Format::default);
+    let plan = budget::fit(
+        &candidates,
+        &TimeBudget {
+            minutes: hours() * 60,
+            longest: longest().map(|hours| hours * 60),
+            format: format(),
+        },
+    );
+    rsx! {
+        div { class: "row",
+            label { class: "field",
+                span { class: "tiny", {tr!("budget-hours")} }
+                input {
+                    r#type: "number",
+                    min: "1",
+                    value: "{hours}",
+                    onchange: move |evt| {
+                        if let Ok(next) = evt.value().parse::<u32>() {
+                            hours.set(next.max(1));
+                        }
+                    },
+                }
+            }
+            label { class: "field", title: tr!("budget-longest-hint"),
+                span { class: "tiny", {tr!("budget-longest")} }
+                input {
+                    r#type: "number",
+                    min: "0",
+                    value: "{longest().unwrap_or_default()}",
+                    onchange: move |evt| {
+                        if let Ok(next) = evt.value().parse::<u32>() {
+                            longest.set((next > 0).then_some(next));
+                        }
+                    },
+                }
+            }
+            label { class: "field",
+                span { class: "tiny", {tr!("budget-format")} }
+                select {
+                    onchange: move |evt| {
+                        if let Some(next) = Format::ALL.into_iter().find(|f| f.label() == evt.value()) {
+                            format.set(next);
+                        }
+                    },
+                    for option_format in Format::ALL {
+                        option {
+                            value: option_format.label(),
+                            selected: option_format == format(),
+                            {i18n::budget_format_name(option_format)}
+                        }
+                    }
+                }
+            }
+        }
+        if plan.picks.is_empty() {
+            span { class: "tiny", {tr!("budget-empty")} }
+        } else {
+            ol { class: "semantic-results",
+                for pick in &plan.picks {
+                    li { key: "{pick.anime_id}",
+                        span { "{pick.title}" }
+                        span { class: "tiny", " · {watch_order::hours(pick.minutes)} h" }
+                    }
+                }
+            }
+            span { class: "tiny",
+                {tr!("budget-total", hours = watch_order::hours(plan.minutes), count = plan.picks.len())}
+            }
+        }
+    }
+}
+
+/// How one user rates: their scores against everyone's, how harsh they are next to each
+/// anime's consensus, their genres, where they disagree most, and how much of the corpus they
+/// share with others.
+#[component]
+pub fn UserPage(user_id: String) -> Element {
+    let mut app = use_context::<AppState>();
+    let analytics = use_memo(use_reactive!(|user_id| {
+        let metadata = app.metadata.read();
+        UserAnalytics::new(&app.dataset.read(), &user_id, |anime_id| {
+            metadata
+                .get(anime_id)
+                .map(|metadata| metadata.genres.clone())
+                .unwrap_or_default()
+        })
+    }));
+    let analytics = analytics.read();
+    let Some(analytics) = analytics.as_ref() else {
+        return rsx! {
+            {back_button()}
+            p { class: "tiny", {tr!("user-missing")} }
+        };
+    };
+    let name = app.user_name(&user_id);
+    let picked = app
+        .compare_user_with
+        .cloned()
+        .filter(|other| *other != user_id);
+    let picked_name = picked.as_deref().map(|other| app.user_name(other));
+    let is_picked = app.compare_user_with.read().as_deref() == Some(user_id.as_str());
+    let config = app.config.read();
+    let palette = theme::active_palette(&config.palettes, config.palette.as_deref());
+    let scores: Vec<_> = (1..=10)
+        .map(|score| (score.to_string(), analytics.scores[score - 1]))
+        .collect();
+    let genres: Vec<_> = analytics
+        .genres
+        .iter()
+        .map(|genre| (genre.genre.clone(), genre.mean))
+        .collect();
+    let coverage = if analytics.corpus_anime == 0 {
+        0.0
+    } else {
+        analytics.ratings as f64 / analytics.corpus_anime as f64
+    };
+    let contrarian: Vec<_> = analytics
+        .contrarian
+        .iter()
+        .map(|opinion| {
+            let node = app.graph.read().find_anime(opinion.anime_id);
+            (
+                opinion.clone(),
+                node,
+                app.list_title(opinion.anime_id, &opinion.title),
+            )
+        })
+        .collect();
+    rsx! {
+        {back_button()}
+        h2 { "{name}" }
+        div { class: "row",
+            if let (Some(other), Some(other_name)) = (picked, picked_name) {
+                button {
+                    class: "action",
+                    onclick: {
+                        let user_id = user_id.clone();
+                        move |_| {
+                            navigator()
+                                .push(Route::CompareUsers {
+                                    left: other.clone(),
+                                    right: user_id.clone(),
+                                });
+                        }
+                    },
+                    {tr!("anime-compare-with", title = other_name)}
+                }
+            }
+            button {
+                class: "action",
+                disabled: is_picked,
+                onclick: {
+                    let user_id = user_id.clone();
+                    move |_| app.compare_user_with.set(Some(user_id.clone()))
+                },
+                {tr!("anime-pick-compare")}
+            }
+        }
+        div { class: "stats",
+            StatRow { label: tr!("anime-ratings"), value: analytics.ratings.to_string() }
+            StatRow { label: tr!("user-mean"), value: format!("{:.2}", analytics.mean) }
+            StatRow { label: tr!("anime-global-mean"), value: format!("{:.2}", analytics.global_mean) }
+            StatRow { label: tr!("user-harshness"), value: format!("{:+.2}", analytics.harshness) }
+            StatRow {
+                label: tr!("user-shared-anime"),
+                value: format!("{} / {}", analytics.shared_anime, analytics.ratings),
+            }
+            StatRow {
+                label: tr!("user-overlapping"),
+                value: analytics.overlapping_users.to_string(),
+            }
+        }
+        p { class: "tiny", {tr!("user-harshness-hint")} }
+        Histogram {
+            title: tr!("anime-scores"),
+            bars: scores,
+            color: palette.user_node.clone(),
+        }
+        if genres.len() >= 3 {
+            RadarChart {
+                title: tr!("user-genres"),
+                axes: genres,
+                max: 10.0,
+                color: palette.user_node.clone(),
+            }
+        } else {
+            p { class: "tiny", {tr!("user-genres-missing")} }
+        }
+        ShareBar {
+            title: tr!("user-coverage"),
+            share: coverage,
+            label: tr!(
+                "user-coverage-share", rated = analytics.ratings, total = analytics.corpus_anime,
+                percent = format!("{:.1}", coverage * 100.0)
+            ),
+            color: palette.user_node.clone(),
+        }
+        div { class: "field",
+            span { class: "tiny", {tr!("user-contrarian")} }
+            if contrarian.is_empty() {
+                span { class: "tiny", {tr!("user-contrarian-empty")} }
+            }
+            ul { class: "semantic-results",
+                for (opinion, node, title) in contrarian {
+                    li { key: "{opinion.anime_id}",
+                        if let Some(node) = node {
+                            button {
+                                class: "link",
+                                onclick: move |_| app.open_node(node),
+                                "{title}"
+                            }
+                        } else {
+                            span { "{title}" }
+                        }
+                        span { class: "tiny",
+                            " "
+                            {
+                                tr!(
+                                    "user-opinion", score = format!("{:.1}", opinion.score), consensus =
+                                    format!("{:.1}", opinion.consensus)
+                                )
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        ActivityCalendar { user_id: Some(user_id.clone()) }
+    }
+}
+
+/// Two anime side by side: their shared raters and which of the two those preferred, what the
+/// similarity score between them is made of, and the genres they share.
+#[component]
+pub fn ComparePage(left: u32, right: u32) -> Element {
+    let app = use_context::<AppState>();
+    let comparison = use_memo(use_reactive!(|left, right| {
+        let metadata = app.metadata.read();
+        AnimeComparison::new(&app.dataset.read(), left, right, |anime_id| {
+            metadata
+                .get(anime_id)
+                .map(|metadata| metadata.genres.clone())
+                .unwrap_or_default()
+        })
+    }));
+    let comparison = comparison.read();
+    let left_title = app.list_title(left, &comparison.left_title);
+    let right_title = app.list_title(right, &comparison.right_title);
+    let config = app.config.read();
+    let palette = theme::active_palette(&config.palettes, config.palette.as_deref());
+    let preferences = vec![
+        (left_title.clone(), comparison.prefer_left),
+        (right_title.clone(), comparison.prefer_right),
+        (tr!("compare-tied"), comparison.tied),
+    ];
+    let parts: Vec<_> = comparison
+        .parts
+        .iter()
+        .map(|part| (part.clone(), app.find_user_node(&part.user_id)))
+        .collect();
+    let genre_list = |genres: &[String]| {
+        if genres.is_empty() {
+            tr("compare-genres-none")
+        } else {
+            genres.join(", ")
+        }
+    };
+    let genre_overlap = comparison.genre_overlap();
+    rsx! {
+        {back_button()}
+        h2 { {tr!("compare-title", left = left_title.clone(), right = right_title.clone())} }
+        div { class: "stats",
+            StatRow {
+                label: tr!("compare-ratings", title = left_title.clone()),
+                value: comparison.left_ratings.to_string(),
+            }
+            StatRow {
+                label: tr!("compare-ratings", title = right_title.clone()),
+                value: comparison.right_ratings.to_string(),
+            }
+            StatRow { label: tr!("compare-shared"), value: comparison.shared_raters.to_string() }
+        }
+        if comparison.shared_raters == 0 {
+            p { class: "tiny", {tr!("compare-no-shared")} }
+        } else {
+            RankedBars {
+                title: tr!("compare-preferred"),
+                bars: preferences,
+                color: palette.anime_node.clone(),
+            }
+            div { class: "stats",
+                StatRow {
+                    label: tr!("compare-mean", title = left_title.clone()),
+                    value: format!("{:.2}", comparison.left_mean),
+                }
+                StatRow {
+                    label: tr!("compare-mean", title = right_title.clone()),
+                    value: format!("{:.2}", comparison.right_mean),
+                }
+                StatRow {
+                    label: tr!("compare-relative", title = left_title.clone()),
+                    value: format!("{:+.2}", comparison.left_relative),
+                }
+                StatRow {
+                    label: tr!("compare-relative", title = right_title.clone()),
+                    value: format!("{:+.2}", comparison.right_relative),
+                }
+                StatRow {
+                    label: tr!("compare-similarity"),
+                    value: format!("{:+.2}", comparison.similarity),
+                }
+            }
+            p { class: "tiny", {tr!("compare-similarity-hint")} }
+            div { class: "field",
+                span { class: "tiny", {tr!("compare-parts")} }
+                table { class: "ranking",
+                    thead {
+                        tr {
+                            th { {tr!("compare-column-user")} }
+                            th { "{left_title}" }
+                            th { "{right_title}" }
+                            th { {tr!("compare-column-part")} }
+                        }
+                    }
+                    tbody {
+                        for (part, node) in parts {
+                            tr { key: "{part.user_id}",
+                                td {
+                                    if let Some(node) = node {
+                                        button {
+                                            class: "link",
+                                            onclick: move |_| app.open_node(node),
+                                            "{part.user_id}"
+                                        }
+                                    } else {
+                                        "{part.user_id}"
+                                    }
+                                }
+                                td { {format!("{:+.2}", part.left)} }
+                                td { {format!("{:+.2}", part.right)} }
+                                td { {format!("{:+.2}", part.pair_score())} }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        ShareBar {
+            title: tr!("compare-genres"),
+            share: genre_overlap,
+            label: tr!(
+                "compare-genres-shared", percent = format!("{:.0}", genre_overlap * 100.0), genres =
+                genre_list(&comparison.shared_genres)
+            ),
+            color: palette.anime_node.clone(),
+        }
+        p { class: "tiny",
+            {tr!("compare-genres-only", title = left_title, genres = genre_list(&comparison.left_genres))}
+        }
+        p { class: "tiny",
+            {tr!("compare-genres-only", title = right_title, genres = genre_list(&comparison.right_genres))}
+        }
+    }
+}
+
+/// Two users side by side: how much of what they rated overlaps and how alike their scores are,
+/// where they agree and disagree most, and anime neither rated that both would enjoy.
+#[component]
+pub fn CompareUsersPage(left: String, right: String) -> Element {
+    let app = use_context::<AppState>();
+    let comparison = use_memo(use_reactive!(|left, right| UserComparison::new(
+        &app.dataset.read(),
+        &left,
+        &right
+    )));
+    let together = use_memo(use_reactive!(|left, right| {
+        let users = [app.find_user_node(&left), app.find_user_node(&right)];
+        let [Some(first), Some(second)] = users else {
+            return Vec::new();
+        };
+        recommend::recommend_together(&app.graph.read(), [first, second], LISTED_TOGETHER)
+    }));
+    let comparison = comparison.read();
+    let Some(comparison) = comparison.as_ref() else {
+        return rsx! {
+            {back_button()}
+            p { class: "tiny", {tr!("user-missing")} }
+        };
+    };
+    let names = (app.user_name(&left), app.user_name(&right));
+    let config = app.config.read();
+    let palette = theme::active_palette(&config.palettes, config.palette.as_deref());
+    let pearson = comparison
+        .pearson
+        .map_or_else(|| tr("compare-users-pearson-none"), |r| format!("{r:+.2}"));
+    let together: Vec<_> = together
+        .read()
+        .iter()
+        .map(|pick| (pick.clone(), app.list_title(pick.anime_id, &pick.title)))
+        .collect();
+    rsx! {
+        {back_button()}
+        h2 { {tr!("compare-title", left = names.0.clone(), right = names.1.clone())} }
+        div { class: "stats",
+            StatRow {
+                label: tr!("compare-ratings", title = names.0.clone()),
+                value: comparison.left_ratings.to_string(),
+            }
+            StatRow {
+                label: tr!("compare-ratings", title = names.1.clone()),
+                value: comparison.right_ratings.to_string(),
+            }
+            StatRow { label: tr!("compare-users-shared"), value: comparison.shared.to_string() }
+            StatRow { label: tr!("compare-users-pearson"), value: pearson }
+        }
+        ShareBar {
+            title: tr!("compare-users-jaccard"),
+            share: comparison.jaccard,
+            label: tr!("compare-users-jaccard-share", percent = format!("{:.1}", comparison.jaccard * 100.0)),
+            color: palette.user_node.clone(),
+        }
+        OpinionTable {
+            title: tr!("compare-users-agreements"),
+            names: names.clone(),
+            opinions: comparison.agreements.clone(),
+        }
+        OpinionTable {
+            title: tr!("compare-users-disagreements"),
+            names: names.clone(),
+            opinions: comparison.disagreements.clone(),
+        }
+        div { class: "field",
+            span { class: "tiny", {tr!("compare-users-together")} }
+            if together.is_empty() {
+                span { class: "tiny", {tr!("compare-users-together-empty")} }
+            }
+            ul { class: "semantic-results",
+                for (pick, title) in together {
+                    li { key: "{pick.anime_id}",
+                        button {
+                            class: "link",
+                            onclick: move |_| app.open_node(pick.node),
+                            "{title}"
+                        }
+                        span { class: "tiny", " {pick.scores[0]:.2} · {pick.scores[1]:.2}" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Anime with two users' scores for them; titles open the anime.
+#[component]
+fn OpinionTable(title: String, names: (String, String), opinions: Vec<SharedOpinion>) -> Element {
+    let app = use_context::<AppState>();
+    let rows: Vec<_> = opinions
+        .into_iter()
+        .map(|opinion| {
+            let node = app.graph.read().find_anime(opinion.anime_id);
+            let title = app.list_title(opinion.anime_id, &opinion.title);
+            (opinion, node, title)
+        })
+        .collect();
+    rsx! {
+        div { class: "field",
+            span { class: "tiny", "{title}" }
+            if rows.is_empty() {
+                span { class: "tiny", {tr!("compare-users-none")} }
+            } else {
+                table { class: "ranking",
+                    thead {
+                        tr {
+                            th { {tr!("stats-column-anime")} }
+                            th { "{names.0}" }
+                            th { "{names.1}" }
+                        }
+                    }
+                    tbody {
+                        for (opinion, node, title) in rows {
+                            tr { key: "{opinion.anime_id}",
+                                td {
+                                    if let Some(node) = node {
+                                        button {
+                                            class: "link",
+                                            onclick: move |_| app.open_node(node),
+                                            "{title}"
+                                        }
+                                    } else {
+                                        "{title}"
+                                    }
+                                }
+                                td { {format!("{:.1}", opinion.left)} }
+                                td { {format!("{:.1}", opinion.right)} }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns to the tab an analytics page was opened from.
+fn back_button() -> Element {
+    rsx! {
+        button { class: "action", onclick: move |_| navigator().go_back(), {tr!("anime-back")} }
+    }
+}
+
+/// Taste tribes: users grouped by how they score, each with its signature shows and members.
+/// Finding them runs as a background job and the result stays until the dataset changes.
+#[component]
+pub fn TribesPage() -> Element {
+    let mut app = use_context::<AppState>();
+    let mut count = use_signal(|| tribes::DEFAULT_TRIBES);
+    let running = app.tasks.read().is_running(TaskKind::Tribes);
+    let find = move |_| {
+        let dataset = app.dataset.peek().clone();
+        let count = count();
+        tasks::spawn_blocking(
+            app.tasks,
+            TaskKind::Tribes,
+            tr("tribes-job"),
+            move |_| TribeReport::new(&dataset, count),
+            move |report| match report {
+                Some(report) => app.tribes.set(Some(report)),
+                None => app.import_status.set(Some(tr("tribes-failed"))),
+            },
+        );
+    };
+    let save = move |_| {
+        spawn(async move {
+            let Some(path) = menu::pick_tribe_report().await else {
+                return;
+            };
+            let markdown = app.tribes.peek().as_ref().map(|report| {
+                report.to_markdown(|anime_id, title| app.list_title(anime_id, title))
+            });
+            let Some(markdown) = markdown else {
+                return;
+            };
+            app.import_status
+                .set(Some(match std::fs::write(&path, markdown) {
+                    Ok(()) => tr!("tribes-saved", path = path.display().to_string()),
+                    Err(err) => tr!("tribes-save-failed", error = err.to_string()),
+                }));
+        });
+    };
+    let report = app.tribes.read();
+    rsx! {
+        p { class: "tiny", {tr!("tribes-intro")} }
+        div { class: "row",
+            label { class: "field",
+                span { class: "tiny", {tr!("tribes-count")} }
+                input {
+                    r#type: "number",
+                    min: "1",
+                    value: "{count}",
+                    onchange: move |evt| {
+                        if let Ok(next) = evt.value().parse::<usize>() {
+                            count.set(next.max(1));
+                        }
+                    },
+                }
+            }
+            button { class: "action", disabled: running, onclick: find, {tr!("tribes-find")} }
+            if report.is_some() {
+                button { class: "action", onclick: save, {tr!("tribes-save")} }
+            }
+        }
+        if let Some(report) = report.as_ref() {
+            if report.tribes.is_empty() {
+                p { class: "tiny", {tr!("tribes-none")} }
+            }
+            for (index, tribe) in report.tribes.iter().cloned().enumerate() {
+                TribeSection { key: "{index}", number: index + 1, tribe }
+            }
+            if report.unassigned > 0 {
+                p { class: "tiny", {tr!("tribes-unassigned", count = report.unassigned)} }
+            }
+        }
+    }
+}
+
+/// One tribe: its size and cohesion, its signature shows, and the members closest to its
+/// centre, each opening their page.
+#[component]
+fn TribeSection(number: usize, tribe: Tribe) -> Element {
+    let app = use_context::<AppState>();
+    let signatures: Vec<_> = tribe
+        .signatures
+        .iter()
+        .map(|signature| {
+            let node = app.graph.read().find_anime(signature.anime_id);
+            let title = app.list_title(signature.anime_id, &signature.title);
+            (signature.clone(), node, title)
+        })
+        .collect();
+    let members: Vec<_> = tribe
+        .members
+        .iter()
+        .take(SHOWN_TRIBE_MEMBERS)
+        .map(|user_id| (user_id.clone(), app.user_name(user_id)))
+        .collect();
+    let hidden_members = tribe.size - members.len();
+    rsx! {
+        div { class: "field",
+            strong {
+                {tr!("tribes-heading", number = number, count = tribe.size, cohesion = format!("{:.2}", tribe.cohesion))}
+            }
+            if signatures.is_empty() {
+                span { class: "tiny", {tr!("tribes-no-signature")} }
+            } else {
+                table { class: "ranking",
+                    thead {
+                        tr {
+                            th { {tr!("stats-column-anime")} }
+                            th { {tr!("stats-column-raters")} }
+                            th { {tr!("tribes-column-tribe-mean")} }
+                            th { {tr!("tribes-column-others-mean")} }
+                        }
+                    }
+                    tbody {
+                        for (signature, node, title) in signatures {
+                            tr { key: "{signature.anime_id}",
+                                td {
+                                    if let Some(node) = node {
+                                        button {
+                                            class: "link",
+                                            onclick: move |_| app.open_node(node),
+                                            "{title}"
+                                        }
+                                    } else {
+                                        "{title}"
+                                    }
+                                }
+                                td { "{signature.raters}" }
+                                td { {format!("{:.2}", signature.tribe_mean)} }
+                                td { {format!("{:.2}", signature.others_mean)} }
+                            }
+                        }
+                    }
+                }
+            }
+            span { class: "tiny", {tr!("tribes-members")} }
+            div { class: "row",
+                for (user_id, name) in members {
+                    button {
+                        key: "{user_id}",
+                        class: "link",
+                        onclick: move |_| {
+                            navigator().push(Route::User { user_id: user_id.clone() });
+                        },
+                        "{name}"
+                    }
+                }
+            }
+            if hidden_members > 0 {
+                span { class: "tiny", {tr!("tribes-more-members", count = hidden_members)} }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn WatchlistPage() -> Element {
+    let app = use_context::<AppState>();
+    rsx! {
+        Watchlist { app }
+    }
+}
+
+/// The importers and the profile picker, which decides where imports go.
+#[component]
+pub fn ImportPage() -> Element {
+    let app = use_context::<AppState>();
+    let shell = use_context::<Shell>();
+    rsx! {
+        ImportPanel { app, csv_table: shell.csv_table }
+        ProfilePanel { app }
+    }
+}
+
+#[component]
+pub fn SettingsPage() -> Element {
+    let mut app = use_context::<AppState>();
+    let mut shell = use_context::<Shell>();
+    let config = app.config.read();
+    let max_edges = config.max_rendered_edges();
+    let (palettes, skipped_palettes) = theme::available_palettes(&config.palettes);
+    let palette = theme::active_palette(&config.palettes, config.palette.as_deref()).name;
+    rsx! {
+        StartupDataset {
+            pinned: config.dataset.as_ref().map(|path| path.display().to_string()),
+            current: shell.watched_path.read().as_ref().map(|path| path.display().to_string()),
+            on_change: move |path: Option<String>| {
+                let mut config = app.config.write();
+                config.dataset = path.map(PathBuf::from);
+                let _ = config.save();
+            },
+        }
+        RenderControls {
+            max_edges,
+            layout_seed: app.graph.read().layout_seed,
+            on_max_edges: move |max_edges: usize| {
+                let mut config = app.config.write();
+                config.max_rendered_edges = Some(max_edges);
+                let _ = config.save();
+            },
+            on_layout_seed: move |seed: u64| {
+                app.graph.write().layout_seed = seed;
+                shell.relayout.call(*app.viewport.peek());
+                let mut config = app.config.write();
+                config.layout_seed = seed;
+                let _ = config.save();
+            },
+        }
+        label { class: "field", title: tr!("settings-memory-budget-hint"),
+            span { class: "tiny", {tr!("settings-memory-budget")} }
+            input {
+                r#type: "number",
+                min: "0",
+                step: "256",
+                value: "{config.memory_budget_mb.unwrap_or_default()}",
+                onchange: move |evt| {
+                    if let Ok(mb) = evt.value().parse::<u64>() {
+                        let mut config = app.config.write();
+                        config.memory_budget_mb = (mb > 0).then_some(mb);
+                        let _ = config.save();
+                    }
+                },
+            }
+        }
+        label { class: "field", title: tr!("settings-similar-neighbors-hint"),
+            span { class: "tiny", {tr!("settings-similar-neighbors")} }
+            input {
+                r#type: "number",
+                min: "1",
+                value: "{config.similar_neighbors()}",
+                onchange: move |evt| {
+                    if let Ok(neighbors) = evt.value().parse::<usize>() {
+                        let neighbors = neighbors.max(1);
+                        {
+                            let mut config = app.config.write();
+                            config.similar_neighbors = Some(neighbors);
+                            let _ = config.save();
+                        }
+                        app.graph.write().similar_neighbors = neighbors;
+                        app.rebuild_graph();
+                    }
+                },
+            }
+        }
+        label { class: "toggle",
+            input {
+                r#type: "checkbox",
+                checked: !config.fresh_start,
+                onchange: move |evt| {
+                    let mut config = app.config.write();
+                    config.fresh_start = !evt.checked();
+                    let _ = config.save();
+                },
+            }
+            span { {tr!("settings-restore-session")} }
+        }
+        label { class: "toggle", title: tr!("settings-tray-hint"),
+            input {
+                r#type: "checkbox",
+                checked: config.tray,
+                onchange: move |evt| {
+                    let mut config = app.config.write();
+                    config.tray = evt.checked();
+                    let _ = config.save();
+                },
+            }
+            span { {tr!("settings-tray")} }
+        }
+        NotificationControls {
+            enabled: config.notifications.enabled,
+            quiet_hours: config.notifications.quiet_hours.map(|quiet| (quiet.start, quiet.end)),
+            on_enabled: move |enabled: bool| {
+                let mut config = app.config.write();
+                config.notifications.enabled = enabled;
+                let _ = config.save();
+            },
+            on_quiet_hours: move |hours: Option<(u8, u8)>| {
+                let mut config = app.config.write();
+                config.notifications.quiet_hours = hours.map(|(start, end)| QuietHours { start, end });
+                let _ = config.save();
+            },
+        }
+        button {
+            class: "action",
+            title: tr!("settings-start-fresh-hint"),
+            onclick: move |_| shell.start_fresh.call(()),
+            {tr!("settings-start-fresh")}
+        }
+        button {
+            class: "action",
+            onclick: move |_| shell.log_open.toggle(),
+            if shell.log_open.cloned() {
+                {tr!("settings-hide-log")}
+            } else {
+                {tr!("settings-show-log")}
+            }
+        }
+        DuplicatePicker {
+            active: config.duplicates,
+            on_select: move |duplicates: MergeStrategy| {
+                {
+                    let mut config = app.config.write();
+                    config.duplicates = duplicates;
+                    let _ = config.save();
+                }
+                // Re-read the open file so the new policy shows straight away.
+                if let Some(path) = shell.watched_path.cloned() {
+                    shell.reload.call(path);
+                }
+            },
+        }
+        LanguagePicker {
+            active: config.locale(),
+            on_select: move |locale: Locale| {
+                i18n::set_locale(locale);
+                let mut config = app.config.write();
+                config.language = Some(locale);
+                let _ = config.save();
+            },
+        }
+        TitlePicker {
+            label: tr!("titles-label"),
+            active: Some(config.titles.form),
+            on_select: move |form: Option<TitleForm>| {
+                if let Some(form) = form {
+                    set_titles(app, |titles| titles.form = form);
+                }
+            },
+        }
+        ThemePicker {
+            active: config.theme,
+            on_select: move |preference: ThemePreference| {
+                let mut config = app.config.write();
+                config.theme = preference;
+                let _ = config.save();
+            },
+        }
+        ScalePicker {
+            active: config.ui_scale,
+            on_select: move |scale: UiScale| {
+                let mut config = app.config.write();
+                config.ui_scale = scale;
+                let _ = config.save();
+            },
+        }
+        PalettePicker {
+            palettes,
+            active: palette,
+            skipped: skipped_palettes,
+            on_select: move |name: String| {
+                let mut config = app.config.write();
+                config.palette = Some(name);
+                // Best effort: the choice still applies for this session if saving fails.
+                let _ = config.save();
+            },
+        }
+        MoodSettings { table: config.moods.clone() }
+    }
+}
+
+/// Changes the mood table and saves it.
+fn set_moods(mut app: AppState, change: impl FnOnce(&mut MoodTable)) {
+    let mut config = app.config.write();
+    change(&mut config.moods);
+    let _ = config.save();
+}
+
+/// The mood table as rows of a name and its comma-separated tags.
+#[component]
+fn MoodSettings(table: MoodTable) -> Element {
+    let app = use_context::<AppState>();
+    rsx! {
+        div { class: "field", title: tr!("moods-settings-hint"),
+            span { class: "tiny", {tr!("moods-settings")} }
+            for (index, mood) in table.moods.into_iter().enumerate() {
+                div { class: "row", key: "{index}",
+                    input {
+                        r#type: "text",
+                        aria_label: tr!("moods-name"),
+                        value: "{mood.name}",
+                        onchange: move |evt| {
+                            let name = evt.value().trim().to_string();
+                            if !name.is_empty() {
+                                set_moods(app, |table| table.moods[index].name = name);
+                            }
+                        },
+                    }
+                    input {
+                        r#type: "text",
+                        aria_label: tr!("moods-tags"),
+                        value: mood.tags.join(", "),
+                        onchange: move |evt| {
+                            let tags = moods::parse_tags(&evt.value());
+                            set_moods(app, |table| table.moods[index].tags = tags);
+                        },
+                    }
+                    button {
+                        class: "link",
+                        onclick: move |_| set_moods(app, |table| {
+                            table.moods.remove(index);
+                        }),
+                        {tr!("moods-remove")}
+                    }
+                }
+            }
+            div { class: "row",
+                button {
+                    class: "action",
+                    onclick: move |_| set_moods(app, |table| {
+                        let name = tr!("moods-new", number = table.moods.len() + 1);
+                        table.moods.push(Mood { name, tags: Vec::new() });
+                    }),
+                    {tr!("moods-add")}
+                }
+                button {
+                    class: "action",
+                    onclick: move |_| set_moods(app, |table| *table = MoodTable::default()),
+                    {tr!("moods-reset")}
+                }
+            }
+        }
+    }
+}
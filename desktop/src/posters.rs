@@ -0,0 +1,145 @@
+//! Poster thumbnails, downloaded the first time an anime is shown and kept in a `posters`
+//! directory next to the app's other data. The directory is capped at [`MAX_CACHE_BYTES`];
+//! the least recently shown posters are evicted first.
+//!
+//! Posters are handed to the webview as `data:` URIs, which needs no file access from it.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use base64::Engine;
+use reqwest::Client;
+
+use crate::config;
+use crate::import::ImportError;
+
+const CACHE_DIR_NAME: &str = "posters";
+const MAX_CACHE_BYTES: u64 = 64 * 1024 * 1024;
+/// Larger downloads are refused; cover images are a few hundred KB at most.
+const MAX_POSTER_BYTES: usize = 4 * 1024 * 1024;
+
+fn cache_dir() -> Option<PathBuf> {
+    config::data_file(CACHE_DIR_NAME)
+}
+
+fn cache_path(anime_id: u32) -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join(anime_id.to_string()))
+}
+
+/// The cached poster, marking it as recently used.
+pub fn cached(anime_id: u32) -> Option<Vec<u8>> {
+    let path = cache_path(anime_id)?;
+    let bytes = fs::read(&path).ok()?;
+    // Best effort: a stale timestamp only makes this poster an earlier eviction candidate.
+    let _ = fs::File::options()
+        .write(true)
+        .open(&path)
+        .and_then(|file| file.set_modified(SystemTime::now()));
+    Some(bytes)
+}
+
+/// Downloads a poster, stores it and evicts old posters beyond the size limit.
+pub async fn download(client: &Client, anime_id: u32, url: &str) -> Result<Vec<u8>, ImportError> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| ImportError::Http(format!("{url}: {err}")))?;
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|err| ImportError::Http(format!("{url}: {err}")))?;
+    if bytes.len() > MAX_POSTER_BYTES || mime_type(&bytes).is_none() {
+        return Err(ImportError::Parse(format!("{url} is not a poster image")));
+    }
+
+    let dir =
+        cache_dir().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(anime_id.to_string()), &bytes)?;
+    evict(&dir, MAX_CACHE_BYTES)?;
+    Ok(bytes.to_vec())
+}
+
+/// Deletes the least recently used posters in `dir` until they fit in `limit` bytes.
+fn evict(dir: &Path, limit: u64) -> io::Result<()> {
+    let mut files: Vec<(SystemTime, u64, PathBuf)> = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            metadata.is_file().then(|| {
+                let used = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                (used, metadata.len(), entry.path())
+            })
+        })
+        .collect();
+    let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+    files.sort_by_key(|(used, _, _)| *used);
+    for (_, size, path) in files {
+        if total <= limit {
+            break;
+        }
+        fs::remove_file(path)?;
+        total -= size;
+    }
+    Ok(())
+}
+
+/// Image type from the file signature; only formats webviews display are accepted.
+fn mime_type(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [0x89, b'P', b'N', b'G', ..] => Some("image/png"),
+        [b'G', b'I', b'F', b'8', ..] => Some("image/gif"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// A `data:` URI for an `<img>` source.
+pub fn data_uri(bytes: &[u8]) -> Option<String> {
+    let mime = mime_type(bytes)?;
+    let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+    Some(format!("data:{mime};base64,{encoded}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn only_image_files_become_data_uris() {
+        let png = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        assert_eq!(
+            data_uri(&png).as_deref(),
+            Some("data:image/png;base64,iVBORw0KGgo=")
+        );
+        assert_eq!(mime_type(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(mime_type(&[0xFF, 0xD8, 0xFF, 0xE0]), Some("image/jpeg"));
+        assert_eq!(data_uri(b"<html>not found</html>"), None);
+    }
+
+    #[test]
+    fn the_least_recently_shown_posters_go_first() {
+        let dir = std::env::temp_dir().join(format!("wasiw-posters-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let start = SystemTime::now() - Duration::from_secs(3600);
+        for (anime_id, minutes) in [(1, 30), (2, 10), (3, 20)] {
+            let path = dir.join(anime_id.to_string());
+            fs::write(&path, [0; 100]).unwrap();
+            let file = fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(start + Duration::from_secs(minutes * 60))
+                .unwrap();
+        }
+        evict(&dir, 250).unwrap();
+        assert!(!dir.join("2").exists());
+        assert!(dir.join("1").exists() && dir.join("3").exists());
+        evict(&dir, 250).unwrap();
+        assert!(dir.join("3").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,161 @@
+//! Local profiles: named sets of the user's own ratings, each with a blacklist of anime never
+//! to recommend and a watchlist, stored in `profiles.json` in the platform data directory. The
+//! active profile is merged into the dataset as a local user, so it is the one recommendations
+//! are made for; the others stay on disk until switched to.
+
+use std::collections::BTreeSet;
+use std::io;
+
+use serde::{Deserialize, Serialize};
+use wasiw_core::{Dataset, Rating, UserRatings};
+
+use crate::config;
+use crate::import::LOCAL_PROFILE_PREFIX;
+
+pub mod panel;
+
+const PROFILES_FILE_NAME: &str = "profiles.json";
+const PROFILES_BACKUP_FILE_NAME: &str = "profiles.json.bak";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub ratings: Vec<Rating>,
+    /// Anime ids left out of this profile's recommendations.
+    #[serde(default)]
+    pub blacklist: BTreeSet<u32>,
+    /// Anime ids saved to watch later; rating one takes it off.
+    #[serde(default)]
+    pub watchlist: BTreeSet<u32>,
+}
+
+impl Profile {
+    pub fn user_id(&self) -> String {
+        format!("{LOCAL_PROFILE_PREFIX}{}", self.name)
+    }
+
+    /// Adds `ratings`, replacing earlier ratings of the same anime.
+    pub fn rate(&mut self, ratings: Vec<Rating>) {
+        for rating in ratings {
+            self.ratings
+                .retain(|existing| existing.anime_id != rating.anime_id);
+            self.watchlist.remove(&rating.anime_id);
+            self.ratings.push(rating);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profiles {
+    /// Name of the active profile; `None` keeps imports as separate local users.
+    pub active: Option<String>,
+    pub profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    /// Reads the profiles, starting with none when there is no file. A file that doesn't parse
+    /// is copied to `profiles.json.bak` before the error is returned, so the empty profiles the
+    /// app starts with instead don't overwrite the only copy of the user's ratings on save.
+    pub fn load() -> Result<Self, String> {
+        let Some(content) = config::read_data_file(PROFILES_FILE_NAME) else {
+            return Ok(Self::default());
+        };
+        serde_json::from_slice(&content).map_err(|err| {
+            let kept = config::data_file(PROFILES_BACKUP_FILE_NAME)
+                .is_some_and(|backup| config::write_atomic(&backup, &content).is_ok());
+            if kept {
+                format!(
+                    "Could not read the saved profiles ({err}); they were kept as {PROFILES_BACKUP_FILE_NAME}"
+                )
+            } else {
+                format!("Could not read the saved profiles ({err}) and could not back them up")
+            }
+        })
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let content = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        config::write_data_file(PROFILES_FILE_NAME, &content)
+    }
+
+    pub fn active(&self) -> Option<&Profile> {
+        let name = self.active.as_deref()?;
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    pub fn active_mut(&mut self) -> Option<&mut Profile> {
+        let name = self.active.as_deref()?;
+        self.profiles
+            .iter_mut()
+            .find(|profile| profile.name == name)
+    }
+
+    /// The profile a dataset user id belongs to.
+    pub fn for_user(&self, user_id: &str) -> Option<&Profile> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.user_id() == user_id)
+    }
+
+    pub fn for_user_mut(&mut self, user_id: &str) -> Option<&mut Profile> {
+        self.profiles
+            .iter_mut()
+            .find(|profile| profile.user_id() == user_id)
+    }
+
+    /// Adds an empty profile called `name` and makes it active.
+    pub fn create(&mut self, name: &str) -> Result<(), String> {
+        let name = self.check_name(name)?;
+        self.profiles.push(Profile {
+            name: name.clone(),
+            ..Profile::default()
+        });
+        self.active = Some(name);
+        Ok(())
+    }
+
+    /// Renames the active profile.
+    pub fn rename_active(&mut self, name: &str) -> Result<(), String> {
+        let name = self.check_name(name)?;
+        let profile = self
+            .active_mut()
+            .ok_or_else(|| "no profile is active".to_string())?;
+        profile.name = name.clone();
+        self.active = Some(name);
+        Ok(())
+    }
+
+    /// Deletes the active profile; no profile is active afterwards.
+    pub fn delete_active(&mut self) {
+        if let Some(name) = self.active.take() {
+            self.profiles.retain(|profile| profile.name != name);
+        }
+    }
+
+    fn check_name(&self, name: &str) -> Result<String, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("a profile needs a name".to_string());
+        }
+        if self.profiles.iter().any(|profile| profile.name == name) {
+            return Err(format!("there already is a profile called {name}"));
+        }
+        Ok(name.to_string())
+    }
+
+    /// Puts the active profile into `dataset` as a local user and takes the other profiles
+    /// out, so switching leaves only one profile in the graph.
+    pub fn apply(&self, dataset: &mut Dataset) {
+        let active = self.active().map(Profile::user_id);
+        dataset.users.retain(|user| {
+            Some(&user.user_id) == active.as_ref() || self.for_user(&user.user_id).is_none()
+        });
+        if let Some(profile) = self.active().filter(|profile| !profile.ratings.is_empty()) {
+            dataset.merge_profile(UserRatings {
+                user_id: profile.user_id(),
+                ratings: profile.ratings.clone(),
+            });
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! The profile picker, the Watchlist tab, and what the details card does for a profile: its
+//! watchlist, hidden anime and ratings given from recommendations.
+
+use dioxus::prelude::*;
+use wasiw_core::{Rating, UserRatings};
+
+use super::{Profile, Profiles};
+use crate::import::LOCAL_PROFILE_PREFIX;
+use crate::state::AppState;
+use crate::store_user;
+use crate::sync::dialog::update_lists;
+
+/// Local profiles: switching, creating, renaming and deleting them.
+#[component]
+pub fn ProfilePanel(app: AppState) -> Element {
+    let mut name = use_signal(String::new);
+    let (names, active) = {
+        let profiles = app.profiles.read();
+        let names: Vec<String> = profiles
+            .profiles
+            .iter()
+            .map(|profile| profile.name.clone())
+            .collect();
+        (names, profiles.active.clone())
+    };
+    let mut confirm_delete = use_signal(|| false);
+    let has_name = !name.read().trim().is_empty();
+    rsx! {
+        div { class: "field",
+            span { class: "tiny", "Profile" }
+            select {
+                onchange: move |evt| {
+                    let value = evt.value();
+                    confirm_delete.set(false);
+                    edit_profiles(app, |profiles| {
+                        profiles.active = (!value.is_empty()).then_some(value);
+                        Ok(None)
+                    });
+                },
+                option { value: "", selected: active.is_none(), "None (imports stay separate)" }
+                for profile in names {
+                    option {
+                        value: "{profile}",
+                        selected: active.as_ref() == Some(&profile),
+                        "{profile}"
+                    }
+                }
+            }
+            input {
+                r#type: "text",
+                placeholder: "Profile name",
+                value: "{name}",
+                oninput: move |evt| name.set(evt.value()),
+            }
+            div { class: "row",
+                button {
+                    class: "action",
+                    disabled: !has_name,
+                    onclick: move |_| {
+                        let name = name.take();
+                        edit_profiles(app, |profiles| profiles.create(&name).map(|_| None));
+                    },
+                    "New profile"
+                }
+                if active.is_some() {
+                    button {
+                        class: "action",
+                        disabled: !has_name,
+                        onclick: move |_| {
+                            let name = name.take();
+                            edit_profiles(app, |profiles| {
+                                let old = profiles.active().map(Profile::user_id);
+                                profiles.rename_active(&name).map(|_| old)
+                            });
+                        },
+                        "Rename"
+                    }
+                    if confirm_delete() {
+                        button {
+                            class: "action",
+                            onclick: move |_| {
+                                confirm_delete.set(false);
+                                edit_profiles(app, |profiles| {
+                                    let old = profiles.active().map(Profile::user_id);
+                                    profiles.delete_active();
+                                    Ok(old)
+                                });
+                            },
+                            "Delete it and its ratings"
+                        }
+                    } else {
+                        button { class: "action", onclick: move |_| confirm_delete.set(true), "Delete…" }
+                    }
+                }
+            }
+            if active.is_some() {
+                span { class: "tiny", "Imports and ratings go into the active profile." }
+            }
+        }
+    }
+}
+
+/// The active profile's watchlist, and the anime it hid from its recommendations.
+#[component]
+pub fn Watchlist(app: AppState) -> Element {
+    let (watchlist, hidden) = {
+        let profiles = app.profiles.read();
+        let Some(active) = profiles.active() else {
+            return rsx! {
+                p { class: "tiny", "Pick or create a profile on the Import tab to keep a watchlist." }
+            };
+        };
+        let watchlist: Vec<(u32, String)> = active
+            .watchlist
+            .iter()
+            .map(|&anime_id| (anime_id, app.anime_title(anime_id)))
+            .collect();
+        (watchlist, active.blacklist.len())
+    };
+    let pick = move |anime_id: u32| {
+        let found = app.graph.peek().find_anime(anime_id);
+        if let Some(idx) = found {
+            app.open_node(idx);
+        }
+    };
+    rsx! {
+        div { class: "field",
+            if watchlist.is_empty() {
+                span { class: "tiny", "The watchlist is empty; add recommendations with Watch later." }
+            } else {
+                ul { class: "semantic-results",
+                    for (anime_id, title) in watchlist {
+                        li {
+                            button { class: "link", onclick: move |_| pick(anime_id), "{title}" }
+                        }
+                    }
+                }
+            }
+            if hidden > 0 {
+                div { class: "row",
+                    span { class: "tiny", "{hidden} anime hidden from recommendations" }
+                    button {
+                        class: "action",
+                        onclick: move |_| {
+                            edit_profiles(app, |profiles| {
+                                if let Some(profile) = profiles.active_mut() {
+                                    profile.blacklist.clear();
+                                }
+                                Ok(None)
+                            });
+                        },
+                        "Show again"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Changes the profiles with `edit`, saves them and rebuilds the graph with the active one.
+/// `edit` returns a profile user id to take out of the dataset, after a rename or delete.
+fn edit_profiles(
+    mut app: AppState,
+    edit: impl FnOnce(&mut Profiles) -> Result<Option<String>, String>,
+) {
+    let result = edit(&mut app.profiles.write());
+    match result {
+        Ok(removed) => {
+            if let Some(removed) = removed {
+                app.dataset
+                    .write()
+                    .users
+                    .retain(|user| user.user_id != removed);
+            }
+            let _ = app.profiles.peek().save();
+            app.rebuild_graph();
+            show_active_profile(app);
+            let status = match app.profiles.peek().active() {
+                Some(profile) => format!(
+                    "Recommending for {} ({} ratings).",
+                    profile.name,
+                    profile.ratings.len()
+                ),
+                None => "No profile is active; imports become separate users.".to_string(),
+            };
+            app.import_status.set(Some(status));
+        }
+        Err(err) => app.import_status.set(Some(format!("Profiles: {err}"))),
+    }
+}
+
+/// Selects the active profile's node and opens its recommendations.
+fn show_active_profile(app: AppState) {
+    let user_id = app.profiles.peek().active().map(Profile::user_id);
+    if let Some(idx) = user_id.and_then(|user_id| app.find_user_node(&user_id)) {
+        app.open_details(idx);
+    }
+}
+
+/// The dataset user id of the selected node when it belongs to a local profile.
+fn selected_profile(app: AppState) -> Option<String> {
+    let idx = app.selected.cloned()?;
+    let user_id = app.graph.peek().nodes[idx]
+        .id
+        .strip_prefix("user:")?
+        .to_string();
+    app.profiles
+        .peek()
+        .for_user(&user_id)
+        .is_some()
+        .then_some(user_id)
+}
+
+pub fn toggle_watchlist(mut app: AppState, anime_id: u32) {
+    let Some(user_id) = selected_profile(app) else {
+        return;
+    };
+    let mut profiles = app.profiles.write();
+    if let Some(profile) = profiles.for_user_mut(&user_id) {
+        if !profile.watchlist.remove(&anime_id) {
+            profile.watchlist.insert(anime_id);
+        }
+    }
+    let _ = profiles.save();
+}
+
+pub fn hide_recommendation(mut app: AppState, anime_id: u32) {
+    let Some(user_id) = selected_profile(app) else {
+        return;
+    };
+    {
+        let mut profiles = app.profiles.write();
+        if let Some(profile) = profiles.for_user_mut(&user_id) {
+            profile.blacklist.insert(anime_id);
+            profile.watchlist.remove(&anime_id);
+        }
+        let _ = profiles.save();
+    }
+    let title = app.anime_title(anime_id);
+    app.import_status.set(Some(format!(
+        "{title} won't be recommended to {} again.",
+        user_id.trim_start_matches(LOCAL_PROFILE_PREFIX)
+    )));
+}
+
+/// Adds a rating for a recommendation to the selected local profile, then pushes it to the
+/// connected accounts. The profile stays selected so its new recommendations show.
+pub fn mark_watched(mut app: AppState, anime_id: u32, score: u8) {
+    let Some(user) = app.selected.cloned() else {
+        return;
+    };
+    let node_id = app.graph.peek().nodes[user].id.clone();
+    let title = app.anime_title(anime_id);
+    let rating = Rating {
+        status: Some("completed".to_string()),
+        ..Rating::new(anime_id, title.clone(), f64::from(score))
+    };
+    let updated: UserRatings = {
+        let mut dataset = app.dataset.write();
+        let Some(profile) = dataset
+            .users
+            .iter_mut()
+            .find(|candidate| node_id.strip_prefix("user:") == Some(&candidate.user_id))
+        else {
+            return;
+        };
+        profile.ratings.retain(|rating| rating.anime_id != anime_id);
+        profile.ratings.push(rating.clone());
+        profile.clone()
+    };
+    {
+        let mut profiles = app.profiles.write();
+        if let Some(profile) = profiles.for_user_mut(&updated.user_id) {
+            profile.rate(vec![rating]);
+            let _ = profiles.save();
+        } else {
+            store_user(&updated);
+        }
+    }
+    app.update_user_graph(&updated.user_id);
+    if let Some(idx) = app.find_user_node(&updated.user_id) {
+        app.selected.set(Some(idx));
+        app.details_open.set(true);
+    }
+    update_lists(app, anime_id, score, format!("Rated {title} {score}/10."));
+}
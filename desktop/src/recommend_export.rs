@@ -0,0 +1,321 @@
+//! Writes a recommendation list, with scores, explanations and cached metadata, as CSV, JSON
+//! or a Markdown report that reads well when pasted into a chat or an issue.
+
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde_json::json;
+
+use wasiw_core::compression;
+use wasiw_core::recommend::Recommendation;
+
+use crate::metadata::MetadataCache;
+
+/// Contributors named per direction in the CSV and Markdown explanations.
+const EXPLAINED_CONTRIBUTORS: usize = 3;
+/// Synopses in the Markdown report are cut to about this many characters.
+const SYNOPSIS_CHARS: usize = 280;
+
+const CSV_HEADERS: [&str; 11] = [
+    "rank",
+    "animeId",
+    "title",
+    "score",
+    "supportCount",
+    "strongest",
+    "explanation",
+    "year",
+    "episodes",
+    "genres",
+    "posterUrl",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecommendationFormat {
+    Csv,
+    Json,
+    Markdown,
+}
+
+impl RecommendationFormat {
+    pub const ALL: [RecommendationFormat; 3] = [Self::Markdown, Self::Csv, Self::Json];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Csv => "CSV",
+            Self::Json => "JSON",
+            Self::Markdown => "Markdown report",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Markdown => "md",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = compression::inner_extension(path)?;
+        match extension.as_str() {
+            "markdown" => Some(Self::Markdown),
+            other => Self::ALL
+                .into_iter()
+                .find(|format| format.extension() == other),
+        }
+    }
+}
+
+/// Writes `recommendations` for the user labelled `user`.
+pub fn write(
+    writer: &mut dyn Write,
+    user: &str,
+    recommendations: &[Recommendation],
+    metadata: &MetadataCache,
+    format: RecommendationFormat,
+) -> io::Result<()> {
+    match format {
+        RecommendationFormat::Csv => write_csv(writer, recommendations, metadata),
+        RecommendationFormat::Json => write_json(writer, user, recommendations, metadata),
+        RecommendationFormat::Markdown => write_markdown(writer, user, recommendations, metadata),
+    }
+}
+
+fn write_csv(
+    writer: &mut dyn Write,
+    recommendations: &[Recommendation],
+    metadata: &MetadataCache,
+) -> io::Result<()> {
+    let mut csv = ::csv::Writer::from_writer(writer);
+    csv.write_record(CSV_HEADERS)?;
+    for (rank, recommendation) in recommendations.iter().enumerate() {
+        let meta = metadata.get(recommendation.anime_id);
+        let optional = |value: Option<u32>| value.map(|v| v.to_string()).unwrap_or_default();
+        csv.write_record([
+            &(rank + 1).to_string(),
+            &recommendation.anime_id.to_string(),
+            &recommendation.title,
+            &recommendation.score.to_string(),
+            &recommendation.support_count.to_string(),
+            &recommendation.strongest.to_string(),
+            &recommendation.explanation(EXPLAINED_CONTRIBUTORS),
+            &optional(meta.and_then(|m| m.year)),
+            &optional(meta.and_then(|m| m.episodes)),
+            &meta.map(|m| m.genres.join("|")).unwrap_or_default(),
+            meta.and_then(|m| m.poster_url.as_deref()).unwrap_or(""),
+        ])?;
+    }
+    csv.flush()
+}
+
+fn write_json(
+    writer: &mut dyn Write,
+    user: &str,
+    recommendations: &[Recommendation],
+    metadata: &MetadataCache,
+) -> io::Result<()> {
+    let items: Vec<serde_json::Value> = recommendations
+        .iter()
+        .enumerate()
+        .map(|(rank, recommendation)| {
+            let contributions: Vec<serde_json::Value> = recommendation
+                .contributions
+                .iter()
+                .map(|c| {
+                    json!({
+                        "title": c.title,
+                        "edgeWeight": c.edge_weight,
+                        "weightFactor": c.weight_factor,
+                        "weightedScore": c.weighted_score,
+                    })
+                })
+                .collect();
+            json!({
+                "rank": rank + 1,
+                "animeId": recommendation.anime_id,
+                "title": recommendation.title,
+                "score": recommendation.score,
+                "supportCount": recommendation.support_count,
+                "strongest": recommendation.strongest,
+                "contributions": contributions,
+                "metadata": metadata.get(recommendation.anime_id),
+            })
+        })
+        .collect();
+    let report = json!({ "user": user, "recommendations": items });
+    serde_json::to_writer_pretty(&mut *writer, &report).map_err(io::Error::other)?;
+    writeln!(writer)
+}
+
+fn write_markdown(
+    w: &mut dyn Write,
+    user: &str,
+    recommendations: &[Recommendation],
+    metadata: &MetadataCache,
+) -> io::Result<()> {
+    writeln!(w, "# Anime recommendations for {}", markdown_text(user))?;
+    writeln!(w)?;
+    writeln!(
+        w,
+        "Ranked by how strongly the anime {} rated point to each show through the rating graph.",
+        markdown_text(user)
+    )?;
+    writeln!(w)?;
+    for (rank, recommendation) in recommendations.iter().enumerate() {
+        writeln!(
+            w,
+            "{}. **{}** — score {:+.2}",
+            rank + 1,
+            markdown_text(&recommendation.title),
+            recommendation.score
+        )?;
+        let meta = metadata.get(recommendation.anime_id);
+        if let Some(line) = meta.map(|m| m.summary()).filter(|line| !line.is_empty()) {
+            writeln!(w, "   - {}", markdown_text(&line))?;
+        }
+        writeln!(
+            w,
+            "   - {}",
+            markdown_text(&recommendation.explanation(EXPLAINED_CONTRIBUTORS))
+        )?;
+        if let Some(synopsis) = meta.and_then(|m| m.synopsis.as_deref()) {
+            writeln!(w, "   - {}", markdown_text(&shorten(synopsis)))?;
+        }
+        writeln!(
+            w,
+            "   - [MyAnimeList](https://myanimelist.net/anime/{})",
+            recommendation.anime_id
+        )?;
+    }
+    Ok(())
+}
+
+fn shorten(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= SYNOPSIS_CHARS {
+        return text;
+    }
+    let cut: String = text.chars().take(SYNOPSIS_CHARS).collect();
+    let cut = cut.rsplit_once(' ').map_or(cut.as_str(), |(head, _)| head);
+    format!("{cut}…")
+}
+
+/// Escapes characters Markdown would read as formatting.
+fn markdown_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#' | '|'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::AnimeMetadata;
+    use wasiw_core::recommend::Contribution;
+
+    fn recommendation(anime_id: u32, title: &str, score: f64) -> Recommendation {
+        Recommendation {
+            anime_id,
+            node: 0,
+            title: title.to_string(),
+            score,
+            strongest: score,
+            support_count: 1,
+            contributions: vec![Contribution {
+                title: "Monster".to_string(),
+                edge_weight: 0.8,
+                weight_factor: 1.5,
+                weighted_score: score,
+            }],
+        }
+    }
+
+    fn export(format: RecommendationFormat) -> String {
+        let mut metadata = MetadataCache::default();
+        metadata.extend([(
+            1535,
+            AnimeMetadata {
+                genres: vec!["Mystery".to_string(), "Thriller".to_string()],
+                episodes: Some(37),
+                year: Some(2006),
+                synopsis: Some("A notebook. ".repeat(40)),
+                ..AnimeMetadata::default()
+            },
+        )]);
+        let recommendations = [
+            recommendation(1535, "Death Note", 1.2),
+            recommendation(31240, "Re:Zero *Starting* | Life", 0.4),
+        ];
+        let mut out = Vec::new();
+        write(&mut out, "some_user", &recommendations, &metadata, format).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn csv_and_json_list_every_recommendation_with_its_metadata() {
+        let csv = export(RecommendationFormat::Csv);
+        let mut reader = ::csv::Reader::from_reader(csv.as_bytes());
+        assert_eq!(reader.headers().unwrap(), CSV_HEADERS.as_slice());
+        let rows: Vec<::csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(&rows[0][0], "1");
+        assert_eq!(&rows[0][2], "Death Note");
+        assert_eq!(&rows[0][7], "2006");
+        assert_eq!(&rows[0][9], "Mystery|Thriller");
+        assert!(rows[0][6].contains("Monster"), "{}", &rows[0][6]);
+        assert_eq!(&rows[1][2], "Re:Zero *Starting* | Life");
+        assert_eq!(&rows[1][7], "");
+
+        let json: serde_json::Value =
+            serde_json::from_str(&export(RecommendationFormat::Json)).unwrap();
+        assert_eq!(json["user"], "some_user");
+        let listed = json["recommendations"].as_array().unwrap();
+        assert_eq!(listed[1]["rank"], 2);
+        assert_eq!(listed[0]["metadata"]["episodes"], 37);
+        assert!(listed[1]["metadata"].is_null());
+        assert_eq!(listed[0]["contributions"][0]["title"], "Monster");
+    }
+
+    #[test]
+    fn markdown_escapes_titles_and_shortens_synopses() {
+        let markdown = export(RecommendationFormat::Markdown);
+        assert!(markdown.starts_with("# Anime recommendations for some\\_user\n"));
+        assert!(markdown.contains("1. **Death Note** — score +1.20\n"));
+        assert!(markdown.contains("   - 2006 · 37 episodes · Mystery, Thriller\n"));
+        assert!(markdown.contains("2. **Re:Zero \\*Starting\\* \\| Life** — score +0.40\n"));
+        assert!(markdown.contains("(https://myanimelist.net/anime/31240)"));
+        let synopsis = markdown
+            .lines()
+            .find(|line| line.contains("notebook"))
+            .unwrap();
+        // Cut between words, not inside one.
+        let kept = synopsis
+            .trim_start_matches("   - ")
+            .strip_suffix('…')
+            .unwrap();
+        assert!(kept.chars().count() <= SYNOPSIS_CHARS);
+        assert!("A notebook. ".repeat(40).starts_with(&format!("{kept} ")));
+    }
+
+    #[test]
+    fn formats_follow_the_file_extension() {
+        let format = |name: &str| RecommendationFormat::from_path(Path::new(name));
+        assert_eq!(format("picks.md"), Some(RecommendationFormat::Markdown));
+        assert_eq!(
+            format("picks.markdown"),
+            Some(RecommendationFormat::Markdown)
+        );
+        assert_eq!(format("picks.csv.gz"), Some(RecommendationFormat::Csv));
+        assert_eq!(format("picks.json"), Some(RecommendationFormat::Json));
+        assert_eq!(format("picks.txt"), None);
+    }
+}
@@ -0,0 +1,387 @@
+//! A standalone HTML report for one user — taste profile, a snapshot of their corner of the
+//! graph and their top recommendations — to send to someone who doesn't have the app. Styles,
+//! the SVG snapshot and cached posters are embedded, so the file opens anywhere offline.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+use quick_xml::escape::escape;
+
+use wasiw_core::recommend::Recommendation;
+use wasiw_core::{Edge, EdgeKind, GraphModel, UserRatings};
+use wasiw_ui::theme::Palette;
+
+use crate::metadata::MetadataCache;
+use crate::posters;
+
+/// Recommendations listed in the report.
+const REPORT_RECOMMENDATIONS: usize = 15;
+const FAVOURITES: usize = 5;
+const TOP_GENRES: usize = 6;
+/// Similarity edges drawn in the snapshot, strongest first.
+const SNAPSHOT_EDGES: usize = 400;
+const SNAPSHOT_PADDING: f32 = 24.0;
+
+const REPORT_CSS: &str = r#"
+  body { margin: 0; padding: 32px 16px; font-family: system-ui, sans-serif; background: #f5f6f8; color: #1d2330; }
+  main { max-width: 760px; margin: 0 auto; }
+  h1 { margin: 0 0 4px; font-size: 26px; }
+  h2 { margin: 28px 0 10px; font-size: 18px; }
+  .muted { color: #5d6677; font-size: 13px; }
+  .stats { display: flex; gap: 24px; flex-wrap: wrap; }
+  .stats div { display: flex; flex-direction: column; }
+  .stats strong { font-size: 20px; }
+  .genres span { display: inline-block; margin: 0 6px 6px 0; padding: 3px 10px; border-radius: 999px; background: #e3e7ef; font-size: 13px; }
+  svg { width: 100%; height: auto; background: #ffffff; border: 1px solid #d8dce5; border-radius: 12px; }
+  ol { padding-left: 24px; }
+  li { margin-bottom: 14px; }
+  li img { float: left; width: 48px; height: 68px; margin: 0 12px 6px 0; border-radius: 4px; object-fit: cover; }
+  li p { margin: 2px 0; }
+  li::after { content: ""; display: block; clear: both; }
+"#;
+
+/// What a user's ratings say about them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TasteProfile {
+    pub rated: usize,
+    pub mean_score: f64,
+    /// Highest-rated anime with their scores.
+    pub favourites: Vec<(String, f64)>,
+    /// Genres of the anime the user rated above their mean, most frequent first.
+    pub genres: Vec<(String, usize)>,
+}
+
+impl TasteProfile {
+    /// Genres come from the ratings themselves, or from the metadata cache when the source
+    /// didn't provide them.
+    pub fn new(user: &UserRatings, metadata: &MetadataCache) -> Self {
+        let rated = user.ratings.len();
+        let mean_score = if rated == 0 {
+            0.0
+        } else {
+            user.ratings.iter().map(|r| r.raw_score).sum::<f64>() / rated as f64
+        };
+
+        let mut favourites: Vec<(String, f64)> = user
+            .ratings
+            .iter()
+            .map(|rating| (rating.title.clone(), rating.raw_score))
+            .collect();
+        favourites.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        favourites.truncate(FAVOURITES);
+
+        let mut genre_counts: HashMap<&str, usize> = HashMap::new();
+        for rating in user.ratings.iter().filter(|r| r.raw_score > mean_score) {
+            let genres = if rating.genres.is_empty() {
+                metadata
+                    .get(rating.anime_id)
+                    .map_or(&[][..], |m| &m.genres[..])
+            } else {
+                &rating.genres[..]
+            };
+            for genre in genres {
+                *genre_counts.entry(genre.as_str()).or_default() += 1;
+            }
+        }
+        let mut genres: Vec<(String, usize)> = genre_counts
+            .into_iter()
+            .map(|(genre, count)| (genre.to_string(), count))
+            .collect();
+        genres.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        genres.truncate(TOP_GENRES);
+
+        Self {
+            rated,
+            mean_score,
+            favourites,
+            genres,
+        }
+    }
+}
+
+/// Everything the report shows.
+pub struct Report<'a> {
+    pub graph: &'a GraphModel,
+    /// The user's node in `graph`.
+    pub user: usize,
+    pub profile: &'a TasteProfile,
+    pub recommendations: &'a [Recommendation],
+    pub metadata: &'a MetadataCache,
+    pub palette: &'a Palette,
+}
+
+pub fn write(w: &mut dyn Write, report: &Report) -> io::Result<()> {
+    let label = escape(report.graph.nodes[report.user].label.as_str()).into_owned();
+    writeln!(w, "<!DOCTYPE html>")?;
+    writeln!(w, r#"<html lang="en">"#)?;
+    writeln!(w, "<head>")?;
+    writeln!(w, r#"<meta charset="utf-8">"#)?;
+    writeln!(
+        w,
+        r#"<meta name="viewport" content="width=device-width, initial-scale=1">"#
+    )?;
+    writeln!(w, "<title>Anime taste of {label}</title>")?;
+    writeln!(w, "<style>{REPORT_CSS}</style>")?;
+    writeln!(w, "</head>")?;
+    writeln!(w, "<body>")?;
+    writeln!(w, "<main>")?;
+    writeln!(w, "<h1>Anime taste of {label}</h1>")?;
+    writeln!(
+        w,
+        r#"<p class="muted">Made with What Anime Should I Watch from anonymized user ratings.</p>"#
+    )?;
+    write_profile(w, report.profile)?;
+    writeln!(w, "<h2>Where they sit in the graph</h2>")?;
+    writeln!(
+        w,
+        r#"<p class="muted">The anime they rated, linked to them, and the recommended anime (ringed) linked to the rated anime that point to them.</p>"#
+    )?;
+    write_snapshot(w, report)?;
+    write_recommendations(w, report)?;
+    writeln!(w, "</main>")?;
+    writeln!(w, "</body>")?;
+    writeln!(w, "</html>")
+}
+
+fn write_profile(w: &mut dyn Write, profile: &TasteProfile) -> io::Result<()> {
+    writeln!(w, "<h2>Taste profile</h2>")?;
+    writeln!(w, r#"<div class="stats">"#)?;
+    writeln!(
+        w,
+        r#"<div><strong>{}</strong><span class="muted">anime rated</span></div>"#,
+        profile.rated
+    )?;
+    writeln!(
+        w,
+        r#"<div><strong>{:.1}</strong><span class="muted">mean score out of 10</span></div>"#,
+        profile.mean_score
+    )?;
+    writeln!(w, "</div>")?;
+    if !profile.genres.is_empty() {
+        writeln!(
+            w,
+            r#"<p class="muted">Genres they rate above their mean</p>"#
+        )?;
+        writeln!(w, r#"<div class="genres">"#)?;
+        for (genre, count) in &profile.genres {
+            writeln!(w, "<span>{} · {count}</span>", escape(genre.as_str()))?;
+        }
+        writeln!(w, "</div>")?;
+    }
+    if !profile.favourites.is_empty() {
+        writeln!(w, r#"<p class="muted">Favourites</p>"#)?;
+        writeln!(w, "<ul>")?;
+        for (title, score) in &profile.favourites {
+            writeln!(w, "<li>{} — {score:.1}</li>", escape(title.as_str()))?;
+        }
+        writeln!(w, "</ul>")?;
+    }
+    Ok(())
+}
+
+/// The user, the anime they rated and the recommended anime at their current layout
+/// positions, cropped to those nodes.
+fn write_snapshot(w: &mut dyn Write, report: &Report) -> io::Result<()> {
+    let graph = report.graph;
+    let recommended: HashSet<usize> = report
+        .recommendations
+        .iter()
+        .take(REPORT_RECOMMENDATIONS)
+        .map(|r| r.node)
+        .collect();
+    let mut shown: HashSet<usize> = recommended.clone();
+    shown.insert(report.user);
+    let mut edges: Vec<&Edge> = Vec::new();
+    for edge in &graph.edges {
+        if edge.kind == EdgeKind::Rating && edge.source == report.user {
+            shown.insert(edge.target);
+            edges.push(edge);
+        }
+    }
+    let mut similarities: Vec<&Edge> = graph
+        .edges
+        .iter()
+        .filter(|edge| {
+            edge.kind == EdgeKind::Similarity
+                && edge.weight > 0.0
+                && (recommended.contains(&edge.source) != recommended.contains(&edge.target))
+                && shown.contains(&edge.source)
+                && shown.contains(&edge.target)
+        })
+        .collect();
+    similarities.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+    similarities.truncate(SNAPSHOT_EDGES);
+    edges.extend(similarities);
+
+    let (mut min_x, mut min_y) = (f32::MAX, f32::MAX);
+    let (mut max_x, mut max_y) = (f32::MIN, f32::MIN);
+    for node in shown.iter().map(|&idx| &graph.nodes[idx]) {
+        min_x = min_x.min(node.x - node.radius);
+        min_y = min_y.min(node.y - node.radius);
+        max_x = max_x.max(node.x + node.radius);
+        max_y = max_y.max(node.y + node.radius);
+    }
+    let width = (max_x - min_x) + SNAPSHOT_PADDING * 2.0;
+    let height = (max_y - min_y) + SNAPSHOT_PADDING * 2.0;
+    // Strokes and rings scale with the crop so they read the same at any size.
+    let unit = width.max(height) / 600.0;
+    writeln!(
+        w,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {width} {height}" role="img">"#,
+        min_x - SNAPSHOT_PADDING,
+        min_y - SNAPSHOT_PADDING
+    )?;
+    for edge in edges {
+        let (source, target) = (&graph.nodes[edge.source], &graph.nodes[edge.target]);
+        writeln!(
+            w,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" stroke-opacity="0.5"/>"#,
+            source.x,
+            source.y,
+            target.x,
+            target.y,
+            escape(report.palette.edge_color(edge.kind)),
+            edge.stroke_width * unit
+        )?;
+    }
+    let mut nodes: Vec<usize> = shown.into_iter().collect();
+    nodes.sort_unstable();
+    for idx in nodes {
+        let node = &graph.nodes[idx];
+        let ring = if recommended.contains(&idx) || idx == report.user {
+            format!(r##" stroke="#1d2330" stroke-width="{}""##, 1.5 * unit)
+        } else {
+            String::new()
+        };
+        writeln!(
+            w,
+            r#"<circle cx="{}" cy="{}" r="{}" fill="{}"{ring}><title>{}</title></circle>"#,
+            node.x,
+            node.y,
+            node.radius * unit.max(1.0),
+            escape(report.palette.node_color(node.node_type)),
+            escape(node.label.as_str())
+        )?;
+    }
+    writeln!(w, "</svg>")
+}
+
+fn write_recommendations(w: &mut dyn Write, report: &Report) -> io::Result<()> {
+    if report.recommendations.is_empty() {
+        return Ok(());
+    }
+    writeln!(w, "<h2>What they should watch next</h2>")?;
+    writeln!(w, "<ol>")?;
+    for recommendation in report.recommendations.iter().take(REPORT_RECOMMENDATIONS) {
+        writeln!(w, "<li>")?;
+        // Only posters already cached are embedded; the report never waits on downloads.
+        if let Some(uri) =
+            posters::cached(recommendation.anime_id).and_then(|bytes| posters::data_uri(&bytes))
+        {
+            writeln!(w, r#"<img src="{uri}" alt="">"#)?;
+        }
+        writeln!(
+            w,
+            r#"<p><a href="https://myanimelist.net/anime/{}"><strong>{}</strong></a> <span class="muted">score {:+.2}</span></p>"#,
+            recommendation.anime_id,
+            escape(recommendation.title.as_str()),
+            recommendation.score
+        )?;
+        let meta = report.metadata.get(recommendation.anime_id);
+        if let Some(line) = meta.map(|m| m.summary()).filter(|line| !line.is_empty()) {
+            writeln!(w, r#"<p class="muted">{}</p>"#, escape(line.as_str()))?;
+        }
+        writeln!(
+            w,
+            r#"<p class="muted">{}</p>"#,
+            escape(recommendation.explanation(3).as_str())
+        )?;
+        writeln!(w, "</li>")?;
+    }
+    writeln!(w, "</ol>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::AnimeMetadata;
+    use wasiw_core::recommend::recommend;
+    use wasiw_core::{build_graph, Dataset, Rating, Viewport};
+
+    fn user(user_id: &str, scores: &[(u32, f64)]) -> UserRatings {
+        UserRatings {
+            user_id: user_id.to_string(),
+            ratings: scores
+                .iter()
+                .map(|&(anime_id, score)| Rating::new(anime_id, format!("Anime {anime_id}"), score))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn profiles_count_genres_only_of_anime_rated_above_the_mean() {
+        let mut rated = user("a", &[(1, 9.0), (2, 8.0), (3, 4.0)]);
+        rated.ratings[0].genres = vec!["Drama".to_string()];
+        rated.ratings[2].genres = vec!["Comedy".to_string()];
+        let mut metadata = MetadataCache::default();
+        metadata.extend([(
+            2,
+            AnimeMetadata {
+                genres: vec!["Drama".to_string(), "Mystery".to_string()],
+                ..AnimeMetadata::default()
+            },
+        )]);
+        let profile = TasteProfile::new(&rated, &metadata);
+        assert_eq!(profile.rated, 3);
+        assert!((profile.mean_score - 7.0).abs() < 1e-9);
+        assert_eq!(profile.favourites[0], ("Anime 1".to_string(), 9.0));
+        assert_eq!(
+            profile.genres,
+            [("Drama".to_string(), 2), ("Mystery".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn reports_escape_what_users_and_sources_wrote() {
+        let users = vec![
+            user("a", &[(1, 9.0), (2, 4.0)]),
+            user("b", &[(1, 9.0), (2, 4.0), (3, 9.0), (4, 3.0)]),
+            user("c", &[(1, 8.0), (2, 3.0), (3, 10.0), (4, 4.0)]),
+        ];
+        let rated = users[0].clone();
+        let mut graph = build_graph(Dataset::new(users), Viewport::default());
+        let node = graph.find_user("a").unwrap();
+        graph.nodes[node].label = "<b>Tom & Jerry</b>".to_string();
+        let title = graph
+            .nodes
+            .iter()
+            .position(|n| n.label == "Anime 3")
+            .unwrap();
+        graph.nodes[title].label = "Anime <3".to_string();
+        let mut recommendations = recommend(&graph, node, 10);
+        assert!(!recommendations.is_empty());
+        recommendations[0].title = "Anime <3".to_string();
+        let metadata = MetadataCache::default();
+        let profile = TasteProfile::new(&rated, &metadata);
+
+        let mut out = Vec::new();
+        let report = Report {
+            graph: &graph,
+            user: node,
+            profile: &profile,
+            recommendations: &recommendations,
+            metadata: &metadata,
+            palette: &wasiw_ui::theme::default_palette(),
+        };
+        write(&mut out, &report).unwrap();
+        let html = String::from_utf8(out).unwrap();
+        assert!(html.contains("<title>Anime taste of &lt;b&gt;Tom &amp; Jerry&lt;/b&gt;</title>"));
+        assert!(!html.contains("<b>"));
+        assert!(!html.contains("Anime <3"));
+        assert!(html.contains("<strong>Anime &lt;3</strong>"));
+        assert!(html.contains("<title>Anime &lt;3</title></circle>"));
+        assert!(html.contains("https://myanimelist.net/anime/3"));
+        assert_eq!(html.matches("<svg").count(), 1);
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+}
@@ -0,0 +1,71 @@
+//! The side panel's tabs, one route each, and the analytics pages of an anime, a user, and two
+//! of either side by side. [`App`] is the layout around them: the panel with the tab bar and the
+//! open tab's page, and the graph canvas beside it, which every tab shares.
+
+use dioxus::prelude::*;
+use wasiw_ui::i18n::tr;
+
+use crate::pages::{
+    AnimePage, ComparePage, CompareUsersPage, GraphPage, ImportPage, RecommendationsPage,
+    SettingsPage, StatsPage, TribesPage, UserPage, WatchlistPage,
+};
+use crate::App;
+
+#[derive(Debug, Clone, PartialEq, Routable)]
+#[rustfmt::skip]
+pub enum Route {
+    #[layout(App)]
+        #[route("/", GraphPage)]
+        Graph {},
+        #[route("/recommendations", RecommendationsPage)]
+        Recommendations {},
+        #[route("/stats", StatsPage)]
+        Stats {},
+        #[route("/tribes", TribesPage)]
+        Tribes {},
+        #[route("/watchlist", WatchlistPage)]
+        Watchlist {},
+        #[route("/import", ImportPage)]
+        Import {},
+        #[route("/settings", SettingsPage)]
+        Settings {},
+        // Not tabs: opened by opening a node from the graph or a list.
+        #[route("/anime/:anime_id", AnimePage)]
+        Anime { anime_id: u32 },
+        #[route("/user/:user_id", UserPage)]
+        User { user_id: String },
+        #[route("/compare/:left/:right", ComparePage)]
+        Compare { left: u32, right: u32 },
+        #[route("/compare-users/:left/:right", CompareUsersPage)]
+        CompareUsers { left: String, right: String },
+}
+
+impl Route {
+    /// The tabs in the order the tab bar shows them.
+    pub fn tabs() -> [Self; 7] {
+        [
+            Self::Graph {},
+            Self::Recommendations {},
+            Self::Stats {},
+            Self::Tribes {},
+            Self::Watchlist {},
+            Self::Import {},
+            Self::Settings {},
+        ]
+    }
+
+    pub fn label(&self) -> String {
+        tr(match self {
+            Self::Graph {} => "tab-graph",
+            Self::Recommendations {} => "tab-recommendations",
+            Self::Stats {} => "tab-stats",
+            Self::Tribes {} => "tab-tribes",
+            Self::Watchlist {} => "tab-watchlist",
+            Self::Import {} => "tab-import",
+            Self::Settings {} => "tab-settings",
+            Self::Anime { .. } => "tab-anime",
+            Self::User { .. } => "tab-user",
+            Self::Compare { .. } | Self::CompareUsers { .. } => "tab-compare",
+        })
+    }
+}
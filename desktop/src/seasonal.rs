@@ -0,0 +1,203 @@
+//! The anime airing this season, from Jikan's `seasons/now`, for scoring new shows that nobody
+//! in the dataset has rated yet against a user's taste.
+
+use std::collections::HashSet;
+
+use reqwest::Client;
+use serde::Deserialize;
+use wasiw_core::airing::Broadcast;
+use wasiw_core::content::Candidate;
+
+use crate::import::http::get_json;
+use crate::import::{jikan, ImportError};
+use crate::metadata::{AnimeMetadata, JikanAnime};
+
+/// A season has a few hundred entries at most, 25 to a page.
+const MAX_PAGES: usize = 20;
+
+#[derive(Deserialize)]
+struct SeasonPage {
+    data: Vec<SeasonEntry>,
+    pagination: Option<Pagination>,
+}
+
+#[derive(Deserialize)]
+struct Pagination {
+    has_next_page: bool,
+}
+
+#[derive(Deserialize)]
+struct SeasonEntry {
+    season: Option<String>,
+    #[serde(default)]
+    airing: bool,
+    broadcast: Option<JikanBroadcast>,
+    #[serde(flatten)]
+    anime: JikanAnime,
+}
+
+#[derive(Deserialize)]
+struct JikanBroadcast {
+    day: Option<String>,
+    time: Option<String>,
+    timezone: Option<String>,
+}
+
+impl JikanBroadcast {
+    /// The weekly slot; Jikan gives it in Japan's time zone, and [`Broadcast`] assumes so.
+    fn slot(&self) -> Option<Broadcast> {
+        if self
+            .timezone
+            .as_deref()
+            .is_some_and(|zone| zone != "Asia/Tokyo")
+        {
+            return None;
+        }
+        Broadcast::parse(self.day.as_deref()?, self.time.as_deref()?)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Season {
+    /// e.g. `Fall 2026`, or `This season` when Jikan doesn't say.
+    pub name: String,
+    pub anime: Vec<SeasonalAnime>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeasonalAnime {
+    pub anime_id: u32,
+    pub title: String,
+    pub metadata: AnimeMetadata,
+    /// When new episodes air, for shows still airing.
+    pub broadcast: Option<Broadcast>,
+}
+
+impl Season {
+    pub fn candidates(&self) -> Vec<Candidate> {
+        self.anime
+            .iter()
+            .map(|anime| Candidate {
+                anime_id: anime.anime_id,
+                title: anime.title.clone(),
+                features: anime.metadata.content_features(),
+            })
+            .collect()
+    }
+}
+
+/// Fetches every anime airing this season. `progress` receives short status lines.
+pub async fn fetch_current(
+    client: &Client,
+    mut progress: impl FnMut(String),
+) -> Result<Season, ImportError> {
+    let mut listing = Listing::default();
+    for page in 1..=MAX_PAGES {
+        progress(format!("Fetching this season's anime, page {page}…"));
+        let url = format!("https://api.jikan.moe/v4/seasons/now?page={page}");
+        let body: SeasonPage = get_json(client, &jikan::LIMITER, &url).await?;
+        if !listing.add_page(body) {
+            break;
+        }
+    }
+    Ok(listing.into_season())
+}
+
+/// The season's pages as they arrive.
+#[derive(Default)]
+struct Listing {
+    name: Option<String>,
+    seen: HashSet<u32>,
+    anime: Vec<SeasonalAnime>,
+}
+
+impl Listing {
+    /// Adds a page's anime, returning whether there is another page.
+    fn add_page(&mut self, page: SeasonPage) -> bool {
+        for entry in page.data {
+            if self.name.is_none() {
+                self.name = entry
+                    .season
+                    .as_deref()
+                    .zip(entry.anime.year)
+                    .map(|(season, year)| {
+                        let mut season = season.to_string();
+                        if let Some(first) = season.get_mut(..1) {
+                            first.make_ascii_uppercase();
+                        }
+                        format!("{season} {year}")
+                    });
+            }
+            // Jikan repeats entries across page boundaries.
+            if self.seen.insert(entry.anime.mal_id) {
+                let broadcast = entry
+                    .broadcast
+                    .as_ref()
+                    .filter(|_| entry.airing)
+                    .and_then(JikanBroadcast::slot);
+                self.anime.push(SeasonalAnime {
+                    anime_id: entry.anime.mal_id,
+                    title: entry.anime.title.clone(),
+                    metadata: entry.anime.into_metadata(),
+                    broadcast,
+                });
+            }
+        }
+        page.pagination.is_some_and(|p| p.has_next_page)
+    }
+
+    fn into_season(self) -> Season {
+        Season {
+            name: self.name.unwrap_or_else(|| "This season".to_string()),
+            anime: self.anime,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(json: &str) -> SeasonPage {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn pages_make_one_named_season_with_weekly_slots() {
+        let mut listing = Listing::default();
+        assert!(listing.add_page(page(
+            r#"{ "pagination": { "has_next_page": true }, "data": [
+                { "mal_id": 1, "title": "Frieren", "season": "fall", "year": 2026, "airing": true,
+                  "genres": [{ "name": "Fantasy" }],
+                  "broadcast": { "day": "Fridays", "time": "23:00", "timezone": "Asia/Tokyo" } },
+                { "mal_id": 2, "title": "Elsewhere", "airing": true,
+                  "broadcast": { "day": "Mondays", "time": "12:00", "timezone": "America/New_York" } }
+            ] }"#
+        )));
+        assert!(!listing.add_page(page(
+            r#"{ "pagination": { "has_next_page": false }, "data": [
+                { "mal_id": 2, "title": "Elsewhere", "airing": true },
+                { "mal_id": 3, "title": "Finished", "airing": false,
+                  "broadcast": { "day": "Sundays", "time": "09:30", "timezone": "Asia/Tokyo" } }
+            ] }"#
+        )));
+        let season = listing.into_season();
+        assert_eq!(season.name, "Fall 2026");
+        let ids: Vec<u32> = season.anime.iter().map(|anime| anime.anime_id).collect();
+        assert_eq!(ids, [1, 2, 3]);
+        assert_eq!(
+            season.anime[0].broadcast,
+            Broadcast::parse("Friday", "23:00")
+        );
+        assert!(season.anime[0].broadcast.is_some());
+        // Only slots in Japan's time zone are understood, and only for shows still airing.
+        assert_eq!(season.anime[1].broadcast, None);
+        assert_eq!(season.anime[2].broadcast, None);
+
+        let candidates = season.candidates();
+        assert_eq!(candidates[0].title, "Frieren");
+        assert_eq!(candidates[0].features.genres, ["Fantasy"]);
+
+        assert_eq!(Listing::default().into_season().name, "This season");
+    }
+}
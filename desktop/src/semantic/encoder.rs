@@ -0,0 +1,140 @@
+//! A sentence encoder run with tract: a MiniLM-style ONNX model and its `tokenizer.json`,
+//! mean-pooled over the tokens and normalized so dot products are cosine similarities.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tokenizers::Tokenizer;
+use tract_onnx::prelude::*;
+
+/// Tokens each text is cut or padded to. Synopses past this are mostly plot detail.
+const MAX_TOKENS: usize = 128;
+const MODEL_FILE_NAME: &str = "model.onnx";
+const TOKENIZER_FILE_NAME: &str = "tokenizer.json";
+
+#[derive(Debug)]
+pub struct EncoderError(String);
+
+impl fmt::Display for EncoderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for EncoderError {}
+
+impl EncoderError {
+    pub fn new(message: &str) -> Self {
+        Self(message.to_string())
+    }
+}
+
+fn error(context: &str, err: impl fmt::Display) -> EncoderError {
+    EncoderError(format!("{context}: {err}"))
+}
+
+pub struct Encoder {
+    model: TypedRunnableModel<TypedModel>,
+    tokenizer: Tokenizer,
+    /// Whether the model takes `token_type_ids` as its third input.
+    token_types: bool,
+    /// Identifies the model, so embeddings from another one aren't mixed in.
+    pub fingerprint: u64,
+}
+
+impl Encoder {
+    /// Loads `model.onnx` and `tokenizer.json` from `dir`.
+    pub fn load(dir: &Path) -> Result<Self, EncoderError> {
+        let model_path = dir.join(MODEL_FILE_NAME);
+        let fingerprint = fs::metadata(&model_path)
+            .map_err(|err| error(&model_path.display().to_string(), err))?
+            .len();
+        let tokenizer = Tokenizer::from_file(dir.join(TOKENIZER_FILE_NAME))
+            .map_err(|err| error(TOKENIZER_FILE_NAME, err))?;
+        let mut model = tract_onnx::onnx()
+            .model_for_path(&model_path)
+            .map_err(|err| error(MODEL_FILE_NAME, err))?;
+        let inputs = model.input_outlets().map_or(0, |outlets| outlets.len());
+        for input in 0..inputs {
+            model
+                .set_input_fact(input, i64::fact([1, MAX_TOKENS]).into())
+                .map_err(|err| error(MODEL_FILE_NAME, err))?;
+        }
+        let model = model
+            .into_optimized()
+            .and_then(|model| model.into_runnable())
+            .map_err(|err| error(MODEL_FILE_NAME, err))?;
+        Ok(Self {
+            model,
+            tokenizer,
+            token_types: inputs >= 3,
+            fingerprint,
+        })
+    }
+
+    /// The unit-length embedding of `text`.
+    pub fn embed(&self, text: &str) -> Result<Vec<f32>, EncoderError> {
+        let encoding = self
+            .tokenizer
+            .encode(text, true)
+            .map_err(|err| error("tokenizer", err))?;
+        let padded = |values: &[u32]| {
+            let mut padded: Vec<i64> = values
+                .iter()
+                .take(MAX_TOKENS)
+                .map(|&value| i64::from(value))
+                .collect();
+            padded.resize(MAX_TOKENS, 0);
+            tract_ndarray::Array2::from_shape_vec((1, MAX_TOKENS), padded)
+                .map(|array| array.into_tensor().into())
+                .map_err(|err| error("tokenizer", err))
+        };
+        let mask = padded(encoding.get_attention_mask())?;
+        let mut inputs: TVec<TValue> = tvec![padded(encoding.get_ids())?, mask];
+        if self.token_types {
+            inputs.push(padded(encoding.get_type_ids())?);
+        }
+        let outputs = self
+            .model
+            .run(inputs)
+            .map_err(|err| error("encoder", err))?;
+        // Token embeddings, [1, tokens, dimensions].
+        let hidden = outputs[0]
+            .to_array_view::<f32>()
+            .map_err(|err| error("encoder", err))?;
+        let tokens = encoding.get_attention_mask().len().clamp(1, MAX_TOKENS);
+        let dimensions = hidden.shape().last().copied().unwrap_or(0);
+        let mut pooled = vec![0.0_f32; dimensions];
+        for token in 0..tokens {
+            for (dimension, value) in pooled.iter_mut().enumerate() {
+                *value += hidden[[0, token, dimension]];
+            }
+        }
+        normalize(&mut pooled);
+        Ok(pooled)
+    }
+}
+
+/// Scales `vector` to unit length; zero vectors are left alone.
+pub fn normalize(vector: &mut [f32]) {
+    let length = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+    if length > 0.0 {
+        vector.iter_mut().for_each(|value| *value /= length);
+    }
+}
+
+/// Where the encoder is looked for: the configured directory, else `models/sentence-encoder`
+/// next to the executable (where release builds bundle it), else in the data directory.
+pub fn model_dir(configured: Option<&Path>) -> Option<PathBuf> {
+    let bundled = std::env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.join("models").join("sentence-encoder")));
+    let data = crate::config::data_file("sentence-encoder");
+    configured
+        .map(Path::to_path_buf)
+        .into_iter()
+        .chain(bundled)
+        .chain(data)
+        .find(|dir| dir.join(MODEL_FILE_NAME).is_file())
+}
@@ -0,0 +1,357 @@
+//! Natural-language search over anime ("dark psychological thriller like Death Note but
+//! shorter"): the query is embedded with a sentence encoder and compared with embeddings of
+//! every cached synopsis, then the closest candidates are re-ranked by the user's taste profile
+//! from [`wasiw_core::content`]. A title named in the query pulls the query towards that anime,
+//! and "shorter" or "longer" filters by episode count. Built with the `semantic` feature;
+//! synopsis embeddings are cached in `synopsis-embeddings.bin` in the platform data directory.
+
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::path::Path;
+
+use wasiw_core::content::{Candidate, TasteProfile};
+
+use crate::config;
+use crate::import::titles;
+use crate::metadata::{AnimeMetadata, MetadataCache};
+
+mod encoder;
+
+use encoder::Encoder;
+pub use encoder::{model_dir, EncoderError};
+
+const INDEX_FILE_NAME: &str = "synopsis-embeddings.bin";
+const INDEX_MAGIC: &[u8; 8] = b"WASIWEMB";
+/// Closest anime by meaning that the taste profile re-ranks.
+const CANDIDATES: usize = 100;
+/// Share of the final score that comes from the taste profile.
+const TASTE_WEIGHT: f32 = 0.3;
+/// Episode counts "short" and "long" mean when the query names no anime to compare with.
+const SHORT_EPISODES: u32 = 13;
+const LONG_EPISODES: u32 = 24;
+/// Embeddings are saved this often while indexing, so a closed window keeps most of the work.
+const SAVE_EVERY: usize = 500;
+pub const MAX_RESULTS: usize = 20;
+
+/// Synopsis embeddings by anime id, all from one encoder.
+#[derive(Debug, Clone, Default)]
+pub struct SynopsisIndex {
+    fingerprint: u64,
+    vectors: HashMap<u32, Vec<f32>>,
+}
+
+impl SynopsisIndex {
+    /// The cached index for the encoder with `fingerprint`, or an empty one when it is missing,
+    /// unreadable or from another encoder.
+    pub fn load(fingerprint: u64) -> Self {
+        config::read_data_file(INDEX_FILE_NAME)
+            .and_then(|bytes| Self::decode(&bytes))
+            .filter(|index| index.fingerprint == fingerprint)
+            .unwrap_or(Self {
+                fingerprint,
+                vectors: HashMap::new(),
+            })
+    }
+
+    /// Magic, fingerprint and dimensions, then each anime id followed by its vector, all
+    /// little-endian.
+    fn decode(mut bytes: &[u8]) -> Option<Self> {
+        let mut magic = [0; 8];
+        bytes.read_exact(&mut magic).ok()?;
+        if &magic != INDEX_MAGIC {
+            return None;
+        }
+        let fingerprint = u64::from_le_bytes(bytes.get(..8)?.try_into().ok()?);
+        let dimensions = u32::from_le_bytes(bytes.get(8..12)?.try_into().ok()?) as usize;
+        let mut vectors = HashMap::new();
+        for record in bytes[12..].chunks_exact(4 + 4 * dimensions) {
+            let anime_id = u32::from_le_bytes(record[..4].try_into().ok()?);
+            let vector = record[4..]
+                .chunks_exact(4)
+                .map(|value| f32::from_le_bytes(value.try_into().unwrap_or_default()))
+                .collect();
+            vectors.insert(anime_id, vector);
+        }
+        Some(Self {
+            fingerprint,
+            vectors,
+        })
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        config::write_data_file(INDEX_FILE_NAME, &self.encode())
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let dimensions = self.vectors.values().next().map_or(0, Vec::len);
+        let mut bytes = Vec::with_capacity(20 + self.vectors.len() * (4 + 4 * dimensions));
+        bytes.extend_from_slice(INDEX_MAGIC);
+        bytes.extend_from_slice(&self.fingerprint.to_le_bytes());
+        bytes.extend_from_slice(&(dimensions as u32).to_le_bytes());
+        for (anime_id, vector) in &self.vectors {
+            bytes.extend_from_slice(&anime_id.to_le_bytes());
+            for value in vector {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    pub fn contains(&self, anime_id: u32) -> bool {
+        self.vectors.contains_key(&anime_id)
+    }
+
+    pub fn insert(&mut self, anime_id: u32, vector: Vec<f32>) {
+        self.vectors.insert(anime_id, vector);
+    }
+}
+
+/// The encoder and index, loaded by the first search and kept for the session.
+#[derive(Default)]
+pub struct SemanticEngine {
+    encoder: Option<Encoder>,
+    index: SynopsisIndex,
+}
+
+impl SemanticEngine {
+    /// Loads the encoder from `model_dir` if it isn't yet and embeds the `documents` (anime id
+    /// and text) that aren't indexed. `progress` receives short status lines.
+    pub fn prepare(
+        &mut self,
+        model_dir: &Path,
+        documents: Vec<(u32, String)>,
+        mut progress: impl FnMut(String),
+    ) -> Result<(), EncoderError> {
+        if self.encoder.is_none() {
+            progress("Loading the sentence encoder…".to_string());
+            let encoder = Encoder::load(model_dir)?;
+            self.index = SynopsisIndex::load(encoder.fingerprint);
+            self.encoder = Some(encoder);
+        }
+        let Some(encoder) = &self.encoder else {
+            return Ok(());
+        };
+        let missing: Vec<(u32, String)> = documents
+            .into_iter()
+            .filter(|(anime_id, _)| !self.index.contains(*anime_id))
+            .collect();
+        for (done, (anime_id, text)) in missing.iter().enumerate() {
+            if done.is_multiple_of(50) {
+                progress(format!("Indexing synopses… {done}/{}", missing.len()));
+            }
+            self.index.insert(*anime_id, encoder.embed(text)?);
+            if (done + 1).is_multiple_of(SAVE_EVERY) {
+                let _ = self.index.save();
+            }
+        }
+        if !missing.is_empty() {
+            // Best effort: an unsaved index is rebuilt next session.
+            let _ = self.index.save();
+        }
+        Ok(())
+    }
+
+    /// [`search`] with the loaded encoder; call [`Self::prepare`] first.
+    pub fn search(
+        &self,
+        query: &str,
+        titles: &HashMap<u32, String>,
+        metadata: &MetadataCache,
+        taste: Option<&TasteProfile>,
+        limit: usize,
+    ) -> Result<Vec<SemanticMatch>, EncoderError> {
+        let encoder = self
+            .encoder
+            .as_ref()
+            .ok_or_else(|| EncoderError::new("the sentence encoder isn't loaded"))?;
+        search(encoder, &self.index, query, titles, metadata, taste, limit)
+    }
+}
+
+/// What is embedded for an anime: its title, genres and synopsis. `None` without a synopsis,
+/// since a title and genres alone say little about what a show is like.
+pub fn document(title: &str, metadata: &AnimeMetadata) -> Option<String> {
+    let synopsis = metadata.synopsis.as_deref()?.trim();
+    if synopsis.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "{title}. {}. {synopsis}",
+        metadata.genres.join(", ")
+    ))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Length {
+    Shorter,
+    Longer,
+}
+
+/// An anime found by meaning, with how well it matched and how well it suits the user.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SemanticMatch {
+    pub anime_id: u32,
+    pub title: String,
+    /// Cosine similarity of the synopsis to the query, from -1 to 1.
+    pub relevance: f32,
+    /// Content similarity to the user's rated anime, from 0 to 1, when re-ranked.
+    pub taste: Option<f64>,
+}
+
+/// The anime whose synopses best match `query`, re-ranked by `taste` when given. `titles`
+/// holds the anime that may be returned; anime the user rated are left out.
+fn search(
+    encoder: &Encoder,
+    index: &SynopsisIndex,
+    query: &str,
+    titles: &HashMap<u32, String>,
+    metadata: &MetadataCache,
+    taste: Option<&TasteProfile>,
+    limit: usize,
+) -> Result<Vec<SemanticMatch>, EncoderError> {
+    let normalized = format!(" {} ", titles::normalize(query));
+    let length = normalized.split_whitespace().find_map(|word| match word {
+        "short" | "shorter" => Some(Length::Shorter),
+        "long" | "longer" => Some(Length::Longer),
+        _ => None,
+    });
+    // The longest title named in the query, e.g. "like Death Note".
+    let reference = titles
+        .iter()
+        .map(|(&anime_id, title)| (anime_id, titles::normalize(title)))
+        .filter(|(_, title)| title.len() >= 4 && normalized.contains(&format!(" {title} ")))
+        .max_by_key(|(_, title)| title.len())
+        .map(|(anime_id, _)| anime_id);
+
+    let mut wanted = encoder.embed(query)?;
+    if let Some(vector) = reference.and_then(|anime_id| index.vectors.get(&anime_id)) {
+        for (value, reference) in wanted.iter_mut().zip(vector) {
+            *value += reference;
+        }
+        encoder::normalize(&mut wanted);
+    }
+    let reference_episodes = reference
+        .and_then(|anime_id| metadata.get(anime_id))
+        .and_then(|metadata| metadata.episodes);
+    let fits_length = |anime_id: u32| {
+        let Some(length) = length else {
+            return true;
+        };
+        let Some(episodes) = metadata.get(anime_id).and_then(|m| m.episodes) else {
+            return false;
+        };
+        match (length, reference_episodes) {
+            (Length::Shorter, Some(reference)) => episodes < reference,
+            (Length::Longer, Some(reference)) => episodes > reference,
+            (Length::Shorter, None) => episodes <= SHORT_EPISODES,
+            (Length::Longer, None) => episodes >= LONG_EPISODES,
+        }
+    };
+
+    let mut matches: Vec<SemanticMatch> = index
+        .vectors
+        .iter()
+        .filter(|(&anime_id, _)| Some(anime_id) != reference && fits_length(anime_id))
+        .filter(|(anime_id, _)| taste.is_none_or(|taste| !taste.rated.contains(anime_id)))
+        .filter_map(|(&anime_id, vector)| {
+            Some(SemanticMatch {
+                anime_id,
+                title: titles.get(&anime_id)?.clone(),
+                relevance: vector.iter().zip(&wanted).map(|(a, b)| a * b).sum(),
+                taste: None,
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| b.relevance.total_cmp(&a.relevance));
+    matches.truncate(CANDIDATES);
+
+    if let Some(taste) = taste.filter(|taste| !taste.entries.is_empty()) {
+        let candidates: Vec<Candidate> = matches
+            .iter()
+            .map(|found| Candidate {
+                anime_id: found.anime_id,
+                title: found.title.clone(),
+                features: metadata
+                    .get(found.anime_id)
+                    .map(AnimeMetadata::content_features)
+                    .unwrap_or_default(),
+            })
+            .collect();
+        let scores: HashMap<u32, f64> = taste
+            .recommend(&candidates, candidates.len())
+            .into_iter()
+            .map(|recommendation| (recommendation.anime_id, recommendation.score))
+            .collect();
+        for found in &mut matches {
+            found.taste = Some(scores.get(&found.anime_id).copied().unwrap_or(0.0));
+        }
+        // Both scores are scaled to 0–1 over the candidates before they're blended.
+        let range = |values: Vec<f32>| {
+            let low = values.iter().copied().fold(f32::INFINITY, f32::min);
+            let high = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            move |value: f32| {
+                if high > low {
+                    (value - low) / (high - low)
+                } else {
+                    1.0
+                }
+            }
+        };
+        let relevance = range(matches.iter().map(|found| found.relevance).collect());
+        let suits = range(
+            matches
+                .iter()
+                .map(|found| found.taste.unwrap_or(0.0) as f32)
+                .collect(),
+        );
+        let blended = |found: &SemanticMatch| {
+            (1.0 - TASTE_WEIGHT) * relevance(found.relevance)
+                + TASTE_WEIGHT * suits(found.taste.unwrap_or(0.0) as f32)
+        };
+        matches.sort_by(|a, b| blended(b).total_cmp(&blended(a)));
+    }
+    matches.truncate(limit);
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_index_reads_back_what_it_wrote_and_nothing_else() {
+        let mut index = SynopsisIndex {
+            fingerprint: 0x5eed,
+            vectors: HashMap::new(),
+        };
+        index.insert(1, vec![0.5, -0.25, 1.0]);
+        index.insert(20, vec![0.0, 0.75, -1.0]);
+        let bytes = index.encode();
+        let decoded = SynopsisIndex::decode(&bytes).unwrap();
+        assert_eq!(decoded.fingerprint, 0x5eed);
+        assert_eq!(decoded.vectors, index.vectors);
+        assert!(decoded.contains(20) && !decoded.contains(3));
+
+        // A truncated record is dropped; another file format isn't read at all.
+        let decoded = SynopsisIndex::decode(&bytes[..bytes.len() - 1]).unwrap();
+        assert_eq!(decoded.vectors.len(), 1);
+        assert!(SynopsisIndex::decode(b"WASIWIDX\0\0\0\0\0\0\0\0\0\0\0\0").is_none());
+        assert!(SynopsisIndex::decode(&bytes[..10]).is_none());
+    }
+
+    #[test]
+    fn documents_need_a_synopsis() {
+        let mut metadata = AnimeMetadata {
+            genres: vec!["Drama".to_string(), "Mystery".to_string()],
+            ..AnimeMetadata::default()
+        };
+        assert_eq!(document("Monster", &metadata), None);
+        metadata.synopsis = Some("  ".to_string());
+        assert_eq!(document("Monster", &metadata), None);
+        metadata.synopsis = Some(" A surgeon saves a boy who grows up to kill. ".to_string());
+        assert_eq!(
+            document("Monster", &metadata).unwrap(),
+            "Monster. Drama, Mystery. A surgeon saves a boy who grows up to kill."
+        );
+    }
+}
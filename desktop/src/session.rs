@@ -0,0 +1,98 @@
+//! The UI session: which dataset file is open, the side-panel tab, the camera, the filter and
+//! the selection. Saved to `session.json` in the platform data directory when the window closes
+//! and restored at the next launch, unless the app is started with `--fresh` or the settings
+//! say to start fresh every time.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use wasiw_core::GraphFilter;
+use wasiw_ui::camera::Camera;
+
+use crate::config;
+
+const SESSION_FILE_NAME: &str = "session.json";
+/// Command-line flag that skips restoring the last session.
+pub const FRESH_FLAG: &str = "--fresh";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// Dataset file the graph was built from; `None` for merged or generated datasets.
+    pub dataset: Option<PathBuf>,
+    /// Route of the side-panel tab, e.g. `/settings`.
+    pub tab: String,
+    pub camera: Camera,
+    pub filter: GraphFilter,
+    pub search: String,
+    /// Node id of the selection, e.g. `anime:5114`, which survives the graph being rebuilt.
+    pub selected: Option<String>,
+    pub details_open: bool,
+}
+
+impl Session {
+    /// The last session, or `None` when there is none to restore, it is unreadable, or
+    /// `fresh` asks to skip it. `--fresh` on the command line always skips it.
+    pub fn restore(fresh: bool) -> Option<Self> {
+        if fresh || std::env::args().any(|arg| arg == FRESH_FLAG) {
+            return None;
+        }
+        config::read_data_file(SESSION_FILE_NAME)
+            .and_then(|content| serde_json::from_slice(&content).ok())
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let content = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        config::write_data_file(SESSION_FILE_NAME, &content)
+    }
+
+    /// Forgets the saved session, so the next launch starts fresh.
+    pub fn clear() -> io::Result<()> {
+        match config::data_file(SESSION_FILE_NAME) {
+            Some(path) if path.exists() => fs::remove_file(path),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_survive_a_round_trip_and_older_files_fill_in_defaults() {
+        let session = Session {
+            dataset: Some(PathBuf::from("/data/anonymized-ratings.json")),
+            tab: "/settings".to_string(),
+            camera: Camera {
+                zoom: 2.5,
+                pan_x: -40.0,
+                pan_y: 12.0,
+            },
+            filter: GraphFilter {
+                min_anime_ratings: 3,
+                ..GraphFilter::default()
+            },
+            search: "bebop".to_string(),
+            selected: Some("anime:1".to_string()),
+            details_open: true,
+        };
+        let content = serde_json::to_vec_pretty(&session).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<Session>(&content).unwrap(),
+            session
+        );
+
+        let older: Session = serde_json::from_str(r#"{"tab": "/stats"}"#).unwrap();
+        assert_eq!(older.tab, "/stats");
+        assert_eq!(older.camera, Camera::default());
+        assert_eq!(older.selected, None);
+    }
+
+    #[test]
+    fn fresh_starts_skip_the_saved_session() {
+        assert_eq!(Session::restore(true), None);
+    }
+}
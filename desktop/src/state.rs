@@ -0,0 +1,325 @@
+//! The signals the app's actions share. `App` creates them; bundling them lets the handlers for
+//! imports, profiles and account sync live next to those modules rather than all inside `App`,
+//! and gives the tab pages, which get both bundles from context, what they show and change.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use wasiw_core::budget::BudgetCandidate;
+use wasiw_core::delta;
+use wasiw_core::memory::MemoryUsage;
+use wasiw_core::recommend::{self, Recommendation};
+use wasiw_core::relations::Relations;
+use wasiw_core::tribes::TribeReport;
+use wasiw_core::watch_order::{PlanInfo, WatchOrder};
+use wasiw_core::{Dataset, EdgeKind, GraphModel, NodeType, QualityReport, Viewport};
+use wasiw_ui::camera::{Camera, ViewBox};
+use wasiw_ui::canvas::{ContextMenu, EdgeHover};
+use wasiw_ui::navigation::TabCursor;
+
+use crate::config::AppConfig;
+use crate::idmap::IdMap;
+use crate::import::csv::CsvTable;
+use crate::metadata::MetadataCache;
+use crate::profiles::Profiles;
+use crate::routes::Route;
+use crate::sync::Accounts;
+use crate::tasks::TaskList;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct AppState {
+    pub dataset: Signal<Dataset>,
+    pub profiles: Signal<Profiles>,
+    pub graph: Signal<GraphModel>,
+    pub config: Signal<AppConfig>,
+    pub accounts: Signal<Accounts>,
+    pub tasks: Signal<TaskList>,
+    pub id_map: Signal<Option<Rc<IdMap>>>,
+    /// Cached metadata; fetching what's missing is started from the menu.
+    pub metadata: Signal<MetadataCache>,
+    /// Sequels, prequels and side stories from the cached metadata, drawn over the graph and
+    /// used to list each franchise once among recommendations.
+    pub relations: Memo<Relations>,
+    /// The status line under the import panel, where most actions report back.
+    pub import_status: Signal<Option<String>>,
+    pub selected: Signal<Option<usize>>,
+    pub details_open: Signal<bool>,
+    pub camera: Signal<Camera>,
+    pub viewport: Signal<Viewport>,
+    /// Bumped to cancel the layout transition in flight.
+    pub transition: Signal<u64>,
+    pub tab_cursor: Signal<Option<TabCursor>>,
+    pub context_menu: Signal<Option<ContextMenu>>,
+    pub hovered_edge: Signal<Option<EdgeHover>>,
+    pub expanded_communities: Signal<HashSet<usize>>,
+    /// The anime picked to compare another with, from its analytics page.
+    pub compare_with: Signal<Option<u32>>,
+    /// The same for users, by dataset id.
+    pub compare_user_with: Signal<Option<String>>,
+    /// The taste tribes last found, until the dataset changes.
+    pub tribes: Signal<Option<TribeReport>>,
+    /// What loading the open dataset dropped, for its health checks.
+    pub load_report: Signal<QualityReport>,
+    /// Roughly what the dataset, graph and caches take, for the Stats tab.
+    pub memory: Signal<MemoryUsage>,
+}
+
+/// The rest of what the tab pages need from `App`: signals of the window around the graph, and
+/// actions that live there.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Shell {
+    pub search: Signal<String>,
+    pub fullscreen: Signal<bool>,
+    pub log_open: Signal<bool>,
+    /// The file the dataset came from, re-read when it changes.
+    pub watched_path: Signal<Option<PathBuf>>,
+    /// The CSV whose columns are being mapped.
+    pub csv_table: Signal<Option<CsvTable>>,
+    /// Animates the graph into the layout for the viewport.
+    pub relayout: Callback<Viewport>,
+    /// Re-reads a dataset file in the background.
+    pub reload: Callback<PathBuf>,
+    /// Resets the view and forgets the saved session.
+    pub start_fresh: Callback<()>,
+    #[cfg(feature = "semantic")]
+    pub semantic_results: Signal<Option<(String, Vec<crate::semantic::SemanticMatch>)>>,
+    #[cfg(feature = "semantic")]
+    pub semantic_search: Callback<String>,
+}
+
+impl AppState {
+    /// Shows a freshly built graph. Node indices change, so anything that refers to a node is
+    /// reset.
+    pub fn install_graph(mut self, mut next: GraphModel) {
+        self.label_titles(&mut next);
+        *self.transition.write() += 1;
+        self.graph.set(next);
+        self.selected.set(None);
+        self.details_open.set(false);
+        self.tab_cursor.set(None);
+        self.context_menu.set(None);
+        self.hovered_edge.set(None);
+        self.expanded_communities.write().clear();
+    }
+
+    /// Labels `graph`'s anime with their title in the form the graph shows.
+    pub fn label_titles(&self, graph: &mut GraphModel) {
+        let form = self.config.peek().titles.graph();
+        self.metadata.peek().label_titles(graph, form);
+    }
+
+    /// Relabels the graph in place, after the title form or the metadata changed.
+    pub fn relabel_graph(self) {
+        let mut graph = self.graph;
+        self.label_titles(&mut graph.write());
+    }
+
+    /// The title lists show for `anime_id`, where `title` is the graph's.
+    pub fn list_title(&self, anime_id: u32, title: &str) -> String {
+        let form = self.config.read().titles.lists();
+        self.metadata
+            .read()
+            .get(anime_id)
+            .and_then(|metadata| metadata.titles.get(form))
+            .unwrap_or(title)
+            .to_string()
+    }
+
+    /// Rebuilds the graph after the dataset or the active profile changed, keeping layout and
+    /// filter settings.
+    pub fn rebuild_graph(mut self) {
+        self.profiles.peek().apply(&mut self.dataset.write());
+        let metadata = self.metadata.peek();
+        let next = self.graph.peek().settings().build_with_facets(
+            self.dataset.peek().clone(),
+            |anime_id, mode| metadata.facets(anime_id, mode),
+            |_| {},
+        );
+        drop(metadata);
+        self.install_graph(next);
+    }
+
+    /// Updates the graph after only `user_id`'s ratings changed, such as a rating added to a
+    /// local profile, without rebuilding it from every user; rebuilds it when the change needs
+    /// that after all.
+    pub fn update_user_graph(mut self, user_id: &str) {
+        self.profiles.peek().apply(&mut self.dataset.write());
+        let next = delta::update_user(&self.graph.peek(), &self.dataset.peek(), user_id);
+        match next {
+            Some(next) => self.install_graph(next),
+            None => self.rebuild_graph(),
+        }
+    }
+
+    /// Selects `node` and pans the camera if it is outside the view.
+    pub fn select_node(mut self, node: usize) {
+        self.selected.set(Some(node));
+        let (x, y) = {
+            let graph = self.graph.read();
+            (graph.nodes[node].x, graph.nodes[node].y)
+        };
+        let fitted = ViewBox::fit(&self.graph.read().nodes, *self.viewport.read());
+        let view = self.camera.read().apply(fitted);
+        if x < view.x || x > view.x + view.width || y < view.y || y > view.y + view.height {
+            self.camera.write().center_on(x, y, fitted);
+        }
+    }
+
+    /// Selects `node` and opens its details card.
+    pub fn open_details(mut self, node: usize) {
+        self.select_node(node);
+        self.details_open.set(true);
+    }
+
+    /// Opens `node`'s details card, and its analytics page in the side panel.
+    pub fn open_node(self, node: usize) {
+        self.open_details(node);
+        let page = {
+            let graph = self.graph.peek();
+            let node = &graph.nodes[node];
+            match (node.node_type, node.anime_id()) {
+                (_, Some(anime_id)) => Route::Anime { anime_id },
+                (NodeType::User, None) => Route::User {
+                    user_id: node.id.trim_start_matches("user:").to_string(),
+                },
+                // Genres have no page of their own.
+                _ => return,
+            }
+        };
+        navigator().push(page);
+    }
+
+    /// Recommendations for the user at node `user` of `graph`, best first and each franchise
+    /// listed once, by its best-ranked entry; at most `limit`.
+    pub fn recommend(&self, graph: &GraphModel, user: usize, limit: usize) -> Vec<Recommendation> {
+        let relations = self.relations.read();
+        if relations.is_empty() {
+            return recommend::recommend(graph, user, limit);
+        }
+        let mut recommendations = recommend::recommend(graph, user, usize::MAX);
+        relations.collapse(&mut recommendations, |recommendation| {
+            recommendation.anime_id
+        });
+        recommendations.truncate(limit);
+        recommendations
+    }
+
+    /// The title to start each of `anime` with for the user at node `user` of `graph`, as
+    /// lists show it, where that is an earlier entry of its franchise the user hasn't rated.
+    pub fn entry_points(
+        &self,
+        graph: &GraphModel,
+        user: usize,
+        anime: impl IntoIterator<Item = u32>,
+    ) -> HashMap<u32, String> {
+        let relations = self.relations.read();
+        if relations.is_empty() {
+            return HashMap::new();
+        }
+        let rated = rated_anime(graph, user);
+        let metadata = self.metadata.read();
+        let year = |anime_id: u32| metadata.get(anime_id)?.year;
+        anime
+            .into_iter()
+            .filter_map(|anime_id| {
+                let entry = relations.entry_point(anime_id, |prior| rated.contains(&prior), year);
+                (entry != anime_id)
+                    .then(|| (anime_id, self.list_title(entry, &self.anime_title(entry))))
+            })
+            .collect()
+    }
+
+    /// `anime` with the earlier entries of their franchises that lead up to them and the user
+    /// at node `user` of `graph` hasn't rated, each once.
+    pub fn with_lead_up(&self, graph: &GraphModel, user: usize, anime: &[u32]) -> Vec<u32> {
+        let relations = self.relations.read();
+        let rated = rated_anime(graph, user);
+        let metadata = self.metadata.read();
+        let year = |anime_id: u32| metadata.get(anime_id)?.year;
+        let mut planned = Vec::new();
+        for &anime_id in anime {
+            for step in relations.path_to_entry(anime_id, |prior| rated.contains(&prior), year) {
+                if !planned.contains(&step) {
+                    planned.push(step);
+                }
+            }
+        }
+        planned
+    }
+
+    /// `anime` in watch order, with the titles lists show and lengths from cached metadata.
+    pub fn watch_order(&self, anime: &[u32]) -> WatchOrder {
+        let metadata = self.metadata.read();
+        WatchOrder::plan(&self.relations.read(), anime, |anime_id| {
+            let cached = metadata.get(anime_id);
+            PlanInfo {
+                title: self.list_title(anime_id, &self.anime_title(anime_id)),
+                year: cached.and_then(|cached| cached.year),
+                episodes: cached.and_then(|cached| cached.episodes),
+                episode_minutes: cached.and_then(|cached| cached.episode_minutes),
+            }
+        })
+    }
+
+    /// `recommendations` as candidates for a time budget, each with the unrated entries that
+    /// lead up to it; those with an entry of unknown length are left out.
+    pub fn budget_candidates(
+        &self,
+        graph: &GraphModel,
+        user: usize,
+        recommendations: &[Recommendation],
+    ) -> Vec<BudgetCandidate> {
+        recommendations
+            .iter()
+            .filter_map(|recommendation| {
+                let path = self.with_lead_up(graph, user, &[recommendation.anime_id]);
+                let order = self.watch_order(&path);
+                (order.unknown_lengths() == 0).then(|| BudgetCandidate {
+                    anime_id: recommendation.anime_id,
+                    title: self.list_title(recommendation.anime_id, &recommendation.title),
+                    score: recommendation.score,
+                    minutes: order.total_minutes(),
+                    movie: order.entries.iter().all(|entry| entry.episodes == Some(1)),
+                })
+            })
+            .collect()
+    }
+
+    /// The node index of the user with dataset id `user_id`.
+    pub fn find_user_node(&self, user_id: &str) -> Option<usize> {
+        let node_id = format!("user:{user_id}");
+        self.graph
+            .peek()
+            .nodes
+            .iter()
+            .position(|node| node.id == node_id)
+    }
+
+    /// The label of the user with dataset id `user_id` in the graph, or the id itself.
+    pub fn user_name(&self, user_id: &str) -> String {
+        self.find_user_node(user_id).map_or_else(
+            || user_id.to_string(),
+            |idx| self.graph.peek().nodes[idx].label.clone(),
+        )
+    }
+
+    /// The title of `anime_id` in the graph, or its MyAnimeList id when it isn't there.
+    pub fn anime_title(&self, anime_id: u32) -> String {
+        let graph = self.graph.peek();
+        graph.find_anime(anime_id).map_or_else(
+            || format!("MAL #{anime_id}"),
+            |idx| graph.nodes[idx].label.clone(),
+        )
+    }
+}
+
+/// The anime the user at node `user` of `graph` rated.
+fn rated_anime(graph: &GraphModel, user: usize) -> HashSet<u32> {
+    graph
+        .links(user)
+        .filter(|(_, edge)| edge.kind == EdgeKind::Rating)
+        .filter_map(|(anime, _)| graph.nodes[anime].anime_id())
+        .collect()
+}
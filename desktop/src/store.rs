@@ -0,0 +1,172 @@
+//! SQLite-backed rating store (behind the `sqlite` feature).
+//!
+//! Opening a dataset copies it into `ratings.db` in the platform data directory once; later
+//! launches read rows from there instead of re-parsing the JSON. Imported profiles are written
+//! per user, so they survive restarts without rewriting the corpus.
+
+use std::path::PathBuf;
+
+use rusqlite::{params, Connection};
+use wasiw_core::{Dataset, Rating, UserRatings};
+
+use crate::config;
+
+const DB_FILE_NAME: &str = "ratings.db";
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS ratings (
+        user_id TEXT NOT NULL,
+        anime_id INTEGER NOT NULL,
+        title TEXT NOT NULL,
+        raw_score REAL NOT NULL,
+        PRIMARY KEY (user_id, anime_id)
+    ) WITHOUT ROWID;
+";
+
+pub struct RatingStore {
+    connection: Connection,
+}
+
+impl RatingStore {
+    pub fn path() -> Option<PathBuf> {
+        config::data_file(DB_FILE_NAME)
+    }
+
+    pub fn open() -> rusqlite::Result<Self> {
+        let path = Self::path().ok_or(rusqlite::Error::InvalidPath(PathBuf::from(DB_FILE_NAME)))?;
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    fn with_connection(connection: Connection) -> rusqlite::Result<Self> {
+        connection.pragma_update(None, "journal_mode", "WAL")?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self { connection })
+    }
+
+    /// Replaces everything in the store with `dataset`.
+    pub fn save_dataset(&mut self, dataset: &Dataset) -> rusqlite::Result<()> {
+        let transaction = self.connection.transaction()?;
+        transaction.execute("DELETE FROM ratings", [])?;
+        {
+            let mut insert = transaction.prepare(
+                "INSERT OR REPLACE INTO ratings (user_id, anime_id, title, raw_score)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for user in &dataset.users {
+                for rating in &user.ratings {
+                    insert.execute(params![
+                        user.user_id,
+                        rating.anime_id,
+                        rating.title,
+                        rating.raw_score
+                    ])?;
+                }
+            }
+        }
+        transaction.commit()
+    }
+
+    /// Replaces one user's ratings, leaving the rest of the corpus untouched.
+    pub fn save_user(&mut self, user: &UserRatings) -> rusqlite::Result<()> {
+        let transaction = self.connection.transaction()?;
+        transaction.execute("DELETE FROM ratings WHERE user_id = ?1", [&user.user_id])?;
+        {
+            let mut insert = transaction.prepare(
+                "INSERT OR REPLACE INTO ratings (user_id, anime_id, title, raw_score)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for rating in &user.ratings {
+                insert.execute(params![
+                    user.user_id,
+                    rating.anime_id,
+                    rating.title,
+                    rating.raw_score
+                ])?;
+            }
+        }
+        transaction.commit()
+    }
+
+    /// Reads every stored user, or `None` when the store is empty. Rows come back in primary-key
+    /// order, so each user is assembled from one contiguous run without a lookup table.
+    pub fn load(&self) -> rusqlite::Result<Option<Dataset>> {
+        let mut query = self.connection.prepare(
+            "SELECT user_id, anime_id, title, raw_score FROM ratings ORDER BY user_id, anime_id",
+        )?;
+        let mut rows = query.query([])?;
+        let mut users: Vec<UserRatings> = Vec::new();
+        while let Some(row) = rows.next()? {
+            let user_id: String = row.get(0)?;
+            if users.last().is_none_or(|user| user.user_id != user_id) {
+                users.push(UserRatings {
+                    user_id,
+                    ratings: Vec::new(),
+                });
+            }
+            let user = users.last_mut().expect("a user was just pushed");
+            user.ratings
+                .push(Rating::new(row.get(1)?, row.get(2)?, row.get(3)?));
+        }
+        Ok((!users.is_empty()).then_some(Dataset::new(users)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(user_id: &str, scores: &[(u32, f64)]) -> UserRatings {
+        UserRatings {
+            user_id: user_id.to_string(),
+            ratings: scores
+                .iter()
+                .map(|&(anime_id, score)| Rating::new(anime_id, format!("Anime {anime_id}"), score))
+                .collect(),
+        }
+    }
+
+    fn scores(dataset: &Dataset) -> Vec<(String, Vec<(u32, f64)>)> {
+        dataset
+            .users
+            .iter()
+            .map(|user| {
+                let ratings = user.ratings.iter().map(|r| (r.anime_id, r.raw_score));
+                (user.user_id.clone(), ratings.collect())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn saved_users_come_back_and_profiles_replace_only_their_own_rows() {
+        let mut store =
+            RatingStore::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        assert!(store.load().unwrap().is_none());
+
+        let dataset = Dataset::new(vec![
+            user("bob", &[(5, 7.0), (1, 9.0)]),
+            user("alice", &[(2, 6.5)]),
+        ]);
+        store.save_dataset(&dataset).unwrap();
+        store
+            .save_user(&user("alice", &[(3, 8.0), (4, 4.0)]))
+            .unwrap();
+
+        let loaded = store.load().unwrap().unwrap();
+        assert_eq!(
+            scores(&loaded),
+            [
+                ("alice".to_string(), vec![(3, 8.0), (4, 4.0)]),
+                ("bob".to_string(), vec![(1, 9.0), (5, 7.0)]),
+            ]
+        );
+        assert_eq!(loaded.users[1].ratings[0].title, "Anime 1");
+
+        store
+            .save_dataset(&Dataset::new(vec![user("carol", &[(1, 5.0)])]))
+            .unwrap();
+        assert_eq!(store.load().unwrap().unwrap().users.len(), 1);
+    }
+}
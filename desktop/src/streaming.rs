@@ -0,0 +1,310 @@
+//! Where recommended anime can be streamed legally in the user's region. With a TMDB API read
+//! access token, availability comes from TMDB's watch providers (JustWatch data) for the region
+//! in `config.toml`; without one, from the streaming services MyAnimeList lists through Jikan,
+//! which doesn't say where they're available. Answers are cached in `streaming.json` in the
+//! platform data directory for a week.
+
+use std::collections::HashMap;
+use std::io;
+use std::time::{Duration, SystemTime};
+
+use reqwest::{Client, Url};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::import::http::{get_json, RateLimiter};
+use crate::import::{jikan, ImportError};
+
+const CACHE_FILE_NAME: &str = "streaming.json";
+/// Cached answers older than this are looked up again; catalogs change every month or so.
+const MAX_AGE_SECS: u64 = 7 * 24 * 60 * 60;
+const TMDB_API: &str = "https://api.themoviedb.org/3";
+/// TMDB's genre id for animation, to tell an anime from a live-action show of the same name.
+const TMDB_ANIMATION: u32 = 16;
+
+/// TMDB allows about 40 requests a second; stay well under it.
+static TMDB_LIMITER: RateLimiter = RateLimiter::new(Duration::from_millis(100));
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Availability {
+    /// Region the services are available in, or `None` when the source doesn't say.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// Subscription, free and ad-supported services; rentals and purchases are left out.
+    pub services: Vec<String>,
+}
+
+impl Availability {
+    /// e.g. `Stream in US: Crunchyroll, Netflix`.
+    pub fn summary(&self) -> String {
+        match (&self.region, self.services.is_empty()) {
+            (Some(region), true) => format!("Not streaming in {region}"),
+            (None, true) => "No known streaming service".to_string(),
+            (Some(region), false) => format!("Stream in {region}: {}", self.services.join(", ")),
+            (None, false) => format!("Stream on {} (region unknown)", self.services.join(", ")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CachedAvailability {
+    /// Unix seconds.
+    fetched_at: u64,
+    #[serde(flatten)]
+    availability: Availability,
+}
+
+/// Availability by region and anime id.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AvailabilityCache {
+    entries: HashMap<String, CachedAvailability>,
+}
+
+impl AvailabilityCache {
+    /// Reads the cache, starting empty when it is missing or unreadable.
+    pub fn load() -> Self {
+        config::read_data_file(CACHE_FILE_NAME)
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let content = serde_json::to_vec(self).map_err(io::Error::other)?;
+        config::write_data_file(CACHE_FILE_NAME, &content)
+    }
+
+    /// The cached answer for `anime_id` in `region`, unless it is out of date or came from the
+    /// other source than `regional` asks for (TMDB when true, Jikan when false).
+    pub fn get(&self, region: &str, anime_id: u32, regional: bool) -> Option<&Availability> {
+        self.entries
+            .get(&key(region, anime_id))
+            .filter(|entry| now().saturating_sub(entry.fetched_at) < MAX_AGE_SECS)
+            .filter(|entry| entry.availability.region.is_some() == regional)
+            .map(|entry| &entry.availability)
+    }
+
+    pub fn insert(&mut self, region: &str, anime_id: u32, availability: Availability) {
+        let entry = CachedAvailability {
+            fetched_at: now(),
+            availability,
+        };
+        self.entries.insert(key(region, anime_id), entry);
+    }
+}
+
+fn key(region: &str, anime_id: u32) -> String {
+    format!("{}:{anime_id}", region.to_ascii_uppercase())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[derive(Deserialize)]
+struct TmdbSearch {
+    results: Vec<TmdbResult>,
+}
+
+#[derive(Deserialize)]
+struct TmdbResult {
+    id: u64,
+    #[serde(default)]
+    genre_ids: Vec<u32>,
+}
+
+#[derive(Deserialize)]
+struct TmdbProviders {
+    #[serde(default)]
+    results: HashMap<String, TmdbRegion>,
+}
+
+#[derive(Deserialize)]
+struct TmdbRegion {
+    #[serde(default)]
+    flatrate: Vec<TmdbProvider>,
+    #[serde(default)]
+    free: Vec<TmdbProvider>,
+    #[serde(default)]
+    ads: Vec<TmdbProvider>,
+}
+
+#[derive(Deserialize)]
+struct TmdbProvider {
+    provider_name: String,
+}
+
+#[derive(Deserialize)]
+struct JikanStreaming {
+    data: Vec<JikanService>,
+}
+
+#[derive(Deserialize)]
+struct JikanService {
+    name: String,
+}
+
+/// Looks up where `title` (MyAnimeList id `anime_id`) streams in `region`, e.g. `US`.
+pub async fn lookup(
+    client: &Client,
+    tmdb_token: Option<&str>,
+    region: &str,
+    anime_id: u32,
+    title: &str,
+) -> Result<Availability, ImportError> {
+    let region = region.to_ascii_uppercase();
+    let Some(token) = tmdb_token.filter(|token| !token.is_empty()) else {
+        let url = format!("https://api.jikan.moe/v4/anime/{anime_id}/streaming");
+        let streaming: JikanStreaming = get_json(client, &jikan::LIMITER, &url).await?;
+        return Ok(Availability {
+            region: None,
+            services: streaming
+                .data
+                .into_iter()
+                .map(|service| service.name)
+                .collect(),
+        });
+    };
+
+    // Series first, then films, keeping the first animated match.
+    let mut found = None;
+    for kind in ["tv", "movie"] {
+        let search: TmdbSearch = tmdb_get(
+            client,
+            token,
+            &format!("search/{kind}"),
+            &[("query", title)],
+        )
+        .await?;
+        if let Some(result) = search
+            .results
+            .into_iter()
+            .find(|result| result.genre_ids.contains(&TMDB_ANIMATION))
+        {
+            found = Some((kind, result.id));
+            break;
+        }
+    }
+    let Some((kind, id)) = found else {
+        return Ok(Availability {
+            region: Some(region),
+            services: Vec::new(),
+        });
+    };
+
+    let providers: TmdbProviders =
+        tmdb_get(client, token, &format!("{kind}/{id}/watch/providers"), &[]).await?;
+    Ok(Availability {
+        services: providers.services(&region),
+        region: Some(region),
+    })
+}
+
+impl TmdbProviders {
+    /// The services streaming in `region`, each once.
+    fn services(&self, region: &str) -> Vec<String> {
+        let mut services: Vec<String> = Vec::new();
+        if let Some(offers) = self.results.get(region) {
+            for provider in offers
+                .flatrate
+                .iter()
+                .chain(&offers.free)
+                .chain(&offers.ads)
+            {
+                if !services.contains(&provider.provider_name) {
+                    services.push(provider.provider_name.clone());
+                }
+            }
+        }
+        services
+    }
+}
+
+/// GETs a TMDB endpoint. The token goes in a header so it never shows up in an error message.
+async fn tmdb_get<T: DeserializeOwned>(
+    client: &Client,
+    token: &str,
+    path: &str,
+    query: &[(&str, &str)],
+) -> Result<T, ImportError> {
+    let url = Url::parse_with_params(&format!("{TMDB_API}/{path}"), query)
+        .map_err(|err| ImportError::Http(err.to_string()))?;
+    TMDB_LIMITER.wait().await;
+    client
+        .get(url)
+        .bearer_auth(token)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| ImportError::Http(err.to_string()))?
+        .json()
+        .await
+        .map_err(|err| ImportError::Parse(format!("TMDB {path}: {err}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn availability(region: Option<&str>, services: &[&str]) -> Availability {
+        Availability {
+            region: region.map(str::to_string),
+            services: services.iter().map(|service| service.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn summaries_say_where_and_whether_the_region_is_known() {
+        assert_eq!(
+            availability(Some("US"), &["Crunchyroll", "Netflix"]).summary(),
+            "Stream in US: Crunchyroll, Netflix"
+        );
+        assert_eq!(
+            availability(Some("JP"), &[]).summary(),
+            "Not streaming in JP"
+        );
+        assert_eq!(
+            availability(None, &["Netflix"]).summary(),
+            "Stream on Netflix (region unknown)"
+        );
+        assert_eq!(
+            availability(None, &[]).summary(),
+            "No known streaming service"
+        );
+    }
+
+    #[test]
+    fn providers_list_each_streaming_service_once() {
+        let providers: TmdbProviders = serde_json::from_str(
+            r#"{ "results": {
+                "US": { "flatrate": [{ "provider_name": "Crunchyroll" }],
+                        "free": [{ "provider_name": "Tubi" }],
+                        "ads": [{ "provider_name": "Crunchyroll" }],
+                        "buy": [{ "provider_name": "Apple TV" }] },
+                "GB": { "flatrate": [{ "provider_name": "Netflix" }] }
+            } }"#,
+        )
+        .unwrap();
+        assert_eq!(providers.services("US"), ["Crunchyroll", "Tubi"]);
+        assert!(providers.services("DE").is_empty());
+    }
+
+    #[test]
+    fn cached_answers_expire_and_keep_to_their_source() {
+        let mut cache = AvailabilityCache::default();
+        cache.insert("us", 1, availability(Some("US"), &["Netflix"]));
+        cache.insert("US", 2, availability(None, &["Crunchyroll"]));
+        assert_eq!(cache.get("US", 1, true).unwrap().services, ["Netflix"]);
+        assert!(cache.get("US", 1, false).is_none());
+        assert!(cache.get("us", 2, false).is_some());
+        assert!(cache.get("GB", 1, true).is_none());
+
+        let saved = serde_json::to_vec(&cache).unwrap();
+        let mut reloaded: AvailabilityCache = serde_json::from_slice(&saved).unwrap();
+        assert_eq!(reloaded, cache);
+        reloaded.entries.get_mut(&key("US", 1)).unwrap().fetched_at = now() - MAX_AGE_SECS;
+        assert!(reloaded.get("US", 1, true).is_none());
+    }
+}
@@ -0,0 +1,204 @@
+//! Connecting MyAnimeList and AniList accounts from the app, and keeping their lists up to date
+//! with ratings given in it.
+
+use dioxus::prelude::*;
+use wasiw_ui::accessibility::focus_on_mount;
+
+use super::{
+    anilist_authorize_url, connect_anilist, connect_mal, push_rating, SyncSite,
+    ANILIST_REDIRECT_URI, MAL_REDIRECT_URI,
+};
+use crate::state::AppState;
+use crate::{capitalize, http_client};
+
+/// Signs in to `site`; closes by clearing `connect_site` once connected or cancelled.
+#[component]
+pub fn ConnectDialog(
+    app: AppState,
+    site: SyncSite,
+    connect_site: Signal<Option<SyncSite>>,
+) -> Element {
+    let mut client_id = use_signal(move || {
+        let config = app.config.peek();
+        match site {
+            SyncSite::MyAnimeList => config.mal_client_id.clone(),
+            SyncSite::AniList => config.anilist_client_id.clone(),
+        }
+        .unwrap_or_default()
+    });
+    let mut token = use_signal(String::new);
+    let busy = use_signal(|| false);
+    let (register_url, redirect) = match site {
+        SyncSite::MyAnimeList => ("https://myanimelist.net/apiconfig", MAL_REDIRECT_URI),
+        SyncSite::AniList => (
+            "https://anilist.co/settings/developer",
+            ANILIST_REDIRECT_URI,
+        ),
+    };
+    let ready = !client_id.read().trim().is_empty()
+        && (site == SyncSite::MyAnimeList || !token.read().trim().is_empty());
+    rsx! {
+        div { class: "dialog-backdrop",
+            div {
+                class: "dialog",
+                role: "dialog",
+                aria_modal: "true",
+                aria_label: "Connect {site.label()}",
+                tabindex: "-1",
+                onmounted: focus_on_mount,
+                div { class: "row",
+                    strong { "Connect {site.label()}" }
+                    button { class: "close", onclick: move |_| connect_site.set(None), "×" }
+                }
+                p { class: "tiny",
+                    "Create an API client at {register_url} with redirect URL {redirect} and paste its client id below. Ratings you give recommendations with “Watched…” are then added to your list as completed."
+                }
+                div { class: "dialog-grid",
+                    label { class: "field",
+                        span { class: "tiny", "Client id" }
+                        input {
+                            value: "{client_id}",
+                            oninput: move |evt| client_id.set(evt.value()),
+                        }
+                    }
+                    if site == SyncSite::AniList {
+                        button {
+                            class: "action",
+                            disabled: client_id.read().trim().is_empty(),
+                            onclick: move |_| {
+                                if let Ok(url) = anilist_authorize_url(client_id.read().trim()) {
+                                    let _ = open::that(url.as_str());
+                                }
+                            },
+                            "Get a token from AniList"
+                        }
+                        label { class: "field",
+                            span { class: "tiny", "Token shown by AniList" }
+                            textarea {
+                                value: "{token}",
+                                oninput: move |evt| token.set(evt.value()),
+                            }
+                        }
+                    }
+                }
+                div { class: "row",
+                    button { class: "action", onclick: move |_| connect_site.set(None), "Cancel" }
+                    button {
+                        class: "action",
+                        disabled: !ready || busy(),
+                        onclick: move |_| connect(app, site, client_id(), token(), busy, connect_site),
+                        if site == SyncSite::MyAnimeList { "Sign in in the browser" } else { "Connect" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn connect(
+    mut app: AppState,
+    site: SyncSite,
+    client_id: String,
+    token: String,
+    mut busy: Signal<bool>,
+    mut connect_site: Signal<Option<SyncSite>>,
+) {
+    let client_id = client_id.trim().to_string();
+    {
+        let mut config = app.config.write();
+        let slot = match site {
+            SyncSite::MyAnimeList => &mut config.mal_client_id,
+            SyncSite::AniList => &mut config.anilist_client_id,
+        };
+        *slot = (!client_id.is_empty()).then(|| client_id.clone());
+        let _ = config.save();
+    }
+    busy.set(true);
+    app.import_status
+        .set(Some(format!("Connecting your {} account…", site.label())));
+    spawn(async move {
+        let result = match site {
+            SyncSite::MyAnimeList => connect_mal(&http_client(), &client_id).await,
+            SyncSite::AniList => connect_anilist(&http_client(), &token).await,
+        };
+        busy.set(false);
+        match result {
+            Ok(account) => {
+                app.import_status.set(Some(format!(
+                    "Connected {} account {}; anime you mark watched are now added to its list.",
+                    site.label(),
+                    account.name
+                )));
+                let mut accounts = app.accounts.write();
+                accounts.connect(account);
+                if let Err(err) = accounts.save() {
+                    app.import_status.set(Some(format!(
+                        "Connected, but the sign-in couldn't be saved for next time: {err}"
+                    )));
+                }
+                connect_site.set(None);
+            }
+            Err(err) => app
+                .import_status
+                .set(Some(format!("Could not connect {}: {err}", site.label()))),
+        }
+    });
+}
+
+/// Forgets every connected account.
+pub fn disconnect_all(mut app: AppState) {
+    let names: Vec<String> = app
+        .accounts
+        .peek()
+        .accounts
+        .iter()
+        .map(|account| format!("{} ({})", account.name, account.site.label()))
+        .collect();
+    let mut accounts = app.accounts.write();
+    accounts.accounts.clear();
+    app.import_status
+        .set(Some(match (names.is_empty(), accounts.save()) {
+            (true, _) => "No accounts are connected.".to_string(),
+            (false, Ok(())) => format!("Disconnected {}.", names.join(", ")),
+            (false, Err(err)) => format!("Could not forget the accounts: {err}"),
+        }));
+}
+
+/// Pushes a rating to every connected account, reporting each outcome after `rated`.
+pub fn update_lists(mut app: AppState, anime_id: u32, score: u8, rated: String) {
+    let targets = app.accounts.peek().accounts.clone();
+    if targets.is_empty() {
+        app.import_status.set(Some(rated));
+        return;
+    }
+    app.import_status
+        .set(Some(format!("{rated} Updating your lists…")));
+    let mal_client_id = app.config.peek().mal_client_id.clone();
+    spawn(async move {
+        let mut outcomes = Vec::new();
+        for mut account in targets {
+            let before = account.clone();
+            let result = push_rating(
+                &http_client(),
+                &mut account,
+                mal_client_id.as_deref(),
+                anime_id,
+                score,
+            )
+            .await;
+            if account != before {
+                let mut accounts = app.accounts.write();
+                accounts.connect(account.clone());
+                let _ = accounts.save();
+            }
+            outcomes.push(match result {
+                Ok(()) => format!("updated {}'s {} list", account.name, account.site.label()),
+                Err(err) => format!("could not update {}: {err}", account.site.label()),
+            });
+        }
+        app.import_status.set(Some(format!(
+            "{rated} {}.",
+            capitalize(&outcomes.join("; "))
+        )));
+    });
+}
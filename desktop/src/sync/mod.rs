@@ -0,0 +1,404 @@
+//! Pushing ratings made in the app back to the user's MyAnimeList or AniList account.
+//!
+//! Both sites need an API client the user registers themselves; its id goes in
+//! `config.toml`. MyAnimeList uses the OAuth authorization-code flow with PKCE and a loopback
+//! redirect to [`MAL_REDIRECT_URI`], so nothing has to be pasted. AniList only hands tokens to
+//! desktop apps through its PIN page, so the user copies the token from there into the app.
+//! Tokens are kept in `accounts.toml` in the platform data directory, readable only by the
+//! user on Unix, not in the config file that may get shared.
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::time::{Duration, Instant, SystemTime};
+
+use rand::RngExt;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::config;
+
+pub mod dialog;
+
+const ACCOUNTS_FILE_NAME: &str = "accounts.toml";
+/// Register this as the app redirect URL when creating the MyAnimeList API client.
+pub const MAL_REDIRECT_URI: &str = "http://127.0.0.1:53682/callback";
+const MAL_REDIRECT_PORT: u16 = 53682;
+const MAL_AUTHORIZE_URL: &str = "https://myanimelist.net/v1/oauth2/authorize";
+const MAL_TOKEN_URL: &str = "https://myanimelist.net/v1/oauth2/token";
+const MAL_API: &str = "https://api.myanimelist.net/v2";
+/// Register this as the redirect URL of the AniList API client.
+pub const ANILIST_REDIRECT_URI: &str = "https://anilist.co/api/v2/oauth/pin";
+const ANILIST_AUTHORIZE_URL: &str = "https://anilist.co/api/v2/oauth/authorize";
+const ANILIST_ENDPOINT: &str = "https://graphql.anilist.co";
+/// How long the loopback listener waits for the browser to come back.
+const AUTHORIZE_TIMEOUT: Duration = Duration::from_secs(300);
+/// How long a connection to the redirect listener may take to send its request line.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Tokens this close to expiry are refreshed before use.
+const REFRESH_MARGIN_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SyncSite {
+    MyAnimeList,
+    AniList,
+}
+
+impl SyncSite {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::MyAnimeList => "MyAnimeList",
+            Self::AniList => "AniList",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SyncError {
+    Io(io::Error),
+    /// The site rejected the request or the sign-in.
+    Http(String),
+    /// No API client id is configured for the site.
+    NoClientId(SyncSite),
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Http(msg) => write!(f, "request failed: {msg}"),
+            Self::NoClientId(site) => write!(f, "no {} API client id is set", site.label()),
+        }
+    }
+}
+
+impl std::error::Error for SyncError {}
+
+impl From<io::Error> for SyncError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<reqwest::Error> for SyncError {
+    fn from(err: reqwest::Error) -> Self {
+        Self::Http(err.to_string())
+    }
+}
+
+/// A signed-in account ratings are pushed to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Account {
+    pub site: SyncSite,
+    /// Username on the site, shown in the app.
+    pub name: String,
+    pub access_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// Unix time the access token expires at.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Accounts {
+    pub accounts: Vec<Account>,
+}
+
+impl Accounts {
+    /// Reads the saved accounts, or none when the file is missing or unreadable.
+    pub fn load() -> Self {
+        config::read_data_file(ACCOUNTS_FILE_NAME)
+            .and_then(|content| String::from_utf8(content).ok())
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = config::data_file(ACCOUNTS_FILE_NAME)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+        let content = toml::to_string_pretty(self).map_err(io::Error::other)?;
+        config::write_private(&path, content.as_bytes())
+    }
+
+    /// Adds `account`, replacing an earlier one for the same site.
+    pub fn connect(&mut self, account: Account) {
+        self.accounts
+            .retain(|existing| existing.site != account.site);
+        self.accounts.push(account);
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Signs in to MyAnimeList in the browser and returns the account.
+pub async fn connect_mal(client: &Client, client_id: &str) -> Result<Account, SyncError> {
+    let mut rng = rand::rng();
+    // PKCE verifier, sent as-is since MyAnimeList only supports the `plain` method.
+    let verifier: String = (0..64)
+        .map(|_| char::from(b"abcdefghijklmnopqrstuvwxyz0123456789"[rng.random_range(0..36)]))
+        .collect();
+    let state: String = (0..16)
+        .map(|_| format!("{:02x}", rng.random::<u8>()))
+        .collect();
+    let url = reqwest::Url::parse_with_params(
+        MAL_AUTHORIZE_URL,
+        [
+            ("response_type", "code"),
+            ("client_id", client_id),
+            ("code_challenge", &verifier),
+            ("code_challenge_method", "plain"),
+            ("state", &state),
+            ("redirect_uri", MAL_REDIRECT_URI),
+        ],
+    )
+    .map_err(|err| SyncError::Http(err.to_string()))?;
+
+    let listener = TcpListener::bind(("127.0.0.1", MAL_REDIRECT_PORT))?;
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(wait_for_code(&listener, &state));
+    });
+    open::that(url.as_str())?;
+    let code = receiver
+        .await
+        .map_err(|_| SyncError::Http("sign-in was interrupted".to_string()))??;
+
+    let token: MalToken = client
+        .post(MAL_TOKEN_URL)
+        .form(&[
+            ("client_id", client_id),
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("code_verifier", &verifier),
+            ("redirect_uri", MAL_REDIRECT_URI),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let me: MalUser = client
+        .get(format!("{MAL_API}/users/@me"))
+        .bearer_auth(&token.access_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(token.into_account(me.name))
+}
+
+#[derive(Deserialize)]
+struct MalToken {
+    access_token: String,
+    refresh_token: String,
+    expires_in: u64,
+}
+
+impl MalToken {
+    fn into_account(self, name: String) -> Account {
+        Account {
+            site: SyncSite::MyAnimeList,
+            name,
+            access_token: self.access_token,
+            refresh_token: Some(self.refresh_token),
+            expires_at: Some(now() + self.expires_in),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct MalUser {
+    name: String,
+}
+
+/// Answers the browser's redirect to the loopback address and returns the authorization code.
+fn wait_for_code(listener: &TcpListener, state: &str) -> Result<String, SyncError> {
+    listener.set_nonblocking(true)?;
+    let deadline = Instant::now() + AUTHORIZE_TIMEOUT;
+    loop {
+        let mut stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                if Instant::now() > deadline {
+                    return Err(SyncError::Http("timed out waiting for sign-in".to_string()));
+                }
+                std::thread::sleep(Duration::from_millis(200));
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+        let mut request_line = String::new();
+        // A connection that sends nothing (a browser's speculative preconnect) is dropped rather
+        // than holding up the real redirect.
+        if BufReader::new(&stream)
+            .read_line(&mut request_line)
+            .is_err()
+        {
+            continue;
+        }
+        // `GET /callback?code=…&state=… HTTP/1.1`; anything else (a favicon) is ignored.
+        let callback = request_line
+            .split_whitespace()
+            .nth(1)
+            .filter(|target| target.starts_with("/callback?"))
+            .and_then(|target| reqwest::Url::parse(&format!("http://localhost{target}")).ok());
+        let Some(callback) = callback else {
+            let _ = stream.write_all(b"HTTP/1.1 404 Not Found\r\nConnection: close\r\n\r\n");
+            continue;
+        };
+        let param = |name: &str| {
+            callback
+                .query_pairs()
+                .find_map(|(key, value)| (key == name).then(|| value.into_owned()))
+        };
+        let result = match (param("code"), param("state"), param("error")) {
+            (_, _, Some(error)) => Err(SyncError::Http(format!("sign-in refused: {error}"))),
+            (Some(code), Some(returned), None) if returned == state => Ok(code),
+            _ => Err(SyncError::Http("unexpected sign-in response".to_string())),
+        };
+        let message = match &result {
+            Ok(_) => "Signed in. You can close this tab and return to the app.",
+            Err(_) => "Sign-in failed. Return to the app for details.",
+        };
+        let _ = write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nConnection: close\r\n\r\n<!doctype html><title>What Anime Should I Watch</title><p>{message}</p>"
+        );
+        return result;
+    }
+}
+
+/// The AniList page that shows a token to paste into [`connect_anilist`].
+pub fn anilist_authorize_url(client_id: &str) -> Result<reqwest::Url, SyncError> {
+    reqwest::Url::parse_with_params(
+        ANILIST_AUTHORIZE_URL,
+        [("client_id", client_id), ("response_type", "token")],
+    )
+    .map_err(|err| SyncError::Http(err.to_string()))
+}
+
+/// Checks a pasted AniList token and returns the account it belongs to.
+pub async fn connect_anilist(client: &Client, token: &str) -> Result<Account, SyncError> {
+    let token = token.trim();
+    let data = anilist_query(client, token, "query { Viewer { name } }", json!({})).await?;
+    let name = data["Viewer"]["name"]
+        .as_str()
+        .ok_or_else(|| SyncError::Http("AniList didn't return the account".to_string()))?;
+    Ok(Account {
+        site: SyncSite::AniList,
+        name: name.to_string(),
+        access_token: token.to_string(),
+        refresh_token: None,
+        // AniList tokens last a year and can't be refreshed; an expired one fails on push.
+        expires_at: None,
+    })
+}
+
+async fn anilist_query(
+    client: &Client,
+    token: &str,
+    query: &str,
+    variables: serde_json::Value,
+) -> Result<serde_json::Value, SyncError> {
+    let mut body: serde_json::Value = client
+        .post(ANILIST_ENDPOINT)
+        .bearer_auth(token)
+        .json(&json!({ "query": query, "variables": variables }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    if let Some(message) = body["errors"][0]["message"].as_str() {
+        return Err(SyncError::Http(message.to_string()));
+    }
+    Ok(body["data"].take())
+}
+
+/// Marks `anime_id` (a MyAnimeList id) completed with `score` out of 10 on the account's list.
+/// MyAnimeList tokens are refreshed first when about to expire, updating `account`.
+pub async fn push_rating(
+    client: &Client,
+    account: &mut Account,
+    mal_client_id: Option<&str>,
+    anime_id: u32,
+    score: u8,
+) -> Result<(), SyncError> {
+    match account.site {
+        SyncSite::MyAnimeList => {
+            if account
+                .expires_at
+                .is_some_and(|expires_at| expires_at <= now() + REFRESH_MARGIN_SECS)
+            {
+                let client_id = mal_client_id.ok_or(SyncError::NoClientId(account.site))?;
+                refresh_mal(client, account, client_id).await?;
+            }
+            client
+                .patch(format!("{MAL_API}/anime/{anime_id}/my_list_status"))
+                .bearer_auth(&account.access_token)
+                .form(&[("status", "completed"), ("score", &score.to_string())])
+                .send()
+                .await?
+                .error_for_status()?;
+            Ok(())
+        }
+        SyncSite::AniList => {
+            let token = &account.access_token;
+            let media = anilist_query(
+                client,
+                token,
+                "query ($idMal: Int) { Media(idMal: $idMal, type: ANIME) { id } }",
+                json!({ "idMal": anime_id }),
+            )
+            .await?;
+            let media_id = media["Media"]["id"].as_u64().ok_or_else(|| {
+                SyncError::Http(format!(
+                    "AniList has no anime with MyAnimeList id {anime_id}"
+                ))
+            })?;
+            // `scoreRaw` is out of 100 whatever score format the account displays.
+            anilist_query(
+                client,
+                token,
+                "mutation ($mediaId: Int, $score: Int) { SaveMediaListEntry(mediaId: $mediaId, status: COMPLETED, scoreRaw: $score) { id } }",
+                json!({ "mediaId": media_id, "score": u32::from(score) * 10 }),
+            )
+            .await?;
+            Ok(())
+        }
+    }
+}
+
+async fn refresh_mal(
+    client: &Client,
+    account: &mut Account,
+    client_id: &str,
+) -> Result<(), SyncError> {
+    let refresh_token = account.refresh_token.clone().ok_or_else(|| {
+        SyncError::Http("the MyAnimeList sign-in expired; connect the account again".to_string())
+    })?;
+    let token: MalToken = client
+        .post(MAL_TOKEN_URL)
+        .form(&[
+            ("client_id", client_id),
+            ("grant_type", "refresh_token"),
+            ("refresh_token", &refresh_token),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    *account = token.into_account(account.name.clone());
+    Ok(())
+}
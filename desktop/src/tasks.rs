@@ -0,0 +1,268 @@
+//! Background jobs: imports, metadata and season fetches, dataset reloads, preparing the search
+//! model, computing layouts and finding taste tribes run through here, so each is listed in the side panel with its
+//! progress while it runs and can be cancelled from there. Jobs are either futures on the UI
+//! thread ([`spawn_async`]) or work on a worker thread whose result is handed back to the UI
+//! thread ([`spawn_blocking`]).
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use dioxus::core::Task;
+use dioxus::prelude::*;
+use tokio::sync::mpsc::UnboundedSender;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskKind {
+    Import,
+    Reload,
+    Metadata,
+    Season,
+    #[cfg(feature = "semantic")]
+    Search,
+    Layout,
+    Tribes,
+}
+
+/// Set when a job is cancelled. Async jobs are dropped at their next `.await`; work on a worker
+/// thread checks [`Worker::is_cancelled`] between steps, and its result is discarded either way.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+pub struct Job {
+    pub id: u64,
+    pub kind: TaskKind,
+    pub name: String,
+    /// The latest progress line, e.g. `page 3 of 12`.
+    pub progress: Option<String>,
+    /// How much is done, from 0 to 1, for jobs that know.
+    pub fraction: Option<f32>,
+    pub started: Instant,
+    task: Option<Task>,
+    token: CancelToken,
+}
+
+impl Job {
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+/// The running jobs, oldest first.
+#[derive(Default)]
+pub struct TaskList {
+    next_id: u64,
+    jobs: Vec<Job>,
+}
+
+impl TaskList {
+    pub fn jobs(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    pub fn is_running(&self, kind: TaskKind) -> bool {
+        self.jobs.iter().any(|job| job.kind == kind)
+    }
+
+    pub fn cancel(&mut self, id: u64) {
+        if let Some(position) = self.jobs.iter().position(|job| job.id == id) {
+            let job = self.jobs.remove(position);
+            tracing::info!(kind = ?job.kind, "cancelled {}", job.name);
+            job.token.cancel();
+            if let Some(task) = job.task {
+                task.cancel();
+            }
+        }
+    }
+
+    /// Cancels every job of `kind`, for jobs a newer one supersedes.
+    pub fn cancel_kind(&mut self, kind: TaskKind) {
+        let ids: Vec<u64> = self
+            .jobs
+            .iter()
+            .filter(|job| job.kind == kind)
+            .map(|job| job.id)
+            .collect();
+        for id in ids {
+            self.cancel(id);
+        }
+    }
+
+    fn start(&mut self, kind: TaskKind, name: String) -> (u64, CancelToken) {
+        self.next_id += 1;
+        let token = CancelToken::default();
+        tracing::debug!(kind = ?kind, "started {name}");
+        self.jobs.push(Job {
+            id: self.next_id,
+            kind,
+            name,
+            progress: None,
+            fraction: None,
+            started: Instant::now(),
+            task: None,
+            token: token.clone(),
+        });
+        (self.next_id, token)
+    }
+
+    fn attach(&mut self, id: u64, task: Task) {
+        match self.jobs.iter_mut().find(|job| job.id == id) {
+            Some(job) => job.task = Some(task),
+            // Cancelled before it was spawned.
+            None => task.cancel(),
+        }
+    }
+
+    fn set_progress(&mut self, id: u64, line: String) {
+        if let Some(job) = self.jobs.iter_mut().find(|job| job.id == id) {
+            job.progress = Some(line);
+        }
+    }
+
+    fn set_fraction(&mut self, id: u64, fraction: f32) {
+        if let Some(job) = self.jobs.iter_mut().find(|job| job.id == id) {
+            job.fraction = Some(fraction.clamp(0.0, 1.0));
+        }
+    }
+
+    fn finish(&mut self, id: u64) {
+        self.jobs.retain(|job| job.id != id);
+    }
+}
+
+/// Reports an async job's progress to the task list.
+#[derive(Clone, Copy)]
+pub struct Progress {
+    tasks: Signal<TaskList>,
+    id: u64,
+}
+
+impl Progress {
+    pub fn report(mut self, line: impl Into<String>) {
+        self.tasks.write().set_progress(self.id, line.into());
+    }
+
+    /// [`Self::report`], with how much is done from 0 to 1 for a progress bar.
+    pub fn report_fraction(mut self, line: impl Into<String>, fraction: f32) {
+        let mut tasks = self.tasks.write();
+        tasks.set_progress(self.id, line.into());
+        tasks.set_fraction(self.id, fraction);
+    }
+}
+
+/// Handed to work running on a worker thread.
+pub struct Worker<T> {
+    sender: UnboundedSender<Update<T>>,
+    token: CancelToken,
+}
+
+impl<T> Worker<T> {
+    pub fn progress(&self, line: impl Into<String>) {
+        let _ = self.sender.send(Update::Progress(line.into()));
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+enum Update<T> {
+    Progress(String),
+    Done(T),
+}
+
+/// Runs the future `job` builds as a listed job on the UI thread.
+pub fn spawn_async<F>(
+    mut tasks: Signal<TaskList>,
+    kind: TaskKind,
+    name: impl Into<String>,
+    job: impl FnOnce(Progress) -> F,
+) where
+    F: Future<Output = ()> + 'static,
+{
+    let (id, _) = tasks.write().start(kind, name.into());
+    let future = job(Progress { tasks, id });
+    let task = spawn(async move {
+        future.await;
+        tasks.write().finish(id);
+    });
+    tasks.write().attach(id, task);
+}
+
+/// Runs `work` as a listed job on a worker thread, then `done` with its result on the UI thread
+/// unless the job was cancelled first. `done` gets `None` if the work panicked.
+pub fn spawn_blocking<T: Send + 'static>(
+    mut tasks: Signal<TaskList>,
+    kind: TaskKind,
+    name: impl Into<String>,
+    work: impl FnOnce(&Worker<T>) -> T + Send + 'static,
+    done: impl FnOnce(Option<T>) + 'static,
+) {
+    let name = name.into();
+    let (id, token) = tasks.write().start(kind, name.clone());
+    let (sender, mut updates) = tokio::sync::mpsc::unbounded_channel();
+    let worker = Worker { sender, token };
+    std::thread::spawn(move || {
+        let result = work(&worker);
+        let _ = worker.sender.send(Update::Done(result));
+    });
+    let task = spawn(async move {
+        let result = loop {
+            match updates.recv().await {
+                Some(Update::Progress(line)) => tasks.write().set_progress(id, line),
+                Some(Update::Done(result)) => break Some(result),
+                None => {
+                    tracing::warn!("{name} stopped without finishing");
+                    break None;
+                }
+            }
+        };
+        tasks.write().finish(id);
+        done(result);
+    });
+    tasks.write().attach(id, task);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancelling_removes_the_job_and_trips_its_token() {
+        let mut tasks = TaskList::default();
+        let (import, import_token) = tasks.start(TaskKind::Import, "Importing".to_string());
+        let (first_layout, first_token) = tasks.start(TaskKind::Layout, "Laying out".to_string());
+        let (second_layout, second_token) = tasks.start(TaskKind::Layout, "Again".to_string());
+        assert!(import < first_layout && first_layout < second_layout);
+
+        tasks.set_progress(import, "page 3 of 12".to_string());
+        tasks.set_fraction(import, 1.5);
+        assert_eq!(tasks.jobs()[0].progress.as_deref(), Some("page 3 of 12"));
+        assert_eq!(tasks.jobs()[0].fraction, Some(1.0));
+
+        tasks.cancel_kind(TaskKind::Layout);
+        assert!(first_token.is_cancelled() && second_token.is_cancelled());
+        assert!(!tasks.is_running(TaskKind::Layout));
+        assert!(tasks.is_running(TaskKind::Import));
+
+        // Finishing, unlike cancelling, leaves the token alone; unknown ids are ignored.
+        tasks.finish(import);
+        tasks.cancel(import);
+        assert!(!import_token.is_cancelled());
+        assert!(tasks.jobs().is_empty());
+
+        let (next, _) = tasks.start(TaskKind::Tribes, "Finding tribes".to_string());
+        assert!(next > second_layout);
+    }
+}
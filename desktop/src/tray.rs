@@ -0,0 +1,152 @@
+//! The tray icon, for keeping the app around in the background as a quick recommendation
+//! utility: Recommend something now, which shows one pick in a small toast window, Open and
+//! Quit. Closing the main window hides it while the tray is on.
+
+use std::time::Duration;
+
+use dioxus::desktop::tao::dpi::{LogicalSize, PhysicalPosition};
+use dioxus::desktop::tao::window::WindowBuilder;
+use dioxus::desktop::trayicon::menu::{Menu, MenuItem, PredefinedMenuItem};
+use dioxus::desktop::trayicon::{Icon, TrayIcon, TrayIconBuilder};
+use dioxus::desktop::{Config, WindowCloseBehaviour};
+use dioxus::prelude::*;
+use wasiw_core::recommend::Recommendation;
+use wasiw_ui::i18n::tr;
+use wasiw_ui::theme;
+
+use crate::config::AppConfig;
+use crate::state::AppState;
+
+pub const RECOMMEND_ID: &str = "tray-recommend";
+pub const OPEN_ID: &str = "tray-open";
+pub const QUIT_ID: &str = "tray-quit";
+
+/// Recommend something now cycles through this many of the best picks.
+const PICKS: usize = 5;
+/// How long a toast stays up unless clicked away.
+const TOAST_SECONDS: u64 = 8;
+const TOAST_WIDTH: f64 = 340.0;
+const TOAST_HEIGHT: f64 = 96.0;
+/// Gap between the toast and the screen corner.
+const TOAST_MARGIN: f64 = 24.0;
+
+/// The tray icon and its menu. Dropping it removes the icon.
+pub fn build(tooltip: &str) -> Result<TrayIcon, String> {
+    let menu = Menu::new();
+    menu.append_items(&[
+        &MenuItem::with_id(RECOMMEND_ID, tr("tray-recommend"), true, None),
+        &MenuItem::with_id(OPEN_ID, tr("tray-open"), true, None),
+        &PredefinedMenuItem::separator(),
+        &MenuItem::with_id(QUIT_ID, tr("tray-quit"), true, None),
+    ])
+    .map_err(|err| err.to_string())?;
+    let mut tray = TrayIconBuilder::new()
+        .with_menu(Box::new(menu))
+        .with_menu_on_left_click(true)
+        .with_tooltip(tooltip);
+    match dioxus::desktop::default_icon::<Icon>() {
+        Ok(icon) => tray = tray.with_icon(icon),
+        Err(err) => tracing::debug!("no tray icon image: {err}"),
+    }
+    tray.build().map_err(|err| err.to_string())
+}
+
+/// Hides the main window on close while the tray is on, so the app keeps running in it.
+pub fn set_keep_running(keep_running: bool) {
+    dioxus::desktop::window().set_close_behavior(if keep_running {
+        WindowCloseBehaviour::WindowHides
+    } else {
+        WindowCloseBehaviour::WindowCloses
+    });
+}
+
+/// Brings the main window back from the tray.
+pub fn show_window() {
+    let window = dioxus::desktop::window();
+    window.set_visible(true);
+    window.set_minimized(false);
+    window.set_focus();
+}
+
+/// The `index`th of the best [`PICKS`] for the active profile, or for the selected user
+/// without one, skipping anime the profile hid. `None` without a user or recommendations.
+pub fn pick(app: AppState, index: usize) -> Option<Recommendation> {
+    let active = app
+        .profiles
+        .peek()
+        .active()
+        .map(|profile| profile.user_id());
+    let user = match active {
+        Some(user_id) => app.find_user_node(&user_id)?,
+        None => app
+            .selected
+            .cloned()
+            .filter(|&idx| app.graph.peek().nodes[idx].id.starts_with("user:"))?,
+    };
+    let model = app.graph.peek();
+    let hidden = app
+        .profiles
+        .peek()
+        .for_user(model.nodes[user].id.trim_start_matches("user:"))
+        .map(|profile| profile.blacklist.clone())
+        .unwrap_or_default();
+    let picks: Vec<_> = app
+        .recommend(&model, user, PICKS + hidden.len())
+        .into_iter()
+        .filter(|recommendation| !hidden.contains(&recommendation.anime_id))
+        .take(PICKS)
+        .collect();
+    let count = picks.len();
+    picks.into_iter().nth(index.checked_rem(count)?)
+}
+
+/// Shows `title` and `detail` in a small window in the corner of the screen, above other
+/// windows, until it times out or is clicked.
+pub fn show_toast(title: String, detail: String) {
+    let main = dioxus::desktop::window();
+    let mut window = WindowBuilder::new()
+        .with_title(title.clone())
+        .with_decorations(false)
+        .with_always_on_top(true)
+        .with_resizable(false)
+        .with_focused(false)
+        .with_inner_size(LogicalSize::new(TOAST_WIDTH, TOAST_HEIGHT));
+    if let Some(monitor) = main.primary_monitor().or_else(|| main.current_monitor()) {
+        let scale = monitor.scale_factor();
+        let corner = monitor.position();
+        let size = monitor.size();
+        window = window.with_position(PhysicalPosition::new(
+            f64::from(corner.x) + f64::from(size.width) - (TOAST_WIDTH + TOAST_MARGIN) * scale,
+            f64::from(corner.y) + TOAST_MARGIN * scale,
+        ));
+    }
+    let dom = VirtualDom::new_with_props(Toast, ToastProps { title, detail });
+    main.new_window(dom, Config::new().with_window(window).with_menu(None));
+}
+
+/// The toast window's content; it closes itself.
+#[component]
+fn Toast(title: String, detail: String) -> Element {
+    use_hook(|| {
+        spawn(async {
+            tokio::time::sleep(Duration::from_secs(TOAST_SECONDS)).await;
+            dioxus::desktop::window().close();
+        })
+    });
+    let config = AppConfig::load();
+    let theme_class = config
+        .theme
+        .resolve(crate::os_theme(dioxus::desktop::window().theme()))
+        .class();
+    let scale_class = config.ui_scale.class();
+    rsx! {
+        style { {theme::APP_CSS} }
+        main {
+            class: "app toast {theme_class} {scale_class}",
+            role: "status",
+            onclick: |_| dioxus::desktop::window().close(),
+            strong { "{title}" }
+            p { class: "tiny", "{detail}" }
+        }
+    }
+}
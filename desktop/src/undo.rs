@@ -0,0 +1,69 @@
+//! Undo and redo for changes to how the graph is built and shown: its layout, filter and
+//! sampling. Kept for the session only.
+
+use wasiw_core::sampling::Sampling;
+use wasiw_core::{GraphFilter, GraphModel, LayoutKind};
+
+/// Changes kept to undo, oldest dropped first.
+const UNDO_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GraphView {
+    pub layout: LayoutKind,
+    pub layout_seed: u64,
+    pub filter: GraphFilter,
+    pub sampling: Sampling,
+}
+
+impl GraphView {
+    pub fn of(graph: &GraphModel) -> Self {
+        Self {
+            layout: graph.layout,
+            layout_seed: graph.layout_seed,
+            filter: graph.filter,
+            sampling: graph.sampling,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct UndoStack {
+    past: Vec<GraphView>,
+    future: Vec<GraphView>,
+    current: Option<GraphView>,
+}
+
+impl UndoStack {
+    /// Notes the view after a change, making the one before it undoable. Seeing the view undo
+    /// or redo just returned records nothing.
+    pub fn observe(&mut self, view: GraphView) {
+        match self.current.replace(view) {
+            Some(previous) if previous != view => {
+                self.past.push(previous);
+                if self.past.len() > UNDO_LIMIT {
+                    self.past.remove(0);
+                }
+                self.future.clear();
+            }
+            _ => {}
+        }
+    }
+
+    /// The view to go back to.
+    pub fn undo(&mut self) -> Option<GraphView> {
+        let previous = self.past.pop()?;
+        if let Some(current) = self.current.replace(previous) {
+            self.future.push(current);
+        }
+        Some(previous)
+    }
+
+    /// The view undo last went back from.
+    pub fn redo(&mut self) -> Option<GraphView> {
+        let next = self.future.pop()?;
+        if let Some(current) = self.current.replace(next) {
+            self.past.push(current);
+        }
+        Some(next)
+    }
+}
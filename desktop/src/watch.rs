@@ -0,0 +1,60 @@
+//! Watches the loaded dataset file so the graph can be rebuilt when an external pipeline
+//! regenerates it.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+
+/// Writers often touch a file several times in a row; reload once things go quiet.
+pub const DEBOUNCE: Duration = Duration::from_millis(400);
+
+/// Keeps the OS watch alive; dropping it stops the notifications.
+pub struct DatasetWatcher {
+    _watcher: notify::RecommendedWatcher,
+    changes: mpsc::UnboundedReceiver<()>,
+}
+
+impl DatasetWatcher {
+    /// Watches the file's directory rather than the file itself, because pipelines usually
+    /// replace the file (write to a temp file, then rename), which ends a watch on the inode.
+    pub fn new(path: &Path) -> notify::Result<Self> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let dir = path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."));
+        let (sender, changes) = mpsc::unbounded_channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                let relevant = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                    && event.paths.contains(&path);
+                if relevant {
+                    let _ = sender.send(());
+                }
+            })?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+        Ok(Self {
+            _watcher: watcher,
+            changes,
+        })
+    }
+
+    /// Waits for the next burst of changes to finish. Returns `false` once the watch ended.
+    pub async fn changed(&mut self) -> bool {
+        if self.changes.recv().await.is_none() {
+            return false;
+        }
+        loop {
+            match tokio::time::timeout(DEBOUNCE, self.changes.recv()).await {
+                Ok(Some(())) => continue,
+                Ok(None) => return false,
+                Err(_) => return true,
+            }
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! The main window's size, position, maximized state and monitor, saved to `window.json` in the
+//! platform data directory when it closes and applied when it opens. A window saved on a monitor
+//! that is gone, or that no longer covers the saved position, is moved onto one that exists.
+
+use std::io;
+
+use dioxus::desktop::tao::dpi::{LogicalSize, PhysicalPosition};
+use dioxus::desktop::tao::monitor::MonitorHandle;
+use dioxus::desktop::tao::window::{Window, WindowBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+
+const WINDOW_FILE_NAME: &str = "window.json";
+/// Size of the first window, in logical pixels: room for the side panel next to the canvas.
+const DEFAULT_WIDTH: f64 = 1360.0;
+const DEFAULT_HEIGHT: f64 = 880.0;
+/// Smallest saved size that is restored, so a window shrunk to nothing comes back usable.
+const MIN_WIDTH: f64 = 480.0;
+const MIN_HEIGHT: f64 = 360.0;
+/// Pixels of the window's top-left corner that must be on a monitor to leave it where it is.
+const VISIBLE_MARGIN: i32 = 48;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowState {
+    /// Inner size in logical pixels, from before the window was maximized.
+    pub width: f64,
+    pub height: f64,
+    /// Outer top-left corner in physical pixels on the virtual desktop.
+    pub x: Option<i32>,
+    pub y: Option<i32>,
+    pub maximized: bool,
+    /// Name of the monitor the window was on, as the OS reports it.
+    pub monitor: Option<String>,
+}
+
+impl Default for WindowState {
+    fn default() -> Self {
+        Self {
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            x: None,
+            y: None,
+            maximized: false,
+            monitor: None,
+        }
+    }
+}
+
+impl WindowState {
+    /// The saved state, or the default size when there is none or it is unreadable.
+    pub fn load() -> Self {
+        config::read_data_file(WINDOW_FILE_NAME)
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let content = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        config::write_data_file(WINDOW_FILE_NAME, &content)
+    }
+
+    /// `window` sized, placed and maximized as saved. Whether the position is still on screen
+    /// can only be checked once the window exists; see [`Self::keep_on_screen`].
+    pub fn apply(&self, window: WindowBuilder) -> WindowBuilder {
+        let window = window
+            .with_inner_size(LogicalSize::new(
+                self.width.max(MIN_WIDTH),
+                self.height.max(MIN_HEIGHT),
+            ))
+            .with_maximized(self.maximized);
+        match (self.x, self.y) {
+            (Some(x), Some(y)) => window.with_position(PhysicalPosition::new(x, y)),
+            _ => window,
+        }
+    }
+
+    /// The state of `window` as it is now. While it is maximized, the size from `previous` is
+    /// kept, so un-maximizing after the next launch returns to it.
+    pub fn capture(window: &Window, previous: &Self) -> Self {
+        let maximized = window.is_maximized();
+        let monitor = window.current_monitor().and_then(|monitor| monitor.name());
+        if maximized {
+            return Self {
+                maximized,
+                monitor: monitor.or_else(|| previous.monitor.clone()),
+                ..previous.clone()
+            };
+        }
+        let size = window.inner_size().to_logical::<f64>(window.scale_factor());
+        let position = window.outer_position().ok();
+        Self {
+            width: size.width,
+            height: size.height,
+            x: position.map(|p| p.x),
+            y: position.map(|p| p.y),
+            maximized,
+            monitor,
+        }
+    }
+
+    /// Moves the window onto a monitor when the saved position isn't on any: onto the saved
+    /// monitor if it is still connected (its resolution or arrangement changed), else centered
+    /// on the primary monitor (it was unplugged).
+    pub fn keep_on_screen(&self, window: &Window) {
+        let (Some(x), Some(y)) = (self.x, self.y) else {
+            return;
+        };
+        let monitors: Vec<MonitorHandle> = window.available_monitors().collect();
+        if monitors.iter().any(|monitor| covers(monitor, x, y)) {
+            return;
+        }
+        let saved = self.monitor.as_ref().and_then(|name| {
+            monitors
+                .iter()
+                .find(|monitor| monitor.name().as_ref() == Some(name))
+        });
+        let Some(target) = saved.cloned().or_else(|| window.primary_monitor()) else {
+            return;
+        };
+        let origin = target.position();
+        let area = target.size();
+        let size = window.outer_size();
+        let centered = |area: u32, size: u32| (area.saturating_sub(size) / 2) as i32;
+        window.set_outer_position(PhysicalPosition::new(
+            origin.x + centered(area.width, size.width),
+            origin.y + centered(area.height, size.height),
+        ));
+    }
+}
+
+/// Whether enough of a window with its top-left corner at `(x, y)` shows on `monitor` to grab.
+fn covers(monitor: &MonitorHandle, x: i32, y: i32) -> bool {
+    let origin = monitor.position();
+    let size = monitor.size();
+    area_covers((origin.x, origin.y), (size.width, size.height), x, y)
+}
+
+/// [`covers`] for a monitor at `origin` of `size` physical pixels.
+fn area_covers(origin: (i32, i32), size: (u32, u32), x: i32, y: i32) -> bool {
+    let right = origin.0 + size.0 as i32;
+    let bottom = origin.1 + size.1 as i32;
+    x + VISIBLE_MARGIN > origin.0
+        && x + VISIBLE_MARGIN < right
+        && y >= origin.1 - VISIBLE_MARGIN
+        && y + VISIBLE_MARGIN < bottom
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_count_as_on_screen_while_their_corner_can_be_grabbed() {
+        // A 1920×1080 monitor to the right of the primary one.
+        let (origin, size) = ((1920, 0), (1920, 1080));
+        assert!(area_covers(origin, size, 2000, 100));
+        assert!(area_covers(origin, size, 1900, -40));
+        assert!(!area_covers(origin, size, 100, 100));
+        assert!(!area_covers(origin, size, 3830, 100));
+        assert!(!area_covers(origin, size, 2000, 1050));
+        assert!(!area_covers(origin, size, 2000, -60));
+    }
+
+    #[test]
+    fn saved_states_round_trip() {
+        let state = WindowState {
+            width: 1024.0,
+            height: 700.0,
+            x: Some(-1800),
+            y: Some(40),
+            maximized: true,
+            monitor: Some("DP-2".to_string()),
+        };
+        let content = serde_json::to_vec_pretty(&state).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<WindowState>(&content).unwrap(),
+            state
+        );
+        assert_eq!(WindowState::default().x, None);
+    }
+}
@@ -0,0 +1,356 @@
+//! Python bindings for `wasiw-core`, so notebooks run exactly the engine the desktop app and
+//! the CLI do.
+//!
+//! ```python
+//! import wasiw
+//!
+//! dataset = wasiw.Dataset.load("data/anonymized-ratings.json")
+//! print(dataset.report)
+//! engine = dataset.fit()
+//! for rec in engine.recommend("0123abcd", top=10):
+//!     print(f"{rec.score:+.2f} {rec.title}  ({rec.explanation})")
+//! engine.similar(5114)
+//! ```
+//!
+//! Loading and fitting release the GIL, so they can run on a thread pool.
+
+use std::path::PathBuf;
+
+use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use wasiw_core::recommend::{self, Recommendation, SimilarAnime};
+use wasiw_core::stats::Stats;
+use wasiw_core::{
+    dataset, Dataset, DatasetError, EdgeKind, FindUserError, GraphModel, MergeStrategy, NodeType,
+    QualityReport, Viewport,
+};
+
+/// Explanation entries per direction in `Recommendation.explanation`.
+const EXPLAINED_CONTRIBUTORS: usize = 3;
+
+fn dataset_error(err: DatasetError) -> PyErr {
+    match err {
+        DatasetError::Io(err) => err.into(),
+        other => PyValueError::new_err(other.to_string()),
+    }
+}
+
+fn merge_strategy(name: &str) -> PyResult<MergeStrategy> {
+    match name {
+        "latest-wins" => Ok(MergeStrategy::LatestWins),
+        "highest" => Ok(MergeStrategy::Highest),
+        "average" => Ok(MergeStrategy::Average),
+        _ => Err(PyValueError::new_err(format!(
+            "unknown duplicates strategy {name:?}; expected \"latest-wins\", \"highest\" or \"average\""
+        ))),
+    }
+}
+
+/// Dataset JSON as text or bytes (plain, gzip or zstd).
+#[derive(FromPyObject)]
+enum JsonInput<'py> {
+    Text(String),
+    Bytes(Bound<'py, PyBytes>),
+}
+
+/// Ratings loaded and checked the way every frontend loads them.
+#[pyclass(name = "Dataset", module = "wasiw", frozen)]
+struct PyDataset {
+    inner: Dataset,
+    report: QualityReport,
+}
+
+#[pymethods]
+impl PyDataset {
+    /// Reads `anonymized-ratings.json`, optionally `.gz`/`.zst` compressed (or Parquet, when
+    /// built with the `parquet` feature). `duplicates` is `"latest-wins"`, `"highest"` or
+    /// `"average"`.
+    #[staticmethod]
+    #[pyo3(signature = (path, duplicates = "latest-wins"))]
+    fn load(py: Python<'_>, path: PathBuf, duplicates: &str) -> PyResult<Self> {
+        let duplicates = merge_strategy(duplicates)?;
+        let (inner, report) = py
+            .allow_threads(|| dataset::read(&path, duplicates))
+            .map_err(dataset_error)?;
+        Ok(Self { inner, report })
+    }
+
+    /// Parses a dataset already in memory, e.g. downloaded with `requests`.
+    #[staticmethod]
+    #[pyo3(signature = (data, duplicates = "latest-wins"))]
+    fn from_json(py: Python<'_>, data: JsonInput<'_>, duplicates: &str) -> PyResult<Self> {
+        let duplicates = merge_strategy(duplicates)?;
+        let bytes = match &data {
+            JsonInput::Text(text) => text.as_bytes(),
+            JsonInput::Bytes(bytes) => bytes.as_bytes(),
+        };
+        let (inner, report) = py
+            .allow_threads(|| dataset::parse(bytes, duplicates))
+            .map_err(dataset_error)?;
+        Ok(Self { inner, report })
+    }
+
+    #[getter]
+    fn user_count(&self) -> usize {
+        self.inner.users.len()
+    }
+
+    #[getter]
+    fn rating_count(&self) -> usize {
+        self.inner.users.iter().map(|user| user.ratings.len()).sum()
+    }
+
+    /// What was dropped or fixed on load, as one sentence.
+    #[getter]
+    fn report(&self) -> String {
+        self.report.summary()
+    }
+
+    /// Builds the rating graph with its similarity edges and communities.
+    fn fit(&self, py: Python<'_>) -> Engine {
+        let (graph, stats) =
+            py.allow_threads(|| Stats::build(self.inner.clone(), Viewport::default(), |_| {}));
+        Engine { graph, stats }
+    }
+
+    fn __len__(&self) -> usize {
+        self.user_count()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "<Dataset: {} users, {} ratings>",
+            self.user_count(),
+            self.rating_count()
+        )
+    }
+}
+
+/// A fitted graph: recommendations and similarity queries.
+#[pyclass(module = "wasiw", frozen)]
+struct Engine {
+    graph: GraphModel,
+    stats: Stats,
+}
+
+impl Engine {
+    fn anime_node(&self, anime_id: u32) -> PyResult<usize> {
+        self.graph
+            .find_anime(anime_id)
+            .ok_or_else(|| PyKeyError::new_err(format!("no anime with id {anime_id}")))
+    }
+}
+
+#[pymethods]
+impl Engine {
+    /// Every user id in the graph.
+    fn users(&self) -> Vec<String> {
+        self.graph
+            .nodes
+            .iter()
+            .filter(|node| node.node_type == NodeType::User)
+            .filter_map(|node| node.id.strip_prefix("user:").map(str::to_string))
+            .collect()
+    }
+
+    /// Ranked recommendations for a user id, or an unambiguous prefix of one.
+    #[pyo3(signature = (user, top = 20))]
+    fn recommend(&self, user: &str, top: usize) -> PyResult<Vec<PyRecommendation>> {
+        let node = self.graph.find_user(user).map_err(|err| match err {
+            FindUserError::NotFound(_) => PyKeyError::new_err(err.to_string()),
+            FindUserError::Ambiguous(..) => PyValueError::new_err(err.to_string()),
+        })?;
+        Ok(recommend::recommend(&self.graph, node, top)
+            .into_iter()
+            .map(PyRecommendation::from)
+            .collect())
+    }
+
+    /// The anime most similar to another, by similarity-edge score.
+    #[pyo3(signature = (anime_id, top = 20))]
+    fn similar(&self, anime_id: u32, top: usize) -> PyResult<Vec<PySimilarAnime>> {
+        let node = self.anime_node(anime_id)?;
+        Ok(recommend::similar(&self.graph, node, top)
+            .into_iter()
+            .map(PySimilarAnime::from)
+            .collect())
+    }
+
+    /// Every anime–anime edge as `(anime_id, anime_id, score, co_raters)`, for loading into
+    /// pandas or networkx.
+    fn similarity_edges(&self) -> Vec<(u32, u32, f64, usize)> {
+        let nodes = &self.graph.nodes;
+        self.graph
+            .edges
+            .iter()
+            .filter(|edge| edge.kind == EdgeKind::Similarity)
+            .filter_map(|edge| {
+                Some((
+                    nodes[edge.source].anime_id()?,
+                    nodes[edge.target].anime_id()?,
+                    edge.weight,
+                    edge.co_raters,
+                ))
+            })
+            .collect()
+    }
+
+    /// The same counts as `wasiw stats --json`.
+    fn stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let stats = &self.stats;
+        let dict = PyDict::new(py);
+        dict.set_item("users", stats.users)?;
+        dict.set_item("anime", stats.anime)?;
+        dict.set_item("ratings", stats.ratings)?;
+        dict.set_item("mean_score", stats.mean_score)?;
+        dict.set_item("similarity_edges", stats.similarity_edges)?;
+        dict.set_item("communities", stats.communities)?;
+        dict.set_item("components", stats.components)?;
+        Ok(dict)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "<Engine: {} users, {} anime, {} similarity edges>",
+            self.stats.users, self.stats.anime, self.stats.similarity_edges
+        )
+    }
+}
+
+#[pyclass(name = "Recommendation", module = "wasiw", frozen, get_all)]
+struct PyRecommendation {
+    anime_id: u32,
+    title: String,
+    score: f64,
+    /// The largest single contribution to `score`.
+    strongest: f64,
+    /// Rated anime connected to this one by a similarity edge.
+    support_count: usize,
+    /// `(title, weighted score)` of the rated anime behind the score, strongest first.
+    contributions: Vec<(String, f64)>,
+    explanation: String,
+}
+
+impl From<Recommendation> for PyRecommendation {
+    fn from(recommendation: Recommendation) -> Self {
+        Self {
+            explanation: recommendation.explanation(EXPLAINED_CONTRIBUTORS),
+            anime_id: recommendation.anime_id,
+            title: recommendation.title,
+            score: recommendation.score,
+            strongest: recommendation.strongest,
+            support_count: recommendation.support_count,
+            contributions: recommendation
+                .contributions
+                .into_iter()
+                .map(|contribution| (contribution.title, contribution.weighted_score))
+                .collect(),
+        }
+    }
+}
+
+#[pymethods]
+impl PyRecommendation {
+    fn __repr__(&self) -> String {
+        format!(
+            "<Recommendation {} {:?} score={:+.2}>",
+            self.anime_id, self.title, self.score
+        )
+    }
+}
+
+#[pyclass(name = "SimilarAnime", module = "wasiw", frozen, get_all)]
+struct PySimilarAnime {
+    anime_id: u32,
+    title: String,
+    score: f64,
+    /// Users who rated both.
+    co_raters: usize,
+}
+
+impl From<SimilarAnime> for PySimilarAnime {
+    fn from(similar: SimilarAnime) -> Self {
+        Self {
+            anime_id: similar.anime_id,
+            title: similar.title,
+            score: similar.score,
+            co_raters: similar.co_raters,
+        }
+    }
+}
+
+#[pymethods]
+impl PySimilarAnime {
+    fn __repr__(&self) -> String {
+        format!(
+            "<SimilarAnime {} {:?} score={:+.2}>",
+            self.anime_id, self.title, self.score
+        )
+    }
+}
+
+#[pymodule]
+fn wasiw(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyDataset>()?;
+    module.add_class::<Engine>()?;
+    module.add_class::<PyRecommendation>()?;
+    module.add_class::<PySimilarAnime>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What `Dataset.from_json(...).fit()` gives, without an interpreter.
+    fn fit(json: &str) -> (PyDataset, Engine) {
+        let (inner, report) = dataset::parse(json.as_bytes(), merge_strategy("average").unwrap())
+            .map_err(|err| err.to_string())
+            .unwrap();
+        let (graph, stats) = Stats::build(inner.clone(), Viewport::default(), |_| {});
+        (PyDataset { inner, report }, Engine { graph, stats })
+    }
+
+    #[test]
+    fn fitted_datasets_answer_queries_with_python_friendly_values() {
+        let (dataset, engine) = fit(r#"{"users": [
+                {"userId": "alice", "ratings": [
+                    {"animeId": 1, "title": "Monster", "rawScore": 9},
+                    {"animeId": 2, "title": "Mushishi", "rawScore": 4}
+                ]},
+                {"userId": "bob", "ratings": [
+                    {"animeId": 1, "title": "Monster", "rawScore": 8},
+                    {"animeId": 2, "title": "Mushishi", "rawScore": 3},
+                    {"animeId": 3, "title": "Planetes", "rawScore": 9}
+                ]},
+                {"userId": "carol", "ratings": [
+                    {"animeId": 1, "title": "Monster", "rawScore": 10},
+                    {"animeId": 3, "title": "Planetes", "rawScore": 8},
+                    {"animeId": 3, "title": "Planetes", "rawScore": 10}
+                ]}
+            ]}"#);
+        assert_eq!(dataset.__repr__(), "<Dataset: 3 users, 7 ratings>");
+        assert!(dataset.report().contains("resolved 1 duplicate ratings"));
+
+        let mut users = engine.users();
+        users.sort();
+        assert_eq!(users, ["alice", "bob", "carol"]);
+        assert!(engine.__repr__().starts_with("<Engine: 3 users, 3 anime, "));
+        let edges = engine.similarity_edges();
+        assert_eq!(edges.len(), engine.stats.similarity_edges);
+        assert!(edges
+            .iter()
+            .all(|&(a, b, _, co_raters)| a != b && co_raters > 0));
+
+        let similar = engine.similar(1, 5).unwrap();
+        assert!(similar.iter().all(|anime| anime.anime_id != 1));
+        let recommendations = engine.recommend("alice", 5).unwrap();
+        assert!(recommendations
+            .iter()
+            .all(|recommendation| recommendation.anime_id == 3));
+        assert!(engine.recommend("nobody", 5).is_err());
+        assert!(engine.similar(99, 5).is_err());
+        assert!(merge_strategy("newest").is_err());
+    }
+}
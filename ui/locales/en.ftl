@@ -0,0 +1,391 @@
+# English, the source language. Every key here needs a translation in the other files; the
+# tests check that, and that placeables like { $count } match.
+
+## Side panel
+app-title = What Anime Should I Watch
+app-tagline = Desktop Dioxus graph from anonymized user ratings.
+tab-graph = Graph
+tab-recommendations = Recommendations
+tab-stats = Stats
+tab-tribes = Tribes
+tab-watchlist = Watchlist
+tab-import = Import
+tab-settings = Settings
+tab-anime = Anime
+tab-user = User
+tab-compare = Compare
+
+## Graph tab
+search-placeholder = Search anime or users…
+search-matches = { $count } matching nodes
+graph-edge-cap = For readability, the SVG caps visible edges at { $count }.
+graph-super-nodes = Zoom out to collapse communities into super-nodes; double-click one to expand it.
+graph-reset-view = Reset view
+graph-fullscreen = Fullscreen graph (F11)
+graph-exit-fullscreen = Exit fullscreen (Esc)
+panel-label = Controls
+tabs-label = Panels
+skip-to-graph = Skip to the graph
+canvas-label = Graph of { $users } users and { $anime } anime
+canvas-keys = Graph. Arrow keys move between nodes, Tab steps through the selected node's connections, Enter opens its details.
+canvas-selected = Selected: { $name }
+graph-text-view = Show the graph as text
+graph-drawing-view = Show the graph as a drawing
+graph-relations = Sequels and side stories
+graph-relations-none = No relations yet; fetch anime metadata first.
+outline-title = Graph as text
+outline-summary = { $shown } of { $total } nodes, most connected first, each with its strongest connections.
+outline-user = User
+outline-anime = Anime
+outline-genre = Genre
+outline-studio = Studio
+outline-director = Director
+outline-connections = { $count } connections
+outline-neighbors = Connections of { $name }
+edge-co-raters-label = Co-raters
+edge-co-raters-one = 1 user rated both
+edge-co-raters = { $count } users rated both
+edge-similarity = Similarity score
+context-highlight = Highlight matches
+context-zoom = Zoom to node
+dialog-cancel = Cancel
+dialog-close = Close
+layout-label = Layout
+layout-rings = Rings
+layout-communities = Communities
+graph-mode-label = Graph of
+graph-mode-ratings = Users and anime
+graph-mode-genres = Genres
+graph-mode-studios = Studios
+graph-mode-directors = Directors
+graph-mode-facets-hint = Two are joined by how many users like anime of both. Genres come from the dataset or from fetched metadata; studios and directors from fetched metadata only.
+filter-show-users = Show users
+filter-min-ratings = Hide anime with fewer than { $count } ratings
+sampling-label = Sample
+sampling-all = All data
+sampling-random-users = Random users
+sampling-top-anime = Most-rated anime
+sampling-stratified = Stratified users
+sampling-anime-count = Number of anime
+sampling-user-count = Number of users
+sampling-showing = Showing { $shown } of { $total } users.
+
+## Recommendations and Stats tabs
+recommendations-pick = Pick a profile on the Import tab, or select a user in the graph, to see recommendations.
+recommendations-for = For { $name }
+recommendations-details = Details…
+recommendations-empty = Nothing to recommend yet; rate a few more anime.
+recommendations-start-with = start with { $title }
+recommendations-plan = Plan a watch order
+recommendations-budget = Fit into a time budget
+recommendations-mood-empty = No recommendations have all the picked moods; fetch metadata for tags, or pick fewer.
+stats-users = Users
+stats-anime = Anime
+stats-ratings = Ratings
+stats-nodes = Nodes
+stats-edges = Edges
+stats-communities = Communities
+stats-components = Connected pieces
+stats-memory-dataset = Memory: dataset
+stats-memory-graph = Memory: graph
+stats-memory-similarity = Memory: similarity edges
+stats-memory-caches = Memory: posters, blurbs and tribes
+stats-memory-total = Memory in all
+stats-memory-of-budget = { $used } of { $budget }
+stats-chart-scores = Ratings by score
+stats-chart-spread = Users by mean score and variance
+stats-axis-mean = Mean score
+stats-axis-variance = Variance
+stats-spread-sampled = { $shown } of { $total } users plotted
+stats-chart-most-rated = Most rated anime
+stats-chart-least-rated = Least rated anime
+stats-chart-genres = Ratings by genre
+stats-genres-other = Other
+stats-genres-unknown = { $count } ratings are of anime without known genres; fetching metadata fills them in.
+stats-chart-density = Sparsity
+stats-density = { $percent }% of user–anime pairs are rated
+stats-min-raters = Raters an anime needs to be ranked
+stats-polarizing = Most polarizing anime
+stats-consensus = Strongest consensus
+stats-ranking-empty = No anime has that many raters.
+stats-column-anime = Anime
+stats-column-raters = Raters
+stats-column-mean = Average
+stats-column-variance = Variance
+health-healthy = Dataset health: every check passed.
+health-summary = Dataset health: { $errors } errors (data dropped on load) and { $warnings } warnings.
+health-save = Save health report
+health-saved = Saved the health report to { $path }.
+health-save-failed = Could not save the health report: { $error }
+health-scores-out-of-range = Scores outside their scale (dropped)
+health-empty-users = Users without usable ratings (dropped)
+health-other-drops = Other users and ratings dropped
+health-ignored-fields = Optional fields ignored
+health-duplicate-ratings = Repeated ratings resolved
+health-user-id-collisions = User ids differing only in case or spacing
+health-title-mismatches = Anime listed under different titles
+health-shared-titles = Titles shared by different anime ids
+health-copied-users = Users whose ratings copy another's
+tribes-intro = Groups users by how they score, not just what they watched, and describes each tribe by the shows it rates furthest above everyone else.
+tribes-count = Tribes
+tribes-find = Find tribes
+tribes-save = Save report
+tribes-job = Finding taste tribes
+tribes-failed = Finding tribes failed; see the log.
+tribes-saved = Saved the tribes report to { $path }.
+tribes-save-failed = Could not save the tribes report: { $error }
+tribes-none = No tribes: nobody rated two anime differently.
+tribes-heading = Tribe { $number }: { $count } users, cohesion { $cohesion }
+tribes-no-signature = No show stands out for this tribe.
+tribes-column-tribe-mean = Tribe mean
+tribes-column-others-mean = Others' mean
+tribes-members = Members
+tribes-more-members = … and { $count } more
+tribes-unassigned = { $count } users aren't in a tribe: they rated fewer than two anime or scored everything the same.
+activity-undated = No rating has a date, so there is no activity calendar.
+activity-title = Ratings by day in { $year }: { $count }
+activity-some-undated = { $count } ratings without a date are left out.
+activity-day = { $date }: { $count } ratings
+anime-back = Back
+anime-unrated = Nobody in this dataset rated this anime.
+anime-ratings = Ratings
+anime-mean = Average score
+anime-global-mean = Dataset average
+anime-difference = Difference
+anime-controversy = Controversy (variance)
+anime-scores = Ratings by score
+anime-trend-mean = Average score by month
+anime-trend-ratings = Ratings by month
+anime-no-trend = The ratings have no dates, so there is no trend over time.
+anime-partners = Often rated together
+anime-shared = { $count } shared raters
+anime-pick-compare = Pick for comparison
+anime-compare-with = Compare with { $title }
+anime-compare = compare
+anime-watch-order = Franchise watch order
+watch-order-for = Watch order for { $name }
+watch-order-total = About { $hours } hours in all.
+watch-order-total-unknown = About { $hours } hours, plus { $count } of unknown length.
+watch-order-export = Save as checklist…
+watch-order-saved = Saved the watch order to { $path }.
+watch-order-save-failed = Could not save the watch order: { $error }
+watch-order-start-with = Start with { $entry }
+budget-hours = Hours available
+budget-longest = Longest single pick (hours)
+budget-longest-hint = 0 for no limit; a pick counts the earlier entries it needs as well.
+budget-format = Format
+budget-any = Shows and movies
+budget-movies = Movies only
+budget-series = Series only
+budget-empty = Nothing fits; try more hours, or fetch metadata so episode lengths are known.
+budget-total = { $count } picks, about { $hours } hours.
+moods-label = Filter by mood
+moods-settings = Moods
+moods-settings-hint = Each mood stands for the tags and genres after it, separated by commas; an anime has the mood when it has any of them.
+moods-name = Mood name
+moods-tags = Tags
+moods-remove = Remove
+moods-new = Mood { $number }
+moods-add = Add mood
+moods-reset = Restore default moods
+compare-title = { $left } vs { $right }
+compare-ratings = Ratings of { $title }
+compare-shared = Users who rated both
+compare-no-shared = Nobody rated both, so there is no preference split or similarity score.
+compare-preferred = Preferred by users who rated both
+compare-tied = Scored the same
+compare-mean = Their average for { $title }
+compare-relative = Their score for { $title }, relative to their own average
+compare-similarity = Similarity score
+compare-similarity-hint = The similarity score is the mean of the two relative scores: high when the users who rated both liked both more than they usually like anime.
+compare-parts = Biggest parts of the similarity score
+compare-column-user = User
+compare-column-part = Part
+compare-genres = Genre overlap
+compare-genres-shared = { $percent }% shared: { $genres }
+compare-genres-only = Only { $title }: { $genres }
+compare-genres-none = none
+compare-users-shared = Anime both rated
+compare-users-pearson = Score correlation (Pearson)
+compare-users-pearson-none = not enough shared anime
+compare-users-jaccard = Overlap of what they rated (Jaccard)
+compare-users-jaccard-share = { $percent }% of the anime either rated, both rated
+compare-users-agreements = Where they agree
+compare-users-disagreements = Where they disagree most
+compare-users-none = None.
+compare-users-together = Both would enjoy
+compare-users-together-empty = No anime is recommended to both yet.
+user-missing = This user is not in the open dataset.
+user-mean = Average score
+user-harshness = Harshness
+user-harshness-hint = Harshness is how far below each anime's average from other raters this user scores, on average; below zero is generous.
+user-shared-anime = Anime others rated too
+user-overlapping = Users with anime in common
+user-genres = Average score by genre
+user-genres-missing = Genres for at least three of this user's genres are needed for the chart; fetching metadata fills them in.
+user-coverage = Overlap with the corpus
+user-coverage-share = { $rated } of { $total } anime rated ({ $percent }%)
+user-contrarian = Most contrarian opinions
+user-contrarian-empty = None of this user's anime has enough other raters to compare with.
+user-opinion = gave { $score }, others { $consensus }
+
+## Settings tab
+settings-language = Language
+settings-restore-session = Restore the last session at launch
+settings-start-fresh = Start fresh
+settings-start-fresh-hint = Reset the camera, filters, search and selection, and forget the saved session
+settings-tray = Keep running in the tray
+settings-tray-hint = Show a tray icon with Recommend something now, Open and Quit; closing the window hides it there
+settings-memory-budget = Memory budget in MB (0 for none)
+settings-memory-budget-hint = Over it, cached posters, blurbs and tribes are dropped first, then the graph is built from a sample of users
+settings-similar-neighbors = Similar anime kept per anime
+settings-similar-neighbors-hint = Each anime keeps its strongest similarity edges; fewer use less memory and draw faster, more give recommendations more to go on
+memory-dropped-caches = Over the memory budget: dropped cached posters, blurbs and tribes.
+memory-sampled = Over the memory budget: the graph now shows a sample of { $users } users.
+tray-recommend = Recommend something now
+tray-open = Open
+tray-quit = Quit
+tray-because = Because you liked { $title }
+tray-score = Score { $score }
+tray-no-pick = Nothing to recommend yet
+tray-no-pick-hint = Pick a profile or select a user in the graph first
+notify-enabled = Notify me about new episodes on my watchlist and strong seasonal matches
+notify-quiet-hours = Quiet hours
+notify-quiet-from = From
+notify-quiet-to = to
+notify-episode = New episode aired
+notify-match = A strong match in { $season }
+settings-show-log = Show log
+settings-hide-log = Hide log
+startup-label = Dataset at startup
+startup-latest = The most recently opened dataset
+startup-use-open = Use the open file
+startup-clear = Clear
+render-max-edges = Edges drawn at most
+render-seed = Layout seed (0 keeps dataset order)
+duplicates-label = Duplicate ratings
+duplicates-latest = Latest rating wins
+duplicates-highest = Highest score wins
+duplicates-average = Average the scores
+titles-label = Anime titles
+titles-graph = Titles in the graph
+titles-lists = Titles in lists
+titles-default = Same as default ({ $form })
+titles-romaji = Romaji
+titles-english = English
+titles-native = Native
+theme-label = Theme
+theme-system = System
+theme-dark = Dark
+theme-light = Light
+scale-label = Interface size
+scale-compact = Compact
+scale-comfortable = Comfortable
+scale-large = Large
+palette-label = Palette
+palette-skipped = { $count } palette(s) in config.toml were invalid and skipped.
+
+## Command palette
+command-palette-placeholder = Type a command or an anime title
+command-palette-empty = No matching command.
+command-go-to = Go to { $tab }
+command-layout = Layout: { $layout }
+command-sample = Sample: { $sampling }
+command-show-users = Show users
+command-hide-users = Hide users
+command-show-rating-edges = Show rating edges
+command-hide-rating-edges = Hide rating edges
+command-show-similarity-edges = Show similarity edges
+command-hide-similarity-edges = Hide similarity edges
+command-group-tab = Tab
+command-group-graph = Graph
+command-group-view = View
+command-group-anime = Anime
+
+## Keyboard shortcuts
+shortcuts-title = Keyboard shortcuts
+shortcuts-hint = Arrow keys move the selection in the graph and Tab steps through its neighbors. Rebind shortcuts under [keymap] in the config file, e.g. undo = "Ctrl+U".
+shortcut-zoom-in = Zoom in
+shortcut-zoom-out = Zoom out
+shortcut-fit-view = Fit graph to view
+shortcut-search = Search
+shortcut-next-tab = Next tab
+shortcut-previous-tab = Previous tab
+shortcut-undo = Undo graph change
+shortcut-redo = Redo graph change
+shortcut-screenshot = Save screenshot…
+shortcut-command-palette = Command palette
+shortcut-fullscreen = Toggle fullscreen
+shortcut-shortcuts = Keyboard shortcuts
+
+## Menu bar
+menu-file = File
+menu-accounts = Accounts
+menu-window = Window
+menu-open-recent = Open recent
+menu-open-dataset = Open dataset…
+menu-merge-datasets = Merge datasets…
+menu-generate-synthetic = Generate synthetic dataset…
+menu-load-id-map = Load id map…
+menu-fetch-metadata = Fetch anime metadata
+menu-save-dataset = Save dataset as…
+menu-export-anonymized = Export anonymized…
+menu-export-graph = Export graph…
+menu-connect-mal = Connect MyAnimeList…
+menu-connect-anilist = Connect AniList…
+menu-disconnect-accounts = Disconnect accounts
+
+## Dialogs
+crash-title = Something went wrong
+crash-location = At { $location }
+crash-last-file = Last file read:
+crash-fresh-hint = Starting fresh forgets the saved session and stops opening that file at launch.
+crash-retry = Try again
+crash-start-fresh = Start fresh
+crash-quit = Quit
+load-error-title = Could not load the dataset
+load-error-keep = Keep current dataset
+load-error-sample = Use sample dataset
+load-error-open = Open another file…
+quality-title = Data-quality report
+quality-rest = Everything else loaded normally.
+quality-user = User
+quality-anime = Anime
+quality-problem = Problem
+quality-unlisted = …and { $count } more not listed.
+log-title = Log
+log-level = { $level } and above
+log-all-modules = All modules
+log-empty = Nothing logged at this level yet.
+log-file = Also written to { $file }
+merge-title = Merge datasets
+merge-hint = Users with the same id are combined into one user.
+merge-duplicates = When a user rated an anime in several files
+merge-include-current = Merge into the current dataset
+merge-confirm = Merge
+synthetic-title = Generate synthetic dataset
+synthetic-hint = Popularity follows a power law and users favour a few genres. The same seed always gives the same data.
+synthetic-users = Users
+synthetic-anime = Anime
+synthetic-density = Density (share of anime each user rates)
+synthetic-skew = Popularity skew
+synthetic-seed = Seed
+synthetic-generate = Generate
+semantic-label = Describe what you want to watch
+semantic-placeholder = dark psychological thriller like Death Note but shorter
+semantic-search = Search by meaning
+semantic-searching = Searching…
+semantic-results = Results for “{ $query }”
+semantic-match = match { $relevance }
+semantic-match-taste = match { $relevance }, taste { $taste }
+anonymize-title = Export anonymized dataset
+anonymize-hint = User ids are replaced by a salted SHA-256 hash. Reuse the pipeline's salt to get matching ids; keep it secret either way.
+anonymize-salt = Salt
+anonymize-k = Drop users whose ratings match fewer than k users
+anonymize-noise = Score noise
+anonymize-noise-none = No noise
+anonymize-noise-jitter = Uniform jitter (± points)
+anonymize-noise-laplace = Differential privacy (ε)
+anonymize-amount = Amount
+anonymize-keep-dates = Keep rating dates
+anonymize-export = Export…
@@ -0,0 +1,390 @@
+# 日本語
+
+## Side panel
+app-title = What Anime Should I Watch
+app-tagline = 匿名化された評価データから作るアニメのグラフ。
+tab-graph = グラフ
+tab-recommendations = おすすめ
+tab-stats = 統計
+tab-tribes = 好みのグループ
+tab-watchlist = 見たいリスト
+tab-import = インポート
+tab-settings = 設定
+tab-anime = アニメ
+tab-user = ユーザー
+tab-compare = 比較
+
+## Graph tab
+search-placeholder = アニメやユーザーを検索…
+search-matches = 一致するノード { $count } 件
+graph-edge-cap = 見やすさのため、表示するエッジは最大 { $count } 本です。
+graph-super-nodes = ズームアウトするとコミュニティがまとまったノードになります。ダブルクリックで展開できます。
+graph-reset-view = 表示をリセット
+graph-fullscreen = グラフを全画面表示 (F11)
+graph-exit-fullscreen = 全画面表示を終了 (Esc)
+panel-label = 操作
+tabs-label = パネル
+skip-to-graph = グラフへ移動
+canvas-label = { $users } 人のユーザーと { $anime } 本のアニメのグラフ
+canvas-keys = グラフ。矢印キーでノード間を移動し、Tab で選択中のノードのつながりを順にたどり、Enter で詳細を開きます。
+canvas-selected = 選択中: { $name }
+graph-text-view = グラフをテキストで表示
+graph-drawing-view = グラフを図で表示
+graph-relations = 続編・外伝
+graph-relations-none = 関連作品の情報がまだありません。先にアニメのメタデータを取得してください。
+outline-title = テキストで見るグラフ
+outline-summary = { $total } 件中 { $shown } 件のノードを、つながりの多い順に、最も強いつながりとともに表示しています。
+outline-user = ユーザー
+outline-anime = アニメ
+outline-genre = ジャンル
+outline-studio = スタジオ
+outline-director = 監督
+outline-connections = つながり { $count } 件
+outline-neighbors = { $name } のつながり
+edge-co-raters-label = 共通の評価者
+edge-co-raters-one = 1 人が両方を評価
+edge-co-raters = { $count } 人が両方を評価
+edge-similarity = 類似度スコア
+context-highlight = 一致するノードを強調
+context-zoom = このノードにズーム
+dialog-cancel = キャンセル
+dialog-close = 閉じる
+layout-label = レイアウト
+layout-rings = リング
+layout-communities = コミュニティ
+graph-mode-label = グラフの対象
+graph-mode-ratings = ユーザーとアニメ
+graph-mode-genres = ジャンル
+graph-mode-studios = スタジオ
+graph-mode-directors = 監督
+graph-mode-facets-hint = 両方のアニメを好むユーザーが多いほど強く結ばれます。ジャンルはデータセットか取得したメタデータから、スタジオと監督は取得したメタデータからのみ取ります。
+filter-show-users = ユーザーを表示
+filter-min-ratings = 評価が { $count } 件未満のアニメを隠す
+sampling-label = サンプル
+sampling-all = すべてのデータ
+sampling-random-users = ランダムなユーザー
+sampling-top-anime = 評価の多いアニメ
+sampling-stratified = 層別抽出したユーザー
+sampling-anime-count = アニメの数
+sampling-user-count = ユーザーの数
+sampling-showing = { $total } 人中 { $shown } 人を表示しています。
+
+## Recommendations and Stats tabs
+recommendations-pick = おすすめを見るには、インポートタブでプロフィールを選ぶか、グラフでユーザーを選択してください。
+recommendations-for = { $name } さんへ
+recommendations-details = 詳細…
+recommendations-empty = まだおすすめがありません。もう少しアニメを評価してください。
+recommendations-start-with = まずは { $title } から
+recommendations-plan = 視聴順を組む
+recommendations-budget = 時間内に収める
+recommendations-mood-empty = 選んだ気分すべてに当てはまるおすすめがありません。メタデータを取得してタグを揃えるか、選ぶ気分を減らしてください。
+stats-users = ユーザー
+stats-anime = アニメ
+stats-ratings = 評価
+stats-nodes = ノード
+stats-edges = エッジ
+stats-communities = コミュニティ
+stats-components = 連結成分
+stats-memory-dataset = メモリ: データセット
+stats-memory-graph = メモリ: グラフ
+stats-memory-similarity = メモリ: 類似度エッジ
+stats-memory-caches = メモリ: ポスター・紹介文・トライブ
+stats-memory-total = メモリ合計
+stats-memory-of-budget = { $budget } 中 { $used }
+stats-chart-scores = スコア別の評価数
+stats-chart-spread = ユーザーごとの平均スコアと分散
+stats-axis-mean = 平均スコア
+stats-axis-variance = 分散
+stats-spread-sampled = { $total } 人中 { $shown } 人を表示
+stats-chart-most-rated = 評価の多いアニメ
+stats-chart-least-rated = 評価の少ないアニメ
+stats-chart-genres = ジャンル別の評価数
+stats-genres-other = その他
+stats-genres-unknown = { $count } 件の評価はジャンル不明のアニメです。メタデータを取得すると補完されます。
+stats-chart-density = 疎密度
+stats-density = ユーザーとアニメの組み合わせの { $percent }% が評価済み
+stats-min-raters = ランキングに必要な評価者数
+stats-polarizing = 評価が最も割れたアニメ
+stats-consensus = 評価が最も一致したアニメ
+stats-ranking-empty = その人数の評価者がいるアニメはありません。
+stats-column-anime = アニメ
+stats-column-raters = 評価者
+stats-column-mean = 平均
+stats-column-variance = 分散
+health-healthy = データセットの状態: すべてのチェックに合格しました。
+health-summary = データセットの状態: エラー { $errors } 件（読み込み時に除外）、警告 { $warnings } 件。
+health-save = 状態レポートを保存
+health-saved = 状態レポートを { $path } に保存しました。
+health-save-failed = 状態レポートを保存できませんでした: { $error }
+health-scores-out-of-range = 範囲外のスコア（除外）
+health-empty-users = 有効な評価のないユーザー（除外）
+health-other-drops = その他の除外されたユーザーと評価
+health-ignored-fields = 無視された任意フィールド
+health-duplicate-ratings = 解決された重複評価
+health-user-id-collisions = 大文字小文字や空白だけが異なるユーザーID
+health-title-mismatches = 異なるタイトルで登録されたアニメ
+health-shared-titles = 異なるアニメIDで共有されているタイトル
+health-copied-users = 他のユーザーの評価と同一のユーザー
+tribes-intro = 視聴したものだけでなく採点の傾向でユーザーをグループに分け、各グループを他のユーザーより特に高く評価する作品で説明します。
+tribes-count = グループ数
+tribes-find = グループを探す
+tribes-save = レポートを保存
+tribes-job = 好みのグループを探しています
+tribes-failed = グループを探せませんでした。ログを確認してください。
+tribes-saved = グループのレポートを { $path } に保存しました。
+tribes-save-failed = グループのレポートを保存できませんでした: { $error }
+tribes-none = グループがありません。2作品以上を異なる点数で評価したユーザーがいません。
+tribes-heading = グループ { $number }: { $count } 人、まとまり { $cohesion }
+tribes-no-signature = このグループで目立つ作品はありません。
+tribes-column-tribe-mean = グループ平均
+tribes-column-others-mean = その他の平均
+tribes-members = メンバー
+tribes-more-members = …ほか { $count } 人
+tribes-unassigned = { $count } 人はどのグループにも入っていません。評価が2作品未満か、すべて同じ点数です。
+activity-undated = 日付のある評価がないため、アクティビティカレンダーはありません。
+activity-title = { $year } 年の日別の評価: { $count } 件
+activity-some-undated = 日付のない { $count } 件の評価は含まれていません。
+activity-day = { $date }: { $count } 件の評価
+anime-back = 戻る
+anime-unrated = このデータセットではまだ誰も評価していません。
+anime-ratings = 評価数
+anime-mean = 平均スコア
+anime-global-mean = データセット全体の平均
+anime-difference = 差
+anime-controversy = 賛否の分かれ具合（分散）
+anime-scores = スコア別の評価数
+anime-trend-mean = 月ごとの平均スコア
+anime-trend-ratings = 月ごとの評価数
+anime-no-trend = 評価に日付がないため、推移は表示できません。
+anime-partners = 一緒に評価されることが多いアニメ
+anime-shared = 共通の評価者 { $count } 人
+anime-pick-compare = 比較対象に選ぶ
+anime-compare-with = { $title } と比較
+anime-compare = 比較
+anime-watch-order = シリーズの視聴順
+watch-order-for = { $name } の視聴順
+watch-order-total = 合計およそ { $hours } 時間。
+watch-order-total-unknown = およそ { $hours } 時間、ほかに長さ不明の作品が { $count } 本。
+watch-order-export = チェックリストとして保存…
+watch-order-saved = 視聴順を { $path } に保存しました。
+watch-order-save-failed = 視聴順を保存できませんでした: { $error }
+watch-order-start-with = まずは { $entry } から
+budget-hours = 使える時間（時間）
+budget-longest = 1作品の上限（時間）
+budget-longest-hint = 0で上限なし。前作など先に観るべき作品の時間も含みます。
+budget-format = 形式
+budget-any = シリーズと映画
+budget-movies = 映画のみ
+budget-series = シリーズのみ
+budget-empty = 収まる作品がありません。時間を増やすか、メタデータを取得して話数と尺を確認してください。
+budget-total = { $count } 作品、約 { $hours } 時間。
+moods-label = 気分で絞り込む
+moods-settings = 気分
+moods-settings-hint = 各気分は、カンマ区切りで並べたタグとジャンルを表します。どれか一つでも持つアニメがその気分に当てはまります。
+moods-name = 気分の名前
+moods-tags = タグ
+moods-remove = 削除
+moods-new = 気分 { $number }
+moods-add = 気分を追加
+moods-reset = 既定の気分に戻す
+compare-title = { $left } と { $right }
+compare-ratings = { $title } の評価数
+compare-shared = 両方を評価したユーザー
+compare-no-shared = 両方を評価した人がいないため、好みの割れ方や類似度はありません。
+compare-preferred = 両方を評価したユーザーが好んだ方
+compare-tied = 同じスコア
+compare-mean = { $title } の平均
+compare-relative = { $title } のスコア（本人の平均との差）
+compare-similarity = 類似度
+compare-similarity-hint = 類似度は2つの相対スコアの平均です。両方を評価した人が、普段より両方を高く評価しているほど高くなります。
+compare-parts = 類似度への寄与が大きいユーザー
+compare-column-user = ユーザー
+compare-column-part = 寄与
+compare-genres = ジャンルの重なり
+compare-genres-shared = { $percent }% 共通: { $genres }
+compare-genres-only = { $title } のみ: { $genres }
+compare-genres-none = なし
+compare-users-shared = 両方が評価したアニメ
+compare-users-pearson = スコアの相関（ピアソン）
+compare-users-pearson-none = 共通のアニメが足りません
+compare-users-jaccard = 評価したアニメの重なり（ジャッカード）
+compare-users-jaccard-share = どちらかが評価したアニメのうち { $percent }% を両方が評価
+compare-users-agreements = 意見が一致したアニメ
+compare-users-disagreements = 意見が最も分かれたアニメ
+compare-users-none = ありません。
+compare-users-together = 2人とも楽しめそうなアニメ
+compare-users-together-empty = 2人ともにおすすめされるアニメはまだありません。
+user-missing = このユーザーは開いているデータセットにいません。
+user-mean = 平均スコア
+user-harshness = 辛口度
+user-harshness-hint = 辛口度は、各アニメの他の評価者の平均よりどれだけ低く付けるかの平均です。マイナスは甘口です。
+user-shared-anime = 他の人も評価したアニメ
+user-overlapping = 共通のアニメを持つユーザー
+user-genres = ジャンル別の平均スコア
+user-genres-missing = グラフには3つ以上のジャンルが必要です。メタデータを取得すると補完されます。
+user-coverage = コーパスとの重なり
+user-coverage-share = { $total } 作品中 { $rated } 作品を評価（{ $percent }%）
+user-contrarian = 最も少数派の意見
+user-contrarian-empty = 比較できるほど他の評価者がいるアニメがありません。
+user-opinion = 本人 { $score }、他の人 { $consensus }
+
+## Settings tab
+settings-language = 言語
+settings-restore-session = 起動時に前回のセッションを復元する
+settings-start-fresh = 最初からやり直す
+settings-start-fresh-hint = カメラ、フィルター、検索、選択をリセットし、保存されたセッションを消去します
+settings-tray = トレイに常駐する
+settings-tray-hint = 「今すぐおすすめ」「開く」「終了」を持つトレイアイコンを表示し、ウィンドウを閉じてもトレイに残ります
+settings-memory-budget = メモリ上限（MB、0 で無制限）
+settings-memory-budget-hint = 超えると、まずキャッシュ済みのポスター・紹介文・トライブを破棄し、次にユーザーを抽出してグラフを作ります
+settings-similar-neighbors = アニメごとに残す類似アニメ数
+settings-similar-neighbors-hint = 各アニメは類似度の強いエッジだけを残します。少ないほどメモリが減り描画が速くなり、多いほどおすすめの手がかりが増えます
+memory-dropped-caches = メモリ上限を超えたため、キャッシュ済みのポスター・紹介文・トライブを破棄しました。
+memory-sampled = メモリ上限を超えたため、グラフは { $users } 人のユーザーの抽出で表示しています。
+tray-recommend = 今すぐおすすめ
+tray-open = 開く
+tray-quit = 終了
+tray-because = { $title } が好きなあなたに
+tray-score = スコア { $score }
+tray-no-pick = まだおすすめがありません
+tray-no-pick-hint = 先にプロフィールを選ぶか、グラフでユーザーを選択してください
+notify-enabled = ウォッチリストの新しいエピソードと相性の良い今期アニメを通知する
+notify-quiet-hours = おやすみ時間
+notify-quiet-from = 開始
+notify-quiet-to = 終了
+notify-episode = 新しいエピソードが放送されました
+notify-match = { $season } のおすすめ
+settings-show-log = ログを表示
+settings-hide-log = ログを隠す
+startup-label = 起動時のデータセット
+startup-latest = 最後に開いたデータセット
+startup-use-open = 開いているファイルを使う
+startup-clear = 解除
+render-max-edges = 描画するエッジの上限
+render-seed = レイアウトのシード (0 でデータセットの順)
+duplicates-label = 重複した評価
+duplicates-latest = 最新の評価を使う
+duplicates-highest = 最高点を使う
+duplicates-average = 平均をとる
+titles-label = アニメのタイトル
+titles-graph = グラフのタイトル
+titles-lists = 一覧のタイトル
+titles-default = 既定と同じ ({ $form })
+titles-romaji = ローマ字
+titles-english = 英語
+titles-native = 原語
+theme-label = テーマ
+theme-system = システム
+theme-dark = ダーク
+theme-light = ライト
+scale-label = 表示サイズ
+scale-compact = 小さめ
+scale-comfortable = 標準
+scale-large = 大きめ
+palette-label = 配色
+palette-skipped = config.toml の配色 { $count } 件が無効なため読み込みませんでした。
+
+## Command palette
+command-palette-placeholder = コマンドやアニメのタイトルを入力
+command-palette-empty = 一致するコマンドがありません。
+command-go-to = { $tab } へ移動
+command-layout = レイアウト: { $layout }
+command-sample = サンプル: { $sampling }
+command-show-users = ユーザーを表示
+command-hide-users = ユーザーを隠す
+command-show-rating-edges = 評価のエッジを表示
+command-hide-rating-edges = 評価のエッジを隠す
+command-show-similarity-edges = 類似のエッジを表示
+command-hide-similarity-edges = 類似のエッジを隠す
+command-group-tab = タブ
+command-group-graph = グラフ
+command-group-view = 表示
+command-group-anime = アニメ
+
+## Keyboard shortcuts
+shortcuts-title = キーボードショートカット
+shortcuts-hint = 矢印キーでグラフの選択を移動し、Tab で隣のノードを順にたどります。ショートカットは設定ファイルの [keymap] で変更できます (例: undo = "Ctrl+U")。
+shortcut-zoom-in = ズームイン
+shortcut-zoom-out = ズームアウト
+shortcut-fit-view = グラフ全体を表示
+shortcut-search = 検索
+shortcut-next-tab = 次のタブ
+shortcut-previous-tab = 前のタブ
+shortcut-undo = グラフの変更を元に戻す
+shortcut-redo = グラフの変更をやり直す
+shortcut-screenshot = スクリーンショットを保存…
+shortcut-command-palette = コマンドパレット
+shortcut-fullscreen = 全画面表示の切り替え
+shortcut-shortcuts = キーボードショートカット
+
+## Menu bar
+menu-file = ファイル
+menu-accounts = アカウント
+menu-window = ウィンドウ
+menu-open-recent = 最近使ったファイル
+menu-open-dataset = データセットを開く…
+menu-merge-datasets = データセットを結合…
+menu-generate-synthetic = 合成データセットを生成…
+menu-load-id-map = ID 対応表を読み込む…
+menu-fetch-metadata = アニメのメタデータを取得
+menu-save-dataset = データセットを別名で保存…
+menu-export-anonymized = 匿名化してエクスポート…
+menu-export-graph = グラフをエクスポート…
+menu-connect-mal = MyAnimeList に接続…
+menu-connect-anilist = AniList に接続…
+menu-disconnect-accounts = アカウントの接続を解除
+
+## ダイアログ
+crash-title = 問題が発生しました
+crash-location = 発生箇所: { $location }
+crash-last-file = 最後に読み込んだファイル:
+crash-fresh-hint = 最初からやり直すと、保存したセッションを破棄し、起動時にそのファイルを開かなくなります。
+crash-retry = 再試行
+crash-start-fresh = 最初からやり直す
+crash-quit = 終了
+load-error-title = データセットを読み込めませんでした
+load-error-keep = 現在のデータセットを使い続ける
+load-error-sample = サンプルデータセットを使う
+load-error-open = 別のファイルを開く…
+quality-title = データ品質レポート
+quality-rest = それ以外は通常どおり読み込みました。
+quality-user = ユーザー
+quality-anime = アニメ
+quality-problem = 問題
+quality-unlisted = …ほか { $count } 件は省略しています。
+log-title = ログ
+log-level = { $level } 以上
+log-all-modules = すべてのモジュール
+log-empty = このレベルのログはまだありません。
+log-file = { $file } にも書き込んでいます
+merge-title = データセットを結合
+merge-hint = 同じ ID のユーザーは 1 人のユーザーにまとめます。
+merge-duplicates = 同じアニメを複数のファイルで評価しているとき
+merge-include-current = 現在のデータセットに結合する
+merge-confirm = 結合
+synthetic-title = 合成データセットを生成
+synthetic-hint = 人気度はべき乗則に従い、ユーザーは少数のジャンルを好みます。同じシードからは常に同じデータができます。
+synthetic-users = ユーザー数
+synthetic-anime = アニメ数
+synthetic-density = 密度 (各ユーザーが評価するアニメの割合)
+synthetic-skew = 人気の偏り
+synthetic-seed = シード
+synthetic-generate = 生成
+semantic-label = 見たいアニメを言葉で説明してください
+semantic-placeholder = デスノートのようなダークな心理スリラーで、もっと短いもの
+semantic-search = 意味で検索
+semantic-searching = 検索中…
+semantic-results = “{ $query }” の検索結果
+semantic-match = 一致度 { $relevance }
+semantic-match-taste = 一致度 { $relevance }、好み { $taste }
+anonymize-title = 匿名化したデータセットをエクスポート
+anonymize-hint = ユーザー ID はソルト付きの SHA-256 ハッシュに置き換えます。パイプラインと同じソルトを使うと ID が一致します。いずれの場合もソルトは秘密にしてください。
+anonymize-salt = ソルト
+anonymize-k = 評価が一致するユーザーが k 人未満のユーザーを除外
+anonymize-noise = スコアのノイズ
+anonymize-noise-none = ノイズなし
+anonymize-noise-jitter = 一様な揺らぎ (± 点)
+anonymize-noise-laplace = 差分プライバシー (ε)
+anonymize-amount = 量
+anonymize-keep-dates = 評価日を残す
+anonymize-export = エクスポート…
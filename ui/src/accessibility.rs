@@ -0,0 +1,191 @@
+//! Support for screen readers and keyboard-only use: the graph as text, ranked adjacency lists
+//! a screen reader can walk, and moving focus into dialogs as they open.
+
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+use dioxus::prelude::*;
+use wasiw_core::{GraphModel, NodeType};
+
+use crate::i18n::tr;
+use crate::navigation::{connection_detail, NeighborSummary};
+
+/// Nodes the text view lists; the rest are reached by searching.
+pub const OUTLINE_NODES: usize = 200;
+/// Connections listed under each node.
+pub const OUTLINE_NEIGHBORS: usize = 8;
+
+/// One node of the text view with its strongest connections.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineEntry {
+    pub node: usize,
+    pub label: String,
+    pub node_type: NodeType,
+    /// Visible connections, of which `neighbors` lists the strongest.
+    pub degree: usize,
+    pub neighbors: Vec<NeighborSummary>,
+}
+
+/// The visible nodes in `only` (all of them without it), most connected first, at most
+/// `limit`, each with up to `neighbors` connections ranked by co-raters, then weight. Also
+/// returns how many nodes there were before the limit.
+pub fn outline(
+    graph: &GraphModel,
+    only: Option<&HashSet<usize>>,
+    limit: usize,
+    neighbors: usize,
+) -> (Vec<OutlineEntry>, usize) {
+    let listed =
+        |idx: usize| !graph.nodes[idx].hidden && only.is_none_or(|only| only.contains(&idx));
+    let visible_links = |idx: usize| {
+        graph
+            .links(idx)
+            .filter(|(_, edge)| graph.is_edge_visible(edge))
+    };
+    let degree: Vec<usize> = (0..graph.nodes.len())
+        .map(|idx| visible_links(idx).count())
+        .collect();
+    let mut ranked: Vec<usize> = (0..graph.nodes.len()).filter(|&idx| listed(idx)).collect();
+    let total = ranked.len();
+    ranked.sort_by_key(|&idx| Reverse(degree[idx]));
+    ranked.truncate(limit);
+
+    let entries = ranked
+        .into_iter()
+        .map(|idx| {
+            let mut links: Vec<_> = visible_links(idx).collect();
+            links.sort_by(|(_, a), (_, b)| {
+                b.co_raters
+                    .cmp(&a.co_raters)
+                    .then(b.weight.total_cmp(&a.weight))
+            });
+            OutlineEntry {
+                node: idx,
+                label: graph.nodes[idx].label.clone(),
+                node_type: graph.nodes[idx].node_type,
+                degree: degree[idx],
+                neighbors: links
+                    .into_iter()
+                    .take(neighbors)
+                    .map(|(other, edge)| NeighborSummary {
+                        node: other,
+                        label: graph.nodes[other].label.clone(),
+                        detail: connection_detail(edge),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    (entries, total)
+}
+
+/// The graph as nested lists, shown in place of the drawing. Every name is a button that picks
+/// its node.
+#[component]
+pub fn GraphOutline(
+    graph: ReadSignal<GraphModel>,
+    /// Search matches; only these are listed while set.
+    matches: Option<HashSet<usize>>,
+    selected: Option<usize>,
+    on_pick: EventHandler<usize>,
+) -> Element {
+    let (entries, total) = outline(
+        &graph.read(),
+        matches.as_ref(),
+        OUTLINE_NODES,
+        OUTLINE_NEIGHBORS,
+    );
+    let shown = entries.len();
+    rsx! {
+        section { class: "outline", aria_label: tr!("outline-title"),
+            h2 { {tr!("outline-title")} }
+            p { class: "tiny", {tr!("outline-summary", shown = shown, total = total)} }
+            ol {
+                for entry in entries {
+                    li { key: "{entry.node}",
+                        button {
+                            class: "link",
+                            aria_current: if selected == Some(entry.node) { "true" },
+                            onclick: move |_| on_pick.call(entry.node),
+                            "{entry.label}"
+                        }
+                        span { class: "tiny",
+                            " · "
+                            {tr(match entry.node_type {
+                                NodeType::User => "outline-user",
+                                NodeType::Anime => "outline-anime",
+                                NodeType::Genre => "outline-genre",
+                                NodeType::Studio => "outline-studio",
+                                NodeType::Director => "outline-director",
+                            })}
+                            " · "
+                            {tr!("outline-connections", count = entry.degree)}
+                        }
+                        if !entry.neighbors.is_empty() {
+                            ol { aria_label: tr!("outline-neighbors", name = entry.label),
+                                for neighbor in entry.neighbors {
+                                    li { key: "{neighbor.node}",
+                                        button {
+                                            class: "link",
+                                            onclick: move |_| on_pick.call(neighbor.node),
+                                            "{neighbor.label}"
+                                        }
+                                        span { class: "tiny", " {neighbor.detail}" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Moves keyboard focus to an element as it mounts, e.g. a dialog opening over the app, so
+/// keyboard and screen-reader users land in it. Use as `onmounted: focus_on_mount`.
+pub fn focus_on_mount(evt: MountedEvent) {
+    spawn(async move {
+        let _ = evt.set_focus(true).await;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasiw_core::{build_graph, Dataset, Rating, UserRatings, Viewport};
+
+    fn graph() -> GraphModel {
+        let user = |user_id: &str, anime: &[u32]| UserRatings {
+            user_id: user_id.to_string(),
+            ratings: anime
+                .iter()
+                .map(|&id| Rating::new(id, format!("Anime {id}"), 8.0))
+                .collect(),
+        };
+        build_graph(
+            Dataset::new(vec![user("a", &[1, 2, 3]), user("b", &[1, 2])]),
+            Viewport::default(),
+        )
+    }
+
+    #[test]
+    fn most_connected_nodes_come_first() {
+        let graph = graph();
+        let (entries, total) = outline(&graph, None, 2, 1);
+        assert_eq!(total, graph.nodes.len());
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].degree >= entries[1].degree);
+        assert!(entries.iter().all(|entry| entry.neighbors.len() == 1));
+    }
+
+    #[test]
+    fn only_matches_are_listed_while_searching() {
+        let graph = graph();
+        let anime = graph.find_anime(3).unwrap();
+        let (entries, total) = outline(&graph, Some(&HashSet::from([anime])), 10, 10);
+        assert_eq!(total, 1);
+        assert_eq!(entries[0].label, "Anime 3");
+        assert_eq!(entries[0].degree, 3);
+    }
+}
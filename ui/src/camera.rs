@@ -0,0 +1,270 @@
+//! The camera over the graph canvas: the viewBox that fits every node, zoom and pan on top of
+//! it, and touch gestures.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use wasiw_core::{Node, Viewport};
+
+/// Padding, in layout units, kept around the node bounding box when fitting the viewBox.
+const VIEW_PADDING: f32 = 24.0;
+const MIN_ZOOM: f32 = 0.2;
+const MAX_ZOOM: f32 = 12.0;
+/// Movement, in CSS pixels, after which a held touch no longer counts as a long press.
+pub const LONG_PRESS_SLOP: f32 = 10.0;
+pub const LONG_PRESS_MS: u64 = 550;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewBox {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ViewBox {
+    /// Fits the node bounding box into a viewBox with the same aspect ratio as the canvas.
+    pub fn fit(nodes: &[Node], viewport: Viewport) -> Self {
+        if nodes.iter().all(|node| node.hidden) {
+            return Self {
+                x: 0.0,
+                y: 0.0,
+                width: viewport.width,
+                height: viewport.height,
+            };
+        }
+
+        let (mut min_x, mut min_y) = (f32::MAX, f32::MAX);
+        let (mut max_x, mut max_y) = (f32::MIN, f32::MIN);
+        for node in nodes.iter().filter(|node| !node.hidden) {
+            min_x = min_x.min(node.x - node.radius);
+            min_y = min_y.min(node.y - node.radius);
+            max_x = max_x.max(node.x + node.radius);
+            max_y = max_y.max(node.y + node.radius);
+        }
+
+        let mut width = (max_x - min_x) + VIEW_PADDING * 2.0;
+        let mut height = (max_y - min_y) + VIEW_PADDING * 2.0;
+        let aspect = viewport.width / viewport.height.max(1.0);
+        if width / height < aspect {
+            width = height * aspect;
+        } else {
+            height = width / aspect;
+        }
+
+        Self {
+            x: (min_x + max_x) / 2.0 - width / 2.0,
+            y: (min_y + max_y) / 2.0 - height / 2.0,
+            width,
+            height,
+        }
+    }
+
+    /// Maps a canvas-local pixel position into layout coordinates.
+    pub fn layout_point(self, px: f32, py: f32, viewport: Viewport) -> (f32, f32) {
+        (
+            self.x + px / viewport.width.max(1.0) * self.width,
+            self.y + py / viewport.height.max(1.0) * self.height,
+        )
+    }
+}
+
+impl fmt::Display for ViewBox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {} {}", self.x, self.y, self.width, self.height)
+    }
+}
+
+/// Zoom and pan applied on top of the viewBox that fits the whole graph.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Camera {
+    pub zoom: f32,
+    /// Offset of the view center from the fitted center, in layout units.
+    pub pan_x: f32,
+    pub pan_y: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            pan_x: 0.0,
+            pan_y: 0.0,
+        }
+    }
+}
+
+impl Camera {
+    pub fn apply(&self, fitted: ViewBox) -> ViewBox {
+        let width = fitted.width / self.zoom;
+        let height = fitted.height / self.zoom;
+        let center_x = fitted.x + fitted.width / 2.0 + self.pan_x;
+        let center_y = fitted.y + fitted.height / 2.0 + self.pan_y;
+        ViewBox {
+            x: center_x - width / 2.0,
+            y: center_y - height / 2.0,
+            width,
+            height,
+        }
+    }
+
+    /// Zooms by `factor` while keeping the canvas point `(px, py)` under the same layout point.
+    pub fn zoom_about(
+        &mut self,
+        factor: f32,
+        px: f32,
+        py: f32,
+        fitted: ViewBox,
+        viewport: Viewport,
+    ) {
+        let before = self.apply(fitted);
+        let (focus_x, focus_y) = before.layout_point(px, py, viewport);
+
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+
+        let after = self.apply(fitted);
+        let (moved_x, moved_y) = after.layout_point(px, py, viewport);
+        self.pan_x += focus_x - moved_x;
+        self.pan_y += focus_y - moved_y;
+    }
+
+    /// Pans so the content follows a pointer that moved `(dx, dy)` canvas pixels.
+    pub fn pan_by_pixels(&mut self, dx: f32, dy: f32, fitted: ViewBox, viewport: Viewport) {
+        let view = self.apply(fitted);
+        self.pan_x -= dx * view.width / viewport.width.max(1.0);
+        self.pan_y -= dy * view.height / viewport.height.max(1.0);
+    }
+
+    pub fn center_on(&mut self, x: f32, y: f32, fitted: ViewBox) {
+        self.pan_x = x - (fitted.x + fitted.width / 2.0);
+        self.pan_y = y - (fitted.y + fitted.height / 2.0);
+    }
+}
+
+/// In-progress touch interaction on the canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Gesture {
+    #[default]
+    Idle,
+    /// Two fingers down: distance and midpoint (canvas pixels) from the previous move event.
+    Pinch {
+        distance: f32,
+        mid_x: f32,
+        mid_y: f32,
+    },
+    /// One finger held on a node; `token` identifies the pending long-press timer.
+    Press {
+        node: usize,
+        x: f32,
+        y: f32,
+        token: u64,
+    },
+}
+
+pub fn pinch_metrics(a: (f32, f32), b: (f32, f32)) -> (f32, f32, f32) {
+    let distance = ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt();
+    (distance, (a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use wasiw_core::NodeType;
+
+    use super::*;
+
+    fn node(x: f32, y: f32, hidden: bool) -> Node {
+        Node {
+            id: format!("anime:{x}"),
+            label: String::new(),
+            node_type: NodeType::Anime,
+            x,
+            y,
+            radius: 4.0,
+            hidden,
+        }
+    }
+
+    fn close(a: (f32, f32), b: (f32, f32)) -> bool {
+        (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3
+    }
+
+    #[test]
+    fn the_fitted_view_holds_every_visible_node_at_the_canvas_aspect() {
+        let viewport = Viewport {
+            width: 800.0,
+            height: 400.0,
+            scale_factor: 1.0,
+        };
+        let nodes = [
+            node(0.0, 0.0, false),
+            node(100.0, 100.0, false),
+            node(5000.0, 0.0, true),
+        ];
+        let fitted = ViewBox::fit(&nodes, viewport);
+        assert!((fitted.width / fitted.height - 2.0).abs() < 1e-4);
+        assert!((fitted.height - (108.0 + 2.0 * VIEW_PADDING)).abs() < 1e-3);
+        assert!(close(
+            (
+                fitted.x + fitted.width / 2.0,
+                fitted.y + fitted.height / 2.0
+            ),
+            (50.0, 50.0)
+        ));
+        assert!(close(
+            fitted.layout_point(400.0, 200.0, viewport),
+            (50.0, 50.0)
+        ));
+
+        let empty = ViewBox::fit(&[node(1.0, 1.0, true)], viewport);
+        assert_eq!(empty.to_string(), "0 0 800 400");
+    }
+
+    #[test]
+    fn zooming_keeps_the_point_under_the_pointer() {
+        let viewport = Viewport::default();
+        let fitted = ViewBox {
+            x: 0.0,
+            y: 0.0,
+            width: viewport.width,
+            height: viewport.height,
+        };
+        let mut camera = Camera::default();
+        let pointer = (200.0, 150.0);
+        let before = camera
+            .apply(fitted)
+            .layout_point(pointer.0, pointer.1, viewport);
+        camera.zoom_about(2.0, pointer.0, pointer.1, fitted, viewport);
+        assert_eq!(camera.zoom, 2.0);
+        let after = camera
+            .apply(fitted)
+            .layout_point(pointer.0, pointer.1, viewport);
+        assert!(close(before, after));
+
+        camera.zoom_about(100.0, 0.0, 0.0, fitted, viewport);
+        assert_eq!(camera.zoom, MAX_ZOOM);
+        camera.zoom_about(1e-6, 0.0, 0.0, fitted, viewport);
+        assert_eq!(camera.zoom, MIN_ZOOM);
+    }
+
+    #[test]
+    fn panning_follows_the_pointer_and_centering_moves_the_view() {
+        let viewport = Viewport::default();
+        let fitted = ViewBox {
+            x: 0.0,
+            y: 0.0,
+            width: viewport.width * 2.0,
+            height: viewport.height * 2.0,
+        };
+        let mut camera = Camera::default();
+        camera.pan_by_pixels(10.0, -5.0, fitted, viewport);
+        assert!(close((camera.pan_x, camera.pan_y), (-20.0, 10.0)));
+
+        camera.center_on(30.0, 40.0, fitted);
+        let view = camera.apply(fitted);
+        assert!(close(
+            (view.x + view.width / 2.0, view.y + view.height / 2.0),
+            (30.0, 40.0)
+        ));
+        assert_eq!(pinch_metrics((0.0, 0.0), (6.0, 8.0)), (10.0, 3.0, 4.0));
+    }
+}
@@ -0,0 +1,450 @@
+//! The graph canvas: what to draw for the current camera, and the nodes, edges, super-nodes,
+//! edge tooltip and node context menu drawn on it. The drawing goes through a
+//! [`GraphRenderer`] for the frontend's [`RenderBackend`]: live SVG elements, or a painted
+//! `<canvas>` under invisible SVG targets for the pointer events.
+
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use dioxus::core::Task;
+use dioxus::prelude::*;
+use wasiw_core::community::{self, ClusterScene};
+use wasiw_core::relations::{RelationKind, Relations};
+use wasiw_core::{EdgeKind, GraphModel, RenderEdge, Viewport};
+
+use crate::accessibility::focus_on_mount;
+use crate::camera::{Camera, ViewBox};
+use crate::cull::CullIndex;
+use crate::i18n::tr;
+use crate::render::{
+    arrow_path, Canvas2dRenderer, CircleKind, Frame, GraphRenderer, Highlight, RenderBackend,
+};
+use crate::schedule::NEXT_FRAME_JS;
+use crate::theme::Palette;
+
+/// Edges drawn at most unless the app configures another cap, for readability and speed.
+pub const MAX_RENDERED_EDGES: usize = 1400;
+/// Below this camera zoom, communities are drawn as single super-nodes.
+pub const SUPER_NODE_ZOOM: f32 = 0.75;
+/// Edges added per frame while a new graph comes onto the canvas, after its nodes.
+pub const EDGES_PER_FRAME: usize = 250;
+/// Id of the `<canvas>` the [`RenderBackend::Canvas2d`] backend paints.
+const PAINT_CANVAS_ID: &str = "graph-paint";
+
+/// What the canvas draws for one camera position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scene {
+    /// The viewBox that fits the graph, before the camera is applied.
+    pub fitted: ViewBox,
+    pub view_box: ViewBox,
+    /// One device pixel expressed in viewBox units, so thin edges stay visible on high-DPI
+    /// screens.
+    pub hairline: f32,
+    /// Stroke width of the invisible hover target laid over similarity edges.
+    pub hit_width: f32,
+    pub edges: Vec<RenderEdge>,
+    /// Set when zoomed out far enough to collapse communities into super-nodes.
+    pub clusters: Option<ClusterScene>,
+    /// Relations drawn over the edges as `(from, to, kind)` node indices, between nodes the
+    /// scene shows; see [`Scene::with_relations`].
+    pub relations: Vec<(usize, usize, RelationKind)>,
+}
+
+impl Scene {
+    /// The scene for `camera`, taking its edges from those in view when `cull` has been built
+    /// for `graph` (see [`use_cull_index`](crate::cull::use_cull_index)).
+    pub fn new(
+        graph: &GraphModel,
+        viewport: Viewport,
+        camera: Camera,
+        expanded_communities: &HashSet<usize>,
+        max_edges: usize,
+        cull: Option<&CullIndex>,
+    ) -> Self {
+        let fitted = ViewBox::fit(&graph.nodes, viewport);
+        let view_box = camera.apply(fitted);
+        let hairline = view_box.width / viewport.width / viewport.scale_factor;
+        let clusters =
+            (camera.zoom < SUPER_NODE_ZOOM && graph.communities.count() > 1).then(|| {
+                community::aggregate(graph, &graph.communities, expanded_communities, hairline)
+            });
+        let edges = match &clusters {
+            Some(scene) => scene.edges.iter().take(max_edges).cloned().collect(),
+            None => match cull.filter(|index| index.fits(graph)) {
+                Some(index) => index.render_edges(graph, view_box, max_edges, hairline),
+                None => graph.render_edges(max_edges, hairline),
+            },
+        };
+        Self {
+            fitted,
+            view_box,
+            hairline,
+            // About eight CSS pixels regardless of zoom.
+            hit_width: hairline * viewport.scale_factor * 8.0,
+            edges,
+            clusters,
+            relations: Vec::new(),
+        }
+    }
+
+    /// The scene with `relations` drawn as arrows between the anime it shows.
+    pub fn with_relations(mut self, graph: &GraphModel, relations: &Relations) -> Self {
+        let shown = |idx: usize| !graph.nodes[idx].hidden && self.shows_node(idx);
+        self.relations = relations
+            .in_graph(graph)
+            .into_iter()
+            .filter(|&(from, to, _)| shown(from) && shown(to))
+            .collect();
+        self
+    }
+
+    /// Whether node `idx` is drawn on its own rather than inside a super-node.
+    pub fn shows_node(&self, idx: usize) -> bool {
+        self.clusters
+            .as_ref()
+            .is_none_or(|scene| scene.visible.contains(&idx))
+    }
+}
+
+/// An edge under the pointer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeHover {
+    pub edge: usize,
+    /// Pointer position in canvas-local pixels when the hover started.
+    pub x: f32,
+    pub y: f32,
+}
+
+/// The graph itself. Pointer and touch events are passed up with the node or edge they hit;
+/// the frontend decides what they do.
+#[component]
+pub fn GraphCanvas(
+    graph: ReadSignal<GraphModel>,
+    #[props(default)] backend: RenderBackend,
+    scene: Scene,
+    palette: Palette,
+    /// Search matches; the rest of the graph is dimmed while set.
+    matches: Option<HashSet<usize>>,
+    selected: Option<usize>,
+    hovered_edge: Option<usize>,
+    on_select: EventHandler<usize>,
+    /// Double-click on a node.
+    on_open: EventHandler<usize>,
+    /// Double-click on a super-node.
+    on_expand: EventHandler<usize>,
+    on_edge_enter: EventHandler<(usize, Event<MouseData>)>,
+    on_edge_leave: EventHandler<()>,
+    #[props(default)] on_wheel: EventHandler<Event<WheelData>>,
+    #[props(default)] on_touch_start: EventHandler<Event<TouchData>>,
+    #[props(default)] on_touch_move: EventHandler<Event<TouchData>>,
+    #[props(default)] on_touch_end: EventHandler<Event<TouchData>>,
+    #[props(default)] on_node_touch: EventHandler<(usize, Event<TouchData>)>,
+    #[props(default)] on_node_context_menu: EventHandler<(usize, Event<MouseData>)>,
+) -> Element {
+    // A new graph mounts its nodes at once and its edges a few hundred per frame, so the
+    // window keeps painting instead of waiting for thousands of lines. Layout transitions and
+    // relabelling keep the graph's size, so they draw in full.
+    let size = use_memo(move || {
+        let model = graph.read();
+        (model.nodes.len(), model.edges.len())
+    });
+    let mut drawn = use_signal(|| 0_usize);
+    let mut adding = use_signal(|| None::<Task>);
+    // Edges in the scene, for the task to know when it has drawn them all.
+    let in_scene = use_hook(|| Rc::new(Cell::new(0)));
+    in_scene.set(scene.edges.len());
+    let task_in_scene = in_scene.clone();
+    use_effect(move || {
+        size();
+        if let Some(task) = adding.take() {
+            task.cancel();
+        }
+        drawn.set(0);
+        let in_scene = task_in_scene.clone();
+        adding.set(Some(spawn(async move {
+            while *drawn.peek() < in_scene.get() {
+                // Without a frame to wait for, everything is drawn straight away.
+                if document::eval(NEXT_FRAME_JS).join::<bool>().await.is_err() {
+                    break;
+                }
+                let next = *drawn.peek() + EDGES_PER_FRAME;
+                drawn.set(next);
+            }
+            // From here on, pans and zooms draw every edge of their scene.
+            drawn.set(usize::MAX);
+        })));
+    });
+    let model = graph.read();
+    let highlight = Highlight {
+        matches: matches.as_ref(),
+        selected,
+        hovered_edge,
+    };
+    let frame = Frame::new(&model, &scene, &palette, highlight, drawn());
+    let mut svg = LiveSvg {
+        graph,
+        paint: backend == RenderBackend::Svg,
+        on_select,
+        on_open,
+        on_expand,
+        on_edge_enter,
+        on_edge_leave,
+        on_node_touch,
+        on_node_context_menu,
+    };
+    let script = (backend == RenderBackend::Canvas2d).then(|| {
+        Canvas2dRenderer {
+            canvas_id: PAINT_CANVAS_ID.to_string(),
+            outline: String::new(),
+        }
+        .render(&frame)
+    });
+    use_effect(use_reactive!(|script| {
+        if let Some(script) = script {
+            // Without a webview to run it, there is nothing to paint.
+            let _ = document::eval(&script);
+        }
+    }));
+    rsx! {
+        if script.is_some() {
+            canvas { id: PAINT_CANVAS_ID, class: "graph-paint" }
+        }
+        svg {
+            width: "100%",
+            height: "100%",
+            view_box: "{scene.view_box}",
+            // The painted canvas stretches the viewBox to fill it, so the targets must too.
+            preserve_aspect_ratio: if script.is_some() { "none" } else { "xMidYMid meet" },
+            "role": "img",
+            "aria-label": tr!("canvas-label", users = model.user_count, anime = model.anime_count),
+            onwheel: move |evt| on_wheel.call(evt),
+            ontouchstart: move |evt| on_touch_start.call(evt),
+            ontouchmove: move |evt| on_touch_move.call(evt),
+            ontouchend: move |evt| on_touch_end.call(evt),
+            ontouchcancel: move |evt| on_touch_end.call(evt),
+            {svg.render(&frame)}
+        }
+    }
+}
+
+/// Draws frames as the canvas's live SVG elements, with the pointer events passed up. Without
+/// `paint` only the invisible targets are drawn, over a canvas painted by another backend.
+struct LiveSvg {
+    graph: ReadSignal<GraphModel>,
+    paint: bool,
+    on_select: EventHandler<usize>,
+    on_open: EventHandler<usize>,
+    on_expand: EventHandler<usize>,
+    on_edge_enter: EventHandler<(usize, Event<MouseData>)>,
+    on_edge_leave: EventHandler<()>,
+    on_node_touch: EventHandler<(usize, Event<TouchData>)>,
+    on_node_context_menu: EventHandler<(usize, Event<MouseData>)>,
+}
+
+impl GraphRenderer for LiveSvg {
+    type Output = Element;
+
+    fn render(&mut self, frame: &Frame<'_>) -> Element {
+        let LiveSvg {
+            graph,
+            paint,
+            on_select,
+            on_open,
+            on_expand,
+            on_edge_enter,
+            on_edge_leave,
+            on_node_touch,
+            on_node_context_menu,
+        } = *self;
+        let model = graph.read();
+        let fill = |color: &str| {
+            if paint {
+                color.to_string()
+            } else {
+                "transparent".to_string()
+            }
+        };
+        rsx! {
+            for line in frame.lines.iter() {
+                if paint {
+                    line {
+                        x1: "{line.x1}",
+                        y1: "{line.y1}",
+                        x2: "{line.x2}",
+                        y2: "{line.y2}",
+                        stroke: "{line.color}",
+                        stroke_width: "{line.width}",
+                        stroke_opacity: "{line.opacity}",
+                        class: if line.hovered { "edge hovered" } else { "edge" },
+                    }
+                }
+                if let (EdgeKind::Similarity, Some(index)) = (line.kind, line.edge) {
+                    // Transparent, wider twin of the edge so thin strokes are easy to hover.
+                    line {
+                        class: "edge-hit",
+                        x1: "{line.x1}",
+                        y1: "{line.y1}",
+                        x2: "{line.x2}",
+                        y2: "{line.y2}",
+                        stroke_width: "{frame.hit_width}",
+                        onmouseenter: move |evt: Event<MouseData>| on_edge_enter.call((index, evt)),
+                        onmouseleave: move |_| on_edge_leave.call(()),
+                    }
+                }
+            }
+            if paint {
+                for arrow in frame.arrows.iter() {
+                    path {
+                        class: "relation",
+                        d: arrow_path(arrow),
+                        fill: "none",
+                        stroke: "{arrow.color}",
+                        stroke_width: "{arrow.width}",
+                        stroke_opacity: "{arrow.opacity}",
+                    }
+                }
+            }
+            for circle in frame.circles.iter() {
+                match circle.kind {
+                    CircleKind::SuperNode(community) => rsx! {
+                        circle {
+                            key: "community:{community}",
+                            class: "super-node",
+                            cx: "{circle.x}",
+                            cy: "{circle.y}",
+                            r: "{circle.radius}",
+                            fill: fill(circle.color),
+                            ondoubleclick: move |evt| {
+                                evt.stop_propagation();
+                                on_expand.call(community);
+                            },
+                            title { "{circle.label}" }
+                        }
+                    },
+                    CircleKind::Node(idx) => rsx! {
+                        circle {
+                            key: "{model.nodes[idx].id}",
+                            class: if paint { circle.state.class() } else { "node-hit" },
+                            onclick: move |evt| {
+                                evt.stop_propagation();
+                                on_select.call(idx);
+                            },
+                            ondoubleclick: move |_| on_open.call(idx),
+                            cx: "{circle.x}",
+                            cy: "{circle.y}",
+                            r: "{circle.radius}",
+                            fill: fill(circle.color),
+                            ontouchstart: move |evt| on_node_touch.call((idx, evt)),
+                            oncontextmenu: move |evt| on_node_context_menu.call((idx, evt)),
+                            title { "{circle.label}" }
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeTooltip {
+    pub x: f32,
+    pub y: f32,
+    pub left: String,
+    pub right: String,
+    pub left_poster: Option<Rc<str>>,
+    pub right_poster: Option<Rc<str>>,
+    pub co_raters: usize,
+    pub score: f64,
+}
+
+impl EdgeTooltip {
+    /// The tooltip for a hovered edge; `poster` gives a node's thumbnail, if loaded.
+    pub fn new(
+        graph: &GraphModel,
+        hover: EdgeHover,
+        poster: impl Fn(usize) -> Option<Rc<str>>,
+    ) -> Option<Self> {
+        let edge = graph.edges.get(hover.edge)?;
+        Some(Self {
+            x: hover.x,
+            y: hover.y,
+            left: graph.nodes[edge.source].label.clone(),
+            right: graph.nodes[edge.target].label.clone(),
+            left_poster: poster(edge.source),
+            right_poster: poster(edge.target),
+            co_raters: edge.co_raters,
+            score: edge.weight,
+        })
+    }
+}
+
+#[component]
+pub fn EdgeTooltipCard(tooltip: EdgeTooltip) -> Element {
+    let raters = if tooltip.co_raters == 1 {
+        tr!("edge-co-raters-one")
+    } else {
+        tr!("edge-co-raters", count = tooltip.co_raters)
+    };
+    rsx! {
+        div {
+            class: "edge-tooltip",
+            role: "tooltip",
+            style: "left: {tooltip.x + 12.0}px; top: {tooltip.y + 12.0}px;",
+            if tooltip.left_poster.is_some() || tooltip.right_poster.is_some() {
+                div { class: "row",
+                    for poster in [tooltip.left_poster.clone(), tooltip.right_poster.clone()].into_iter().flatten() {
+                        img { class: "thumbnail", src: "{poster}", alt: "" }
+                    }
+                }
+            }
+            strong { "{tooltip.left}" }
+            span { class: "tiny", "↔" }
+            strong { "{tooltip.right}" }
+            div { class: "row",
+                span { {tr!("edge-co-raters-label")} }
+                span { "{raters}" }
+            }
+            div { class: "row",
+                span { {tr!("edge-similarity")} }
+                span { "{tooltip.score:+.2}" }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContextMenu {
+    pub node: usize,
+    /// Anchor position in canvas-local pixels.
+    pub x: f32,
+    pub y: f32,
+}
+
+#[component]
+pub fn NodeContextMenu(
+    x: f32,
+    y: f32,
+    label: String,
+    on_search: EventHandler<String>,
+    on_focus: EventHandler<()>,
+) -> Element {
+    let search_label = label.clone();
+    rsx! {
+        div {
+            class: "context-menu",
+            style: "left: {x}px; top: {y}px;",
+            role: "menu",
+            aria_label: "{label}",
+            onclick: move |evt| evt.stop_propagation(),
+            strong { "{label}" }
+            button {
+                role: "menuitem",
+                onmounted: focus_on_mount,
+                onclick: move |_| on_search.call(search_label.clone()),
+                {tr!("context-highlight")}
+            }
+            button { role: "menuitem", onclick: move |_| on_focus.call(()), {tr!("context-zoom")} }
+        }
+    }
+}
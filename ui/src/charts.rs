@@ -0,0 +1,413 @@
+//! Small SVG charts for the stats tab. Each draws into a fixed view box that scales to the panel
+//! width, takes its aggregates ready-made (see `wasiw_core::stats::Breakdown`) and labels itself
+//! for screen readers.
+
+use dioxus::prelude::*;
+
+/// View box width every chart draws in.
+const WIDTH: f64 = 300.0;
+/// Space under the plot for axis labels.
+const AXIS: f64 = 14.0;
+const BAR_HEIGHT: f64 = 14.0;
+const BAR_GAP: f64 = 3.0;
+/// Share of the width ranked bars leave for their labels.
+const LABEL_SHARE: f64 = 0.45;
+/// Longest label ranked bars show before cutting it short.
+const MAX_LABEL_CHARS: usize = 26;
+
+/// Vertical bars, one per labelled count, e.g. ratings by score.
+#[component]
+pub fn Histogram(title: String, bars: Vec<(String, usize)>, color: String) -> Element {
+    let height = 100.0;
+    let max = bars
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+    let slot = WIDTH / bars.len().max(1) as f64;
+    rsx! {
+        figure { class: "chart",
+            figcaption { class: "tiny", "{title}" }
+            svg {
+                view_box: "0 0 {WIDTH} {height + AXIS}",
+                "role": "img",
+                "aria-label": "{title}",
+                for (index, (label, count)) in bars.into_iter().enumerate() {
+                    g { key: "{index}",
+                        rect {
+                            x: "{index as f64 * slot + 1.0}",
+                            y: "{height - height * count as f64 / max}",
+                            width: "{(slot - 2.0).max(1.0)}",
+                            height: "{height * count as f64 / max}",
+                            fill: "{color}",
+                            title { "{label}: {count}" }
+                        }
+                        text {
+                            class: "chart-label",
+                            x: "{index as f64 * slot + slot / 2.0}",
+                            y: "{height + AXIS - 3.0}",
+                            text_anchor: "middle",
+                            "{label}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Horizontal bars with their labels and counts, longest first as given, e.g. the most rated
+/// anime.
+#[component]
+pub fn RankedBars(title: String, bars: Vec<(String, usize)>, color: String) -> Element {
+    let max = bars
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+    let height = bars.len() as f64 * (BAR_HEIGHT + BAR_GAP);
+    let left = WIDTH * LABEL_SHARE;
+    // Room for the count after the longest bar.
+    let span = WIDTH - left - 36.0;
+    rsx! {
+        figure { class: "chart",
+            figcaption { class: "tiny", "{title}" }
+            svg {
+                view_box: "0 0 {WIDTH} {height.max(1.0)}",
+                "role": "img",
+                "aria-label": "{title}",
+                for (index, (label, count)) in bars.into_iter().enumerate() {
+                    g { key: "{index}",
+                        text {
+                            class: "chart-label",
+                            x: "{left - 4.0}",
+                            y: "{index as f64 * (BAR_HEIGHT + BAR_GAP) + BAR_HEIGHT - 3.0}",
+                            text_anchor: "end",
+                            title { "{label}" }
+                            {shorten(&label)}
+                        }
+                        rect {
+                            x: "{left}",
+                            y: "{index as f64 * (BAR_HEIGHT + BAR_GAP)}",
+                            width: "{(span * count as f64 / max).max(1.0)}",
+                            height: "{BAR_HEIGHT}",
+                            fill: "{color}",
+                        }
+                        text {
+                            class: "chart-label",
+                            x: "{left + (span * count as f64 / max).max(1.0) + 4.0}",
+                            y: "{index as f64 * (BAR_HEIGHT + BAR_GAP) + BAR_HEIGHT - 3.0}",
+                            "{count}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Points on two axes starting at zero, e.g. each user's mean score against its variance.
+#[component]
+pub fn ScatterPlot(
+    title: String,
+    points: Vec<(f64, f64)>,
+    /// Largest value on each axis.
+    x_max: f64,
+    y_max: f64,
+    x_label: String,
+    y_label: String,
+    color: String,
+) -> Element {
+    let height = 160.0;
+    let plot_height = height - AXIS;
+    let x_max = x_max.max(f64::EPSILON);
+    let y_max = y_max.max(f64::EPSILON);
+    rsx! {
+        figure { class: "chart",
+            figcaption { class: "tiny", "{title}" }
+            svg {
+                view_box: "0 0 {WIDTH} {height}",
+                "role": "img",
+                "aria-label": "{title}",
+                line {
+                    class: "chart-axis",
+                    x1: "0",
+                    y1: "{plot_height}",
+                    x2: "{WIDTH}",
+                    y2: "{plot_height}",
+                }
+                line {
+                    class: "chart-axis",
+                    x1: "0",
+                    y1: "0",
+                    x2: "0",
+                    y2: "{plot_height}",
+                }
+                for (index, (x, y)) in points.into_iter().enumerate() {
+                    circle {
+                        key: "{index}",
+                        cx: "{WIDTH * (x / x_max).clamp(0.0, 1.0)}",
+                        cy: "{plot_height - plot_height * (y / y_max).clamp(0.0, 1.0)}",
+                        r: "2",
+                        fill: "{color}",
+                        fill_opacity: "0.6",
+                    }
+                }
+                text {
+                    class: "chart-label",
+                    x: "{WIDTH}",
+                    y: "{height - 3.0}",
+                    text_anchor: "end",
+                    "{x_label} → {x_max:.0}"
+                }
+                text { class: "chart-label", x: "4", y: "10", "{y_label} ↑ {y_max:.1}" }
+            }
+        }
+    }
+}
+
+/// Values joined in order, e.g. an anime's mean score month by month. Only the first and last
+/// labels are written out.
+#[component]
+pub fn LineChart(title: String, points: Vec<(String, f64)>, y_max: f64, color: String) -> Element {
+    let height = 100.0;
+    let plot_height = height - AXIS;
+    let y_max = y_max.max(f64::EPSILON);
+    let step = WIDTH / points.len().saturating_sub(1).max(1) as f64;
+    let line = points
+        .iter()
+        .enumerate()
+        .map(|(index, (_, value))| {
+            let y = plot_height - plot_height * (value / y_max).clamp(0.0, 1.0);
+            format!("{:.1},{y:.1}", index as f64 * step)
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let first = points
+        .first()
+        .map(|(label, _)| label.clone())
+        .unwrap_or_default();
+    let last = points
+        .last()
+        .map(|(label, _)| label.clone())
+        .unwrap_or_default();
+    rsx! {
+        figure { class: "chart",
+            figcaption { class: "tiny", "{title}" }
+            svg {
+                view_box: "0 0 {WIDTH} {height}",
+                "role": "img",
+                "aria-label": "{title}",
+                line {
+                    class: "chart-axis",
+                    x1: "0",
+                    y1: "{plot_height}",
+                    x2: "{WIDTH}",
+                    y2: "{plot_height}",
+                }
+                polyline {
+                    points: "{line}",
+                    fill: "none",
+                    stroke: "{color}",
+                    stroke_width: "2",
+                }
+                text { class: "chart-label", x: "0", y: "{height - 3.0}", "{first}" }
+                text {
+                    class: "chart-label",
+                    x: "{WIDTH}",
+                    y: "{height - 3.0}",
+                    text_anchor: "end",
+                    "{last}"
+                }
+            }
+        }
+    }
+}
+
+/// Values on spokes around a centre, e.g. a user's mean score per genre. Needs three spokes
+/// or more to enclose an area.
+#[component]
+pub fn RadarChart(title: String, axes: Vec<(String, f64)>, max: f64, color: String) -> Element {
+    let size = 220.0;
+    let centre = size / 2.0;
+    // Room around the rings for the spoke labels.
+    let radius = centre - 34.0;
+    let max = max.max(f64::EPSILON);
+    let count = axes.len().max(1) as f64;
+    let point = |index: usize, share: f64| {
+        let angle = std::f64::consts::TAU * index as f64 / count - std::f64::consts::FRAC_PI_2;
+        (
+            centre + radius * share * angle.cos(),
+            centre + radius * share * angle.sin(),
+        )
+    };
+    let polygon = |shares: Vec<f64>| {
+        shares
+            .into_iter()
+            .enumerate()
+            .map(|(index, share)| {
+                let (x, y) = point(index, share);
+                format!("{x:.1},{y:.1}")
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let rings: Vec<String> = [0.5, 1.0]
+        .into_iter()
+        .map(|ring| polygon(vec![ring; axes.len()]))
+        .collect();
+    let shape = polygon(
+        axes.iter()
+            .map(|(_, value)| (value / max).clamp(0.0, 1.0))
+            .collect(),
+    );
+    let spokes: Vec<_> = axes
+        .iter()
+        .enumerate()
+        .map(|(index, (label, value))| {
+            let (x, y) = point(index, 1.0);
+            let (label_x, label_y) = point(index, 1.22);
+            (label.clone(), *value, x, y, label_x, label_y)
+        })
+        .collect();
+    rsx! {
+        figure { class: "chart",
+            figcaption { class: "tiny", "{title}" }
+            svg {
+                view_box: "0 0 {size} {size}",
+                "role": "img",
+                "aria-label": "{title}",
+                for ring in rings {
+                    polygon { class: "chart-axis", points: "{ring}", fill: "none" }
+                }
+                for (index, (label, value, x, y, label_x, label_y)) in spokes.into_iter().enumerate() {
+                    g { key: "{index}",
+                        line {
+                            class: "chart-axis",
+                            x1: "{centre}",
+                            y1: "{centre}",
+                            x2: "{x}",
+                            y2: "{y}",
+                        }
+                        text {
+                            class: "chart-label",
+                            x: "{label_x}",
+                            y: "{label_y}",
+                            text_anchor: "middle",
+                            dominant_baseline: "middle",
+                            title { "{label}: {value:.1}" }
+                            {shorten(&label)}
+                        }
+                    }
+                }
+                polygon {
+                    points: "{shape}",
+                    fill: "{color}",
+                    fill_opacity: "0.35",
+                    stroke: "{color}",
+                    stroke_width: "2",
+                }
+            }
+        }
+    }
+}
+
+/// Days in columns of weeks, Monday at the top, shaded by how much happened on each, like a
+/// contribution calendar. `first_weekday` is the first day's, in days after Monday. Clicking a
+/// day passes its index in `days` to `on_select`.
+#[component]
+pub fn CalendarHeatmap(
+    title: String,
+    first_weekday: u8,
+    days: Vec<(String, usize)>,
+    selected: Option<usize>,
+    color: String,
+    on_select: EventHandler<usize>,
+) -> Element {
+    let offset = usize::from(first_weekday.min(6));
+    let weeks = (days.len() + offset).div_ceil(7).max(1);
+    let cell = WIDTH / weeks as f64;
+    let max = days
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+    rsx! {
+        figure { class: "chart",
+            figcaption { class: "tiny", "{title}" }
+            svg {
+                view_box: "0 0 {WIDTH} {cell * 7.0}",
+                "role": "img",
+                "aria-label": "{title}",
+                for (index, (label, count)) in days.into_iter().enumerate() {
+                    rect {
+                        key: "{index}",
+                        class: if count == 0 { "chart-cell chart-track" } else { "chart-cell" },
+                        class: if selected == Some(index) { "selected" },
+                        x: "{((index + offset) / 7) as f64 * cell}",
+                        y: "{((index + offset) % 7) as f64 * cell}",
+                        width: "{(cell - 1.0).max(0.5)}",
+                        height: "{(cell - 1.0).max(0.5)}",
+                        rx: "1",
+                        fill: if count > 0 { "{color}" },
+                        // Four shades, like a contribution calendar.
+                        fill_opacity: if count > 0 { "{(4.0 * count as f64 / max).ceil() / 4.0}" },
+                        onclick: move |_| on_select.call(index),
+                        title { "{label}: {count}" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A single share of a whole as a filled bar, e.g. how much of the rating matrix is filled.
+#[component]
+pub fn ShareBar(title: String, share: f64, label: String, color: String) -> Element {
+    rsx! {
+        figure { class: "chart",
+            figcaption { class: "tiny", "{title}" }
+            svg {
+                view_box: "0 0 {WIDTH} {BAR_HEIGHT}",
+                "role": "img",
+                "aria-label": "{title}: {label}",
+                rect {
+                    class: "chart-track",
+                    width: "{WIDTH}",
+                    height: "{BAR_HEIGHT}",
+                }
+                // Tiny shares still get a visible sliver.
+                rect {
+                    width: "{(WIDTH * share.clamp(0.0, 1.0)).max(1.0)}",
+                    height: "{BAR_HEIGHT}",
+                    fill: "{color}",
+                }
+            }
+            span { class: "tiny", "{label}" }
+        }
+    }
+}
+
+fn shorten(label: &str) -> String {
+    if label.chars().count() <= MAX_LABEL_CHARS {
+        return label.to_string();
+    }
+    let cut: String = label.chars().take(MAX_LABEL_CHARS - 1).collect();
+    format!("{cut}…")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_labels_are_cut_short() {
+        assert_eq!(shorten("Mushishi"), "Mushishi");
+        let long = shorten("Yahari Ore no Seishun Love Comedy wa Machigatteiru");
+        assert_eq!(long.chars().count(), MAX_LABEL_CHARS);
+        assert!(long.ends_with('…'));
+    }
+}
@@ -0,0 +1,456 @@
+//! Side-panel controls: stats, search, layout and filter, sampling, duplicate handling, title
+//! form, theme, interface scale, language, palette, and the startup dataset and rendering
+//! settings.
+
+use dioxus::prelude::*;
+use wasiw_core::sampling::Sampling;
+use wasiw_core::titles::TitleForm;
+use wasiw_core::{GraphFilter, GraphMode, LayoutKind, MergeStrategy};
+
+use crate::i18n::{self, tr, Locale};
+use crate::theme::{Palette, ThemePreference, UiScale};
+
+#[component]
+pub fn LayoutControls(
+    layout: LayoutKind,
+    filter: GraphFilter,
+    on_layout: EventHandler<LayoutKind>,
+    on_filter: EventHandler<GraphFilter>,
+) -> Element {
+    rsx! {
+        label { class: "field",
+            span { class: "tiny", {tr!("layout-label")} }
+            select {
+                onchange: move |evt| {
+                    if let Some(kind) = LayoutKind::ALL.into_iter().find(|k| k.label() == evt.value()) {
+                        on_layout.call(kind);
+                    }
+                },
+                for kind in LayoutKind::ALL {
+                    option { value: kind.label(), selected: kind == layout, {i18n::layout_name(kind)} }
+                }
+            }
+        }
+        label { class: "toggle",
+            input {
+                r#type: "checkbox",
+                checked: filter.show_users,
+                onchange: move |evt| {
+                    on_filter.call(GraphFilter {
+                        show_users: evt.checked(),
+                        ..filter
+                    })
+                },
+            }
+            span { {tr!("filter-show-users")} }
+        }
+        label { class: "field",
+            span { class: "tiny", {tr!("filter-min-ratings", count = filter.min_anime_ratings)} }
+            input {
+                r#type: "range",
+                min: "1",
+                max: "10",
+                value: "{filter.min_anime_ratings}",
+                onchange: move |evt| {
+                    if let Ok(min_anime_ratings) = evt.value().parse() {
+                        on_filter.call(GraphFilter {
+                            min_anime_ratings,
+                            ..filter
+                        });
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Picks what the graph's nodes are: users and anime, or genres.
+#[component]
+pub fn GraphModePicker(mode: GraphMode, on_change: EventHandler<GraphMode>) -> Element {
+    rsx! {
+        label { class: "field",
+            span { class: "tiny", {tr!("graph-mode-label")} }
+            select {
+                onchange: move |evt| {
+                    if let Some(mode) = GraphMode::ALL.into_iter().find(|m| m.label() == evt.value()) {
+                        on_change.call(mode);
+                    }
+                },
+                for option_mode in GraphMode::ALL {
+                    option {
+                        value: option_mode.label(),
+                        selected: option_mode == mode,
+                        {i18n::graph_mode_name(option_mode)}
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Picks a subsample of the dataset to build the graph from; applied before the layout runs.
+#[component]
+pub fn SamplingControls(
+    sampling: Sampling,
+    total_users: usize,
+    shown_users: usize,
+    on_change: EventHandler<Sampling>,
+) -> Element {
+    rsx! {
+        label { class: "field",
+            span { class: "tiny", {tr!("sampling-label")} }
+            select {
+                onchange: move |evt| {
+                    if let Some(kind) = Sampling::KINDS.into_iter().find(|k| k.label() == evt.value()) {
+                        on_change.call(kind.with_count(sampling.count().unwrap_or(Sampling::DEFAULT_COUNT)));
+                    }
+                },
+                for kind in Sampling::KINDS {
+                    option {
+                        value: kind.label(),
+                        selected: kind.label() == sampling.label(),
+                        {i18n::sampling_name(kind)}
+                    }
+                }
+            }
+        }
+        if let Some(count) = sampling.count() {
+            label { class: "field",
+                span { class: "tiny",
+                    if matches!(sampling, Sampling::TopAnime { .. }) {
+                        {tr!("sampling-anime-count")}
+                    } else {
+                        {tr!("sampling-user-count")}
+                    }
+                }
+                input {
+                    r#type: "number",
+                    min: "1",
+                    value: "{count}",
+                    onchange: move |evt| {
+                        if let Ok(count) = evt.value().parse::<usize>() {
+                            on_change.call(sampling.with_count(count.max(1)));
+                        }
+                    },
+                }
+            }
+            p { class: "tiny", {tr!("sampling-showing", shown = shown_users, total = total_users)} }
+        }
+    }
+}
+
+/// Chooses how an anime rated more than once by one user is resolved when a file loads.
+#[component]
+pub fn DuplicatePicker(active: MergeStrategy, on_select: EventHandler<MergeStrategy>) -> Element {
+    rsx! {
+        label { class: "field",
+            span { class: "tiny", {tr!("duplicates-label")} }
+            select {
+                onchange: move |evt| {
+                    if let Some(strategy) = MergeStrategy::ALL
+                        .into_iter()
+                        .find(|s| s.label() == evt.value())
+                    {
+                        on_select.call(strategy);
+                    }
+                },
+                for strategy in MergeStrategy::ALL {
+                    option {
+                        value: strategy.label(),
+                        selected: strategy == active,
+                        {i18n::duplicates_name(strategy)}
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Chooses the form anime titles are shown in. With `default`, the picker is a view's own
+/// choice and offers following the default as well; `None` stands for that.
+#[component]
+pub fn TitlePicker(
+    label: String,
+    active: Option<TitleForm>,
+    default: Option<TitleForm>,
+    on_select: EventHandler<Option<TitleForm>>,
+) -> Element {
+    rsx! {
+        label { class: "field",
+            span { class: "tiny", "{label}" }
+            select {
+                onchange: move |evt| {
+                    let form = TitleForm::ALL.into_iter().find(|f| f.label() == evt.value());
+                    if form.is_some() || default.is_some() {
+                        on_select.call(form);
+                    }
+                },
+                if let Some(default) = default {
+                    option { value: "", selected: active.is_none(),
+                        {tr!("titles-default", form = i18n::title_form_name(default))}
+                    }
+                }
+                for form in TitleForm::ALL {
+                    option {
+                        value: form.label(),
+                        selected: active == Some(form),
+                        {i18n::title_form_name(form)}
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn ThemePicker(active: ThemePreference, on_select: EventHandler<ThemePreference>) -> Element {
+    rsx! {
+        label { class: "field",
+            span { class: "tiny", {tr!("theme-label")} }
+            select {
+                onchange: move |evt| {
+                    if let Some(preference) = ThemePreference::ALL
+                        .into_iter()
+                        .find(|p| p.label() == evt.value())
+                    {
+                        on_select.call(preference);
+                    }
+                },
+                for preference in ThemePreference::ALL {
+                    option {
+                        value: preference.label(),
+                        selected: preference == active,
+                        {preference.name()}
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn ScalePicker(active: UiScale, on_select: EventHandler<UiScale>) -> Element {
+    rsx! {
+        label { class: "field",
+            span { class: "tiny", {tr!("scale-label")} }
+            select {
+                onchange: move |evt| {
+                    if let Some(scale) = UiScale::ALL.into_iter().find(|s| s.label() == evt.value()) {
+                        on_select.call(scale);
+                    }
+                },
+                for scale in UiScale::ALL {
+                    option {
+                        value: scale.label(),
+                        selected: scale == active,
+                        {scale.name()}
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn LanguagePicker(active: Locale, on_select: EventHandler<Locale>) -> Element {
+    rsx! {
+        label { class: "field",
+            span { class: "tiny", {tr!("settings-language")} }
+            select {
+                onchange: move |evt| {
+                    if let Some(locale) = Locale::ALL
+                        .into_iter()
+                        .find(|l| l.native_name() == evt.value())
+                    {
+                        on_select.call(locale);
+                    }
+                },
+                for locale in Locale::ALL {
+                    option {
+                        value: locale.native_name(),
+                        selected: locale == active,
+                        "{locale.native_name()}"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn PalettePicker(
+    palettes: Vec<Palette>,
+    active: String,
+    skipped: usize,
+    on_select: EventHandler<String>,
+) -> Element {
+    rsx! {
+        label { class: "field",
+            span { class: "tiny", {tr!("palette-label")} }
+            select { onchange: move |evt| on_select.call(evt.value()),
+                for palette in palettes {
+                    option {
+                        value: "{palette.name}",
+                        selected: palette.name == active,
+                        "{palette.name}"
+                    }
+                }
+            }
+            if skipped > 0 {
+                span { class: "tiny", {tr!("palette-skipped", count = skipped)} }
+            }
+        }
+    }
+}
+
+/// The dataset opened at startup: a pinned file, or whichever dataset was opened last.
+#[component]
+pub fn StartupDataset(
+    pinned: Option<String>,
+    /// File the graph is currently built from, offered for pinning.
+    current: Option<String>,
+    on_change: EventHandler<Option<String>>,
+) -> Element {
+    let offer = current
+        .clone()
+        .filter(|current| pinned.as_ref() != Some(current));
+    rsx! {
+        div { class: "field",
+            span { class: "tiny", {tr!("startup-label")} }
+            match &pinned {
+                Some(path) => rsx! { span { class: "tiny", strong { "{path}" } } },
+                None => rsx! { span { class: "tiny", {tr!("startup-latest")} } },
+            }
+            if let Some(current) = offer {
+                button {
+                    class: "action",
+                    onclick: move |_| on_change.call(Some(current.clone())),
+                    {tr!("startup-use-open")}
+                }
+            }
+            if pinned.is_some() {
+                button { class: "action", onclick: move |_| on_change.call(None), {tr!("startup-clear")} }
+            }
+        }
+    }
+}
+
+/// How many edges the canvas draws and the seed the layout shuffles nodes with.
+#[component]
+pub fn RenderControls(
+    max_edges: usize,
+    layout_seed: u64,
+    on_max_edges: EventHandler<usize>,
+    on_layout_seed: EventHandler<u64>,
+) -> Element {
+    rsx! {
+        label { class: "field",
+            span { class: "tiny", {tr!("render-max-edges")} }
+            input {
+                r#type: "number",
+                min: "100",
+                step: "100",
+                value: "{max_edges}",
+                onchange: move |evt| {
+                    if let Ok(max_edges) = evt.value().parse::<usize>() {
+                        on_max_edges.call(max_edges.max(100));
+                    }
+                },
+            }
+        }
+        label { class: "field",
+            span { class: "tiny", {tr!("render-seed")} }
+            input {
+                r#type: "number",
+                min: "0",
+                value: "{layout_seed}",
+                onchange: move |evt| {
+                    if let Ok(seed) = evt.value().parse::<u64>() {
+                        on_layout_seed.call(seed);
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Desktop notifications on or off, and the hours of the day without them, from `start` up
+/// to `end`.
+#[component]
+pub fn NotificationControls(
+    enabled: bool,
+    quiet_hours: Option<(u8, u8)>,
+    on_enabled: EventHandler<bool>,
+    on_quiet_hours: EventHandler<Option<(u8, u8)>>,
+) -> Element {
+    let hour_input = move |hour: u8, set: fn((u8, u8), u8) -> (u8, u8)| {
+        rsx! {
+            input {
+                r#type: "number",
+                min: "0",
+                max: "23",
+                value: "{hour}",
+                onchange: move |evt| {
+                    if let (Some(hours), Ok(hour)) = (quiet_hours, evt.value().parse::<u8>()) {
+                        on_quiet_hours.call(Some(set(hours, hour.min(23))));
+                    }
+                },
+            }
+        }
+    };
+    rsx! {
+        label { class: "toggle",
+            input {
+                r#type: "checkbox",
+                checked: enabled,
+                onchange: move |evt| on_enabled.call(evt.checked()),
+            }
+            span { {tr!("notify-enabled")} }
+        }
+        if enabled {
+            label { class: "toggle",
+                input {
+                    r#type: "checkbox",
+                    checked: quiet_hours.is_some(),
+                    onchange: move |evt| on_quiet_hours.call(evt.checked().then_some((22, 7))),
+                }
+                span { {tr!("notify-quiet-hours")} }
+            }
+            if let Some((start, end)) = quiet_hours {
+                div { class: "row",
+                    span { class: "tiny", {tr!("notify-quiet-from")} }
+                    {hour_input(start, |(_, end), start| (start, end))}
+                    span { class: "tiny", {tr!("notify-quiet-to")} }
+                    {hour_input(end, |(start, _), end| (start, end))}
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn SearchBox(query: Signal<String>, match_count: Option<usize>) -> Element {
+    rsx! {
+        div { class: "search",
+            input {
+                r#type: "search",
+                placeholder: tr!("search-placeholder"),
+                value: "{query}",
+                oninput: move |evt| query.set(evt.value()),
+            }
+            if let Some(count) = match_count {
+                span { class: "tiny", {tr!("search-matches", count = count)} }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn StatRow(label: String, value: String) -> Element {
+    rsx! {
+        div { class: "row",
+            span { "{label}" }
+            strong { "{value}" }
+        }
+    }
+}
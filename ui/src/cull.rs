@@ -0,0 +1,290 @@
+//! Which edges lie in view. The canvas draws at most a few thousand edges, and without an
+//! index those are simply the strongest visible ones anywhere, so zoomed in most of them are
+//! off screen. [`CullIndex`] files each edge under the cells of a grid over the layout that
+//! its bounding box covers, and a scene takes the strongest edges among those near the
+//! viewBox instead. Building it walks every edge, so [`use_cull_index`] spreads that over
+//! frames with the [`schedule`](crate::schedule) budget and scenes go without it meanwhile.
+
+use dioxus::prelude::*;
+use wasiw_core::{Edge, GraphModel, RenderEdge};
+
+use crate::camera::ViewBox;
+use crate::schedule::{use_frame_scheduler, FrameBudget};
+
+/// Grid cells along the longer side of the layout.
+const GRID_SIDE: usize = 64;
+/// Edges spanning more cells than this are kept in one list checked on every query, so a few
+/// long edges don't fill most of the grid.
+const MAX_CELLS_PER_EDGE: usize = 32;
+/// Edges filed between budget checks.
+const EDGES_PER_STEP: usize = 4096;
+
+/// Edges by grid cell, for the node positions the graph had when it was built.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CullIndex {
+    x: f32,
+    y: f32,
+    cell: f32,
+    columns: usize,
+    rows: usize,
+    /// Edge indices per cell, row by row, in edge order.
+    cells: Vec<Vec<u32>>,
+    /// Edges spanning too many cells to file.
+    long: Vec<u32>,
+    node_count: usize,
+    edge_count: usize,
+}
+
+impl CullIndex {
+    /// An empty grid over the bounding box of `graph`'s nodes.
+    fn empty(graph: &GraphModel) -> Self {
+        let (mut min_x, mut min_y) = (f32::MAX, f32::MAX);
+        let (mut max_x, mut max_y) = (f32::MIN, f32::MIN);
+        for node in &graph.nodes {
+            min_x = min_x.min(node.x);
+            min_y = min_y.min(node.y);
+            max_x = max_x.max(node.x);
+            max_y = max_y.max(node.y);
+        }
+        if graph.nodes.is_empty() {
+            (min_x, min_y, max_x, max_y) = (0.0, 0.0, 0.0, 0.0);
+        }
+        let cell = ((max_x - min_x).max(max_y - min_y) / GRID_SIDE as f32).max(1.0);
+        let columns = ((max_x - min_x) / cell) as usize + 1;
+        let rows = ((max_y - min_y) / cell) as usize + 1;
+        Self {
+            x: min_x,
+            y: min_y,
+            cell,
+            columns,
+            rows,
+            cells: vec![Vec::new(); columns * rows],
+            long: Vec::new(),
+            node_count: graph.nodes.len(),
+            edge_count: graph.edges.len(),
+        }
+    }
+
+    /// Builds the whole index at once.
+    pub fn new(graph: &GraphModel) -> Self {
+        let mut index = Self::empty(graph);
+        index.file(graph, 0..graph.edges.len());
+        index
+    }
+
+    fn file(&mut self, graph: &GraphModel, edges: std::ops::Range<usize>) {
+        for index in edges {
+            let edge = &graph.edges[index];
+            let (columns, rows) = self.span(bounds(graph, edge));
+            if columns.len() * rows.len() > MAX_CELLS_PER_EDGE {
+                self.long.push(index as u32);
+                continue;
+            }
+            for row in rows {
+                for column in columns.clone() {
+                    self.cells[row * self.columns + column].push(index as u32);
+                }
+            }
+        }
+    }
+
+    /// The cell columns and rows a box covers, clamped to the grid.
+    fn span(
+        &self,
+        (min_x, min_y, max_x, max_y): (f32, f32, f32, f32),
+    ) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+        let cell = |value: f32, origin: f32, count: usize| {
+            (((value - origin) / self.cell).max(0.0) as usize).min(count - 1)
+        };
+        (
+            cell(min_x, self.x, self.columns)..cell(max_x, self.x, self.columns) + 1,
+            cell(min_y, self.y, self.rows)..cell(max_y, self.y, self.rows) + 1,
+        )
+    }
+
+    /// Whether the index was built for a graph of `graph`'s size.
+    pub fn fits(&self, graph: &GraphModel) -> bool {
+        self.node_count == graph.nodes.len() && self.edge_count == graph.edges.len()
+    }
+
+    /// Up to `limit` visible edges crossing `view`, strongest first as in
+    /// [`GraphModel::render_edges`], at least `min_stroke_width` wide.
+    pub fn render_edges(
+        &self,
+        graph: &GraphModel,
+        view: ViewBox,
+        limit: usize,
+        min_stroke_width: f32,
+    ) -> Vec<RenderEdge> {
+        let covers_grid = view.x <= self.x
+            && view.y <= self.y
+            && view.x + view.width >= self.x + self.columns as f32 * self.cell
+            && view.y + view.height >= self.y + self.rows as f32 * self.cell;
+        if covers_grid {
+            return graph.render_edges(limit, min_stroke_width);
+        }
+        let (columns, rows) =
+            self.span((view.x, view.y, view.x + view.width, view.y + view.height));
+        let mut candidates = self.long.clone();
+        for row in rows {
+            for column in columns.clone() {
+                candidates.extend(&self.cells[row * self.columns + column]);
+            }
+        }
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+            .into_iter()
+            .map(|index| (index as usize, &graph.edges[index as usize]))
+            .filter(|(_, edge)| graph.is_edge_visible(edge) && crosses(bounds(graph, edge), view))
+            .take(limit)
+            .map(|(index, edge)| RenderEdge {
+                x1: graph.nodes[edge.source].x,
+                y1: graph.nodes[edge.source].y,
+                x2: graph.nodes[edge.target].x,
+                y2: graph.nodes[edge.target].y,
+                kind: edge.kind,
+                stroke_width: edge.stroke_width.max(min_stroke_width),
+                edge: Some(index),
+            })
+            .collect()
+    }
+}
+
+/// An edge's bounding box as `(min_x, min_y, max_x, max_y)`.
+fn bounds(graph: &GraphModel, edge: &Edge) -> (f32, f32, f32, f32) {
+    let (source, target) = (&graph.nodes[edge.source], &graph.nodes[edge.target]);
+    (
+        source.x.min(target.x),
+        source.y.min(target.y),
+        source.x.max(target.x),
+        source.y.max(target.y),
+    )
+}
+
+fn crosses((min_x, min_y, max_x, max_y): (f32, f32, f32, f32), view: ViewBox) -> bool {
+    max_x >= view.x
+        && min_x <= view.x + view.width
+        && max_y >= view.y
+        && min_y <= view.y + view.height
+}
+
+/// A [`CullIndex`] filed a budget at a time.
+#[derive(Debug, Clone)]
+pub struct CullBuild {
+    /// Taken once every edge is filed.
+    index: Option<CullIndex>,
+    next: usize,
+}
+
+impl CullBuild {
+    pub fn new(graph: &GraphModel) -> Self {
+        Self {
+            index: Some(CullIndex::empty(graph)),
+            next: 0,
+        }
+    }
+
+    /// Files edges until all are filed or `budget` is spent; returns the index once done,
+    /// and `None` after that.
+    pub fn step(&mut self, graph: &GraphModel, budget: &FrameBudget) -> Option<CullIndex> {
+        let index = self.index.as_mut()?;
+        let total = index.edge_count.min(graph.edges.len());
+        loop {
+            let end = (self.next + EDGES_PER_STEP).min(total);
+            index.file(graph, self.next..end);
+            self.next = end;
+            if end == total {
+                return self.index.take();
+            }
+            if budget.is_spent() {
+                return None;
+            }
+        }
+    }
+}
+
+/// The cull index of `graph`, rebuilt over the next frames whenever the graph changes and
+/// `None` until then.
+pub fn use_cull_index(graph: ReadSignal<GraphModel>) -> ReadSignal<Option<CullIndex>> {
+    let mut index = use_signal(|| None::<CullIndex>);
+    let mut scheduler = use_frame_scheduler();
+    use_effect(move || {
+        let mut build = CullBuild::new(&graph.read());
+        index.set(None);
+        scheduler.schedule("cull-index", move |budget: &FrameBudget| {
+            match build.step(&graph.peek(), budget) {
+                Some(built) => {
+                    index.set(Some(built));
+                    true
+                }
+                None => false,
+            }
+        });
+    });
+    index.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use wasiw_core::{build_graph, Dataset, EdgeKind, Rating, UserRatings, Viewport};
+
+    fn graph() -> GraphModel {
+        let users = (0..60_u32)
+            .map(|u| UserRatings {
+                user_id: format!("u{u}"),
+                ratings: (0..5)
+                    .map(|k| {
+                        let anime_id = 1 + (u * 3 + k * 7) % 50;
+                        Rating::new(anime_id, format!("Anime {anime_id}"), f64::from(k + 5))
+                    })
+                    .collect(),
+            })
+            .collect();
+        build_graph(Dataset::new(users), Viewport::default())
+    }
+
+    #[test]
+    fn finds_the_edges_crossing_the_view() {
+        let graph = graph();
+        let built = CullIndex::new(&graph);
+        let mut build = CullBuild::new(&graph);
+        // A spent budget still files a step's worth of edges each frame.
+        let spent = FrameBudget::new(Duration::ZERO);
+        let stepped = std::iter::repeat_with(|| build.step(&graph, &spent))
+            .flatten()
+            .next()
+            .unwrap();
+        assert_eq!(stepped, built);
+        assert!(built.fits(&graph));
+
+        let fitted = ViewBox::fit(&graph.nodes, Viewport::default());
+        let whole = built.render_edges(&graph, fitted, usize::MAX, 0.0);
+        assert_eq!(whole, graph.render_edges(usize::MAX, 0.0));
+
+        let corner = ViewBox {
+            width: fitted.width / 4.0,
+            height: fitted.height / 4.0,
+            ..fitted
+        };
+        let culled = built.render_edges(&graph, corner, usize::MAX, 0.0);
+        let expected: Vec<RenderEdge> = whole
+            .into_iter()
+            .filter(|edge| {
+                let bounds = (
+                    edge.x1.min(edge.x2),
+                    edge.y1.min(edge.y2),
+                    edge.x1.max(edge.x2),
+                    edge.y1.max(edge.y2),
+                );
+                crosses(bounds, corner)
+            })
+            .collect();
+        assert_eq!(culled, expected);
+        assert!(culled.len() < graph.edges.len());
+        assert!(culled.iter().any(|edge| edge.kind == EdgeKind::Rating));
+    }
+}
@@ -0,0 +1,341 @@
+//! The details card for the selected node: its neighbors, poster and metadata for anime, and
+//! graph recommendations for users, next to a tab of this season's anime scored by content for
+//! frontends that can fetch the season.
+
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use wasiw_core::content::ContentRecommendation;
+use wasiw_core::recommend::{self, Recommendation};
+use wasiw_core::{GraphModel, NodeType};
+
+use crate::i18n::tr;
+use crate::navigation::{self, NeighborSummary};
+
+/// Neighbors listed in the node details card.
+pub const DETAIL_NEIGHBORS: usize = 12;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeDetailsData {
+    pub label: String,
+    pub kind: &'static str,
+    pub degree: usize,
+    /// Neighbors with their poster thumbnails, when loaded.
+    pub neighbors: Vec<(NeighborSummary, Option<Rc<str>>)>,
+    /// Graph recommendations with their thumbnails, for user nodes.
+    pub recommendations: Vec<(Recommendation, Option<Rc<str>>)>,
+    /// Where recommended anime stream, by anime id, for frontends that look it up.
+    pub streaming: HashMap<u32, String>,
+    /// The title to start a recommended anime's franchise with, by anime id, when that isn't
+    /// the anime itself; for frontends that know the anime's relations.
+    pub entry_points: HashMap<u32, String>,
+    /// Natural-language explanations by anime id, for frontends that write them; `None` while
+    /// one is being written.
+    pub blurbs: HashMap<u32, Option<String>>,
+    /// Anime on the user's watchlist, for frontends that keep one.
+    pub watchlist: HashSet<u32>,
+    /// Year, episodes and genres, for anime with fetched metadata.
+    pub summary: Option<String>,
+    pub synopsis: Option<String>,
+    pub poster: Option<Rc<str>>,
+    /// This season's anime scored for the user, once the frontend has fetched the season.
+    pub seasonal: Option<SeasonalPicks>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeasonalPicks {
+    /// e.g. `Fall 2026`.
+    pub season: String,
+    /// With each pick's year, episodes and genres, and thumbnail when loaded.
+    pub picks: Vec<(ContentRecommendation, String, Option<Rc<str>>)>,
+    /// How many of the user's rated anime have metadata to compare, out of how many.
+    pub profiled: usize,
+    pub rated: usize,
+}
+
+impl NodeDetailsData {
+    /// Details for node `idx`; `poster` gives a node's thumbnail, if loaded. Metadata is
+    /// filled in by the frontend that has it.
+    pub fn new(
+        graph: &GraphModel,
+        idx: usize,
+        poster: impl Fn(usize) -> Option<Rc<str>>,
+    ) -> Option<Self> {
+        let node = graph.nodes.get(idx)?;
+        Some(Self {
+            label: node.label.clone(),
+            kind: match node.node_type {
+                NodeType::User => "User",
+                NodeType::Anime => "Anime",
+                NodeType::Genre => "Genre",
+                NodeType::Studio => "Studio",
+                NodeType::Director => "Director",
+            },
+            degree: navigation::neighbors(graph, idx).len(),
+            neighbors: navigation::neighbor_summaries(graph, idx, DETAIL_NEIGHBORS)
+                .into_iter()
+                .map(|neighbor| {
+                    let thumbnail = poster(neighbor.node);
+                    (neighbor, thumbnail)
+                })
+                .collect(),
+            recommendations: recommend::recommend(graph, idx, recommend::MAX_RECOMMENDATIONS)
+                .into_iter()
+                .map(|recommendation| {
+                    let thumbnail = poster(recommendation.node);
+                    (recommendation, thumbnail)
+                })
+                .collect(),
+            streaming: HashMap::new(),
+            entry_points: HashMap::new(),
+            blurbs: HashMap::new(),
+            watchlist: HashSet::new(),
+            summary: None,
+            synopsis: None,
+            poster: poster(idx),
+            seasonal: None,
+        })
+    }
+}
+
+/// The export, watched, watchlist and hide buttons, and the season tab, are only shown by
+/// frontends that pass their handlers.
+#[component]
+pub fn NodeDetails(
+    details: NodeDetailsData,
+    on_export_recommendations: Option<EventHandler<()>>,
+    on_export_report: Option<EventHandler<()>>,
+    /// A recommendation was marked watched, with the anime id and a 1–10 score.
+    on_mark_watched: Option<EventHandler<(u32, u8)>>,
+    /// A blurb was asked for, with the recommendation's anime id.
+    on_explain: Option<EventHandler<u32>>,
+    /// A recommendation was added to or taken off the watchlist, by anime id.
+    on_watchlist: Option<EventHandler<u32>>,
+    /// A recommendation should never be shown again, by anime id.
+    on_hide: Option<EventHandler<u32>>,
+    /// The "This season" tab asked for the season before it was fetched.
+    on_fetch_season: Option<EventHandler<()>>,
+    on_close: EventHandler<()>,
+) -> Element {
+    // The recommendation being scored after "Watched" was clicked, and the chosen score.
+    let mut rating = use_signal(|| None::<u32>);
+    let mut score = use_signal(|| 8_u8);
+    let mut season_tab = use_signal(|| false);
+    let tabs = on_fetch_season.is_some() && details.kind == "User";
+    rsx! {
+        aside { class: "details", aria_label: "{details.label}",
+            div { class: "row",
+                strong { "{details.label}" }
+                button {
+                    class: "close",
+                    aria_label: tr!("dialog-close"),
+                    onclick: move |_| on_close.call(()),
+                    "×"
+                }
+            }
+            p { class: "tiny", "{details.kind} · {details.degree} connections" }
+            if let Some(poster) = details.poster {
+                img { class: "poster", src: "{poster}", alt: "Poster" }
+            }
+            if let Some(summary) = details.summary.filter(|summary| !summary.is_empty()) {
+                p { class: "tiny", "{summary}" }
+            }
+            if let Some(synopsis) = details.synopsis {
+                p { class: "synopsis", "{synopsis}" }
+            }
+            ol {
+                for (neighbor, thumbnail) in details.neighbors {
+                    li {
+                        if let Some(thumbnail) = thumbnail {
+                            img { class: "thumbnail", src: "{thumbnail}", alt: "" }
+                        }
+                        span { "{neighbor.label}" }
+                        span { class: "tiny", " — {neighbor.detail}" }
+                    }
+                }
+            }
+            if tabs {
+                div { class: "tabs",
+                    button {
+                        class: if !season_tab() { "active" },
+                        onclick: move |_| season_tab.set(false),
+                        "Recommendations"
+                    }
+                    button {
+                        class: if season_tab() { "active" },
+                        onclick: move |_| season_tab.set(true),
+                        "This season"
+                    }
+                }
+            }
+            if tabs && season_tab() {
+                SeasonTab { seasonal: details.seasonal, on_fetch_season }
+            } else if !details.recommendations.is_empty() {
+                div { class: "row",
+                    strong { "Recommendations" }
+                    if let Some(on_export) = on_export_recommendations {
+                        button {
+                            class: "action",
+                            onclick: move |_| on_export.call(()),
+                            "Export…"
+                        }
+                    }
+                    if let Some(on_export) = on_export_report {
+                        button {
+                            class: "action",
+                            title: "A standalone HTML page with this user's taste profile, graph and recommendations",
+                            onclick: move |_| on_export.call(()),
+                            "Share report…"
+                        }
+                    }
+                }
+                ol { class: "recommendations",
+                    for (recommendation, thumbnail) in details.recommendations {
+                        li { title: "{recommendation.explanation(3)}",
+                            if let Some(thumbnail) = thumbnail {
+                                img { class: "thumbnail", src: "{thumbnail}", alt: "" }
+                            }
+                            span { "{recommendation.title}" }
+                            span { class: "tiny", " — {recommendation.score:+.2}" }
+                            p { class: "tiny", "{recommendation.explanation(2)}" }
+                            if let Some(entry) = details.entry_points.get(&recommendation.anime_id) {
+                                p { class: "tiny", {tr!("watch-order-start-with", entry = entry)} }
+                            }
+                            if let Some(streaming) = details.streaming.get(&recommendation.anime_id) {
+                                p { class: "tiny", "{streaming}" }
+                            }
+                            match details.blurbs.get(&recommendation.anime_id) {
+                                Some(Some(blurb)) => rsx! {
+                                    p { class: "blurb", "{blurb}" }
+                                },
+                                Some(None) => rsx! {
+                                    p { class: "tiny", "Writing an explanation…" }
+                                },
+                                None => rsx! {
+                                    if let Some(on_explain) = on_explain {
+                                        button {
+                                            class: "action",
+                                            title: "Have the local model explain this recommendation",
+                                            onclick: move |_| on_explain.call(recommendation.anime_id),
+                                            "Explain"
+                                        }
+                                    }
+                                },
+                            }
+                            if on_watchlist.is_some() || on_hide.is_some() {
+                                div { class: "row",
+                                    if let Some(on_watchlist) = on_watchlist {
+                                        button {
+                                            class: "action",
+                                            onclick: move |_| on_watchlist.call(recommendation.anime_id),
+                                            if details.watchlist.contains(&recommendation.anime_id) {
+                                                "On watchlist ✓"
+                                            } else {
+                                                "Watch later"
+                                            }
+                                        }
+                                    }
+                                    if let Some(on_hide) = on_hide {
+                                        button {
+                                            class: "action",
+                                            title: "Never recommend this anime to this profile",
+                                            onclick: move |_| on_hide.call(recommendation.anime_id),
+                                            "Not interested"
+                                        }
+                                    }
+                                }
+                            }
+                            if let Some(on_mark_watched) = on_mark_watched {
+                                if rating() == Some(recommendation.anime_id) {
+                                    div { class: "row",
+                                        select {
+                                            value: "{score}",
+                                            onchange: move |evt| {
+                                                if let Ok(value) = evt.value().parse() {
+                                                    score.set(value);
+                                                }
+                                            },
+                                            for value in (1..=10_u8).rev() {
+                                                option { value: "{value}", selected: value == score(), "{value}" }
+                                            }
+                                        }
+                                        button {
+                                            class: "action",
+                                            onclick: move |_| {
+                                                on_mark_watched.call((recommendation.anime_id, score()));
+                                                rating.set(None);
+                                            },
+                                            "Save rating"
+                                        }
+                                        button { class: "action", onclick: move |_| rating.set(None), "Cancel" }
+                                    }
+                                } else {
+                                    button {
+                                        class: "action",
+                                        title: "Add a rating for this anime to the profile",
+                                        onclick: move |_| rating.set(Some(recommendation.anime_id)),
+                                        "Watched…"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SeasonTab(
+    seasonal: Option<SeasonalPicks>,
+    on_fetch_season: Option<EventHandler<()>>,
+) -> Element {
+    let Some(seasonal) = seasonal else {
+        return rsx! {
+            p { class: "tiny",
+                "Score the anime airing this season against this user's ratings by genre, studio and year."
+            }
+            if let Some(on_fetch) = on_fetch_season {
+                button { class: "action", onclick: move |_| on_fetch.call(()), "Fetch this season" }
+            }
+        };
+    };
+    let coverage = match (seasonal.profiled, seasonal.rated) {
+        (0, _) => Some(
+            "None of this user's rated anime have metadata yet, so there is nothing to compare."
+                .to_string(),
+        ),
+        (profiled, rated) if profiled < rated => Some(format!(
+            "Based on the {profiled} of {rated} rated anime with metadata."
+        )),
+        _ => None,
+    };
+    rsx! {
+        div { class: "row",
+            strong { "{seasonal.season} for you" }
+        }
+        if let Some(coverage) = coverage {
+            p { class: "tiny", "{coverage}" }
+        }
+        if seasonal.picks.is_empty() && seasonal.profiled > 0 {
+            p { class: "tiny", "Nothing airing this season resembles what this user rated." }
+        }
+        ol { class: "recommendations",
+            for (pick, summary, thumbnail) in seasonal.picks {
+                li { title: "{pick.explanation(5)}",
+                    if let Some(thumbnail) = thumbnail {
+                        img { class: "thumbnail", src: "{thumbnail}", alt: "" }
+                    }
+                    span { "{pick.title}" }
+                    span { class: "tiny", " — {pick.score:.2}" }
+                    if !summary.is_empty() {
+                        p { class: "tiny", "{summary}" }
+                    }
+                    p { class: "tiny", "{pick.explanation(2)}" }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Translated interface text. Strings live in Fluent-style files under `ui/locales/`, one per
+//! language: `key = text` lines, `#` comments, and `{ $name }` placeables filled in by [`tr!`].
+//! Text missing from a translation falls back to English.
+//!
+//! [`tr`] reads the current locale from a global signal, so components showing translated text
+//! re-render when [`set_locale`] switches language.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::LazyLock;
+
+use dioxus::core::Runtime;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use wasiw_core::budget::Format;
+use wasiw_core::health::CheckKind;
+use wasiw_core::sampling::Sampling;
+use wasiw_core::titles::TitleForm;
+use wasiw_core::{GraphMode, LayoutKind, MergeStrategy};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "ja")]
+    Japanese,
+}
+
+impl Locale {
+    pub const ALL: [Self; 2] = [Self::English, Self::Japanese];
+
+    /// The language's name in itself, as the language picker lists it.
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Self::English => "English",
+            Self::Japanese => "日本語",
+        }
+    }
+
+    /// The locale for a language tag like `ja-JP` or `ja_JP.UTF-8`; English for languages
+    /// without a translation.
+    pub fn from_tag(tag: &str) -> Self {
+        if tag.to_lowercase().starts_with("ja") {
+            Self::Japanese
+        } else {
+            Self::English
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Self::English => include_str!("../locales/en.ftl"),
+            Self::Japanese => include_str!("../locales/ja.ftl"),
+        }
+    }
+}
+
+/// The locale as an index into [`Locale::ALL`], readable before the Dioxus runtime starts.
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+/// Mirrors [`CURRENT`] so reading text subscribes the component to language changes.
+static LOCALE: GlobalSignal<Locale> = Signal::global(stored_locale);
+
+static MESSAGES: LazyLock<[HashMap<&'static str, &'static str>; 2]> =
+    LazyLock::new(|| Locale::ALL.map(|locale| parse(locale.source())));
+
+fn stored_locale() -> Locale {
+    Locale::ALL[usize::from(CURRENT.load(Ordering::Relaxed))]
+}
+
+pub fn locale() -> Locale {
+    if Runtime::try_current().is_some() {
+        *LOCALE.read()
+    } else {
+        stored_locale()
+    }
+}
+
+/// Switches language. Before launch this only sets the language the app opens in.
+pub fn set_locale(locale: Locale) {
+    let index = Locale::ALL.iter().position(|&known| known == locale);
+    CURRENT.store(index.unwrap_or(0) as u8, Ordering::Relaxed);
+    if Runtime::try_current().is_some() {
+        *LOCALE.write() = locale;
+    }
+}
+
+fn parse(source: &'static str) -> HashMap<&'static str, &'static str> {
+    source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, text)| (key.trim(), text.trim()))
+        .collect()
+}
+
+fn message(locale: Locale, key: &str) -> Option<&'static str> {
+    let index = Locale::ALL.iter().position(|&known| known == locale)?;
+    MESSAGES[index].get(key).copied()
+}
+
+/// The text for `key` in the current language.
+pub fn tr(key: &str) -> String {
+    tr_with(key, &[])
+}
+
+/// The text for `key` with its `{ $name }` placeables filled from `args`; see [`tr!`].
+pub fn tr_with(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let Some(text) = message(locale(), key).or_else(|| message(Locale::English, key)) else {
+        return key.to_string();
+    };
+    let mut filled = text.to_string();
+    for (name, value) in args {
+        filled = filled.replace(&format!("{{ ${name} }}"), &value.to_string());
+    }
+    filled
+}
+
+/// `tr!("key")`, or `tr!("key", count = n)` to fill `{ $count }`.
+#[macro_export]
+macro_rules! tr {
+    ($key:expr) => {
+        $crate::i18n::tr($key)
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::tr_with($key, &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),+])
+    };
+}
+
+pub use crate::tr;
+
+/// Display names for the core's graph mode, layout, sampling, duplicate, title and health
+/// check options, which only know their English labels.
+pub fn graph_mode_name(mode: GraphMode) -> String {
+    tr(match mode {
+        GraphMode::Ratings => "graph-mode-ratings",
+        GraphMode::Genres => "graph-mode-genres",
+        GraphMode::Studios => "graph-mode-studios",
+        GraphMode::Directors => "graph-mode-directors",
+    })
+}
+
+pub fn layout_name(kind: LayoutKind) -> String {
+    tr(match kind {
+        LayoutKind::Rings => "layout-rings",
+        LayoutKind::Communities => "layout-communities",
+    })
+}
+
+pub fn sampling_name(sampling: Sampling) -> String {
+    tr(match sampling {
+        Sampling::All => "sampling-all",
+        Sampling::RandomUsers { .. } => "sampling-random-users",
+        Sampling::TopAnime { .. } => "sampling-top-anime",
+        Sampling::Stratified { .. } => "sampling-stratified",
+    })
+}
+
+pub fn duplicates_name(strategy: MergeStrategy) -> String {
+    tr(match strategy {
+        MergeStrategy::LatestWins => "duplicates-latest",
+        MergeStrategy::Highest => "duplicates-highest",
+        MergeStrategy::Average => "duplicates-average",
+    })
+}
+
+pub fn title_form_name(form: TitleForm) -> String {
+    tr(match form {
+        TitleForm::Romaji => "titles-romaji",
+        TitleForm::English => "titles-english",
+        TitleForm::Native => "titles-native",
+    })
+}
+
+pub fn health_check_name(kind: CheckKind) -> String {
+    tr(match kind {
+        CheckKind::ScoresOutOfRange => "health-scores-out-of-range",
+        CheckKind::EmptyUsers => "health-empty-users",
+        CheckKind::OtherDrops => "health-other-drops",
+        CheckKind::IgnoredFields => "health-ignored-fields",
+        CheckKind::DuplicateRatings => "health-duplicate-ratings",
+        CheckKind::UserIdCollisions => "health-user-id-collisions",
+        CheckKind::TitleMismatches => "health-title-mismatches",
+        CheckKind::SharedTitles => "health-shared-titles",
+        CheckKind::CopiedUsers => "health-copied-users",
+    })
+}
+
+pub fn budget_format_name(format: Format) -> String {
+    tr(match format {
+        Format::Any => "budget-any",
+        Format::Movies => "budget-movies",
+        Format::Series => "budget-series",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeables(text: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = text
+            .split("{ $")
+            .skip(1)
+            .filter_map(|rest| rest.split_once(" }").map(|(name, _)| name))
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    #[test]
+    fn translations_cover_every_english_key_with_the_same_placeables() {
+        let english = parse(Locale::English.source());
+        for locale in Locale::ALL {
+            let messages = parse(locale.source());
+            for (key, text) in &english {
+                let translated = messages
+                    .get(key)
+                    .unwrap_or_else(|| panic!("{locale:?} is missing `{key}`"));
+                assert_eq!(
+                    placeables(translated),
+                    placeables(text),
+                    "`{key}` in {locale:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn placeables_are_filled_and_unknown_keys_fall_back_to_the_key() {
+        assert_eq!(tr!("no-such-key"), "no-such-key");
+        let text = tr!("search-matches", count = 3);
+        assert!(text.contains('3') && !text.contains('$'), "{text}");
+    }
+
+    #[test]
+    fn language_tags_pick_a_locale() {
+        assert_eq!(Locale::from_tag("ja_JP.UTF-8"), Locale::Japanese);
+        assert_eq!(Locale::from_tag("en-GB"), Locale::English);
+        assert_eq!(Locale::from_tag("fr"), Locale::English);
+    }
+}
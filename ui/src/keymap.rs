@@ -0,0 +1,313 @@
+//! Keyboard shortcuts: the chord each action is bound to, by default or as a keymap rebinds it,
+//! and the cheat sheet that lists them.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::accessibility::focus_on_mount;
+use crate::i18n::tr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Shortcut {
+    ZoomIn,
+    ZoomOut,
+    FitView,
+    Search,
+    NextTab,
+    PreviousTab,
+    Undo,
+    Redo,
+    Screenshot,
+    CommandPalette,
+    Fullscreen,
+    Shortcuts,
+}
+
+impl Shortcut {
+    pub const ALL: [Self; 12] = [
+        Self::ZoomIn,
+        Self::ZoomOut,
+        Self::FitView,
+        Self::Search,
+        Self::NextTab,
+        Self::PreviousTab,
+        Self::Undo,
+        Self::Redo,
+        Self::Screenshot,
+        Self::CommandPalette,
+        Self::Fullscreen,
+        Self::Shortcuts,
+    ];
+
+    /// The name a keymap binds it under, e.g. `zoom-in`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ZoomIn => "zoom-in",
+            Self::ZoomOut => "zoom-out",
+            Self::FitView => "fit-view",
+            Self::Search => "search",
+            Self::NextTab => "next-tab",
+            Self::PreviousTab => "previous-tab",
+            Self::Undo => "undo",
+            Self::Redo => "redo",
+            Self::Screenshot => "screenshot",
+            Self::CommandPalette => "command-palette",
+            Self::Fullscreen => "fullscreen",
+            Self::Shortcuts => "shortcuts",
+        }
+    }
+
+    pub fn label(self) -> String {
+        tr(&format!("shortcut-{}", self.name()))
+    }
+
+    fn default_chord(self) -> &'static str {
+        match self {
+            Self::ZoomIn => "Ctrl+=",
+            Self::ZoomOut => "Ctrl+-",
+            Self::FitView => "Ctrl+0",
+            Self::Search => "Ctrl+F",
+            Self::NextTab => "Ctrl+Tab",
+            Self::PreviousTab => "Ctrl+Shift+Tab",
+            Self::Undo => "Ctrl+Z",
+            Self::Redo => "Ctrl+Shift+Z",
+            Self::Screenshot => "Ctrl+Shift+S",
+            Self::CommandPalette => "Ctrl+K",
+            Self::Fullscreen => "F11",
+            Self::Shortcuts => "F1",
+        }
+    }
+}
+
+/// A key and the modifiers held with it, written like `Ctrl+Shift+Z` or `F11`. `Ctrl` also
+/// matches Cmd, so one keymap works on macOS too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chord {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub key: Key,
+}
+
+impl Chord {
+    /// Whether pressing `key` with `modifiers` plays this chord. Letters compare without case;
+    /// Shift is ignored for other characters unless the chord names it, since some layouts need
+    /// it to type them.
+    pub fn matches(&self, key: &Key, modifiers: Modifiers) -> bool {
+        let same_key = match (&self.key, key) {
+            (Key::Character(wanted), Key::Character(pressed)) => {
+                wanted.to_lowercase() == pressed.to_lowercase()
+            }
+            (wanted, pressed) => wanted == pressed,
+        };
+        let shift = match &self.key {
+            Key::Character(wanted) if !self.shift && !wanted.chars().any(char::is_alphabetic) => {
+                true
+            }
+            _ => modifiers.shift() == self.shift,
+        };
+        same_key
+            && shift
+            && (modifiers.ctrl() || modifiers.meta()) == self.ctrl
+            && modifiers.alt() == self.alt
+    }
+}
+
+impl FromStr for Chord {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        // `Ctrl++` binds the plus key itself.
+        let (modifiers, key) = match text.strip_suffix("++") {
+            Some(modifiers) => (modifiers, "+"),
+            None => text.rsplit_once('+').unwrap_or(("", text)),
+        };
+        let mut chord = Chord {
+            ctrl: false,
+            alt: false,
+            shift: false,
+            key: parse_key(key.trim()).ok_or_else(|| format!("unknown key `{key}` in `{text}`"))?,
+        };
+        for modifier in modifiers.split('+').filter(|part| !part.trim().is_empty()) {
+            match modifier.trim().to_lowercase().as_str() {
+                "ctrl" | "control" | "cmd" | "meta" => chord.ctrl = true,
+                "alt" | "option" => chord.alt = true,
+                "shift" => chord.shift = true,
+                _ => return Err(format!("unknown modifier `{modifier}` in `{text}`")),
+            }
+        }
+        Ok(chord)
+    }
+}
+
+fn parse_key(name: &str) -> Option<Key> {
+    if name.chars().count() == 1 {
+        return Some(Key::Character(name.to_lowercase()));
+    }
+    match name.parse() {
+        Ok(Key::Character(_)) | Err(_) => None,
+        Ok(key) => Some(key),
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (held, name) in [
+            (self.ctrl, "Ctrl+"),
+            (self.alt, "Alt+"),
+            (self.shift, "Shift+"),
+        ] {
+            if held {
+                f.write_str(name)?;
+            }
+        }
+        match &self.key {
+            Key::Character(key) => f.write_str(&key.to_uppercase()),
+            key => write!(f, "{key}"),
+        }
+    }
+}
+
+/// The chord for each shortcut: its default unless the user's keymap rebinds it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<(Shortcut, Chord)>,
+}
+
+impl Keymap {
+    /// Applies `overrides`, chords by shortcut. Ones that don't parse keep the default and are
+    /// returned, described, alongside the keymap.
+    pub fn new(overrides: &BTreeMap<Shortcut, String>) -> (Self, Vec<String>) {
+        let mut problems = Vec::new();
+        let bindings = Shortcut::ALL
+            .into_iter()
+            .map(|shortcut| {
+                let chord = overrides
+                    .get(&shortcut)
+                    .and_then(|text| {
+                        text.parse()
+                            .map_err(|err| problems.push(format!("{}: {err}", shortcut.label())))
+                            .ok()
+                    })
+                    .unwrap_or_else(|| {
+                        shortcut
+                            .default_chord()
+                            .parse()
+                            .expect("default chords parse")
+                    });
+                (shortcut, chord)
+            })
+            .collect();
+        (Self { bindings }, problems)
+    }
+
+    /// The shortcut pressing `key` with `modifiers` runs, if any.
+    pub fn shortcut(&self, key: &Key, modifiers: Modifiers) -> Option<Shortcut> {
+        self.bindings
+            .iter()
+            .find(|(_, chord)| chord.matches(key, modifiers))
+            .map(|&(shortcut, _)| shortcut)
+    }
+
+    pub fn chord(&self, shortcut: Shortcut) -> Option<&Chord> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == shortcut)
+            .map(|(_, chord)| chord)
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&BTreeMap::new()).0
+    }
+}
+
+/// Every shortcut with its chord.
+#[component]
+pub fn ShortcutSheet(keymap: Keymap, on_close: EventHandler<()>) -> Element {
+    rsx! {
+        div { class: "dialog-backdrop", onclick: move |_| on_close.call(()),
+            div {
+                class: "dialog",
+                role: "dialog",
+                aria_modal: "true",
+                aria_label: tr!("shortcuts-title"),
+                onclick: move |evt| evt.stop_propagation(),
+                div { class: "row",
+                    strong { {tr!("shortcuts-title")} }
+                    button {
+                        class: "close",
+                        aria_label: tr!("dialog-close"),
+                        onmounted: focus_on_mount,
+                        onclick: move |_| on_close.call(()),
+                        "×"
+                    }
+                }
+                table { class: "shortcuts",
+                    for (shortcut, chord) in keymap.bindings {
+                        tr { key: "{shortcut.name()}",
+                            td { "{shortcut.label()}" }
+                            td {
+                                kbd { "{chord}" }
+                            }
+                        }
+                    }
+                }
+                p { class: "tiny", {tr!("shortcuts-hint")} }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(text: &str) -> Chord {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn chords_parse_and_print_back() {
+        assert_eq!(chord("ctrl+shift+z").to_string(), "Ctrl+Shift+Z");
+        assert_eq!(chord("F11").key, Key::F11);
+        assert_eq!(chord("Ctrl++").key, Key::Character("+".to_string()));
+        assert!("Hyperctrl+Z".parse::<Chord>().is_err());
+        assert!("Ctrl+NoSuchKey".parse::<Chord>().is_err());
+    }
+
+    #[test]
+    fn shift_tells_undo_from_redo() {
+        let keymap = Keymap::default();
+        let z = Key::Character("z".to_string());
+        assert_eq!(
+            keymap.shortcut(&z, Modifiers::CONTROL),
+            Some(Shortcut::Undo)
+        );
+        assert_eq!(
+            keymap.shortcut(&z, Modifiers::CONTROL | Modifiers::SHIFT),
+            Some(Shortcut::Redo)
+        );
+        assert_eq!(keymap.shortcut(&z, Modifiers::META), Some(Shortcut::Undo));
+        assert_eq!(keymap.shortcut(&z, Modifiers::empty()), None);
+    }
+
+    #[test]
+    fn overrides_replace_defaults_and_bad_ones_are_reported() {
+        let overrides = BTreeMap::from([
+            (Shortcut::Undo, "Ctrl+U".to_string()),
+            (Shortcut::Redo, "Ctrl+Nope".to_string()),
+        ]);
+        let (keymap, problems) = Keymap::new(&overrides);
+        assert_eq!(keymap.chord(Shortcut::Undo), Some(&chord("Ctrl+U")));
+        assert_eq!(keymap.chord(Shortcut::Redo), Some(&chord("Ctrl+Shift+Z")));
+        assert_eq!(problems.len(), 1);
+    }
+}
@@ -0,0 +1,31 @@
+//! Dioxus components shared by the desktop app and the browser build. Nothing here touches the
+//! file system, native windows or menus; each frontend wires those up and passes data and
+//! event handlers in.
+//!
+//! - [`canvas`] draws the graph for a camera position ([`camera`]) as live SVG or a painted
+//!   2D canvas; [`render`] describes a frame apart from Dioxus and draws it with either, or as
+//!   a standalone SVG document.
+//! - [`schedule`] spreads slow work over animation frames within a time budget, such as
+//!   building [`cull`]'s index of the edges in view.
+//! - [`details`] is the card for the selected node, [`controls`] the side-panel widgets and
+//!   [`charts`] the stats tab's charts.
+//! - [`navigation`] moves the selection from the keyboard; [`palette`] runs commands from it,
+//!   and [`keymap`] binds shortcuts.
+//! - [`theme`] holds the palettes and the stylesheet, [`theme::APP_CSS`].
+//! - [`i18n`] translates the interface text; [`accessibility`] shows the graph as text for
+//!   screen readers.
+
+pub mod accessibility;
+pub mod camera;
+pub mod canvas;
+pub mod charts;
+pub mod controls;
+pub mod cull;
+pub mod details;
+pub mod i18n;
+pub mod keymap;
+pub mod navigation;
+pub mod palette;
+pub mod render;
+pub mod schedule;
+pub mod theme;
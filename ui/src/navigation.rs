@@ -0,0 +1,252 @@
+//! Keyboard navigation over the graph: arrow keys move to the nearest node in a direction, Tab
+//! steps through a node's neighbors.
+
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+use wasiw_core::{Edge, EdgeKind, GraphModel};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    fn vector(self) -> (f32, f32) {
+        match self {
+            Self::Up => (0.0, -1.0),
+            Self::Down => (0.0, 1.0),
+            Self::Left => (-1.0, 0.0),
+            Self::Right => (1.0, 0.0),
+        }
+    }
+}
+
+/// Tab traversal state: which node's neighbors are being cycled and where we are in that list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TabCursor {
+    pub anchor: usize,
+    pub position: usize,
+}
+
+/// Nearest visible node roughly in `direction` from `from`. Candidates off-axis are penalized
+/// so pressing Right prefers a node straight to the right over a slightly closer diagonal one.
+pub fn nearest_in_direction(
+    graph: &GraphModel,
+    from: usize,
+    direction: Direction,
+) -> Option<usize> {
+    let origin = &graph.nodes[from];
+    let (dx, dy) = direction.vector();
+    graph
+        .nodes
+        .iter()
+        .enumerate()
+        .filter(|(idx, node)| *idx != from && !node.hidden)
+        .filter_map(|(idx, node)| {
+            let (vx, vy) = (node.x - origin.x, node.y - origin.y);
+            let distance = vx.hypot(vy);
+            let along = (vx * dx + vy * dy) / distance.max(f32::EPSILON);
+            // Within 60 degrees of the requested direction.
+            (along > 0.5).then_some((idx, distance * (1.0 + 2.0 * (1.0 - along))))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(idx, _)| idx)
+}
+
+/// Visible node closest to a layout point, used to start navigation without a selection.
+pub fn nearest_to(graph: &GraphModel, x: f32, y: f32) -> Option<usize> {
+    graph
+        .nodes
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| !node.hidden)
+        .min_by(|(_, a), (_, b)| {
+            (a.x - x)
+                .hypot(a.y - y)
+                .total_cmp(&(b.x - x).hypot(b.y - y))
+        })
+        .map(|(idx, _)| idx)
+}
+
+/// Tab target: the cursor to remember and the node to select. Without a selection, Tab starts at
+/// the first visible node; otherwise it cycles the anchor's neighbors.
+pub fn tab_step(
+    graph: &GraphModel,
+    selected: Option<usize>,
+    cursor: Option<TabCursor>,
+    backwards: bool,
+) -> Option<(Option<TabCursor>, usize)> {
+    let Some(current) = selected else {
+        let first = graph.nodes.iter().position(|node| !node.hidden)?;
+        return Some((None, first));
+    };
+
+    // Keep cycling the same anchor while the current selection came from that cycle.
+    let cursor = cursor
+        .filter(|cursor| neighbors(graph, cursor.anchor).get(cursor.position) == Some(&current));
+    let anchor = cursor.map_or(current, |cursor| cursor.anchor);
+    let neighbors = neighbors(graph, anchor);
+    if neighbors.is_empty() {
+        return None;
+    }
+    let len = neighbors.len();
+    let position = match (cursor, backwards) {
+        (Some(cursor), false) => (cursor.position + 1) % len,
+        (Some(cursor), true) => (cursor.position + len - 1) % len,
+        (None, false) => 0,
+        (None, true) => len - 1,
+    };
+    Some((Some(TabCursor { anchor, position }), neighbors[position]))
+}
+
+/// Visible neighbors of `node`, strongest connections first.
+pub fn neighbors(graph: &GraphModel, node: usize) -> Vec<usize> {
+    let mut linked: Vec<(usize, usize, f64)> = graph
+        .links(node)
+        .filter(|(_, edge)| graph.is_edge_visible(edge))
+        .map(|(other, edge)| (other, edge.co_raters, edge.weight))
+        .collect();
+    linked.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.total_cmp(&a.2)));
+    let mut seen = HashSet::new();
+    linked.retain(|(other, _, _)| seen.insert(*other));
+    linked.into_iter().map(|(other, _, _)| other).collect()
+}
+
+/// One line of the details card: a neighbor and how it connects to the selected node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NeighborSummary {
+    /// Index of the neighbor in `graph.nodes`.
+    pub node: usize,
+    pub label: String,
+    pub detail: String,
+}
+
+pub fn neighbor_summaries(graph: &GraphModel, node: usize, limit: usize) -> Vec<NeighborSummary> {
+    let mut rows: Vec<(usize, NeighborSummary)> = graph
+        .links(node)
+        .filter(|(_, edge)| graph.is_edge_visible(edge))
+        .map(|(other, edge)| {
+            let summary = NeighborSummary {
+                node: other,
+                label: graph.nodes[other].label.clone(),
+                detail: connection_detail(edge),
+            };
+            (edge.co_raters, summary)
+        })
+        .collect();
+    rows.sort_by_key(|(co_raters, _)| Reverse(*co_raters));
+    rows.into_iter()
+        .take(limit)
+        .map(|(_, summary)| summary)
+        .collect()
+}
+
+/// How `edge` connects its ends, e.g. `12 co-raters, score +0.40`.
+pub fn connection_detail(edge: &Edge) -> String {
+    match edge.kind {
+        EdgeKind::Similarity => format!("{} co-raters, score {:+.2}", edge.co_raters, edge.weight),
+        EdgeKind::Rating => format!("rated {:+.2} vs. the user's mean", edge.weight),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasiw_core::{build_graph, Dataset, Rating, UserRatings, Viewport};
+
+    /// Users `a` and `b` both rate anime 1–3, `a` rating 1 highest, placed on a cross:
+    /// anime 1 in the middle, 2 to its right, 3 below it, the users far off the axes.
+    fn graph() -> GraphModel {
+        let user = |user_id: &str, scores: [f64; 3]| UserRatings {
+            user_id: user_id.to_string(),
+            ratings: (1..=3)
+                .zip(scores)
+                .map(|(anime_id, score)| Rating::new(anime_id, format!("Anime {anime_id}"), score))
+                .collect(),
+        };
+        let mut graph = build_graph(
+            Dataset::new(vec![
+                user("a", [10.0, 6.0, 2.0]),
+                user("b", [8.0, 8.0, 5.0]),
+            ]),
+            Viewport::default(),
+        );
+        for node in &mut graph.nodes {
+            (node.x, node.y) = match node.id.as_str() {
+                "anime:1" => (0.0, 0.0),
+                "anime:2" => (100.0, 10.0),
+                "anime:3" => (0.0, 60.0),
+                "user:a" => (-300.0, -600.0),
+                _ => (300.0, -600.0),
+            };
+        }
+        graph
+    }
+
+    fn index(graph: &GraphModel, id: &str) -> usize {
+        graph.nodes.iter().position(|node| node.id == id).unwrap()
+    }
+
+    #[test]
+    fn arrows_move_to_the_nearest_node_that_way() {
+        let mut graph = graph();
+        let center = index(&graph, "anime:1");
+        let step = |graph: &GraphModel, direction| {
+            nearest_in_direction(graph, center, direction).map(|node| graph.nodes[node].id.clone())
+        };
+        assert_eq!(step(&graph, Direction::Right).as_deref(), Some("anime:2"));
+        assert_eq!(step(&graph, Direction::Down).as_deref(), Some("anime:3"));
+        assert_eq!(step(&graph, Direction::Left), None);
+        let below = index(&graph, "anime:3");
+        graph.nodes[below].hidden = true;
+        assert_eq!(step(&graph, Direction::Down), None);
+        assert_eq!(
+            nearest_to(&graph, 90.0, 0.0),
+            Some(index(&graph, "anime:2"))
+        );
+    }
+
+    #[test]
+    fn tab_cycles_the_anchors_neighbors_both_ways() {
+        let graph = graph();
+        let first = graph.nodes.iter().position(|node| !node.hidden).unwrap();
+        assert_eq!(tab_step(&graph, None, None, false), Some((None, first)));
+
+        let anchor = index(&graph, "user:a");
+        let around = neighbors(&graph, anchor);
+        assert_eq!(around.len(), 3);
+        let mut cursor = None;
+        let mut selected = Some(anchor);
+        let mut visited = Vec::new();
+        for _ in 0..4 {
+            let (next_cursor, node) = tab_step(&graph, selected, cursor, false).unwrap();
+            visited.push(node);
+            (cursor, selected) = (next_cursor, Some(node));
+        }
+        assert_eq!(visited, [around[0], around[1], around[2], around[0]]);
+        let (_, back) = tab_step(&graph, selected, cursor, true).unwrap();
+        assert_eq!(back, around[2]);
+    }
+
+    #[test]
+    fn neighbor_summaries_describe_each_connection() {
+        let graph = graph();
+        let anime = index(&graph, "anime:1");
+        let summaries = neighbor_summaries(&graph, anime, 10);
+        let user = index(&graph, "user:a");
+        let rater = summaries
+            .iter()
+            .find(|summary| summary.node == user)
+            .unwrap();
+        assert_eq!(rater.label, graph.nodes[user].label);
+        assert_eq!(rater.detail, "rated +4.00 vs. the user's mean");
+        assert!(summaries
+            .iter()
+            .any(|summary| summary.detail.starts_with("2 co-raters, score ")));
+        assert_eq!(neighbor_summaries(&graph, anime, 1).len(), 1);
+    }
+}
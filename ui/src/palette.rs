@@ -0,0 +1,218 @@
+//! The command palette: a search box over every action the frontend offers, matched fuzzily
+//! and driven from the keyboard. Commands run recently rank first.
+
+use dioxus::prelude::*;
+
+use crate::i18n::tr;
+
+/// Commands listed at most; the rest are reached by typing more.
+const SHOWN_COMMANDS: usize = 12;
+/// Extra score for the most recent command, less for each older one.
+const RECENT_BONUS: i32 = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteCommand {
+    /// Handed back when the command is run.
+    pub id: String,
+    pub label: String,
+    /// Shown after the label, e.g. the menu the action is also in.
+    pub group: String,
+    /// The shortcut that runs it too, e.g. `Ctrl+Z`.
+    pub keys: Option<String>,
+    /// Listed only once the query matches: too many to browse, like every anime in the graph.
+    pub searched_only: bool,
+}
+
+/// How well `query` matches `text`: its characters must appear in order, case-insensitive.
+/// Runs of adjacent characters and matches at the start of a word score higher. `None` when
+/// `text` doesn't contain the query's characters in order.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous = None;
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = position + text[position..].iter().position(|&c| c == wanted)?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += 4;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 6;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+/// The commands matching `query`, best first; `recent` lists command ids, most recent first.
+/// An empty query lists the recent commands, then the rest in their given order.
+pub fn rank<'a>(
+    commands: &'a [PaletteCommand],
+    query: &str,
+    recent: &[String],
+) -> Vec<&'a PaletteCommand> {
+    let bonus = |command: &PaletteCommand| {
+        recent
+            .iter()
+            .position(|id| *id == command.id)
+            .map_or(0, |position| {
+                RECENT_BONUS * (recent.len() - position) as i32
+            })
+    };
+    let searching = !query.trim().is_empty();
+    let mut ranked: Vec<(i32, &PaletteCommand)> = commands
+        .iter()
+        .filter(|command| searching || !command.searched_only)
+        .filter_map(|command| {
+            let score = if searching {
+                fuzzy_score(query, &command.label)?
+            } else {
+                0
+            };
+            Some((score + bonus(command), command))
+        })
+        .collect();
+    // Stable, so equal scores keep the given order; shorter labels win a tie while searching.
+    ranked
+        .sort_by_key(|(score, command)| (-score, if searching { command.label.len() } else { 0 }));
+    ranked.into_iter().map(|(_, command)| command).collect()
+}
+
+/// The palette, opened over the app. Arrow keys move the highlight, Enter runs it and Escape
+/// closes the palette, as does clicking outside it.
+#[component]
+pub fn CommandPalette(
+    commands: Vec<PaletteCommand>,
+    recent: Vec<String>,
+    on_run: EventHandler<String>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let mut query = use_signal(String::new);
+    let mut highlighted = use_signal(|| 0_usize);
+    let shown: Vec<PaletteCommand> = rank(&commands, &query.read(), &recent)
+        .into_iter()
+        .take(SHOWN_COMMANDS)
+        .cloned()
+        .collect();
+    let highlight = highlighted().min(shown.len().saturating_sub(1));
+    let chosen = shown.get(highlight).map(|command| command.id.clone());
+    let count = shown.len();
+    rsx! {
+        div { class: "dialog-backdrop", onclick: move |_| on_close.call(()),
+            div {
+                class: "dialog palette",
+                role: "dialog",
+                aria_modal: "true",
+                aria_label: tr!("shortcut-command-palette"),
+                onclick: move |evt| evt.stop_propagation(),
+                input {
+                    r#type: "search",
+                    placeholder: tr!("command-palette-placeholder"),
+                    value: "{query}",
+                    onmounted: move |evt| async move {
+                        let _ = evt.set_focus(true).await;
+                    },
+                    oninput: move |evt| {
+                        query.set(evt.value());
+                        highlighted.set(0);
+                    },
+                    onkeydown: move |evt| {
+                        // Keep the arrow keys and Escape away from the graph behind.
+                        evt.stop_propagation();
+                        match evt.key() {
+                            Key::ArrowDown if count > 0 => {
+                                evt.prevent_default();
+                                highlighted.set((highlight + 1) % count);
+                            }
+                            Key::ArrowUp if count > 0 => {
+                                evt.prevent_default();
+                                highlighted.set((highlight + count - 1) % count);
+                            }
+                            Key::Enter => {
+                                if let Some(id) = chosen.clone() {
+                                    on_run.call(id);
+                                }
+                            }
+                            Key::Escape => on_close.call(()),
+                            _ => {}
+                        }
+                    },
+                }
+                if shown.is_empty() {
+                    p { class: "tiny", {tr!("command-palette-empty")} }
+                }
+                ul { class: "palette-results", role: "listbox",
+                    for (index, command) in shown.into_iter().enumerate() {
+                        li {
+                            key: "{command.id}",
+                            class: if index == highlight { "active" },
+                            role: "option",
+                            aria_selected: index == highlight,
+                            onmouseenter: move |_| highlighted.set(index),
+                            onclick: move |_| on_run.call(command.id.clone()),
+                            span { "{command.label}" }
+                            span { class: "tiny",
+                                if let Some(keys) = &command.keys {
+                                    kbd { "{keys}" }
+                                    " "
+                                }
+                                "{command.group}"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(id: &str, label: &str) -> PaletteCommand {
+        PaletteCommand {
+            id: id.to_string(),
+            label: label.to_string(),
+            group: "Test".to_string(),
+            keys: None,
+            searched_only: false,
+        }
+    }
+
+    #[test]
+    fn fuzzy_score_needs_the_characters_in_order() {
+        assert!(fuzzy_score("opds", "Open dataset…").is_some());
+        assert!(fuzzy_score("OPEN", "Open dataset…").is_some());
+        assert_eq!(fuzzy_score("dso", "Open dataset…"), None);
+    }
+
+    #[test]
+    fn word_starts_and_runs_score_higher() {
+        assert!(fuzzy_score("ex", "Export graph…") > fuzzy_score("ex", "Index"));
+        assert!(fuzzy_score("gra", "Export graph…") > fuzzy_score("gra", "Generate a…"));
+    }
+
+    #[test]
+    fn recent_commands_rank_first() {
+        let commands = [
+            command("open", "Open dataset…"),
+            command("export", "Export graph…"),
+        ];
+        let recent = ["export".to_string()];
+        let ranked = rank(&commands, "", &recent);
+        assert_eq!(ranked[0].id, "export");
+        assert_eq!(rank(&commands, "open", &recent)[0].id, "open");
+    }
+
+    #[test]
+    fn searched_only_commands_need_a_query() {
+        let mut anime = command("anime:1", "Cowboy Bebop");
+        anime.searched_only = true;
+        let commands = [command("open", "Open dataset…"), anime];
+        assert_eq!(rank(&commands, "", &[]).len(), 1);
+        assert_eq!(rank(&commands, "bebop", &[])[0].id, "anime:1");
+    }
+}
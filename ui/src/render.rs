@@ -0,0 +1,555 @@
+//! Drawing the graph, apart from any one way of drawing it. [`Frame`] describes what a
+//! [`Scene`] looks like with a palette, the selection and search matches applied: lines and
+//! circles with their colors, widths and opacities, in layout units under a viewBox. Backends
+//! implement [`GraphRenderer`] to turn a frame into their own output, so each can be tested
+//! without a Dioxus runtime, and a frontend picks the [`RenderBackend`]
+//! [`crate::canvas::GraphCanvas`] draws with:
+//!
+//! - [`SvgRenderer`] writes a standalone SVG document, e.g. for exporting the view; the
+//!   canvas's own SVG renderer draws the same frame as live SVG elements.
+//! - [`Canvas2dRenderer`] writes a script that paints a `<canvas>` element's 2D context, for
+//!   browsers where thousands of SVG elements are slow.
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use wasiw_core::relations::RelationKind;
+use wasiw_core::{EdgeKind, GraphModel};
+
+use crate::camera::ViewBox;
+use crate::canvas::Scene;
+use crate::theme::Palette;
+
+/// Edge opacity, and while search matches dim the rest of the graph.
+const EDGE_OPACITY: f32 = 0.55;
+const DIMMED_EDGE_OPACITY: f32 = 0.2;
+/// Node opacity outside the search matches, as `.node.dimmed` in the stylesheet.
+const DIMMED_NODE_OPACITY: f32 = 0.18;
+/// Outline width of matched and selected nodes, as `.node.match`.
+const OUTLINE_WIDTH: f32 = 2.0;
+/// Relation arrows are drawn over the edges, so they stay more opaque.
+const ARROW_OPACITY: f32 = 0.9;
+const DIMMED_ARROW_OPACITY: f32 = 0.3;
+/// Arrow stroke width and head length in device pixels, whatever the zoom.
+const ARROW_WIDTH: f32 = 1.5;
+const ARROW_HEAD: f32 = 7.0;
+
+/// What stands out in a frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Highlight<'a> {
+    /// Search matches; the rest of the graph is dimmed while set.
+    pub matches: Option<&'a HashSet<usize>>,
+    pub selected: Option<usize>,
+    pub hovered_edge: Option<usize>,
+}
+
+/// An edge to draw.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line<'a> {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+    pub kind: EdgeKind,
+    /// A CSS hex color from the palette.
+    pub color: &'a str,
+    pub width: f32,
+    pub opacity: f32,
+    /// Index into `GraphModel::edges`, or `None` for edges aggregated from several.
+    pub edge: Option<usize>,
+    pub hovered: bool,
+}
+
+/// A relation between two anime, pointing from one to the next in watch order. The shaft runs
+/// between the two circles' rims, and the head is two strokes back from its tip.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arrow<'a> {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+    /// The free ends of the head's two strokes, which meet at `(x2, y2)`.
+    pub head: [(f32, f32); 2],
+    pub kind: RelationKind,
+    pub color: &'a str,
+    pub width: f32,
+    pub opacity: f32,
+}
+
+impl Arrow<'_> {
+    /// The shaft and the head's two strokes, as `((x1, y1), (x2, y2))`.
+    pub fn segments(&self) -> [((f32, f32), (f32, f32)); 3] {
+        let tip = (self.x2, self.y2);
+        [
+            ((self.x1, self.y1), tip),
+            (self.head[0], tip),
+            (self.head[1], tip),
+        ]
+    }
+}
+
+/// What a circle stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircleKind {
+    /// The node at this index of `GraphModel::nodes`.
+    Node(usize),
+    /// A collapsed community.
+    SuperNode(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeState {
+    Normal,
+    Selected,
+    Match,
+    Dimmed,
+}
+
+impl NodeState {
+    /// The node's class in the stylesheet.
+    pub fn class(self) -> &'static str {
+        match self {
+            Self::Normal => "node",
+            Self::Selected => "node selected",
+            Self::Match => "node match",
+            Self::Dimmed => "node dimmed",
+        }
+    }
+
+    pub fn opacity(self) -> f32 {
+        if self == Self::Dimmed {
+            DIMMED_NODE_OPACITY
+        } else {
+            1.0
+        }
+    }
+
+    /// Whether the node is outlined in the text color.
+    pub fn outlined(self) -> bool {
+        matches!(self, Self::Selected | Self::Match)
+    }
+}
+
+/// A node or super-node to draw.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Circle<'a> {
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    pub color: &'a str,
+    pub kind: CircleKind,
+    pub state: NodeState,
+    /// Shown on hover.
+    pub label: Cow<'a, str>,
+}
+
+/// Everything a backend draws for one scene, back to front: edges, then relation arrows, then
+/// super-nodes, then nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame<'a> {
+    pub view_box: ViewBox,
+    /// Stroke width of the hover target laid over similarity edges, for backends with
+    /// pointer events.
+    pub hit_width: f32,
+    pub lines: Vec<Line<'a>>,
+    pub arrows: Vec<Arrow<'a>>,
+    pub circles: Vec<Circle<'a>>,
+}
+
+impl<'a> Frame<'a> {
+    /// `scene` of `graph` in `palette`, with only the first `edge_limit` of its edges while a
+    /// new graph is still coming onto the canvas.
+    pub fn new(
+        graph: &'a GraphModel,
+        scene: &'a Scene,
+        palette: &'a Palette,
+        highlight: Highlight<'_>,
+        edge_limit: usize,
+    ) -> Self {
+        let edge_opacity = if highlight.matches.is_some() {
+            DIMMED_EDGE_OPACITY
+        } else {
+            EDGE_OPACITY
+        };
+        let lines = scene
+            .edges
+            .iter()
+            .take(edge_limit)
+            .map(|edge| Line {
+                x1: edge.x1,
+                y1: edge.y1,
+                x2: edge.x2,
+                y2: edge.y2,
+                kind: edge.kind,
+                color: palette.edge_color(edge.kind),
+                width: edge.stroke_width,
+                opacity: edge_opacity,
+                edge: edge.edge,
+                hovered: edge.edge.is_some() && edge.edge == highlight.hovered_edge,
+            })
+            .collect();
+
+        let arrow_opacity = if highlight.matches.is_some() {
+            DIMMED_ARROW_OPACITY
+        } else {
+            ARROW_OPACITY
+        };
+        // The scene's hairline is one device pixel.
+        let (width, head) = (scene.hairline * ARROW_WIDTH, scene.hairline * ARROW_HEAD);
+        let arrows = scene
+            .relations
+            .iter()
+            .filter_map(|&(from, to, kind)| {
+                let (from, to) = (&graph.nodes[from], &graph.nodes[to]);
+                let (dx, dy) = (to.x - from.x, to.y - from.y);
+                let length = dx.hypot(dy);
+                // Nothing to point along when the two circles touch.
+                if length <= from.radius + to.radius {
+                    return None;
+                }
+                let (ux, uy) = (dx / length, dy / length);
+                let (x2, y2) = (to.x - ux * to.radius, to.y - uy * to.radius);
+                let (back_x, back_y) = (x2 - ux * head, y2 - uy * head);
+                let (side_x, side_y) = (-uy * head / 2.0, ux * head / 2.0);
+                Some(Arrow {
+                    x1: from.x + ux * from.radius,
+                    y1: from.y + uy * from.radius,
+                    x2,
+                    y2,
+                    head: [
+                        (back_x + side_x, back_y + side_y),
+                        (back_x - side_x, back_y - side_y),
+                    ],
+                    kind,
+                    color: &palette.relation_edge,
+                    width,
+                    opacity: arrow_opacity,
+                })
+            })
+            .collect();
+
+        let super_nodes = scene
+            .clusters
+            .iter()
+            .flat_map(|clusters| &clusters.super_nodes)
+            .map(|super_node| Circle {
+                x: super_node.x,
+                y: super_node.y,
+                radius: super_node.radius,
+                color: &palette.anime_node,
+                kind: CircleKind::SuperNode(super_node.community),
+                state: NodeState::Normal,
+                label: Cow::Owned(format!(
+                    "Community of {} nodes (double-click to expand)",
+                    super_node.member_count
+                )),
+            });
+        let nodes = graph
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|&(idx, node)| !node.hidden && scene.shows_node(idx))
+            .map(|(idx, node)| Circle {
+                x: node.x,
+                y: node.y,
+                radius: node.radius,
+                color: palette.node_color(node.node_type),
+                kind: CircleKind::Node(idx),
+                state: match highlight.matches {
+                    _ if highlight.selected == Some(idx) => NodeState::Selected,
+                    Some(matched) if matched.contains(&idx) => NodeState::Match,
+                    Some(_) => NodeState::Dimmed,
+                    None => NodeState::Normal,
+                },
+                label: Cow::Borrowed(&node.label),
+            });
+
+        Self {
+            view_box: scene.view_box,
+            hit_width: scene.hit_width,
+            lines,
+            arrows,
+            circles: super_nodes.chain(nodes).collect(),
+        }
+    }
+}
+
+/// How [`crate::canvas::GraphCanvas`] draws.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RenderBackend {
+    /// Live SVG elements, styled by the stylesheet.
+    #[default]
+    Svg,
+    /// A `<canvas>` painted by [`Canvas2dRenderer`], under invisible SVG targets for pointer
+    /// events.
+    Canvas2d,
+}
+
+/// A way of drawing frames.
+pub trait GraphRenderer {
+    type Output;
+
+    fn render(&mut self, frame: &Frame<'_>) -> Self::Output;
+}
+
+/// Renders frames as standalone SVG documents.
+#[derive(Debug, Clone, Default)]
+pub struct SvgRenderer {
+    /// Outline color of selected and matched nodes, `currentColor` when empty.
+    pub outline: String,
+}
+
+impl GraphRenderer for SvgRenderer {
+    type Output = String;
+
+    fn render(&mut self, frame: &Frame<'_>) -> String {
+        let outline = if self.outline.is_empty() {
+            "currentColor"
+        } else {
+            &self.outline
+        };
+        let mut svg = String::with_capacity(96 * (frame.lines.len() + frame.circles.len()));
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{}">"#,
+            frame.view_box
+        );
+        for line in &frame.lines {
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" stroke-opacity="{}"/>"#,
+                line.x1, line.y1, line.x2, line.y2, line.color, line.width, line.opacity
+            );
+        }
+        for arrow in &frame.arrows {
+            let _ = writeln!(
+                svg,
+                r#"<path d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-opacity="{}"/>"#,
+                arrow_path(arrow),
+                arrow.color,
+                arrow.width,
+                arrow.opacity
+            );
+        }
+        for circle in &frame.circles {
+            let _ = write!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="{}""#,
+                circle.x, circle.y, circle.radius, circle.color
+            );
+            if circle.state.outlined() {
+                let _ = write!(svg, r#" stroke="{outline}" stroke-width="{OUTLINE_WIDTH}""#);
+            }
+            if circle.state == NodeState::Dimmed {
+                let _ = write!(svg, r#" opacity="{DIMMED_NODE_OPACITY}""#);
+            }
+            let _ = writeln!(svg, "><title>{}</title></circle>", escape(&circle.label));
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// An arrow as SVG path data, e.g. `M0 0L10 0M8 -1L10 0L8 1`.
+pub fn arrow_path(arrow: &Arrow<'_>) -> String {
+    let [(x1, y1), (x2, y2)] = arrow.head;
+    format!(
+        "M{} {}L{} {}M{x1} {y1}L{} {}L{x2} {y2}",
+        arrow.x1, arrow.y1, arrow.x2, arrow.y2, arrow.x2, arrow.y2
+    )
+}
+
+/// Text escaped for SVG content.
+fn escape(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<', '>']) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;"),
+    )
+}
+
+/// Renders frames as a script painting the `<canvas>` element with id `canvas_id`, sized to
+/// the element in device pixels and scaled so the frame's viewBox fills it. Lines of the same
+/// color, width and opacity go in one path.
+#[derive(Debug, Clone, Default)]
+pub struct Canvas2dRenderer {
+    pub canvas_id: String,
+    /// Outline color of selected and matched nodes, the context's default black when empty.
+    pub outline: String,
+}
+
+impl GraphRenderer for Canvas2dRenderer {
+    type Output = String;
+
+    fn render(&mut self, frame: &Frame<'_>) -> String {
+        let view = frame.view_box;
+        let mut js = String::with_capacity(48 * (frame.lines.len() + frame.circles.len()));
+        let _ = writeln!(
+            js,
+            "const canvas = document.getElementById({:?});\n\
+             if (!canvas) return false;\n\
+             const ratio = window.devicePixelRatio || 1;\n\
+             const width = Math.round(canvas.clientWidth * ratio);\n\
+             const height = Math.round(canvas.clientHeight * ratio);\n\
+             if (canvas.width !== width || canvas.height !== height) {{\n\
+               canvas.width = width;\n\
+               canvas.height = height;\n\
+             }}\n\
+             const ctx = canvas.getContext(\"2d\");\n\
+             ctx.setTransform(1, 0, 0, 1, 0, 0);\n\
+             ctx.clearRect(0, 0, canvas.width, canvas.height);\n\
+             const sx = canvas.width / {}, sy = canvas.height / {};\n\
+             ctx.setTransform(sx, 0, 0, sy, {} * -sx, {} * -sy);",
+            self.canvas_id,
+            view.width.max(f32::EPSILON),
+            view.height.max(f32::EPSILON),
+            view.x,
+            view.y
+        );
+        let mut style = None;
+        for line in &frame.lines {
+            let next = (line.color, line.width, line.opacity);
+            if style != Some(next) {
+                if style.is_some() {
+                    js.push_str("ctx.stroke();\n");
+                }
+                let _ = writeln!(
+                    js,
+                    "ctx.strokeStyle = {:?}; ctx.lineWidth = {}; ctx.globalAlpha = {}; ctx.beginPath();",
+                    line.color, line.width, line.opacity
+                );
+                style = Some(next);
+            }
+            let _ = writeln!(
+                js,
+                "ctx.moveTo({}, {}); ctx.lineTo({}, {});",
+                line.x1, line.y1, line.x2, line.y2
+            );
+        }
+        if style.is_some() {
+            js.push_str("ctx.stroke();\n");
+        }
+        for arrow in &frame.arrows {
+            let _ = write!(
+                js,
+                "ctx.strokeStyle = {:?}; ctx.lineWidth = {}; ctx.globalAlpha = {}; ctx.beginPath();",
+                arrow.color, arrow.width, arrow.opacity
+            );
+            for ((x1, y1), (x2, y2)) in arrow.segments() {
+                let _ = write!(js, " ctx.moveTo({x1}, {y1}); ctx.lineTo({x2}, {y2});");
+            }
+            js.push_str(" ctx.stroke();\n");
+        }
+        if !self.outline.is_empty() {
+            let _ = writeln!(js, "ctx.strokeStyle = {:?};", self.outline);
+        }
+        let _ = writeln!(js, "ctx.lineWidth = {OUTLINE_WIDTH};");
+        for circle in &frame.circles {
+            let _ = writeln!(
+                js,
+                "ctx.globalAlpha = {}; ctx.fillStyle = {:?}; ctx.beginPath(); \
+                 ctx.arc({}, {}, {}, 0, 2 * Math.PI); ctx.fill();{}",
+                circle.state.opacity(),
+                circle.color,
+                circle.x,
+                circle.y,
+                circle.radius,
+                if circle.state.outlined() {
+                    " ctx.stroke();"
+                } else {
+                    ""
+                }
+            );
+        }
+        js.push_str("ctx.globalAlpha = 1;\nreturn true;\n");
+        js
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::Camera;
+    use crate::theme::default_palette;
+    use wasiw_core::relations::{Relation, Relations};
+    use wasiw_core::{build_graph, Dataset, Rating, UserRatings, Viewport};
+
+    fn graph() -> GraphModel {
+        let user = |user_id: &str, anime: &[u32]| UserRatings {
+            user_id: user_id.to_string(),
+            ratings: anime
+                .iter()
+                .map(|&id| Rating::new(id, format!("Anime <{id}>"), f64::from(id + 4)))
+                .collect(),
+        };
+        build_graph(
+            Dataset::new(vec![user("a", &[1, 2, 3]), user("b", &[1, 2])]),
+            Viewport::default(),
+        )
+    }
+
+    #[test]
+    fn every_backend_draws_the_same_frame() {
+        let graph = graph();
+        let scene = Scene::new(
+            &graph,
+            Viewport::default(),
+            Camera::default(),
+            &HashSet::new(),
+            usize::MAX,
+            None,
+        );
+        let sequel = [Relation {
+            kind: RelationKind::Sequel,
+            anime_id: 2,
+        }];
+        let scene = scene.with_relations(&graph, &Relations::new([(1, &sequel[..])]));
+        let palette = default_palette();
+        let anime = graph.find_anime(3).unwrap();
+        let matches = HashSet::from([anime]);
+        let highlight = Highlight {
+            matches: Some(&matches),
+            ..Highlight::default()
+        };
+        let frame = Frame::new(&graph, &scene, &palette, highlight, usize::MAX);
+        assert_eq!(frame.lines.len(), scene.edges.len());
+        assert_eq!(frame.arrows.len(), 1);
+        assert_eq!(frame.circles.len(), graph.nodes.len());
+        let matched = frame
+            .circles
+            .iter()
+            .find(|circle| circle.kind == CircleKind::Node(anime))
+            .unwrap();
+        assert_eq!(matched.state, NodeState::Match);
+        assert_eq!(
+            frame
+                .circles
+                .iter()
+                .filter(|circle| circle.state == NodeState::Dimmed)
+                .count(),
+            graph.nodes.len() - 1
+        );
+
+        let svg = SvgRenderer::default().render(&frame);
+        assert_eq!(svg.matches("<line ").count(), frame.lines.len());
+        assert_eq!(svg.matches("<path ").count(), frame.arrows.len());
+        assert_eq!(svg.matches("<circle ").count(), frame.circles.len());
+        assert!(svg.contains("<title>Anime &lt;3&gt;</title>"));
+
+        let js = Canvas2dRenderer::default().render(&frame);
+        assert_eq!(js.matches("ctx.arc(").count(), frame.circles.len());
+        // Each arrow is a shaft and two head strokes.
+        let segments = frame.lines.len() + 3 * frame.arrows.len();
+        assert_eq!(js.matches("ctx.lineTo(").count(), segments);
+
+        assert!(js.contains("canvas.width = width;"));
+
+        // Frames of a graph still coming onto the canvas stop at the edge limit.
+        let partial = Frame::new(&graph, &scene, &palette, Highlight::default(), 1);
+        assert_eq!(partial.lines.len(), 1);
+    }
+}
@@ -0,0 +1,174 @@
+//! Work too slow for one frame, spread over several: each animation frame gives queued jobs
+//! [`DEFERRED_BUDGET`] of its time and they pick up where they stopped on the next, so panning
+//! and zooming keep to 16 ms however large the graph. Jobs run in the order queued and a job
+//! queued under the key of one still waiting replaces it, as when the graph changes again
+//! before the work for its last version is done.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use dioxus::core::spawn_forever;
+use dioxus::prelude::*;
+use web_time::Instant;
+
+/// Time per frame handed to deferred work, leaving the rest of 16 ms to the interaction and
+/// painting.
+pub const DEFERRED_BUDGET: Duration = Duration::from_millis(4);
+/// Resolves on the webview's next animation frame.
+pub(crate) const NEXT_FRAME_JS: &str =
+    "await new Promise(resolve => requestAnimationFrame(resolve)); return true;";
+
+/// The time one frame has left for deferred work.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameBudget {
+    deadline: Instant,
+}
+
+impl FrameBudget {
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            deadline: Instant::now() + budget,
+        }
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.deadline.saturating_duration_since(Instant::now())
+    }
+
+    pub fn is_spent(&self) -> bool {
+        Instant::now() >= self.deadline
+    }
+}
+
+/// A job that can stop and carry on later.
+pub trait Deferred {
+    /// Does work until it is finished or `budget` is spent, returning whether it finished.
+    /// Each call should make some progress, however little budget is left. Jobs must not
+    /// queue jobs themselves; they can write signals whose effects do.
+    fn step(&mut self, budget: &FrameBudget) -> bool;
+}
+
+impl<F: FnMut(&FrameBudget) -> bool> Deferred for F {
+    fn step(&mut self, budget: &FrameBudget) -> bool {
+        self(budget)
+    }
+}
+
+/// Jobs waiting for frame time.
+#[derive(Default)]
+pub struct Scheduler {
+    jobs: VecDeque<(&'static str, Box<dyn Deferred>)>,
+}
+
+impl Scheduler {
+    /// Queues `job` under `key`, in place of a job waiting under the same key.
+    pub fn schedule(&mut self, key: &'static str, job: impl Deferred + 'static) {
+        let job: Box<dyn Deferred> = Box::new(job);
+        match self.jobs.iter_mut().find(|(queued, _)| *queued == key) {
+            Some(slot) => slot.1 = job,
+            None => self.jobs.push_back((key, job)),
+        }
+    }
+
+    /// Drops the job waiting under `key`, if any.
+    pub fn cancel(&mut self, key: &str) {
+        self.jobs.retain(|(queued, _)| *queued != key);
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Runs the jobs in order within `budget`, dropping those that finish.
+    pub fn run(&mut self, budget: &FrameBudget) {
+        while let Some((_, job)) = self.jobs.front_mut() {
+            if job.step(budget) {
+                self.jobs.pop_front();
+            }
+            if budget.is_spent() {
+                break;
+            }
+        }
+    }
+}
+
+/// A [`Scheduler`] run on animation frames, shared through context by
+/// [`use_frame_scheduler`].
+#[derive(Clone, Copy)]
+pub struct FrameScheduler {
+    jobs: Signal<Scheduler>,
+    running: Signal<bool>,
+}
+
+impl FrameScheduler {
+    /// Queues `job` under `key` and makes sure frames are being handed out.
+    pub fn schedule(&mut self, key: &'static str, job: impl Deferred + 'static) {
+        self.jobs.write().schedule(key, job);
+        if *self.running.peek() {
+            return;
+        }
+        self.running.set(true);
+        let mut jobs = self.jobs;
+        let mut running = self.running;
+        // At the root, so the loop outlives the component that queued the job.
+        spawn_forever(async move {
+            while !jobs.peek().is_idle() {
+                // Without frames to wait for, the work runs a budget at a time regardless.
+                let _ = document::eval(NEXT_FRAME_JS).join::<bool>().await;
+                jobs.write().run(&FrameBudget::new(DEFERRED_BUDGET));
+            }
+            running.set(false);
+        });
+    }
+
+    pub fn cancel(&mut self, key: &str) {
+        self.jobs.write().cancel(key);
+    }
+}
+
+/// The app's frame scheduler, made by the first component to ask for it.
+pub fn use_frame_scheduler() -> FrameScheduler {
+    use_root_context(|| FrameScheduler {
+        jobs: Signal::new_in_scope(Scheduler::default(), ScopeId::ROOT),
+        running: Signal::new_in_scope(false, ScopeId::ROOT),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn jobs_carry_on_across_frames_and_are_replaced_by_key() {
+        let done = Rc::new(RefCell::new(Vec::new()));
+        let counting = |name: &'static str, steps: usize| {
+            let done = done.clone();
+            let mut left = steps;
+            move |_: &FrameBudget| {
+                left -= 1;
+                if left == 0 {
+                    done.borrow_mut().push(name);
+                }
+                left == 0
+            }
+        };
+        let mut scheduler = Scheduler::default();
+        scheduler.schedule("index", counting("old index", 1));
+        scheduler.schedule("clusters", counting("clusters", 2));
+        scheduler.schedule("index", counting("index", 3));
+
+        // A spent budget still lets the first job make one step a frame.
+        let spent = FrameBudget::new(Duration::ZERO);
+        scheduler.run(&spent);
+        scheduler.run(&spent);
+        assert!(done.borrow().is_empty());
+        scheduler.run(&spent);
+        assert_eq!(*done.borrow(), ["index"]);
+
+        scheduler.run(&FrameBudget::new(Duration::from_secs(60)));
+        assert_eq!(*done.borrow(), ["index", "clusters"]);
+        assert!(scheduler.is_idle());
+    }
+}
@@ -0,0 +1,790 @@
+//! Light and dark themes, interface scales, graph color palettes and the stylesheet every
+//! frontend shares.
+
+use serde::{Deserialize, Serialize};
+use wasiw_core::{EdgeKind, NodeType};
+
+/// Theme selection persisted in the config file; `System` follows the OS appearance.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemePreference {
+    #[default]
+    System,
+    Dark,
+    Light,
+}
+
+impl ThemePreference {
+    pub const ALL: [ThemePreference; 3] = [Self::System, Self::Dark, Self::Light];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::System => "System",
+            Self::Dark => "Dark",
+            Self::Light => "Light",
+        }
+    }
+
+    /// The name in the interface language, as the theme picker shows it.
+    pub fn name(self) -> String {
+        crate::i18n::tr(match self {
+            Self::System => "theme-system",
+            Self::Dark => "theme-dark",
+            Self::Light => "theme-light",
+        })
+    }
+
+    pub fn resolve(self, system: ThemeVariant) -> ThemeVariant {
+        match self {
+            Self::System => system,
+            Self::Dark => ThemeVariant::Dark,
+            Self::Light => ThemeVariant::Light,
+        }
+    }
+}
+
+/// How large text, controls and spacing are, for high-DPI monitors and low vision. Every size
+/// in [`APP_CSS`] scales with it, except the graph's, which zooms on its own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UiScale {
+    Compact,
+    #[default]
+    Comfortable,
+    Large,
+}
+
+impl UiScale {
+    pub const ALL: [UiScale; 3] = [Self::Compact, Self::Comfortable, Self::Large];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Compact => "Compact",
+            Self::Comfortable => "Comfortable",
+            Self::Large => "Large",
+        }
+    }
+
+    /// The name in the interface language, as the scale picker shows it.
+    pub fn name(self) -> String {
+        crate::i18n::tr(match self {
+            Self::Compact => "scale-compact",
+            Self::Comfortable => "scale-comfortable",
+            Self::Large => "scale-large",
+        })
+    }
+
+    /// Class on the app root that sets `--ui-scale`.
+    pub fn class(self) -> &'static str {
+        match self {
+            Self::Compact => "scale-compact",
+            Self::Comfortable => "scale-comfortable",
+            Self::Large => "scale-large",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeVariant {
+    Dark,
+    Light,
+}
+
+impl ThemeVariant {
+    /// Class on the app root that selects the matching set of CSS variables.
+    pub fn class(self) -> &'static str {
+        match self {
+            Self::Dark => "theme-dark",
+            Self::Light => "theme-light",
+        }
+    }
+}
+
+/// Colors used to draw the graph. Values are CSS hex colors (`#rgb`, `#rrggbb` or `#rrggbbaa`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Palette {
+    pub name: String,
+    pub user_node: String,
+    pub anime_node: String,
+    pub rating_edge: String,
+    pub similarity_edge: String,
+    /// Sequel, side-story and other relation arrows; palettes saved before they were drawn get
+    /// the default's.
+    #[serde(default = "default_relation_edge")]
+    pub relation_edge: String,
+}
+
+impl Palette {
+    pub fn node_color(&self, node_type: NodeType) -> &str {
+        match node_type {
+            NodeType::User => &self.user_node,
+            NodeType::Anime | NodeType::Genre | NodeType::Studio | NodeType::Director => {
+                &self.anime_node
+            }
+        }
+    }
+
+    pub fn edge_color(&self, kind: EdgeKind) -> &str {
+        match kind {
+            EdgeKind::Rating => &self.rating_edge,
+            EdgeKind::Similarity => &self.similarity_edge,
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        !self.name.trim().is_empty()
+            && [
+                &self.user_node,
+                &self.anime_node,
+                &self.rating_edge,
+                &self.similarity_edge,
+                &self.relation_edge,
+            ]
+            .iter()
+            .all(|color| is_hex_color(color))
+    }
+}
+
+pub fn default_palette() -> Palette {
+    Palette {
+        name: "Default".to_string(),
+        user_node: "#ff8a00".to_string(),
+        anime_node: "#0f8b8d".to_string(),
+        rating_edge: "#f4d35ea6".to_string(),
+        similarity_edge: "#6fffe980".to_string(),
+        relation_edge: default_relation_edge(),
+    }
+}
+
+fn default_relation_edge() -> String {
+    "#ff5d8fe6".to_string()
+}
+
+/// Okabe–Ito colors, distinguishable under the common forms of color vision deficiency.
+pub fn colorblind_palette() -> Palette {
+    Palette {
+        name: "Colorblind-safe (Okabe–Ito)".to_string(),
+        user_node: "#e69f00".to_string(),
+        anime_node: "#56b4e9".to_string(),
+        rating_edge: "#f0e442a6".to_string(),
+        similarity_edge: "#009e7399".to_string(),
+        relation_edge: "#cc79a7e6".to_string(),
+    }
+}
+
+/// Built-in palettes followed by the valid ones of `custom` (user-defined palettes).
+/// Returns the palettes and how many user palettes were skipped as invalid.
+pub fn available_palettes(custom: &[Palette]) -> (Vec<Palette>, usize) {
+    let mut palettes = vec![default_palette(), colorblind_palette()];
+    let mut skipped = 0;
+    for palette in custom {
+        if palette.is_valid() && !palettes.iter().any(|p| p.name == palette.name) {
+            palettes.push(palette.clone());
+        } else {
+            skipped += 1;
+        }
+    }
+    (palettes, skipped)
+}
+
+/// The palette called `name`, falling back to the default one.
+pub fn active_palette(custom: &[Palette], name: Option<&str>) -> Palette {
+    let (palettes, _) = available_palettes(custom);
+    name.and_then(|name| palettes.iter().find(|p| p.name == name))
+        .cloned()
+        .unwrap_or_else(default_palette)
+}
+
+fn is_hex_color(value: &str) -> bool {
+    let Some(hex) = value.strip_prefix('#') else {
+        return false;
+    };
+    matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+pub const APP_CSS: &str = r#"
+  html, body {
+    margin: 0;
+    height: 100%;
+  }
+  .theme-dark {
+    --glow: #2e5678;
+    --bg-from: #091019;
+    --bg-to: #17354f;
+    --text: #f4f1de;
+    --muted: #b0b8c0;
+    --surface: #0e1723cc;
+    --surface-solid: #0e1723f2;
+    --border: #ffffff26;
+    --border-soft: #ffffff1f;
+    --border-strong: #ffffff33;
+    --canvas: #070d14;
+    --control: #17354f;
+    color-scheme: dark;
+  }
+  .theme-light {
+    --glow: #c9def2;
+    --bg-from: #f6f8fb;
+    --bg-to: #dbe6f0;
+    --text: #1b2631;
+    --muted: #586572;
+    --surface: #ffffffcc;
+    --surface-solid: #fffffff2;
+    --border: #0000001f;
+    --border-soft: #00000017;
+    --border-strong: #00000029;
+    --canvas: #f9fbfd;
+    --control: #dbe6f0;
+    color-scheme: light;
+  }
+  .app {
+    --ui-scale: 1;
+    margin: 0;
+    height: 100vh;
+    display: grid;
+    grid-template-columns: calc(320px * var(--ui-scale)) 1fr;
+    gap: calc(16px * var(--ui-scale));
+    padding: calc(16px * var(--ui-scale));
+    background: radial-gradient(circle at 20% 20%, var(--glow) 0%, transparent 45%),
+      linear-gradient(160deg, var(--bg-from) 0%, var(--bg-to) 100%);
+    color: var(--text);
+    font-family: Segoe UI, sans-serif;
+    font-size: calc(16px * var(--ui-scale));
+    box-sizing: border-box;
+  }
+  .app.scale-compact {
+    --ui-scale: 0.875;
+  }
+  .app.scale-large {
+    --ui-scale: 1.25;
+  }
+  /* Controls don't inherit the font size; `:where` keeps this below any class's. */
+  :where(.app) :is(button, input, select, textarea) {
+    font-size: calc(13px * var(--ui-scale));
+  }
+  .panel {
+    border: 1px solid var(--border);
+    border-radius: 14px;
+    padding: calc(14px * var(--ui-scale));
+    background: var(--surface);
+  }
+  .muted {
+    color: var(--muted);
+    margin-top: 0;
+  }
+  .stats {
+    margin-top: calc(14px * var(--ui-scale));
+    border: 1px solid var(--border-soft);
+    border-radius: 12px;
+    padding: calc(10px * var(--ui-scale));
+  }
+  .chart {
+    margin: calc(12px * var(--ui-scale)) 0 0;
+  }
+  .chart svg {
+    display: block;
+    width: 100%;
+    height: auto;
+    margin-top: calc(4px * var(--ui-scale));
+  }
+  .chart-label {
+    fill: var(--muted);
+    font-size: 9px;
+  }
+  .chart-axis {
+    stroke: var(--border-strong);
+  }
+  .chart-track {
+    fill: var(--border-soft);
+  }
+  .chart-cell {
+    cursor: pointer;
+  }
+  .chart-cell.selected {
+    stroke: var(--text);
+    stroke-width: 1;
+  }
+  .ranking {
+    width: 100%;
+    border-collapse: collapse;
+    font-size: calc(12px * var(--ui-scale));
+  }
+  .ranking th,
+  .ranking td {
+    padding: calc(3px * var(--ui-scale)) calc(6px * var(--ui-scale));
+    border-bottom: 1px solid var(--border-soft);
+    text-align: left;
+  }
+  .ranking td + td {
+    font-variant-numeric: tabular-nums;
+  }
+  .row {
+    display: flex;
+    justify-content: space-between;
+    font-size: calc(14px * var(--ui-scale));
+    padding: 2px 0;
+  }
+  .tiny {
+    color: var(--muted);
+    font-size: calc(12px * var(--ui-scale));
+  }
+  .app.fullscreen {
+    grid-template-columns: 1fr;
+    padding: 0;
+  }
+  .app:focus {
+    outline: none;
+  }
+  /* The tray's one-pick window. */
+  .app.toast {
+    grid-template-columns: 1fr;
+    align-content: center;
+    gap: calc(4px * var(--ui-scale));
+    padding: calc(12px * var(--ui-scale));
+    border: 1px solid var(--border-strong);
+    cursor: pointer;
+  }
+  .app.toast p {
+    margin: 0;
+  }
+  .app.dropping {
+    outline: 2px dashed var(--border-strong);
+    outline-offset: -8px;
+  }
+  .search {
+    display: grid;
+    gap: calc(4px * var(--ui-scale));
+    margin-top: calc(10px * var(--ui-scale));
+  }
+  .search input {
+    padding: calc(6px * var(--ui-scale)) calc(8px * var(--ui-scale));
+    border-radius: 8px;
+    border: 1px solid var(--border-strong);
+    background: var(--canvas);
+    color: inherit;
+  }
+  .field {
+    display: grid;
+    gap: calc(4px * var(--ui-scale));
+    margin-top: calc(10px * var(--ui-scale));
+  }
+  .field input[type="text"] {
+    padding: calc(6px * var(--ui-scale)) calc(8px * var(--ui-scale));
+    border-radius: 8px;
+    border: 1px solid var(--border-strong);
+    background: var(--canvas);
+    color: inherit;
+  }
+  .field .action {
+    margin-top: 2px;
+  }
+  .field select {
+    padding: calc(5px * var(--ui-scale)) calc(6px * var(--ui-scale));
+    border-radius: 8px;
+    border: 1px solid var(--border-strong);
+    background: var(--canvas);
+    color: inherit;
+  }
+  .toggle {
+    display: flex;
+    align-items: center;
+    gap: calc(6px * var(--ui-scale));
+    margin-top: calc(10px * var(--ui-scale));
+    font-size: calc(13px * var(--ui-scale));
+  }
+  .action {
+    margin-top: calc(12px * var(--ui-scale));
+    padding: calc(6px * var(--ui-scale)) calc(10px * var(--ui-scale));
+    border-radius: 8px;
+    border: 1px solid var(--border-strong);
+    background: var(--control);
+    color: inherit;
+    cursor: pointer;
+  }
+  .chips {
+    display: flex;
+    flex-wrap: wrap;
+    gap: calc(6px * var(--ui-scale));
+    margin: calc(6px * var(--ui-scale)) 0;
+  }
+  .chip {
+    padding: calc(3px * var(--ui-scale)) calc(10px * var(--ui-scale));
+    border-radius: 999px;
+    border: 1px solid var(--border-strong);
+    background: var(--control);
+    color: var(--muted);
+    font-size: calc(12px * var(--ui-scale));
+    cursor: pointer;
+  }
+  .chip[aria-pressed="true"] {
+    color: var(--text);
+    border-color: var(--text);
+  }
+  .semantic-results {
+    max-height: calc(240px * var(--ui-scale));
+    overflow: auto;
+    margin: calc(4px * var(--ui-scale)) 0;
+    padding-left: calc(20px * var(--ui-scale));
+    font-size: calc(13px * var(--ui-scale));
+  }
+  .link {
+    padding: 0;
+    border: 0;
+    background: none;
+    color: inherit;
+    font: inherit;
+    text-align: left;
+    text-decoration: underline;
+    cursor: pointer;
+  }
+  .sr-only,
+  .skip-link:not(:focus) {
+    position: absolute;
+    width: 1px;
+    height: 1px;
+    overflow: hidden;
+    clip-path: inset(50%);
+    white-space: nowrap;
+  }
+  .skip-link:focus {
+    position: absolute;
+    z-index: 3;
+    top: 8px;
+    left: 8px;
+  }
+  :focus-visible {
+    outline: 2px solid var(--text);
+    outline-offset: 2px;
+  }
+  .outline {
+    height: 100%;
+    overflow: auto;
+    padding: calc(16px * var(--ui-scale)) calc(24px * var(--ui-scale));
+    box-sizing: border-box;
+    font-size: calc(14px * var(--ui-scale));
+  }
+  .outline ol ol {
+    margin: 2px 0 calc(8px * var(--ui-scale));
+    font-size: calc(13px * var(--ui-scale));
+  }
+  .hud {
+    position: absolute;
+    top: 12px;
+    left: 12px;
+    width: calc(260px * var(--ui-scale));
+    padding: calc(10px * var(--ui-scale));
+    border-radius: 12px;
+    border: 1px solid var(--border);
+    background: var(--surface);
+  }
+  .hud .search {
+    margin-top: 0;
+  }
+  .hud-stats {
+    display: flex;
+    gap: calc(10px * var(--ui-scale));
+    margin-top: calc(8px * var(--ui-scale));
+    font-size: calc(13px * var(--ui-scale));
+  }
+  .context-menu {
+    position: absolute;
+    z-index: 2;
+    display: grid;
+    gap: calc(4px * var(--ui-scale));
+    min-width: calc(180px * var(--ui-scale));
+    padding: calc(8px * var(--ui-scale));
+    border-radius: 10px;
+    border: 1px solid var(--border-strong);
+    background: var(--surface-solid);
+    font-size: calc(13px * var(--ui-scale));
+  }
+  .context-menu button {
+    padding: calc(6px * var(--ui-scale)) calc(8px * var(--ui-scale));
+    border: 0;
+    border-radius: 6px;
+    background: var(--control);
+    color: inherit;
+    text-align: left;
+    cursor: pointer;
+  }
+  .canvas-wrap svg {
+    touch-action: none;
+  }
+  /* Behind the SVG targets and the cards over the graph, above the wrap's background. */
+  .graph-paint {
+    position: absolute;
+    z-index: -1;
+    inset: 0;
+    width: 100%;
+    height: 100%;
+    pointer-events: none;
+  }
+  .node-hit {
+    cursor: pointer;
+  }
+  .node.match {
+    stroke: var(--text);
+    stroke-width: 2;
+  }
+  .node.dimmed {
+    opacity: 0.18;
+  }
+  .details .poster {
+    display: block;
+    max-width: 100%;
+    max-height: calc(220px * var(--ui-scale));
+    margin-top: calc(8px * var(--ui-scale));
+    border-radius: 8px;
+  }
+  .thumbnail {
+    width: calc(32px * var(--ui-scale));
+    height: calc(45px * var(--ui-scale));
+    margin-right: 6px;
+    border-radius: 4px;
+    object-fit: cover;
+    vertical-align: middle;
+  }
+  .tabs {
+    display: flex;
+    flex-wrap: wrap;
+    gap: calc(4px * var(--ui-scale));
+    margin-top: calc(10px * var(--ui-scale));
+    border-bottom: 1px solid var(--border-soft);
+  }
+  .tabs button,
+  .tabs a {
+    padding: calc(5px * var(--ui-scale)) calc(10px * var(--ui-scale));
+    border: 0;
+    border-radius: 8px 8px 0 0;
+    background: transparent;
+    color: var(--muted);
+    text-decoration: none;
+    cursor: pointer;
+  }
+  .tabs button.active,
+  .tabs a.active {
+    background: var(--control);
+    color: inherit;
+  }
+  .details .recommendations {
+    max-height: calc(280px * var(--ui-scale));
+    overflow: auto;
+  }
+  .details .recommendations p {
+    margin: 2px 0 calc(6px * var(--ui-scale));
+  }
+  .details .blurb {
+    margin: 2px 0 calc(6px * var(--ui-scale));
+    font-size: calc(12px * var(--ui-scale));
+    font-style: italic;
+    line-height: 1.4;
+  }
+  .details .synopsis {
+    max-height: calc(160px * var(--ui-scale));
+    overflow: auto;
+    font-size: calc(12px * var(--ui-scale));
+    line-height: 1.4;
+  }
+  .dialog-backdrop {
+    position: fixed;
+    inset: 0;
+    z-index: 10;
+    display: grid;
+    place-items: center;
+    background: rgba(0, 0, 0, 0.45);
+  }
+  .dialog {
+    width: min(calc(640px * var(--ui-scale)), 90vw);
+    max-height: 85vh;
+    overflow: auto;
+    padding: calc(14px * var(--ui-scale));
+    border-radius: 14px;
+    border: 1px solid var(--border-strong);
+    background: var(--surface-solid);
+  }
+  .dialog.palette {
+    align-self: start;
+    margin-top: 12vh;
+  }
+  .palette input {
+    width: 100%;
+  }
+  .palette-results {
+    margin: calc(8px * var(--ui-scale)) 0 0;
+    padding: 0;
+    list-style: none;
+  }
+  .palette-results li {
+    display: flex;
+    justify-content: space-between;
+    gap: calc(12px * var(--ui-scale));
+    padding: calc(5px * var(--ui-scale)) calc(8px * var(--ui-scale));
+    border-radius: 8px;
+    cursor: pointer;
+  }
+  .palette-results li.active {
+    background: var(--control);
+  }
+  .shortcuts {
+    width: 100%;
+    border-collapse: collapse;
+  }
+  .shortcuts td {
+    padding: calc(4px * var(--ui-scale)) calc(6px * var(--ui-scale));
+    border-bottom: 1px solid var(--border-soft);
+  }
+  kbd {
+    padding: 1px calc(6px * var(--ui-scale));
+    border-radius: 4px;
+    border: 1px solid var(--border-strong);
+    background: var(--control);
+    font-family: ui-monospace, monospace;
+    font-size: calc(12px * var(--ui-scale));
+  }
+  .task-list {
+    margin: calc(8px * var(--ui-scale)) 0;
+    padding: 0;
+    list-style: none;
+  }
+  .task-list li {
+    padding: calc(4px * var(--ui-scale)) 0;
+    border-bottom: 1px solid var(--border-soft);
+  }
+  .task-progress {
+    display: block;
+    width: 100%;
+    height: calc(6px * var(--ui-scale));
+  }
+  .log-panel {
+    position: fixed;
+    right: 12px;
+    bottom: 12px;
+    z-index: 5;
+    width: min(calc(720px * var(--ui-scale)), 60vw);
+    padding: calc(8px * var(--ui-scale)) calc(10px * var(--ui-scale));
+    border-radius: 12px;
+    border: 1px solid var(--border-strong);
+    background: var(--surface-solid);
+  }
+  .log-entries {
+    max-height: calc(240px * var(--ui-scale));
+    margin: calc(6px * var(--ui-scale)) 0 0;
+    padding: 0;
+    overflow: auto;
+    list-style: none;
+    font-family: ui-monospace, monospace;
+    font-size: calc(11px * var(--ui-scale));
+    line-height: 1.4;
+  }
+  .log-entries .log-warn {
+    color: #d89b2b;
+  }
+  .log-entries .log-error {
+    color: #e0566b;
+  }
+  .load-errors {
+    padding-left: calc(18px * var(--ui-scale));
+  }
+  .load-errors code {
+    word-break: break-all;
+  }
+  .load-errors p {
+    margin: 2px 0 calc(8px * var(--ui-scale));
+  }
+  .health-error {
+    color: #e0566b;
+  }
+  .health-warning {
+    color: #d89b2b;
+  }
+  .dialog-grid {
+    display: grid;
+    grid-template-columns: 1fr 1fr;
+    column-gap: calc(12px * var(--ui-scale));
+  }
+  .dialog .preview {
+    width: 100%;
+    margin-top: calc(8px * var(--ui-scale));
+    border-collapse: collapse;
+    font-size: calc(12px * var(--ui-scale));
+  }
+  .dialog .preview th,
+  .dialog .preview td {
+    padding: calc(3px * var(--ui-scale)) calc(6px * var(--ui-scale));
+    border-bottom: 1px solid var(--border-soft);
+    text-align: left;
+    white-space: nowrap;
+  }
+  .canvas-wrap {
+    position: relative;
+    min-width: 0;
+    min-height: 0;
+    border: 1px solid var(--border);
+    border-radius: 14px;
+    overflow: hidden;
+    isolation: isolate;
+    background: var(--canvas);
+  }
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn palette(name: &str, color: &str) -> Palette {
+        Palette {
+            name: name.to_string(),
+            user_node: color.to_string(),
+            ..default_palette()
+        }
+    }
+
+    #[test]
+    fn only_the_system_preference_follows_the_os() {
+        for system in [ThemeVariant::Dark, ThemeVariant::Light] {
+            assert_eq!(ThemePreference::System.resolve(system), system);
+            assert_eq!(ThemePreference::Dark.resolve(system), ThemeVariant::Dark);
+            assert_eq!(ThemePreference::Light.resolve(system), ThemeVariant::Light);
+        }
+        let saved: Vec<ThemePreference> =
+            serde_json::from_str(r#"["system", "dark", "light"]"#).unwrap();
+        assert_eq!(saved, ThemePreference::ALL);
+        assert_eq!(ThemeVariant::Light.class(), "theme-light");
+    }
+
+    #[test]
+    fn invalid_or_clashing_user_palettes_are_skipped() {
+        let custom = [
+            palette("Sunset", "#ff5500"),
+            palette("Short", "#f50"),
+            palette("Named", "orange"),
+            palette("Default", "#000000"),
+            palette("  ", "#000000"),
+            palette("Sunset", "#112233"),
+            palette("Overlong", "#ff550000ff"),
+        ];
+        let (palettes, skipped) = available_palettes(&custom);
+        let names: Vec<&str> = palettes.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["Default", "Colorblind-safe (Okabe–Ito)", "Sunset", "Short"]
+        );
+        assert_eq!(skipped, 5);
+
+        assert_eq!(active_palette(&custom, Some("Sunset")).user_node, "#ff5500");
+        assert_eq!(active_palette(&custom, Some("Named")), default_palette());
+        assert_eq!(active_palette(&custom, None), default_palette());
+    }
+
+    #[test]
+    fn palettes_saved_before_relation_arrows_get_the_default_color() {
+        let saved: Palette = serde_json::from_str(
+            r##"{ "name": "Old", "user_node": "#111", "anime_node": "#222",
+                  "rating_edge": "#333", "similarity_edge": "#444" }"##,
+        )
+        .unwrap();
+        assert_eq!(saved.relation_edge, default_palette().relation_edge);
+        assert_eq!(saved.node_color(NodeType::Studio), "#222");
+        assert_eq!(saved.edge_color(EdgeKind::Similarity), "#444");
+    }
+}
@@ -0,0 +1,135 @@
+//! JavaScript bindings for `wasiw-core`, for static sites that want the recommender without
+//! the Dioxus app. Built with `wasm-pack build --target web`:
+//!
+//! ```js
+//! import init, { loadDataset } from "./pkg/wasiw_wasm.js";
+//!
+//! await init();
+//! const bytes = new Uint8Array(await (await fetch("anonymized-ratings.json")).arrayBuffer());
+//! const engine = loadDataset(bytes);
+//! engine.recommend("0123abcd", 10); // [{ animeId, title, score, supportCount, ... }]
+//! engine.similar(5114, 10);         // [{ animeId, title, score, coRaters }]
+//! ```
+//!
+//! Results are plain objects with the same camelCase fields as the `wasiw serve` API.
+
+use serde::Serialize;
+use wasiw_core::recommend;
+use wasiw_core::stats::Stats;
+use wasiw_core::{dataset, GraphModel, MergeStrategy, NodeType, QualityReport, Viewport};
+use wasm_bindgen::prelude::*;
+
+/// Results per query when `top` isn't given.
+const DEFAULT_TOP: usize = 20;
+
+/// Parses a dataset (plain or gzip JSON) and builds its graph. `duplicates` is
+/// `"latest-wins"` (the default), `"highest"` or `"average"`.
+#[wasm_bindgen(js_name = loadDataset)]
+pub fn load_dataset(data: &[u8], duplicates: Option<String>) -> Result<Engine, JsError> {
+    let duplicates = match duplicates.as_deref() {
+        None | Some("latest-wins") => MergeStrategy::LatestWins,
+        Some("highest") => MergeStrategy::Highest,
+        Some("average") => MergeStrategy::Average,
+        Some(other) => {
+            return Err(JsError::new(&format!(
+                "unknown duplicates strategy {other:?}; expected \"latest-wins\", \"highest\" or \"average\""
+            )))
+        }
+    };
+    let (dataset, report) =
+        dataset::parse(data, duplicates).map_err(|err| JsError::new(&err.to_string()))?;
+    let (graph, stats) = Stats::build(dataset, Viewport::default(), |_| {});
+    Ok(Engine {
+        graph,
+        stats,
+        report,
+    })
+}
+
+/// A loaded dataset and its graph.
+#[wasm_bindgen]
+pub struct Engine {
+    graph: GraphModel,
+    stats: Stats,
+    report: QualityReport,
+}
+
+#[wasm_bindgen]
+impl Engine {
+    /// Ranked recommendations for a user id, or an unambiguous prefix of one.
+    pub fn recommend(&self, user: &str, top: Option<usize>) -> Result<JsValue, JsError> {
+        let node = self
+            .graph
+            .find_user(user)
+            .map_err(|err| JsError::new(&err.to_string()))?;
+        to_js(&recommend::recommend(
+            &self.graph,
+            node,
+            top.unwrap_or(DEFAULT_TOP),
+        ))
+    }
+
+    /// The anime most similar to another, by similarity-edge score.
+    pub fn similar(&self, anime_id: u32, top: Option<usize>) -> Result<JsValue, JsError> {
+        let node = self
+            .graph
+            .find_anime(anime_id)
+            .ok_or_else(|| JsError::new(&format!("no anime with id {anime_id}")))?;
+        to_js(&recommend::similar(
+            &self.graph,
+            node,
+            top.unwrap_or(DEFAULT_TOP),
+        ))
+    }
+
+    /// `{ users, anime, ratings, meanScore, similarityEdges, communities }`.
+    pub fn stats(&self) -> Result<JsValue, JsError> {
+        to_js(&self.stats)
+    }
+
+    /// Every user id in the graph.
+    pub fn users(&self) -> Vec<String> {
+        self.graph
+            .nodes
+            .iter()
+            .filter(|node| node.node_type == NodeType::User)
+            .filter_map(|node| node.id.strip_prefix("user:").map(str::to_string))
+            .collect()
+    }
+
+    /// What was dropped or fixed on load, as one sentence.
+    #[wasm_bindgen(getter)]
+    pub fn report(&self) -> String {
+        self.report.summary()
+    }
+}
+
+fn to_js(value: &impl Serialize) -> Result<JsValue, JsError> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|err| JsError::new(&err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loading_reports_fixes_and_lists_the_users() {
+        let json = r#"{"users": [
+            {"userId": "a", "ratings": [
+                {"animeId": 1, "title": "Monster", "rawScore": 6},
+                {"animeId": 1, "title": "Monster", "rawScore": 9},
+                {"animeId": "two", "title": "Broken", "rawScore": 7}
+            ]},
+            {"userId": "b", "ratings": [{"animeId": 1, "title": "Monster", "rawScore": 8}]}
+        ]}"#;
+        let engine = load_dataset(json.as_bytes(), Some("highest".to_string())).unwrap();
+        assert_eq!(engine.users(), ["a", "b"]);
+        assert_eq!(
+            engine.report(),
+            "Dropped 0 users and 1 ratings; ignored 0 invalid optional fields; resolved 1 duplicate ratings."
+        );
+        assert_eq!(engine.stats.ratings, 2);
+    }
+}
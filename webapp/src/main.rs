@@ -0,0 +1,408 @@
+//! The graph explorer in the browser: the desktop app's canvas, details card and controls
+//! compiled to WebAssembly. The dataset is fetched over HTTP — `?dataset=<url>`, or
+//! `anonymized-ratings.json` next to the page — or chosen from disk with the file picker.
+//! Nothing leaves the browser; there is no config, metadata or poster cache.
+
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use wasiw_core::sampling::Sampling;
+use wasiw_core::{
+    build_graph, dataset, Dataset, GraphFilter, LayoutKind, MergeStrategy, QualityReport, Viewport,
+};
+use wasiw_ui::camera::{Camera, ViewBox};
+use wasiw_ui::canvas::{
+    EdgeHover, EdgeTooltip, EdgeTooltipCard, GraphCanvas, Scene, MAX_RENDERED_EDGES,
+    SUPER_NODE_ZOOM,
+};
+use wasiw_ui::controls::{
+    DuplicatePicker, LayoutControls, PalettePicker, SamplingControls, SearchBox, StatRow,
+    ThemePicker,
+};
+use wasiw_ui::cull::use_cull_index;
+use wasiw_ui::details::{NodeDetails, NodeDetailsData};
+use wasiw_ui::render::RenderBackend;
+use wasiw_ui::theme::{self, ThemePreference, ThemeVariant};
+
+/// Fetched, relative to the page, when the URL has no `dataset` parameter.
+const DEFAULT_DATASET_URL: &str = "anonymized-ratings.json";
+
+fn main() {
+    dioxus::launch(App);
+}
+
+/// Where the current dataset came from, shown in the panel.
+#[derive(Debug, Clone, PartialEq)]
+enum Source {
+    Url(String),
+    Upload(String),
+}
+
+impl Source {
+    fn name(&self) -> &str {
+        match self {
+            Self::Url(name) | Self::Upload(name) => name,
+        }
+    }
+}
+
+#[component]
+fn App() -> Element {
+    let mut dataset = use_signal(|| Dataset::new(Vec::new()));
+    let mut graph = use_signal(|| build_graph(Dataset::new(Vec::new()), Viewport::default()));
+    let cull_index = use_cull_index(graph.into());
+    let mut source = use_signal(|| None::<Source>);
+    let mut status = use_signal(|| None::<String>);
+    let mut loading = use_signal(|| false);
+    let mut dataset_url = use_signal(initial_dataset_url);
+    let mut duplicates = use_signal(MergeStrategy::default);
+    let mut viewport = use_signal(Viewport::default);
+    let mut camera = use_signal(Camera::default);
+    let mut canvas_element = use_signal(|| None::<Rc<MountedData>>);
+    let mut canvas_origin = use_signal(|| (0.0_f32, 0.0_f32));
+    let search = use_signal(String::new);
+    let mut selected = use_signal(|| None::<usize>);
+    let mut details_open = use_signal(|| false);
+    let mut hovered_edge = use_signal(|| None::<EdgeHover>);
+    let mut expanded_communities = use_signal(HashSet::<usize>::new);
+    let mut theme_preference = use_signal(ThemePreference::default);
+    let mut palette_name = use_signal(|| None::<String>);
+
+    // Parses and shows a dataset; the previous one stays when the new one is unusable.
+    let mut install = move |bytes: &[u8], from: Source| match dataset::parse(bytes, duplicates()) {
+        Ok((next, report)) => {
+            let next_graph = graph.peek().rebuild(next.clone());
+            status.set(Some(loaded_summary(&next, &report, &from)));
+            dataset.set(next);
+            graph.set(next_graph);
+            source.set(Some(from));
+            selected.set(None);
+            details_open.set(false);
+            hovered_edge.set(None);
+            expanded_communities.write().clear();
+            camera.set(Camera::default());
+        }
+        Err(err) => status.set(Some(format!("Could not load {}: {err}", from.name()))),
+    };
+
+    let mut fetch_dataset = move |url: String| {
+        loading.set(true);
+        status.set(Some(format!("Fetching {url}…")));
+        spawn(async move {
+            match fetch(&url).await {
+                Ok(bytes) => install(&bytes, Source::Url(url)),
+                Err(err) => status.set(Some(format!(
+                    "Could not fetch {url}: {err}. Choose a dataset file instead."
+                ))),
+            }
+            loading.set(false);
+        });
+    };
+
+    use_hook(move || fetch_dataset(dataset_url.peek().clone()));
+
+    let on_upload = move |evt: Event<FormData>| async move {
+        let Some(file) = evt.files().into_iter().next() else {
+            return;
+        };
+        let name = file.name();
+        loading.set(true);
+        status.set(Some(format!("Reading {name}…")));
+        match file.read_bytes().await {
+            Ok(bytes) => install(&bytes, Source::Upload(name)),
+            Err(err) => status.set(Some(format!("Could not read {name}: {err}"))),
+        }
+        loading.set(false);
+    };
+
+    // No animation here: the browser build has no timer runtime, so nodes jump into place.
+    let mut relayout = move || {
+        let targets = graph.peek().layout_targets();
+        graph.write().set_positions(&targets);
+    };
+
+    let on_canvas_resize = move |evt: Event<ResizeData>| {
+        let Ok(size) = evt.get_content_box_size() else {
+            return;
+        };
+        let next = Viewport {
+            width: size.width as f32,
+            height: size.height as f32,
+            scale_factor: device_pixel_ratio(),
+        };
+        if next.width < 1.0 || next.height < 1.0 {
+            return;
+        }
+        if next.differs_significantly(&graph.peek().layout_viewport) {
+            graph.write().layout_viewport = next;
+            relayout();
+        }
+        viewport.set(next);
+        spawn(async move {
+            let Some(element) = canvas_element() else {
+                return;
+            };
+            if let Ok(rect) = element.get_client_rect().await {
+                canvas_origin.set((rect.origin.x as f32, rect.origin.y as f32));
+            }
+        });
+    };
+
+    let to_canvas = move |x: f64, y: f64| {
+        let (origin_x, origin_y) = canvas_origin();
+        (x as f32 - origin_x, y as f32 - origin_y)
+    };
+
+    let on_wheel = move |evt: Event<WheelData>| {
+        evt.prevent_default();
+        let delta = evt.delta().strip_units();
+        let point = evt.client_coordinates();
+        let (px, py) = to_canvas(point.x, point.y);
+        let factor = (-delta.y as f32 * 0.0015).exp();
+        let fitted = ViewBox::fit(&graph.read().nodes, viewport());
+        camera
+            .write()
+            .zoom_about(factor, px, py, fitted, viewport());
+    };
+
+    // Zooming back in shows individual nodes everywhere, so forget per-cluster expansions.
+    use_effect(move || {
+        if camera().zoom >= SUPER_NODE_ZOOM && !expanded_communities.peek().is_empty() {
+            expanded_communities.write().clear();
+        }
+    });
+
+    let model = graph.read();
+    let scene = Scene::new(
+        &model,
+        viewport(),
+        camera(),
+        &expanded_communities.read(),
+        MAX_RENDERED_EDGES,
+        cull_index.read().as_ref(),
+    );
+    let matches = model.search(&search.read());
+    let match_count = matches.as_ref().map(|m| m.len());
+    let (palettes, _) = theme::available_palettes(&[]);
+    let palette = theme::active_palette(&[], palette_name.read().as_deref());
+    let theme_class = theme_preference().resolve(system_theme()).class();
+    let edge_tooltip = hovered_edge().and_then(|hover| EdgeTooltip::new(&model, hover, |_| None));
+    let details = selected()
+        .filter(|_| details_open())
+        .and_then(|idx| NodeDetailsData::new(&model, idx, |_| None));
+
+    rsx! {
+        style { {theme::APP_CSS} }
+        main { class: "app {theme_class}",
+            section { class: "panel",
+                h1 { "What Anime Should I Watch" }
+                p { class: "muted", "Graph from anonymized user ratings, running in your browser." }
+                SearchBox { query: search, match_count }
+                div { class: "stats",
+                    StatRow { label: "Users", value: model.user_count.to_string() }
+                    StatRow { label: "Anime", value: model.anime_count.to_string() }
+                    StatRow { label: "Nodes", value: model.nodes.len().to_string() }
+                    StatRow { label: "Edges (rendered)", value: scene.edges.len().to_string() }
+                    StatRow { label: "Communities", value: model.communities.count().to_string() }
+                }
+                if let Some(source) = source() {
+                    p { class: "tiny", "Dataset: {source.name()}" }
+                }
+                if let Some(status) = status() {
+                    p { class: "tiny", "{status}" }
+                }
+                form {
+                    class: "field",
+                    onsubmit: move |evt| {
+                        evt.prevent_default();
+                        fetch_dataset(dataset_url());
+                    },
+                    span { class: "tiny", "Dataset URL" }
+                    input {
+                        r#type: "url",
+                        value: "{dataset_url}",
+                        oninput: move |evt| dataset_url.set(evt.value()),
+                    }
+                    button { class: "action", r#type: "submit", disabled: loading(), "Load" }
+                }
+                label { class: "field",
+                    span { class: "tiny", "Or open a file (plain or .gz JSON)" }
+                    input {
+                        r#type: "file",
+                        accept: ".json,.gz,application/json",
+                        disabled: loading(),
+                        onchange: on_upload,
+                    }
+                }
+                LayoutControls {
+                    layout: model.layout,
+                    filter: model.filter,
+                    on_layout: move |kind: LayoutKind| {
+                        graph.write().layout = kind;
+                        relayout();
+                    },
+                    on_filter: move |filter: GraphFilter| {
+                        graph.write().apply_filter(filter);
+                        relayout();
+                    },
+                }
+                SamplingControls {
+                    sampling: model.sampling,
+                    total_users: dataset.read().users.len(),
+                    shown_users: model.user_count,
+                    on_change: move |sampling: Sampling| {
+                        graph.write().sampling = sampling;
+                        let next = graph.peek().rebuild(dataset.peek().clone());
+                        graph.set(next);
+                        selected.set(None);
+                        details_open.set(false);
+                        hovered_edge.set(None);
+                    },
+                }
+                DuplicatePicker {
+                    active: duplicates(),
+                    on_select: move |strategy: MergeStrategy| duplicates.set(strategy),
+                }
+                p { class: "tiny", "Duplicate handling applies to the next dataset loaded." }
+                ThemePicker {
+                    active: theme_preference(),
+                    on_select: move |preference| theme_preference.set(preference),
+                }
+                PalettePicker {
+                    palettes,
+                    active: palette.name.clone(),
+                    skipped: 0,
+                    on_select: move |name| palette_name.set(Some(name)),
+                }
+                button {
+                    class: "action",
+                    onclick: move |_| camera.set(Camera::default()),
+                    "Reset view"
+                }
+            }
+            section {
+                class: "canvas-wrap",
+                onresize: on_canvas_resize,
+                onmounted: move |evt| canvas_element.set(Some(evt.data())),
+                if let Some(details) = details {
+                    NodeDetails {
+                        details,
+                        on_close: move |_| details_open.set(false),
+                    }
+                }
+                if let Some(tooltip) = edge_tooltip {
+                    EdgeTooltipCard { tooltip }
+                }
+                GraphCanvas {
+                    graph,
+                    // Browsers paint a canvas much faster than thousands of SVG elements.
+                    backend: RenderBackend::Canvas2d,
+                    scene,
+                    palette,
+                    matches,
+                    selected: selected(),
+                    hovered_edge: hovered_edge().map(|hover| hover.edge),
+                    on_select: move |idx| selected.set(Some(idx)),
+                    on_open: move |idx| {
+                        selected.set(Some(idx));
+                        details_open.set(true);
+                    },
+                    on_expand: move |community| {
+                        expanded_communities.write().insert(community);
+                    },
+                    on_edge_enter: move |(edge, evt): (usize, Event<MouseData>)| {
+                        let point = evt.client_coordinates();
+                        let (x, y) = to_canvas(point.x, point.y);
+                        hovered_edge.set(Some(EdgeHover { edge, x, y }));
+                    },
+                    on_edge_leave: move |_| hovered_edge.set(None),
+                    on_wheel,
+                }
+            }
+        }
+    }
+}
+
+fn loaded_summary(dataset: &Dataset, report: &QualityReport, source: &Source) -> String {
+    let loaded = format!(
+        "Loaded {} users from {}.",
+        dataset.users.len(),
+        source.name()
+    );
+    if report.is_clean() {
+        loaded
+    } else {
+        format!("{loaded} {}", report.summary())
+    }
+}
+
+/// `?dataset=<url>` from the page address, or the default next to the page.
+fn initial_dataset_url() -> String {
+    web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .and_then(|search| web_sys::UrlSearchParams::new_with_str(&search).ok())
+        .and_then(|params| params.get("dataset"))
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| DEFAULT_DATASET_URL.to_string())
+}
+
+/// Fetches `url`, resolved against the page address so relative paths work.
+async fn fetch(url: &str) -> Result<Vec<u8>, String> {
+    let base = web_sys::window()
+        .and_then(|window| window.location().href().ok())
+        .unwrap_or_default();
+    let absolute = web_sys::Url::new_with_base(url, &base)
+        .map(|url| url.href())
+        .map_err(|_| "not a valid URL".to_string())?;
+    let response = reqwest::get(absolute)
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|err| err.to_string())?;
+    let bytes = response.bytes().await.map_err(|err| err.to_string())?;
+    Ok(bytes.to_vec())
+}
+
+fn device_pixel_ratio() -> f32 {
+    web_sys::window().map_or(1.0, |window| window.device_pixel_ratio() as f32)
+}
+
+/// The browser's light/dark preference, for the System theme.
+fn system_theme() -> ThemeVariant {
+    let light = web_sys::window()
+        .and_then(|window| window.match_media("(prefers-color-scheme: light)").ok())
+        .flatten()
+        .is_some_and(|query| query.matches());
+    if light {
+        ThemeVariant::Light
+    } else {
+        ThemeVariant::Dark
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_summary_names_the_source_and_only_mentions_fixes_when_there_were_any() {
+        let json = r#"{"users": [
+            {"userId": "a", "ratings": [{"animeId": 1, "title": "Monster", "rawScore": 9}]},
+            {"userId": "b", "ratings": [{"animeId": "two", "title": "Broken", "rawScore": 7}]}
+        ]}"#;
+        let (clean, report) = dataset::parse(
+            br#"{"users": [{"userId": "a", "ratings": [{"animeId": 1, "title": "Monster", "rawScore": 9}]}]}"#,
+            MergeStrategy::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            loaded_summary(&clean, &report, &Source::Url("ratings.json".to_string())),
+            "Loaded 1 users from ratings.json."
+        );
+        let (dirty, report) = dataset::parse(json.as_bytes(), MergeStrategy::default()).unwrap();
+        assert_eq!(
+            loaded_summary(&dirty, &report, &Source::Upload("mine.json".to_string())),
+            format!("Loaded 1 users from mine.json. {}", report.summary())
+        );
+    }
+}